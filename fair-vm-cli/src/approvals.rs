@@ -0,0 +1,126 @@
+//! 授权风险扫描：列出地址仍然有效的代币授权，确认后批量发送撤销交易
+
+use crate::batch::confirm;
+use clap::Args;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{BlockNumber, U256};
+use fair_vm_sdk::address::{parse_address, ChecksumPolicy};
+use fair_vm_sdk::approvals::{
+    revoke_requests, ActiveApproval, ApprovalKind, ApprovalScanner, DEFAULT_SCAN_BLOCKS,
+};
+use fair_vm_sdk::wallet::FairWallet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Args, Debug)]
+pub struct ApprovalsArgs {
+    /// 授权所有者地址
+    pub address: String,
+    /// RPC URL
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    pub rpc_url: String,
+    /// 扫描起始区块
+    #[arg(long, default_value_t = 0)]
+    pub from_block: u64,
+    /// 每次查询日志的区块数
+    #[arg(long, default_value_t = DEFAULT_SCAN_BLOCKS)]
+    pub scan_blocks: u64,
+    /// 只列出无限额度和 NFT 操作权限
+    #[arg(long)]
+    pub unlimited_only: bool,
+    /// 撤销列出的全部授权，需要提供所有者的私钥或助记词
+    #[arg(long, requires = "key")]
+    pub revoke: bool,
+    /// 私钥或助记词
+    #[arg(long)]
+    pub key: Option<String>,
+    /// 跳过确认
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+fn describe(approval: &ActiveApproval) -> String {
+    match approval.kind {
+        ApprovalKind::Allowance(_) if approval.is_unlimited() => "无限额度".to_string(),
+        ApprovalKind::Allowance(amount) => format!("额度 {}", amount),
+        ApprovalKind::Operator(_) => "全部 NFT 操作权限".to_string(),
+    }
+}
+
+/// 扫描并按需撤销授权
+pub async fn scan(
+    args: ApprovalsArgs,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let owner = parse_address(&args.address, policy)?;
+    let provider = Arc::new(Provider::<Http>::try_from(args.rpc_url.as_str())?);
+    let scanner = ApprovalScanner::new(provider.clone()).with_scan_blocks(args.scan_blocks);
+    let mut approvals = scanner.active_approvals(owner, args.from_block).await?;
+    if args.unlimited_only {
+        approvals.retain(ActiveApproval::is_unlimited);
+    }
+    if approvals.is_empty() {
+        println!("没有有效的授权");
+        return Ok(());
+    }
+    for approval in &approvals {
+        println!(
+            "代币 {:?}  被授权方 {:?}  {}  (区块 {})",
+            approval.token,
+            approval.spender,
+            describe(approval),
+            approval.approved_at
+        );
+    }
+    println!(
+        "共 {} 项授权，其中高风险 {} 项",
+        approvals.len(),
+        approvals.iter().filter(|a| a.is_unlimited()).count()
+    );
+    let Some(key) = args.key.filter(|_| args.revoke) else {
+        return Ok(());
+    };
+
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet = if key.contains(' ') {
+        FairWallet::from_mnemonic(&key, chain_id)?
+    } else {
+        FairWallet::from_private_key(key.trim_start_matches("0x"), chain_id)?
+    };
+    let signer = LocalWallet::from_str(&wallet.export_private_key())?.with_chain_id(chain_id);
+    if signer.address() != owner {
+        return Err(format!("私钥地址 {:?} 不是授权所有者 {:?}", signer.address(), owner).into());
+    }
+    if !args.yes && !confirm(&format!("确认撤销 {} 项授权?", approvals.len()))? {
+        println!("已取消");
+        return Ok(());
+    }
+
+    let gas_price = provider.get_gas_price().await?;
+    let mut nonce = provider
+        .get_transaction_count(owner, Some(BlockNumber::Pending.into()))
+        .await?;
+    for (approval, request) in approvals.iter().zip(revoke_requests(owner, &approvals)) {
+        let mut tx: TypedTransaction = request
+            .gas_price(gas_price)
+            .nonce(nonce)
+            .chain_id(chain_id)
+            .into();
+        let gas = provider.estimate_gas(&tx, None).await?;
+        tx.set_gas(gas);
+        let signature = signer.sign_transaction(&tx).await?;
+        let pending = provider
+            .send_raw_transaction(tx.rlp_signed(&signature))
+            .await?;
+        println!(
+            "已撤销代币 {:?} 对 {:?} 的授权: {:?}",
+            approval.token,
+            approval.spender,
+            pending.tx_hash()
+        );
+        nonce += U256::one();
+    }
+    Ok(())
+}
@@ -0,0 +1,414 @@
+//! 批量转账：从 CSV 读取收款地址和金额，确认总费用后按顺序分配 nonce 并发送
+
+use crate::bench::parse_duration;
+use clap::Args;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, TransactionRequest, H256, U256};
+use fair_vm_sdk::address::{parse_address, ChecksumPolicy};
+use fair_vm_sdk::units::{parse_amount, Unit};
+use fair_vm_sdk::wallet::FairWallet;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// 转账交易 gas 限制
+const TRANSFER_GAS: u64 = 21_000;
+
+/// 打包情况轮询间隔
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Args, Debug)]
+pub struct SendBatchArgs {
+    /// 收款 CSV 文件，表头为 address,amount，金额可带单位如 1.5fair，不带单位时为 wei
+    #[arg(long)]
+    pub csv: String,
+    /// 私钥或助记词
+    #[arg(long)]
+    pub key: String,
+    /// RPC URL
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    pub rpc_url: String,
+    /// 结果 CSV 文件，默认在输入文件名后加 .results.csv
+    #[arg(long)]
+    pub output: Option<String>,
+    /// 同时在途的提交请求数
+    #[arg(long, default_value_t = 8)]
+    pub concurrency: usize,
+    /// 提交结束后等待打包的最长时间
+    #[arg(long, default_value = "60s", value_parser = parse_duration)]
+    pub wait: Duration,
+    /// 跳过确认
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+/// CSV 中的一行收款
+#[derive(Debug, Clone, Deserialize)]
+struct PayoutRow {
+    address: String,
+    amount: String,
+}
+
+/// 校验后的收款
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payout {
+    /// CSV 中的行号，表头为第 1 行
+    pub line: usize,
+    pub to: Address,
+    pub amount: U256,
+}
+
+/// 单笔转账的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutStatus {
+    /// 已打包且执行成功
+    Confirmed,
+    /// 已打包但执行失败
+    Reverted,
+    /// 已提交，等待超时前未打包
+    Pending,
+    /// 提交失败
+    Failed,
+    /// 之前的提交失败，为避免 nonce 空洞未提交
+    Skipped,
+}
+
+/// 结果 CSV 中的一行
+#[derive(Debug, Clone, Serialize)]
+pub struct PayoutResult {
+    pub line: usize,
+    pub address: String,
+    pub amount: String,
+    pub nonce: Option<u64>,
+    pub tx_hash: Option<String>,
+    pub status: PayoutStatus,
+    pub error: String,
+}
+
+impl PayoutResult {
+    fn new(payout: &Payout, status: PayoutStatus) -> Self {
+        Self {
+            line: payout.line,
+            address: format!("{:?}", payout.to),
+            amount: payout.amount.to_string(),
+            nonce: None,
+            tx_hash: None,
+            status,
+            error: String::new(),
+        }
+    }
+}
+
+/// 读取并校验收款 CSV，任一行无效时返回全部错误
+pub fn read_payouts(
+    reader: impl std::io::Read,
+    policy: ChecksumPolicy,
+) -> Result<Vec<Payout>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut payouts = Vec::new();
+    let mut errors = Vec::new();
+    for (index, row) in reader.deserialize::<PayoutRow>().enumerate() {
+        let line = index + 2;
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                errors.push(format!("第 {} 行: {}", line, e));
+                continue;
+            }
+        };
+        let to = parse_address(&row.address, policy);
+        let amount = parse_amount(&row.amount, Unit::Wei);
+        match (to, amount) {
+            (Ok(to), Ok(amount)) if !amount.is_zero() => payouts.push(Payout { line, to, amount }),
+            (Err(e), _) => errors.push(format!("第 {} 行: 无效地址 {}: {}", line, row.address, e)),
+            _ => errors.push(format!("第 {} 行: 无效金额 {}", line, row.amount)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    if payouts.is_empty() {
+        return Err("CSV 中没有收款记录".to_string());
+    }
+    Ok(payouts)
+}
+
+/// 总费用：转账金额之和加上按当前 gas 价格估算的手续费
+pub fn total_cost(payouts: &[Payout], gas_price: U256) -> U256 {
+    let amount = payouts
+        .iter()
+        .fold(U256::zero(), |total, payout| total + payout.amount);
+    amount + gas_price * TRANSFER_GAS * payouts.len()
+}
+
+fn default_output(csv: &str) -> String {
+    format!("{}.results.csv", csv.trim_end_matches(".csv"))
+}
+
+fn write_results(path: &Path, results: &[PayoutResult]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for result in results {
+        writer.serialize(result)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn confirm(prompt: &str) -> Result<bool, std::io::Error> {
+    print!("{} [y/N] ", prompt);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// 执行批量转账
+pub async fn send_batch(
+    args: SendBatchArgs,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.concurrency == 0 {
+        return Err("concurrency 必须大于 0".into());
+    }
+    let payouts = read_payouts(std::fs::File::open(&args.csv)?, policy)?;
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| default_output(&args.csv));
+
+    let provider = Arc::new(Provider::<Http>::try_from(args.rpc_url.as_str())?);
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet = if args.key.contains(' ') {
+        FairWallet::from_mnemonic(&args.key, chain_id)?
+    } else {
+        FairWallet::from_private_key(args.key.trim_start_matches("0x"), chain_id)?
+    };
+    let signer = LocalWallet::from_str(&wallet.export_private_key())?.with_chain_id(chain_id);
+    let from = signer.address();
+
+    let gas_price = provider.get_gas_price().await?;
+    let balance = provider.get_balance(from, None).await?;
+    let cost = total_cost(&payouts, gas_price);
+    println!("发送方: {:?}", from);
+    println!("收款笔数: {}", payouts.len());
+    println!(
+        "预计总费用: {} wei (含手续费，gas 价格 {} wei)",
+        cost, gas_price
+    );
+    println!("当前余额: {} wei", balance);
+    if balance < cost {
+        return Err("余额不足以支付全部转账".into());
+    }
+    if !args.yes && !confirm("确认发送?")? {
+        println!("已取消");
+        return Ok(());
+    }
+
+    let mut nonce = provider
+        .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+        .await?;
+    let mut results: Vec<PayoutResult> = payouts
+        .iter()
+        .map(|payout| PayoutResult::new(payout, PayoutStatus::Skipped))
+        .collect();
+    let mut tasks = JoinSet::new();
+    let mut aborted = false;
+
+    for (index, payout) in payouts.iter().enumerate() {
+        while tasks.len() >= args.concurrency {
+            if let Some(done) = tasks.join_next().await {
+                aborted |= record_submission(&mut results, done?);
+            }
+        }
+        if aborted {
+            break;
+        }
+
+        let request = TransactionRequest::new()
+            .from(from)
+            .to(payout.to)
+            .value(payout.amount)
+            .gas(TRANSFER_GAS)
+            .gas_price(gas_price)
+            .nonce(nonce)
+            .chain_id(chain_id);
+        results[index].nonce = Some(nonce.as_u64());
+        nonce += U256::one();
+
+        let tx: TypedTransaction = request.into();
+        let signature = signer.sign_transaction(&tx).await?;
+        let raw = tx.rlp_signed(&signature);
+        let provider = provider.clone();
+        tasks.spawn(async move {
+            let result = provider
+                .send_raw_transaction(raw)
+                .await
+                .map(|pending| pending.tx_hash())
+                .map_err(|e| e.to_string());
+            (index, result)
+        });
+    }
+    while let Some(done) = tasks.join_next().await {
+        aborted |= record_submission(&mut results, done?);
+    }
+    if aborted {
+        println!("存在提交失败的转账，后续转账已跳过");
+    }
+
+    wait_for_receipts(&provider, &mut results, args.wait).await;
+    write_results(Path::new(&output), &results)?;
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    println!(
+        "已确认: {}, 执行失败: {}, 未打包: {}, 提交失败: {}, 已跳过: {}",
+        count(PayoutStatus::Confirmed),
+        count(PayoutStatus::Reverted),
+        count(PayoutStatus::Pending),
+        count(PayoutStatus::Failed),
+        count(PayoutStatus::Skipped)
+    );
+    println!("结果已写入: {}", output);
+    Ok(())
+}
+
+/// 记录提交结果，返回提交是否失败
+fn record_submission(
+    results: &mut [PayoutResult],
+    (index, result): (usize, Result<H256, String>),
+) -> bool {
+    let entry = &mut results[index];
+    match result {
+        Ok(hash) => {
+            entry.tx_hash = Some(format!("{:?}", hash));
+            entry.status = PayoutStatus::Pending;
+            false
+        }
+        Err(e) => {
+            entry.status = PayoutStatus::Failed;
+            entry.error = e;
+            true
+        }
+    }
+}
+
+/// 轮询已提交转账的收据，直到全部打包或超时
+async fn wait_for_receipts(
+    provider: &Provider<Http>,
+    results: &mut [PayoutResult],
+    wait: Duration,
+) {
+    let deadline = Instant::now() + wait;
+    loop {
+        for entry in results
+            .iter_mut()
+            .filter(|entry| entry.status == PayoutStatus::Pending)
+        {
+            let Some(hash) = entry
+                .tx_hash
+                .as_deref()
+                .and_then(|h| H256::from_str(h).ok())
+            else {
+                continue;
+            };
+            if let Ok(Some(receipt)) = provider.get_transaction_receipt(hash).await {
+                entry.status = if receipt.status == Some(0u64.into()) {
+                    PayoutStatus::Reverted
+                } else {
+                    PayoutStatus::Confirmed
+                };
+            }
+        }
+        let pending = results
+            .iter()
+            .any(|entry| entry.status == PayoutStatus::Pending);
+        if !pending || Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_payouts() {
+        let csv = "address,amount\n\
+                   0x0000000000000000000000000000000000000001, 100\n\
+                   0x0000000000000000000000000000000000000002,2000000000000000000\n";
+        let payouts = read_payouts(csv.as_bytes(), ChecksumPolicy::Enforce).unwrap();
+        assert_eq!(payouts.len(), 2);
+        assert_eq!(payouts[0].line, 2);
+        assert_eq!(payouts[0].amount, U256::from(100));
+        assert_eq!(payouts[1].to, Address::from_low_u64_be(2));
+
+        let csv = "address,amount\n0x0000000000000000000000000000000000000001,1.5 fair\n";
+        let payouts = read_payouts(csv.as_bytes(), ChecksumPolicy::Enforce).unwrap();
+        assert_eq!(payouts[0].amount, U256::exp10(17) * 15);
+
+        let err = read_payouts(
+            "address,amount\nnot-an-address,1\n0x0000000000000000000000000000000000000001,x\n"
+                .as_bytes(),
+            ChecksumPolicy::Enforce,
+        )
+        .unwrap_err();
+        assert!(err.contains("第 2 行: 无效地址"));
+        assert!(err.contains("第 3 行: 无效金额"));
+        assert!(read_payouts("address,amount\n".as_bytes(), ChecksumPolicy::Enforce).is_err());
+
+        // 大小写混合但校验和错误的地址只有明确跳过校验时才接受
+        let typo = "address,amount\n0xFB6916095ca1df60bB79Ce92cE3Ea74c37c5d359,1\n";
+        let err = read_payouts(typo.as_bytes(), ChecksumPolicy::Enforce).unwrap_err();
+        assert!(err.contains("校验和"));
+        assert!(read_payouts(typo.as_bytes(), ChecksumPolicy::Ignore).is_ok());
+    }
+
+    #[test]
+    fn test_total_cost() {
+        let payouts = vec![
+            Payout {
+                line: 2,
+                to: Address::zero(),
+                amount: U256::from(1_000),
+            },
+            Payout {
+                line: 3,
+                to: Address::zero(),
+                amount: U256::from(500),
+            },
+        ];
+        assert_eq!(
+            total_cost(&payouts, U256::from(2)),
+            U256::from(1_500 + 2 * 2 * TRANSFER_GAS)
+        );
+        assert_eq!(default_output("payouts.csv"), "payouts.results.csv");
+    }
+
+    #[test]
+    fn test_record_submission() {
+        let payout = Payout {
+            line: 2,
+            to: Address::zero(),
+            amount: U256::one(),
+        };
+        let mut results = vec![PayoutResult::new(&payout, PayoutStatus::Skipped)];
+        assert!(!record_submission(&mut results, (0, Ok(H256::zero()))));
+        assert_eq!(results[0].status, PayoutStatus::Pending);
+        assert!(record_submission(
+            &mut results,
+            (0, Err("nonce too low".into()))
+        ));
+        assert_eq!(results[0].status, PayoutStatus::Failed);
+        assert_eq!(results[0].error, "nonce too low");
+    }
+}
@@ -0,0 +1,310 @@
+//! 压测工具：按固定速率生成并提交签名交易，统计提交延迟与打包情况
+
+use clap::Args;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{BlockNumber, Bytes, TransactionRequest, H256, U256};
+use fair_vm_sdk::address::{parse_address, ChecksumPolicy};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+
+/// 打包情况轮询间隔
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 转账交易 gas 限制
+const TRANSFER_GAS: u64 = 21_000;
+
+/// 合约调用 gas 限制
+const CALL_GAS: u64 = 200_000;
+
+#[derive(Args, Debug)]
+pub struct SpamArgs {
+    /// RPC URL
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    pub rpc_url: String,
+    /// 私钥文件，每行一个十六进制私钥
+    #[arg(long)]
+    pub keys: String,
+    /// 目标每秒交易数
+    #[arg(long, default_value_t = 100)]
+    pub tps: u64,
+    /// 压测时长，例如 60s、5m
+    #[arg(long, default_value = "60s", value_parser = parse_duration)]
+    pub duration: Duration,
+    /// 提交结束后等待打包的最长时间
+    #[arg(long, default_value = "30s", value_parser = parse_duration)]
+    pub wait: Duration,
+    /// 合约地址，设置后按 `call_ratio` 比例发送合约调用
+    #[arg(long)]
+    pub contract: Option<String>,
+    /// 合约调用数据（十六进制）
+    #[arg(long, default_value = "")]
+    pub calldata: String,
+    /// 合约调用占比，取值 0 到 1
+    #[arg(long, default_value_t = 0.0)]
+    pub call_ratio: f64,
+}
+
+/// 解析 `500ms`、`60s`、`5m` 形式的时长，无单位时按秒计
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("无效的时长: {}", value))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 3600)),
+        _ => Err(format!("无效的时长单位: {}", unit)),
+    }
+}
+
+/// 发送账户
+struct Sender {
+    wallet: LocalWallet,
+    nonce: U256,
+}
+
+/// 已提交的交易
+struct Submitted {
+    sent_at: Instant,
+    submit_latency: Duration,
+}
+
+/// 压测统计
+#[derive(Default)]
+struct Stats {
+    submit_errors: usize,
+    submit_latencies: Vec<Duration>,
+    inclusion_latencies: Vec<Duration>,
+    failed_receipts: usize,
+    pending: HashMap<H256, Submitted>,
+}
+
+/// 计算百分位数，`samples` 需已排序
+fn percentile(samples: &[Duration], p: f64) -> Duration {
+    if samples.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((samples.len() as f64 - 1.0) * p).round() as usize;
+    samples[rank.min(samples.len() - 1)]
+}
+
+fn summarize(name: &str, samples: &mut [Duration]) {
+    samples.sort();
+    println!(
+        "{}: p50 {:?}, p95 {:?}, p99 {:?}, max {:?}",
+        name,
+        percentile(samples, 0.50),
+        percentile(samples, 0.95),
+        percentile(samples, 0.99),
+        samples.last().copied().unwrap_or_default()
+    );
+}
+
+/// 加载私钥并查询各账户起始 nonce
+async fn load_senders(
+    provider: &Provider<Http>,
+    path: &str,
+    chain_id: u64,
+) -> Result<Vec<Sender>, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut senders = Vec::new();
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let wallet = LocalWallet::from_str(line.trim_start_matches("0x"))?.with_chain_id(chain_id);
+        let nonce = provider
+            .get_transaction_count(wallet.address(), Some(BlockNumber::Pending.into()))
+            .await?;
+        senders.push(Sender { wallet, nonce });
+    }
+    if senders.is_empty() {
+        return Err(format!("私钥文件 {} 为空", path).into());
+    }
+    Ok(senders)
+}
+
+/// 检查已提交交易是否已被打包
+async fn poll_receipts(provider: &Provider<Http>, stats: &Mutex<Stats>) {
+    let hashes: Vec<H256> = stats.lock().await.pending.keys().copied().collect();
+    for hash in hashes {
+        if let Ok(Some(receipt)) = provider.get_transaction_receipt(hash).await {
+            let mut stats = stats.lock().await;
+            if let Some(submitted) = stats.pending.remove(&hash) {
+                stats.inclusion_latencies.push(submitted.sent_at.elapsed());
+                if receipt.status == Some(0u64.into()) {
+                    stats.failed_receipts += 1;
+                }
+            }
+        }
+    }
+}
+
+/// 执行压测
+pub async fn spam(
+    args: SpamArgs,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.tps == 0 {
+        return Err("tps 必须大于 0".into());
+    }
+    if !(0.0..=1.0).contains(&args.call_ratio) {
+        return Err("call_ratio 必须在 0 到 1 之间".into());
+    }
+
+    let provider = Arc::new(Provider::<Http>::try_from(args.rpc_url.as_str())?);
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let gas_price = provider.get_gas_price().await?;
+    let mut senders = load_senders(&provider, &args.keys, chain_id).await?;
+    let contract = args
+        .contract
+        .as_deref()
+        .map(|contract| parse_address(contract, policy))
+        .transpose()?;
+    let calldata = Bytes::from(hex::decode(args.calldata.trim_start_matches("0x"))?);
+
+    println!(
+        "开始压测: {} 个账户, 目标 {} TPS, 持续 {:?}",
+        senders.len(),
+        args.tps,
+        args.duration
+    );
+
+    let stats = Arc::new(Mutex::new(Stats::default()));
+    let mut tasks = JoinSet::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / args.tps as f64));
+    let mut last_poll = Instant::now();
+    let started = Instant::now();
+    let mut sent = 0u64;
+
+    while started.elapsed() < args.duration {
+        ticker.tick().await;
+
+        let sender = &mut senders[(sent as usize) % senders.len()];
+        let is_call = contract.is_some() && (rand::random::<f64>() < args.call_ratio);
+        let request = if is_call {
+            TransactionRequest::new()
+                .to(contract.unwrap())
+                .data(calldata.clone())
+                .gas(CALL_GAS)
+        } else {
+            // 转账给自己，避免压测账户余额流失到外部
+            TransactionRequest::new()
+                .to(sender.wallet.address())
+                .value(1u64)
+                .gas(TRANSFER_GAS)
+        }
+        .from(sender.wallet.address())
+        .nonce(sender.nonce)
+        .gas_price(gas_price)
+        .chain_id(chain_id);
+        sender.nonce += U256::one();
+        sent += 1;
+
+        let tx: TypedTransaction = request.into();
+        let signature = sender.wallet.sign_transaction(&tx).await?;
+        let raw = tx.rlp_signed(&signature);
+
+        let provider = provider.clone();
+        let stats = stats.clone();
+        tasks.spawn(async move {
+            let sent_at = Instant::now();
+            let result = provider.send_raw_transaction(raw).await;
+            let submit_latency = sent_at.elapsed();
+            let mut stats = stats.lock().await;
+            match result {
+                Ok(pending) => {
+                    stats.submit_latencies.push(submit_latency);
+                    stats.pending.insert(
+                        pending.tx_hash(),
+                        Submitted {
+                            sent_at,
+                            submit_latency,
+                        },
+                    );
+                }
+                Err(e) => {
+                    stats.submit_errors += 1;
+                    log::debug!("提交交易失败: {}", e);
+                }
+            }
+        });
+
+        if last_poll.elapsed() >= RECEIPT_POLL_INTERVAL {
+            poll_receipts(&provider, &stats).await;
+            last_poll = Instant::now();
+        }
+    }
+    let submit_elapsed = started.elapsed();
+    while tasks.join_next().await.is_some() {}
+
+    let deadline = Instant::now() + args.wait;
+    while Instant::now() < deadline && !stats.lock().await.pending.is_empty() {
+        poll_receipts(&provider, &stats).await;
+        tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+    }
+
+    let mut stats = stats.lock().await;
+    let accepted = stats.submit_latencies.len();
+    let included = stats.inclusion_latencies.len();
+    println!(
+        "已发送: {} ({:.1} TPS)",
+        sent,
+        sent as f64 / submit_elapsed.as_secs_f64()
+    );
+    println!("提交成功: {}, 提交失败: {}", accepted, stats.submit_errors);
+    println!(
+        "已打包: {} ({:.1}%), 执行失败: {}, 未打包: {}",
+        included,
+        if accepted == 0 {
+            0.0
+        } else {
+            included as f64 * 100.0 / accepted as f64
+        },
+        stats.failed_receipts,
+        stats.pending.len()
+    );
+    let slowest_pending = stats
+        .pending
+        .values()
+        .map(|submitted| submitted.submit_latency)
+        .max();
+    if let Some(latency) = slowest_pending {
+        println!("未打包交易中最慢的提交延迟: {:?}", latency);
+    }
+    summarize("提交延迟", &mut stats.submit_latencies);
+    summarize("打包延迟", &mut stats.inclusion_latencies);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("60s").unwrap(), Duration::from_secs(60));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("10").unwrap(), Duration::from_secs(10));
+        assert!(parse_duration("1d").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 0.5), Duration::from_millis(51));
+        assert_eq!(percentile(&samples, 0.99), Duration::from_millis(99));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+}
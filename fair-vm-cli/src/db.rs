@@ -0,0 +1,97 @@
+//! 数据目录维护：查看和迁移磁盘格式版本
+//!
+//! 节点启动时会自动升级数据目录，`migrate` 用于提前预览迁移计划、在关闭自动迁移的节点上
+//! 手动升级，或在回退节点版本前把数据目录降级到旧版本能识别的格式。
+
+use clap::{Args, Subcommand};
+use fair_vm::storage::schema::{self, Direction, MigrationPlan, Migrations};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// 迁移数据目录格式，默认升级到当前版本
+    Migrate(MigrateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// 数据目录
+    #[arg(long, default_value = "data")]
+    pub data_dir: PathBuf,
+    /// 目标版本，低于当前版本时依次撤销
+    #[arg(long)]
+    pub to: Option<u32>,
+    /// 只显示迁移计划，不修改数据目录
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub async fn handle(cmd: DbCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        DbCommands::Migrate(args) => migrate(args).await,
+    }
+}
+
+async fn migrate(args: MigrateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let target = args.to.unwrap_or(schema::SCHEMA_VERSION);
+    let plan = Migrations::builtin()
+        .run(&args.data_dir, target, args.dry_run)
+        .await?;
+    for line in describe(&plan, args.dry_run) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// 迁移计划的说明
+fn describe(plan: &MigrationPlan, dry_run: bool) -> Vec<String> {
+    if plan.is_empty() {
+        return vec![format!("数据目录已是版本 {}，无需迁移", plan.to)];
+    }
+    let mut lines = vec![format!(
+        "{}版本 {} -> {}",
+        if dry_run {
+            "迁移计划: "
+        } else {
+            "已迁移: "
+        },
+        plan.from,
+        plan.to
+    )];
+    lines.extend(plan.steps.iter().map(|step| {
+        let action = match step.direction {
+            Direction::Up => "升级",
+            Direction::Down => "撤销",
+        };
+        format!("  {} v{}: {}", action, step.version, step.description)
+    }));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_migrate_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = |dry_run| MigrateArgs {
+            data_dir: dir.path().to_path_buf(),
+            to: None,
+            dry_run,
+        };
+        migrate(args(true)).await.unwrap();
+        assert_eq!(schema::read_version(dir.path()).await.unwrap(), None);
+        migrate(args(false)).await.unwrap();
+        assert_eq!(
+            schema::read_version(dir.path()).await.unwrap(),
+            Some(schema::SCHEMA_VERSION)
+        );
+
+        let plan = Migrations::builtin().plan(1, 0).unwrap();
+        assert_eq!(
+            describe(&plan, true),
+            vec!["迁移计划: 版本 1 -> 0", "  撤销 v1: 记录磁盘格式版本"]
+        );
+    }
+}
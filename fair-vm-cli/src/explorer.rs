@@ -0,0 +1,449 @@
+//! 静态区块浏览器：从本地节点读取最近的区块、交易和相关账户，生成可直接托管的 HTML 页面
+//!
+//! 适合不想部署完整浏览器的小型私有网络。生成的目录结构为 `index.html`、`block/<高度>.html`、
+//! `tx/<哈希>.html` 和 `address/<地址>.html`，页面之间使用相对链接，可以直接用浏览器打开。
+
+use clap::{Args, Subcommand};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, Block, Transaction, TransactionReceipt, H256, U256, U64};
+use fair_vm_sdk::address::to_checksum;
+use fair_vm_sdk::units::{format_fair, format_gwei};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum ExplorerCommands {
+    /// 导出静态 HTML 浏览器
+    Export(ExportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// RPC URL
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    pub rpc_url: String,
+    /// 输出目录
+    #[arg(long, default_value = "explorer")]
+    pub out: PathBuf,
+    /// 导出最近的区块数
+    #[arg(long, default_value_t = 100)]
+    pub blocks: u64,
+}
+
+/// 账户页面的数据
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountSummary {
+    pub balance: U256,
+    pub nonce: U256,
+    /// 合约代码长度，外部账户为 0
+    pub code_size: usize,
+    /// 导出范围内与该账户相关的交易，按区块从新到旧
+    pub transactions: Vec<H256>,
+}
+
+/// 生成页面所需的链上数据
+#[derive(Debug, Clone, Default)]
+pub struct ExplorerData {
+    pub chain_id: u64,
+    /// 按高度从新到旧
+    pub blocks: Vec<Block<Transaction>>,
+    pub receipts: HashMap<H256, TransactionReceipt>,
+    pub accounts: BTreeMap<Address, AccountSummary>,
+}
+
+pub async fn handle(cmd: ExplorerCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        ExplorerCommands::Export(args) => export(args).await,
+    }
+}
+
+async fn export(args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let provider = Provider::<Http>::try_from(args.rpc_url.as_str())?;
+    let data = fetch(&provider, args.blocks).await?;
+    let pages = render(&data);
+    write_site(&args.out, &pages)?;
+    println!(
+        "已导出 {} 个区块、{} 笔交易、{} 个账户到 {}",
+        data.blocks.len(),
+        data.receipts.len(),
+        data.accounts.len(),
+        args.out.display()
+    );
+    Ok(())
+}
+
+/// 从节点读取最近 `count` 个区块及其交易、收据和相关账户
+pub async fn fetch(
+    provider: &Provider<Http>,
+    count: u64,
+) -> Result<ExplorerData, Box<dyn std::error::Error>> {
+    let head = provider.get_block_number().await?.as_u64();
+    let first = (head + 1).saturating_sub(count);
+    let mut data = ExplorerData {
+        chain_id: provider.get_chainid().await?.as_u64(),
+        ..Default::default()
+    };
+    for number in (first..=head).rev() {
+        let Some(block) = provider.get_block_with_txs(number).await? else {
+            continue;
+        };
+        for tx in &block.transactions {
+            let receipt = provider.get_transaction_receipt(tx.hash).await?;
+            let contract = receipt.as_ref().and_then(|r| r.contract_address);
+            for address in [Some(tx.from), tx.to, contract].into_iter().flatten() {
+                let account = data.accounts.entry(address).or_default();
+                if account.transactions.last() != Some(&tx.hash) {
+                    account.transactions.push(tx.hash);
+                }
+            }
+            if let Some(receipt) = receipt {
+                data.receipts.insert(tx.hash, receipt);
+            }
+        }
+        data.blocks.push(block);
+    }
+    for (address, account) in data.accounts.iter_mut() {
+        account.balance = provider.get_balance(*address, None).await?;
+        account.nonce = provider.get_transaction_count(*address, None).await?;
+        account.code_size = provider.get_code(*address, None).await?.len();
+    }
+    Ok(data)
+}
+
+/// 转义 HTML 文本
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse}td,th{padding:4px 10px;border-bottom:1px solid #ddd;text-align:left}\
+code{font-size:0.9em}a{color:#0b5fa5;text-decoration:none}";
+
+/// 包装页面，`depth` 为页面相对站点根目录的层数
+fn page(title: &str, depth: usize, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\
+         <style>{STYLE}</style></head>\n<body><p><a href=\"{root}index.html\">FairVM 浏览器</a></p>\n\
+         <h1>{title}</h1>\n{body}</body></html>\n",
+        title = escape(title),
+        root = "../".repeat(depth),
+    )
+}
+
+fn block_link(number: Option<U64>, root: &str) -> String {
+    match number {
+        Some(n) => format!("<a href=\"{root}block/{n}.html\">{n}</a>"),
+        None => "待打包".to_string(),
+    }
+}
+
+fn tx_link(hash: &H256, root: &str) -> String {
+    format!("<a href=\"{root}tx/{hash:?}.html\"><code>{hash:?}</code></a>")
+}
+
+fn address_link(address: &Address, root: &str) -> String {
+    format!(
+        "<a href=\"{root}address/{address:?}.html\"><code>{}</code></a>",
+        to_checksum(*address)
+    )
+}
+
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table><tr>");
+    for header in headers {
+        let _ = write!(html, "<th>{}</th>", escape(header));
+    }
+    html.push_str("</tr>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            let _ = write!(html, "<td>{}</td>", cell);
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn fields(rows: Vec<(&str, String)>) -> String {
+    let rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|(name, value)| vec![escape(name), value])
+        .collect();
+    table(&["字段", "值"], &rows)
+}
+
+fn tx_rows(transactions: &[&Transaction], data: &ExplorerData, root: &str) -> Vec<Vec<String>> {
+    transactions
+        .iter()
+        .map(|tx| {
+            let status = match data.receipts.get(&tx.hash).and_then(|r| r.status) {
+                Some(status) if status.as_u64() == 1 => "成功",
+                Some(_) => "失败",
+                None => "-",
+            };
+            vec![
+                tx_link(&tx.hash, root),
+                block_link(tx.block_number, root),
+                address_link(&tx.from, root),
+                tx.to
+                    .map(|to| address_link(&to, root))
+                    .unwrap_or_else(|| "创建合约".to_string()),
+                format_fair(tx.value),
+                status.to_string(),
+            ]
+        })
+        .collect()
+}
+
+const TX_HEADERS: [&str; 6] = ["交易", "区块", "发送方", "接收方", "金额", "状态"];
+
+/// 生成全部页面，返回相对路径和内容
+pub fn render(data: &ExplorerData) -> Vec<(PathBuf, String)> {
+    let mut pages = Vec::new();
+
+    let rows: Vec<Vec<String>> = data
+        .blocks
+        .iter()
+        .map(|block| {
+            vec![
+                block_link(block.number, ""),
+                format!("<code>{:?}</code>", block.hash.unwrap_or_default()),
+                block.timestamp.to_string(),
+                block.transactions.len().to_string(),
+                block.gas_used.to_string(),
+            ]
+        })
+        .collect();
+    let mut body = format!("<p>链 ID: {}</p>\n<h2>最近区块</h2>\n", data.chain_id);
+    body.push_str(&table(
+        &["高度", "哈希", "时间戳", "交易数", "gas 用量"],
+        &rows,
+    ));
+    let recent: Vec<&Transaction> = data
+        .blocks
+        .iter()
+        .flat_map(|block| &block.transactions)
+        .take(25)
+        .collect();
+    body.push_str("<h2>最近交易</h2>\n");
+    body.push_str(&table(&TX_HEADERS, &tx_rows(&recent, data, "")));
+    pages.push((PathBuf::from("index.html"), page("FairVM 浏览器", 0, &body)));
+
+    for block in &data.blocks {
+        let Some(number) = block.number else {
+            continue;
+        };
+        let mut body = fields(vec![
+            ("高度", number.to_string()),
+            (
+                "哈希",
+                format!("<code>{:?}</code>", block.hash.unwrap_or_default()),
+            ),
+            (
+                "父区块哈希",
+                format!("<code>{:?}</code>", block.parent_hash),
+            ),
+            ("时间戳", block.timestamp.to_string()),
+            (
+                "gas 用量",
+                format!("{} / {}", block.gas_used, block.gas_limit),
+            ),
+            (
+                "基础费用",
+                block
+                    .base_fee_per_gas
+                    .map(format_gwei)
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            ("状态根", format!("<code>{:?}</code>", block.state_root)),
+        ]);
+        let transactions: Vec<&Transaction> = block.transactions.iter().collect();
+        body.push_str("<h2>交易</h2>\n");
+        body.push_str(&table(&TX_HEADERS, &tx_rows(&transactions, data, "../")));
+        pages.push((
+            PathBuf::from(format!("block/{}.html", number)),
+            page(&format!("区块 {}", number), 1, &body),
+        ));
+
+        for tx in &block.transactions {
+            let receipt = data.receipts.get(&tx.hash);
+            let body = fields(vec![
+                ("哈希", format!("<code>{:?}</code>", tx.hash)),
+                ("区块", block_link(tx.block_number, "../")),
+                ("发送方", address_link(&tx.from, "../")),
+                (
+                    "接收方",
+                    tx.to
+                        .map(|to| address_link(&to, "../"))
+                        .unwrap_or_else(|| "创建合约".to_string()),
+                ),
+                (
+                    "创建的合约",
+                    receipt
+                        .and_then(|r| r.contract_address)
+                        .map(|address| address_link(&address, "../"))
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+                ("金额", format_fair(tx.value)),
+                ("nonce", tx.nonce.to_string()),
+                (
+                    "gas 用量",
+                    receipt
+                        .and_then(|r| r.gas_used)
+                        .map(|gas| format!("{} / {}", gas, tx.gas))
+                        .unwrap_or_else(|| tx.gas.to_string()),
+                ),
+                (
+                    "gas 价格",
+                    receipt
+                        .and_then(|r| r.effective_gas_price)
+                        .or(tx.gas_price)
+                        .map(format_gwei)
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+                ("日志数", receipt.map_or(0, |r| r.logs.len()).to_string()),
+                ("输入数据", format!("<code>{}</code>", tx.input)),
+            ]);
+            pages.push((
+                PathBuf::from(format!("tx/{:?}.html", tx.hash)),
+                page("交易详情", 1, &body),
+            ));
+        }
+    }
+
+    let by_hash: HashMap<H256, &Transaction> = data
+        .blocks
+        .iter()
+        .flat_map(|block| &block.transactions)
+        .map(|tx| (tx.hash, tx))
+        .collect();
+    for (address, account) in &data.accounts {
+        let mut body = fields(vec![
+            ("地址", format!("<code>{}</code>", to_checksum(*address))),
+            ("余额", format_fair(account.balance)),
+            ("nonce", account.nonce.to_string()),
+            (
+                "类型",
+                if account.code_size > 0 {
+                    format!("合约（代码 {} 字节）", account.code_size)
+                } else {
+                    "外部账户".to_string()
+                },
+            ),
+        ]);
+        let transactions: Vec<&Transaction> = account
+            .transactions
+            .iter()
+            .filter_map(|hash| by_hash.get(hash).copied())
+            .collect();
+        body.push_str("<h2>交易</h2>\n");
+        body.push_str(&table(&TX_HEADERS, &tx_rows(&transactions, data, "../")));
+        pages.push((
+            PathBuf::from(format!("address/{:?}.html", address)),
+            page("账户", 1, &body),
+        ));
+    }
+    pages
+}
+
+/// 写出页面，已有的同名文件会被覆盖
+pub fn write_site(out: &Path, pages: &[(PathBuf, String)]) -> std::io::Result<()> {
+    for (path, html) in pages {
+        let path = out.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, html)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> ExplorerData {
+        let from = Address::repeat_byte(1);
+        let to = Address::repeat_byte(2);
+        let tx = Transaction {
+            hash: H256::repeat_byte(0xaa),
+            block_number: Some(U64::from(7)),
+            from,
+            to: Some(to),
+            value: U256::exp10(18),
+            ..Default::default()
+        };
+        let block = Block {
+            number: Some(U64::from(7)),
+            hash: Some(H256::repeat_byte(0xbb)),
+            transactions: vec![tx.clone()],
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: tx.hash,
+            status: Some(U64::one()),
+            ..Default::default()
+        };
+        let account = AccountSummary {
+            transactions: vec![tx.hash],
+            ..Default::default()
+        };
+        ExplorerData {
+            chain_id: 1337,
+            blocks: vec![block],
+            receipts: HashMap::from([(tx.hash, receipt)]),
+            accounts: BTreeMap::from([(from, account.clone()), (to, account)]),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let pages = render(&data());
+        let paths: Vec<String> = pages
+            .iter()
+            .map(|(path, _)| path.display().to_string())
+            .collect();
+        assert_eq!(paths.len(), 5);
+        assert_eq!(paths[0], "index.html");
+        assert!(paths.contains(&"block/7.html".to_string()));
+        assert!(paths.contains(&format!("tx/{:?}.html", H256::repeat_byte(0xaa))));
+        assert!(paths.contains(&format!("address/{:?}.html", Address::repeat_byte(2))));
+
+        let index = &pages[0].1;
+        assert!(index.contains("<a href=\"block/7.html\">7</a>"));
+        assert!(index.contains("1 FAIR"));
+        assert!(index.contains("成功"));
+        // 子目录中的页面使用相对链接回到根目录
+        let block = &pages[1].1;
+        assert!(block.contains("href=\"../index.html\""));
+        assert!(block.contains(&format!(
+            "href=\"../tx/{:?}.html\"",
+            H256::repeat_byte(0xaa)
+        )));
+    }
+
+    #[test]
+    fn test_escape_and_write() {
+        assert_eq!(
+            escape("<a href=\"x\">&'"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;"
+        );
+        let dir = tempfile::tempdir().unwrap();
+        write_site(dir.path(), &render(&data())).unwrap();
+        assert!(dir.path().join("index.html").exists());
+        assert!(dir.path().join("block/7.html").exists());
+    }
+}
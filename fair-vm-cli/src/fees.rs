@@ -0,0 +1,239 @@
+//! 费用市场模拟：用合成或记录的负载比较不同费用参数下的基础费用走势
+//!
+//! 每组目标用量与变化分母的组合各模拟一次，终端输出汇总和走势图，`--csv` 输出逐块数据，
+//! 便于在创世前选定 `fees` 参数。
+
+use clap::{Args, Subcommand};
+use ethers::types::U256;
+use fair_vm::fee_market::{self, FeeSimulation, SyntheticLoad};
+use fair_vm::{FeesConfig, Genesis};
+use std::path::{Path, PathBuf};
+
+/// 走势图使用的字符，从低到高
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Subcommand)]
+pub enum FeesCommands {
+    /// 在不同费用参数下重放负载并绘制基础费用走势
+    Simulate(SimulateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    /// 创世配置文件，未指定时使用默认费用参数
+    #[arg(long)]
+    pub genesis: Option<PathBuf>,
+    /// 目标区块 gas 用量，逗号分隔多个值
+    #[arg(long, value_delimiter = ',')]
+    pub gas_target: Vec<u64>,
+    /// 基础费用变化分母，逗号分隔多个值
+    #[arg(long, value_delimiter = ',')]
+    pub denominator: Vec<u64>,
+    /// 初始基础费用（wei）
+    #[arg(long)]
+    pub base_fee: Option<u64>,
+    /// 交易愿意支付的最高费用（wei），基础费用超过时需求被挤出，0 表示不限
+    #[arg(long)]
+    pub max_fee: Option<u64>,
+    /// 合成负载：constant:GAS、ramp:FROM:TO 或 spike:BASE:PEAK:PERIOD
+    #[arg(long, default_value = "constant:12000000", conflicts_with = "input")]
+    pub load: SyntheticLoad,
+    /// 记录的负载：费用统计 JSON 或每行一个 gas 用量的文本
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+    /// 合成负载的区块数
+    #[arg(long, default_value_t = 200)]
+    pub blocks: usize,
+    /// 走势图宽度（字符）
+    #[arg(long, default_value_t = 60)]
+    pub width: usize,
+    /// 逐块数据输出的 CSV 文件
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+}
+
+pub async fn handle(cmd: FeesCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        FeesCommands::Simulate(args) => simulate(args).await,
+    }
+}
+
+async fn simulate(args: SimulateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let base = match &args.genesis {
+        Some(path) => serde_json::from_slice::<Genesis>(&tokio::fs::read(path).await?)?.fees,
+        None => Genesis::default().fees,
+    };
+    let base = FeesConfig {
+        base_fee: args.base_fee.unwrap_or(base.base_fee),
+        max_fee: args.max_fee.unwrap_or(base.max_fee),
+        ..base
+    };
+    let load = match &args.input {
+        Some(path) => fee_market::parse_recorded_load(&tokio::fs::read_to_string(path).await?)?,
+        None => args.load.generate(args.blocks),
+    };
+    if load.is_empty() {
+        return Err("负载为空".into());
+    }
+
+    let simulations: Vec<FeeSimulation> = scenarios(&base, &args.gas_target, &args.denominator)
+        .iter()
+        .map(|fees| fee_market::simulate(fees, &load))
+        .collect();
+    for line in report(&simulations, args.width) {
+        println!("{}", line);
+    }
+    if let Some(path) = &args.csv {
+        write_csv(path, &simulations)?;
+        println!("逐块数据已写入 {}", path.display());
+    }
+    Ok(())
+}
+
+/// 目标用量与变化分母的全部组合，未指定的一项使用基础配置中的值
+fn scenarios(base: &FeesConfig, gas_targets: &[u64], denominators: &[u64]) -> Vec<FeesConfig> {
+    let or_base = |values: &[u64], default: u64| {
+        if values.is_empty() {
+            vec![default]
+        } else {
+            values.to_vec()
+        }
+    };
+    let denominators = or_base(denominators, base.base_fee_change_denominator);
+    or_base(gas_targets, base.gas_target)
+        .into_iter()
+        .flat_map(|gas_target| {
+            denominators.iter().map(move |&denominator| FeesConfig {
+                gas_target,
+                base_fee_change_denominator: denominator,
+                ..base.clone()
+            })
+        })
+        .collect()
+}
+
+/// 汇总表和走势图，各组走势图使用同一纵轴
+fn report(simulations: &[FeeSimulation], width: usize) -> Vec<String> {
+    let low = simulations
+        .iter()
+        .map(FeeSimulation::min_base_fee)
+        .min()
+        .unwrap_or_default();
+    let high = simulations
+        .iter()
+        .map(FeeSimulation::max_base_fee)
+        .max()
+        .unwrap_or_default();
+    let mut lines = vec![format!(
+        "{:>12} {:>6} {:>14} {:>14} {:>14} {:>8} {:>14}",
+        "gas_target", "denom", "min", "max", "final", "util%", "priced_out"
+    )];
+    for simulation in simulations {
+        lines.push(format!(
+            "{:>12} {:>6} {:>14} {:>14} {:>14} {:>8.1} {:>14}",
+            simulation.gas_target,
+            simulation.base_fee_change_denominator,
+            simulation.min_base_fee(),
+            simulation.max_base_fee(),
+            simulation.final_base_fee(),
+            simulation.average_utilization(),
+            simulation.priced_out()
+        ));
+    }
+    lines.push(String::new());
+    lines.push(format!("基础费用走势（{} - {} wei）", low, high));
+    for simulation in simulations {
+        lines.push(format!(
+            "{:>12}/{:<4} {}",
+            simulation.gas_target,
+            simulation.base_fee_change_denominator,
+            sparkline(&simulation.base_fees(), width, low, high)
+        ));
+    }
+    lines
+}
+
+/// 把序列按宽度分桶取平均，映射到 [`SPARK_LEVELS`]
+fn sparkline(values: &[U256], width: usize, low: U256, high: U256) -> String {
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let to_f64 = |value: U256| value.min(U256::from(u128::MAX)).as_u128() as f64;
+    let (low, high) = (to_f64(low), to_f64(high));
+    let buckets = width.min(values.len());
+    (0..buckets)
+        .map(|i| {
+            let bucket = &values[i * values.len() / buckets..(i + 1) * values.len() / buckets];
+            let mean = bucket.iter().map(|v| to_f64(*v)).sum::<f64>() / bucket.len() as f64;
+            let level = if high > low {
+                ((mean - low) / (high - low) * (SPARK_LEVELS.len() - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+        })
+        .collect()
+}
+
+fn write_csv(path: &Path, simulations: &[FeeSimulation]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record([
+        "gas_target",
+        "denominator",
+        "block",
+        "base_fee",
+        "demand",
+        "gas_used",
+        "backlog",
+        "priced_out",
+    ])?;
+    for simulation in simulations {
+        for block in &simulation.blocks {
+            writer.write_record([
+                simulation.gas_target.to_string(),
+                simulation.base_fee_change_denominator.to_string(),
+                block.number.to_string(),
+                block.base_fee.to_string(),
+                block.demand.to_string(),
+                block.gas_used.to_string(),
+                block.backlog.to_string(),
+                block.priced_out.to_string(),
+            ])?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenarios_and_sparkline() {
+        let base = Genesis::default().fees;
+        let all = scenarios(&base, &[1, 2], &[]);
+        assert_eq!(
+            all.iter()
+                .map(|fees| (fees.gas_target, fees.base_fee_change_denominator))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, base.base_fee_change_denominator),
+                (2, base.base_fee_change_denominator)
+            ]
+        );
+
+        let values: Vec<U256> = (0..8u64).map(U256::from).collect();
+        assert_eq!(
+            sparkline(&values, 8, U256::zero(), U256::from(7)),
+            "▁▂▃▄▅▆▇█"
+        );
+        assert_eq!(
+            sparkline(&values, 4, U256::zero(), U256::from(7))
+                .chars()
+                .count(),
+            4
+        );
+        assert_eq!(sparkline(&values[..1], 4, U256::zero(), U256::zero()), "▁");
+    }
+}
@@ -1,7 +1,25 @@
-use clap::{Parser, Subcommand};
+mod approvals;
+mod batch;
+mod bench;
+mod db;
+mod explorer;
+mod fees;
+mod multisig;
+mod node;
+mod report;
+mod subnet;
+mod validator;
+
+use clap::{Args, Parser, Subcommand};
 use ethers::providers::{Http, Provider};
-use ethers::types::{Address, Bytes, U256};
+use ethers::types::{Bytes, U256};
 // use fairvm_sdk::{client::Client, wallet::Wallet};
+use fair_vm::chain_metadata::ChainMetadata;
+use fair_vm::consensus::ordering_record;
+use fair_vm::faucet::{FaucetErrorResponse, FaucetGrant, FaucetRequest};
+use fair_vm_sdk::address::{parse_address, ChecksumPolicy};
+use fair_vm_sdk::units::{format_fair, parse_amount, Unit};
+use fair_vm_sdk::wallet::message::MessageSignerImpl;
 use fair_vm_sdk::wallet::FairWallet;
 // 请根据实际类型导入 FairWallet 或 HardwareWallet，如果需要
 // use fairvm_sdk::wallet::HardwareWallet;
@@ -11,9 +29,17 @@ use std::str::FromStr;
 /// 默认链 ID
 const CHAIN_ID: u64 = 1337;
 
+/// 解析命令行金额，不带单位时为 wei
+fn parse_value(value: &str) -> Result<U256, String> {
+    parse_amount(value, Unit::Wei).map_err(|e| e.to_string())
+}
+
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// 不校验大小写混合的十六进制地址的 EIP-55 校验和
+    #[arg(long, global = true)]
+    allow_bad_checksum: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -25,6 +51,107 @@ enum Commands {
         #[command(subcommand)]
         action: WalletCommands,
     },
+    /// 链相关操作
+    Chain {
+        #[command(subcommand)]
+        action: ChainCommands,
+    },
+    /// 测试网水龙头
+    Faucet {
+        #[command(subcommand)]
+        action: FaucetCommands,
+    },
+    /// 压测工具
+    Bench {
+        #[command(subcommand)]
+        action: BenchCommands,
+    },
+    /// 原生多签钱包
+    Multisig {
+        #[command(subcommand)]
+        action: multisig::MultisigCommands,
+    },
+    /// 子网部署
+    Subnet {
+        #[command(subcommand)]
+        action: subnet::SubnetCommands,
+    },
+    /// P 链验证人登记
+    Validator {
+        #[command(subcommand)]
+        action: validator::ValidatorCommands,
+    },
+    /// 调试工具
+    Debug {
+        #[command(subcommand)]
+        action: DebugCommands,
+    },
+    /// 数据目录维护
+    Db {
+        #[command(subcommand)]
+        action: db::DbCommands,
+    },
+    /// 静态区块浏览器
+    Explorer {
+        #[command(subcommand)]
+        action: explorer::ExplorerCommands,
+    },
+    /// 费用参数工具
+    Fees {
+        #[command(subcommand)]
+        action: fees::FeesCommands,
+    },
+    /// 对账报表
+    Report {
+        #[command(subcommand)]
+        action: report::ReportCommands,
+    },
+    /// 节点管理
+    Node {
+        #[command(subcommand)]
+        action: node::NodeCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum DebugCommands {
+    /// 重放出块排序记录，解释交易为何被打包或未被打包
+    ReplayOrdering {
+        /// 排序记录文件，例如 ordering-100.json
+        path: String,
+        /// 只解释指定交易哈希
+        #[arg(long)]
+        tx: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum BenchCommands {
+    /// 按固定速率发送签名交易并统计提交与打包延迟
+    Spam(bench::SpamArgs),
+}
+
+#[derive(Subcommand)]
+enum FaucetCommands {
+    /// 领取测试代币
+    Request {
+        /// 水龙头地址，例如 http://127.0.0.1:8080/faucet
+        url: String,
+        /// 接收地址
+        address: String,
+        /// 人机验证令牌（可选）
+        #[arg(long)]
+        captcha: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChainCommands {
+    /// 打印 EIP-3085 wallet_addEthereumChain 参数
+    Metadata {
+        /// RPC URL
+        rpc_url: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -90,8 +217,9 @@ enum WalletCommands {
     SendFromLedger {
         /// 接收地址
         to: String,
-        /// 发送金额(wei)
-        value: String,
+        /// 发送金额，如 1.5fair、20gwei，不带单位时为 wei
+        #[arg(value_parser = parse_value)]
+        value: U256,
         /// RPC URL
         rpc_url: String,
         /// 派生路径（可选）
@@ -104,8 +232,9 @@ enum WalletCommands {
         /// 接收地址
         to: String,
 
-        /// 发送金额(wei)
-        value: String,
+        /// 发送金额，如 1.5fair、20gwei，不带单位时为 wei
+        #[arg(value_parser = parse_value)]
+        value: U256,
 
         /// 私钥或助记词
         key: String,
@@ -119,8 +248,9 @@ enum WalletCommands {
         /// 接收地址
         to: String,
 
-        /// 发送金额(wei)
-        value: String,
+        /// 发送金额，如 1.5fair、20gwei，不带单位时为 wei
+        #[arg(value_parser = parse_value)]
+        value: U256,
 
         /// 数据(可选)
         #[arg(long)]
@@ -144,6 +274,57 @@ enum WalletCommands {
         /// RPC URL
         rpc_url: String,
     },
+
+    /// 从 CSV 批量转账，结果写入 CSV
+    SendBatch(batch::SendBatchArgs),
+
+    /// 扫描地址仍然有效的代币授权，可批量撤销
+    Approvals(approvals::ApprovalsArgs),
+
+    /// 按 EIP-191 个人消息格式签名文本或文件
+    SignMessage {
+        /// 私钥或助记词
+        key: String,
+
+        #[command(flatten)]
+        message: MessageInput,
+    },
+
+    /// 验证 EIP-191 个人消息签名
+    VerifyMessage {
+        /// 签名者地址
+        #[arg(long)]
+        address: String,
+
+        /// 十六进制签名
+        #[arg(long)]
+        signature: String,
+
+        #[command(flatten)]
+        message: MessageInput,
+    },
+}
+
+/// 待签名或验证的消息，文本和文件二选一
+#[derive(Args)]
+struct MessageInput {
+    /// 消息文本
+    #[arg(long, conflicts_with = "file", required_unless_present = "file")]
+    text: Option<String>,
+
+    /// 消息文件，按原始字节签名
+    #[arg(long)]
+    file: Option<String>,
+}
+
+impl MessageInput {
+    fn read(self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match (self.text, self.file) {
+            (Some(text), _) => Ok(text.into_bytes()),
+            (None, Some(path)) => Ok(std::fs::read(path)?),
+            (None, None) => Err("需要指定 --text 或 --file".into()),
+        }
+    }
 }
 
 fn generate_random_private_key() -> String {
@@ -152,7 +333,10 @@ fn generate_random_private_key() -> String {
     hex::encode(secret_key.secret_bytes())
 }
 
-async fn handle_wallet_command(cmd: WalletCommands) -> Result<(), Box<dyn std::error::Error>> {
+async fn handle_wallet_command(
+    cmd: WalletCommands,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
     match cmd {
         WalletCommands::New { mnemonic } => {
             if mnemonic {
@@ -235,8 +419,7 @@ async fn handle_wallet_command(cmd: WalletCommands) -> Result<(), Box<dyn std::e
             let provider = Provider::<Http>::try_from(&rpc_url)?;
             let wallet = FairWallet::connect_ledger(path, CHAIN_ID).await?;
 
-            let to = Address::from_str(&to)?;
-            let value = U256::from_str(&value)?;
+            let to = parse_address(&to, policy)?;
 
             let tx = ethers::types::TransactionRequest {
                 to: Some(ethers::types::NameOrAddress::Address(to)),
@@ -261,8 +444,7 @@ async fn handle_wallet_command(cmd: WalletCommands) -> Result<(), Box<dyn std::e
                 FairWallet::from_private_key(&key, CHAIN_ID)?
             };
 
-            let to = Address::from_str(&to)?;
-            let value = U256::from_str(&value)?;
+            let to = parse_address(&to, policy)?;
 
             let tx = ethers::types::TransactionRequest {
                 to: Some(ethers::types::NameOrAddress::Address(to)),
@@ -281,8 +463,7 @@ async fn handle_wallet_command(cmd: WalletCommands) -> Result<(), Box<dyn std::e
             rpc_url,
         } => {
             let provider = Provider::<Http>::try_from(&rpc_url)?;
-            let to = Address::from_str(&to)?;
-            let value = U256::from_str(&value)?;
+            let to = parse_address(&to, policy)?;
             let data = match data {
                 Some(d) => {
                     let decoded = hex::decode(d).unwrap();
@@ -313,7 +494,7 @@ async fn handle_wallet_command(cmd: WalletCommands) -> Result<(), Box<dyn std::e
 
         WalletCommands::GetNonce { address, rpc_url } => {
             let provider = Provider::<Http>::try_from(&rpc_url)?;
-            let address = Address::from_str(&address)?;
+            let address = parse_address(&address, policy)?;
             let wallet = FairWallet::from_private_key(
                 "0000000000000000000000000000000000000000000000000000000000000001",
                 CHAIN_ID,
@@ -321,6 +502,107 @@ async fn handle_wallet_command(cmd: WalletCommands) -> Result<(), Box<dyn std::e
             let nonce = wallet.get_nonce(&provider, address).await?;
             println!("账户 nonce: {}", nonce);
         }
+
+        WalletCommands::SendBatch(args) => batch::send_batch(args, policy).await?,
+
+        WalletCommands::Approvals(args) => approvals::scan(args, policy).await?,
+
+        WalletCommands::SignMessage { key, message } => {
+            let wallet = if key.contains(" ") {
+                FairWallet::from_mnemonic(&key, CHAIN_ID)?
+            } else {
+                FairWallet::from_private_key(&key, CHAIN_ID)?
+            };
+            let signature = wallet.sign_personal_message(&message.read()?).await?;
+            println!("地址: {:?}", wallet.address().await?);
+            println!("签名: 0x{}", signature);
+        }
+
+        WalletCommands::VerifyMessage {
+            address,
+            signature,
+            message,
+        } => {
+            let address = parse_address(&address, policy)?;
+            let signature = ethers::types::Signature::from_str(&signature)?;
+            let signer = MessageSignerImpl::new(address);
+            if !signer.verify_message(&message.read()?, &signature, address)? {
+                return Err(format!("签名无效，签名者不是 {:?}", address).into());
+            }
+            println!("签名有效，签名者: {:?}", address);
+        }
+    }
+    Ok(())
+}
+
+async fn handle_chain_command(cmd: ChainCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        ChainCommands::Metadata { rpc_url } => {
+            let provider = Provider::<Http>::try_from(&rpc_url)?;
+            let metadata: ChainMetadata = provider.request("fairvm_chainMetadata", ()).await?;
+            metadata.validate()?;
+            println!("{}", serde_json::to_string_pretty(&metadata)?);
+        }
+    }
+    Ok(())
+}
+
+async fn handle_faucet_command(
+    cmd: FaucetCommands,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        FaucetCommands::Request {
+            url,
+            address,
+            captcha,
+        } => {
+            parse_address(&address, policy)?;
+            let response = reqwest::Client::new()
+                .post(&url)
+                .json(&FaucetRequest { address, captcha })
+                .send()
+                .await?;
+            if response.status().is_success() {
+                let grant: FaucetGrant = response.json().await?;
+                println!("已领取 {}", format_fair(grant.amount));
+                println!("当前余额: {}", format_fair(grant.balance));
+            } else {
+                let error: FaucetErrorResponse = response.json().await?;
+                return Err(error.error.into());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_debug_command(cmd: DebugCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        DebugCommands::ReplayOrdering { path, tx } => {
+            let record = ordering_record::load_record(&path)?;
+            println!(
+                "区块 {}: 候选交易 {} 笔, 打包 {} 笔",
+                record.block_number,
+                record.candidates.len(),
+                record.included.len()
+            );
+            if record.verify() {
+                println!("重放结果与记录一致");
+            } else {
+                println!("重放结果与记录不一致: {:?}", record.replay());
+            }
+
+            let hashes = match tx {
+                Some(tx) => vec![ethers::types::H256::from_str(&tx)?],
+                None => record.candidates.iter().map(|c| c.hash).collect(),
+            };
+            for hash in hashes {
+                match record.explain(&hash) {
+                    Some(explanation) => println!("{}", explanation),
+                    None => println!("交易 {:?} 不在候选交易中", hash),
+                }
+            }
+        }
     }
     Ok(())
 }
@@ -328,9 +610,28 @@ async fn handle_wallet_command(cmd: WalletCommands) -> Result<(), Box<dyn std::e
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    let policy = if cli.allow_bad_checksum {
+        ChecksumPolicy::Ignore
+    } else {
+        ChecksumPolicy::Enforce
+    };
 
     match cli.command {
-        Commands::Wallet { action } => handle_wallet_command(action).await?,
+        Commands::Wallet { action } => handle_wallet_command(action, policy).await?,
+        Commands::Chain { action } => handle_chain_command(action).await?,
+        Commands::Faucet { action } => handle_faucet_command(action, policy).await?,
+        Commands::Bench { action } => match action {
+            BenchCommands::Spam(args) => bench::spam(args, policy).await?,
+        },
+        Commands::Multisig { action } => multisig::handle(action, policy).await?,
+        Commands::Subnet { action } => subnet::handle(action).await?,
+        Commands::Validator { action } => validator::handle(action).await?,
+        Commands::Debug { action } => handle_debug_command(action)?,
+        Commands::Db { action } => db::handle(action).await?,
+        Commands::Explorer { action } => explorer::handle(action).await?,
+        Commands::Fees { action } => fees::handle(action).await?,
+        Commands::Report { action } => report::handle(action, policy).await?,
+        Commands::Node { action } => node::handle(action).await?,
     }
 
     Ok(())
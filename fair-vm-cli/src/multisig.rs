@@ -0,0 +1,340 @@
+//! 原生多签工作流：生成提案文件，各持有者离线签名后汇总提交
+//!
+//! 提案文件和签名文件都是 JSON，可以通过任意渠道在持有者之间传递；
+//! 只有 `create` 和 `submit` 需要连接节点。
+
+use clap::{Args, Subcommand};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, Bytes, TransactionRequest, H160, U256};
+use fair_vm::native_multisig::{
+    MultisigConfig, MultisigProposal, NativeMultisigCall, NATIVE_MULTISIG_ADDRESS,
+    NATIVE_MULTISIG_GAS,
+};
+use fair_vm_sdk::address::{parse_address, ChecksumPolicy};
+use fair_vm_sdk::wallet::FairWallet;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Subcommand)]
+pub enum MultisigCommands {
+    /// 在链上登记多签钱包
+    Create {
+        #[command(flatten)]
+        wallet: WalletArgs,
+        #[command(flatten)]
+        sender: SenderArgs,
+    },
+    /// 打印多签钱包地址
+    Address {
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    /// 生成转账提案文件
+    Propose(ProposeArgs),
+    /// 对提案签名，生成签名文件
+    Sign {
+        /// 提案文件
+        #[arg(long)]
+        proposal: String,
+        /// 持有者私钥或助记词
+        #[arg(long)]
+        key: String,
+        /// 签名文件，默认在提案文件名后加签名者地址
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// 汇总签名并在达到门限后提交
+    Submit {
+        /// 提案文件
+        #[arg(long)]
+        proposal: String,
+        /// 签名文件，可重复指定
+        #[arg(long = "signature", required = true)]
+        signatures: Vec<String>,
+        #[command(flatten)]
+        sender: SenderArgs,
+    },
+}
+
+/// 多签钱包配置参数
+#[derive(Args, Debug)]
+pub struct WalletArgs {
+    /// 持有者地址，逗号分隔
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub owners: Vec<String>,
+    /// 执行所需的签名数
+    #[arg(long)]
+    pub threshold: usize,
+    /// 盐值，相同持有者可创建多个钱包
+    #[arg(long, default_value_t = 0)]
+    pub salt: u64,
+}
+
+impl WalletArgs {
+    fn config(&self, policy: ChecksumPolicy) -> Result<MultisigConfig, Box<dyn std::error::Error>> {
+        let owners = self
+            .owners
+            .iter()
+            .map(|owner| parse_address(owner, policy).map(fair_vm::Address::from))
+            .collect::<Result<Vec<_>, _>>()?;
+        let config = MultisigConfig {
+            owners,
+            threshold: self.threshold,
+            salt: self.salt,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// 发送交易的账户参数
+#[derive(Args, Debug)]
+pub struct SenderArgs {
+    /// 支付手续费的私钥或助记词，不必是持有者
+    #[arg(long)]
+    pub key: String,
+    /// RPC URL
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    pub rpc_url: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ProposeArgs {
+    #[command(flatten)]
+    pub wallet: WalletArgs,
+    /// 接收地址
+    #[arg(long)]
+    pub to: String,
+    /// 转账金额，如 1.5fair，不带单位时为 wei
+    #[arg(long, value_parser = crate::parse_value)]
+    pub value: U256,
+    /// 钱包当前 nonce，每执行一个提案加 1
+    #[arg(long, default_value_t = 0)]
+    pub nonce: u64,
+    /// 链 ID
+    #[arg(long, default_value_t = crate::CHAIN_ID)]
+    pub chain_id: u64,
+    /// 提案文件
+    #[arg(long, default_value = "proposal.json")]
+    pub out: String,
+}
+
+/// 持有者的离线签名
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureFile {
+    pub signer: Address,
+    pub signature: Bytes,
+}
+
+fn load_signer(key: &str, chain_id: u64) -> Result<LocalWallet, Box<dyn std::error::Error>> {
+    let wallet = if key.contains(' ') {
+        FairWallet::from_mnemonic(key, chain_id)?
+    } else {
+        FairWallet::from_private_key(key.trim_start_matches("0x"), chain_id)?
+    };
+    Ok(LocalWallet::from_str(&wallet.export_private_key())?.with_chain_id(chain_id))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(|e| format!("解析 {} 失败: {}", path, e).into())
+}
+
+fn write_json<T: Serialize>(path: &str, value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+fn default_signature_path(proposal: &str, signer: &Address) -> String {
+    let stem = proposal.trim_end_matches(".json");
+    format!("{}.{:?}.sig.json", stem, signer)
+}
+
+/// 对提案签名
+pub async fn sign_proposal(
+    proposal: &MultisigProposal,
+    signer: &LocalWallet,
+) -> Result<SignatureFile, Box<dyn std::error::Error>> {
+    let owner = fair_vm::Address::from(signer.address());
+    if !proposal.wallet.owners.contains(&owner) {
+        return Err(format!("{:?} 不是多签钱包持有者", signer.address()).into());
+    }
+    let signature = signer.sign_message(proposal.digest().as_bytes()).await?;
+    Ok(SignatureFile {
+        signer: signer.address(),
+        signature: signature.to_vec().into(),
+    })
+}
+
+/// 汇总签名并校验是否达到门限，同一持有者的多份签名只计一次
+pub fn collect_signatures(
+    proposal: &MultisigProposal,
+    files: &[SignatureFile],
+) -> Result<Vec<Bytes>, Box<dyn std::error::Error>> {
+    let mut signatures: Vec<Bytes> = Vec::new();
+    let mut signers = Vec::new();
+    for file in files {
+        let recovered = proposal.signers(std::slice::from_ref(&file.signature))?;
+        if recovered[0] != fair_vm::Address::from(file.signer) {
+            return Err(format!("{:?} 的签名与提案不符", file.signer).into());
+        }
+        if signers.contains(&file.signer) {
+            continue;
+        }
+        signers.push(file.signer);
+        signatures.push(file.signature.clone());
+    }
+    proposal.verify(&signatures)?;
+    Ok(signatures)
+}
+
+async fn send_call(
+    call: &NativeMultisigCall,
+    sender: &SenderArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let provider = Provider::<Http>::try_from(sender.rpc_url.as_str())?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    if let NativeMultisigCall::Execute { proposal, .. } = call {
+        if proposal.chain_id != chain_id {
+            return Err(format!(
+                "提案链 ID {} 与节点链 ID {} 不一致",
+                proposal.chain_id, chain_id
+            )
+            .into());
+        }
+    }
+    let signer = load_signer(&sender.key, chain_id)?;
+    let nonce = provider
+        .get_transaction_count(signer.address(), Some(BlockNumber::Pending.into()))
+        .await?;
+    let request = TransactionRequest::new()
+        .from(signer.address())
+        .to(H160::from(NATIVE_MULTISIG_ADDRESS))
+        .value(U256::zero())
+        .data(call.to_transaction_data())
+        .gas(NATIVE_MULTISIG_GAS)
+        .gas_price(provider.get_gas_price().await?)
+        .nonce(nonce)
+        .chain_id(chain_id);
+    let tx: TypedTransaction = request.into();
+    let signature = signer.sign_transaction(&tx).await?;
+    let pending = provider
+        .send_raw_transaction(tx.rlp_signed(&signature))
+        .await?;
+    println!("交易已提交: {:?}", pending.tx_hash());
+    Ok(())
+}
+
+/// 执行多签命令
+pub async fn handle(
+    cmd: MultisigCommands,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        MultisigCommands::Create { wallet, sender } => {
+            let config = wallet.config(policy)?;
+            println!("多签钱包地址: {:?}", H160::from(config.address()));
+            send_call(&NativeMultisigCall::Create { wallet: config }, &sender).await?;
+        }
+        MultisigCommands::Address { wallet } => {
+            println!("{:?}", H160::from(wallet.config(policy)?.address()));
+        }
+        MultisigCommands::Propose(args) => {
+            let proposal = MultisigProposal {
+                chain_id: args.chain_id,
+                wallet: args.wallet.config(policy)?,
+                to: parse_address(&args.to, policy)?.into(),
+                value: args.value,
+                nonce: args.nonce,
+            };
+            write_json(&args.out, &proposal)?;
+            println!("提案摘要: {:?}", proposal.digest());
+            println!("提案已写入: {}", args.out);
+        }
+        MultisigCommands::Sign {
+            proposal: path,
+            key,
+            out,
+        } => {
+            let proposal: MultisigProposal = read_json(&path)?;
+            let signer = load_signer(&key, proposal.chain_id)?;
+            let file = sign_proposal(&proposal, &signer).await?;
+            let out = out.unwrap_or_else(|| default_signature_path(&path, &file.signer));
+            write_json(&out, &file)?;
+            println!("签名已写入: {}", out);
+        }
+        MultisigCommands::Submit {
+            proposal,
+            signatures,
+            sender,
+        } => {
+            let proposal: MultisigProposal = read_json(&proposal)?;
+            let files = signatures
+                .iter()
+                .map(|path| read_json(path))
+                .collect::<Result<Vec<SignatureFile>, _>>()?;
+            let signatures = collect_signatures(&proposal, &files)?;
+            println!(
+                "已收集 {}/{} 个有效签名",
+                signatures.len(),
+                proposal.wallet.threshold
+            );
+            send_call(
+                &NativeMultisigCall::Execute {
+                    proposal,
+                    signatures,
+                },
+                &sender,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner(seed: u8) -> LocalWallet {
+        LocalWallet::from_bytes(&[seed; 32]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_collect() {
+        let owners: Vec<LocalWallet> = (1..=3).map(owner).collect();
+        let proposal = MultisigProposal {
+            chain_id: 1337,
+            wallet: MultisigConfig {
+                owners: owners.iter().map(|o| o.address().into()).collect(),
+                threshold: 2,
+                salt: 0,
+            },
+            to: Address::repeat_byte(9).into(),
+            value: U256::from(5),
+            nonce: 0,
+        };
+
+        let first = sign_proposal(&proposal, &owners[0]).await.unwrap();
+        let second = sign_proposal(&proposal, &owners[1]).await.unwrap();
+        assert!(sign_proposal(&proposal, &owner(8)).await.is_err());
+
+        // 同一签名重复提供只计一次
+        assert!(collect_signatures(&proposal, &[first.clone(), first.clone()]).is_err());
+        let signatures = collect_signatures(&proposal, &[first.clone(), second]).unwrap();
+        assert_eq!(signatures.len(), 2);
+
+        // 声明的签名者与实际签名者不符
+        let forged = SignatureFile {
+            signer: owners[2].address(),
+            signature: first.signature.clone(),
+        };
+        assert!(collect_signatures(&proposal, &[first, forged]).is_err());
+
+        // 签名文件可以 JSON 往返
+        let json = serde_json::to_string(&signatures[0]).unwrap();
+        assert_eq!(serde_json::from_str::<Bytes>(&json).unwrap(), signatures[0]);
+    }
+}
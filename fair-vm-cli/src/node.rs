@@ -0,0 +1,194 @@
+//! 节点管理
+//!
+//! `start` 按配置文件启动 FairVM 并开放 RPC 端点，Ctrl+C 停止。`--ephemeral` 以临时模式运行：
+//! 链只保存在内存中，不写入数据目录，适合 CI 任务和临时试验；配合 `--shutdown-after` 到期自动退出。
+//! `--light` 以轻节点运行：不执行区块，只从 `--light-source` 同步并校验区块头，第一个区块头必须
+//! 链接到 `--trusted-parent` 给出的可信锚点。
+
+use clap::{Args, Subcommand};
+use fair_vm::api::{ApiServer, VmExt};
+use fair_vm::light::{self, LightClient, LightClientConfig, RpcLightSource};
+use fair_vm::FairVM;
+use fair_vm_core::config::Config;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+#[derive(Subcommand)]
+pub enum NodeCommands {
+    /// 启动节点
+    Start(NodeArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct NodeArgs {
+    /// 配置文件，未指定时使用默认配置
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// 数据目录，覆盖配置文件中的设置
+    #[arg(long)]
+    pub data_dir: Option<PathBuf>,
+    /// 临时模式，不向磁盘写入任何内容
+    #[arg(long)]
+    pub ephemeral: bool,
+    /// 临时模式下运行指定秒数后自动停止
+    #[arg(long, requires = "ephemeral")]
+    pub shutdown_after: Option<u64>,
+    /// 以轻节点运行，只同步并校验区块头
+    #[arg(long, conflicts_with = "ephemeral")]
+    pub light: bool,
+    /// 轻节点的数据源（全节点 JSON-RPC 地址），覆盖配置文件中的设置
+    #[arg(long, requires = "light")]
+    pub light_source: Option<String>,
+    /// 轻节点的可信锚点：起始区块的父区块哈希，覆盖配置文件中的设置
+    #[arg(long, requires = "light")]
+    pub trusted_parent: Option<String>,
+}
+
+/// 合并命令行参数后的节点配置
+fn node_config(args: &NodeArgs) -> Result<Config, String> {
+    let mut config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    if let Some(data_dir) = &args.data_dir {
+        config.set_data_dir(data_dir.clone());
+    }
+    if args.ephemeral {
+        config.ephemeral.enabled = true;
+        config.ephemeral.shutdown_after_secs = args.shutdown_after;
+    }
+    if args.light {
+        config.light.enabled = true;
+        if let Some(url) = &args.light_source {
+            config.light.source_url = url.clone();
+        }
+        if let Some(anchor) = &args.trusted_parent {
+            config.light.trusted_parent = Some(anchor.clone());
+        }
+    }
+    config.validate_rpc()?;
+    config.ephemeral.validate()?;
+    config.light.validate()?;
+    Ok(config)
+}
+
+pub async fn handle(cmd: NodeCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        NodeCommands::Start(args) => start(args).await,
+    }
+}
+
+async fn start(args: NodeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = node_config(&args)?;
+    if config.light.enabled {
+        return start_light(&config).await;
+    }
+    let mut fair_vm = FairVM::with_config(config.clone());
+    fair_vm.start().await?;
+    let mut shutdown = fair_vm.shutdown_signal();
+    let fair_vm = Arc::new(RwLock::new(fair_vm));
+
+    let vm: Arc<RwLock<dyn VmExt>> = fair_vm.clone();
+    let server = ApiServer::new(vm).with_cache(&config.rpc.cache);
+    let http = server.start_http(&config.rpc.http).await?;
+    if http.is_some() {
+        println!("HTTP RPC 监听于 {}", config.rpc.http.addr());
+    }
+    // IPC 套接字是数据目录下的文件，临时模式不开放
+    #[cfg(unix)]
+    let _ipc = if config.ephemeral.enabled {
+        if config.rpc.ipc.enabled {
+            println!("临时模式不开放 IPC 端点");
+        }
+        None
+    } else {
+        server.start_ipc(&config.rpc.ipc, &config.data_dir).await?
+    };
+    if config.ephemeral.enabled {
+        println!("以临时模式运行，停止后不保留任何状态");
+    }
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => println!("收到中断信号，正在停止节点"),
+        _ = shutdown.wait_for(|stop| *stop) => println!("运行时长已到，正在停止节点"),
+    }
+    if let Some(http) = http {
+        http.abort();
+    }
+    fair_vm.write().await.stop().await?;
+    Ok(())
+}
+
+/// 以轻节点运行，按间隔同步区块头直到收到中断信号
+async fn start_light(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let client_config = LightClientConfig::from_config(&config.light)?;
+    let source = RpcLightSource::new(config.light.source_url.clone());
+    let client = Arc::new(RwLock::new(LightClient::new(source, client_config)));
+    let interval = Duration::from_millis(config.light.interval_ms);
+    let task = light::spawn(client.clone(), interval);
+    println!(
+        "以轻节点模式运行，从 {} 同步区块头",
+        config.light.source_url
+    );
+
+    tokio::signal::ctrl_c().await?;
+    println!("收到中断信号，正在停止轻节点");
+    task.abort();
+    if let Some(height) = client.read().await.height() {
+        println!("已验证到区块 {}", height);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_config() {
+        let args = NodeArgs {
+            config: None,
+            data_dir: Some(PathBuf::from("/tmp/fairvm")),
+            ephemeral: true,
+            shutdown_after: Some(60),
+            light: false,
+            light_source: None,
+            trusted_parent: None,
+        };
+        let config = node_config(&args).unwrap();
+        assert!(config.ephemeral.enabled);
+        assert_eq!(config.ephemeral.shutdown_after_secs, Some(60));
+        assert_eq!(config.data_dir, PathBuf::from("/tmp/fairvm"));
+
+        let args = NodeArgs {
+            shutdown_after: Some(0),
+            ..args
+        };
+        assert!(node_config(&args).is_err());
+    }
+
+    #[test]
+    fn test_light_node_config() {
+        let args = NodeArgs {
+            config: None,
+            data_dir: None,
+            ephemeral: false,
+            shutdown_after: None,
+            light: true,
+            light_source: Some("http://127.0.0.1:8545".to_string()),
+            trusted_parent: None,
+        };
+        // 没有可信锚点时拒绝启动
+        assert!(node_config(&args).is_err());
+
+        let args = NodeArgs {
+            trusted_parent: Some(format!("0x{}", "ab".repeat(32))),
+            ..args
+        };
+        let config = node_config(&args).unwrap();
+        assert!(config.light.enabled);
+        assert_eq!(config.light.source_url, "http://127.0.0.1:8545");
+    }
+}
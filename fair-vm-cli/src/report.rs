@@ -0,0 +1,227 @@
+//! 对账报表：按账户导出区块范围内的转入、转出、手续费和余额
+//!
+//! 数据来自节点的账户转账历史索引（`fairvm_getAccountHistory`），区块范围超过单次查询上限时
+//! 分段查询。金额、手续费和余额均以 wei 为单位，余额为交易执行后的账户余额。
+
+use clap::{Args, Subcommand};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, U256};
+use fair_vm::account_history::{AccountTransfer, MAX_HISTORY_RANGE};
+use fair_vm_sdk::address::{parse_address, to_checksum, ChecksumPolicy};
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// 导出账户的转账与手续费 CSV
+    Account(AccountArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AccountArgs {
+    /// RPC URL
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    pub rpc_url: String,
+    /// 账户地址
+    #[arg(long)]
+    pub address: String,
+    /// 起始区块
+    #[arg(long, default_value_t = 0)]
+    pub from_block: u64,
+    /// 结束区块，默认最新区块
+    #[arg(long)]
+    pub to_block: Option<u64>,
+    /// 输出 CSV 文件，默认写到标准输出
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+/// 报表中的一行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportRow {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub transaction_hash: String,
+    /// in、out 或 self
+    pub direction: &'static str,
+    /// 对方地址，转给自己时为本账户
+    pub counterparty: String,
+    /// 实际转移的金额，执行失败的交易为 0
+    pub amount: U256,
+    /// 本账户支付的手续费，转入为 0
+    pub fee: U256,
+    pub status: bool,
+    pub balance: U256,
+}
+
+pub async fn handle(
+    cmd: ReportCommands,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        ReportCommands::Account(args) => account(args, policy).await,
+    }
+}
+
+async fn account(
+    args: AccountArgs,
+    policy: ChecksumPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let address = parse_address(&args.address, policy)?;
+    let provider = Provider::<Http>::try_from(args.rpc_url.as_str())?;
+    let to_block = match args.to_block {
+        Some(to_block) => to_block,
+        None => provider.get_block_number().await?.as_u64(),
+    };
+    if to_block < args.from_block {
+        return Err("结束区块不能小于起始区块".into());
+    }
+
+    let mut transfers: Vec<AccountTransfer> = Vec::new();
+    let mut from = args.from_block;
+    loop {
+        let to = to_block.min(from.saturating_add(MAX_HISTORY_RANGE - 1));
+        let page: Vec<AccountTransfer> = provider
+            .request("fairvm_getAccountHistory", (address, from, to))
+            .await?;
+        transfers.extend(page);
+        if to == to_block {
+            break;
+        }
+        from = to + 1;
+    }
+
+    let rows = rows(address, &transfers);
+    match &args.out {
+        Some(path) => {
+            write_csv(std::fs::File::create(path)?, &rows)?;
+            eprintln!("已导出 {} 条记录到 {}", rows.len(), path.display());
+        }
+        None => write_csv(std::io::stdout(), &rows)?,
+    }
+    Ok(())
+}
+
+/// 把转账记录换算为本账户视角的报表行
+pub fn rows(address: Address, transfers: &[AccountTransfer]) -> Vec<ReportRow> {
+    transfers
+        .iter()
+        .map(|transfer| {
+            let outgoing = transfer.from == address;
+            let to = transfer.to.unwrap_or_default();
+            let (direction, counterparty) = match (outgoing, to == address) {
+                (true, true) => ("self", address),
+                (true, false) => ("out", to),
+                _ => ("in", transfer.from),
+            };
+            ReportRow {
+                block_number: transfer.block_number,
+                timestamp: transfer.timestamp,
+                transaction_hash: format!("{:?}", transfer.transaction_hash),
+                direction,
+                counterparty: to_checksum(counterparty),
+                amount: if transfer.status {
+                    transfer.value
+                } else {
+                    U256::zero()
+                },
+                fee: if outgoing { transfer.fee } else { U256::zero() },
+                status: transfer.status,
+                balance: transfer.balance,
+            }
+        })
+        .collect()
+}
+
+fn write_csv(output: impl Write, rows: &[ReportRow]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(output);
+    writer.write_record([
+        "block",
+        "timestamp",
+        "tx_hash",
+        "direction",
+        "counterparty",
+        "amount",
+        "fee",
+        "status",
+        "balance",
+    ])?;
+    for row in rows {
+        writer.write_record([
+            row.block_number.to_string(),
+            row.timestamp.to_string(),
+            row.transaction_hash.clone(),
+            row.direction.to_string(),
+            row.counterparty.clone(),
+            row.amount.to_string(),
+            row.fee.to_string(),
+            row.status.to_string(),
+            row.balance.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    fn transfer(from: u64, to: u64, status: bool, balance: u64) -> AccountTransfer {
+        AccountTransfer {
+            transaction_hash: H256::from_low_u64_be(1),
+            block_number: 7,
+            timestamp: 1_700_000_000,
+            from: Address::from_low_u64_be(from),
+            to: Some(Address::from_low_u64_be(to)),
+            value: U256::from(100),
+            fee: U256::from(21),
+            status,
+            balance: U256::from(balance),
+        }
+    }
+
+    #[test]
+    fn test_rows() {
+        let me = Address::from_low_u64_be(1);
+        let transfers = [
+            transfer(2, 1, true, 100),
+            transfer(1, 3, true, 0),
+            transfer(1, 3, false, 0),
+            transfer(1, 1, true, 0),
+        ];
+        let rows = rows(me, &transfers);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| (row.direction, row.amount.as_u64(), row.fee.as_u64()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("in", 100, 0),
+                ("out", 100, 21),
+                ("out", 0, 21),
+                ("self", 100, 21)
+            ]
+        );
+        assert_eq!(
+            rows[0].counterparty,
+            to_checksum(Address::from_low_u64_be(2))
+        );
+        assert_eq!(
+            rows[1].counterparty,
+            to_checksum(Address::from_low_u64_be(3))
+        );
+
+        let mut output = Vec::new();
+        write_csv(&mut output, &rows).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(
+            lines.next(),
+            Some("block,timestamp,tx_hash,direction,counterparty,amount,fee,status,balance")
+        );
+        assert_eq!(lines.count(), 4);
+    }
+}
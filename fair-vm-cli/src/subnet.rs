@@ -0,0 +1,318 @@
+//! 子网部署：创建子网、安装 VM 插件并以给定的创世文件创建 FairVM 链
+//!
+//! 两种方式：指定 `--network-runner` 时通过 avalanche-network-runner 启动本地集群并
+//! 创建链；否则使用 `--key` 通过节点的公开 API 依次提交 CreateSubnetTx 和
+//! CreateChainTx。VM 插件以 VM ID 为文件名复制到插件目录，每个验证人都需要安装。
+
+use avalanche_network_runner_sdk::{BlockchainSpec, Client, GlobalConfig, StartRequest};
+use avalanche_types::{ids, subnet, wallet};
+use clap::{Args, Subcommand};
+use fair_vm::genesis::Genesis;
+use fair_vm::plugin;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// 默认的链名称，同时也是 VM 名称
+pub const DEFAULT_CHAIN_NAME: &str = "fairvm";
+
+/// 等待 network-runner 集群就绪的超时时间
+const CLUSTER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 轮询集群状态的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Subcommand)]
+pub enum SubnetCommands {
+    /// 创建子网和 FairVM 链，打印链 ID 与 RPC 地址
+    Create(CreateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CreateArgs {
+    /// 创世文件
+    #[arg(long)]
+    pub genesis: String,
+    /// VM 插件二进制
+    #[arg(long)]
+    pub vm_plugin: String,
+    /// 链名称，VM ID 由其计算
+    #[arg(long, default_value = DEFAULT_CHAIN_NAME)]
+    pub chain_name: String,
+    /// AvalancheGo 插件目录，默认为可执行文件旁的 plugins 目录
+    #[arg(long)]
+    pub plugin_dir: Option<String>,
+    /// network-runner gRPC 地址，指定时启动本地集群
+    #[arg(long, conflicts_with_all = ["key", "subnet_id"], requires = "avalanchego")]
+    pub network_runner: Option<String>,
+    /// AvalancheGo 可执行文件，network-runner 方式需要
+    #[arg(long)]
+    pub avalanchego: Option<String>,
+    /// 集群节点数
+    #[arg(long, default_value_t = 5)]
+    pub num_nodes: u32,
+    /// 支付手续费的 P 链私钥，PrivateKey-... 或十六进制
+    #[arg(long, required_unless_present = "network_runner")]
+    pub key: Option<String>,
+    /// AvalancheGo API 地址
+    #[arg(long, default_value = "http://127.0.0.1:9650")]
+    pub node_url: String,
+    /// 使用已有子网，不再创建
+    #[arg(long)]
+    pub subnet_id: Option<String>,
+}
+
+/// 部署结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    pub vm_id: ids::Id,
+    pub subnet_id: ids::Id,
+    pub blockchain_id: ids::Id,
+    /// 各节点的 API 地址
+    pub node_urls: Vec<String>,
+}
+
+impl Deployment {
+    /// 节点上链的 RPC 地址
+    pub fn rpc_url(node_url: &str, blockchain_id: &ids::Id) -> String {
+        format!(
+            "{}/ext/bc/{}/rpc",
+            node_url.trim_end_matches('/'),
+            blockchain_id
+        )
+    }
+
+    fn print(&self) {
+        println!("VM ID: {}", self.vm_id);
+        println!("子网 ID: {}", self.subnet_id);
+        println!("链 ID: {}", self.blockchain_id);
+        for node_url in &self.node_urls {
+            println!("RPC: {}", Self::rpc_url(node_url, &self.blockchain_id));
+        }
+    }
+}
+
+/// 读取并校验创世文件
+fn read_genesis(path: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let data = std::fs::read(path)?;
+    serde_json::from_slice::<Genesis>(&data)
+        .map_err(|e| format!("无效的创世文件 {}: {}", path, e))?;
+    Ok(data)
+}
+
+/// 默认插件目录：AvalancheGo 可执行文件旁的 plugins 目录
+fn default_plugin_dir(avalanchego: &str) -> PathBuf {
+    Path::new(avalanchego)
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("plugins")
+}
+
+/// 以 VM ID 为文件名将插件复制到插件目录
+pub fn install_plugin(
+    vm_plugin: &Path,
+    plugin_dir: &Path,
+    vm_id: &ids::Id,
+) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(plugin_dir)?;
+    let target = plugin_dir.join(vm_id.to_string());
+    std::fs::copy(vm_plugin, &target)?;
+    Ok(target)
+}
+
+/// 处理子网命令
+pub async fn handle(cmd: SubnetCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        SubnetCommands::Create(args) => create(args).await,
+    }
+}
+
+async fn create(args: CreateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let genesis = read_genesis(&args.genesis)?;
+    let vm_id = subnet::vm_name_to_id(&args.chain_name)?;
+    if args.network_runner.is_none() {
+        // 节点协议版本不兼容时插件无法加载，提前报错
+        let version = plugin::handshake(&args.node_url).await?;
+        println!("AvalancheGo 版本: {}", version.version);
+    }
+
+    let plugin_dir = match (&args.plugin_dir, &args.avalanchego) {
+        (Some(dir), _) => Some(PathBuf::from(dir)),
+        (None, Some(avalanchego)) => Some(default_plugin_dir(avalanchego)),
+        (None, None) => None,
+    };
+    match &plugin_dir {
+        Some(dir) => {
+            let target = install_plugin(Path::new(&args.vm_plugin), dir, &vm_id)?;
+            println!("VM 插件已安装: {}", target.display());
+        }
+        None => println!(
+            "未指定插件目录，请在每个验证人的插件目录中安装 {} 并命名为 {}",
+            args.vm_plugin, vm_id
+        ),
+    }
+
+    let deployment = match &args.network_runner {
+        Some(endpoint) => {
+            let plugin_dir = plugin_dir.ok_or("network-runner 方式需要插件目录")?;
+            create_with_network_runner(&args, endpoint, &plugin_dir, vm_id).await?
+        }
+        None => create_with_api(&args, genesis, vm_id).await?,
+    };
+    deployment.print();
+    Ok(())
+}
+
+/// 通过 network-runner 启动本地集群并创建链
+async fn create_with_network_runner(
+    args: &CreateArgs,
+    endpoint: &str,
+    plugin_dir: &Path,
+    vm_id: ids::Id,
+) -> Result<Deployment, Box<dyn std::error::Error>> {
+    let cli = Client::new(endpoint).await;
+    cli.ping().await?;
+
+    let exec_path = args.avalanchego.clone().ok_or("需要 --avalanchego")?;
+    cli.start(StartRequest {
+        exec_path,
+        num_nodes: Some(args.num_nodes),
+        plugin_dir: plugin_dir.display().to_string(),
+        global_node_config: Some(serde_json::to_string(&GlobalConfig {
+            log_level: String::from("info"),
+        })?),
+        blockchain_specs: vec![BlockchainSpec {
+            vm_name: args.chain_name.clone(),
+            genesis: args.genesis.clone(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    })
+    .await?;
+    println!("集群启动中，等待链创建完成...");
+
+    let start = Instant::now();
+    loop {
+        if start.elapsed() > CLUSTER_TIMEOUT {
+            return Err("等待集群就绪超时".into());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if cli.health().await.is_err() {
+            continue;
+        }
+        let status = cli.status().await?;
+        let Some(cluster_info) = status.cluster_info else {
+            continue;
+        };
+        let Some(chain) = cluster_info
+            .custom_chains
+            .values()
+            .find(|chain| chain.chain_name == args.chain_name)
+        else {
+            continue;
+        };
+        let mut node_urls: Vec<String> = cluster_info
+            .node_infos
+            .values()
+            .map(|node| node.uri.clone())
+            .collect();
+        node_urls.sort();
+        return Ok(Deployment {
+            vm_id,
+            subnet_id: ids::Id::from_str(&chain.subnet_id)?,
+            blockchain_id: ids::Id::from_str(&chain.chain_id)?,
+            node_urls,
+        });
+    }
+}
+
+/// 通过节点公开 API 提交 CreateSubnetTx 和 CreateChainTx
+async fn create_with_api(
+    args: &CreateArgs,
+    genesis: Vec<u8>,
+    vm_id: ids::Id,
+) -> Result<Deployment, Box<dyn std::error::Error>> {
+    let key = crate::validator::parse_key(args.key.as_deref().ok_or("需要 --key")?)?;
+    let wallet = wallet::Builder::new(&key)
+        .base_http_url(args.node_url.clone())
+        .build()
+        .await?;
+
+    let subnet_id = match &args.subnet_id {
+        Some(subnet_id) => ids::Id::from_str(subnet_id)?,
+        None => {
+            let subnet_id = wallet
+                .p()
+                .create_subnet()
+                .check_acceptance(true)
+                .issue()
+                .await?;
+            println!("CreateSubnetTx 已提交: {}", subnet_id);
+            subnet_id
+        }
+    };
+
+    let blockchain_id = wallet
+        .p()
+        .create_chain()
+        .subnet_id(subnet_id)
+        .genesis_data(genesis)
+        .vm_id(vm_id)
+        .chain_name(args.chain_name.clone())
+        .check_acceptance(true)
+        .issue()
+        .await?;
+    println!("CreateChainTx 已提交: {}", blockchain_id);
+    println!(
+        "各验证人需以 --track-subnets={} 启动，可用 validator add 登记验证人",
+        subnet_id
+    );
+
+    Ok(Deployment {
+        vm_id,
+        subnet_id,
+        blockchain_id,
+        node_urls: vec![args.node_url.clone()],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = dir.path().join("fairvm");
+        std::fs::write(&plugin, b"plugin").unwrap();
+        let vm_id = subnet::vm_name_to_id(DEFAULT_CHAIN_NAME).unwrap();
+
+        let plugin_dir = dir.path().join("avalanchego").join("plugins");
+        let target = install_plugin(&plugin, &plugin_dir, &vm_id).unwrap();
+        assert_eq!(target, plugin_dir.join(vm_id.to_string()));
+        assert_eq!(std::fs::read(target).unwrap(), b"plugin");
+        assert_eq!(
+            default_plugin_dir("/opt/avalanchego/avalanchego"),
+            PathBuf::from("/opt/avalanchego/plugins")
+        );
+    }
+
+    #[test]
+    fn test_rpc_url() {
+        let blockchain_id = ids::Id::from_slice(&[1; 32]);
+        assert_eq!(
+            Deployment::rpc_url("http://127.0.0.1:9650/", &blockchain_id),
+            format!("http://127.0.0.1:9650/ext/bc/{}/rpc", blockchain_id)
+        );
+    }
+
+    #[test]
+    fn test_read_genesis() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genesis.json");
+        std::fs::write(&path, serde_json::to_vec(&Genesis::default()).unwrap()).unwrap();
+        assert!(read_genesis(path.to_str().unwrap()).is_ok());
+        std::fs::write(&path, b"{}").unwrap();
+        assert!(read_genesis(path.to_str().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! P 链验证人登记：将节点加入 FairVM 子网的验证人集合
+//!
+//! 子网验证人必须同时是主网验证人，子网验证期也不能超出主网验证期。`add` 会先查询节点
+//! 是否已是主网验证人，必要时依次提交 AddValidatorTx 和 AddSubnetValidatorTx；
+//! 未通过参数给出的质押金额、天数和权重会在终端中询问。
+
+use avalanche_types::ids::{self, node};
+use avalanche_types::jsonrpc::client::{info, p};
+use avalanche_types::key::secp256k1::private_key::Key;
+use avalanche_types::wallet;
+use clap::{Args, Subcommand};
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+/// 1 AVAX 对应的 nAVAX 数
+pub const NANO_AVAX: u64 = 1_000_000_000;
+
+/// 主网网络 ID
+pub const MAINNET_ID: u32 = 1;
+
+/// Fuji 测试网网络 ID
+pub const FUJI_ID: u32 = 5;
+
+/// 默认的子网验证权重
+pub const DEFAULT_SUBNET_WEIGHT: u64 = 100;
+
+#[derive(Subcommand)]
+pub enum ValidatorCommands {
+    /// 将节点登记为子网验证人，必要时先加入主网验证人
+    Add(AddArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// 支付手续费的 P 链私钥，PrivateKey-... 或十六进制
+    #[arg(long)]
+    pub key: String,
+    /// AvalancheGo API 地址
+    #[arg(long, default_value = "http://127.0.0.1:9650")]
+    pub node_url: String,
+    /// 子网 ID
+    #[arg(long)]
+    pub subnet_id: String,
+    /// 节点 ID，默认使用 node_url 所指节点
+    #[arg(long)]
+    pub node_id: Option<String>,
+    /// 主网质押金额(AVAX)，节点还不是主网验证人时需要
+    #[arg(long)]
+    pub stake: Option<String>,
+    /// 验证天数
+    #[arg(long)]
+    pub days: Option<u64>,
+    /// 子网验证权重
+    #[arg(long)]
+    pub weight: Option<u64>,
+    /// 距开始验证的秒数，交易须在开始前被接受
+    #[arg(long, default_value_t = 60)]
+    pub start_delay: u64,
+    /// 不询问，未给出的参数使用默认值
+    #[arg(long)]
+    pub yes: bool,
+    /// 只构建交易，不提交
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// 网络的质押限制
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakingRules {
+    /// 最小主网质押(nAVAX)
+    pub min_stake: u64,
+    /// 最大主网质押(nAVAX)
+    pub max_stake: u64,
+    pub min_days: u64,
+    pub max_days: u64,
+}
+
+impl StakingRules {
+    /// 按网络 ID 取质押限制，本地网络与 Fuji 相同
+    pub fn for_network(network_id: u32) -> Self {
+        match network_id {
+            MAINNET_ID => Self {
+                min_stake: 2_000 * NANO_AVAX,
+                max_stake: 3_000_000 * NANO_AVAX,
+                min_days: 14,
+                max_days: 365,
+            },
+            _ => Self {
+                min_stake: NANO_AVAX,
+                max_stake: 3_000_000 * NANO_AVAX,
+                min_days: 1,
+                max_days: 365,
+            },
+        }
+    }
+
+    /// 检查质押金额
+    pub fn check_stake(&self, stake: u64) -> Result<(), String> {
+        if stake < self.min_stake || stake > self.max_stake {
+            return Err(format!(
+                "质押金额须在 {} 到 {} AVAX 之间",
+                format_avax(self.min_stake),
+                format_avax(self.max_stake)
+            ));
+        }
+        Ok(())
+    }
+
+    /// 检查验证天数
+    pub fn check_days(&self, days: u64) -> Result<(), String> {
+        if days < self.min_days || days > self.max_days {
+            return Err(format!(
+                "验证天数须在 {} 到 {} 天之间",
+                self.min_days, self.max_days
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 将 AVAX 金额解析为 nAVAX，最多 9 位小数
+pub fn parse_avax(s: &str) -> Result<u64, String> {
+    let invalid = || format!("无效的 AVAX 金额: {}", s);
+    let (whole, fraction) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+    if whole.is_empty() && fraction.is_empty() || fraction.len() > 9 {
+        return Err(invalid());
+    }
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| invalid())?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<9}", fraction).parse().map_err(|_| invalid())?
+    };
+    whole
+        .checked_mul(NANO_AVAX)
+        .and_then(|n| n.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+/// 将 nAVAX 格式化为 AVAX
+pub fn format_avax(amount: u64) -> String {
+    let fraction = amount % NANO_AVAX;
+    if fraction == 0 {
+        return (amount / NANO_AVAX).to_string();
+    }
+    let fraction = format!("{:09}", fraction);
+    format!("{}.{}", amount / NANO_AVAX, fraction.trim_end_matches('0'))
+}
+
+pub(crate) fn parse_key(key: &str) -> Result<Key, Box<dyn std::error::Error>> {
+    if key.starts_with("PrivateKey-") {
+        Ok(Key::from_cb58(key)?)
+    } else {
+        Ok(Key::from_hex(key.trim_start_matches("0x"))?)
+    }
+}
+
+/// 询问参数，直接回车使用默认值，`--yes` 时不询问
+fn prompt<T, F>(label: &str, default: String, yes: bool, parse: F) -> Result<T, String>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    if yes {
+        return parse(&default);
+    }
+    loop {
+        print!("{} [{}]: ", label, default);
+        std::io::stdout().flush().map_err(|e| e.to_string())?;
+        let mut answer = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(|e| e.to_string())?;
+        let answer = match answer.trim() {
+            "" => default.as_str(),
+            answer => answer,
+        };
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// 处理验证人命令
+pub async fn handle(cmd: ValidatorCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        ValidatorCommands::Add(args) => add(args).await,
+    }
+}
+
+async fn add(args: AddArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let subnet_id = ids::Id::from_str(&args.subnet_id)?;
+    let node_id = match &args.node_id {
+        Some(node_id) => node::Id::from_str(node_id)?,
+        None => {
+            info::get_node_id(&args.node_url)
+                .await?
+                .result
+                .ok_or("无法获取节点 ID")?
+                .node_id
+        }
+    };
+    let network_id = info::get_network_id(&args.node_url)
+        .await?
+        .result
+        .ok_or("无法获取网络 ID")?
+        .network_id;
+    let rules = StakingRules::for_network(network_id);
+    println!("网络 ID: {}", network_id);
+    println!("节点 ID: {}", node_id);
+    println!("子网 ID: {}", subnet_id);
+
+    let key = parse_key(&args.key)?;
+    let wallet = wallet::Builder::new(&key)
+        .base_http_url(args.node_url.clone())
+        .build()
+        .await?;
+    let balance = wallet.p().balance().await?;
+    println!("P 链地址: {}", wallet.p_address);
+    println!("P 链余额: {} AVAX", format_avax(balance));
+
+    let validators = p::get_primary_network_validators(&args.node_url)
+        .await?
+        .result
+        .and_then(|result| result.validators)
+        .unwrap_or_default();
+    let is_primary = validators.iter().any(|v| v.node_id == node_id);
+
+    let days = prompt(
+        "验证天数",
+        args.days.unwrap_or(rules.min_days).to_string(),
+        args.yes,
+        |s| {
+            let days = s.parse().map_err(|_| format!("无效的天数: {}", s))?;
+            rules.check_days(days).map(|_| days)
+        },
+    )?;
+
+    if is_primary {
+        println!("节点已是主网验证人，子网验证期须在主网验证期内");
+    } else {
+        let stake = prompt(
+            "主网质押金额(AVAX)",
+            args.stake.clone().unwrap_or(format_avax(rules.min_stake)),
+            args.yes,
+            |s| {
+                let stake = parse_avax(s)?;
+                rules.check_stake(stake).map(|_| stake)
+            },
+        )?;
+        if stake > balance {
+            return Err(format!("P 链余额不足，需要 {} AVAX", format_avax(stake)).into());
+        }
+
+        let (tx_id, added) = wallet
+            .p()
+            .add_validator()
+            .node_id(node_id)
+            .stake_amount(stake)
+            .validate_period_in_days(days, args.start_delay)
+            .check_acceptance(true)
+            .dry_mode(args.dry_run)
+            .issue()
+            .await?;
+        if added {
+            println!("AddValidatorTx 已提交: {}", tx_id);
+        }
+    }
+
+    let weight = prompt(
+        "子网验证权重",
+        args.weight.unwrap_or(DEFAULT_SUBNET_WEIGHT).to_string(),
+        args.yes,
+        |s| match s.parse::<u64>() {
+            Ok(weight) if weight > 0 => Ok(weight),
+            _ => Err(format!("无效的权重: {}", s)),
+        },
+    )?;
+
+    let (tx_id, added) = wallet
+        .p()
+        .add_subnet_validator()
+        .node_id(node_id)
+        .subnet_id(subnet_id)
+        .weight(weight)
+        .validate_period_in_days(days, args.start_delay)
+        .check_acceptance(true)
+        .dry_mode(args.dry_run)
+        .issue()
+        .await?;
+    if args.dry_run {
+        println!("交易已构建，未提交");
+    } else if added {
+        println!("AddSubnetValidatorTx 已提交: {}", tx_id);
+        println!(
+            "请在节点配置中加入 --track-subnets={} 并重启节点",
+            subnet_id
+        );
+    } else {
+        println!("节点已是子网 {} 的验证人", subnet_id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_avax() {
+        assert_eq!(parse_avax("2000").unwrap(), 2_000 * NANO_AVAX);
+        assert_eq!(parse_avax("1.5").unwrap(), 1_500_000_000);
+        assert_eq!(parse_avax(".000000001").unwrap(), 1);
+        assert!(parse_avax("1.0000000001").is_err());
+        assert!(parse_avax("abc").is_err());
+        assert!(parse_avax(".").is_err());
+        assert_eq!(format_avax(1_500_000_000), "1.5");
+        assert_eq!(format_avax(2_000 * NANO_AVAX), "2000");
+    }
+
+    #[test]
+    fn test_staking_rules() {
+        let mainnet = StakingRules::for_network(MAINNET_ID);
+        assert!(mainnet.check_stake(NANO_AVAX).is_err());
+        assert!(mainnet.check_stake(2_000 * NANO_AVAX).is_ok());
+        assert!(mainnet.check_days(7).is_err());
+        assert!(mainnet.check_days(14).is_ok());
+
+        let fuji = StakingRules::for_network(FUJI_ID);
+        assert!(fuji.check_stake(NANO_AVAX).is_ok());
+        assert!(fuji.check_days(1).is_ok());
+        assert!(fuji.check_days(366).is_err());
+    }
+}
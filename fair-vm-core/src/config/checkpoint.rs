@@ -0,0 +1,44 @@
+//! 检查点同步配置
+//!
+//! 新节点从可信数据源获取签名检查点（区块哈希和状态根），校验签名者属于可信列表后
+//! 直接从检查点开始运行，检查点之前的状态按需从数据源下载。
+
+use serde::{Deserialize, Serialize};
+
+/// 检查点同步配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointConfig {
+    /// 可信数据源的 JSON-RPC 地址
+    pub url: String,
+    /// 可信的检查点签名者地址（十六进制）
+    pub trusted_signers: Vec<String>,
+}
+
+impl CheckpointConfig {
+    /// 检查配置
+    pub fn validate(&self) -> Result<(), String> {
+        if self.url.is_empty() {
+            return Err("检查点数据源地址不能为空".to_string());
+        }
+        if self.trusted_signers.is_empty() {
+            return Err("至少需要一个可信的检查点签名者".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_config_validate() {
+        let mut config = CheckpointConfig {
+            url: "http://127.0.0.1:8545".to_string(),
+            trusted_signers: vec!["0x0101010101010101010101010101010101010101".to_string()],
+        };
+        assert!(config.validate().is_ok());
+        config.trusted_signers.clear();
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! 临时模式配置
+//!
+//! 用于 CI 和临时试验：链只保存在内存中，不向数据目录写入任何内容，停止后不留下状态，
+//! 避免上一次运行的残留导致结果不稳定。可设置运行时长，到期后节点自动停止。
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 临时模式配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EphemeralConfig {
+    /// 是否启用，默认关闭
+    pub enabled: bool,
+    /// 启动后自动停止的秒数，为空时一直运行
+    pub shutdown_after_secs: Option<u64>,
+}
+
+impl EphemeralConfig {
+    /// 检查配置，未启用时不检查
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.shutdown_after_secs == Some(0) {
+            return Err("自动停止时间必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// 自动停止前的运行时长，未启用临时模式时为空
+    pub fn shutdown_after(&self) -> Option<Duration> {
+        if !self.enabled {
+            return None;
+        }
+        self.shutdown_after_secs.map(Duration::from_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ephemeral_config() {
+        // 旧配置文件没有该字段时保持关闭
+        let mut config: EphemeralConfig = serde_json::from_str("{}").unwrap();
+        assert!(!config.enabled);
+        config.shutdown_after_secs = Some(0);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.shutdown_after(), None);
+
+        config.enabled = true;
+        assert!(config.validate().is_err());
+        config.shutdown_after_secs = Some(30);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.shutdown_after(), Some(Duration::from_secs(30)));
+    }
+}
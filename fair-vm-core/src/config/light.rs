@@ -0,0 +1,85 @@
+//! 轻节点配置
+//!
+//! 启用后 `node start` 不运行完整节点，只从全节点同步并校验区块头，关注的账户通过状态证明获取。
+//! 区块头链必须从可信锚点开始：`trusted_parent` 为 `start_block` 的父区块哈希，应从自己运行的
+//! 全节点或已签名的检查点取得，否则数据源可以伪造整条区块头链。
+
+use serde::{Deserialize, Serialize};
+
+/// 轻节点配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LightConfig {
+    /// 是否以轻节点运行，默认关闭
+    pub enabled: bool,
+    /// 全节点的 JSON-RPC 地址
+    pub source_url: String,
+    /// 第一个同步的区块高度
+    pub start_block: u64,
+    /// 可信锚点：`start_block` 的父区块哈希（十六进制）
+    pub trusted_parent: Option<String>,
+    /// 关注的账户地址（十六进制）
+    pub watched: Vec<String>,
+    /// 同步间隔（毫秒）
+    pub interval_ms: u64,
+}
+
+impl Default for LightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source_url: String::new(),
+            start_block: 1,
+            trusted_parent: None,
+            watched: Vec::new(),
+            interval_ms: 2000,
+        }
+    }
+}
+
+impl LightConfig {
+    /// 检查配置，未启用时不检查
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.source_url.is_empty() {
+            return Err("轻节点数据源地址不能为空".to_string());
+        }
+        if self.start_block == 0 {
+            return Err("轻节点起始区块高度必须大于 0".to_string());
+        }
+        let Some(anchor) = &self.trusted_parent else {
+            return Err("轻节点必须配置可信锚点 trusted_parent".to_string());
+        };
+        let hex = anchor.trim().trim_start_matches("0x");
+        if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("可信锚点不是有效的区块哈希: {}", anchor));
+        }
+        if self.interval_ms == 0 {
+            return Err("同步间隔必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_config_validate() {
+        // 旧配置文件没有该字段时保持关闭
+        let mut config: LightConfig = serde_json::from_str("{}").unwrap();
+        assert!(!config.enabled);
+        assert!(config.validate().is_ok());
+
+        config.enabled = true;
+        config.source_url = "http://127.0.0.1:8545".to_string();
+        assert!(config.validate().is_err());
+        config.trusted_parent = Some("0x1234".to_string());
+        assert!(config.validate().is_err());
+        config.trusted_parent = Some(format!("0x{}", "ab".repeat(32)));
+        assert!(config.validate().is_ok());
+    }
+}
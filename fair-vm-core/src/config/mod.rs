@@ -1,6 +1,26 @@
+use crate::params::ChainConfig;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+mod checkpoint;
+mod ephemeral;
+mod light;
+mod producer;
+mod resources;
+mod rpc;
+mod telemetry;
+
+pub use checkpoint::CheckpointConfig;
+pub use ephemeral::EphemeralConfig;
+pub use light::LightConfig;
+pub use producer::BlockProducerConfig;
+pub use resources::ResourceConfig;
+pub use rpc::{
+    method_namespace, IpcConfig, RpcCacheConfig, RpcConfig, TransportConfig, DEFAULT_IPC_FILE,
+    PUBLIC_NAMESPACES, RPC_NAMESPACES,
+};
+pub use telemetry::TelemetryConfig;
+
 /// 配置类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -34,6 +54,53 @@ pub struct Config {
     pub log_level: String,
     /// 日志文件
     pub log_file: Option<PathBuf>,
+    /// 链参数
+    #[serde(default)]
+    pub chain_config: ChainConfig,
+    /// 签名验证线程数，0 表示使用 CPU 核数
+    #[serde(default)]
+    pub verification_threads: usize,
+    /// 相邻区块的最小时间间隔（秒）
+    #[serde(default)]
+    pub min_block_interval: u64,
+    /// 区块时间戳允许超前本地时钟的最大秒数
+    #[serde(default = "default_max_timestamp_drift")]
+    pub max_timestamp_drift: u64,
+    /// 本地未收录的函数选择器向该签名目录查询，为空时只使用内置签名
+    #[serde(default)]
+    pub selector_lookup_url: Option<String>,
+    /// RPC 传输配置
+    #[serde(default)]
+    pub rpc: RpcConfig,
+    /// 检查点同步配置，为空时从创世区块开始同步
+    #[serde(default)]
+    pub checkpoint: Option<CheckpointConfig>,
+    /// 启动时自动迁移数据目录格式，关闭后需要迁移时拒绝启动
+    #[serde(default = "default_auto_migrate")]
+    pub auto_migrate: bool,
+    /// 匿名遥测上报，默认关闭
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    /// 内存水位和文件描述符限制
+    #[serde(default)]
+    pub resources: ResourceConfig,
+    /// 自动出块，默认关闭
+    #[serde(default)]
+    pub block_producer: BlockProducerConfig,
+    /// 临时模式，默认关闭
+    #[serde(default)]
+    pub ephemeral: EphemeralConfig,
+    /// 轻节点模式，默认关闭
+    #[serde(default)]
+    pub light: LightConfig,
+}
+
+fn default_auto_migrate() -> bool {
+    true
+}
+
+fn default_max_timestamp_drift() -> u64 {
+    15
 }
 
 impl Default for Config {
@@ -54,6 +121,19 @@ impl Default for Config {
             block_pool_size: 100,
             log_level: "info".to_string(),
             log_file: None,
+            chain_config: ChainConfig::default(),
+            verification_threads: 0,
+            min_block_interval: 0,
+            max_timestamp_drift: default_max_timestamp_drift(),
+            selector_lookup_url: None,
+            rpc: RpcConfig::default(),
+            checkpoint: None,
+            auto_migrate: default_auto_migrate(),
+            telemetry: TelemetryConfig::default(),
+            resources: ResourceConfig::default(),
+            block_producer: BlockProducerConfig::default(),
+            ephemeral: EphemeralConfig::default(),
+            light: LightConfig::default(),
         }
     }
 }
@@ -166,6 +246,36 @@ impl Config {
     pub fn set_log_file(&mut self, log_file: Option<PathBuf>) {
         self.log_file = log_file;
     }
+
+    /// 设置链参数
+    pub fn set_chain_config(&mut self, chain_config: ChainConfig) {
+        self.chain_config = chain_config;
+    }
+
+    /// 设置签名验证线程数
+    pub fn set_verification_threads(&mut self, verification_threads: usize) {
+        self.verification_threads = verification_threads;
+    }
+
+    /// 设置相邻区块的最小时间间隔
+    pub fn set_min_block_interval(&mut self, min_block_interval: u64) {
+        self.min_block_interval = min_block_interval;
+    }
+
+    /// 设置区块时间戳允许超前本地时钟的最大秒数
+    pub fn set_max_timestamp_drift(&mut self, max_timestamp_drift: u64) {
+        self.max_timestamp_drift = max_timestamp_drift;
+    }
+
+    /// 设置 RPC 传输配置
+    pub fn set_rpc_config(&mut self, rpc: RpcConfig) {
+        self.rpc = rpc;
+    }
+
+    /// 检查 RPC 传输配置
+    pub fn validate_rpc(&self) -> Result<(), String> {
+        self.rpc.validate(self.port)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,68 @@
+//! 出块配置
+//!
+//! 默认关闭，由共识层驱动出块的节点无需启用。启用后节点按固定间隔从交易池打包交易并提交给共识引擎。
+
+use serde::{Deserialize, Serialize};
+
+/// 出块配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockProducerConfig {
+    /// 是否自动出块，默认关闭
+    pub enabled: bool,
+    /// 出块间隔（毫秒）
+    pub interval_ms: u64,
+    /// 单个区块最多打包的交易数
+    pub max_transactions: usize,
+    /// 交易池没有可执行交易时是否跳过本轮出块
+    pub skip_empty: bool,
+    /// 记录在区块中的出块者节点 ID，如 `NodeID-...`
+    pub proposer: Option<String>,
+}
+
+impl Default for BlockProducerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 2000,
+            max_transactions: 1000,
+            skip_empty: true,
+            proposer: None,
+        }
+    }
+}
+
+impl BlockProducerConfig {
+    /// 检查配置，未启用时不检查
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.interval_ms == 0 {
+            return Err("出块间隔必须大于 0".to_string());
+        }
+        if self.max_transactions == 0 {
+            return Err("单个区块的交易数上限必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_producer_config_validate() {
+        // 旧配置文件没有该字段时保持关闭
+        let mut config: BlockProducerConfig = serde_json::from_str("{}").unwrap();
+        assert!(!config.enabled);
+        config.interval_ms = 0;
+        assert!(config.validate().is_ok());
+
+        config.enabled = true;
+        assert!(config.validate().is_err());
+        config.interval_ms = 500;
+        assert!(config.validate().is_ok());
+    }
+}
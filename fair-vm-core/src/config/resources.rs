@@ -0,0 +1,73 @@
+//! 资源限制配置
+//!
+//! 内存水位按常驻内存（RSS）判断：超过软水位时收缩缓存和交易池，超过硬水位时清空可丢弃
+//! 的数据。启动时检查进程可打开的文件数，低于要求时拒绝启动并给出调整方法。
+
+use serde::{Deserialize, Serialize};
+
+/// 资源限制配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceConfig {
+    /// 内存软水位（MiB），0 表示不检查
+    pub memory_soft_limit_mb: u64,
+    /// 内存硬水位（MiB），0 表示不检查
+    pub memory_hard_limit_mb: u64,
+    /// 内存检查间隔（秒）
+    pub check_interval_secs: u64,
+    /// 启动时要求的最小文件描述符数，0 表示不检查
+    pub min_open_files: u64,
+}
+
+impl Default for ResourceConfig {
+    fn default() -> Self {
+        Self {
+            memory_soft_limit_mb: 0,
+            memory_hard_limit_mb: 0,
+            check_interval_secs: 10,
+            min_open_files: 1024,
+        }
+    }
+}
+
+impl ResourceConfig {
+    /// 是否配置了内存水位
+    pub fn memory_guard_enabled(&self) -> bool {
+        self.memory_soft_limit_mb > 0 || self.memory_hard_limit_mb > 0
+    }
+
+    /// 检查配置
+    pub fn validate(&self) -> Result<(), String> {
+        if self.memory_guard_enabled() && self.check_interval_secs == 0 {
+            return Err("内存检查间隔必须大于 0".to_string());
+        }
+        if self.memory_soft_limit_mb > 0
+            && self.memory_hard_limit_mb > 0
+            && self.memory_soft_limit_mb >= self.memory_hard_limit_mb
+        {
+            return Err(format!(
+                "内存软水位 {} MiB 必须低于硬水位 {} MiB",
+                self.memory_soft_limit_mb, self.memory_hard_limit_mb
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_config_validate() {
+        let mut config = ResourceConfig::default();
+        assert!(!config.memory_guard_enabled());
+        assert!(config.validate().is_ok());
+
+        config.memory_soft_limit_mb = 4096;
+        config.memory_hard_limit_mb = 2048;
+        assert!(config.validate().is_err());
+        config.memory_hard_limit_mb = 6144;
+        assert!(config.validate().is_ok());
+    }
+}
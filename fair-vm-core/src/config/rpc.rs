@@ -0,0 +1,259 @@
+//! RPC 传输配置
+//!
+//! HTTP、WebSocket 和 IPC 端点分别配置是否启用、监听地址和开放的命名空间，
+//! 便于对内对外暴露不同的接口，例如只在本机 IPC 上开放 `debug` 和 `admin` 命名空间。
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 已知的 RPC 命名空间
+pub const RPC_NAMESPACES: &[&str] = &[
+    "eth", "chain", "fairvm", "static", "wallet", "debug", "admin",
+];
+
+/// 对外传输默认开放的命名空间
+pub const PUBLIC_NAMESPACES: &[&str] = &["eth", "chain", "fairvm", "static", "wallet"];
+
+/// 默认的 IPC 套接字文件名，相对路径基于数据目录
+pub const DEFAULT_IPC_FILE: &str = "fairvm.ipc";
+
+/// 方法名所属的命名空间，即第一个下划线之前的部分
+pub fn method_namespace(method: &str) -> &str {
+    method.split('_').next().unwrap_or(method)
+}
+
+fn namespaces(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn check_namespaces(transport: &str, names: &[String]) -> Result<(), String> {
+    if names.is_empty() {
+        return Err(format!("{} 已启用但没有开放任何命名空间", transport));
+    }
+    match names
+        .iter()
+        .find(|name| !RPC_NAMESPACES.contains(&name.as_str()))
+    {
+        Some(name) => Err(format!("{} 配置了未知的命名空间 {}", transport, name)),
+        None => Ok(()),
+    }
+}
+
+/// TCP 传输（HTTP 或 WebSocket）配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 监听地址
+    pub bind: String,
+    /// 监听端口
+    pub port: u16,
+    /// 开放的命名空间
+    pub namespaces: Vec<String>,
+}
+
+impl TransportConfig {
+    fn local(enabled: bool, port: u16) -> Self {
+        Self {
+            enabled,
+            bind: "127.0.0.1".to_string(),
+            port,
+            namespaces: namespaces(PUBLIC_NAMESPACES),
+        }
+    }
+
+    /// 监听地址和端口
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.bind, self.port)
+    }
+
+    /// 是否开放方法所属的命名空间
+    pub fn allows(&self, method: &str) -> bool {
+        let namespace = method_namespace(method);
+        self.namespaces.iter().any(|name| name == namespace)
+    }
+}
+
+/// IPC（Unix 域套接字）传输配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpcConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 套接字路径，相对路径基于数据目录
+    pub path: PathBuf,
+    /// 开放的命名空间
+    pub namespaces: Vec<String>,
+}
+
+impl Default for IpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from(DEFAULT_IPC_FILE),
+            namespaces: namespaces(RPC_NAMESPACES),
+        }
+    }
+}
+
+impl IpcConfig {
+    /// 套接字的实际路径
+    pub fn socket_path(&self, data_dir: &Path) -> PathBuf {
+        if self.path.is_absolute() {
+            self.path.clone()
+        } else {
+            data_dir.join(&self.path)
+        }
+    }
+
+    /// 是否开放方法所属的命名空间
+    pub fn allows(&self, method: &str) -> bool {
+        let namespace = method_namespace(method);
+        self.namespaces.iter().any(|name| name == namespace)
+    }
+}
+
+/// 不可变查询（已接受的区块、交易和收据）的响应缓存配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcCacheConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 最多缓存的响应数
+    pub max_entries: usize,
+    /// 缓存响应的总字节数上限
+    pub max_bytes: usize,
+    /// 响应的有效期（秒），0 表示不过期
+    pub ttl_secs: u64,
+}
+
+impl Default for RpcCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 10_000,
+            max_bytes: 64 * 1024 * 1024,
+            ttl_secs: 600,
+        }
+    }
+}
+
+/// RPC 传输配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    /// HTTP 端点
+    pub http: TransportConfig,
+    /// WebSocket 端点
+    pub ws: TransportConfig,
+    /// IPC 端点
+    pub ipc: IpcConfig,
+    /// 响应缓存
+    pub cache: RpcCacheConfig,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            http: TransportConfig::local(true, 9545),
+            ws: TransportConfig::local(false, 9546),
+            ipc: IpcConfig::default(),
+            cache: RpcCacheConfig::default(),
+        }
+    }
+}
+
+impl RpcConfig {
+    /// 检查已启用传输的命名空间和端口，`p2p_port` 为节点网络端口
+    pub fn validate(&self, p2p_port: u16) -> Result<(), String> {
+        let mut ports = Vec::new();
+        for (name, transport) in [("HTTP", &self.http), ("WebSocket", &self.ws)] {
+            if !transport.enabled {
+                continue;
+            }
+            check_namespaces(name, &transport.namespaces)?;
+            if transport.bind.is_empty() {
+                return Err(format!("{} 监听地址不能为空", name));
+            }
+            if transport.port == p2p_port {
+                return Err(format!("{} 端口 {} 与网络端口冲突", name, transport.port));
+            }
+            if ports.contains(&transport.port) {
+                return Err(format!("{} 端口 {} 已被其他传输占用", name, transport.port));
+            }
+            ports.push(transport.port);
+        }
+        if self.ipc.enabled {
+            check_namespaces("IPC", &self.ipc.namespaces)?;
+            if self.ipc.path.as_os_str().is_empty() {
+                return Err("IPC 套接字路径不能为空".to_string());
+            }
+        }
+        if self.cache.enabled && (self.cache.max_entries == 0 || self.cache.max_bytes == 0) {
+            return Err("响应缓存的条目数和字节数上限必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_config_default() {
+        let config = RpcConfig::default();
+        assert!(config.validate(8545).is_ok());
+        assert!(config.http.enabled);
+        assert!(!config.ws.enabled);
+        assert!(!config.ipc.enabled);
+        assert_eq!(config.http.addr(), "127.0.0.1:9545");
+
+        assert!(config.http.allows("eth_chainId"));
+        assert!(!config.http.allows("debug_startSession"));
+        assert!(config.ipc.allows("debug_startSession"));
+        assert!(!config.http.allows("admin_collectDebugBundle"));
+        assert!(config.ipc.allows("admin_collectDebugBundle"));
+        assert_eq!(
+            config.ipc.socket_path(Path::new("data")),
+            PathBuf::from("data").join(DEFAULT_IPC_FILE)
+        );
+    }
+
+    #[test]
+    fn test_rpc_config_validate() {
+        let mut config = RpcConfig::default();
+        config.ws.enabled = true;
+        config.ws.port = config.http.port;
+        assert!(config.validate(8545).is_err());
+
+        config.ws.port = 8545;
+        assert!(config.validate(8545).is_err());
+
+        config.ws.port = 9546;
+        config.ws.namespaces = vec!["admin".to_string()];
+        assert!(config.validate(8545).is_err());
+
+        config.ws.namespaces.clear();
+        assert!(config.validate(8545).is_err());
+
+        // 未启用的传输不检查
+        config.ws.enabled = false;
+        assert!(config.validate(8545).is_ok());
+
+        config.cache.max_entries = 0;
+        assert!(config.validate(8545).is_err());
+        config.cache.enabled = false;
+        assert!(config.validate(8545).is_ok());
+    }
+
+    #[test]
+    fn test_rpc_config_partial_json() {
+        let config: RpcConfig = serde_json::from_str(
+            r#"{"ws": {"enabled": true, "bind": "0.0.0.0", "port": 8600, "namespaces": ["eth"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.http, RpcConfig::default().http);
+        assert_eq!(config.ws.addr(), "0.0.0.0:8600");
+        assert!(config.validate(8545).is_ok());
+    }
+}
@@ -0,0 +1,71 @@
+//! 遥测上报配置
+//!
+//! 默认关闭，只有显式设置 `enabled` 和上报地址后节点才会定期上报匿名的运行概况。
+//! 上报内容见 `fair_vm::telemetry::TelemetryReport`。
+
+use serde::{Deserialize, Serialize};
+
+/// 遥测上报配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// 是否上报，默认关闭
+    #[serde(default)]
+    pub enabled: bool,
+    /// 上报地址，以 JSON POST 发送
+    #[serde(default)]
+    pub endpoint: String,
+    /// 上报间隔（秒）
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_interval_secs() -> u64 {
+    3600
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: String::new(),
+            interval_secs: default_interval_secs(),
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /// 检查配置，未启用时不检查
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.endpoint.is_empty() {
+            return Err("已启用遥测但没有配置上报地址".to_string());
+        }
+        if self.interval_secs == 0 {
+            return Err("遥测上报间隔必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telemetry_config_opt_in() {
+        // 旧配置文件没有该字段时保持关闭
+        let config: TelemetryConfig = serde_json::from_str("{}").unwrap();
+        assert!(!config.enabled);
+        assert!(config.validate().is_ok());
+
+        let mut config = TelemetryConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        config.endpoint = "https://telemetry.example.com/v1/report".to_string();
+        assert!(config.validate().is_ok());
+    }
+}
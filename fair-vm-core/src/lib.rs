@@ -3,6 +3,8 @@ pub mod config;
 pub mod logger;
 pub mod network;
 pub mod params;
+pub mod rng;
+pub mod serde_hex;
 pub mod state;
 pub mod types;
 pub mod vm;
@@ -16,4 +18,4 @@ pub use types::{Address, Hash, Header, Log, Receipt, Transaction};
 
 pub use blockchain::*;
 pub use config::*;
-pub use vm::{ExecutionContext, ExecutionResult, State, Vm};
+pub use vm::{ExecutionContext, ExecutionResult, RevertReason, State, Vm};
@@ -1,10 +1,34 @@
 use chrono::Local;
 use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+/// 内存中保留的最近日志行数，供崩溃报告使用
+pub const RECENT_LOG_CAPACITY: usize = 1000;
+
+/// 最近的日志行，超过容量时丢弃最早的
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+fn remember(line: &str) {
+    if let Ok(mut logs) = RECENT_LOGS.lock() {
+        if logs.len() == RECENT_LOG_CAPACITY {
+            logs.pop_front();
+        }
+        logs.push_back(line.to_string());
+    }
+}
+
+/// 最近输出的日志，按时间先后排列
+pub fn recent_logs() -> Vec<String> {
+    RECENT_LOGS
+        .lock()
+        .map(|logs| logs.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
 /// 日志记录器
 pub struct Logger {
     /// 日志级别
@@ -46,6 +70,7 @@ impl Log for Logger {
         if self.enabled(record.metadata()) {
             let log = self.format_log(record);
             println!("{}", log);
+            remember(&log);
 
             if let Some(file) = &self.file {
                 if let Ok(mut file) = file.lock() {
@@ -134,6 +159,25 @@ mod tests {
         assert_eq!(get_level(), Level::Info);
     }
 
+    #[test]
+    fn test_recent_logs() {
+        let logger = Logger::new(LevelFilter::Info, None).unwrap();
+        for i in 0..=RECENT_LOG_CAPACITY {
+            logger.log(
+                &log::Record::builder()
+                    .level(Level::Info)
+                    .target("recent")
+                    .args(format_args!("line {}", i))
+                    .build(),
+            );
+        }
+        let logs = recent_logs();
+        assert_eq!(logs.len(), RECENT_LOG_CAPACITY);
+        let last = format!("line {}", RECENT_LOG_CAPACITY);
+        assert!(logs.iter().any(|line| line.ends_with(&last)));
+        assert!(!logs.iter().any(|line| line.ends_with(" line 0")));
+    }
+
     #[test]
     fn test_set_level() {
         set_level(Level::Debug);
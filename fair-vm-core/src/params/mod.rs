@@ -1,17 +1,144 @@
-use primitive_types::U256;
+use serde::{Deserialize, Serialize};
 
+/// 链参数
+///
+/// 链 ID 与各硬分叉的激活区块，节点配置、创世配置和执行器都以此为准。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ChainConfig {
-    pub chain_id: U256,
-    pub homestead_block: U256,
-    pub eip150_block: U256,
-    pub eip155_block: U256,
-    pub eip158_block: U256,
-    pub byzantium_block: U256,
-    pub constantinople_block: U256,
-    pub petersburg_block: U256,
-    pub istanbul_block: U256,
-    pub muir_glacier_block: U256,
-    pub berlin_block: U256,
-    pub london_block: U256,
+    pub chain_id: u64,
+    pub homestead_block: u64,
+    pub eip150_block: u64,
+    pub eip155_block: u64,
+    pub eip158_block: u64,
+    pub byzantium_block: u64,
+    pub constantinople_block: u64,
+    pub petersburg_block: u64,
+    pub istanbul_block: u64,
+    pub muir_glacier_block: u64,
+    pub berlin_block: u64,
+    pub london_block: u64,
+    /// 起区块头须承诺交易根、收据根和状态根的区块，为空时沿用旧规则，为零的根视为未承诺
+    #[serde(default)]
+    pub root_commitment_block: Option<u64>,
     // ... 可根据需要继续扩展
 }
+
+impl Default for ChainConfig {
+    /// 链 ID 为 1，全部硬分叉自创世区块起激活
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl ChainConfig {
+    /// 创建全部硬分叉自创世区块起激活的链参数
+    pub fn new(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            homestead_block: 0,
+            eip150_block: 0,
+            eip155_block: 0,
+            eip158_block: 0,
+            byzantium_block: 0,
+            constantinople_block: 0,
+            petersburg_block: 0,
+            istanbul_block: 0,
+            muir_glacier_block: 0,
+            berlin_block: 0,
+            london_block: 0,
+            root_commitment_block: None,
+        }
+    }
+
+    /// 检查硬分叉激活区块按顺序排列
+    pub fn validate(&self) -> Result<(), String> {
+        let forks = [
+            ("homestead", self.homestead_block),
+            ("eip150", self.eip150_block),
+            ("eip155", self.eip155_block),
+            ("eip158", self.eip158_block),
+            ("byzantium", self.byzantium_block),
+            ("constantinople", self.constantinople_block),
+            ("petersburg", self.petersburg_block),
+            ("istanbul", self.istanbul_block),
+            ("muirGlacier", self.muir_glacier_block),
+            ("berlin", self.berlin_block),
+            ("london", self.london_block),
+        ];
+        for pair in forks.windows(2) {
+            if pair[1].1 < pair[0].1 {
+                return Err(format!(
+                    "硬分叉 {} 的激活区块 {} 早于 {} 的激活区块 {}",
+                    pair[1].0, pair[1].1, pair[0].0, pair[0].1
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 是否启用 EIP-155 重放保护
+    pub fn is_eip155(&self, block_number: u64) -> bool {
+        block_number >= self.eip155_block
+    }
+
+    /// 是否已激活 Berlin
+    pub fn is_berlin(&self, block_number: u64) -> bool {
+        block_number >= self.berlin_block
+    }
+
+    /// 是否已激活 London
+    pub fn is_london(&self, block_number: u64) -> bool {
+        block_number >= self.london_block
+    }
+
+    /// 区块头是否须承诺全部根，创世区块不要求
+    pub fn requires_roots(&self, block_number: u64) -> bool {
+        block_number > 0
+            && self
+                .root_commitment_block
+                .is_some_and(|activation| block_number >= activation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fork_activation() {
+        let config = ChainConfig {
+            berlin_block: 10,
+            london_block: 20,
+            ..ChainConfig::new(2024)
+        };
+        assert!(config.validate().is_ok());
+        assert!(config.is_eip155(0));
+        assert!(!config.is_berlin(9));
+        assert!(config.is_berlin(10));
+        assert!(!config.is_london(19));
+        assert!(config.is_london(20));
+        assert!(!config.requires_roots(20));
+
+        let config = ChainConfig {
+            root_commitment_block: Some(0),
+            ..config
+        };
+        assert!(!config.requires_roots(0));
+        assert!(config.requires_roots(1));
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["chainId"], 2024);
+        assert_eq!(json["londonBlock"], 20);
+    }
+
+    #[test]
+    fn test_validate_fork_order() {
+        let config = ChainConfig {
+            berlin_block: 30,
+            london_block: 20,
+            ..ChainConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! 十六进制编码
+//!
+//! JSON 中的字节数据统一编码为 `0x` 前缀的小写十六进制字符串，与以太坊 JSON-RPC 一致。
+//! 反序列化同时接受旧版本写出的字节数组，已落盘的数据无需转换。
+//!
+//! 地址另有 EIP-55 校验和格式：字母的大小写由地址哈希决定，解析用户输入的地址时用
+//! [`decode_address`] 校验，抄错一位的地址会被拒绝。
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserializer, Serializer};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use thiserror::Error;
+
+/// 十六进制解析错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HexError {
+    #[error("无效的十六进制: {0}")]
+    InvalidHex(#[from] hex::FromHexError),
+    #[error("长度错误: 需要 {expected} 字节，实际 {found} 字节")]
+    InvalidLength { expected: usize, found: usize },
+    #[error("地址校验和不匹配: {0}")]
+    ChecksumMismatch(String),
+}
+
+/// 编码为 `0x` 前缀的十六进制字符串
+pub fn encode(bytes: impl AsRef<[u8]>) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// 解码十六进制字符串，`0x` 前缀可省略
+pub fn decode(s: &str) -> Result<Vec<u8>, HexError> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    Ok(hex::decode(digits)?)
+}
+
+/// 解码定长十六进制字符串
+pub fn decode_array<const N: usize>(s: &str) -> Result<[u8; N], HexError> {
+    let bytes = decode(s)?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| HexError::InvalidLength {
+            expected: N,
+            found: bytes.len(),
+        })
+}
+
+/// EIP-55 校验和地址
+///
+/// 小写十六进制地址的 keccak256 哈希中，对应半字节不小于 8 的字母改为大写。
+pub fn to_checksum(address: &[u8; 20]) -> String {
+    let digits = hex::encode(address);
+    let hash = Keccak256::digest(digits.as_bytes());
+    let checksummed: String = digits
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+/// 十六进制字母是否大小写混合，只有混合时才带有校验和
+pub fn is_mixed_case(s: &str) -> bool {
+    s.chars().any(|c| c.is_ascii_uppercase()) && s.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// 解析地址，大小写混合时校验 EIP-55 校验和
+///
+/// 全小写或全大写的地址不带校验和，按普通十六进制解析。
+pub fn decode_address(s: &str) -> Result<[u8; 20], HexError> {
+    let address = decode_array(s)?;
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    if is_mixed_case(digits) && to_checksum(&address)[2..] != *digits {
+        return Err(HexError::ChecksumMismatch(s.to_string()));
+    }
+    Ok(address)
+}
+
+/// 十六进制字符串或旧版字节数组
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("0x 前缀的十六进制字符串")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        decode(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+/// 用于 `#[serde(with = "serde_hex::bytes")]` 的变长字节
+pub mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        deserializer.deserialize_any(BytesVisitor)
+    }
+}
+
+/// 用于 `#[serde(with = "serde_hex::array")]` 的定长字节
+pub mod array {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        bytes: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let bytes = deserializer.deserialize_any(BytesVisitor)?;
+        let found = bytes.len();
+        bytes
+            .try_into()
+            .map_err(|_| de::Error::custom(HexError::InvalidLength { expected: N, found }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        #[serde(with = "bytes")]
+        data: Vec<u8>,
+        #[serde(with = "array")]
+        key: [u8; 4],
+    }
+
+    #[test]
+    fn test_round_trip_and_legacy() {
+        let sample = Sample {
+            data: vec![0xde, 0xad],
+            key: [0, 1, 2, 3],
+        };
+        let json = serde_json::to_string(&sample).unwrap();
+        assert_eq!(json, r#"{"data":"0xdead","key":"0x00010203"}"#);
+        assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), sample);
+
+        let legacy = r#"{"data":[222,173],"key":[0,1,2,3]}"#;
+        assert_eq!(serde_json::from_str::<Sample>(legacy).unwrap(), sample);
+        assert!(serde_json::from_str::<Sample>(r#"{"data":"0x","key":"0x00"}"#).is_err());
+
+        assert_eq!(decode("DEAD").unwrap(), vec![0xde, 0xad]);
+        assert_eq!(
+            decode_array::<2>("0x00"),
+            Err(HexError::InvalidLength {
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_checksum() {
+        // EIP-55 中的示例地址
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        ] {
+            let address = decode_address(checksummed).unwrap();
+            assert_eq!(to_checksum(&address), checksummed);
+            assert_eq!(
+                decode_address(&checksummed.to_lowercase()).unwrap(),
+                address
+            );
+            assert_eq!(
+                decode_address(&format!("0x{}", checksummed[2..].to_uppercase())).unwrap(),
+                address
+            );
+        }
+        assert_eq!(
+            decode_address("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            Err(HexError::ChecksumMismatch(
+                "0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string()
+            ))
+        );
+    }
+}
@@ -1,7 +1,9 @@
+use crate::rng::{self, RngCore};
+use crate::serde_hex::{self, HexError};
 use primitive_types::{H160, H256, U256};
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
 /// 地址类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -10,9 +12,13 @@ pub struct Address(pub H160);
 impl Address {
     /// 创建随机地址
     pub fn random() -> Self {
-        let mut rng = rand::thread_rng();
+        rng::with_rng(|rng| Self::random_with(rng))
+    }
+
+    /// 用指定的随机数源创建随机地址
+    pub fn random_with<R: RngCore + ?Sized>(rng: &mut R) -> Self {
         let mut bytes = [0u8; 20];
-        rng.fill(&mut bytes);
+        rng.fill_bytes(&mut bytes);
         Self(H160(bytes))
     }
 
@@ -25,6 +31,11 @@ impl Address {
     pub fn as_bytes(&self) -> &[u8; 20] {
         &self.0 .0
     }
+
+    /// EIP-55 校验和十六进制
+    pub fn to_checksum(&self) -> String {
+        serde_hex::to_checksum(self.as_bytes())
+    }
 }
 
 impl fmt::Display for Address {
@@ -33,6 +44,15 @@ impl fmt::Display for Address {
     }
 }
 
+impl FromStr for Address {
+    type Err = HexError;
+
+    /// 解析十六进制地址，大小写混合时校验 EIP-55 校验和
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_hex::decode_address(s).map(Self::from_bytes)
+    }
+}
+
 /// 哈希类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Hash(pub H256);
@@ -40,9 +60,13 @@ pub struct Hash(pub H256);
 impl Hash {
     /// 创建随机哈希
     pub fn random() -> Self {
-        let mut rng = rand::thread_rng();
+        rng::with_rng(|rng| Self::random_with(rng))
+    }
+
+    /// 用指定的随机数源创建随机哈希
+    pub fn random_with<R: RngCore + ?Sized>(rng: &mut R) -> Self {
         let mut bytes = [0u8; 32];
-        rng.fill(&mut bytes);
+        rng.fill_bytes(&mut bytes);
         Self(H256(bytes))
     }
 
@@ -63,6 +87,14 @@ impl fmt::Display for Hash {
     }
 }
 
+impl FromStr for Hash {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_hex::decode_array(s).map(Self::from_bytes)
+    }
+}
+
 /// 区块头
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Header {
@@ -113,6 +145,16 @@ impl Header {
     }
 }
 
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} (parent {}, timestamp {})",
+            self.number, self.hash, self.parent_hash, self.timestamp
+        )
+    }
+}
+
 /// 交易
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -123,6 +165,7 @@ pub struct Transaction {
     /// 交易值
     pub value: U256,
     /// 交易数据
+    #[serde(with = "serde_hex::bytes")]
     pub data: Vec<u8>,
     /// nonce
     pub nonce: u64,
@@ -167,6 +210,17 @@ impl Transaction {
     }
 }
 
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} -> ", self.hash, self.from)?;
+        match &self.to {
+            Some(to) => write!(f, "{}", to)?,
+            None => f.write_str("contract creation")?,
+        }
+        write!(f, " value {} nonce {}", self.value, self.nonce)
+    }
+}
+
 /// 交易收据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Receipt {
@@ -198,6 +252,7 @@ pub struct Log {
     /// 主题
     pub topics: Vec<Hash>,
     /// 数据
+    #[serde(with = "serde_hex::bytes")]
     pub data: Vec<u8>,
 }
 
@@ -257,4 +312,42 @@ mod tests {
         assert_eq!(tx.gas_price, gas_price);
         assert_eq!(tx.gas_limit, gas_limit);
     }
+
+    #[test]
+    fn test_hex_json() {
+        let from: Address = "0x0101010101010101010101010101010101010101"
+            .parse()
+            .unwrap();
+        assert_eq!(from, Address::from_bytes([1u8; 20]));
+        assert_eq!(from.to_string().parse::<Address>().unwrap(), from);
+        let hash = Hash::random();
+        assert_eq!(hash.to_string().parse::<Hash>().unwrap(), hash);
+        assert!("0x01".parse::<Address>().is_err());
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        assert_eq!(
+            checksummed.parse::<Address>().unwrap().to_checksum(),
+            checksummed
+        );
+        assert!("0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+            .parse::<Address>()
+            .is_err());
+
+        let tx = Transaction::new(
+            from,
+            None,
+            U256::from(255),
+            vec![0xca, 0xfe],
+            1,
+            U256::from(1000),
+            21000,
+        );
+        let json = serde_json::to_value(&tx).unwrap();
+        assert_eq!(json["from"], from.to_string());
+        assert_eq!(json["value"], "0xff");
+        assert_eq!(json["data"], "0xcafe");
+        assert_eq!(json["hash"], tx.hash.to_string());
+        let decoded: Transaction = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.data, tx.data);
+        assert!(tx.to_string().contains("contract creation"));
+    }
 }
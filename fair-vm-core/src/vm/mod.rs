@@ -1,8 +1,12 @@
+use crate::params::ChainConfig;
 use crate::types::{Address, Hash};
 use async_trait::async_trait;
 use primitive_types::U256;
 use std::error::Error;
 
+pub mod revert;
+pub use revert::RevertReason;
+
 /// 执行上下文
 pub struct ExecutionContext {
     /// 区块号
@@ -15,6 +19,8 @@ pub struct ExecutionContext {
     pub block_gas_limit: u64,
     /// 区块奖励
     pub block_reward: u64,
+    /// 链参数
+    pub chain_config: ChainConfig,
 }
 
 /// 执行结果
@@ -25,6 +31,19 @@ pub struct ExecutionResult {
     pub return_data: Vec<u8>,
     /// 状态
     pub status: bool,
+    /// 执行结束时退还的 gas，已从 `gas_used` 中扣除
+    pub gas_refunded: u64,
+}
+
+impl ExecutionResult {
+    /// 执行失败时解码回滚原因，执行成功或没有回滚数据时返回 `None`
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        if self.status {
+            None
+        } else {
+            RevertReason::decode(&self.return_data)
+        }
+    }
 }
 
 /// 状态接口
@@ -89,6 +108,7 @@ impl Vm for BasicVm {
             gas_used: 0,
             return_data: vec![],
             status: true,
+            gas_refunded: 0,
         })
     }
 }
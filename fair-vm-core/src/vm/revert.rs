@@ -0,0 +1,182 @@
+//! 回滚原因解码
+//!
+//! Solidity 的 `require`/`revert("...")` 以 `Error(string)` 编码回滚数据，编译器插入的检查
+//! （溢出、除零、数组越界等）以 `Panic(uint256)` 编码，其余数据（自定义错误等）原样保留。
+
+use primitive_types::U256;
+use std::fmt;
+
+/// `Error(string)` 的函数选择器
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// `Panic(uint256)` 的函数选择器
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// 解码后的回滚原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevertReason {
+    /// `Error(string)` 携带的错误信息
+    Error(String),
+    /// `Panic(uint256)` 携带的错误码
+    Panic(U256),
+    /// 无法识别的回滚数据，例如自定义错误
+    Custom(Vec<u8>),
+}
+
+impl RevertReason {
+    /// 解码回滚数据，数据为空时返回 `None`
+    ///
+    /// 选择器匹配但编码不合法的数据按 [`RevertReason::Custom`] 返回。
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+        let (selector, payload) = data.split_at(4.min(data.len()));
+        let reason = if selector == ERROR_SELECTOR {
+            decode_string(payload).map(Self::Error)
+        } else if selector == PANIC_SELECTOR && payload.len() == 32 {
+            Some(Self::Panic(U256::from_big_endian(payload)))
+        } else {
+            None
+        };
+        Some(reason.unwrap_or_else(|| Self::Custom(data.to_vec())))
+    }
+
+    /// 按 ABI 编码为回滚数据，与 [`RevertReason::decode`] 互逆
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Error(message) => {
+                let mut data = ERROR_SELECTOR.to_vec();
+                data.extend_from_slice(&word(U256::from(32)));
+                data.extend_from_slice(&word(U256::from(message.len())));
+                data.extend_from_slice(message.as_bytes());
+                data.resize(data.len() + (32 - message.len() % 32) % 32, 0);
+                data
+            }
+            Self::Panic(code) => {
+                let mut data = PANIC_SELECTOR.to_vec();
+                data.extend_from_slice(&word(*code));
+                data
+            }
+            Self::Custom(data) => data.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(message) => write!(f, "{}", message),
+            Self::Panic(code) => {
+                if code.bits() <= 64 {
+                    write!(f, "panic 0x{:02x}", code.low_u64())?;
+                } else {
+                    write!(f, "panic 0x{:x}", code)?;
+                }
+                match panic_description(*code) {
+                    Some(description) => write!(f, ": {}", description),
+                    None => Ok(()),
+                }
+            }
+            Self::Custom(data) => write!(f, "0x{}", hex::encode(data)),
+        }
+    }
+}
+
+/// Solidity 编译器定义的 panic 错误码说明
+pub fn panic_description(code: U256) -> Option<&'static str> {
+    if code > U256::from(u8::MAX) {
+        return None;
+    }
+    let description = match code.low_u32() {
+        0x00 => "编译器插入的通用错误",
+        0x01 => "断言失败",
+        0x11 => "算术运算溢出",
+        0x12 => "除以零或对零取模",
+        0x21 => "转换为枚举时数值越界",
+        0x22 => "存储字节数组编码错误",
+        0x31 => "对空数组调用 pop",
+        0x32 => "数组下标越界",
+        0x41 => "内存分配过大",
+        0x51 => "调用未初始化的内部函数",
+        _ => return None,
+    };
+    Some(description)
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+/// 读取 32 字节的长度或偏移，超出 usize 范围时返回 `None`
+fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+    let word = data.get(offset..offset.checked_add(32)?)?;
+    let value = U256::from_big_endian(word);
+    if value > U256::from(usize::MAX) {
+        return None;
+    }
+    Some(value.as_usize())
+}
+
+/// 解码单个 `string` 参数
+fn decode_string(payload: &[u8]) -> Option<String> {
+    let offset = read_usize(payload, 0)?;
+    let length = read_usize(payload, offset)?;
+    let start = offset.checked_add(32)?;
+    let bytes = payload.get(start..start.checked_add(length)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_error() {
+        // require(false, "insufficient balance") 的回滚数据
+        let data = hex::decode(concat!(
+            "08c379a0",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000014",
+            "696e73756666696369656e742062616c616e6365000000000000000000000000",
+        ))
+        .unwrap();
+        let reason = RevertReason::decode(&data).unwrap();
+        assert_eq!(reason, RevertReason::Error("insufficient balance".into()));
+        assert_eq!(reason.encode(), data);
+        assert_eq!(reason.to_string(), "insufficient balance");
+    }
+
+    #[test]
+    fn test_decode_panic() {
+        let reason = RevertReason::Panic(U256::from(0x11));
+        let decoded = RevertReason::decode(&reason.encode()).unwrap();
+        assert_eq!(decoded, reason);
+        assert_eq!(decoded.to_string(), "panic 0x11: 算术运算溢出");
+        assert_eq!(
+            RevertReason::Panic(U256::from(0x99)).to_string(),
+            "panic 0x99"
+        );
+    }
+
+    #[test]
+    fn test_decode_custom() {
+        assert_eq!(RevertReason::decode(&[]), None);
+        // 自定义错误 InsufficientBalance()
+        let data = vec![0xf4, 0xd6, 0x78, 0xb8];
+        assert_eq!(
+            RevertReason::decode(&data),
+            Some(RevertReason::Custom(data.clone()))
+        );
+        assert_eq!(RevertReason::Custom(data).to_string(), "0xf4d678b8");
+        // 选择器匹配但长度字段越界
+        let mut data = RevertReason::Error("abc".into()).encode();
+        data[4 + 63] = 0xff;
+        assert!(matches!(
+            RevertReason::decode(&data),
+            Some(RevertReason::Custom(_))
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+//! Avalanche bech32 地址与 FairVM 十六进制地址转换
+//!
+//! 转换规则见 `fair_vm::avax_address`，这里提供基于 ethers 地址类型的便捷函数。
+//! ethers 的 `Address::from_str` 不校验 EIP-55 校验和，解析用户输入的地址时应使用
+//! [`parse_address`]。
+
+use ethers::types::Address;
+pub use fair_vm::avax_address::{
+    parse_any, parse_any_with, parse_hex, parse_hex_with, to_checksum_hex, AddressError,
+    AddressFormats, AvaxAddress, ChecksumPolicy, KeyAddresses, CHAIN_ALIASES, FUJI_HRP, LOCAL_HRP,
+    MAINNET_HRP,
+};
+
+/// 以 bech32 显示地址，`chain` 为 `X`、`P` 等链别名
+pub fn to_avax(address: Address, chain: Option<&str>, hrp: &str) -> Result<String, AddressError> {
+    AvaxAddress::new(chain, hrp, address.into()).map(|avax| avax.to_string())
+}
+
+/// 解析十六进制或 bech32 地址并校验校验和
+pub fn from_any(s: &str) -> Result<Address, AddressError> {
+    parse_any(s).map(Address::from)
+}
+
+/// 按指定校验和策略解析十六进制或 bech32 地址
+pub fn parse_address(s: &str, policy: ChecksumPolicy) -> Result<Address, AddressError> {
+    parse_any_with(s, policy).map(Address::from)
+}
+
+/// EIP-55 校验和十六进制地址
+pub fn to_checksum(address: Address) -> String {
+    to_checksum_hex(&address.into())
+}
+
+/// 地址的十六进制和 bech32 显示格式
+pub fn formats(address: Address, hrp: &str) -> Result<AddressFormats, AddressError> {
+    AddressFormats::new(&address.into(), hrp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let address = Address::repeat_byte(0x42);
+        let bech32 = to_avax(address, Some("P"), FUJI_HRP).unwrap();
+        assert!(bech32.starts_with("P-fuji1"));
+        assert_eq!(from_any(&bech32).unwrap(), address);
+
+        let formats = formats(address, FUJI_HRP).unwrap();
+        assert_eq!(from_any(&formats.hex).unwrap(), address);
+        assert_eq!(formats.p_chain, bech32);
+    }
+
+    #[test]
+    fn test_parse_address_checksum() {
+        let checksummed = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+        let address = parse_address(checksummed, ChecksumPolicy::Enforce).unwrap();
+        assert_eq!(to_checksum(address), checksummed);
+
+        let typo = "0xFB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+        assert!(matches!(
+            parse_address(typo, ChecksumPolicy::Enforce),
+            Err(AddressError::ChecksumMismatch(_))
+        ));
+        assert_eq!(
+            parse_address(typo, ChecksumPolicy::Ignore).unwrap(),
+            address
+        );
+    }
+}
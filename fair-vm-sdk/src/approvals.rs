@@ -0,0 +1,427 @@
+//! 授权风险扫描
+//!
+//! 按所有者扫描历史 `Approval`（ERC-20）和 `ApprovalForAll`（ERC-721 / ERC-1155）事件，找出曾经授权过的
+//! 代币和被授权方，再逐一查询链上当前额度，列出仍然有效的授权，并生成批量撤销交易。
+//! ERC-20 额度会被 `transferFrom` 消耗而不一定产生事件，有效额度以链上查询为准。
+//! ERC-721 针对单个代币的 `Approval` 在转移时自动清除，不在扫描范围内。
+
+use crate::client::ClientError;
+use crate::nft::{calldata, decode_single, decode_uint, event_topic, topic_address, unexpected};
+use ethers::abi::{ParamType, Token};
+use ethers::providers::Middleware;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Filter, Log, TransactionRequest, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// ERC-20 / ERC-721 `Approval(address,address,uint256)`
+const APPROVAL_EVENT: &str = "Approval(address,address,uint256)";
+
+/// ERC-721 / ERC-1155 `ApprovalForAll(address,address,bool)`
+const APPROVAL_FOR_ALL_EVENT: &str = "ApprovalForAll(address,address,bool)";
+
+/// 每次扫描的默认区块数
+pub const DEFAULT_SCAN_BLOCKS: u64 = 2_000;
+
+/// 额度不低于 2^128 视为无限授权，常见写法为 `type(uint256).max`
+pub fn unlimited_threshold() -> U256 {
+    U256::one() << 128
+}
+
+/// 授权内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApprovalKind {
+    /// ERC-20 额度
+    Allowance(U256),
+    /// 全部 NFT 的操作权限，`false` 表示撤销
+    Operator(bool),
+}
+
+/// 一次授权事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalEvent {
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    pub log_index: u64,
+    /// 代币合约
+    pub token: Address,
+    pub owner: Address,
+    /// 被授权方，`ApprovalForAll` 中为操作者
+    pub spender: Address,
+    pub kind: ApprovalKind,
+}
+
+impl ApprovalEvent {
+    fn is_operator(&self) -> bool {
+        matches!(self.kind, ApprovalKind::Operator(_))
+    }
+}
+
+/// 扫描游标，保存后可从中断处继续扫描
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalCursor {
+    /// 下一次扫描的起始区块
+    pub next_block: u64,
+}
+
+impl ApprovalCursor {
+    pub fn from_block(block: u64) -> Self {
+        Self { next_block: block }
+    }
+}
+
+/// 一页扫描结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalPage {
+    pub events: Vec<ApprovalEvent>,
+    /// 下一页的游标
+    pub cursor: ApprovalCursor,
+    /// 是否已扫描到链头
+    pub done: bool,
+}
+
+/// 仍然有效的授权
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveApproval {
+    pub token: Address,
+    pub spender: Address,
+    /// 链上当前的授权内容
+    pub kind: ApprovalKind,
+    /// 最近一次授权所在区块
+    pub approved_at: u64,
+    /// 最近一次授权的交易
+    pub transaction_hash: H256,
+}
+
+impl ActiveApproval {
+    /// 无限额度或全部 NFT 的操作权限
+    pub fn is_unlimited(&self) -> bool {
+        match self.kind {
+            ApprovalKind::Allowance(amount) => amount >= unlimited_threshold(),
+            ApprovalKind::Operator(approved) => approved,
+        }
+    }
+
+    /// 撤销该授权的交易，`from` 为授权所有者
+    pub fn revoke_request(&self, from: Address) -> TransactionRequest {
+        let data = match self.kind {
+            ApprovalKind::Allowance(_) => calldata(
+                "approve(address,uint256)",
+                &[Token::Address(self.spender), Token::Uint(U256::zero())],
+            ),
+            ApprovalKind::Operator(_) => calldata(
+                "setApprovalForAll(address,bool)",
+                &[Token::Address(self.spender), Token::Bool(false)],
+            ),
+        };
+        TransactionRequest::new()
+            .from(from)
+            .to(self.token)
+            .data(data)
+    }
+}
+
+/// 授权扫描客户端
+pub struct ApprovalScanner<M> {
+    client: Arc<M>,
+    scan_blocks: u64,
+}
+
+impl<M: Middleware> ApprovalScanner<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            scan_blocks: DEFAULT_SCAN_BLOCKS,
+        }
+    }
+
+    /// 设置每页扫描的区块数
+    pub fn with_scan_blocks(mut self, scan_blocks: u64) -> Self {
+        self.scan_blocks = scan_blocks.max(1);
+        self
+    }
+
+    /// 扫描一页 `owner` 发出的授权事件
+    pub async fn events(
+        &self,
+        owner: Address,
+        cursor: ApprovalCursor,
+    ) -> Result<ApprovalPage, ClientError> {
+        let head = self
+            .client
+            .get_block_number()
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))?
+            .as_u64();
+        if cursor.next_block > head {
+            return Ok(ApprovalPage {
+                events: Vec::new(),
+                cursor,
+                done: true,
+            });
+        }
+
+        let to_block = head.min(cursor.next_block.saturating_add(self.scan_blocks - 1));
+        let filter = Filter::new()
+            .topic0(vec![
+                event_topic(APPROVAL_EVENT),
+                event_topic(APPROVAL_FOR_ALL_EVENT),
+            ])
+            .topic1(H256::from(owner))
+            .from_block(cursor.next_block)
+            .to_block(to_block);
+        let logs = self
+            .client
+            .get_logs(&filter)
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))?;
+
+        let mut events = Vec::new();
+        for log in &logs {
+            events.extend(decode_approval_log(log)?);
+        }
+        Ok(ApprovalPage {
+            events,
+            cursor: ApprovalCursor {
+                next_block: to_block + 1,
+            },
+            done: to_block == head,
+        })
+    }
+
+    /// 列出 `owner` 当前仍然有效的授权，按代币和被授权方排序
+    pub async fn active_approvals(
+        &self,
+        owner: Address,
+        from_block: u64,
+    ) -> Result<Vec<ActiveApproval>, ClientError> {
+        let mut cursor = ApprovalCursor::from_block(from_block);
+        let mut events = Vec::new();
+        loop {
+            let page = self.events(owner, cursor).await?;
+            events.extend(page.events);
+            cursor = page.cursor;
+            if page.done {
+                break;
+            }
+        }
+
+        let mut active = Vec::new();
+        for event in latest_approvals(&events) {
+            let kind = match event.kind {
+                ApprovalKind::Allowance(_) => ApprovalKind::Allowance(
+                    self.allowance(event.token, owner, event.spender).await?,
+                ),
+                ApprovalKind::Operator(_) => ApprovalKind::Operator(
+                    self.is_approved_for_all(event.token, owner, event.spender)
+                        .await?,
+                ),
+            };
+            if matches!(kind, ApprovalKind::Allowance(amount) if amount.is_zero())
+                || kind == ApprovalKind::Operator(false)
+            {
+                continue;
+            }
+            active.push(ActiveApproval {
+                token: event.token,
+                spender: event.spender,
+                kind,
+                approved_at: event.block_number,
+                transaction_hash: event.transaction_hash,
+            });
+        }
+        Ok(active)
+    }
+
+    async fn allowance(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<U256, ClientError> {
+        let output = self
+            .call(
+                token,
+                "allowance(address,address)",
+                &[Token::Address(owner), Token::Address(spender)],
+            )
+            .await?;
+        decode_uint(&output)
+    }
+
+    async fn is_approved_for_all(
+        &self,
+        token: Address,
+        owner: Address,
+        operator: Address,
+    ) -> Result<bool, ClientError> {
+        let output = self
+            .call(
+                token,
+                "isApprovedForAll(address,address)",
+                &[Token::Address(owner), Token::Address(operator)],
+            )
+            .await?;
+        match decode_single(ParamType::Bool, &output)? {
+            Token::Bool(approved) => Ok(approved),
+            token => Err(unexpected(token)),
+        }
+    }
+
+    async fn call(
+        &self,
+        contract: Address,
+        signature: &str,
+        args: &[Token],
+    ) -> Result<Bytes, ClientError> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(contract)
+            .data(calldata(signature, args))
+            .into();
+        self.client
+            .call(&tx, None)
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))
+    }
+}
+
+/// 解析授权事件，其他事件和 ERC-721 单个代币的授权返回 `None`
+pub fn decode_approval_log(log: &Log) -> Result<Option<ApprovalEvent>, ClientError> {
+    let Some(topic0) = log.topics.first() else {
+        return Ok(None);
+    };
+    let operator = *topic0 == event_topic(APPROVAL_FOR_ALL_EVENT);
+    if !operator && *topic0 != event_topic(APPROVAL_EVENT) {
+        return Ok(None);
+    }
+    // ERC-721 的 Approval 签名相同但 tokenId 已索引，有 4 个主题
+    if log.topics.len() != 3 {
+        return Ok(None);
+    }
+    let invalid = || ClientError::Other(format!("无效的授权事件: {:?}", log.transaction_hash));
+    let kind = if operator {
+        match decode_single(ParamType::Bool, &log.data).map_err(|_| invalid())? {
+            Token::Bool(approved) => ApprovalKind::Operator(approved),
+            _ => return Err(invalid()),
+        }
+    } else {
+        ApprovalKind::Allowance(decode_uint(&log.data).map_err(|_| invalid())?)
+    };
+    Ok(Some(ApprovalEvent {
+        block_number: log.block_number.map_or(0, |n| n.as_u64()),
+        transaction_hash: log.transaction_hash.unwrap_or_default(),
+        log_index: log.log_index.map_or(0, |i| i.as_u64()),
+        token: log.address,
+        owner: topic_address(&log.topics[1]),
+        spender: topic_address(&log.topics[2]),
+        kind,
+    }))
+}
+
+/// 每个代币和被授权方只保留最近一次授权，按代币和被授权方排序
+pub fn latest_approvals(events: &[ApprovalEvent]) -> Vec<ApprovalEvent> {
+    let mut latest: HashMap<(Address, Address, bool), &ApprovalEvent> = HashMap::new();
+    for event in events {
+        let key = (event.token, event.spender, event.is_operator());
+        match latest.get(&key) {
+            Some(seen)
+                if (seen.block_number, seen.log_index) > (event.block_number, event.log_index) => {}
+            _ => {
+                latest.insert(key, event);
+            }
+        }
+    }
+    let mut latest: Vec<ApprovalEvent> = latest.into_values().cloned().collect();
+    latest.sort_by_key(|event| (event.token, event.spender, event.is_operator()));
+    latest
+}
+
+/// 批量撤销授权的交易
+pub fn revoke_requests(owner: Address, approvals: &[ActiveApproval]) -> Vec<TransactionRequest> {
+    approvals
+        .iter()
+        .map(|approval| approval.revoke_request(owner))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi;
+
+    fn log(topic0: &str, token: u8, spender: u8, data: Token, block: u64) -> Log {
+        Log {
+            address: Address::repeat_byte(token),
+            topics: vec![
+                event_topic(topic0),
+                H256::from(Address::repeat_byte(1)),
+                H256::from(Address::repeat_byte(spender)),
+            ],
+            data: abi::encode(&[data]).into(),
+            block_number: Some(block.into()),
+            log_index: Some(0u64.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_and_latest() {
+        let logs = [
+            log(APPROVAL_EVENT, 0xaa, 2, Token::Uint(U256::MAX), 5),
+            log(APPROVAL_EVENT, 0xaa, 2, Token::Uint(U256::from(10)), 3),
+            log(APPROVAL_FOR_ALL_EVENT, 0xbb, 2, Token::Bool(true), 4),
+            log(APPROVAL_FOR_ALL_EVENT, 0xbb, 2, Token::Bool(false), 6),
+        ];
+        let events: Vec<ApprovalEvent> = logs
+            .iter()
+            .map(|log| decode_approval_log(log).unwrap().unwrap())
+            .collect();
+        assert_eq!(events[0].owner, Address::repeat_byte(1));
+        assert_eq!(events[0].kind, ApprovalKind::Allowance(U256::MAX));
+
+        // 事件顺序不影响结果，以区块更晚的为准
+        let latest = latest_approvals(&events);
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].kind, ApprovalKind::Allowance(U256::MAX));
+        assert_eq!(latest[1].kind, ApprovalKind::Operator(false));
+
+        // ERC-721 单个代币的授权不计入
+        let mut erc721 = log(APPROVAL_EVENT, 0xcc, 2, Token::Uint(U256::one()), 1);
+        erc721.topics.push(H256::from_low_u64_be(7));
+        erc721.data = Bytes::new();
+        assert_eq!(decode_approval_log(&erc721).unwrap(), None);
+    }
+
+    #[test]
+    fn test_revoke_requests() {
+        let owner = Address::repeat_byte(1);
+        let approval = |kind| ActiveApproval {
+            token: Address::repeat_byte(0xaa),
+            spender: Address::repeat_byte(2),
+            kind,
+            approved_at: 5,
+            transaction_hash: H256::zero(),
+        };
+        let approvals = [
+            approval(ApprovalKind::Allowance(U256::MAX)),
+            approval(ApprovalKind::Allowance(U256::from(10))),
+            approval(ApprovalKind::Operator(true)),
+        ];
+        assert!(approvals[0].is_unlimited());
+        assert!(!approvals[1].is_unlimited());
+        assert!(approvals[2].is_unlimited());
+
+        let requests = revoke_requests(owner, &approvals);
+        assert_eq!(requests[0].from, Some(owner));
+        let data = requests[0].data.as_ref().unwrap();
+        assert_eq!(&data[..4], &ethers::utils::id("approve(address,uint256)"));
+        assert_eq!(&data[4 + 32..], &[0u8; 32]);
+        let data = requests[2].data.as_ref().unwrap();
+        assert_eq!(
+            &data[..4],
+            &ethers::utils::id("setApprovalForAll(address,bool)")
+        );
+    }
+}
@@ -1,17 +1,27 @@
 //! FairVM客户端实现
 
+pub mod stream;
+pub mod trace;
+pub mod transport;
+
+use crate::deposit::InclusionProof;
+use crate::nft::NftClient;
 use crate::wallet::FairWallet as Wallet;
 use crate::SdkConfig;
-use ethers::providers::{Http, Middleware, Provider};
+use ethers::providers::{Http, JsonRpcError, Middleware, Provider, RpcError};
 use ethers::types::transaction::eip2718::TypedTransaction;
-use ethers::types::H256;
 use ethers::types::{
-    Address, BlockId, BlockNumber, Transaction, TransactionReceipt, TransactionRequest, TxHash,
-    U256,
+    Address, BlockId, BlockNumber, Bytes, Transaction, TransactionReceipt, TransactionRequest,
+    TxHash, U256,
 };
+use ethers::types::{Block, H256};
+use fair_vm::RevertReason;
+use futures::stream::Stream;
+use std::path::Path;
 use std::sync::Arc;
+use stream::BlockStreamConfig;
 use thiserror::Error;
-use url::Url;
+use transport::RpcTransport;
 
 /// 客户端错误类型
 #[derive(Debug, Error)]
@@ -34,10 +44,32 @@ pub enum ClientError {
     #[error("Gas 价格过低: 最低 {minimum}, 提供 {provided}")]
     GasPriceTooLow { minimum: U256, provided: U256 },
 
+    #[error("执行回滚: {reason}")]
+    Reverted {
+        /// 解码后的回滚原因
+        reason: RevertReason,
+        /// 原始回滚数据
+        data: Vec<u8>,
+    },
+
     #[error("其他错误: {0}")]
     Other(String),
 }
 
+impl ClientError {
+    /// 从节点返回的 JSON-RPC 错误中提取回滚原因，错误码 3 且携带回滚数据时返回
+    /// [`ClientError::Reverted`]
+    pub fn from_revert_response(response: &JsonRpcError) -> Option<Self> {
+        if response.code != 3 {
+            return None;
+        }
+        let data = response.data.as_ref()?.as_str()?;
+        let data = hex::decode(data.trim_start_matches("0x")).ok()?;
+        let reason = RevertReason::decode(&data)?;
+        Some(Self::Reverted { reason, data })
+    }
+}
+
 /// FairVM客户端
 pub struct Client {
     /// SDK配置
@@ -45,35 +77,93 @@ pub struct Client {
     /// HTTP客户端
     #[allow(dead_code)]
     http_client: reqwest::Client,
-    provider: Arc<Provider<Http>>,
+    provider: Arc<Provider<RpcTransport>>,
     #[allow(dead_code)]
     wallet: Option<Wallet>,
+    /// 区块流配置
+    stream_config: BlockStreamConfig,
 }
 
 impl Client {
     /// 创建新的客户端实例
     pub fn new(rpc_url: &str) -> Result<Self, String> {
-        let url = Url::parse(rpc_url).map_err(|e| e.to_string())?;
-        let http_client = Http::new(url);
-        let provider = Provider::new(http_client);
+        Ok(Self::with_transport(RpcTransport::http(rpc_url)?))
+    }
 
-        Ok(Self {
+    /// 通过 IPC 套接字连接同一主机上的节点
+    pub async fn connect_ipc(path: impl AsRef<Path>) -> Result<Self, String> {
+        Ok(Self::with_transport(RpcTransport::ipc(path).await?))
+    }
+
+    /// 按地址选择 HTTP 或 IPC 传输，见 [`RpcTransport::connect`]
+    pub async fn connect(endpoint: &str) -> Result<Self, String> {
+        Ok(Self::with_transport(RpcTransport::connect(endpoint).await?))
+    }
+
+    /// 使用指定传输创建客户端实例
+    pub fn with_transport(transport: RpcTransport) -> Self {
+        Self {
             http_client: reqwest::Client::new(),
             config: SdkConfig::default(),
-            provider: Arc::new(provider),
+            provider: Arc::new(Provider::new(transport)),
             wallet: None,
-        })
+            stream_config: BlockStreamConfig::default(),
+        }
     }
 
     /// 使用钱包创建新的客户端实例
     pub fn with_wallet(provider: Provider<Http>, wallet: Wallet) -> Self {
+        let transport = RpcTransport::from(provider.as_ref().clone());
         Self {
-            provider: Arc::new(provider),
+            provider: Arc::new(Provider::new(transport)),
             config: SdkConfig::default(),
             http_client: reqwest::Client::new(),
             wallet: Some(wallet),
+            stream_config: BlockStreamConfig::default(),
         }
     }
+
+    /// 设置 WebSocket 地址，区块流追上链头后通过订阅接收新区块
+    pub fn with_ws_url(mut self, ws_url: &str) -> Self {
+        self.stream_config.ws_url = Some(ws_url.to_string());
+        self
+    }
+
+    /// 启用 RPC 调用日志，`correlation_id` 省略时随机生成
+    ///
+    /// 之后的每次调用通过 `tracing` 记录方法名、参数哈希、耗时和结果，HTTP 请求携带关联 ID
+    /// 请求头，见 [`trace::TracedTransport`]。
+    pub fn with_tracing(mut self, correlation_id: Option<&str>) -> Result<Self, String> {
+        let correlation_id = correlation_id
+            .map(str::to_string)
+            .unwrap_or_else(trace::TracedTransport::random_correlation_id);
+        let inner = Provider::as_ref(&self.provider).clone();
+        let traced = trace::TracedTransport::new(inner, correlation_id)?;
+        self.provider = Arc::new(Provider::new(RpcTransport::Traced(Box::new(traced))));
+        Ok(self)
+    }
+
+    /// 设置区块流配置
+    pub fn with_stream_config(mut self, config: BlockStreamConfig) -> Self {
+        self.stream_config = config;
+        self
+    }
+
+    /// 从指定高度开始的完整区块流
+    ///
+    /// 先通过 HTTP 或 IPC 分页补齐历史区块，追上链头后切换为实时模式。
+    pub fn blocks_from(
+        &self,
+        number: u64,
+    ) -> impl Stream<Item = Result<Block<Transaction>, ClientError>> + Send + 'static {
+        stream::block_stream(self.provider.clone(), number, self.stream_config.clone())
+    }
+
+    /// 只读 NFT 查询客户端，转账需使用带签名的中间件创建 [`NftClient`]
+    pub fn nft(&self) -> NftClient<Provider<RpcTransport>> {
+        NftClient::new(self.provider.clone())
+    }
+
     /// 获取链信息
     pub async fn get_chain_info(&self) -> Result<serde_json::Value, reqwest::Error> {
         // TODO: 实现真实的API调用
@@ -119,6 +209,30 @@ impl Client {
             .map_err(|e| e.to_string())
     }
 
+    /// 获取区块内全部交易的收据，区块不存在时返回 None
+    pub async fn get_block_receipts(
+        &self,
+        block: BlockNumber,
+    ) -> Result<Option<Vec<TransactionReceipt>>, String> {
+        self.provider
+            .request("eth_getBlockReceipts", [block])
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// 获取交易和收据的包含证明，交易不存在或尚未打包时返回 None
+    ///
+    /// 证明需对照可信区块头验证，见 [`crate::deposit::verify_deposits`]。
+    pub async fn get_inclusion_proof(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<Option<InclusionProof>, String> {
+        self.provider
+            .request("fairvm_getInclusionProof", [tx_hash])
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     /// 获取交易详情
     pub async fn get_transaction(&self, tx_hash: TxHash) -> Result<Option<Transaction>, String> {
         self.provider
@@ -139,6 +253,22 @@ impl Client {
             .map_err(|e| e.to_string())
     }
 
+    /// 在最新状态上执行调用，不发送交易
+    ///
+    /// 合约回滚时返回 [`ClientError::Reverted`]，其中包含解码后的回滚原因。
+    pub async fn call(
+        &self,
+        tx: &TransactionRequest,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, ClientError> {
+        let typed_tx: TypedTransaction = tx.clone().into();
+        self.provider.call(&typed_tx, block).await.map_err(|e| {
+            e.as_error_response()
+                .and_then(ClientError::from_revert_response)
+                .unwrap_or_else(|| ClientError::TransactionError(e.to_string()))
+        })
+    }
+
     /// 估算交易所需的 gas
     pub async fn estimate_gas(
         &self,
@@ -179,18 +309,50 @@ mod tests {
     use super::*;
     use ethers::providers::{Http, Provider};
     use std::str::FromStr;
+    use url::Url;
+
+    #[test]
+    fn test_revert_response() {
+        let reason = RevertReason::Error("余额不足".into());
+        let response = JsonRpcError {
+            code: 3,
+            message: "execution reverted: 余额不足".into(),
+            data: Some(serde_json::Value::String(format!(
+                "0x{}",
+                hex::encode(reason.encode())
+            ))),
+        };
+        match ClientError::from_revert_response(&response) {
+            Some(ClientError::Reverted {
+                reason: decoded,
+                data,
+            }) => {
+                assert_eq!(decoded, reason);
+                assert_eq!(data, reason.encode());
+            }
+            other => panic!("未解码回滚原因: {:?}", other),
+        }
+
+        let response = JsonRpcError {
+            code: -32000,
+            message: "执行失败".into(),
+            data: None,
+        };
+        assert!(ClientError::from_revert_response(&response).is_none());
+    }
 
     #[tokio::test]
     #[ignore] // 需要本地节点才能运行
     async fn test_client_creation() {
         let http = Http::new(Url::parse("http://localhost:8545").unwrap());
-        let provider = Provider::new(http);
+        let provider = Provider::new(RpcTransport::from(http));
 
         let client = Client {
             config: SdkConfig::default(),
             http_client: reqwest::Client::new(),
             provider: Arc::new(provider),
             wallet: None,
+            stream_config: BlockStreamConfig::default(),
         };
 
         let test_address = Address::from_str("0x0000000000000000000000000000000000000001").unwrap();
@@ -0,0 +1,213 @@
+//! 区块流
+//!
+//! 先通过 HTTP 或 IPC 分页补齐历史区块，追上链头后切换到 WebSocket 新区块订阅；
+//! 未配置 WebSocket 或订阅断开时退化为 HTTP 轮询。流中的区块按高度严格递增且不遗漏。
+
+use super::transport::RpcTransport;
+use super::ClientError;
+use ethers::providers::{Middleware, Provider, Ws};
+use ethers::types::{Block, Transaction};
+use futures::future::try_join_all;
+use futures::stream::{self, Stream};
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 补齐历史区块时每批并发请求的区块数
+pub const DEFAULT_PAGE_SIZE: u64 = 32;
+
+/// HTTP 轮询间隔
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 新区块通知队列长度
+const HEAD_CHANNEL_SIZE: usize = 64;
+
+/// 区块流配置
+#[derive(Debug, Clone)]
+pub struct BlockStreamConfig {
+    /// WebSocket 地址，为空时使用 HTTP 轮询
+    pub ws_url: Option<String>,
+    /// 每批请求的区块数
+    pub page_size: u64,
+    /// 轮询间隔
+    pub poll_interval: Duration,
+}
+
+impl Default for BlockStreamConfig {
+    fn default() -> Self {
+        Self {
+            ws_url: None,
+            page_size: DEFAULT_PAGE_SIZE,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// 实时模式下的新区块来源
+enum LiveSource {
+    /// 尚未进入实时模式
+    Pending,
+    /// WebSocket 新区块订阅
+    Subscription(mpsc::Receiver<u64>),
+    /// HTTP 轮询
+    Polling,
+}
+
+/// 区块流状态
+struct BlockStreamState {
+    provider: Arc<Provider<RpcTransport>>,
+    config: BlockStreamConfig,
+    /// 下一个要返回的区块高度
+    next: u64,
+    /// 已获取但尚未返回的区块
+    buffer: VecDeque<Block<Transaction>>,
+    /// 已知的链头高度
+    head: Option<u64>,
+    live: LiveSource,
+}
+
+/// 计算下一批需要获取的区块范围
+pub(crate) fn next_range(next: u64, head: u64, page_size: u64) -> Option<RangeInclusive<u64>> {
+    if next > head {
+        return None;
+    }
+    let end = head.min(next.saturating_add(page_size.max(1) - 1));
+    Some(next..=end)
+}
+
+/// 订阅新区块高度，订阅结束时关闭通道
+async fn subscribe_heads(ws_url: &str) -> Result<mpsc::Receiver<u64>, ClientError> {
+    let provider = Provider::<Ws>::connect(ws_url)
+        .await
+        .map_err(|e| ClientError::NetworkError(e.to_string()))?;
+    let (sender, receiver) = mpsc::channel(HEAD_CHANNEL_SIZE);
+
+    tokio::spawn(async move {
+        let mut heads = match provider.subscribe_blocks().await {
+            Ok(heads) => heads,
+            Err(_) => return,
+        };
+        while let Some(block) = heads.next().await {
+            if let Some(number) = block.number {
+                if sender.send(number.as_u64()).await.is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+impl BlockStreamState {
+    /// 获取指定范围内的完整区块
+    async fn fetch(&mut self, range: RangeInclusive<u64>) -> Result<(), ClientError> {
+        let provider = self.provider.clone();
+        let blocks = try_join_all(range.map(|number| {
+            let provider = provider.clone();
+            async move {
+                provider
+                    .get_block_with_txs(number)
+                    .await
+                    .map_err(|e| ClientError::NetworkError(e.to_string()))?
+                    .ok_or_else(|| ClientError::Other(format!("区块 {} 不存在", number)))
+            }
+        }))
+        .await?;
+
+        self.next += blocks.len() as u64;
+        self.buffer.extend(blocks);
+        Ok(())
+    }
+
+    /// 等待链头前进并返回新的链头高度
+    async fn wait_for_head(&mut self) -> Result<u64, ClientError> {
+        if let LiveSource::Pending = self.live {
+            self.live = match &self.config.ws_url {
+                Some(ws_url) => match subscribe_heads(ws_url).await {
+                    Ok(receiver) => LiveSource::Subscription(receiver),
+                    Err(_) => LiveSource::Polling,
+                },
+                None => LiveSource::Polling,
+            };
+        }
+
+        if let LiveSource::Subscription(receiver) = &mut self.live {
+            match receiver.recv().await {
+                Some(head) => return Ok(head),
+                // 订阅断开，退化为轮询
+                None => self.live = LiveSource::Polling,
+            }
+        }
+
+        tokio::time::sleep(self.config.poll_interval).await;
+        self.latest_number().await
+    }
+
+    async fn latest_number(&self) -> Result<u64, ClientError> {
+        self.provider
+            .get_block_number()
+            .await
+            .map(|number| number.as_u64())
+            .map_err(|e| ClientError::NetworkError(e.to_string()))
+    }
+
+    /// 返回下一个区块
+    async fn next_block(&mut self) -> Result<Block<Transaction>, ClientError> {
+        loop {
+            if let Some(block) = self.buffer.pop_front() {
+                return Ok(block);
+            }
+
+            let head = match self.head {
+                Some(head) if head >= self.next => head,
+                // 首次调用时获取链头并开始补齐历史区块
+                None => self.latest_number().await?,
+                Some(_) => self.wait_for_head().await?,
+            };
+            self.head = Some(head);
+
+            if let Some(range) = next_range(self.next, head, self.config.page_size) {
+                self.fetch(range).await?;
+            }
+        }
+    }
+}
+
+/// 创建从指定高度开始的区块流
+pub(crate) fn block_stream(
+    provider: Arc<Provider<RpcTransport>>,
+    from: u64,
+    config: BlockStreamConfig,
+) -> impl Stream<Item = Result<Block<Transaction>, ClientError>> + Send + 'static {
+    let state = BlockStreamState {
+        provider,
+        config,
+        next: from,
+        buffer: VecDeque::new(),
+        head: None,
+        live: LiveSource::Pending,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        let result = state.next_block().await;
+        Some((result, state))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_range() {
+        assert_eq!(next_range(0, 100, 32), Some(0..=31));
+        assert_eq!(next_range(96, 100, 32), Some(96..=100));
+        assert_eq!(next_range(100, 100, 32), Some(100..=100));
+        assert_eq!(next_range(101, 100, 32), None);
+        assert_eq!(next_range(5, 10, 0), Some(5..=5));
+    }
+}
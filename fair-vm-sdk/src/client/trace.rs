@@ -0,0 +1,124 @@
+//! RPC 调用日志与请求追踪
+//!
+//! [`TracedTransport`] 包装 [`RpcTransport`]，通过 `tracing` 为每次调用记录方法名、参数哈希、
+//! 耗时和结果，目标为 `fair_vm_sdk::rpc`。使用 HTTP 传输时关联 ID 随 [`CORRELATION_ID_HEADER`]
+//! 头发给节点，节点把它写入自己的日志，按同一个 ID 即可串起客户端与节点两侧的记录；
+//! IPC 没有请求头，关联 ID 只出现在客户端日志中。
+
+use super::transport::{RpcTransport, TransportError};
+use async_trait::async_trait;
+use ethers::providers::{Http, JsonRpcClient};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+use std::time::Instant;
+
+pub use fair_vm::api::http::CORRELATION_ID_HEADER;
+
+/// 记录调用日志的传输
+#[derive(Debug, Clone)]
+pub struct TracedTransport {
+    inner: RpcTransport,
+    correlation_id: String,
+}
+
+impl TracedTransport {
+    /// 包装传输，HTTP 传输的每个请求都会携带关联 ID 请求头
+    pub fn new(inner: RpcTransport, correlation_id: impl Into<String>) -> Result<Self, String> {
+        let correlation_id = correlation_id.into();
+        let inner = match inner {
+            RpcTransport::Http(http) => {
+                let mut headers = HeaderMap::new();
+                let value = HeaderValue::from_str(&correlation_id)
+                    .map_err(|_| format!("无效的关联 ID: {}", correlation_id))?;
+                headers.insert(CORRELATION_ID_HEADER, value);
+                let client = reqwest::Client::builder()
+                    .default_headers(headers)
+                    .build()
+                    .map_err(|e| e.to_string())?;
+                RpcTransport::Http(Http::new_with_client(http.url().clone(), client))
+            }
+            inner => inner,
+        };
+        Ok(Self {
+            inner,
+            correlation_id,
+        })
+    }
+
+    /// 随机生成的关联 ID
+    pub fn random_correlation_id() -> String {
+        format!("{:016x}", rand::random::<u64>())
+    }
+
+    pub fn correlation_id(&self) -> &str {
+        &self.correlation_id
+    }
+}
+
+/// 参数的短哈希，日志中不记录参数原文，避免泄露签名交易等内容
+pub fn params_hash<T: Serialize>(params: &T) -> String {
+    let encoded = serde_json::to_vec(params).unwrap_or_default();
+    hex::encode(&Sha256::digest(&encoded)[..8])
+}
+
+#[async_trait]
+impl JsonRpcClient for TracedTransport {
+    type Error = TransportError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params_hash = params_hash(&params);
+        let started = Instant::now();
+        let result = self.inner.request(method, params).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(_) => tracing::debug!(
+                target: "fair_vm_sdk::rpc",
+                correlation_id = %self.correlation_id,
+                method,
+                params_hash = %params_hash,
+                duration_ms,
+                outcome = "ok",
+                "RPC 调用完成"
+            ),
+            Err(e) => tracing::warn!(
+                target: "fair_vm_sdk::rpc",
+                correlation_id = %self.correlation_id,
+                method,
+                params_hash = %params_hash,
+                duration_ms,
+                outcome = "error",
+                error = %e,
+                "RPC 调用失败"
+            ),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traced_transport() {
+        assert_eq!(params_hash(&[1, 2]), params_hash(&[1, 2]));
+        assert_ne!(params_hash(&[1, 2]), params_hash(&[2, 1]));
+        assert_eq!(params_hash(&()).len(), 16);
+
+        let http = RpcTransport::http("http://127.0.0.1:9650/ext/bc/fairvm/rpc").unwrap();
+        let traced = TracedTransport::new(http.clone(), "req-42").unwrap();
+        assert_eq!(traced.correlation_id(), "req-42");
+        assert!(TracedTransport::new(http, "bad\nid").is_err());
+        assert_ne!(
+            TracedTransport::random_correlation_id(),
+            TracedTransport::random_correlation_id()
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! RPC 传输
+//!
+//! 客户端可以通过 HTTP 或 IPC（Unix 域套接字）连接节点。同一主机上的工具优先使用 IPC，
+//! 不经过 TCP，也只有能访问套接字文件的用户才能调用。
+
+use super::trace::TracedTransport;
+use async_trait::async_trait;
+use ethers::providers::{
+    Http, HttpClientError, Ipc, IpcError, JsonRpcClient, JsonRpcError, ProviderError, RpcError,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+use std::path::Path;
+use url::Url;
+
+/// IPC 地址前缀，也可以直接使用套接字路径
+pub const IPC_SCHEME: &str = "ipc://";
+
+/// 传输错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    #[error(transparent)]
+    Http(#[from] HttpClientError),
+
+    #[error(transparent)]
+    Ipc(#[from] IpcError),
+}
+
+impl RpcError for TransportError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            TransportError::Http(e) => e.as_error_response(),
+            TransportError::Ipc(e) => e.as_error_response(),
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            TransportError::Http(e) => e.as_serde_error(),
+            TransportError::Ipc(e) => e.as_serde_error(),
+        }
+    }
+}
+
+impl From<TransportError> for ProviderError {
+    fn from(e: TransportError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(e))
+    }
+}
+
+/// HTTP 或 IPC 传输
+#[derive(Debug, Clone)]
+pub enum RpcTransport {
+    Http(Http),
+    Ipc(Ipc),
+    /// 记录调用日志的传输，见 [`TracedTransport`]
+    Traced(Box<TracedTransport>),
+}
+
+impl RpcTransport {
+    /// 使用 HTTP 地址创建传输
+    pub fn http(url: &str) -> Result<Self, String> {
+        let url = Url::parse(url).map_err(|e| e.to_string())?;
+        Ok(RpcTransport::Http(Http::new(url)))
+    }
+
+    /// 连接 IPC 套接字
+    pub async fn ipc(path: impl AsRef<Path>) -> Result<Self, String> {
+        Ipc::connect(path)
+            .await
+            .map(RpcTransport::Ipc)
+            .map_err(|e| e.to_string())
+    }
+
+    /// 按地址选择传输：`http(s)://` 使用 HTTP，`ipc://` 前缀或文件路径使用 IPC
+    pub async fn connect(endpoint: &str) -> Result<Self, String> {
+        if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            Self::http(endpoint)
+        } else {
+            Self::ipc(endpoint.strip_prefix(IPC_SCHEME).unwrap_or(endpoint)).await
+        }
+    }
+}
+
+impl From<Http> for RpcTransport {
+    fn from(http: Http) -> Self {
+        RpcTransport::Http(http)
+    }
+}
+
+impl From<Ipc> for RpcTransport {
+    fn from(ipc: Ipc) -> Self {
+        RpcTransport::Ipc(ipc)
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RpcTransport {
+    type Error = TransportError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            RpcTransport::Http(http) => Ok(http.request(method, params).await?),
+            RpcTransport::Ipc(ipc) => Ok(ipc.request(method, params).await?),
+            RpcTransport::Traced(traced) => traced.request(method, params).await,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+
+    #[tokio::test]
+    async fn test_connect() {
+        assert!(matches!(
+            RpcTransport::connect("http://localhost:9650").await,
+            Ok(RpcTransport::Http(_))
+        ));
+        assert!(RpcTransport::connect("ipc:///nonexistent/fairvm.ipc")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ipc_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fairvm.ipc");
+        let listener = UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            let mut request = Vec::new();
+            // 请求不带换行，读到完整对象的右括号为止
+            reader.read_until(b'}', &mut request).await.unwrap();
+            let request: serde_json::Value = serde_json::from_slice(&request).unwrap();
+            assert_eq!(request["method"], "eth_chainId");
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": "0x7e7",
+            });
+            writer
+                .write_all(format!("{}\n", response).as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let transport = RpcTransport::connect(&format!("{}{}", IPC_SCHEME, path.display()))
+            .await
+            .unwrap();
+        let chain_id: String = transport.request("eth_chainId", ()).await.unwrap();
+        assert_eq!(chain_id, "0x7e7");
+    }
+}
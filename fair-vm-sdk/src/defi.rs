@@ -0,0 +1,568 @@
+//! DeFi 辅助工具
+//!
+//! 封装 ERC-20 授权管理、ERC-4626 金库的预览与存取，以及读取链上参数生成
+//! EIP-2612 permit 和 Permit2 授权，消息的构造与签名见 [`crate::permit`]。
+
+use crate::client::ClientError;
+use crate::nft::{calldata, decode_single, decode_uint, unexpected};
+use crate::permit::{PermitDetails, SignedPermit, UnorderedNonce, PERMIT2_ADDRESS};
+use crate::wallet::FairWallet;
+use ethers::abi::{ParamType, Token};
+use ethers::providers::Middleware;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Signature, TransactionRequest, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub use crate::permit::{Erc2612Permit, DEFAULT_PERMIT_VERSION};
+
+/// 查找 Permit2 未使用 nonce 时最多扫描的位图字数
+pub const MAX_NONCE_WORDS: u64 = 16;
+
+/// 金库中的持仓
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultPosition {
+    /// 金库份额
+    pub shares: U256,
+    /// 份额按当前汇率折算的底层资产
+    pub assets: U256,
+    /// 当前可取出的最大资产
+    pub max_withdraw: U256,
+}
+
+/// DeFi 客户端
+///
+/// 只读查询可使用任意 provider，发送交易需要带签名的中间件（如 `SignerMiddleware`）。
+pub struct DefiClient<M> {
+    client: Arc<M>,
+}
+
+impl<M: Middleware> DefiClient<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self { client }
+    }
+
+    /// ERC-20 `allowance`
+    pub async fn allowance(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<U256, ClientError> {
+        let output = self
+            .call(
+                token,
+                "allowance(address,address)",
+                &[Token::Address(owner), Token::Address(spender)],
+            )
+            .await?;
+        decode_uint(&output)
+    }
+
+    /// ERC-20 `approve`，返回交易哈希
+    pub async fn approve(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+        amount: U256,
+    ) -> Result<H256, ClientError> {
+        self.send(
+            token,
+            owner,
+            "approve(address,uint256)",
+            &[Token::Address(spender), Token::Uint(amount)],
+        )
+        .await
+    }
+
+    /// 授权额度不足 `amount` 时发送 approve，额度已足够时返回 `None`
+    ///
+    /// 部分代币（如 USDT）要求先把非零额度清零才能修改，`reset_first` 为真时先授权为 0。
+    pub async fn ensure_allowance(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+        amount: U256,
+        reset_first: bool,
+    ) -> Result<Option<H256>, ClientError> {
+        let current = self.allowance(token, owner, spender).await?;
+        if current >= amount {
+            return Ok(None);
+        }
+        if reset_first && !current.is_zero() {
+            self.approve(token, owner, spender, U256::zero()).await?;
+        }
+        self.approve(token, owner, spender, amount).await.map(Some)
+    }
+
+    /// 撤销授权
+    pub async fn revoke(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<H256, ClientError> {
+        self.approve(token, owner, spender, U256::zero()).await
+    }
+
+    /// ERC-20 `balanceOf`，对金库即份额余额
+    pub async fn balance_of(&self, token: Address, owner: Address) -> Result<U256, ClientError> {
+        let output = self
+            .call(token, "balanceOf(address)", &[Token::Address(owner)])
+            .await?;
+        decode_uint(&output)
+    }
+
+    /// ERC-4626 底层资产合约
+    pub async fn vault_asset(&self, vault: Address) -> Result<Address, ClientError> {
+        let output = self.call(vault, "asset()", &[]).await?;
+        match decode_single(ParamType::Address, &output)? {
+            Token::Address(asset) => Ok(asset),
+            token => Err(unexpected(token)),
+        }
+    }
+
+    /// ERC-4626 金库管理的资产总量
+    pub async fn total_assets(&self, vault: Address) -> Result<U256, ClientError> {
+        let output = self.call(vault, "totalAssets()", &[]).await?;
+        decode_uint(&output)
+    }
+
+    /// 存入 `assets` 可得的份额
+    pub async fn preview_deposit(&self, vault: Address, assets: U256) -> Result<U256, ClientError> {
+        self.vault_query(vault, "previewDeposit(uint256)", assets)
+            .await
+    }
+
+    /// 铸造 `shares` 需要的资产
+    pub async fn preview_mint(&self, vault: Address, shares: U256) -> Result<U256, ClientError> {
+        self.vault_query(vault, "previewMint(uint256)", shares)
+            .await
+    }
+
+    /// 取出 `assets` 需要销毁的份额
+    pub async fn preview_withdraw(
+        &self,
+        vault: Address,
+        assets: U256,
+    ) -> Result<U256, ClientError> {
+        self.vault_query(vault, "previewWithdraw(uint256)", assets)
+            .await
+    }
+
+    /// 赎回 `shares` 可得的资产
+    pub async fn preview_redeem(&self, vault: Address, shares: U256) -> Result<U256, ClientError> {
+        self.vault_query(vault, "previewRedeem(uint256)", shares)
+            .await
+    }
+
+    /// 份额按当前汇率折算的资产，不含手续费
+    pub async fn convert_to_assets(
+        &self,
+        vault: Address,
+        shares: U256,
+    ) -> Result<U256, ClientError> {
+        self.vault_query(vault, "convertToAssets(uint256)", shares)
+            .await
+    }
+
+    /// 地址在金库中的持仓
+    pub async fn position(
+        &self,
+        vault: Address,
+        owner: Address,
+    ) -> Result<VaultPosition, ClientError> {
+        let shares = self.balance_of(vault, owner).await?;
+        let assets = self.convert_to_assets(vault, shares).await?;
+        let output = self
+            .call(vault, "maxWithdraw(address)", &[Token::Address(owner)])
+            .await?;
+        Ok(VaultPosition {
+            shares,
+            assets,
+            max_withdraw: decode_uint(&output)?,
+        })
+    }
+
+    /// 存入资产，需要事先授权金库使用底层资产
+    pub async fn deposit(
+        &self,
+        vault: Address,
+        from: Address,
+        assets: U256,
+        receiver: Address,
+    ) -> Result<H256, ClientError> {
+        self.send(
+            vault,
+            from,
+            "deposit(uint256,address)",
+            &[Token::Uint(assets), Token::Address(receiver)],
+        )
+        .await
+    }
+
+    /// 按资产数量取出
+    pub async fn withdraw(
+        &self,
+        vault: Address,
+        from: Address,
+        assets: U256,
+        receiver: Address,
+        owner: Address,
+    ) -> Result<H256, ClientError> {
+        self.send(
+            vault,
+            from,
+            "withdraw(uint256,address,address)",
+            &[
+                Token::Uint(assets),
+                Token::Address(receiver),
+                Token::Address(owner),
+            ],
+        )
+        .await
+    }
+
+    /// 按份额数量赎回
+    pub async fn redeem(
+        &self,
+        vault: Address,
+        from: Address,
+        shares: U256,
+        receiver: Address,
+        owner: Address,
+    ) -> Result<H256, ClientError> {
+        self.send(
+            vault,
+            from,
+            "redeem(uint256,address,address)",
+            &[
+                Token::Uint(shares),
+                Token::Address(receiver),
+                Token::Address(owner),
+            ],
+        )
+        .await
+    }
+
+    /// 读取代币的 permit 参数并构造消息
+    ///
+    /// 按链上 `DOMAIN_SEPARATOR` 校验域参数，避免签出链上无法验证的 permit。
+    pub async fn permit_message(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<Erc2612Permit, ClientError> {
+        let name = match decode_single(ParamType::String, &self.call(token, "name()", &[]).await?)?
+        {
+            Token::String(name) => name,
+            token => return Err(unexpected(token)),
+        };
+        let version = match self.call(token, "version()", &[]).await {
+            Ok(output) => match decode_single(ParamType::String, &output)? {
+                Token::String(version) => version,
+                token => return Err(unexpected(token)),
+            },
+            Err(_) => DEFAULT_PERMIT_VERSION.to_string(),
+        };
+        let nonce = decode_uint(
+            &self
+                .call(token, "nonces(address)", &[Token::Address(owner)])
+                .await?,
+        )?;
+        let chain_id = self
+            .client
+            .get_chainid()
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))?
+            .as_u64();
+        let permit = Erc2612Permit {
+            token,
+            name,
+            version,
+            chain_id,
+            owner,
+            spender,
+            value,
+            nonce,
+            deadline,
+        };
+
+        let expected = match decode_single(
+            ParamType::FixedBytes(32),
+            &self.call(token, "DOMAIN_SEPARATOR()", &[]).await?,
+        )? {
+            Token::FixedBytes(bytes) => H256::from_slice(&bytes),
+            token => return Err(unexpected(token)),
+        };
+        if permit.domain_separator()? != expected {
+            return Err(ClientError::Other(format!(
+                "代币 {:?} 的 EIP-712 域与名称/版本不匹配",
+                token
+            )));
+        }
+        Ok(permit)
+    }
+
+    /// 用钱包签名 permit，返回消息和签名
+    pub async fn sign_permit(
+        &self,
+        wallet: &FairWallet,
+        token: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<(Erc2612Permit, Signature), ClientError> {
+        let signed = self
+            .signed_permit(wallet, token, spender, value, deadline)
+            .await?;
+        Ok((signed.message, signed.signature))
+    }
+
+    /// 用钱包签名 permit，返回可校验签名者的 [`SignedPermit`]
+    pub async fn signed_permit(
+        &self,
+        wallet: &FairWallet,
+        token: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<SignedPermit<Erc2612Permit>, ClientError> {
+        let owner = wallet
+            .address()
+            .await
+            .map_err(|e| ClientError::Other(e.to_string()))?;
+        let permit = self
+            .permit_message(token, owner, spender, value, deadline)
+            .await?;
+        Ok(SignedPermit::sign(wallet, permit).await?)
+    }
+
+    /// 提交 permit，`from` 可以是任意付费地址
+    pub async fn submit_permit(
+        &self,
+        from: Address,
+        permit: &Erc2612Permit,
+        signature: &Signature,
+    ) -> Result<H256, ClientError> {
+        let tx = TransactionRequest::new()
+            .from(from)
+            .to(permit.token)
+            .data(permit.calldata(signature));
+        let pending = self
+            .client
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+        Ok(pending.tx_hash())
+    }
+
+    /// 提交 [`Self::signed_permit`] 签出的 permit
+    pub async fn submit_signed_permit(
+        &self,
+        from: Address,
+        permit: &SignedPermit<Erc2612Permit>,
+    ) -> Result<H256, ClientError> {
+        self.submit_permit(from, &permit.message, &permit.signature)
+            .await
+    }
+
+    /// Permit2 中 `owner` 给 `spender` 的代币授权，`nonce` 为下一个 `PermitSingle` 应使用的值
+    pub async fn permit2_allowance(
+        &self,
+        owner: Address,
+        token: Address,
+        spender: Address,
+    ) -> Result<PermitDetails, ClientError> {
+        let output = self
+            .call(
+                PERMIT2_ADDRESS,
+                "allowance(address,address,address)",
+                &[
+                    Token::Address(owner),
+                    Token::Address(token),
+                    Token::Address(spender),
+                ],
+            )
+            .await?;
+        let tokens = ethers::abi::decode(
+            &[
+                ParamType::Uint(160),
+                ParamType::Uint(48),
+                ParamType::Uint(48),
+            ],
+            &output,
+        )
+        .map_err(|e| ClientError::Other(format!("解码返回值失败: {}", e)))?;
+        match tokens.as_slice() {
+            [Token::Uint(amount), Token::Uint(expiration), Token::Uint(nonce)] => {
+                Ok(PermitDetails {
+                    token,
+                    amount: *amount,
+                    expiration: expiration.as_u64(),
+                    nonce: nonce.as_u64(),
+                })
+            }
+            _ => Err(ClientError::Other(format!("意外的返回值: {:?}", tokens))),
+        }
+    }
+
+    /// 第一个未使用的 Permit2 无序 nonce，从位图字 `start_word` 开始查找
+    pub async fn next_permit2_nonce(
+        &self,
+        owner: Address,
+        start_word: U256,
+    ) -> Result<U256, ClientError> {
+        for offset in 0..MAX_NONCE_WORDS {
+            let word = start_word + offset;
+            let output = self
+                .call(
+                    PERMIT2_ADDRESS,
+                    "nonceBitmap(address,uint256)",
+                    &[Token::Address(owner), Token::Uint(word)],
+                )
+                .await?;
+            if let Some(nonce) = UnorderedNonce::first_unused(word, decode_uint(&output)?) {
+                return Ok(nonce.nonce());
+            }
+        }
+        Err(ClientError::Other(format!(
+            "{} 个位图字内没有未使用的 nonce",
+            MAX_NONCE_WORDS
+        )))
+    }
+
+    async fn vault_query(
+        &self,
+        vault: Address,
+        signature: &str,
+        amount: U256,
+    ) -> Result<U256, ClientError> {
+        let output = self.call(vault, signature, &[Token::Uint(amount)]).await?;
+        decode_uint(&output)
+    }
+
+    async fn call(
+        &self,
+        contract: Address,
+        signature: &str,
+        args: &[Token],
+    ) -> Result<Bytes, ClientError> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(contract)
+            .data(calldata(signature, args))
+            .into();
+        self.client
+            .call(&tx, None)
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))
+    }
+
+    async fn send(
+        &self,
+        contract: Address,
+        from: Address,
+        signature: &str,
+        args: &[Token],
+    ) -> Result<H256, ClientError> {
+        let tx = TransactionRequest::new()
+            .from(from)
+            .to(contract)
+            .data(calldata(signature, args));
+        let pending = self
+            .client
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+        Ok(pending.tx_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permit::PermitMessage;
+    use ethers::types::transaction::eip712::Eip712;
+
+    fn permit(owner: Address) -> Erc2612Permit {
+        Erc2612Permit {
+            token: Address::repeat_byte(0xaa),
+            name: "Fair USD".to_string(),
+            version: DEFAULT_PERMIT_VERSION.to_string(),
+            chain_id: 1337,
+            owner,
+            spender: Address::repeat_byte(0xbb),
+            value: U256::exp10(18),
+            nonce: U256::zero(),
+            deadline: U256::from(1_900_000_000u64),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_permit_typed_data() {
+        let wallet = FairWallet::from_private_key(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            1337,
+        )
+        .unwrap();
+        let owner = wallet.address().await.unwrap();
+        let permit = permit(owner);
+        let typed_data = permit.typed_data().unwrap();
+        assert_eq!(typed_data.primary_type, "Permit");
+
+        let signature = wallet.sign_typed_data(&typed_data).await.unwrap();
+        let digest = typed_data.encode_eip712().unwrap();
+        assert_eq!(signature.recover(H256(digest)).unwrap(), owner);
+
+        // 修改任何字段都会改变摘要
+        let mut other = permit.clone();
+        other.nonce = U256::one();
+        assert_ne!(other.typed_data().unwrap().encode_eip712().unwrap(), digest);
+    }
+
+    #[tokio::test]
+    async fn test_signed_permit_recover() {
+        let wallet = FairWallet::from_private_key(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            1337,
+        )
+        .unwrap();
+        let owner = wallet.address().await.unwrap();
+        let signed = SignedPermit::sign(&wallet, permit(owner)).await.unwrap();
+        assert_eq!(signed.recover().unwrap(), owner);
+        assert_eq!(
+            signed.message.digest().unwrap(),
+            H256(
+                signed
+                    .message
+                    .typed_data()
+                    .unwrap()
+                    .encode_eip712()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_permit_calldata() {
+        let permit = permit(Address::repeat_byte(0x11));
+        let signature = Signature {
+            r: U256::from(1),
+            s: U256::from(2),
+            v: 27,
+        };
+        let data = permit.calldata(&signature);
+        assert_eq!(
+            &data[..4],
+            &ethers::utils::id("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)")
+        );
+        assert_eq!(data.len(), 4 + 7 * 32);
+        // v 位于第 5 个参数
+        assert_eq!(data[4 + 5 * 32 - 1], 27);
+    }
+}
@@ -0,0 +1,251 @@
+//! 充值确认
+//!
+//! 托管方用节点返回的包含证明（`fairvm_getInclusionProof`）对照自己信任的区块头确认充值，
+//! 不需要信任 RPC 节点返回的交易和收据。可信区块头可以来自自建的全节点，也可以来自
+//! `fair_vm::light::LightClient` 同步并校验过的区块头链。
+
+use crate::nft::{decode_uint, event_topic, topic_address};
+use ethers::types::{Address, H256, U256};
+use fair_vm::blockchain::BlockHeader;
+pub use fair_vm::inclusion::InclusionProof;
+pub use fair_vm::state_proof::ProofError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// ERC-20 `Transfer(address,address,uint256)`
+const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+
+/// 充值确认错误类型
+#[derive(Debug, Error)]
+pub enum DepositError {
+    #[error("包含证明无效: {0}")]
+    Proof(#[from] ProofError),
+
+    #[error("交易 {0:?} 执行失败")]
+    Failed(H256),
+
+    #[error("确认数不足: 需要 {required}，当前 {actual}")]
+    NotConfirmed { required: u64, actual: u64 },
+}
+
+/// 经证明确认的一笔充值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deposit {
+    pub transaction_hash: H256,
+    pub block_number: u64,
+    pub from: Address,
+    /// 代币合约，原生币充值为空
+    pub token: Option<Address>,
+    pub amount: U256,
+    /// 代币转账事件在区块中的序号，原生币充值为空
+    pub log_index: Option<u64>,
+}
+
+/// 对照可信区块头验证证明，返回交易中转给 `recipient` 的原生币和 ERC-20 充值
+///
+/// 执行失败的交易没有转账效果，返回 [`DepositError::Failed`]。
+pub fn verify_deposits(
+    proof: &InclusionProof,
+    header: &BlockHeader,
+    recipient: Address,
+) -> Result<Vec<Deposit>, DepositError> {
+    proof.verify(header)?;
+    let tx = &proof.transaction;
+    if !proof.receipt.status {
+        return Err(DepositError::Failed(tx.hash));
+    }
+
+    let mut deposits = Vec::new();
+    let to = tx.to.map(|to| Address::from(to.0));
+    if to == Some(recipient) && !tx.value.is_zero() {
+        deposits.push(Deposit {
+            transaction_hash: tx.hash,
+            block_number: proof.block_number,
+            from: Address::from(tx.from.0),
+            token: None,
+            amount: tx.value,
+            log_index: None,
+        });
+    }
+    let transfer = event_topic(TRANSFER_EVENT);
+    for log in &proof.receipt.logs {
+        // ERC-721 的 Transfer 签名相同但 tokenId 已索引，有 4 个主题
+        if log.topics.len() != 3 || log.topics[0] != transfer {
+            continue;
+        }
+        if topic_address(&log.topics[2]) != recipient {
+            continue;
+        }
+        let Ok(amount) = decode_uint(&log.data) else {
+            continue;
+        };
+        deposits.push(Deposit {
+            transaction_hash: tx.hash,
+            block_number: proof.block_number,
+            from: topic_address(&log.topics[1]),
+            token: Some(Address::from(log.address.0)),
+            amount,
+            log_index: Some(log.log_index),
+        });
+    }
+    Ok(deposits)
+}
+
+/// 检查充值所在区块在可信链头 `head` 之下至少有 `required` 个确认，所在区块本身算一个
+pub fn check_confirmations(
+    proof: &InclusionProof,
+    head: u64,
+    required: u64,
+) -> Result<(), DepositError> {
+    let actual = (head + 1).saturating_sub(proof.block_number);
+    if actual < required {
+        return Err(DepositError::NotConfirmed { required, actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fair_vm::account::Address as FairAddress;
+    use fair_vm::blockchain::Block;
+    use fair_vm::merkle;
+    use fair_vm::receipt::{Receipt, ReceiptContext};
+    use fair_vm::transaction::{Transaction, TransactionType};
+    use fair_vm_core::vm::ExecutionResult;
+
+    const CUSTODY: [u8; 20] = [9u8; 20];
+    const TOKEN: [u8; 20] = [5u8; 20];
+
+    fn address_topic(address: [u8; 20]) -> H256 {
+        H256::from(Address::from(address))
+    }
+
+    fn proof(status: bool) -> (InclusionProof, BlockHeader) {
+        let native = Transaction::new(
+            H256::from_low_u64_be(1),
+            FairAddress([1u8; 20]),
+            Some(FairAddress(CUSTODY)),
+            U256::from(500),
+            0,
+            21_000,
+            Some(U256::zero()),
+            Vec::new(),
+            vec![1u8; 65],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        let mut token = native.clone();
+        token.hash = H256::from_low_u64_be(2);
+        token.to = Some(FairAddress(TOKEN));
+        token.value = U256::zero();
+        token.signature = vec![2u8; 65];
+        let transactions = vec![native, token];
+
+        let mut amount = [0u8; 32];
+        U256::from(70).to_big_endian(&mut amount);
+        let logs = vec![
+            (
+                FairAddress(TOKEN),
+                vec![
+                    event_topic(TRANSFER_EVENT),
+                    address_topic([1u8; 20]),
+                    address_topic(CUSTODY),
+                ],
+                amount.to_vec(),
+            ),
+            // 转给其他地址的不计入
+            (
+                FairAddress(TOKEN),
+                vec![
+                    event_topic(TRANSFER_EVENT),
+                    address_topic([1u8; 20]),
+                    address_topic([3u8; 20]),
+                ],
+                amount.to_vec(),
+            ),
+        ];
+        let result = ExecutionResult {
+            gas_used: 21_000,
+            return_data: Vec::new(),
+            status,
+            gas_refunded: 0,
+        };
+        let mut context = ReceiptContext {
+            block_hash: H256::zero(),
+            block_number: 3,
+            transaction_index: 0,
+            base_fee: U256::zero(),
+            cumulative_gas_before: 0,
+            log_index_before: 0,
+        };
+        let mut receipts = Vec::new();
+        for (index, tx) in transactions.iter().enumerate() {
+            context.transaction_index = index as u64;
+            let logs = if index == 1 { logs.clone() } else { Vec::new() };
+            let receipt = Receipt::from_execution(tx, &result, &context, logs, 0);
+            context.cumulative_gas_before = receipt.cumulative_gas_used;
+            receipts.push(receipt);
+        }
+        let header = BlockHeader {
+            parent_hash: H256::zero(),
+            number: 3,
+            timestamp: 0,
+            transactions_root: merkle::transactions_root(&transactions),
+            state_root: H256::zero(),
+            receipts_root: merkle::receipts_root(&receipts),
+            difficulty: 0,
+            block_reward: 0,
+            proposer: None,
+            arrivals_root: H256::zero(),
+        };
+        let block = Block {
+            header: header.clone(),
+            transactions,
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        let proof = InclusionProof::build(&block, &receipts, &H256::from_low_u64_be(2)).unwrap();
+        (proof, header)
+    }
+
+    #[test]
+    fn test_token_deposit() {
+        let (proof, header) = proof(true);
+        let deposits = verify_deposits(&proof, &header, Address::from(CUSTODY)).unwrap();
+        assert_eq!(deposits.len(), 1);
+        assert_eq!(deposits[0].token, Some(Address::from(TOKEN)));
+        assert_eq!(deposits[0].from, Address::from([1u8; 20]));
+        assert_eq!(deposits[0].amount, U256::from(70));
+        assert_eq!(deposits[0].log_index, Some(0));
+
+        // 节点篡改收据中的金额
+        let mut forged = proof.clone();
+        forged.receipt.logs[0].data[31] = 0xff;
+        assert!(matches!(
+            verify_deposits(&forged, &header, Address::from(CUSTODY)),
+            Err(DepositError::Proof(_))
+        ));
+
+        assert!(check_confirmations(&proof, 3, 1).is_ok());
+        assert!(matches!(
+            check_confirmations(&proof, 4, 6),
+            Err(DepositError::NotConfirmed {
+                required: 6,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_failed_transaction() {
+        let (proof, header) = proof(false);
+        assert!(matches!(
+            verify_deposits(&proof, &header, Address::from(CUSTODY)),
+            Err(DepositError::Failed(_))
+        ));
+    }
+}
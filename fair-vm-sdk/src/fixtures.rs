@@ -0,0 +1,270 @@
+//! 确定性测试夹具
+//!
+//! 由种子派生固定的私钥、地址、已签名交易和类型化数据，下游项目无需连接节点即可编写可复现的
+//! 测试。第 `index` 个私钥为 `keccak256("fairvm-fixture" || seed || index)`，结果不是有效私钥时
+//! 继续对其哈希。签名使用 RFC 6979 确定性随机数，相同输入总是得到相同的签名、原始交易和哈希。
+//! 夹具私钥可由种子公开推导，切勿用于真实资产。
+
+use crate::wallet::FairWallet;
+use crate::SdkConfig;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Address, Bytes, Eip1559TransactionRequest, Signature, H256, U256};
+use ethers::utils::keccak256;
+use serde_json::json;
+
+/// 夹具交易的 gas 上限
+pub const FIXTURE_GAS_LIMIT: u64 = 21_000;
+
+/// 夹具交易的最大费用（wei）
+pub const FIXTURE_MAX_FEE_PER_GAS: u64 = 50_000_000_000;
+
+/// 夹具交易的优先费用（wei）
+pub const FIXTURE_PRIORITY_FEE_PER_GAS: u64 = 1_000_000_000;
+
+/// 夹具错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum FixtureError {
+    #[error("签名失败: {0}")]
+    Signing(String),
+
+    #[error("类型化数据错误: {0}")]
+    TypedData(String),
+
+    #[error("钱包错误: {0}")]
+    Wallet(String),
+}
+
+/// 已签名的交易夹具
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedTransactionFixture {
+    pub from: Address,
+    pub transaction: TypedTransaction,
+    pub signature: Signature,
+    /// 签名后的原始交易，可直接用于 `eth_sendRawTransaction`
+    pub raw: Bytes,
+    pub hash: H256,
+}
+
+/// 由种子派生的测试夹具
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixtures {
+    seed: Vec<u8>,
+    chain_id: u64,
+}
+
+impl Fixtures {
+    /// 由种子创建夹具，链 ID 为 SDK 默认值
+    pub fn new(seed: impl AsRef<[u8]>) -> Self {
+        Self {
+            seed: seed.as_ref().to_vec(),
+            chain_id: SdkConfig::default().chain_id,
+        }
+    }
+
+    /// 设置签名使用的链 ID
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// 链 ID
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// 第 `index` 个私钥
+    pub fn secret_key(&self, index: u32) -> [u8; 32] {
+        let mut preimage = b"fairvm-fixture".to_vec();
+        preimage.extend_from_slice(&self.seed);
+        preimage.extend_from_slice(&index.to_be_bytes());
+        let mut key = keccak256(&preimage);
+        // 落在曲线阶之外或为零的概率可以忽略，仍按规则继续哈希
+        while LocalWallet::from_bytes(&key).is_err() {
+            key = keccak256(key);
+        }
+        key
+    }
+
+    /// 第 `index` 个私钥的十六进制表示（不带 0x）
+    pub fn private_key_hex(&self, index: u32) -> String {
+        hex::encode(self.secret_key(index))
+    }
+
+    /// 第 `index` 个签名者
+    pub fn signer(&self, index: u32) -> LocalWallet {
+        LocalWallet::from_bytes(&self.secret_key(index))
+            .expect("夹具私钥已校验")
+            .with_chain_id(self.chain_id)
+    }
+
+    /// 第 `index` 个地址
+    pub fn address(&self, index: u32) -> Address {
+        self.signer(index).address()
+    }
+
+    /// 前 `count` 个地址
+    pub fn addresses(&self, count: u32) -> Vec<Address> {
+        (0..count).map(|index| self.address(index)).collect()
+    }
+
+    /// 第 `index` 个私钥对应的钱包
+    pub fn wallet(&self, index: u32) -> Result<FairWallet, FixtureError> {
+        FairWallet::from_private_key(&self.private_key_hex(index), self.chain_id)
+            .map_err(|e| FixtureError::Wallet(e.to_string()))
+    }
+
+    /// 由第 `index` 个签名者签名交易，未设置链 ID 时使用夹具的链 ID
+    pub fn sign_transaction(
+        &self,
+        index: u32,
+        mut transaction: TypedTransaction,
+    ) -> Result<SignedTransactionFixture, FixtureError> {
+        let signer = self.signer(index);
+        if transaction.chain_id().is_none() {
+            transaction.set_chain_id(self.chain_id);
+        }
+        transaction.set_from(signer.address());
+        let signature = signer
+            .sign_transaction_sync(&transaction)
+            .map_err(|e| FixtureError::Signing(e.to_string()))?;
+        let raw = transaction.rlp_signed(&signature);
+        Ok(SignedTransactionFixture {
+            from: signer.address(),
+            hash: H256(keccak256(&raw)),
+            transaction,
+            signature,
+            raw,
+        })
+    }
+
+    /// 第 `from` 个账户向第 `to` 个账户转账的 EIP-1559 交易，费用字段固定
+    pub fn transfer(
+        &self,
+        from: u32,
+        to: u32,
+        nonce: u64,
+        value: U256,
+    ) -> Result<SignedTransactionFixture, FixtureError> {
+        let request = Eip1559TransactionRequest::new()
+            .to(self.address(to))
+            .value(value)
+            .nonce(nonce)
+            .gas(FIXTURE_GAS_LIMIT)
+            .max_fee_per_gas(FIXTURE_MAX_FEE_PER_GAS)
+            .max_priority_fee_per_gas(FIXTURE_PRIORITY_FEE_PER_GAS)
+            .chain_id(self.chain_id);
+        self.sign_transaction(from, request.into())
+    }
+
+    /// 第 `from` 个账户向第 `to` 个账户授权 `amount` 的 EIP-712 类型化数据
+    pub fn typed_data(
+        &self,
+        from: u32,
+        to: u32,
+        amount: U256,
+        nonce: u64,
+    ) -> Result<TypedData, FixtureError> {
+        serde_json::from_value(json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" },
+                ],
+                "Approval": [
+                    { "name": "owner", "type": "address" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "amount", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                ],
+            },
+            "primaryType": "Approval",
+            "domain": {
+                "name": "FairVM Fixtures",
+                "version": "1",
+                "chainId": self.chain_id,
+                "verifyingContract": self.contract_address(),
+            },
+            "message": {
+                "owner": self.address(from),
+                "spender": self.address(to),
+                "amount": amount,
+                "nonce": nonce,
+            },
+        }))
+        .map_err(|e| FixtureError::TypedData(e.to_string()))
+    }
+
+    /// 由第 `index` 个签名者签名类型化数据
+    pub fn sign_typed_data(
+        &self,
+        index: u32,
+        typed_data: &TypedData,
+    ) -> Result<Signature, FixtureError> {
+        let digest = typed_data
+            .encode_eip712()
+            .map_err(|e| FixtureError::TypedData(e.to_string()))?;
+        self.signer(index)
+            .sign_hash(H256(digest))
+            .map_err(|e| FixtureError::Signing(e.to_string()))
+    }
+
+    /// 由种子派生的合约地址，用作类型化数据的验证合约
+    pub fn contract_address(&self) -> Address {
+        let mut preimage = b"fairvm-fixture-contract".to_vec();
+        preimage.extend_from_slice(&self.seed);
+        Address::from_slice(&keccak256(&preimage)[12..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_keys() {
+        let fixtures = Fixtures::new("seed");
+        assert_eq!(fixtures.addresses(3), Fixtures::new("seed").addresses(3));
+        assert_ne!(fixtures.address(0), fixtures.address(1));
+        assert_ne!(fixtures.address(0), Fixtures::new("other").address(0));
+        assert_eq!(fixtures.private_key_hex(0).len(), 64);
+    }
+
+    #[test]
+    fn test_signed_transfer() {
+        let fixtures = Fixtures::new([1u8; 32]).with_chain_id(43112);
+        let tx = fixtures.transfer(0, 1, 7, U256::from(1_000)).unwrap();
+        assert_eq!(tx, fixtures.transfer(0, 1, 7, U256::from(1_000)).unwrap());
+        assert_eq!(tx.from, fixtures.address(0));
+        assert_eq!(tx.transaction.chain_id(), Some(43112u64.into()));
+        assert_eq!(
+            tx.signature.recover(tx.transaction.sighash()).unwrap(),
+            fixtures.address(0)
+        );
+        assert_ne!(
+            tx.hash,
+            fixtures.transfer(0, 1, 8, U256::from(1_000)).unwrap().hash
+        );
+    }
+
+    #[tokio::test]
+    async fn test_typed_data() {
+        let fixtures = Fixtures::new("typed");
+        let typed_data = fixtures.typed_data(0, 1, U256::from(5), 0).unwrap();
+        let signature = fixtures.sign_typed_data(0, &typed_data).unwrap();
+        assert_eq!(signature, fixtures.sign_typed_data(0, &typed_data).unwrap());
+        let digest = H256(typed_data.encode_eip712().unwrap());
+        assert_eq!(signature.recover(digest).unwrap(), fixtures.address(0));
+
+        // 钱包签名与夹具签名一致
+        let wallet = fixtures.wallet(0).unwrap();
+        assert_eq!(wallet.address().await.unwrap(), fixtures.address(0));
+        assert_eq!(
+            wallet.sign_typed_data(&typed_data).await.unwrap(),
+            signature
+        );
+    }
+}
@@ -1,7 +1,16 @@
 //! FairVM SDK for interacting with FairVM blockchain.
 
+pub mod address;
+pub mod approvals;
 pub mod client;
+pub mod defi;
+pub mod deposit;
+pub mod fixtures;
+pub mod nft;
+pub mod permit;
+pub mod units;
 pub mod wallet;
+pub mod walletconnect;
 
 /// 版本号
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
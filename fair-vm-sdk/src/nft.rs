@@ -0,0 +1,643 @@
+//! NFT 辅助工具
+//!
+//! 封装 ERC-721 / ERC-1155 的常用调用：所有权与余额查询、安全转账、
+//! 基于 Transfer 事件扫描的可续传枚举，以及元数据获取。
+
+use crate::client::ClientError;
+use base64::Engine;
+use ethers::abi::{self, ParamType, Token};
+use ethers::providers::Middleware;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Filter, Log, TransactionRequest, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 每次扫描的默认区块数
+pub const DEFAULT_SCAN_BLOCKS: u64 = 2_000;
+
+/// 默认 IPFS 网关
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+/// ERC-721 `Transfer(address,address,uint256)`
+const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+
+/// ERC-1155 `TransferSingle(address,address,address,uint256,uint256)`
+const TRANSFER_SINGLE_EVENT: &str = "TransferSingle(address,address,address,uint256,uint256)";
+
+/// ERC-1155 `TransferBatch(address,address,address,uint256[],uint256[])`
+const TRANSFER_BATCH_EVENT: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+
+/// NFT 标准
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+/// 一次 NFT 转移
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftTransfer {
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    pub log_index: u64,
+    /// ERC-1155 的操作者，ERC-721 为空
+    pub operator: Option<Address>,
+    pub from: Address,
+    pub to: Address,
+    pub token_id: U256,
+    /// 转移数量，ERC-721 固定为 1
+    pub amount: U256,
+}
+
+/// 扫描游标，保存后可从中断处继续枚举
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferCursor {
+    /// 下一次扫描的起始区块
+    pub next_block: u64,
+}
+
+impl TransferCursor {
+    /// 从指定区块开始扫描，通常为合约部署区块
+    pub fn from_block(block: u64) -> Self {
+        Self { next_block: block }
+    }
+}
+
+/// 一页扫描结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferPage {
+    pub transfers: Vec<NftTransfer>,
+    /// 下一页的游标
+    pub cursor: TransferCursor,
+    /// 是否已扫描到链头
+    pub done: bool,
+}
+
+/// NFT 元数据（ERC-721 / ERC-1155 元数据 JSON）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NftMetadata {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<serde_json::Value>,
+    /// 其余字段
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// NFT 客户端
+///
+/// 只读查询可使用任意 provider，转账需要带签名的中间件（如 `SignerMiddleware`）。
+pub struct NftClient<M> {
+    client: Arc<M>,
+    http: reqwest::Client,
+    ipfs_gateway: String,
+    scan_blocks: u64,
+}
+
+impl<M: Middleware> NftClient<M> {
+    pub fn new(client: Arc<M>) -> Self {
+        Self {
+            client,
+            http: reqwest::Client::new(),
+            ipfs_gateway: DEFAULT_IPFS_GATEWAY.to_string(),
+            scan_blocks: DEFAULT_SCAN_BLOCKS,
+        }
+    }
+
+    /// 设置 IPFS 网关
+    pub fn with_ipfs_gateway(mut self, gateway: &str) -> Self {
+        self.ipfs_gateway = gateway.to_string();
+        self
+    }
+
+    /// 设置每页扫描的区块数
+    pub fn with_scan_blocks(mut self, scan_blocks: u64) -> Self {
+        self.scan_blocks = scan_blocks.max(1);
+        self
+    }
+
+    /// ERC-721 `ownerOf`
+    pub async fn owner_of(
+        &self,
+        contract: Address,
+        token_id: U256,
+    ) -> Result<Address, ClientError> {
+        let output = self
+            .call(contract, "ownerOf(uint256)", &[Token::Uint(token_id)])
+            .await?;
+        match decode_single(ParamType::Address, &output)? {
+            Token::Address(owner) => Ok(owner),
+            token => Err(unexpected(token)),
+        }
+    }
+
+    /// ERC-721 `balanceOf`，返回持有的代币数
+    pub async fn balance_of(&self, contract: Address, owner: Address) -> Result<U256, ClientError> {
+        let output = self
+            .call(contract, "balanceOf(address)", &[Token::Address(owner)])
+            .await?;
+        decode_uint(&output)
+    }
+
+    /// ERC-1155 `balanceOf`，返回指定代币的数量
+    pub async fn balance_of_token(
+        &self,
+        contract: Address,
+        owner: Address,
+        token_id: U256,
+    ) -> Result<U256, ClientError> {
+        let output = self
+            .call(
+                contract,
+                "balanceOf(address,uint256)",
+                &[Token::Address(owner), Token::Uint(token_id)],
+            )
+            .await?;
+        decode_uint(&output)
+    }
+
+    /// ERC-721 `safeTransferFrom`，返回交易哈希
+    pub async fn safe_transfer_from(
+        &self,
+        contract: Address,
+        from: Address,
+        to: Address,
+        token_id: U256,
+    ) -> Result<H256, ClientError> {
+        self.send(
+            contract,
+            from,
+            "safeTransferFrom(address,address,uint256)",
+            &[
+                Token::Address(from),
+                Token::Address(to),
+                Token::Uint(token_id),
+            ],
+        )
+        .await
+    }
+
+    /// ERC-1155 `safeTransferFrom`，返回交易哈希
+    pub async fn safe_transfer_from_erc1155(
+        &self,
+        contract: Address,
+        from: Address,
+        to: Address,
+        token_id: U256,
+        amount: U256,
+        data: Bytes,
+    ) -> Result<H256, ClientError> {
+        self.send(
+            contract,
+            from,
+            "safeTransferFrom(address,address,uint256,uint256,bytes)",
+            &[
+                Token::Address(from),
+                Token::Address(to),
+                Token::Uint(token_id),
+                Token::Uint(amount),
+                Token::Bytes(data.to_vec()),
+            ],
+        )
+        .await
+    }
+
+    /// 扫描一页转移事件
+    ///
+    /// 从游标处最多扫描 `scan_blocks` 个区块，不超过链头。
+    pub async fn transfers(
+        &self,
+        contract: Address,
+        standard: NftStandard,
+        cursor: TransferCursor,
+    ) -> Result<TransferPage, ClientError> {
+        let head = self
+            .client
+            .get_block_number()
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))?
+            .as_u64();
+        if cursor.next_block > head {
+            return Ok(TransferPage {
+                transfers: Vec::new(),
+                cursor,
+                done: true,
+            });
+        }
+
+        let to_block = head.min(cursor.next_block.saturating_add(self.scan_blocks - 1));
+        let topics = match standard {
+            NftStandard::Erc721 => vec![event_topic(TRANSFER_EVENT)],
+            NftStandard::Erc1155 => vec![
+                event_topic(TRANSFER_SINGLE_EVENT),
+                event_topic(TRANSFER_BATCH_EVENT),
+            ],
+        };
+        let filter = Filter::new()
+            .address(contract)
+            .topic0(topics)
+            .from_block(cursor.next_block)
+            .to_block(to_block);
+        let logs = self
+            .client
+            .get_logs(&filter)
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))?;
+
+        let mut transfers = Vec::new();
+        for log in &logs {
+            transfers.extend(decode_transfer_log(log)?);
+        }
+        Ok(TransferPage {
+            transfers,
+            cursor: TransferCursor {
+                next_block: to_block + 1,
+            },
+            done: to_block == head,
+        })
+    }
+
+    /// 通过扫描转移事件列出地址当前持有的代币及数量
+    ///
+    /// `from_block` 应不晚于合约部署区块，否则结果不完整。
+    pub async fn tokens_of(
+        &self,
+        contract: Address,
+        standard: NftStandard,
+        owner: Address,
+        from_block: u64,
+    ) -> Result<Vec<(U256, U256)>, ClientError> {
+        let mut cursor = TransferCursor::from_block(from_block);
+        let mut transfers = Vec::new();
+        loop {
+            let page = self.transfers(contract, standard, cursor).await?;
+            transfers.extend(page.transfers);
+            cursor = page.cursor;
+            if page.done {
+                break;
+            }
+        }
+        Ok(holdings(&transfers, owner))
+    }
+
+    /// 代币元数据地址，ERC-1155 的 `{id}` 占位符会被替换
+    pub async fn token_uri(
+        &self,
+        contract: Address,
+        standard: NftStandard,
+        token_id: U256,
+    ) -> Result<String, ClientError> {
+        let signature = match standard {
+            NftStandard::Erc721 => "tokenURI(uint256)",
+            NftStandard::Erc1155 => "uri(uint256)",
+        };
+        let output = self
+            .call(contract, signature, &[Token::Uint(token_id)])
+            .await?;
+        match decode_single(ParamType::String, &output)? {
+            Token::String(uri) => Ok(expand_token_id(&uri, token_id)),
+            token => Err(unexpected(token)),
+        }
+    }
+
+    /// 获取代币元数据，支持 http(s)、ipfs 和 data URI
+    pub async fn metadata(
+        &self,
+        contract: Address,
+        standard: NftStandard,
+        token_id: U256,
+    ) -> Result<NftMetadata, ClientError> {
+        let uri = self.token_uri(contract, standard, token_id).await?;
+        self.fetch_metadata(&uri).await
+    }
+
+    /// 按地址获取元数据
+    pub async fn fetch_metadata(&self, uri: &str) -> Result<NftMetadata, ClientError> {
+        if let Some(metadata) = parse_data_uri(uri)? {
+            return Ok(metadata);
+        }
+        let url = resolve_uri(uri, &self.ipfs_gateway);
+        self.http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ClientError::NetworkError(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| ClientError::Other(format!("元数据格式错误: {}", e)))
+    }
+
+    async fn call(
+        &self,
+        contract: Address,
+        signature: &str,
+        args: &[Token],
+    ) -> Result<Bytes, ClientError> {
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(contract)
+            .data(calldata(signature, args))
+            .into();
+        self.client
+            .call(&tx, None)
+            .await
+            .map_err(|e| ClientError::NetworkError(e.to_string()))
+    }
+
+    async fn send(
+        &self,
+        contract: Address,
+        from: Address,
+        signature: &str,
+        args: &[Token],
+    ) -> Result<H256, ClientError> {
+        let tx = TransactionRequest::new()
+            .from(from)
+            .to(contract)
+            .data(calldata(signature, args));
+        let pending = self
+            .client
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+        Ok(pending.tx_hash())
+    }
+}
+
+/// 编码调用数据
+pub(crate) fn calldata(signature: &str, args: &[Token]) -> Bytes {
+    let mut data = ethers::utils::id(signature).to_vec();
+    data.extend(abi::encode(args));
+    data.into()
+}
+
+pub(crate) fn event_topic(signature: &str) -> H256 {
+    H256(ethers::utils::keccak256(signature))
+}
+
+pub(crate) fn decode_single(kind: ParamType, output: &[u8]) -> Result<Token, ClientError> {
+    abi::decode(&[kind], output)
+        .map_err(|e| ClientError::Other(format!("解码返回值失败: {}", e)))?
+        .pop()
+        .ok_or_else(|| ClientError::Other("返回值为空".to_string()))
+}
+
+pub(crate) fn decode_uint(output: &[u8]) -> Result<U256, ClientError> {
+    match decode_single(ParamType::Uint(256), output)? {
+        Token::Uint(value) => Ok(value),
+        token => Err(unexpected(token)),
+    }
+}
+
+pub(crate) fn unexpected(token: Token) -> ClientError {
+    ClientError::Other(format!("意外的返回值: {:?}", token))
+}
+
+pub(crate) fn topic_address(topic: &H256) -> Address {
+    Address::from_slice(&topic.as_bytes()[12..])
+}
+
+/// 解析转移事件，非转移事件返回空列表
+pub fn decode_transfer_log(log: &Log) -> Result<Vec<NftTransfer>, ClientError> {
+    let Some(topic0) = log.topics.first() else {
+        return Ok(Vec::new());
+    };
+    let base = |operator, from, to, token_id, amount| NftTransfer {
+        block_number: log.block_number.map_or(0, |n| n.as_u64()),
+        transaction_hash: log.transaction_hash.unwrap_or_default(),
+        log_index: log.log_index.map_or(0, |i| i.as_u64()),
+        operator,
+        from,
+        to,
+        token_id,
+        amount,
+    };
+    let invalid = || ClientError::Other(format!("无效的转移事件: {:?}", log.transaction_hash));
+
+    if *topic0 == event_topic(TRANSFER_EVENT) {
+        // ERC-20 的 Transfer 签名相同但 tokenId 未索引，只有 3 个主题
+        if log.topics.len() != 4 {
+            return Ok(Vec::new());
+        }
+        let token_id = U256::from_big_endian(log.topics[3].as_bytes());
+        return Ok(vec![base(
+            None,
+            topic_address(&log.topics[1]),
+            topic_address(&log.topics[2]),
+            token_id,
+            U256::one(),
+        )]);
+    }
+
+    let single = *topic0 == event_topic(TRANSFER_SINGLE_EVENT);
+    if !single && *topic0 != event_topic(TRANSFER_BATCH_EVENT) {
+        return Ok(Vec::new());
+    }
+    if log.topics.len() != 4 {
+        return Err(invalid());
+    }
+    let operator = Some(topic_address(&log.topics[1]));
+    let from = topic_address(&log.topics[2]);
+    let to = topic_address(&log.topics[3]);
+
+    let kinds = if single {
+        vec![ParamType::Uint(256), ParamType::Uint(256)]
+    } else {
+        vec![
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+        ]
+    };
+    let tokens = abi::decode(&kinds, &log.data).map_err(|_| invalid())?;
+    let (ids, amounts) = match (&tokens[0], &tokens[1]) {
+        (Token::Uint(id), Token::Uint(amount)) => (vec![*id], vec![*amount]),
+        (Token::Array(ids), Token::Array(amounts)) if ids.len() == amounts.len() => (
+            ids.iter().filter_map(|t| t.clone().into_uint()).collect(),
+            amounts
+                .iter()
+                .filter_map(|t| t.clone().into_uint())
+                .collect(),
+        ),
+        _ => return Err(invalid()),
+    };
+    Ok(ids
+        .into_iter()
+        .zip(amounts)
+        .map(|(id, amount)| base(operator, from, to, id, amount))
+        .collect())
+}
+
+/// 根据转移记录计算地址持有的代币及数量，按代币 ID 排序
+pub fn holdings(transfers: &[NftTransfer], owner: Address) -> Vec<(U256, U256)> {
+    let mut balances: HashMap<U256, U256> = HashMap::new();
+    for transfer in transfers {
+        if transfer.to == owner {
+            *balances.entry(transfer.token_id).or_default() += transfer.amount;
+        }
+        if transfer.from == owner {
+            let balance = balances.entry(transfer.token_id).or_default();
+            *balance = balance.saturating_sub(transfer.amount);
+        }
+    }
+    let mut held: Vec<(U256, U256)> = balances
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .collect();
+    held.sort();
+    held
+}
+
+/// 替换 ERC-1155 元数据地址中的 `{id}`（64 位小写十六进制）
+fn expand_token_id(uri: &str, token_id: U256) -> String {
+    uri.replace("{id}", &format!("{:064x}", token_id))
+}
+
+/// 将 ipfs:// 地址转换为网关地址
+fn resolve_uri(uri: &str, gateway: &str) -> String {
+    match uri.strip_prefix("ipfs://") {
+        Some(path) => format!(
+            "{}/{}",
+            gateway.trim_end_matches('/'),
+            path.trim_start_matches("ipfs/")
+        ),
+        None => uri.to_string(),
+    }
+}
+
+/// 解析内联在 data URI 中的元数据，非 data URI 返回 `None`
+fn parse_data_uri(uri: &str) -> Result<Option<NftMetadata>, ClientError> {
+    let Some(rest) = uri.strip_prefix("data:") else {
+        return Ok(None);
+    };
+    let (header, body) = rest
+        .split_once(',')
+        .ok_or_else(|| ClientError::Other("无效的 data URI".to_string()))?;
+    let content = if header.ends_with(";base64") {
+        base64::engine::general_purpose::STANDARD
+            .decode(body)
+            .map_err(|e| ClientError::Other(format!("无效的 data URI: {}", e)))?
+    } else {
+        body.as_bytes().to_vec()
+    };
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| ClientError::Other(format!("元数据格式错误: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address_topic(byte: u8) -> H256 {
+        H256::from(Address::repeat_byte(byte))
+    }
+
+    fn log(topics: Vec<H256>, data: Vec<u8>) -> Log {
+        Log {
+            topics,
+            data: data.into(),
+            block_number: Some(10u64.into()),
+            log_index: Some(2u64.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_erc721_transfer() {
+        let transfer = log(
+            vec![
+                event_topic(TRANSFER_EVENT),
+                address_topic(1),
+                address_topic(2),
+                H256::from_low_u64_be(7),
+            ],
+            vec![],
+        );
+        let decoded = decode_transfer_log(&transfer).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].from, Address::repeat_byte(1));
+        assert_eq!(decoded[0].to, Address::repeat_byte(2));
+        assert_eq!(decoded[0].token_id, U256::from(7));
+        assert_eq!(decoded[0].block_number, 10);
+
+        // ERC-20 Transfer 被忽略
+        let erc20 = log(
+            vec![
+                event_topic(TRANSFER_EVENT),
+                address_topic(1),
+                address_topic(2),
+            ],
+            abi::encode(&[Token::Uint(U256::from(7))]),
+        );
+        assert!(decode_transfer_log(&erc20).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_erc1155_batch_and_holdings() {
+        let batch = log(
+            vec![
+                event_topic(TRANSFER_BATCH_EVENT),
+                address_topic(9),
+                H256::zero(),
+                address_topic(1),
+            ],
+            abi::encode(&[
+                Token::Array(vec![Token::Uint(1.into()), Token::Uint(2.into())]),
+                Token::Array(vec![Token::Uint(5.into()), Token::Uint(1.into())]),
+            ]),
+        );
+        let single = log(
+            vec![
+                event_topic(TRANSFER_SINGLE_EVENT),
+                address_topic(1),
+                address_topic(1),
+                address_topic(2),
+            ],
+            abi::encode(&[Token::Uint(2.into()), Token::Uint(1.into())]),
+        );
+        let mut transfers = decode_transfer_log(&batch).unwrap();
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].operator, Some(Address::repeat_byte(9)));
+        transfers.extend(decode_transfer_log(&single).unwrap());
+
+        assert_eq!(
+            holdings(&transfers, Address::repeat_byte(1)),
+            vec![(U256::from(1), U256::from(5))]
+        );
+        assert_eq!(
+            holdings(&transfers, Address::repeat_byte(2)),
+            vec![(U256::from(2), U256::from(1))]
+        );
+    }
+
+    #[test]
+    fn test_metadata_uris() {
+        assert_eq!(
+            resolve_uri("ipfs://ipfs/Qm123/1.json", "https://gw.example/ipfs/"),
+            "https://gw.example/ipfs/Qm123/1.json"
+        );
+        assert_eq!(
+            resolve_uri("https://example.com/1.json", DEFAULT_IPFS_GATEWAY),
+            "https://example.com/1.json"
+        );
+        assert_eq!(
+            expand_token_id("https://example.com/{id}.json", U256::from(255)),
+            format!("https://example.com/{:0>64}.json", "ff")
+        );
+
+        let json = r#"{"name":"Fair #1","image":"ipfs://Qm1","rarity":"rare"}"#;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(json);
+        let metadata = parse_data_uri(&format!("data:application/json;base64,{}", encoded))
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("Fair #1"));
+        assert_eq!(metadata.extra["rarity"], "rare");
+        assert!(parse_data_uri("https://example.com").unwrap().is_none());
+    }
+}
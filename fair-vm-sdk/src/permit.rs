@@ -0,0 +1,763 @@
+//! 链下授权签名
+//!
+//! 构造并签名 EIP-2612 permit 和 Uniswap Permit2（AllowanceTransfer 的 `PermitSingle`、
+//! SignatureTransfer 的 `PermitTransferFrom`）消息，均基于钱包的类型化数据签名。
+//! 同时提供截止时间、Permit2 无序 nonce 的计算和签名验证工具。
+
+use crate::client::ClientError;
+use crate::nft::calldata;
+use crate::wallet::FairWallet;
+use ethers::abi::Token;
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Address, Bytes, Signature, H160, H256, U256};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 未实现 `version()` 的代币使用的 EIP-712 域版本
+pub const DEFAULT_PERMIT_VERSION: &str = "1";
+
+/// Permit2 在各条链上的部署地址
+pub const PERMIT2_ADDRESS: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x22, 0xd4, 0x73, 0x03, 0x0f, 0x11, 0x6d, 0xde, 0xe9, 0xf6, 0xb4,
+    0x3a, 0xc7, 0x8b, 0xa3,
+]);
+
+/// 默认签名有效期（秒）
+pub const DEFAULT_PERMIT_TTL: u64 = 30 * 60;
+
+/// 授权签名错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum PermitError {
+    #[error("缺少字段: {0}")]
+    MissingField(&'static str),
+
+    #[error("签名已过期: 截止 {deadline}, 当前 {now}")]
+    Expired { deadline: U256, now: u64 },
+
+    #[error("{field} 超出 {bits} 位范围")]
+    Overflow { field: &'static str, bits: usize },
+
+    #[error("类型化数据错误: {0}")]
+    TypedData(String),
+
+    #[error("签名错误: {0}")]
+    Signing(String),
+
+    #[error("签名者 {actual:?} 与期望的 {expected:?} 不一致")]
+    SignerMismatch { expected: Address, actual: Address },
+}
+
+impl From<PermitError> for ClientError {
+    fn from(err: PermitError) -> Self {
+        ClientError::Other(err.to_string())
+    }
+}
+
+/// 当前 Unix 时间（秒）
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `seconds` 秒后的截止时间
+pub fn deadline_in(seconds: u64) -> U256 {
+    U256::from(now().saturating_add(seconds))
+}
+
+fn check_bits(value: U256, field: &'static str, bits: usize) -> Result<(), PermitError> {
+    if value.bits() > bits {
+        return Err(PermitError::Overflow { field, bits });
+    }
+    Ok(())
+}
+
+fn typed_data(value: serde_json::Value) -> Result<TypedData, PermitError> {
+    serde_json::from_value(value).map_err(|e| PermitError::TypedData(e.to_string()))
+}
+
+/// 可签名的授权消息
+pub trait PermitMessage {
+    /// 签名者
+    fn signer(&self) -> Address;
+
+    /// 签名截止时间（秒）
+    fn deadline(&self) -> U256;
+
+    /// EIP-712 类型化数据
+    fn typed_data(&self) -> Result<TypedData, PermitError>;
+
+    /// EIP-712 摘要
+    fn digest(&self) -> Result<H256, PermitError> {
+        self.typed_data()?
+            .encode_eip712()
+            .map(H256)
+            .map_err(|e| PermitError::TypedData(e.to_string()))
+    }
+}
+
+/// 已签名的授权消息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedPermit<T> {
+    pub message: T,
+    pub signature: Signature,
+}
+
+impl<T: PermitMessage> SignedPermit<T> {
+    /// 用钱包签名消息，钱包地址必须是消息的签名者，且消息未过期
+    pub async fn sign(wallet: &FairWallet, message: T) -> Result<Self, PermitError> {
+        let address = wallet
+            .address()
+            .await
+            .map_err(|e| PermitError::Signing(e.to_string()))?;
+        if address != message.signer() {
+            return Err(PermitError::SignerMismatch {
+                expected: message.signer(),
+                actual: address,
+            });
+        }
+        ensure_not_expired(message.deadline(), now())?;
+        let signature = wallet
+            .sign_typed_data(&message.typed_data()?)
+            .await
+            .map_err(|e| PermitError::Signing(e.to_string()))?;
+        Ok(Self { message, signature })
+    }
+
+    /// 签名恢复出的地址
+    pub fn recover(&self) -> Result<Address, PermitError> {
+        self.signature
+            .recover(self.message.digest()?)
+            .map_err(|e| PermitError::Signing(e.to_string()))
+    }
+
+    /// 验证签名者和截止时间
+    pub fn verify(&self, now: u64) -> Result<(), PermitError> {
+        ensure_not_expired(self.message.deadline(), now)?;
+        let actual = self.recover()?;
+        if actual != self.message.signer() {
+            return Err(PermitError::SignerMismatch {
+                expected: self.message.signer(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// 签名的 65 字节编码（r‖s‖v），Permit2 使用此格式
+    pub fn signature_bytes(&self) -> Bytes {
+        self.signature.to_vec().into()
+    }
+}
+
+fn ensure_not_expired(deadline: U256, now: u64) -> Result<(), PermitError> {
+    if deadline < U256::from(now) {
+        return Err(PermitError::Expired { deadline, now });
+    }
+    Ok(())
+}
+
+/// EIP-2612 permit 消息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc2612Permit {
+    /// 代币合约
+    pub token: Address,
+    /// 代币名称，用于 EIP-712 域
+    pub name: String,
+    /// EIP-712 域版本
+    pub version: String,
+    pub chain_id: u64,
+    pub owner: Address,
+    pub spender: Address,
+    pub value: U256,
+    pub nonce: U256,
+    /// 签名失效时间（秒）
+    pub deadline: U256,
+}
+
+impl Erc2612Permit {
+    /// 代币合约 `permit` 调用数据
+    pub fn calldata(&self, signature: &Signature) -> Bytes {
+        calldata(
+            "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+            &[
+                Token::Address(self.owner),
+                Token::Address(self.spender),
+                Token::Uint(self.value),
+                Token::Uint(self.deadline),
+                Token::Uint(U256::from(signature.v)),
+                Token::FixedBytes(word(signature.r)),
+                Token::FixedBytes(word(signature.s)),
+            ],
+        )
+    }
+
+    /// EIP-712 域分隔符，可与代币的 `DOMAIN_SEPARATOR()` 比较
+    pub fn domain_separator(&self) -> Result<H256, PermitError> {
+        Ok(H256(self.typed_data()?.domain.separator()))
+    }
+}
+
+impl PermitMessage for Erc2612Permit {
+    fn signer(&self) -> Address {
+        self.owner
+    }
+
+    fn deadline(&self) -> U256 {
+        self.deadline
+    }
+
+    fn typed_data(&self) -> Result<TypedData, PermitError> {
+        typed_data(json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" },
+                ],
+                "Permit": [
+                    { "name": "owner", "type": "address" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" },
+                ],
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": self.name,
+                "version": self.version,
+                "chainId": self.chain_id,
+                "verifyingContract": format!("{:?}", self.token),
+            },
+            "message": {
+                "owner": format!("{:?}", self.owner),
+                "spender": format!("{:?}", self.spender),
+                "value": self.value.to_string(),
+                "nonce": self.nonce.to_string(),
+                "deadline": self.deadline.to_string(),
+            },
+        }))
+    }
+}
+
+/// 大端编码的 32 字节字
+fn word(value: U256) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes.to_vec()
+}
+
+/// EIP-2612 permit 构建器
+#[derive(Debug, Clone, Default)]
+pub struct PermitBuilder {
+    token: Option<Address>,
+    name: Option<String>,
+    version: Option<String>,
+    chain_id: Option<u64>,
+    owner: Option<Address>,
+    spender: Option<Address>,
+    value: Option<U256>,
+    nonce: Option<U256>,
+    deadline: Option<U256>,
+}
+
+impl PermitBuilder {
+    /// 为代币创建构建器
+    pub fn new(token: Address, name: impl Into<String>, chain_id: u64) -> Self {
+        Self {
+            token: Some(token),
+            name: Some(name.into()),
+            chain_id: Some(chain_id),
+            ..Self::default()
+        }
+    }
+
+    /// EIP-712 域版本，默认为 `1`
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn owner(mut self, owner: Address) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn spender(mut self, spender: Address) -> Self {
+        self.spender = Some(spender);
+        self
+    }
+
+    pub fn value(mut self, value: U256) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// 无限额度
+    pub fn unlimited(self) -> Self {
+        self.value(U256::MAX)
+    }
+
+    /// 代币合约 `nonces(owner)` 的当前值
+    pub fn nonce(mut self, nonce: U256) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// 绝对截止时间（秒）
+    pub fn deadline(mut self, deadline: U256) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// 从现在起 `seconds` 秒后失效
+    pub fn expires_in(self, seconds: u64) -> Self {
+        self.deadline(deadline_in(seconds))
+    }
+
+    /// 构建消息，截止时间默认为 [`DEFAULT_PERMIT_TTL`] 秒后
+    pub fn build(self) -> Result<Erc2612Permit, PermitError> {
+        Ok(Erc2612Permit {
+            token: self.token.ok_or(PermitError::MissingField("token"))?,
+            name: self.name.ok_or(PermitError::MissingField("name"))?,
+            version: self
+                .version
+                .unwrap_or_else(|| DEFAULT_PERMIT_VERSION.to_string()),
+            chain_id: self.chain_id.ok_or(PermitError::MissingField("chain_id"))?,
+            owner: self.owner.ok_or(PermitError::MissingField("owner"))?,
+            spender: self.spender.ok_or(PermitError::MissingField("spender"))?,
+            value: self.value.ok_or(PermitError::MissingField("value"))?,
+            nonce: self.nonce.ok_or(PermitError::MissingField("nonce"))?,
+            deadline: self
+                .deadline
+                .unwrap_or_else(|| deadline_in(DEFAULT_PERMIT_TTL)),
+        })
+    }
+}
+
+fn permit2_domain(chain_id: u64) -> serde_json::Value {
+    json!({
+        "name": "Permit2",
+        "chainId": chain_id,
+        "verifyingContract": format!("{:?}", PERMIT2_ADDRESS),
+    })
+}
+
+fn permit2_domain_types() -> serde_json::Value {
+    json!([
+        { "name": "name", "type": "string" },
+        { "name": "chainId", "type": "uint256" },
+        { "name": "verifyingContract", "type": "address" },
+    ])
+}
+
+/// Permit2 AllowanceTransfer 的授权明细
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermitDetails {
+    pub token: Address,
+    /// 授权额度（uint160）
+    pub amount: U256,
+    /// 授权失效时间（uint48，秒）
+    pub expiration: u64,
+    /// Permit2 中 `(owner, token, spender)` 的当前 nonce（uint48）
+    pub nonce: u64,
+}
+
+/// Permit2 AllowanceTransfer 的 `PermitSingle`，签名后由 `permit` 写入授权额度
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermitSingle {
+    pub chain_id: u64,
+    pub owner: Address,
+    pub details: PermitDetails,
+    pub spender: Address,
+    /// 签名截止时间（秒）
+    pub sig_deadline: U256,
+}
+
+impl PermitSingle {
+    /// 创建授权，授权和签名默认在 [`DEFAULT_PERMIT_TTL`] 秒后失效
+    pub fn new(
+        chain_id: u64,
+        owner: Address,
+        token: Address,
+        amount: U256,
+        spender: Address,
+        nonce: u64,
+    ) -> Self {
+        let deadline = deadline_in(DEFAULT_PERMIT_TTL);
+        Self {
+            chain_id,
+            owner,
+            details: PermitDetails {
+                token,
+                amount,
+                expiration: deadline.as_u64(),
+                nonce,
+            },
+            spender,
+            sig_deadline: deadline,
+        }
+    }
+
+    /// 授权额度的失效时间
+    pub fn with_expiration(mut self, expiration: u64) -> Self {
+        self.details.expiration = expiration;
+        self
+    }
+
+    /// 签名的截止时间
+    pub fn with_sig_deadline(mut self, deadline: U256) -> Self {
+        self.sig_deadline = deadline;
+        self
+    }
+
+    /// Permit2 `permit(owner, permitSingle, signature)` 调用数据
+    pub fn calldata(&self, signature: &Bytes) -> Bytes {
+        let details = &self.details;
+        calldata(
+            "permit(address,((address,uint160,uint48,uint48),address,uint256),bytes)",
+            &[
+                Token::Address(self.owner),
+                Token::Tuple(vec![
+                    Token::Tuple(vec![
+                        Token::Address(details.token),
+                        Token::Uint(details.amount),
+                        Token::Uint(U256::from(details.expiration)),
+                        Token::Uint(U256::from(details.nonce)),
+                    ]),
+                    Token::Address(self.spender),
+                    Token::Uint(self.sig_deadline),
+                ]),
+                Token::Bytes(signature.to_vec()),
+            ],
+        )
+    }
+}
+
+impl PermitMessage for PermitSingle {
+    fn signer(&self) -> Address {
+        self.owner
+    }
+
+    fn deadline(&self) -> U256 {
+        self.sig_deadline
+    }
+
+    fn typed_data(&self) -> Result<TypedData, PermitError> {
+        let details = &self.details;
+        check_bits(details.amount, "amount", 160)?;
+        check_bits(U256::from(details.expiration), "expiration", 48)?;
+        check_bits(U256::from(details.nonce), "nonce", 48)?;
+        typed_data(json!({
+            "types": {
+                "EIP712Domain": permit2_domain_types(),
+                "PermitSingle": [
+                    { "name": "details", "type": "PermitDetails" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "sigDeadline", "type": "uint256" },
+                ],
+                "PermitDetails": [
+                    { "name": "token", "type": "address" },
+                    { "name": "amount", "type": "uint160" },
+                    { "name": "expiration", "type": "uint48" },
+                    { "name": "nonce", "type": "uint48" },
+                ],
+            },
+            "primaryType": "PermitSingle",
+            "domain": permit2_domain(self.chain_id),
+            "message": {
+                "details": {
+                    "token": format!("{:?}", details.token),
+                    "amount": details.amount.to_string(),
+                    "expiration": details.expiration.to_string(),
+                    "nonce": details.nonce.to_string(),
+                },
+                "spender": format!("{:?}", self.spender),
+                "sigDeadline": self.sig_deadline.to_string(),
+            },
+        }))
+    }
+}
+
+/// Permit2 SignatureTransfer 的 `PermitTransferFrom`，签名只能使用一次
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermitTransferFrom {
+    pub chain_id: u64,
+    pub owner: Address,
+    pub token: Address,
+    pub amount: U256,
+    /// 调用 `permitTransferFrom` 的地址
+    pub spender: Address,
+    /// 无序 nonce，见 [`UnorderedNonce`]
+    pub nonce: U256,
+    pub deadline: U256,
+}
+
+impl PermitTransferFrom {
+    /// 创建转账授权，默认在 [`DEFAULT_PERMIT_TTL`] 秒后失效
+    pub fn new(
+        chain_id: u64,
+        owner: Address,
+        token: Address,
+        amount: U256,
+        spender: Address,
+        nonce: U256,
+    ) -> Self {
+        Self {
+            chain_id,
+            owner,
+            token,
+            amount,
+            spender,
+            nonce,
+            deadline: deadline_in(DEFAULT_PERMIT_TTL),
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: U256) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Permit2 `permitTransferFrom(permit, transferDetails, owner, signature)` 调用数据
+    pub fn calldata(&self, to: Address, requested_amount: U256, signature: &Bytes) -> Bytes {
+        calldata(
+            "permitTransferFrom(((address,uint256),uint256,uint256),(address,uint256),address,bytes)",
+            &[
+                Token::Tuple(vec![
+                    Token::Tuple(vec![Token::Address(self.token), Token::Uint(self.amount)]),
+                    Token::Uint(self.nonce),
+                    Token::Uint(self.deadline),
+                ]),
+                Token::Tuple(vec![Token::Address(to), Token::Uint(requested_amount)]),
+                Token::Address(self.owner),
+                Token::Bytes(signature.to_vec()),
+            ],
+        )
+    }
+}
+
+impl PermitMessage for PermitTransferFrom {
+    fn signer(&self) -> Address {
+        self.owner
+    }
+
+    fn deadline(&self) -> U256 {
+        self.deadline
+    }
+
+    fn typed_data(&self) -> Result<TypedData, PermitError> {
+        typed_data(json!({
+            "types": {
+                "EIP712Domain": permit2_domain_types(),
+                "PermitTransferFrom": [
+                    { "name": "permitted", "type": "TokenPermissions" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" },
+                ],
+                "TokenPermissions": [
+                    { "name": "token", "type": "address" },
+                    { "name": "amount", "type": "uint256" },
+                ],
+            },
+            "primaryType": "PermitTransferFrom",
+            "domain": permit2_domain(self.chain_id),
+            "message": {
+                "permitted": {
+                    "token": format!("{:?}", self.token),
+                    "amount": self.amount.to_string(),
+                },
+                "spender": format!("{:?}", self.spender),
+                "nonce": self.nonce.to_string(),
+                "deadline": self.deadline.to_string(),
+            },
+        }))
+    }
+}
+
+/// Permit2 的无序 nonce：高 248 位为位图字序号，低 8 位为字内位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnorderedNonce {
+    pub word: U256,
+    pub bit: u8,
+}
+
+impl UnorderedNonce {
+    /// 拆分 nonce
+    pub fn from_nonce(nonce: U256) -> Self {
+        Self {
+            word: nonce >> 8,
+            bit: nonce.low_u32() as u8,
+        }
+    }
+
+    /// 合成 nonce
+    pub fn nonce(&self) -> U256 {
+        (self.word << 8) | U256::from(self.bit)
+    }
+
+    /// 在 `nonceBitmap(owner, word)` 返回的位图中是否已使用
+    pub fn is_used(&self, bitmap: U256) -> bool {
+        bitmap.bit(self.bit as usize)
+    }
+
+    /// 位图字中第一个未使用的 nonce，字已用尽时为空
+    pub fn first_unused(word: U256, bitmap: U256) -> Option<Self> {
+        (0..=255u8)
+            .map(|bit| Self { word, bit })
+            .find(|nonce| !nonce.is_used(bitmap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    async fn wallet() -> (FairWallet, Address) {
+        let wallet = FairWallet::from_private_key(KEY, 1337).unwrap();
+        let address = wallet.address().await.unwrap();
+        (wallet, address)
+    }
+
+    #[tokio::test]
+    async fn test_erc2612_builder_and_verify() {
+        let (wallet, owner) = wallet().await;
+        assert!(matches!(
+            PermitBuilder::new(Address::repeat_byte(0xaa), "Fair USD", 1337)
+                .owner(owner)
+                .build(),
+            Err(PermitError::MissingField("spender"))
+        ));
+
+        let permit = PermitBuilder::new(Address::repeat_byte(0xaa), "Fair USD", 1337)
+            .owner(owner)
+            .spender(Address::repeat_byte(0xbb))
+            .unlimited()
+            .nonce(U256::zero())
+            .expires_in(600)
+            .build()
+            .unwrap();
+        assert_eq!(permit.version, DEFAULT_PERMIT_VERSION);
+
+        let signed = SignedPermit::sign(&wallet, permit.clone()).await.unwrap();
+        assert_eq!(signed.recover().unwrap(), owner);
+        signed.verify(now()).unwrap();
+        assert!(matches!(
+            signed.verify(now() + 3600),
+            Err(PermitError::Expired { .. })
+        ));
+
+        // 篡改消息后签名不再有效
+        let mut tampered = signed.clone();
+        tampered.message.value = U256::one();
+        assert!(matches!(
+            tampered.verify(now()),
+            Err(PermitError::SignerMismatch { .. })
+        ));
+
+        // 不能为其他地址签名，也不能签已过期的消息
+        let other = PermitBuilder::new(permit.token, "Fair USD", 1337)
+            .owner(Address::repeat_byte(1))
+            .spender(permit.spender)
+            .value(U256::one())
+            .nonce(U256::zero())
+            .build()
+            .unwrap();
+        assert!(SignedPermit::sign(&wallet, other).await.is_err());
+        let expired = Erc2612Permit {
+            deadline: U256::one(),
+            ..permit
+        };
+        assert!(matches!(
+            SignedPermit::sign(&wallet, expired).await,
+            Err(PermitError::Expired { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_permit2_single() {
+        let (wallet, owner) = wallet().await;
+        let token = Address::repeat_byte(0xaa);
+        let spender = Address::repeat_byte(0xbb);
+        let permit = PermitSingle::new(1337, owner, token, U256::exp10(18), spender, 0);
+        let signed = SignedPermit::sign(&wallet, permit).await.unwrap();
+        signed.verify(now()).unwrap();
+        assert_eq!(signed.signature_bytes().len(), 65);
+
+        let data = signed.message.calldata(&signed.signature_bytes());
+        assert_eq!(
+            &data[..4],
+            &ethers::utils::id(
+                "permit(address,((address,uint160,uint48,uint48),address,uint256),bytes)"
+            )
+        );
+
+        let overflow = PermitSingle::new(1337, owner, token, U256::MAX, spender, 0);
+        assert!(matches!(
+            overflow.typed_data(),
+            Err(PermitError::Overflow {
+                field: "amount",
+                bits: 160
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_permit2_transfer_from() {
+        let (wallet, owner) = wallet().await;
+        let permit = PermitTransferFrom::new(
+            1337,
+            owner,
+            Address::repeat_byte(0xaa),
+            U256::from(100),
+            Address::repeat_byte(0xbb),
+            UnorderedNonce {
+                word: U256::one(),
+                bit: 3,
+            }
+            .nonce(),
+        );
+        assert_eq!(permit.nonce, U256::from(259));
+        let signed = SignedPermit::sign(&wallet, permit).await.unwrap();
+        signed.verify(now()).unwrap();
+
+        // 与 AllowanceTransfer 的摘要使用同一个域
+        let single = PermitSingle::new(1337, owner, Address::zero(), U256::zero(), owner, 0);
+        assert_eq!(
+            single.typed_data().unwrap().domain.separator(),
+            signed.message.typed_data().unwrap().domain.separator()
+        );
+    }
+
+    #[test]
+    fn test_unordered_nonce() {
+        let nonce = UnorderedNonce::from_nonce(U256::from(515));
+        assert_eq!(
+            nonce,
+            UnorderedNonce {
+                word: U256::from(2),
+                bit: 3
+            }
+        );
+        assert_eq!(nonce.nonce(), U256::from(515));
+
+        let bitmap = U256::from(0b1011);
+        assert!(nonce.is_used(bitmap));
+        assert_eq!(
+            UnorderedNonce::first_unused(U256::from(2), bitmap).map(|n| n.bit),
+            Some(2)
+        );
+        assert_eq!(UnorderedNonce::first_unused(U256::zero(), U256::MAX), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! 金额单位换算
+//!
+//! 链上金额一律以 wei 计，1 gwei = 10^9 wei，1 FAIR = 10^18 wei。用户输入的金额可以带单位，
+//! 如 `1.5 fair`、`20gwei`，不带单位时按调用方给定的默认单位解析。换算是精确的十进制运算，
+//! 小数位超出单位精度时报错而不是截断。
+
+use ethers::types::U256;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// 金额单位错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum UnitError {
+    #[error("金额为空")]
+    Empty,
+
+    #[error("无效金额: {0}")]
+    InvalidNumber(String),
+
+    #[error("未知单位: {0}，可用单位为 wei、gwei、fair")]
+    UnknownUnit(String),
+
+    #[error("金额 {value} 的小数位超过 {unit} 的精度")]
+    TooPrecise { value: String, unit: Unit },
+
+    #[error("金额 {0} 超出范围")]
+    Overflow(String),
+}
+
+/// 金额单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Wei,
+    Gwei,
+    /// 原生代币
+    Fair,
+}
+
+impl Unit {
+    /// 相对 wei 的小数位数
+    pub fn decimals(&self) -> usize {
+        match self {
+            Self::Wei => 0,
+            Self::Gwei => 9,
+            Self::Fair => 18,
+        }
+    }
+
+    /// 一个单位对应的 wei
+    pub fn wei(&self) -> U256 {
+        U256::exp10(self.decimals())
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Wei => "wei",
+            Self::Gwei => "gwei",
+            Self::Fair => "FAIR",
+        })
+    }
+}
+
+impl FromStr for Unit {
+    type Err = UnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "wei" => Ok(Self::Wei),
+            "gwei" => Ok(Self::Gwei),
+            "fair" => Ok(Self::Fair),
+            _ => Err(UnitError::UnknownUnit(s.trim().to_string())),
+        }
+    }
+}
+
+/// 按单位解析不带单位的十进制数，如 `parse_units("1.5", Unit::Fair)`
+pub fn parse_units(value: &str, unit: Unit) -> Result<U256, UnitError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(UnitError::Empty);
+    }
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty()) || !is_digits(integer) || !is_digits(fraction) {
+        return Err(UnitError::InvalidNumber(value.to_string()));
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > unit.decimals() {
+        return Err(UnitError::TooPrecise {
+            value: value.to_string(),
+            unit,
+        });
+    }
+    let digits = format!(
+        "{}{}{}",
+        integer,
+        fraction,
+        "0".repeat(unit.decimals() - fraction.len())
+    );
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(U256::zero());
+    }
+    U256::from_dec_str(digits).map_err(|_| UnitError::Overflow(value.to_string()))
+}
+
+/// 解析可带单位的金额，返回 wei，不带单位时按 `default_unit` 解析
+pub fn parse_amount(s: &str, default_unit: Unit) -> Result<U256, UnitError> {
+    let s = s.trim();
+    let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let unit = match unit.trim() {
+        "" => default_unit,
+        unit => unit.parse()?,
+    };
+    parse_units(value, unit)
+}
+
+/// 以给定单位显示 wei 金额，省略小数末尾的零
+pub fn format_units(amount: U256, unit: Unit) -> String {
+    let digits = amount.to_string();
+    let decimals = unit.decimals();
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    match fraction.trim_end_matches('0') {
+        "" => integer.to_string(),
+        fraction => format!("{}.{}", integer, fraction),
+    }
+}
+
+/// 以 FAIR 显示 wei 金额，如 `1.5 FAIR`
+pub fn format_fair(amount: U256) -> String {
+    format!("{} {}", format_units(amount, Unit::Fair), Unit::Fair)
+}
+
+/// 以 gwei 显示 wei 金额，如 `25 gwei`，用于 gas 价格
+pub fn format_gwei(amount: U256) -> String {
+    format!("{} {}", format_units(amount, Unit::Gwei), Unit::Gwei)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        let fair = U256::exp10(18);
+        assert_eq!(parse_amount("1.5 fair", Unit::Wei).unwrap(), fair * 3 / 2);
+        assert_eq!(parse_amount("1.5FAIR", Unit::Wei).unwrap(), fair * 3 / 2);
+        assert_eq!(
+            parse_amount(" 20 gwei ", Unit::Wei).unwrap(),
+            U256::from(20_000_000_000u64)
+        );
+        assert_eq!(parse_amount(".25", Unit::Fair).unwrap(), fair / 4);
+        assert_eq!(parse_amount("100", Unit::Wei).unwrap(), U256::from(100));
+        assert_eq!(parse_amount("0.000", Unit::Fair).unwrap(), U256::zero());
+        assert_eq!(
+            parse_amount("1.10", Unit::Wei).unwrap_err(),
+            UnitError::TooPrecise {
+                value: "1.10".to_string(),
+                unit: Unit::Wei,
+            }
+        );
+        assert!(matches!(
+            parse_amount("1.0000000001 gwei", Unit::Wei),
+            Err(UnitError::TooPrecise { .. })
+        ));
+        assert!(matches!(
+            parse_amount("1 eth", Unit::Wei),
+            Err(UnitError::UnknownUnit(_))
+        ));
+        assert!(matches!(
+            parse_amount("-1", Unit::Wei),
+            Err(UnitError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_amount("1.2.3", Unit::Wei),
+            Err(UnitError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_amount(".", Unit::Wei),
+            Err(UnitError::InvalidNumber(_))
+        ));
+        assert_eq!(parse_amount("fair", Unit::Wei), Err(UnitError::Empty));
+        assert!(matches!(
+            parse_amount(&format!("{}0", U256::MAX), Unit::Wei),
+            Err(UnitError::Overflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_units() {
+        let amount = parse_amount("1.5 fair", Unit::Wei).unwrap();
+        assert_eq!(format_fair(amount), "1.5 FAIR");
+        assert_eq!(format_units(amount, Unit::Gwei), "1500000000");
+        assert_eq!(
+            format_units(U256::from(1), Unit::Fair),
+            "0.000000000000000001"
+        );
+        assert_eq!(format_gwei(U256::from(25_100_000_000u64)), "25.1 gwei");
+        assert_eq!(format_fair(U256::zero()), "0 FAIR");
+        for value in ["0.000000000000000001", "123.456", "7"] {
+            let wei = parse_units(value, Unit::Fair).unwrap();
+            assert_eq!(format_units(wei, Unit::Fair), value);
+        }
+    }
+}
@@ -2,12 +2,12 @@ use async_trait::async_trait;
 use ethers::signers::Signer;
 use ethers::types::transaction::eip2718::TypedTransaction;
 use ethers::types::transaction::eip712::Eip712;
-use ethers::types::{Address, Signature};
+use ethers::types::{Address, NameOrAddress, Signature, H256, U256};
+use fair_vm_core::rng;
 use k256::{
     ecdsa::{signature::hazmat::PrehashSigner, Signature as K256Signature, SigningKey},
     SecretKey,
 };
-use rand;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -47,6 +47,9 @@ pub enum FirmwareError {
 
     #[error("不支持的固件版本: {0}")]
     UnsupportedVersion(Version),
+
+    #[error("尚未支持: {0}")]
+    Unsupported(&'static str),
 }
 
 /// 固件版本要求
@@ -114,6 +117,10 @@ pub struct LedgerFirmware {
     pub nano_s_requirement: FirmwareRequirement,
     /// Nano X 的版本要求
     pub nano_x_requirement: FirmwareRequirement,
+    /// Stax 的版本要求
+    pub stax_requirement: FirmwareRequirement,
+    /// Flex 的版本要求
+    pub flex_requirement: FirmwareRequirement,
     /// 当前设备型号
     device_model: Option<DeviceModel>,
     /// 当前固件版本
@@ -130,6 +137,8 @@ pub struct LedgerFirmware {
 pub enum DeviceModel {
     LedgerNanoS,
     LedgerNanoX,
+    LedgerStax,
+    LedgerFlex,
 }
 
 impl LedgerFirmware {
@@ -145,6 +154,8 @@ impl LedgerFirmware {
         let mut firmware = Self {
             nano_s_requirement: FirmwareRequirement::new("2.1.0", "2.2.0", &["2.0.0"])?,
             nano_x_requirement: FirmwareRequirement::new("2.0.0", "2.1.0", &[])?,
+            stax_requirement: FirmwareRequirement::new("1.0.0", "1.1.0", &[])?,
+            flex_requirement: FirmwareRequirement::new("1.0.0", "1.0.1", &[])?,
             device_model: None,
             firmware_version: None,
             derivation_path: base_path.to_string(),
@@ -180,6 +191,8 @@ impl LedgerFirmware {
         match model {
             DeviceModel::LedgerNanoS => self.nano_s_requirement.check_version(&version.to_string()),
             DeviceModel::LedgerNanoX => self.nano_x_requirement.check_version(&version.to_string()),
+            DeviceModel::LedgerStax => self.stax_requirement.check_version(&version.to_string()),
+            DeviceModel::LedgerFlex => self.flex_requirement.check_version(&version.to_string()),
         }
     }
 
@@ -192,6 +205,14 @@ impl LedgerFirmware {
     pub fn get_firmware_version(&self) -> Option<&Version> {
         self.firmware_version.as_ref()
     }
+
+    /// 获取交易在设备上展示的字段
+    pub fn display_data(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<TransactionDisplayData, FirmwareError> {
+        TransactionDisplayData::for_device(tx, self.chain_id)
+    }
 }
 
 /// Trezor固件版本管理器
@@ -211,6 +232,40 @@ pub struct TrezorFirmware {
     chain_id: u64,
     /// 连接状态
     pub connected: bool,
+    /// 设备是否启用了口令保护
+    pub passphrase_protection: bool,
+    /// 当前口令会话
+    #[serde(skip)]
+    session: Option<PassphraseSession>,
+}
+
+/// Trezor 口令输入方式
+#[derive(Clone, PartialEq)]
+pub enum PassphraseEntry {
+    /// 不使用口令，打开标准钱包
+    Standard,
+    /// 在主机端输入口令，打开隐藏钱包
+    Host(String),
+}
+
+impl std::fmt::Debug for PassphraseEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PassphraseEntry::Standard => write!(f, "Standard"),
+            PassphraseEntry::Host(_) => write!(f, "Host(***)"),
+        }
+    }
+}
+
+/// Trezor 口令会话，仅保存口令指纹而不保存明文
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassphraseSession {
+    /// 会话 ID
+    pub session_id: H256,
+    /// 是否为隐藏钱包
+    pub hidden: bool,
+    /// 口令指纹，用于区分不同的隐藏钱包
+    fingerprint: H256,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -237,6 +292,8 @@ impl TrezorFirmware {
             derivation_path: base_path.to_string(),
             chain_id,
             connected: true,
+            passphrase_protection: false,
+            session: None,
         };
 
         // 尝试连接设备并获取信息
@@ -252,9 +309,58 @@ impl TrezorFirmware {
         // 目前使用模拟数据
         self.device_model = Some(TrezorModel::ModelOne);
         self.firmware_version = Some(Version::new(1, 10, 0));
+        self.passphrase_protection = true;
+        Ok(())
+    }
+
+    /// 打开口令会话
+    ///
+    /// 空口令等同于标准钱包；非空口令会派生出与标准钱包完全不同的隐藏钱包。
+    pub fn open_session(&mut self, entry: PassphraseEntry) -> Result<(), FirmwareError> {
+        if !self.connected {
+            return Err(FirmwareError::DeviceNotConnected);
+        }
+        let session_id = H256::random();
+        let session = match entry {
+            PassphraseEntry::Standard => PassphraseSession {
+                session_id,
+                hidden: false,
+                fingerprint: H256::zero(),
+            },
+            PassphraseEntry::Host(passphrase) => {
+                if !self.passphrase_protection {
+                    return Err(FirmwareError::DeviceError("设备未启用口令保护".to_string()));
+                }
+                PassphraseSession {
+                    session_id,
+                    hidden: !passphrase.is_empty(),
+                    fingerprint: if passphrase.is_empty() {
+                        H256::zero()
+                    } else {
+                        H256(ethers::utils::keccak256(passphrase.as_bytes()))
+                    },
+                }
+            }
+        };
+        self.session = Some(session);
         Ok(())
     }
 
+    /// 关闭口令会话，设备将清除缓存的口令
+    pub fn close_session(&mut self) {
+        self.session = None;
+    }
+
+    /// 获取当前口令会话
+    pub fn session(&self) -> Option<&PassphraseSession> {
+        self.session.as_ref()
+    }
+
+    /// 当前是否打开了隐藏钱包
+    pub fn is_hidden_wallet(&self) -> bool {
+        self.session.as_ref().is_some_and(|s| s.hidden)
+    }
+
     /// 检查固件版本
     pub fn check_firmware(&self) -> Result<bool, FirmwareError> {
         let version = self
@@ -281,6 +387,100 @@ impl TrezorFirmware {
     pub fn get_firmware_version(&self) -> Option<&Version> {
         self.firmware_version.as_ref()
     }
+
+    /// 获取交易在设备上展示的字段
+    pub fn display_data(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<TransactionDisplayData, FirmwareError> {
+        TransactionDisplayData::for_device(tx, self.chain_id)
+    }
+}
+
+/// 需要在设备屏幕上展示并由用户确认的交易字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionDisplayData {
+    /// 链 ID
+    pub chain_id: u64,
+    /// nonce
+    pub nonce: U256,
+    /// 接收方地址，合约创建时为空
+    pub to: Option<Address>,
+    /// 转账金额
+    pub value: U256,
+    /// gas 上限
+    pub gas_limit: U256,
+    /// 传统交易的 gas 价格
+    pub gas_price: Option<U256>,
+    /// EIP-1559 最大费用
+    pub max_fee_per_gas: Option<U256>,
+    /// EIP-1559 最大优先费
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// 调用数据长度
+    pub data_len: usize,
+}
+
+impl TransactionDisplayData {
+    /// 从类型化交易中提取设备展示字段
+    pub fn from_transaction(
+        tx: &TypedTransaction,
+        default_chain_id: u64,
+    ) -> Result<Self, FirmwareError> {
+        let to = match tx.to() {
+            Some(NameOrAddress::Address(addr)) => Some(*addr),
+            Some(NameOrAddress::Name(name)) => {
+                return Err(FirmwareError::SigningError(format!(
+                    "设备无法显示未解析的 ENS 名称: {}",
+                    name
+                )))
+            }
+            None => None,
+        };
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match tx {
+            TypedTransaction::Eip1559(inner) => {
+                let max_fee = inner
+                    .max_fee_per_gas
+                    .ok_or_else(|| FirmwareError::SigningError("缺少 maxFeePerGas".to_string()))?;
+                let max_priority_fee = inner.max_priority_fee_per_gas.ok_or_else(|| {
+                    FirmwareError::SigningError("缺少 maxPriorityFeePerGas".to_string())
+                })?;
+                (None, Some(max_fee), Some(max_priority_fee))
+            }
+            _ => (tx.gas_price(), None, None),
+        };
+
+        Ok(Self {
+            chain_id: tx
+                .chain_id()
+                .map(|id| id.as_u64())
+                .unwrap_or(default_chain_id),
+            nonce: tx.nonce().copied().unwrap_or_default(),
+            to,
+            value: tx.value().copied().unwrap_or_default(),
+            gas_limit: tx.gas().copied().unwrap_or_default(),
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            data_len: tx.data().map(|data| data.len()).unwrap_or(0),
+        })
+    }
+
+    /// 提取展示字段，并要求交易链 ID 与设备配置一致
+    pub fn for_device(tx: &TypedTransaction, chain_id: u64) -> Result<Self, FirmwareError> {
+        let display = Self::from_transaction(tx, chain_id)?;
+        if display.chain_id != chain_id {
+            return Err(FirmwareError::SigningError(format!(
+                "交易链 ID {} 与设备配置 {} 不一致",
+                display.chain_id, chain_id
+            )));
+        }
+        Ok(display)
+    }
+
+    /// 是否为 EIP-1559 交易
+    pub fn is_eip1559(&self) -> bool {
+        self.max_fee_per_gas.is_some()
+    }
 }
 
 #[async_trait]
@@ -312,8 +512,7 @@ impl LedgerFirmwareTrait for LedgerFirmware {
 
     async fn sign_message(&self, message: &[u8]) -> Result<Signature, FirmwareError> {
         // 使用 k256 生成一个模拟签名
-        let mut rng = rand::thread_rng();
-        let secret_key = SecretKey::random(&mut rng);
+        let secret_key = rng::with_rng(|rng| SecretKey::random(rng));
         let signing_key = SigningKey::from(secret_key);
         let message_hash = ethers::utils::hash_message(message);
         let signature: K256Signature = signing_key
@@ -342,15 +541,22 @@ impl LedgerFirmwareTrait for LedgerFirmware {
 
 #[async_trait]
 impl TrezorFirmwareTrait for TrezorFirmware {
-    async fn get_address(&self, _derivation_path: &str) -> Result<Address, FirmwareError> {
-        // 返回一个模拟地址
-        Ok(Address::from([0x1; 20]))
+    async fn get_address(&self, derivation_path: &str) -> Result<Address, FirmwareError> {
+        match self.session.as_ref().filter(|s| s.hidden) {
+            // 隐藏钱包由口令派生出独立的种子，模拟为按指纹派生的地址
+            Some(session) => {
+                let mut data = session.fingerprint.as_bytes().to_vec();
+                data.extend_from_slice(derivation_path.as_bytes());
+                Ok(Address::from_slice(&ethers::utils::keccak256(data)[12..]))
+            }
+            // 返回一个模拟地址
+            None => Ok(Address::from([0x1; 20])),
+        }
     }
 
     async fn sign_message(&self, message: &[u8]) -> Result<Signature, FirmwareError> {
         // 使用 k256 生成一个模拟签名
-        let mut rng = rand::thread_rng();
-        let secret_key = SecretKey::random(&mut rng);
+        let secret_key = rng::with_rng(|rng| SecretKey::random(rng));
         let signing_key = SigningKey::from(secret_key);
         let message_hash = ethers::utils::hash_message(message);
         let signature: K256Signature = signing_key
@@ -388,12 +594,15 @@ impl Signer for LedgerFirmware {
         LedgerFirmwareTrait::sign_message(self, message.as_ref()).await
     }
 
+    /// 本 SDK 不提供设备交易签名，展示字段通过 `display_data` 获取
     async fn sign_transaction(
         &self,
         _tx: &TypedTransaction,
     ) -> Result<Signature, <Self as Signer>::Error> {
-        // TODO: 实现实际的交易签名逻辑
-        Err(FirmwareError::SigningError("未实现".to_string()))
+        if !self.connected {
+            return Err(FirmwareError::DeviceNotConnected);
+        }
+        Err(FirmwareError::Unsupported("硬件设备交易签名"))
     }
 
     fn address(&self) -> Address {
@@ -433,12 +642,15 @@ impl Signer for TrezorFirmware {
         TrezorFirmwareTrait::sign_message(self, message.as_ref()).await
     }
 
+    /// 本 SDK 不提供设备交易签名，展示字段通过 `display_data` 获取
     async fn sign_transaction(
         &self,
         _tx: &TypedTransaction,
     ) -> Result<Signature, <Self as Signer>::Error> {
-        // TODO: 实现实际的交易签名逻辑
-        Err(FirmwareError::SigningError("未实现".to_string()))
+        if !self.connected {
+            return Err(FirmwareError::DeviceNotConnected);
+        }
+        Err(FirmwareError::Unsupported("硬件设备交易签名"))
     }
 
     fn address(&self) -> Address {
@@ -472,6 +684,8 @@ impl Default for LedgerFirmware {
         Self {
             nano_s_requirement: FirmwareRequirement::new("2.0.0", "2.1.0", &["1.9.0"]).unwrap(),
             nano_x_requirement: FirmwareRequirement::new("2.0.0", "2.1.0", &["1.9.0"]).unwrap(),
+            stax_requirement: FirmwareRequirement::new("1.0.0", "1.1.0", &[]).unwrap(),
+            flex_requirement: FirmwareRequirement::new("1.0.0", "1.0.1", &[]).unwrap(),
             device_model: None,
             firmware_version: None,
             derivation_path: "m/44'/60'/0'/0/0".to_string(),
@@ -491,6 +705,8 @@ impl Default for TrezorFirmware {
             derivation_path: "m/44'/60'/0'/0/0".to_string(),
             chain_id: 1,
             connected: false,
+            passphrase_protection: false,
+            session: None,
         }
     }
 }
@@ -545,4 +761,82 @@ mod tests {
         let current = "2.3.0";
         assert!(firmware.model_t_requirement.check_version(current).is_ok());
     }
+
+    #[test]
+    fn test_display_data_eip1559() {
+        let tx: TypedTransaction = ethers::types::Eip1559TransactionRequest::new()
+            .to(Address::from([0x2; 20]))
+            .value(100)
+            .nonce(3)
+            .gas(21000)
+            .max_fee_per_gas(50)
+            .max_priority_fee_per_gas(2)
+            .chain_id(2023u64)
+            .into();
+
+        let display = TransactionDisplayData::from_transaction(&tx, 1).unwrap();
+        assert!(display.is_eip1559());
+        assert_eq!(display.chain_id, 2023);
+        assert_eq!(display.max_fee_per_gas, Some(U256::from(50)));
+        assert_eq!(display.max_priority_fee_per_gas, Some(U256::from(2)));
+        assert_eq!(display.gas_price, None);
+    }
+
+    #[tokio::test]
+    async fn test_display_data_chain_id_mismatch() {
+        let mut firmware = LedgerFirmware::default();
+        firmware.connected = true;
+        let tx: TypedTransaction = ethers::types::Eip1559TransactionRequest::new()
+            .max_fee_per_gas(50)
+            .max_priority_fee_per_gas(2)
+            .chain_id(5u64)
+            .into();
+
+        assert!(matches!(
+            firmware.display_data(&tx),
+            Err(FirmwareError::SigningError(_))
+        ));
+        let tx: TypedTransaction = ethers::types::Eip1559TransactionRequest::new()
+            .max_fee_per_gas(50)
+            .max_priority_fee_per_gas(2)
+            .into();
+        assert_eq!(firmware.display_data(&tx).unwrap().chain_id, 1);
+        // 设备交易签名不在本 SDK 范围内
+        assert!(matches!(
+            Signer::sign_transaction(&firmware, &tx).await,
+            Err(FirmwareError::Unsupported(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trezor_hidden_wallet() {
+        let mut firmware = TrezorFirmware::new("m/44'/60'/0'", 1).await.unwrap();
+        let path = "m/44'/60'/0'/0/0";
+
+        firmware.open_session(PassphraseEntry::Standard).unwrap();
+        let standard = TrezorFirmwareTrait::get_address(&firmware, path)
+            .await
+            .unwrap();
+        assert!(!firmware.is_hidden_wallet());
+
+        firmware
+            .open_session(PassphraseEntry::Host("secret".to_string()))
+            .unwrap();
+        let hidden = TrezorFirmwareTrait::get_address(&firmware, path)
+            .await
+            .unwrap();
+        assert!(firmware.is_hidden_wallet());
+        assert_ne!(standard, hidden);
+
+        // 设备未启用口令保护时拒绝隐藏钱包，打开失败时保留原会话
+        firmware.passphrase_protection = false;
+        assert!(matches!(
+            firmware.open_session(PassphraseEntry::Host("other".to_string())),
+            Err(FirmwareError::DeviceError(_))
+        ));
+        assert!(firmware.is_hidden_wallet());
+
+        firmware.close_session();
+        assert!(firmware.session().is_none());
+    }
 }
@@ -1,14 +1,12 @@
 use crate::wallet::firmware::{
-    LedgerFirmware, LedgerFirmwareTrait, TrezorFirmware, TrezorFirmwareTrait,
+    DeviceModel as LedgerModel, LedgerFirmware, LedgerFirmwareTrait, PassphraseEntry,
+    TransactionDisplayData, TrezorFirmware, TrezorFirmwareTrait, TrezorModel,
 };
 use async_trait::async_trait;
 use ethers::signers::Signer;
 use ethers::types::transaction::eip2718::TypedTransaction;
 use ethers::types::transaction::eip712::{Eip712, TypedData as EthersTypedData};
-use ethers::types::{
-    Address, Eip1559TransactionRequest, NameOrAddress, Signature, Transaction, TransactionRequest,
-    H256, U256, U64,
-};
+use ethers::types::{Address, Eip1559TransactionRequest, Signature, TransactionRequest};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -59,12 +57,50 @@ pub enum DeviceModel {
     LedgerNanoS,
     /// Ledger Nano X
     LedgerNanoX,
+    /// Ledger Stax
+    LedgerStax,
+    /// Ledger Flex
+    LedgerFlex,
     /// Trezor One
     TrezorOne,
     /// Trezor Model T
     TrezorModelT,
 }
 
+impl DeviceModel {
+    /// 设备固件是否支持在屏幕上展示 EIP-1559 费用字段
+    pub fn supports_eip1559(&self, firmware_version: &Version) -> bool {
+        match self {
+            DeviceModel::LedgerNanoS | DeviceModel::LedgerNanoX => {
+                firmware_version >= &Version::new(2, 0, 0)
+            }
+            DeviceModel::LedgerStax | DeviceModel::LedgerFlex => true,
+            DeviceModel::TrezorOne => firmware_version >= &Version::new(1, 10, 4),
+            DeviceModel::TrezorModelT => firmware_version >= &Version::new(2, 4, 2),
+        }
+    }
+}
+
+impl From<LedgerModel> for DeviceModel {
+    fn from(model: LedgerModel) -> Self {
+        match model {
+            LedgerModel::LedgerNanoS => DeviceModel::LedgerNanoS,
+            LedgerModel::LedgerNanoX => DeviceModel::LedgerNanoX,
+            LedgerModel::LedgerStax => DeviceModel::LedgerStax,
+            LedgerModel::LedgerFlex => DeviceModel::LedgerFlex,
+        }
+    }
+}
+
+impl From<TrezorModel> for DeviceModel {
+    fn from(model: TrezorModel) -> Self {
+        match model {
+            TrezorModel::ModelOne => DeviceModel::TrezorOne,
+            TrezorModel::ModelT => DeviceModel::TrezorModelT,
+        }
+    }
+}
+
 /// 硬件钱包
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HardwareWallet {
@@ -88,10 +124,18 @@ impl HardwareWallet {
             .await
             .map_err(|e| HardwareWalletError::InvalidDerivationPath(e.to_string()))?;
 
+        let device_model = ledger
+            .get_device_model()
+            .map_or(DeviceModel::LedgerNanoS, DeviceModel::from);
+        let firmware_version = ledger
+            .get_firmware_version()
+            .cloned()
+            .unwrap_or_else(|| Version::new(1, 0, 0));
+
         Ok(Self {
             wallet_type: HardwareWalletType::Ledger(Arc::new(ledger)),
-            device_model: DeviceModel::LedgerNanoS,
-            firmware_version: Version::new(1, 0, 0),
+            device_model,
+            firmware_version,
             base_derivation_path,
             accounts: Vec::new(),
             current_account_index: None,
@@ -99,20 +143,54 @@ impl HardwareWallet {
         })
     }
 
-    /// 创建新的 Trezor 钱包实例
+    /// 创建新的 Trezor 钱包实例（标准钱包）
     pub async fn new_trezor(
         base_path: Option<String>,
         chain_id: u64,
+    ) -> Result<Self, HardwareWalletError> {
+        Self::open_trezor(base_path, chain_id, PassphraseEntry::Standard).await
+    }
+
+    /// 打开 Trezor 口令保护的隐藏钱包
+    pub async fn new_trezor_hidden(
+        base_path: Option<String>,
+        chain_id: u64,
+        passphrase: PassphraseEntry,
+    ) -> Result<Self, HardwareWalletError> {
+        if passphrase == PassphraseEntry::Standard {
+            return Err(HardwareWalletError::Other(
+                "打开隐藏钱包需要提供口令".to_string(),
+            ));
+        }
+        Self::open_trezor(base_path, chain_id, passphrase).await
+    }
+
+    /// 按指定口令输入方式打开 Trezor 钱包
+    async fn open_trezor(
+        base_path: Option<String>,
+        chain_id: u64,
+        passphrase: PassphraseEntry,
     ) -> Result<Self, HardwareWalletError> {
         let base_derivation_path = base_path.unwrap_or_else(|| "m/44'/60'/0'".to_string());
-        let trezor = TrezorFirmware::new(&base_derivation_path, chain_id)
+        let mut trezor = TrezorFirmware::new(&base_derivation_path, chain_id)
             .await
             .map_err(|e| HardwareWalletError::InvalidDerivationPath(e.to_string()))?;
+        trezor
+            .open_session(passphrase)
+            .map_err(|e| HardwareWalletError::Other(e.to_string()))?;
+
+        let device_model = trezor
+            .get_device_model()
+            .map_or(DeviceModel::TrezorOne, DeviceModel::from);
+        let firmware_version = trezor
+            .get_firmware_version()
+            .cloned()
+            .unwrap_or_else(|| Version::new(1, 0, 0));
 
         Ok(Self {
             wallet_type: HardwareWalletType::Trezor(Arc::new(trezor)),
-            device_model: DeviceModel::TrezorOne,
-            firmware_version: Version::new(1, 0, 0),
+            device_model,
+            firmware_version,
             base_derivation_path,
             accounts: Vec::new(),
             current_account_index: None,
@@ -120,6 +198,14 @@ impl HardwareWallet {
         })
     }
 
+    /// 当前是否为 Trezor 隐藏钱包
+    pub fn is_hidden_wallet(&self) -> bool {
+        match &self.wallet_type {
+            HardwareWalletType::Trezor(trezor) => trezor.is_hidden_wallet(),
+            HardwareWalletType::Ledger(_) => false,
+        }
+    }
+
     /// 添加新账户
     pub async fn add_account(&mut self, index: u32) -> Result<Address, HardwareWalletError> {
         let derivation_path = format!("{}/{}", self.base_derivation_path, index);
@@ -179,62 +265,68 @@ impl HardwareWallet {
         }
     }
 
-    /// 签名交易
+    /// 签名传统交易
     pub async fn sign_transaction(
         &self,
         tx: TransactionRequest,
     ) -> Result<Signature, HardwareWalletError> {
-        let typed_tx = TypedTransaction::Legacy(tx);
-        let _tx = Transaction {
-            hash: H256::zero(),
-            nonce: typed_tx.nonce().copied().unwrap_or(U256::zero()),
-            block_hash: None,
-            block_number: None,
-            transaction_index: None,
-            from: self.get_current_account().unwrap_or_default(),
-            to: typed_tx.to().map(|addr| match addr {
-                NameOrAddress::Address(addr) => *addr,
-                NameOrAddress::Name(_) => Address::zero(),
-            }),
-            value: typed_tx.value().copied().unwrap_or(U256::zero()),
-            gas_price: typed_tx.gas_price(),
-            gas: typed_tx.gas().copied().unwrap_or(U256::zero()),
-            input: typed_tx.data().cloned().unwrap_or_default(),
-            v: U64::from(0),
-            r: U256::zero(),
-            s: U256::zero(),
-            transaction_type: None,
-            access_list: None,
-            max_fee_per_gas: None,
-            max_priority_fee_per_gas: None,
-            chain_id: Some(U256::from(self.chain_id)),
-            other: Default::default(),
-        };
-        Ok(Signature {
-            r: U256::zero(),
-            s: U256::zero(),
-            v: 0,
-        })
+        let tx = tx.chain_id(self.chain_id);
+        self.sign_typed_transaction(&TypedTransaction::Legacy(tx))
+            .await
     }
 
     /// 签名 EIP-1559 交易
+    ///
+    /// 固件不支持 EIP-1559 时返回错误，而不是退化为传统交易。本 SDK 不提供设备交易签名，
+    /// 设备展示的 maxFeePerGas、maxPriorityFeePerGas 和 chainId 通过 [`Self::display_data`] 获取。
     pub async fn sign_eip1559_transaction(
         &self,
         tx: &Eip1559TransactionRequest,
+    ) -> Result<Signature, HardwareWalletError> {
+        if !self.supports_eip1559() {
+            return Err(HardwareWalletError::DeviceNotSupported);
+        }
+        let tx = match tx.chain_id {
+            Some(_) => tx.clone(),
+            None => tx.clone().chain_id(self.chain_id),
+        };
+        self.sign_typed_transaction(&TypedTransaction::Eip1559(tx))
+            .await
+    }
+
+    /// 获取交易在设备上展示的字段
+    pub fn display_data(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<TransactionDisplayData, HardwareWalletError> {
+        TransactionDisplayData::for_device(tx, self.chain_id)
+            .map_err(|e| HardwareWalletError::SigningFailed(e.to_string()))
+    }
+
+    /// 当前设备是否支持 EIP-1559 交易
+    pub fn supports_eip1559(&self) -> bool {
+        self.device_model.supports_eip1559(&self.firmware_version)
+    }
+
+    /// 签名类型化交易
+    async fn sign_typed_transaction(
+        &self,
+        tx: &TypedTransaction,
     ) -> Result<Signature, HardwareWalletError> {
         let _account = self
             .get_current_account()
             .ok_or(HardwareWalletError::DeviceNotConnected)?;
-
-        let typed_tx: TypedTransaction = (*tx).clone().into();
+        if matches!(tx, TypedTransaction::Eip1559(_)) && !self.supports_eip1559() {
+            return Err(HardwareWalletError::DeviceNotSupported);
+        }
 
         match &self.wallet_type {
             HardwareWalletType::Ledger(ledger) => ledger
-                .sign_transaction(&typed_tx)
+                .sign_transaction(tx)
                 .await
                 .map_err(|e| HardwareWalletError::SigningFailed(e.to_string())),
             HardwareWalletType::Trezor(trezor) => trezor
-                .sign_transaction(&typed_tx)
+                .sign_transaction(tx)
                 .await
                 .map_err(|e| HardwareWalletError::SigningFailed(e.to_string())),
         }
@@ -296,6 +388,12 @@ impl HardwareWallet {
             (HardwareWalletType::Ledger(_), DeviceModel::LedgerNanoX) => LedgerFirmware::default()
                 .nano_x_requirement
                 .needs_update(&self.firmware_version),
+            (HardwareWalletType::Ledger(_), DeviceModel::LedgerStax) => LedgerFirmware::default()
+                .stax_requirement
+                .needs_update(&self.firmware_version),
+            (HardwareWalletType::Ledger(_), DeviceModel::LedgerFlex) => LedgerFirmware::default()
+                .flex_requirement
+                .needs_update(&self.firmware_version),
             (HardwareWalletType::Trezor(_), DeviceModel::TrezorOne) => TrezorFirmware::default()
                 .model_one_requirement
                 .needs_update(&self.firmware_version),
@@ -334,20 +432,7 @@ impl Signer for HardwareWallet {
     }
 
     async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
-        let _account = self
-            .get_current_account()
-            .ok_or(HardwareWalletError::DeviceNotConnected)?;
-
-        match &self.wallet_type {
-            HardwareWalletType::Ledger(ledger) => ledger
-                .sign_transaction(tx)
-                .await
-                .map_err(|e| HardwareWalletError::SigningFailed(e.to_string())),
-            HardwareWalletType::Trezor(trezor) => trezor
-                .sign_transaction(tx)
-                .await
-                .map_err(|e| HardwareWalletError::SigningFailed(e.to_string())),
-        }
+        self.sign_typed_transaction(tx).await
     }
 
     async fn sign_typed_data<T: Send + Sync + ethers::types::transaction::eip712::Eip712>(
@@ -394,6 +479,7 @@ impl Signer for HardwareWallet {
 mod tests {
     use super::*;
     use ethers::types::transaction::eip712::{EIP712Domain, TypedData as EthersTypedData};
+    use ethers::types::U256;
     use std::collections::BTreeMap;
     use std::str::FromStr;
 
@@ -483,6 +569,34 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_sign_eip1559_transaction() {
+        let (wallet, _) = setup_test_wallet().await;
+        assert!(wallet.supports_eip1559());
+
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::from([0x2; 20]))
+            .value(1)
+            .max_fee_per_gas(100)
+            .max_priority_fee_per_gas(2);
+        let display = wallet
+            .display_data(&TypedTransaction::Eip1559(tx.clone().chain_id(1u64)))
+            .unwrap();
+        assert_eq!(display.max_priority_fee_per_gas, Some(U256::from(2)));
+        // 本 SDK 不提供设备交易签名，返回错误而不是无效签名
+        assert!(matches!(
+            wallet.sign_eip1559_transaction(&tx).await,
+            Err(HardwareWalletError::SigningFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_eip1559_device_support() {
+        assert!(!DeviceModel::TrezorOne.supports_eip1559(&Version::new(1, 10, 0)));
+        assert!(DeviceModel::TrezorOne.supports_eip1559(&Version::new(1, 10, 4)));
+        assert!(DeviceModel::LedgerStax.supports_eip1559(&Version::new(1, 0, 0)));
+    }
+
     #[tokio::test]
     #[ignore] // 需要实际的 Ledger 设备才能运行
     async fn test_ledger_firmware_check() {
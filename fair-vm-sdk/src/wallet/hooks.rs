@@ -0,0 +1,192 @@
+//! 签名前钩子
+//!
+//! 通过 [`FairWallet::add_pre_sign_hook`](super::FairWallet::add_pre_sign_hook) 登记的
+//! [`PreSignHook`] 会在交易签名前按登记顺序逐个等待，全部放行后才生成签名，用于接入人工审批、
+//! 一次性口令、HSM 策略等双人复核流程。钩子在交易策略检查通过之后运行，任一钩子拒绝或超时
+//! 都会中止签名。
+
+use async_trait::async_trait;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, NameOrAddress, H256, U256};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 待签名交易的上下文
+#[derive(Debug, Clone)]
+pub struct SignContext {
+    /// 签名账户
+    pub from: Address,
+    /// 链 ID
+    pub chain_id: u64,
+    /// 将被签名的哈希，可与设备或审批界面上显示的内容核对
+    pub sighash: H256,
+    /// 待签名交易
+    pub transaction: TypedTransaction,
+}
+
+impl SignContext {
+    /// 接收地址，合约创建时为空
+    pub fn to(&self) -> Option<Address> {
+        match self.transaction.to()? {
+            NameOrAddress::Address(addr) => Some(*addr),
+            NameOrAddress::Name(_) => None,
+        }
+    }
+
+    /// 转账金额
+    pub fn value(&self) -> U256 {
+        self.transaction.value().copied().unwrap_or_default()
+    }
+
+    /// 调用数据
+    pub fn data(&self) -> Bytes {
+        self.transaction.data().cloned().unwrap_or_default()
+    }
+}
+
+/// 签名前钩子，返回 `Err` 时拒绝签名，错误内容作为拒绝原因
+#[async_trait]
+pub trait PreSignHook: Send + Sync {
+    /// 用于错误信息的名称
+    fn name(&self) -> &str {
+        "pre_sign_hook"
+    }
+
+    /// 审批一次签名
+    async fn approve(&self, context: &SignContext) -> Result<(), String>;
+}
+
+#[async_trait]
+impl<F, Fut> PreSignHook for F
+where
+    F: Fn(SignContext) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), String>> + Send,
+{
+    async fn approve(&self, context: &SignContext) -> Result<(), String> {
+        self(context.clone()).await
+    }
+}
+
+/// 钱包上登记的签名前钩子
+#[derive(Clone, Default)]
+pub struct PreSignHooks {
+    hooks: Vec<Arc<dyn PreSignHook>>,
+    /// 单个钩子的等待上限，未设置时一直等待
+    timeout: Option<Duration>,
+}
+
+impl std::fmt::Debug for PreSignHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreSignHooks")
+            .field(
+                "hooks",
+                &self
+                    .hooks
+                    .iter()
+                    .map(|hook| hook.name())
+                    .collect::<Vec<_>>(),
+            )
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl PreSignHooks {
+    /// 登记钩子
+    pub fn push(&mut self, hook: Arc<dyn PreSignHook>) {
+        self.hooks.push(hook);
+    }
+
+    /// 设置单个钩子的等待上限
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// 按登记顺序等待全部钩子放行，返回第一个拒绝的钩子名称和原因
+    pub async fn approve(&self, context: &SignContext) -> Result<(), (String, String)> {
+        for hook in &self.hooks {
+            let result = match self.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, hook.approve(context))
+                    .await
+                    .unwrap_or_else(|_| Err(format!("{:?} 内未完成审批", timeout))),
+                None => hook.approve(context).await,
+            };
+            result.map_err(|reason| (hook.name().to_string(), reason))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::TransactionRequest;
+
+    fn context(value: u64) -> SignContext {
+        let transaction: TypedTransaction = TransactionRequest::new()
+            .to(Address::repeat_byte(1))
+            .value(value)
+            .into();
+        SignContext {
+            from: Address::zero(),
+            chain_id: 1,
+            sighash: transaction.sighash(),
+            transaction,
+        }
+    }
+
+    struct Otp;
+
+    #[async_trait]
+    impl PreSignHook for Otp {
+        fn name(&self) -> &str {
+            "otp"
+        }
+
+        async fn approve(&self, context: &SignContext) -> Result<(), String> {
+            if context.value() > U256::from(10) {
+                Err("需要一次性口令".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_in_order() {
+        let mut hooks = PreSignHooks::default();
+        hooks.push(Arc::new(|context: SignContext| async move {
+            assert_eq!(context.to(), Some(Address::repeat_byte(1)));
+            Ok::<_, String>(())
+        }));
+        hooks.push(Arc::new(Otp));
+        assert_eq!(hooks.len(), 2);
+
+        assert!(hooks.approve(&context(10)).await.is_ok());
+        assert_eq!(
+            hooks.approve(&context(11)).await,
+            Err(("otp".to_string(), "需要一次性口令".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hook_timeout() {
+        let mut hooks = PreSignHooks::default();
+        hooks.push(Arc::new(|_: SignContext| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok::<_, String>(())
+        }));
+        hooks.set_timeout(Some(Duration::from_millis(10)));
+        let (name, _) = hooks.approve(&context(1)).await.unwrap_err();
+        assert_eq!(name, "pre_sign_hook");
+    }
+}
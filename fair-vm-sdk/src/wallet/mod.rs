@@ -1,5 +1,8 @@
 //! FairVM钱包实现
 
+use crate::address::{AvaxAddress, KeyAddresses};
+use crate::units::format_gwei;
+#[cfg(feature = "hardware-wallet")]
 use crate::wallet::hardware::{
     HardwareAccount, HardwareWallet, HardwareWalletError, HardwareWalletType,
 };
@@ -8,9 +11,11 @@ use crate::wallet::transaction::{
     TransactionError, TransactionInfo, TransactionManager, TransactionStatus,
 };
 use ethers::{
+    core::k256::elliptic_curve::sec1::ToEncodedPoint,
     core::k256::SecretKey,
     core::types::{
-        Address, Bytes, NameOrAddress, Signature, Transaction, TransactionRequest, H256, U256, U64,
+        Address, Bytes, Eip1559TransactionRequest, NameOrAddress, Signature, Transaction,
+        TransactionRequest, H256, U256, U64,
     },
     middleware::Middleware,
     providers::{Http, Provider},
@@ -31,11 +36,16 @@ use thiserror::Error;
 use tokio::sync::RwLock;
 use typenum::U32;
 
+#[cfg(feature = "hardware-wallet")]
 pub mod firmware;
+#[cfg(feature = "hardware-wallet")]
 pub mod hardware;
+pub mod hooks;
 pub mod keystore;
 pub mod message;
 pub mod mnemonic;
+pub mod policy;
+pub mod replacement;
 pub mod transaction;
 
 /// 费用建议
@@ -53,8 +63,10 @@ impl std::fmt::Display for FeesSuggestion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "基础费用: {} wei\n最大费用: {} wei\n优先费用: {} wei",
-            self.base_fee, self.max_fee_per_gas, self.max_priority_fee_per_gas
+            "基础费用: {}\n最大费用: {}\n优先费用: {}",
+            format_gwei(self.base_fee),
+            format_gwei(self.max_fee_per_gas),
+            format_gwei(self.max_priority_fee_per_gas)
         )
     }
 }
@@ -112,6 +124,15 @@ pub enum WalletError {
 
     #[error("账户错误: {0}")]
     AccountError(String),
+
+    #[error("观察钱包 {0:?} 没有私钥，无法签名")]
+    WatchOnly(Address),
+
+    #[error("交易策略拒绝签名: {0}")]
+    PolicyViolation(#[from] policy::PolicyViolation),
+
+    #[error("签名前钩子 {hook} 拒绝签名: {reason}")]
+    HookRejected { hook: String, reason: String },
 }
 
 impl From<TransactionError> for WalletError {
@@ -132,6 +153,7 @@ impl From<ethers::providers::ProviderError> for WalletError {
     }
 }
 
+#[cfg(feature = "hardware-wallet")]
 impl From<HardwareWalletError> for WalletError {
     fn from(err: HardwareWalletError) -> Self {
         WalletError::HardwareWalletError(err.to_string())
@@ -170,7 +192,10 @@ pub enum WalletType {
         LocalWallet,
     ),
     /// 硬件钱包
+    #[cfg(feature = "hardware-wallet")]
     Hardware(HardwareWallet),
+    /// 观察钱包，只有地址没有私钥，可查询余额和准备未签名交易
+    WatchOnly(Address),
 }
 
 /// 钱包接口
@@ -181,6 +206,12 @@ pub struct FairWallet {
     mnemonic: Option<String>,
     #[serde(skip)]
     transaction_manager: Arc<RwLock<TransactionManager>>,
+    /// 签名前检查的交易策略，不随钱包序列化
+    #[serde(skip)]
+    policy: Option<policy::PolicyEngine>,
+    /// 签名前等待的审批钩子，不随钱包序列化
+    #[serde(skip)]
+    pre_sign_hooks: hooks::PreSignHooks,
 }
 
 impl FairWallet {
@@ -193,6 +224,8 @@ impl FairWallet {
             chain_id,
             mnemonic: Some(mnemonic.get_phrase().to_string()),
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
@@ -205,6 +238,8 @@ impl FairWallet {
             chain_id,
             mnemonic: Some(phrase.to_string()),
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
@@ -221,10 +256,13 @@ impl FairWallet {
             chain_id,
             mnemonic: None,
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
     /// 连接 Ledger 钱包
+    #[cfg(feature = "hardware-wallet")]
     pub async fn connect_ledger(
         derivation_path: Option<String>,
         chain_id: u64,
@@ -238,10 +276,13 @@ impl FairWallet {
             chain_id,
             mnemonic: None,
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
     /// 连接 Trezor 钱包
+    #[cfg(feature = "hardware-wallet")]
     pub async fn connect_trezor(
         derivation_path: Option<String>,
         chain_id: u64,
@@ -255,9 +296,114 @@ impl FairWallet {
             chain_id,
             mnemonic: None,
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
+        })
+    }
+
+    /// 连接 Trezor 口令保护的隐藏钱包
+    #[cfg(feature = "hardware-wallet")]
+    pub async fn connect_trezor_hidden(
+        derivation_path: Option<String>,
+        chain_id: u64,
+        passphrase: firmware::PassphraseEntry,
+    ) -> Result<Self, WalletError> {
+        let hw_wallet = HardwareWallet::new_trezor_hidden(derivation_path, chain_id, passphrase)
+            .await
+            .map_err(|e| WalletError::HardwareWalletError(e.to_string()))?;
+
+        Ok(Self {
+            inner: WalletType::Hardware(hw_wallet),
+            chain_id,
+            mnemonic: None,
+            transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
+    /// 创建观察钱包
+    pub fn watch_only(address: Address, chain_id: u64) -> Self {
+        Self {
+            inner: WalletType::WatchOnly(address),
+            chain_id,
+            mnemonic: None,
+            transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
+        }
+    }
+
+    /// 是否为观察钱包
+    pub fn is_watch_only(&self) -> bool {
+        matches!(self.inner, WalletType::WatchOnly(_))
+    }
+
+    /// 设置签名前检查的交易策略
+    pub fn with_policy(mut self, policy: policy::PolicyEngine) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// 设置或移除交易策略
+    pub fn set_policy(&mut self, policy: Option<policy::PolicyEngine>) {
+        self.policy = policy;
+    }
+
+    /// 当前的交易策略
+    pub fn policy(&self) -> Option<&policy::PolicyEngine> {
+        self.policy.as_ref()
+    }
+
+    /// 登记签名前钩子，交易签名前按登记顺序等待全部钩子放行
+    pub fn add_pre_sign_hook(&mut self, hook: Arc<dyn hooks::PreSignHook>) {
+        self.pre_sign_hooks.push(hook);
+    }
+
+    /// 设置单个签名前钩子的等待上限，超时视为拒绝
+    pub fn set_pre_sign_timeout(&mut self, timeout: Option<std::time::Duration>) {
+        self.pre_sign_hooks.set_timeout(timeout);
+    }
+
+    /// 签名前依次检查交易策略和签名前钩子，观察钱包不检查
+    async fn authorize(&self, tx: &TypedTransaction) -> Result<(), WalletError> {
+        if self.is_watch_only() || (self.policy.is_none() && self.pre_sign_hooks.is_empty()) {
+            return Ok(());
+        }
+        let from = self.address().await?;
+        if let Some(policy) = &self.policy {
+            let request = policy::PolicyRequest {
+                from,
+                to: tx.to().map(|addr| match addr {
+                    NameOrAddress::Address(addr) => *addr,
+                    NameOrAddress::Name(_) => Address::zero(),
+                }),
+                value: tx.value().copied().unwrap_or_default(),
+                data_len: tx.data().map_or(0, |data| data.len()),
+                chain_id: self.chain_id,
+            };
+            policy.authorize(&request)?;
+        }
+        if !self.pre_sign_hooks.is_empty() {
+            // 未指定链 ID 时签名使用钱包的链 ID
+            let mut transaction = tx.clone();
+            if transaction.chain_id().is_none() {
+                transaction.set_chain_id(self.chain_id);
+            }
+            let context = hooks::SignContext {
+                from,
+                chain_id: self.chain_id,
+                sighash: transaction.sighash(),
+                transaction,
+            };
+            self.pre_sign_hooks
+                .approve(&context)
+                .await
+                .map_err(|(hook, reason)| WalletError::HookRejected { hook, reason })?;
+        }
+        Ok(())
+    }
+
     /// 获取助记词
     pub fn get_mnemonic(&self) -> Option<&str> {
         self.mnemonic.as_deref()
@@ -267,7 +413,9 @@ impl FairWallet {
     pub fn export_private_key(&self) -> String {
         match &self.inner {
             WalletType::Local(wallet) => hex::encode(wallet.signer().to_bytes()),
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(_) => "Hardware wallet does not expose private key".to_string(),
+            WalletType::WatchOnly(_) => "Watch-only wallet does not hold a private key".to_string(),
         }
     }
 
@@ -275,9 +423,31 @@ impl FairWallet {
     pub async fn address(&self) -> Result<Address, WalletError> {
         match &self.inner {
             WalletType::Local(wallet) => Ok(wallet.address()),
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hw_wallet) => {
                 Ok(hw_wallet.get_current_account().unwrap_or_default())
             }
+            WalletType::WatchOnly(address) => Ok(*address),
+        }
+    }
+
+    /// 本地钱包私钥在 Avalanche X/P 链上对应的 bech32 地址
+    ///
+    /// Avalanche 与 FairVM 对同一私钥派生的地址不同，需由公钥计算。
+    pub fn avax_address(&self, chain: &str, hrp: &str) -> Result<AvaxAddress, WalletError> {
+        match &self.inner {
+            WalletType::Local(wallet) => {
+                let public_key = wallet.signer().verifying_key().to_encoded_point(true);
+                let addresses = KeyAddresses::from_public_key(public_key.as_bytes())
+                    .map_err(|e| WalletError::AccountError(e.to_string()))?;
+                AvaxAddress::new(Some(chain), hrp, addresses.avalanche)
+                    .map_err(|e| WalletError::AccountError(e.to_string()))
+            }
+            #[cfg(feature = "hardware-wallet")]
+            WalletType::Hardware(_) => Err(WalletError::AccountError(
+                "硬件钱包不支持推导 Avalanche 地址".to_string(),
+            )),
+            WalletType::WatchOnly(address) => Err(WalletError::WatchOnly(*address)),
         }
     }
 
@@ -288,10 +458,12 @@ impl FairWallet {
                 .sign_message(message)
                 .await
                 .map_err(|e| WalletError::SigningError(e.to_string())),
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hw_wallet) => hw_wallet
                 .sign_message(message)
                 .await
                 .map_err(|e| WalletError::HardwareWalletError(e.to_string())),
+            WalletType::WatchOnly(address) => Err(WalletError::WatchOnly(*address)),
         }
     }
 
@@ -310,9 +482,9 @@ impl FairWallet {
         &self,
         tx: TransactionRequest,
     ) -> Result<Transaction, WalletError> {
+        self.authorize(&TypedTransaction::Legacy(tx.clone()))
+            .await?;
         let tx_for_local = tx.clone();
-        let tx_for_hardware = tx.clone();
-        let tx_for_build = tx;
         match &self.inner {
             WalletType::Local(local) => {
                 let signature = local
@@ -349,26 +521,27 @@ impl FairWallet {
                     other: Default::default(),
                 })
             }
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => {
                 let signature = hardware
-                    .sign_transaction(tx_for_hardware)
+                    .sign_transaction(tx.clone())
                     .await
                     .map_err(|e| WalletError::SigningError(format!("硬件钱包签名失败: {}", e)))?;
                 Ok(Transaction {
                     hash: H256::zero(),
-                    nonce: tx_for_build.nonce.unwrap_or_default(),
+                    nonce: tx.nonce.unwrap_or_default(),
                     block_hash: None,
                     block_number: None,
                     transaction_index: None,
                     from: hardware.get_current_account().unwrap_or_default(),
-                    to: tx_for_build.to.map(|addr| match addr {
+                    to: tx.to.map(|addr| match addr {
                         NameOrAddress::Address(addr) => addr,
                         NameOrAddress::Name(_) => Address::zero(),
                     }),
-                    value: tx_for_build.value.unwrap_or_default(),
-                    gas_price: Some(tx_for_build.gas_price.unwrap_or_default()),
-                    gas: tx_for_build.gas.unwrap_or_default(),
-                    input: tx_for_build.data.clone().unwrap_or_default(),
+                    value: tx.value.unwrap_or_default(),
+                    gas_price: Some(tx.gas_price.unwrap_or_default()),
+                    gas: tx.gas.unwrap_or_default(),
+                    input: tx.data.clone().unwrap_or_default(),
                     v: U64::from(signature.v),
                     r: signature.r,
                     s: signature.s,
@@ -380,9 +553,38 @@ impl FairWallet {
                     other: Default::default(),
                 })
             }
+            WalletType::WatchOnly(address) => Err(WalletError::WatchOnly(*address)),
         }
     }
 
+    /// 签名 EIP-1559 交易，返回签名后的原始交易
+    ///
+    /// 固件不支持 EIP-1559 的硬件钱包返回错误；硬件钱包的设备交易签名不在本 SDK 范围内。
+    pub async fn sign_eip1559_transaction(
+        &self,
+        tx: Eip1559TransactionRequest,
+    ) -> Result<Bytes, WalletError> {
+        let tx = match tx.chain_id {
+            Some(_) => tx,
+            None => tx.chain_id(self.chain_id),
+        };
+        let typed_tx = TypedTransaction::Eip1559(tx.clone());
+        self.authorize(&typed_tx).await?;
+        let signature = match &self.inner {
+            WalletType::Local(local) => local
+                .sign_transaction(&typed_tx)
+                .await
+                .map_err(|e| WalletError::SigningError(format!("本地钱包签名失败: {}", e)))?,
+            #[cfg(feature = "hardware-wallet")]
+            WalletType::Hardware(hardware) => hardware
+                .sign_eip1559_transaction(&tx)
+                .await
+                .map_err(|e| WalletError::SigningError(format!("硬件钱包签名失败: {}", e)))?,
+            WalletType::WatchOnly(address) => return Err(WalletError::WatchOnly(*address)),
+        };
+        Ok(typed_tx.rlp_signed(&signature))
+    }
+
     /// 发送交易
     pub async fn send_transaction(
         &self,
@@ -398,6 +600,57 @@ impl FairWallet {
         Ok(pending_tx.tx_hash())
     }
 
+    /// 以更高费用重发尚未打包的交易，返回替换交易的哈希
+    pub async fn speed_up_transaction(
+        &self,
+        provider: &Provider<Http>,
+        tx_hash: H256,
+    ) -> Result<H256, WalletError> {
+        self.replace_transaction(provider, tx_hash, replacement::Replacement::SpeedUp)
+            .await
+    }
+
+    /// 发送同 nonce、金额为 0 的自转账取消尚未打包的交易，返回取消交易的哈希
+    pub async fn cancel_transaction(
+        &self,
+        provider: &Provider<Http>,
+        tx_hash: H256,
+    ) -> Result<H256, WalletError> {
+        self.replace_transaction(provider, tx_hash, replacement::Replacement::Cancel)
+            .await
+    }
+
+    async fn replace_transaction(
+        &self,
+        provider: &Provider<Http>,
+        tx_hash: H256,
+        kind: replacement::Replacement,
+    ) -> Result<H256, WalletError> {
+        let original = provider
+            .get_transaction(tx_hash)
+            .await
+            .map_err(|e| WalletError::NetworkError(e.to_string()))?
+            .ok_or_else(|| WalletError::TransactionError(format!("交易 {:?} 不存在", tx_hash)))?;
+        replacement::check_replaceable(&original, self.address().await?)
+            .map_err(WalletError::TransactionError)?;
+        let suggestion = self.get_fees(provider).await.ok();
+        let raw = match replacement::replacement_transaction(
+            &original,
+            kind,
+            suggestion.as_ref(),
+            self.chain_id,
+        ) {
+            TypedTransaction::Eip1559(tx) => self.sign_eip1559_transaction(tx).await?,
+            TypedTransaction::Legacy(tx) => self.sign_transaction(tx).await?.rlp(),
+            TypedTransaction::Eip2930(_) => unreachable!("替换交易不使用 EIP-2930"),
+        };
+        let pending_tx = provider
+            .send_raw_transaction(raw)
+            .await
+            .map_err(|e| WalletError::TransactionError(e.to_string()))?;
+        Ok(pending_tx.tx_hash())
+    }
+
     /// 获取当前网络的费用建议
     pub async fn get_fees(&self, provider: &Provider<Http>) -> Result<FeesSuggestion, WalletError> {
         let fee_history = provider
@@ -467,24 +720,30 @@ impl FairWallet {
     }
 
     /// 获取硬件钱包类型
+    #[cfg(feature = "hardware-wallet")]
     pub async fn get_hardware_wallet_type(&self) -> Option<HardwareWalletType> {
         match &self.inner {
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hw) => Some(hw.wallet_type()),
             _ => None,
         }
     }
 
     /// 获取硬件钱包派生路径
+    #[cfg(feature = "hardware-wallet")]
     pub async fn get_hardware_derivation_path(&self) -> Option<String> {
         match &self.inner {
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hw) => Some(hw.get_derivation_path().to_string()),
             _ => None,
         }
     }
 
     /// 获取硬件钱包账户列表
+    #[cfg(feature = "hardware-wallet")]
     pub async fn get_hardware_accounts(&self) -> Result<Vec<HardwareAccount>, WalletError> {
         match &self.inner {
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hw) => {
                 let addresses = hw.get_accounts();
                 Ok(addresses
@@ -501,11 +760,13 @@ impl FairWallet {
     }
 
     /// 设置当前硬件钱包账户
+    #[cfg(feature = "hardware-wallet")]
     pub async fn set_hardware_current_account(
         &mut self,
         address: Address,
     ) -> Result<Address, WalletError> {
         match &mut self.inner {
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => {
                 let accounts = hardware.get_accounts();
                 if let Some(index) = accounts.iter().position(|&addr| addr == address) {
@@ -521,10 +782,12 @@ impl FairWallet {
     }
 
     /// 获取当前硬件钱包账户
+    #[cfg(feature = "hardware-wallet")]
     pub async fn get_current_hardware_account(
         &self,
     ) -> Result<Option<HardwareAccount>, WalletError> {
         match &self.inner {
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hw) => Ok(hw.get_current_account().map(|addr| HardwareAccount {
                 address: addr,
                 derivation_path: hw.get_derivation_path().to_string(),
@@ -543,6 +806,8 @@ impl FairWallet {
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(
                 nonce.as_u64() as usize
             ))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         }
     }
 
@@ -593,19 +858,19 @@ impl FairWallet {
         U256::from(manager.get_all_transactions().len() as u64)
     }
 
-    /// 签名普通消息
+    /// 按 EIP-191 个人消息格式签名
     pub async fn sign_personal_message(&self, message: &[u8]) -> Result<Signature, WalletError> {
         match &self.inner {
-            WalletType::Local(wallet) => {
-                let signer = MessageSignerImpl::new(wallet.address());
-                signer
-                    .sign_message(message)
-                    .map_err(|e| WalletError::SigningError(e.to_string()))
-            }
+            WalletType::Local(wallet) => wallet
+                .sign_message(message)
+                .await
+                .map_err(|e| WalletError::SigningError(e.to_string())),
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hw_wallet) => hw_wallet
                 .sign_message(message)
                 .await
                 .map_err(|e| WalletError::HardwareWalletError(e.to_string())),
+            WalletType::WatchOnly(address) => Err(WalletError::WatchOnly(*address)),
         }
     }
 
@@ -632,10 +897,12 @@ impl FairWallet {
                 .sign_typed_data(typed_data)
                 .await
                 .map_err(|e| WalletError::MessageSignError(e.to_string())),
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => hardware
                 .sign_typed_data(typed_data)
                 .await
                 .map_err(|e| WalletError::HardwareWalletError(e.to_string())),
+            WalletType::WatchOnly(address) => Err(WalletError::WatchOnly(*address)),
         }
     }
 
@@ -653,6 +920,7 @@ impl FairWallet {
                     .verify_typed_data_signature(typed_data, signature, address)
                     .map_err(|e| WalletError::VerificationError(e.to_string()))?)
             }
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => {
                 let address = hardware
                     .get_current_account()
@@ -662,6 +930,12 @@ impl FairWallet {
                     .verify_typed_data_signature(typed_data, signature, address)
                     .map_err(|e| WalletError::VerificationError(e.to_string()))?)
             }
+            WalletType::WatchOnly(address) => {
+                let signer = MessageSignerImpl::new(*address);
+                Ok(signer
+                    .verify_typed_data_signature(typed_data, signature, *address)
+                    .map_err(|e| WalletError::VerificationError(e.to_string()))?)
+            }
         }
     }
 
@@ -685,6 +959,8 @@ impl FairWallet {
             chain_id,
             mnemonic: None,
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
@@ -698,10 +974,13 @@ impl FairWallet {
             chain_id,
             mnemonic: Some(mnemonic.to_string()),
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
     /// 创建新的硬件钱包实例
+    #[cfg(feature = "hardware-wallet")]
     pub async fn new_hardware(
         wallet_type: HardwareWalletType,
         base_path: &str,
@@ -721,6 +1000,8 @@ impl FairWallet {
             chain_id,
             mnemonic: None,
             transaction_manager: Arc::new(RwLock::new(TransactionManager::new(100))),
+            policy: None,
+            pre_sign_hooks: hooks::PreSignHooks::default(),
         })
     }
 
@@ -738,8 +1019,10 @@ impl FairWallet {
     }
 
     /// 添加硬件钱包账户
+    #[cfg(feature = "hardware-wallet")]
     pub async fn add_hardware_account(&mut self, index: u32) -> Result<Address, WalletError> {
         match &mut self.inner {
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => hardware
                 .add_account(index)
                 .await
@@ -822,14 +1105,18 @@ impl FairWallet {
     pub async fn get_accounts(&self) -> Vec<Address> {
         match &self.inner {
             WalletType::Local(local) => vec![local.address()],
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => hardware.get_accounts(),
+            WalletType::WatchOnly(address) => vec![*address],
         }
     }
 
     pub async fn get_current_account(&self) -> Option<Address> {
         match &self.inner {
             WalletType::Local(local) => Some(local.address()),
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => hardware.get_current_account(),
+            WalletType::WatchOnly(address) => Some(*address),
         }
     }
 
@@ -844,6 +1131,16 @@ impl FairWallet {
                     ))
                 }
             }
+            WalletType::WatchOnly(watched) => {
+                if *watched == address {
+                    Ok(address)
+                } else {
+                    Err(WalletError::WalletError(
+                        "观察钱包不支持切换账户".to_string(),
+                    ))
+                }
+            }
+            #[cfg(feature = "hardware-wallet")]
             WalletType::Hardware(hardware) => {
                 let accounts = hardware.get_accounts();
                 if let Some(index) = accounts.iter().position(|&addr| addr == address) {
@@ -857,6 +1154,75 @@ impl FairWallet {
         }
     }
 
+    /// 获取账户余额
+    pub async fn get_balance(&self, provider: &Provider<Http>) -> Result<U256, WalletError> {
+        let address = self.address().await?;
+        provider
+            .get_balance(address, None)
+            .await
+            .map_err(|e| WalletError::NetworkError(e.to_string()))
+    }
+
+    /// 准备未签名的交易，填充发送方、nonce、gas 价格和 gas 限额
+    ///
+    /// 观察钱包可用此方法生成交易，再交给离线签名设备签名。
+    pub async fn prepare_transaction(
+        &self,
+        provider: &Provider<Http>,
+        to: Option<Address>,
+        value: U256,
+        data: Bytes,
+    ) -> Result<TransactionRequest, WalletError> {
+        let from = self.address().await?;
+        let nonce = self.get_nonce(provider, from).await?;
+        let gas_price = provider
+            .get_gas_price()
+            .await
+            .map_err(|e| WalletError::NetworkError(e.to_string()))?;
+        let gas = self.estimate_gas(provider, to, value, data.clone()).await?;
+
+        let mut tx = TransactionRequest::new()
+            .from(from)
+            .value(value)
+            .data(data)
+            .nonce(nonce)
+            .gas_price(gas_price)
+            .gas(gas)
+            .chain_id(self.chain_id);
+        if let Some(to) = to {
+            tx = tx.to(to);
+        }
+        Ok(tx)
+    }
+
+    /// 准备未签名的 EIP-1559 交易，费用取自当前网络的费用建议
+    pub async fn prepare_eip1559_transaction(
+        &self,
+        provider: &Provider<Http>,
+        to: Option<Address>,
+        value: U256,
+        data: Bytes,
+    ) -> Result<Eip1559TransactionRequest, WalletError> {
+        let from = self.address().await?;
+        let nonce = self.get_nonce(provider, from).await?;
+        let fees = self.get_fees(provider).await?;
+        let gas = self.estimate_gas(provider, to, value, data.clone()).await?;
+
+        let mut tx = Eip1559TransactionRequest::new()
+            .from(from)
+            .value(value)
+            .data(data)
+            .nonce(nonce)
+            .gas(gas)
+            .max_fee_per_gas(fees.max_fee_per_gas)
+            .max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
+            .chain_id(self.chain_id);
+        if let Some(to) = to {
+            tx = tx.to(to);
+        }
+        Ok(tx)
+    }
+
     /// 估算交易 gas
     pub async fn estimate_gas(
         &self,
@@ -889,6 +1255,7 @@ pub enum TransactionType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "hardware-wallet")]
     use crate::wallet::firmware::LedgerFirmware;
     use ethers::types::transaction::eip712::{EIP712Domain, TypedData as EthersTypedData};
     use std::collections::BTreeMap;
@@ -896,6 +1263,7 @@ mod tests {
 
     const TEST_ADDRESS: &str = "7E5F4552091A69125d5DfCb7b8C2659029395Bdf";
 
+    #[cfg(feature = "hardware-wallet")]
     async fn setup_test_wallet() -> (FairWallet, Address) {
         let wallet = FairWallet::new_hardware(
             HardwareWalletType::Ledger(Arc::new(LedgerFirmware::default())),
@@ -918,6 +1286,7 @@ mod tests {
         (wallet, test_address)
     }
 
+    #[cfg(feature = "hardware-wallet")]
     #[tokio::test]
     async fn test_ledger_wallet() {
         let (wallet, test_address) = setup_test_wallet().await;
@@ -926,6 +1295,7 @@ mod tests {
         assert_eq!(current_account.unwrap(), test_address);
     }
 
+    #[cfg(feature = "hardware-wallet")]
     #[tokio::test]
     async fn test_trezor_wallet() {
         let (wallet, test_address) = setup_test_wallet().await;
@@ -934,6 +1304,7 @@ mod tests {
         assert_eq!(current_account.unwrap(), test_address);
     }
 
+    #[cfg(feature = "hardware-wallet")]
     #[tokio::test]
     async fn test_sign_typed_data() {
         let (wallet, _) = setup_test_wallet().await;
@@ -955,4 +1326,162 @@ mod tests {
         let result = wallet.sign_typed_data(&ethers_typed_data).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_sign_personal_message() {
+        let wallet = FairWallet::from_private_key(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            1,
+        )
+        .unwrap();
+        let address = wallet.address().await.unwrap();
+        let signature = wallet.sign_personal_message(b"hello").await.unwrap();
+        assert_eq!(signature.recover("hello").unwrap(), address);
+        assert!(wallet
+            .verify_personal_message(b"hello", &signature)
+            .await
+            .unwrap());
+        assert!(!wallet
+            .verify_personal_message(b"hello!", &signature)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_watch_only_wallet() {
+        let address = Address::from_str(TEST_ADDRESS).unwrap();
+        let wallet = FairWallet::watch_only(address, 1);
+        assert!(wallet.is_watch_only());
+        assert_eq!(wallet.address().await.unwrap(), address);
+        assert_eq!(wallet.get_accounts().await, vec![address]);
+
+        // 可以记录交易历史
+        let tx = TransactionRequest::new()
+            .to(Address::zero())
+            .value(1u64)
+            .nonce(0u64);
+        let hashes = wallet.add_transactions(vec![tx.clone()]).await.unwrap();
+        assert_eq!(
+            wallet.get_transaction(hashes[0]).await.unwrap().from,
+            address
+        );
+
+        // 任何签名操作都明确报错
+        assert!(matches!(
+            wallet.sign_transaction(tx).await,
+            Err(WalletError::WatchOnly(a)) if a == address
+        ));
+        assert!(matches!(
+            wallet.sign_personal_message(b"hello").await,
+            Err(WalletError::WatchOnly(_))
+        ));
+        assert!(matches!(
+            wallet
+                .sign_eip1559_transaction(Eip1559TransactionRequest::new())
+                .await,
+            Err(WalletError::WatchOnly(_))
+        ));
+        assert!(matches!(
+            wallet.sign_pending_transactions().await,
+            Err(WalletError::WatchOnly(_))
+        ));
+        assert!(wallet.save_to_keystore("unused.json", "password").is_err());
+
+        // 序列化后仍为观察钱包
+        let json = serde_json::to_string(&wallet).unwrap();
+        let restored: FairWallet = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_watch_only());
+    }
+
+    #[tokio::test]
+    async fn test_spending_policy() {
+        let allowed = Address::repeat_byte(1);
+        let wallet = FairWallet::from_private_key(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            1,
+        )
+        .unwrap()
+        .with_policy(policy::PolicyEngine::new(policy::SpendingPolicy {
+            max_value_per_tx: Some(U256::from(100)),
+            allowed_destinations: Some([allowed].into_iter().collect()),
+            ..Default::default()
+        }));
+
+        let tx = TransactionRequest::new()
+            .to(allowed)
+            .value(100u64)
+            .nonce(0u64);
+        wallet.sign_transaction(tx).await.unwrap();
+        assert_eq!(wallet.policy().unwrap().spent_today(), U256::from(100));
+
+        let tx = TransactionRequest::new().to(allowed).value(101u64);
+        assert!(matches!(
+            wallet.sign_transaction(tx).await,
+            Err(WalletError::PolicyViolation(
+                policy::PolicyViolation::PerTransactionLimit { .. }
+            ))
+        ));
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::repeat_byte(2))
+            .value(1u64);
+        assert!(matches!(
+            wallet.sign_eip1559_transaction(tx).await,
+            Err(WalletError::PolicyViolation(
+                policy::PolicyViolation::DestinationNotAllowed(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pre_sign_hooks() {
+        let mut wallet = FairWallet::from_private_key(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            1,
+        )
+        .unwrap();
+        let address = wallet.address().await.unwrap();
+        wallet.add_pre_sign_hook(Arc::new(move |context: hooks::SignContext| async move {
+            assert_eq!(context.from, address);
+            assert_eq!(context.transaction.chain_id(), Some(U64::from(1)));
+            if context.value() > U256::from(10) {
+                Err("审批人拒绝".to_string())
+            } else {
+                Ok(())
+            }
+        }));
+
+        let tx = TransactionRequest::new()
+            .to(Address::repeat_byte(1))
+            .value(10u64)
+            .nonce(0u64);
+        wallet.sign_transaction(tx).await.unwrap();
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::repeat_byte(1))
+            .value(11u64);
+        assert!(matches!(
+            wallet.sign_eip1559_transaction(tx).await,
+            Err(WalletError::HookRejected { reason, .. }) if reason == "审批人拒绝"
+        ));
+    }
+
+    #[test]
+    fn test_avax_address() {
+        // Avalanche 本地网络的预置测试私钥
+        let wallet = FairWallet::from_private_key(
+            "56289e99c94b6912bfc12adc093c9b51124f0dc54ac7a766b2bc5ccf558d8027",
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            wallet.avax_address("P", "local").unwrap().to_string(),
+            "P-local18jma8ppw3nhx5r4ap8clazz0dps7rv5u00z96u"
+        );
+        assert!(wallet.avax_address("Q", "local").is_err());
+
+        let watch_only = FairWallet::watch_only(Address::zero(), 1);
+        assert!(matches!(
+            watch_only.avax_address("X", "avax"),
+            Err(WalletError::WatchOnly(_))
+        ));
+    }
 }
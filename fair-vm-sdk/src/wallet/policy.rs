@@ -0,0 +1,284 @@
+//! 交易策略
+//!
+//! 给 [`FairWallet`](super::FairWallet) 设置 [`PolicyEngine`] 后，每笔交易在签名前按
+//! [`SpendingPolicy`] 检查：单笔金额上限、24 小时滚动窗口内的累计金额上限、允许的接收地址，
+//! 以及超过确认阈值时必须由确认回调放行。任一检查不通过时拒绝签名，适合由服务托管私钥的
+//! 自动化场景。
+//!
+//! 通过检查的交易立即计入累计金额，签名失败也不退回，避免并发签名绕过每日上限。
+
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// 每日额度的滚动窗口（秒）
+pub const DAILY_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// 策略拒绝签名的原因
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PolicyViolation {
+    #[error("交易金额 {value} 超过单笔上限 {limit}")]
+    PerTransactionLimit { value: U256, limit: U256 },
+
+    #[error("24 小时内已花费 {spent}，再加上 {value} 超过每日上限 {limit}")]
+    DailyLimit {
+        spent: U256,
+        value: U256,
+        limit: U256,
+    },
+
+    #[error("接收地址 {0:?} 不在允许列表中")]
+    DestinationNotAllowed(Option<Address>),
+
+    #[error("交易金额 {value} 超过确认阈值 {threshold}，但未设置确认回调")]
+    ConfirmationRequired { value: U256, threshold: U256 },
+
+    #[error("交易金额 {value} 的确认被拒绝")]
+    ConfirmationRejected { value: U256 },
+}
+
+/// 消费策略，未设置的项不做限制
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpendingPolicy {
+    /// 单笔交易金额上限（wei）
+    pub max_value_per_tx: Option<U256>,
+    /// 24 小时内累计金额上限（wei）
+    pub max_value_per_day: Option<U256>,
+    /// 允许的接收地址，设置后合约创建交易也会被拒绝
+    pub allowed_destinations: Option<HashSet<Address>>,
+    /// 金额超过该值时需要确认回调放行（wei）
+    pub confirmation_threshold: Option<U256>,
+}
+
+/// 待签名交易的摘要
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyRequest {
+    /// 发送方地址
+    pub from: Address,
+    /// 接收地址，合约创建时为空
+    pub to: Option<Address>,
+    /// 转账金额（wei）
+    pub value: U256,
+    /// 调用数据长度
+    pub data_len: usize,
+    /// 链 ID
+    pub chain_id: u64,
+}
+
+/// 确认回调，返回 `true` 表示放行
+pub type ConfirmationCallback = Arc<dyn Fn(&PolicyRequest) -> bool + Send + Sync>;
+
+/// 策略引擎，克隆后共享每日额度
+#[derive(Clone)]
+pub struct PolicyEngine {
+    policy: SpendingPolicy,
+    confirm: Option<ConfirmationCallback>,
+    /// 窗口内已放行的（时间戳，金额）
+    spent: Arc<Mutex<VecDeque<(u64, U256)>>>,
+}
+
+impl std::fmt::Debug for PolicyEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolicyEngine")
+            .field("policy", &self.policy)
+            .field("confirm", &self.confirm.is_some())
+            .finish()
+    }
+}
+
+impl PolicyEngine {
+    pub fn new(policy: SpendingPolicy) -> Self {
+        Self {
+            policy,
+            confirm: None,
+            spent: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// 设置超过确认阈值时调用的确认回调
+    pub fn with_confirmation(
+        mut self,
+        confirm: impl Fn(&PolicyRequest) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.confirm = Some(Arc::new(confirm));
+        self
+    }
+
+    /// 当前策略
+    pub fn policy(&self) -> &SpendingPolicy {
+        &self.policy
+    }
+
+    /// 24 小时内已放行的累计金额
+    pub fn spent_today(&self) -> U256 {
+        let mut spent = self.lock();
+        Self::expire(&mut spent, now());
+        spent
+            .iter()
+            .fold(U256::zero(), |sum, (_, value)| sum.saturating_add(*value))
+    }
+
+    /// 检查交易，通过时计入每日额度
+    pub fn authorize(&self, request: &PolicyRequest) -> Result<(), PolicyViolation> {
+        self.authorize_at(request, now())
+    }
+
+    /// 以指定时间（Unix 秒）检查交易，通过时计入每日额度
+    pub fn authorize_at(&self, request: &PolicyRequest, now: u64) -> Result<(), PolicyViolation> {
+        let policy = &self.policy;
+        if let Some(limit) = policy.max_value_per_tx {
+            if request.value > limit {
+                return Err(PolicyViolation::PerTransactionLimit {
+                    value: request.value,
+                    limit,
+                });
+            }
+        }
+        if let Some(allowed) = &policy.allowed_destinations {
+            if !request.to.is_some_and(|to| allowed.contains(&to)) {
+                return Err(PolicyViolation::DestinationNotAllowed(request.to));
+            }
+        }
+
+        // 持锁直到计入额度，并发签名不会同时通过每日上限检查
+        let mut spent = self.lock();
+        Self::expire(&mut spent, now);
+        if let Some(limit) = policy.max_value_per_day {
+            let total = spent
+                .iter()
+                .fold(U256::zero(), |sum, (_, value)| sum.saturating_add(*value));
+            if total.saturating_add(request.value) > limit {
+                return Err(PolicyViolation::DailyLimit {
+                    spent: total,
+                    value: request.value,
+                    limit,
+                });
+            }
+        }
+        if let Some(threshold) = policy.confirmation_threshold {
+            if request.value > threshold {
+                let confirm =
+                    self.confirm
+                        .as_ref()
+                        .ok_or(PolicyViolation::ConfirmationRequired {
+                            value: request.value,
+                            threshold,
+                        })?;
+                if !confirm(request) {
+                    return Err(PolicyViolation::ConfirmationRejected {
+                        value: request.value,
+                    });
+                }
+            }
+        }
+        if !request.value.is_zero() {
+            spent.push_back((now, request.value));
+        }
+        Ok(())
+    }
+
+    fn expire(spent: &mut VecDeque<(u64, U256)>, now: u64) {
+        while spent
+            .front()
+            .is_some_and(|(at, _)| at.saturating_add(DAILY_WINDOW_SECS) <= now)
+        {
+            spent.pop_front();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<(u64, U256)>> {
+        self.spent.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn request(to: Option<Address>, value: u64) -> PolicyRequest {
+        PolicyRequest {
+            from: Address::zero(),
+            to,
+            value: U256::from(value),
+            data_len: 0,
+            chain_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let engine = PolicyEngine::new(SpendingPolicy {
+            max_value_per_tx: Some(U256::from(100)),
+            max_value_per_day: Some(U256::from(150)),
+            ..Default::default()
+        });
+        let to = Some(Address::repeat_byte(1));
+
+        assert!(matches!(
+            engine.authorize_at(&request(to, 101), 0),
+            Err(PolicyViolation::PerTransactionLimit { .. })
+        ));
+        engine.authorize_at(&request(to, 100), 0).unwrap();
+        assert!(matches!(
+            engine.authorize_at(&request(to, 60), 10),
+            Err(PolicyViolation::DailyLimit { .. })
+        ));
+        engine.authorize_at(&request(to, 50), 10).unwrap();
+        // 第一笔移出窗口后恢复额度
+        engine
+            .authorize_at(&request(to, 100), DAILY_WINDOW_SECS)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_destinations_and_confirmation() {
+        let allowed = Address::repeat_byte(1);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let engine = PolicyEngine::new(SpendingPolicy {
+            allowed_destinations: Some([allowed].into_iter().collect()),
+            confirmation_threshold: Some(U256::from(10)),
+            ..Default::default()
+        })
+        .with_confirmation(move |request| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            request.value < U256::from(100)
+        });
+
+        assert_eq!(
+            engine.authorize_at(&request(Some(Address::repeat_byte(2)), 1), 0),
+            Err(PolicyViolation::DestinationNotAllowed(Some(
+                Address::repeat_byte(2)
+            )))
+        );
+        assert_eq!(
+            engine.authorize_at(&request(None, 1), 0),
+            Err(PolicyViolation::DestinationNotAllowed(None))
+        );
+        engine.authorize_at(&request(Some(allowed), 10), 0).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        engine.authorize_at(&request(Some(allowed), 50), 0).unwrap();
+        assert!(matches!(
+            engine.authorize_at(&request(Some(allowed), 100), 0),
+            Err(PolicyViolation::ConfirmationRejected { .. })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let unconfirmed = PolicyEngine::new(engine.policy().clone());
+        assert!(matches!(
+            unconfirmed.authorize_at(&request(Some(allowed), 50), 0),
+            Err(PolicyViolation::ConfirmationRequired { .. })
+        ));
+    }
+}
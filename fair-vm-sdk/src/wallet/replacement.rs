@@ -0,0 +1,193 @@
+//! 交易替换
+//!
+//! 尚未打包的交易可以用同一 nonce、更高费用的交易替换：加速时重发原交易，取消时发送金额为 0
+//! 的自转账。交易池要求费用上限和优先费都至少提高 [`PRICE_BUMP_PERCENT`]，替换交易的费用取
+//! 原费用提高后的值与当前网络费用建议中的较大者。
+
+use super::FeesSuggestion;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{
+    Address, Bytes, Eip1559TransactionRequest, Transaction, TransactionRequest, U256,
+};
+
+/// 替换交易时费用至少提高的百分比，与节点交易池的规则一致
+pub const PRICE_BUMP_PERCENT: u64 = 10;
+
+/// 取消交易使用的 gas 限制
+pub const CANCEL_GAS: u64 = 21_000;
+
+/// 费用按 [`PRICE_BUMP_PERCENT`] 提高后的值，向上取整
+pub fn bump_fee(fee: U256) -> U256 {
+    let bumped = fee.saturating_mul(U256::from(100 + PRICE_BUMP_PERCENT));
+    let (quotient, remainder) = bumped.div_mod(U256::from(100));
+    if remainder.is_zero() {
+        quotient
+    } else {
+        quotient.saturating_add(U256::one())
+    }
+}
+
+/// 替换交易的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replacement {
+    /// 以更高费用重发原交易
+    SpeedUp,
+    /// 发送金额为 0 的自转账
+    Cancel,
+}
+
+/// 构造替换 `original` 的未签名交易
+///
+/// 原交易为 EIP-1559 交易时替换交易也使用 EIP-1559，否则使用传统交易。
+pub fn replacement_transaction(
+    original: &Transaction,
+    kind: Replacement,
+    suggestion: Option<&FeesSuggestion>,
+    chain_id: u64,
+) -> TypedTransaction {
+    let (to, value, data, gas) = match kind {
+        Replacement::SpeedUp => (
+            original.to,
+            original.value,
+            original.input.clone(),
+            original.gas,
+        ),
+        Replacement::Cancel => (
+            Some(original.from),
+            U256::zero(),
+            Bytes::new(),
+            U256::from(CANCEL_GAS),
+        ),
+    };
+    let at_least =
+        |fee: U256, suggested: Option<U256>| bump_fee(fee).max(suggested.unwrap_or_default());
+
+    match (original.max_fee_per_gas, original.max_priority_fee_per_gas) {
+        (Some(max_fee), Some(priority_fee)) => {
+            let max_priority_fee_per_gas = at_least(
+                priority_fee,
+                suggestion.map(|fees| fees.max_priority_fee_per_gas),
+            );
+            let max_fee_per_gas = at_least(max_fee, suggestion.map(|fees| fees.max_fee_per_gas))
+                .max(max_priority_fee_per_gas);
+            let mut tx = Eip1559TransactionRequest::new()
+                .from(original.from)
+                .nonce(original.nonce)
+                .value(value)
+                .data(data)
+                .gas(gas)
+                .max_fee_per_gas(max_fee_per_gas)
+                .max_priority_fee_per_gas(max_priority_fee_per_gas)
+                .chain_id(chain_id);
+            if let Some(to) = to {
+                tx = tx.to(to);
+            }
+            TypedTransaction::Eip1559(tx)
+        }
+        _ => {
+            let gas_price = at_least(
+                original.gas_price.unwrap_or_default(),
+                suggestion.map(|fees| fees.base_fee.saturating_add(fees.max_priority_fee_per_gas)),
+            );
+            let mut tx = TransactionRequest::new()
+                .from(original.from)
+                .nonce(original.nonce)
+                .value(value)
+                .data(data)
+                .gas(gas)
+                .gas_price(gas_price)
+                .chain_id(chain_id);
+            if let Some(to) = to {
+                tx = tx.to(to);
+            }
+            TypedTransaction::Legacy(tx)
+        }
+    }
+}
+
+/// 检查交易能否由 `sender` 替换，返回不能替换的原因
+pub fn check_replaceable(original: &Transaction, sender: Address) -> Result<(), String> {
+    if original.block_number.is_some() {
+        return Err(format!("交易 {:?} 已打包，无法替换", original.hash));
+    }
+    if original.from != sender {
+        return Err(format!(
+            "交易 {:?} 由 {:?} 发送，不属于当前账户 {:?}",
+            original.hash, original.from, sender
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{NameOrAddress, U64};
+
+    fn original(eip1559: bool) -> Transaction {
+        let mut tx = Transaction {
+            from: Address::repeat_byte(1),
+            to: Some(Address::repeat_byte(2)),
+            nonce: U256::from(7),
+            value: U256::from(1_000),
+            gas: U256::from(50_000),
+            input: Bytes::from(vec![1, 2, 3]),
+            ..Default::default()
+        };
+        if eip1559 {
+            tx.max_fee_per_gas = Some(U256::from(200));
+            tx.max_priority_fee_per_gas = Some(U256::from(15));
+        } else {
+            tx.gas_price = Some(U256::from(100));
+        }
+        tx
+    }
+
+    #[test]
+    fn test_bump_fee() {
+        assert_eq!(bump_fee(U256::from(100)), U256::from(110));
+        assert_eq!(bump_fee(U256::from(15)), U256::from(17));
+        assert_eq!(bump_fee(U256::zero()), U256::zero());
+    }
+
+    #[test]
+    fn test_cancel_legacy() {
+        let tx = replacement_transaction(&original(false), Replacement::Cancel, None, 1);
+        let TypedTransaction::Legacy(tx) = tx else {
+            panic!("expected legacy transaction");
+        };
+        assert_eq!(tx.to, Some(NameOrAddress::Address(Address::repeat_byte(1))));
+        assert_eq!(tx.value, Some(U256::zero()));
+        assert_eq!(tx.nonce, Some(U256::from(7)));
+        assert_eq!(tx.gas, Some(U256::from(CANCEL_GAS)));
+        assert_eq!(tx.gas_price, Some(U256::from(110)));
+    }
+
+    #[test]
+    fn test_speed_up_eip1559() {
+        let suggestion = FeesSuggestion {
+            base_fee: U256::from(150),
+            max_fee_per_gas: U256::from(300),
+            max_priority_fee_per_gas: U256::from(15),
+        };
+        let tx =
+            replacement_transaction(&original(true), Replacement::SpeedUp, Some(&suggestion), 1);
+        let TypedTransaction::Eip1559(tx) = tx else {
+            panic!("expected EIP-1559 transaction");
+        };
+        assert_eq!(tx.value, Some(U256::from(1_000)));
+        assert_eq!(tx.data, Some(Bytes::from(vec![1, 2, 3])));
+        // 网络建议更高时采用建议值
+        assert_eq!(tx.max_fee_per_gas, Some(U256::from(300)));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(U256::from(17)));
+    }
+
+    #[test]
+    fn test_check_replaceable() {
+        let mut tx = original(false);
+        assert!(check_replaceable(&tx, Address::repeat_byte(1)).is_ok());
+        assert!(check_replaceable(&tx, Address::repeat_byte(3)).is_err());
+        tx.block_number = Some(U64::from(1));
+        assert!(check_replaceable(&tx, Address::repeat_byte(1)).is_err());
+    }
+}
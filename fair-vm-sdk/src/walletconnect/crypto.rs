@@ -0,0 +1,154 @@
+//! WalletConnect v2 加密工具
+//!
+//! 会话密钥通过 X25519 密钥协商与 HKDF-SHA256 派生，消息使用 ChaCha20-Poly1305 加密，
+//! 信封格式为 `type(1) || [sender_public_key(32)] || iv(12) || sealed`，整体 base64 编码。
+
+use super::WalletConnectError;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// 对称密钥
+pub type SymKey = [u8; 32];
+
+/// 信封类型 0：使用已知对称密钥
+pub const ENVELOPE_TYPE_0: u8 = 0;
+
+/// 信封类型 1：携带发送方公钥，用于首次密钥协商
+pub const ENVELOPE_TYPE_1: u8 = 1;
+
+/// IV 长度
+const IV_LENGTH: usize = 12;
+
+/// 生成随机对称密钥
+pub fn generate_sym_key() -> SymKey {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    key
+}
+
+/// 由对称密钥计算主题（sha256 十六进制）
+pub fn topic_from_sym_key(sym_key: &SymKey) -> String {
+    hex::encode(Sha256::digest(sym_key))
+}
+
+/// X25519 密钥对
+pub struct KeyPair {
+    /// 私钥
+    secret: StaticSecret,
+    /// 公钥
+    public: PublicKey,
+}
+
+impl KeyPair {
+    /// 生成新的密钥对
+    pub fn generate() -> Self {
+        let secret = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let public = PublicKey::from(&secret);
+        Self { secret, public }
+    }
+
+    /// 获取十六进制公钥
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.public.as_bytes())
+    }
+
+    /// 与对方公钥协商出会话对称密钥
+    pub fn derive_sym_key(&self, peer_public_key: &str) -> Result<SymKey, WalletConnectError> {
+        let bytes = hex::decode(peer_public_key.trim_start_matches("0x"))
+            .map_err(|e| WalletConnectError::Crypto(e.to_string()))?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| WalletConnectError::Crypto("公钥长度必须为 32 字节".to_string()))?;
+        let shared = self.secret.diffie_hellman(&PublicKey::from(bytes));
+
+        let mut sym_key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, shared.as_bytes())
+            .expand(&[], &mut sym_key)
+            .map_err(|e| WalletConnectError::Crypto(e.to_string()))?;
+        Ok(sym_key)
+    }
+}
+
+impl std::fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public", &self.public_key_hex())
+            .finish()
+    }
+}
+
+/// 加密消息并封装为信封
+pub fn encrypt(
+    sym_key: &SymKey,
+    plaintext: &[u8],
+    sender_public_key: Option<&[u8; 32]>,
+) -> Result<String, WalletConnectError> {
+    let mut iv = [0u8; IV_LENGTH];
+    rand::thread_rng().fill_bytes(&mut iv);
+    let sealed = ChaCha20Poly1305::new(Key::from_slice(sym_key))
+        .encrypt(Nonce::from_slice(&iv), plaintext)
+        .map_err(|e| WalletConnectError::Crypto(e.to_string()))?;
+
+    let mut envelope = Vec::with_capacity(1 + 32 + IV_LENGTH + sealed.len());
+    match sender_public_key {
+        Some(public_key) => {
+            envelope.push(ENVELOPE_TYPE_1);
+            envelope.extend_from_slice(public_key);
+        }
+        None => envelope.push(ENVELOPE_TYPE_0),
+    }
+    envelope.extend_from_slice(&iv);
+    envelope.extend_from_slice(&sealed);
+    Ok(BASE64.encode(envelope))
+}
+
+/// 解密信封
+pub fn decrypt(sym_key: &SymKey, envelope: &str) -> Result<Vec<u8>, WalletConnectError> {
+    let bytes = BASE64
+        .decode(envelope)
+        .map_err(|e| WalletConnectError::Crypto(e.to_string()))?;
+    let offset = match bytes.first() {
+        Some(&ENVELOPE_TYPE_0) => 1,
+        Some(&ENVELOPE_TYPE_1) => 1 + 32,
+        _ => return Err(WalletConnectError::Crypto("未知的信封类型".to_string())),
+    };
+    if bytes.len() < offset + IV_LENGTH {
+        return Err(WalletConnectError::Crypto("信封长度不足".to_string()));
+    }
+    let (iv, sealed) = bytes[offset..].split_at(IV_LENGTH);
+    ChaCha20Poly1305::new(Key::from_slice(sym_key))
+        .decrypt(Nonce::from_slice(iv), sealed)
+        .map_err(|e| WalletConnectError::Crypto(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_agreement() {
+        let a = KeyPair::generate();
+        let b = KeyPair::generate();
+        let key_a = a.derive_sym_key(&b.public_key_hex()).unwrap();
+        let key_b = b.derive_sym_key(&a.public_key_hex()).unwrap();
+        assert_eq!(key_a, key_b);
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let key = generate_sym_key();
+        for sender in [None, Some(&[7u8; 32])] {
+            let envelope = encrypt(&key, b"hello", sender).unwrap();
+            assert_eq!(decrypt(&key, &envelope).unwrap(), b"hello");
+        }
+        let envelope = encrypt(&key, b"hello", None).unwrap();
+        assert!(decrypt(&generate_sym_key(), &envelope).is_err());
+        assert_eq!(topic_from_sym_key(&key).len(), 64);
+    }
+}
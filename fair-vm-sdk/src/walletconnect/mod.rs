@@ -0,0 +1,1171 @@
+//! WalletConnect v2 集成
+//!
+//! 同时支持两种角色：
+//! - [`WalletConnectDapp`]：dapp 侧，生成配对 URI 并向外部移动钱包请求签名
+//! - [`WalletConnectSigner`]：钱包侧，使用 [`FairWallet`] 作为 WalletConnect dapp 的签名者
+//!
+//! 中继网络通过 [`RelayTransport`] 抽象。本模块只提供进程内的 [`MemoryRelay`]，用于测试和
+//! 同进程集成；接入 WalletConnect 官方中继需要调用方自行实现该 trait（WebSocket 连接与项目 ID 认证）。
+//!
+//! 钱包侧收到的签名请求先检查会话有效期、链和账户，经调用方或 [`RequestApprover`] 确认后才签名。
+
+pub mod crypto;
+
+use crate::wallet::FairWallet;
+use async_trait::async_trait;
+use crypto::{KeyPair, SymKey};
+use ethers::types::transaction::eip712::TypedData;
+use ethers::types::TransactionRequest;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::{Mutex, Notify};
+
+/// 中继协议
+pub const RELAY_PROTOCOL: &str = "irn";
+
+/// 会话有效期（秒）
+pub const SESSION_EXPIRY: u64 = 7 * 24 * 60 * 60;
+
+/// 消息在中继上的保留时间（秒）
+pub const MESSAGE_TTL: u64 = 5 * 60;
+
+/// 中继消息标签
+pub mod tags {
+    /// 会话提案请求
+    pub const SESSION_PROPOSE_REQUEST: u32 = 1100;
+    /// 会话提案响应
+    pub const SESSION_PROPOSE_RESPONSE: u32 = 1101;
+    /// 会话确立请求
+    pub const SESSION_SETTLE_REQUEST: u32 = 1102;
+    /// 会话确立响应
+    pub const SESSION_SETTLE_RESPONSE: u32 = 1103;
+    /// 会话请求
+    pub const SESSION_REQUEST: u32 = 1108;
+    /// 会话请求响应
+    pub const SESSION_REQUEST_RESPONSE: u32 = 1109;
+}
+
+/// 钱包侧支持的 eip155 方法
+pub const SUPPORTED_METHODS: &[&str] = &[
+    "personal_sign",
+    "eth_signTypedData_v4",
+    "eth_signTransaction",
+];
+
+/// 钱包侧支持的 eip155 事件
+pub const SUPPORTED_EVENTS: &[&str] = &["chainChanged", "accountsChanged"];
+
+/// WalletConnect 错误
+#[derive(Debug, Error)]
+pub enum WalletConnectError {
+    #[error("无效的配对 URI: {0}")]
+    InvalidUri(String),
+
+    #[error("加密错误: {0}")]
+    Crypto(String),
+
+    #[error("中继错误: {0}")]
+    Relay(String),
+
+    #[error("协议错误: {0}")]
+    Protocol(String),
+
+    #[error("对方拒绝: {0}")]
+    Rejected(String),
+
+    #[error("等待响应超时")]
+    Timeout,
+
+    #[error("签名错误: {0}")]
+    Signing(String),
+}
+
+/// 配对 URI，形如 `wc:{topic}@2?relay-protocol=irn&symKey={key}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingUri {
+    /// 配对主题
+    pub topic: String,
+    /// 配对对称密钥
+    pub sym_key: SymKey,
+    /// 中继协议
+    pub relay_protocol: String,
+    /// 过期时间戳（秒）
+    pub expiry: Option<u64>,
+}
+
+impl PairingUri {
+    /// 解析配对 URI
+    pub fn parse(uri: &str) -> Result<Self, WalletConnectError> {
+        let rest = uri
+            .strip_prefix("wc:")
+            .ok_or_else(|| WalletConnectError::InvalidUri("缺少 wc: 前缀".to_string()))?;
+        let (path, query) = rest
+            .split_once('?')
+            .ok_or_else(|| WalletConnectError::InvalidUri("缺少查询参数".to_string()))?;
+        let (topic, version) = path
+            .split_once('@')
+            .ok_or_else(|| WalletConnectError::InvalidUri("缺少协议版本".to_string()))?;
+        if version != "2" {
+            return Err(WalletConnectError::InvalidUri(format!(
+                "不支持的协议版本: {}",
+                version
+            )));
+        }
+
+        let params: HashMap<&str, &str> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .collect();
+        let sym_key = params
+            .get("symKey")
+            .ok_or_else(|| WalletConnectError::InvalidUri("缺少 symKey".to_string()))?;
+        let sym_key: SymKey = hex::decode(sym_key)
+            .map_err(|e| WalletConnectError::InvalidUri(e.to_string()))?
+            .try_into()
+            .map_err(|_| WalletConnectError::InvalidUri("symKey 长度必须为 32 字节".to_string()))?;
+        if crypto::topic_from_sym_key(&sym_key) != topic {
+            return Err(WalletConnectError::InvalidUri(
+                "主题与 symKey 不匹配".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            topic: topic.to_string(),
+            sym_key,
+            relay_protocol: params
+                .get("relay-protocol")
+                .unwrap_or(&RELAY_PROTOCOL)
+                .to_string(),
+            expiry: params
+                .get("expiryTimestamp")
+                .and_then(|expiry| expiry.parse().ok()),
+        })
+    }
+}
+
+impl fmt::Display for PairingUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wc:{}@2?relay-protocol={}&symKey={}",
+            self.topic,
+            self.relay_protocol,
+            hex::encode(self.sym_key)
+        )?;
+        if let Some(expiry) = self.expiry {
+            write!(f, "&expiryTimestamp={}", expiry)?;
+        }
+        Ok(())
+    }
+}
+
+/// 参与方元数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// 名称
+    pub name: String,
+    /// 描述
+    pub description: String,
+    /// 网址
+    pub url: String,
+    /// 图标
+    pub icons: Vec<String>,
+}
+
+/// 命名空间
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Namespace {
+    /// 链列表，如 `eip155:2023`
+    #[serde(default)]
+    pub chains: Vec<String>,
+    /// 允许的方法
+    pub methods: Vec<String>,
+    /// 允许的事件
+    pub events: Vec<String>,
+    /// 账户列表，如 `eip155:2023:0x...`（仅会话命名空间）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<String>,
+}
+
+impl Namespace {
+    /// 构造 FairVM 链的 eip155 命名空间
+    pub fn eip155(chain_id: u64, accounts: &[ethers::types::Address]) -> Self {
+        Self {
+            chains: vec![format!("eip155:{}", chain_id)],
+            methods: SUPPORTED_METHODS.iter().map(|m| m.to_string()).collect(),
+            events: SUPPORTED_EVENTS.iter().map(|e| e.to_string()).collect(),
+            accounts: accounts
+                .iter()
+                .map(|addr| format!("eip155:{}:{:?}", chain_id, addr))
+                .collect(),
+        }
+    }
+}
+
+/// JSON-RPC 消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcMessage {
+    /// 消息 ID
+    pub id: u64,
+    /// 协议版本
+    pub jsonrpc: String,
+    /// 方法（请求）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// 参数（请求）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    /// 结果（响应）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// 错误（响应）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+/// JSON-RPC 错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    /// 错误码
+    pub code: i64,
+    /// 错误信息
+    pub message: String,
+}
+
+impl RpcMessage {
+    /// 创建请求
+    pub fn request(method: &str, params: Value) -> Self {
+        Self {
+            id: message_id(),
+            jsonrpc: "2.0".to_string(),
+            method: Some(method.to_string()),
+            params: Some(params),
+            result: None,
+            error: None,
+        }
+    }
+
+    /// 创建成功响应
+    pub fn response(id: u64, result: Value) -> Self {
+        Self {
+            id,
+            jsonrpc: "2.0".to_string(),
+            method: None,
+            params: None,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// 创建错误响应
+    pub fn error(id: u64, code: i64, message: &str) -> Self {
+        Self {
+            id,
+            jsonrpc: "2.0".to_string(),
+            method: None,
+            params: None,
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: message.to_string(),
+            }),
+        }
+    }
+
+    /// 是否为请求
+    pub fn is_request(&self) -> bool {
+        self.method.is_some()
+    }
+}
+
+/// 生成消息 ID（毫秒时间戳 * 1000 + 随机数）
+fn message_id() -> u64 {
+    now_millis() * 1000 + rand::random::<u64>() % 1000
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn now_secs() -> u64 {
+    now_millis() / 1000
+}
+
+/// 中继传输
+#[async_trait]
+pub trait RelayTransport: Send + Sync {
+    /// 向主题发布消息
+    async fn publish(
+        &self,
+        topic: &str,
+        message: String,
+        tag: u32,
+        ttl: u64,
+    ) -> Result<(), WalletConnectError>;
+
+    /// 订阅主题
+    async fn subscribe(&self, topic: &str) -> Result<(), WalletConnectError>;
+
+    /// 接收指定主题的下一条消息
+    async fn receive(&self, topic: &str, timeout: Duration) -> Result<String, WalletConnectError>;
+}
+
+/// 进程内中继中心
+#[derive(Debug, Default)]
+struct RelayHub {
+    /// 主题订阅者
+    subscriptions: HashMap<String, Vec<usize>>,
+    /// 每个客户端的收件箱
+    inboxes: HashMap<usize, VecDeque<(String, String)>>,
+    /// 下一个客户端 ID
+    next_id: usize,
+}
+
+/// 进程内中继，用于测试和同进程集成
+#[derive(Debug, Clone)]
+pub struct MemoryRelay {
+    /// 客户端 ID
+    id: usize,
+    /// 共享的中继中心
+    hub: Arc<Mutex<RelayHub>>,
+    /// 新消息通知
+    notify: Arc<Notify>,
+}
+
+impl MemoryRelay {
+    /// 创建新的中继并返回第一个客户端
+    pub fn new() -> Self {
+        Self {
+            id: 0,
+            hub: Arc::new(Mutex::new(RelayHub {
+                next_id: 1,
+                ..Default::default()
+            })),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    /// 在同一中继上创建新的客户端
+    pub async fn connect(&self) -> Self {
+        let mut hub = self.hub.lock().await;
+        let id = hub.next_id;
+        hub.next_id += 1;
+        Self {
+            id,
+            hub: self.hub.clone(),
+            notify: self.notify.clone(),
+        }
+    }
+}
+
+impl Default for MemoryRelay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl RelayTransport for MemoryRelay {
+    async fn publish(
+        &self,
+        topic: &str,
+        message: String,
+        _tag: u32,
+        _ttl: u64,
+    ) -> Result<(), WalletConnectError> {
+        let mut hub = self.hub.lock().await;
+        let subscribers = hub.subscriptions.get(topic).cloned().unwrap_or_default();
+        for subscriber in subscribers.into_iter().filter(|id| *id != self.id) {
+            hub.inboxes
+                .entry(subscriber)
+                .or_default()
+                .push_back((topic.to_string(), message.clone()));
+        }
+        self.notify.notify_waiters();
+        Ok(())
+    }
+
+    async fn subscribe(&self, topic: &str) -> Result<(), WalletConnectError> {
+        let mut hub = self.hub.lock().await;
+        let subscribers = hub.subscriptions.entry(topic.to_string()).or_default();
+        if !subscribers.contains(&self.id) {
+            subscribers.push(self.id);
+        }
+        Ok(())
+    }
+
+    async fn receive(&self, topic: &str, timeout: Duration) -> Result<String, WalletConnectError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut hub = self.hub.lock().await;
+                if let Some(inbox) = hub.inboxes.get_mut(&self.id) {
+                    if let Some(index) = inbox.iter().position(|(t, _)| t == topic) {
+                        let (_, message) = inbox.remove(index).expect("索引有效");
+                        return Ok(message);
+                    }
+                }
+            }
+
+            tokio::time::timeout_at(deadline, notified)
+                .await
+                .map_err(|_| WalletConnectError::Timeout)?;
+        }
+    }
+}
+
+/// 加密并发布 JSON-RPC 消息
+async fn send_message(
+    transport: &dyn RelayTransport,
+    topic: &str,
+    sym_key: &SymKey,
+    message: &RpcMessage,
+    tag: u32,
+) -> Result<(), WalletConnectError> {
+    let payload =
+        serde_json::to_vec(message).map_err(|e| WalletConnectError::Protocol(e.to_string()))?;
+    let envelope = crypto::encrypt(sym_key, &payload, None)?;
+    transport.publish(topic, envelope, tag, MESSAGE_TTL).await
+}
+
+/// 接收并解密 JSON-RPC 消息
+async fn receive_message(
+    transport: &dyn RelayTransport,
+    topic: &str,
+    sym_key: &SymKey,
+    timeout: Duration,
+) -> Result<RpcMessage, WalletConnectError> {
+    let envelope = transport.receive(topic, timeout).await?;
+    let payload = crypto::decrypt(sym_key, &envelope)?;
+    serde_json::from_slice(&payload).map_err(|e| WalletConnectError::Protocol(e.to_string()))
+}
+
+/// 已建立的会话
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// 会话主题
+    pub topic: String,
+    /// 会话对称密钥
+    pub sym_key: SymKey,
+    /// 对方元数据
+    pub peer_metadata: Metadata,
+    /// 已批准的命名空间
+    pub namespaces: HashMap<String, Namespace>,
+    /// 过期时间戳（秒）
+    pub expiry: u64,
+}
+
+impl Session {
+    /// 会话中批准的账户地址
+    pub fn accounts(&self) -> Vec<ethers::types::Address> {
+        self.namespaces
+            .values()
+            .flat_map(|ns| ns.accounts.iter())
+            .filter_map(|account| account.rsplit(':').next())
+            .filter_map(|addr| addr.parse().ok())
+            .collect()
+    }
+
+    /// 会话是否已过期
+    pub fn is_expired(&self) -> bool {
+        now_secs() >= self.expiry
+    }
+}
+
+/// dapp 侧 WalletConnect 客户端
+pub struct WalletConnectDapp {
+    /// 中继传输
+    transport: Arc<dyn RelayTransport>,
+    /// dapp 元数据
+    metadata: Metadata,
+    /// 本次配对使用的密钥对
+    key_pair: KeyPair,
+    /// 当前配对
+    pairing: Option<PairingUri>,
+}
+
+impl WalletConnectDapp {
+    /// 创建 dapp 客户端
+    pub fn new(transport: Arc<dyn RelayTransport>, metadata: Metadata) -> Self {
+        Self {
+            transport,
+            metadata,
+            key_pair: KeyPair::generate(),
+            pairing: None,
+        }
+    }
+
+    /// 创建配对并发送会话提案，返回需要展示给钱包的 URI
+    pub async fn connect(
+        &mut self,
+        required_namespaces: HashMap<String, Namespace>,
+    ) -> Result<PairingUri, WalletConnectError> {
+        let sym_key = crypto::generate_sym_key();
+        let uri = PairingUri {
+            topic: crypto::topic_from_sym_key(&sym_key),
+            sym_key,
+            relay_protocol: RELAY_PROTOCOL.to_string(),
+            expiry: Some(now_secs() + MESSAGE_TTL),
+        };
+        self.transport.subscribe(&uri.topic).await?;
+
+        let proposal = RpcMessage::request(
+            "wc_sessionPropose",
+            json!({
+                "relays": [{ "protocol": RELAY_PROTOCOL }],
+                "requiredNamespaces": required_namespaces,
+                "proposer": {
+                    "publicKey": self.key_pair.public_key_hex(),
+                    "metadata": self.metadata,
+                },
+            }),
+        );
+        send_message(
+            self.transport.as_ref(),
+            &uri.topic,
+            &uri.sym_key,
+            &proposal,
+            tags::SESSION_PROPOSE_REQUEST,
+        )
+        .await?;
+
+        self.pairing = Some(uri.clone());
+        Ok(uri)
+    }
+
+    /// 等待钱包批准并确立会话
+    pub async fn wait_for_session(&self, timeout: Duration) -> Result<Session, WalletConnectError> {
+        let pairing = self
+            .pairing
+            .as_ref()
+            .ok_or_else(|| WalletConnectError::Protocol("尚未创建配对".to_string()))?;
+
+        let response = receive_message(
+            self.transport.as_ref(),
+            &pairing.topic,
+            &pairing.sym_key,
+            timeout,
+        )
+        .await?;
+        if let Some(error) = response.error {
+            return Err(WalletConnectError::Rejected(error.message));
+        }
+        let responder_key = response
+            .result
+            .as_ref()
+            .and_then(|r| r["responderPublicKey"].as_str())
+            .ok_or_else(|| WalletConnectError::Protocol("缺少 responderPublicKey".to_string()))?;
+
+        let sym_key = self.key_pair.derive_sym_key(responder_key)?;
+        let topic = crypto::topic_from_sym_key(&sym_key);
+        self.transport.subscribe(&topic).await?;
+
+        let settle = receive_message(self.transport.as_ref(), &topic, &sym_key, timeout).await?;
+        if settle.method.as_deref() != Some("wc_sessionSettle") {
+            return Err(WalletConnectError::Protocol(
+                "期望 wc_sessionSettle".to_string(),
+            ));
+        }
+        let params = settle.params.unwrap_or_default();
+        let session = Session {
+            topic: topic.clone(),
+            sym_key,
+            peer_metadata: serde_json::from_value(params["controller"]["metadata"].clone())
+                .unwrap_or_default(),
+            namespaces: serde_json::from_value(params["namespaces"].clone())
+                .map_err(|e| WalletConnectError::Protocol(e.to_string()))?,
+            expiry: params["expiry"].as_u64().unwrap_or(0),
+        };
+
+        send_message(
+            self.transport.as_ref(),
+            &topic,
+            &sym_key,
+            &RpcMessage::response(settle.id, json!(true)),
+            tags::SESSION_SETTLE_RESPONSE,
+        )
+        .await?;
+        Ok(session)
+    }
+
+    /// 通过会话向钱包发送请求并等待结果
+    pub async fn request(
+        &self,
+        session: &Session,
+        chain_id: u64,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, WalletConnectError> {
+        if session.is_expired() {
+            return Err(WalletConnectError::Protocol("会话已过期".to_string()));
+        }
+        let request = RpcMessage::request(
+            "wc_sessionRequest",
+            json!({
+                "request": { "method": method, "params": params },
+                "chainId": format!("eip155:{}", chain_id),
+            }),
+        );
+        send_message(
+            self.transport.as_ref(),
+            &session.topic,
+            &session.sym_key,
+            &request,
+            tags::SESSION_REQUEST,
+        )
+        .await?;
+
+        loop {
+            let message = receive_message(
+                self.transport.as_ref(),
+                &session.topic,
+                &session.sym_key,
+                timeout,
+            )
+            .await?;
+            if message.is_request() || message.id != request.id {
+                continue;
+            }
+            if let Some(error) = message.error {
+                return Err(WalletConnectError::Rejected(error.message));
+            }
+            return Ok(message.result.unwrap_or(Value::Null));
+        }
+    }
+
+    /// 请求个人消息签名
+    pub async fn personal_sign(
+        &self,
+        session: &Session,
+        chain_id: u64,
+        message: &[u8],
+        timeout: Duration,
+    ) -> Result<String, WalletConnectError> {
+        let account = session
+            .accounts()
+            .first()
+            .copied()
+            .ok_or_else(|| WalletConnectError::Protocol("会话中没有账户".to_string()))?;
+        let result = self
+            .request(
+                session,
+                chain_id,
+                "personal_sign",
+                json!([
+                    format!("0x{}", hex::encode(message)),
+                    format!("{:?}", account)
+                ]),
+                timeout,
+            )
+            .await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| WalletConnectError::Protocol("签名结果格式错误".to_string()))
+    }
+}
+
+/// 收到的会话提案
+#[derive(Debug, Clone)]
+pub struct SessionProposal {
+    /// 提案消息 ID
+    pub id: u64,
+    /// 配对
+    pub pairing: PairingUri,
+    /// 提案方公钥
+    pub proposer_public_key: String,
+    /// 提案方元数据
+    pub proposer_metadata: Metadata,
+    /// 要求的命名空间
+    pub required_namespaces: HashMap<String, Namespace>,
+}
+
+/// 收到的会话请求，签名前须经用户确认
+#[derive(Debug, Clone)]
+pub struct SessionRequest {
+    /// 请求消息 ID
+    pub id: u64,
+    /// 请求的链，如 `eip155:1`
+    pub chain: String,
+    /// 请求的方法
+    pub method: String,
+    /// 方法参数
+    pub params: Value,
+    /// 发起请求的 dapp 元数据
+    pub peer_metadata: Metadata,
+}
+
+/// 签名请求确认，通常向用户展示请求内容并等待用户批准或拒绝
+#[async_trait]
+pub trait RequestApprover: Send + Sync {
+    /// 是否批准请求
+    async fn approve(&self, request: &SessionRequest) -> bool;
+}
+
+/// 钱包侧 WalletConnect 签名者
+pub struct WalletConnectSigner {
+    /// 中继传输
+    transport: Arc<dyn RelayTransport>,
+    /// 签名钱包
+    wallet: FairWallet,
+    /// 钱包元数据
+    metadata: Metadata,
+    /// 链 ID
+    chain_id: u64,
+    /// 签名请求确认，未设置时拒绝全部请求
+    approver: Option<Arc<dyn RequestApprover>>,
+}
+
+impl WalletConnectSigner {
+    /// 创建签名者
+    pub fn new(
+        transport: Arc<dyn RelayTransport>,
+        wallet: FairWallet,
+        metadata: Metadata,
+        chain_id: u64,
+    ) -> Self {
+        Self {
+            transport,
+            wallet,
+            metadata,
+            chain_id,
+            approver: None,
+        }
+    }
+
+    /// 使用 dapp 提供的 URI 配对并读取会话提案
+    pub async fn pair(
+        &self,
+        uri: &str,
+        timeout: Duration,
+    ) -> Result<SessionProposal, WalletConnectError> {
+        let pairing = PairingUri::parse(uri)?;
+        if pairing.expiry.is_some_and(|expiry| expiry < now_secs()) {
+            return Err(WalletConnectError::InvalidUri("配对已过期".to_string()));
+        }
+        self.transport.subscribe(&pairing.topic).await?;
+
+        let message = receive_message(
+            self.transport.as_ref(),
+            &pairing.topic,
+            &pairing.sym_key,
+            timeout,
+        )
+        .await?;
+        if message.method.as_deref() != Some("wc_sessionPropose") {
+            return Err(WalletConnectError::Protocol(
+                "期望 wc_sessionPropose".to_string(),
+            ));
+        }
+        let params = message.params.unwrap_or_default();
+        Ok(SessionProposal {
+            id: message.id,
+            pairing,
+            proposer_public_key: params["proposer"]["publicKey"]
+                .as_str()
+                .ok_or_else(|| WalletConnectError::Protocol("缺少提案方公钥".to_string()))?
+                .to_string(),
+            proposer_metadata: serde_json::from_value(params["proposer"]["metadata"].clone())
+                .unwrap_or_default(),
+            required_namespaces: serde_json::from_value(params["requiredNamespaces"].clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// 批准会话提案
+    pub async fn approve(&self, proposal: &SessionProposal) -> Result<Session, WalletConnectError> {
+        let chain = format!("eip155:{}", self.chain_id);
+        if let Some(required) = proposal.required_namespaces.get("eip155") {
+            if !required.chains.is_empty() && !required.chains.contains(&chain) {
+                return Err(WalletConnectError::Protocol(format!(
+                    "不支持请求的链: {:?}",
+                    required.chains
+                )));
+            }
+        }
+
+        let key_pair = KeyPair::generate();
+        let sym_key = key_pair.derive_sym_key(&proposal.proposer_public_key)?;
+        let topic = crypto::topic_from_sym_key(&sym_key);
+        self.transport.subscribe(&topic).await?;
+
+        send_message(
+            self.transport.as_ref(),
+            &proposal.pairing.topic,
+            &proposal.pairing.sym_key,
+            &RpcMessage::response(
+                proposal.id,
+                json!({
+                    "relay": { "protocol": RELAY_PROTOCOL },
+                    "responderPublicKey": key_pair.public_key_hex(),
+                }),
+            ),
+            tags::SESSION_PROPOSE_RESPONSE,
+        )
+        .await?;
+
+        let address = self
+            .wallet
+            .address()
+            .await
+            .map_err(|e| WalletConnectError::Signing(e.to_string()))?;
+        let namespaces = HashMap::from([(
+            "eip155".to_string(),
+            Namespace::eip155(self.chain_id, &[address]),
+        )]);
+        let expiry = now_secs() + SESSION_EXPIRY;
+        let settle = RpcMessage::request(
+            "wc_sessionSettle",
+            json!({
+                "relay": { "protocol": RELAY_PROTOCOL },
+                "namespaces": namespaces,
+                "controller": {
+                    "publicKey": key_pair.public_key_hex(),
+                    "metadata": self.metadata,
+                },
+                "expiry": expiry,
+            }),
+        );
+        send_message(
+            self.transport.as_ref(),
+            &topic,
+            &sym_key,
+            &settle,
+            tags::SESSION_SETTLE_REQUEST,
+        )
+        .await?;
+
+        Ok(Session {
+            topic,
+            sym_key,
+            peer_metadata: proposal.proposer_metadata.clone(),
+            namespaces,
+            expiry,
+        })
+    }
+
+    /// 拒绝会话提案
+    pub async fn reject(
+        &self,
+        proposal: &SessionProposal,
+        reason: &str,
+    ) -> Result<(), WalletConnectError> {
+        send_message(
+            self.transport.as_ref(),
+            &proposal.pairing.topic,
+            &proposal.pairing.sym_key,
+            &RpcMessage::error(proposal.id, 5000, reason),
+            tags::SESSION_PROPOSE_RESPONSE,
+        )
+        .await
+    }
+
+    /// 设置签名请求确认，未设置时 [`WalletConnectSigner::handle_next_request`] 拒绝全部请求
+    pub fn with_approver(mut self, approver: Arc<dyn RequestApprover>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    /// 接收下一条会话请求并做签名前检查
+    ///
+    /// 会话已过期、请求的链不是签名者的链、请求的账户不是钱包地址或方法不受支持时，直接向 dapp
+    /// 返回错误并返回 [`WalletConnectError::Protocol`]。通过检查的请求须由调用方向用户展示并确认，
+    /// 再调用 [`WalletConnectSigner::respond`] 签名或拒绝。
+    pub async fn next_request(
+        &self,
+        session: &Session,
+        timeout: Duration,
+    ) -> Result<SessionRequest, WalletConnectError> {
+        if session.is_expired() {
+            return Err(WalletConnectError::Protocol("会话已过期".to_string()));
+        }
+        loop {
+            let message = receive_message(
+                self.transport.as_ref(),
+                &session.topic,
+                &session.sym_key,
+                timeout,
+            )
+            .await?;
+            // 跳过会话确立确认等响应消息
+            if !message.is_request() {
+                continue;
+            }
+            if message.method.as_deref() != Some("wc_sessionRequest") {
+                continue;
+            }
+
+            let params = message.params.clone().unwrap_or_default();
+            let request = SessionRequest {
+                id: message.id,
+                chain: params["chainId"].as_str().unwrap_or_default().to_string(),
+                method: params["request"]["method"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                params: params["request"]["params"].clone(),
+                peer_metadata: session.peer_metadata.clone(),
+            };
+            if let Err((code, reason)) = self.check_request(session, &request).await {
+                send_message(
+                    self.transport.as_ref(),
+                    &session.topic,
+                    &session.sym_key,
+                    &RpcMessage::error(request.id, code, &reason),
+                    tags::SESSION_REQUEST_RESPONSE,
+                )
+                .await?;
+                return Err(WalletConnectError::Protocol(reason));
+            }
+            return Ok(request);
+        }
+    }
+
+    /// 响应经 [`WalletConnectSigner::next_request`] 取得的请求，`approved` 为假时向 dapp 返回用户拒绝
+    pub async fn respond(
+        &self,
+        session: &Session,
+        request: &SessionRequest,
+        approved: bool,
+    ) -> Result<(), WalletConnectError> {
+        let response = if !approved {
+            RpcMessage::error(request.id, 4001, "用户拒绝请求")
+        } else if session.is_expired() {
+            RpcMessage::error(request.id, 4100, "会话已过期")
+        } else {
+            match self.dispatch(&request.method, &request.params).await {
+                Ok(result) => RpcMessage::response(request.id, result),
+                Err(WalletConnectError::Protocol(e)) => RpcMessage::error(request.id, 4200, &e),
+                Err(e) => RpcMessage::error(request.id, 4001, &e.to_string()),
+            }
+        };
+        send_message(
+            self.transport.as_ref(),
+            &session.topic,
+            &session.sym_key,
+            &response,
+            tags::SESSION_REQUEST_RESPONSE,
+        )
+        .await
+    }
+
+    /// 接收下一条会话请求，经设置的 [`RequestApprover`] 确认后签名，返回处理的方法名
+    ///
+    /// 未设置确认或确认被拒绝时向 dapp 返回用户拒绝，并返回 [`WalletConnectError::Rejected`]。
+    pub async fn handle_next_request(
+        &self,
+        session: &Session,
+        timeout: Duration,
+    ) -> Result<String, WalletConnectError> {
+        let request = self.next_request(session, timeout).await?;
+        let approved = match &self.approver {
+            Some(approver) => approver.approve(&request).await,
+            None => false,
+        };
+        self.respond(session, &request, approved).await?;
+        if !approved {
+            return Err(WalletConnectError::Rejected(format!(
+                "用户拒绝请求 {}",
+                request.method
+            )));
+        }
+        Ok(request.method)
+    }
+
+    /// 检查会话请求，不通过时返回错误码和原因
+    async fn check_request(
+        &self,
+        session: &Session,
+        request: &SessionRequest,
+    ) -> Result<(), (i64, String)> {
+        if session.is_expired() {
+            return Err((4100, "会话已过期".to_string()));
+        }
+        let chain = format!("eip155:{}", self.chain_id);
+        if request.chain != chain {
+            return Err((4901, format!("不支持请求的链: {}", request.chain)));
+        }
+        let account = match request.method.as_str() {
+            "personal_sign" => &request.params[1],
+            "eth_signTypedData_v4" => &request.params[0],
+            "eth_signTransaction" => {
+                let tx_chain = &request.params[0]["chainId"];
+                if !tx_chain.is_null() {
+                    let tx_chain: Option<ethers::types::U64> =
+                        serde_json::from_value(tx_chain.clone()).ok();
+                    if tx_chain != Some(self.chain_id.into()) {
+                        return Err((4901, format!("交易的链 ID 不是 {}", self.chain_id)));
+                    }
+                }
+                &request.params[0]["from"]
+            }
+            method => return Err((4200, format!("不支持的方法: {}", method))),
+        };
+        let address = self
+            .wallet
+            .address()
+            .await
+            .map_err(|e| (4100, e.to_string()))?;
+        let requested: Option<ethers::types::Address> =
+            account.as_str().and_then(|a| a.parse().ok());
+        if requested != Some(address) || !session.accounts().contains(&address) {
+            return Err((4100, format!("请求的账户不是会话账户: {}", account)));
+        }
+        Ok(())
+    }
+
+    /// 根据方法名执行签名
+    async fn dispatch(&self, method: &str, params: &Value) -> Result<Value, WalletConnectError> {
+        let signing = |e: crate::wallet::WalletError| WalletConnectError::Signing(e.to_string());
+        match method {
+            "personal_sign" => {
+                let message = params[0]
+                    .as_str()
+                    .ok_or_else(|| WalletConnectError::Signing("缺少消息参数".to_string()))?;
+                let bytes = match message.strip_prefix("0x") {
+                    Some(hex_message) => hex::decode(hex_message)
+                        .map_err(|e| WalletConnectError::Signing(e.to_string()))?,
+                    None => message.as_bytes().to_vec(),
+                };
+                let signature = self.wallet.sign_message(&bytes).await.map_err(signing)?;
+                Ok(json!(format!("0x{}", signature)))
+            }
+            "eth_signTypedData_v4" => {
+                let typed_data: TypedData = match &params[1] {
+                    Value::String(raw) => serde_json::from_str(raw),
+                    other => serde_json::from_value(other.clone()),
+                }
+                .map_err(|e| WalletConnectError::Signing(e.to_string()))?;
+                let signature = self
+                    .wallet
+                    .sign_typed_data(&typed_data)
+                    .await
+                    .map_err(signing)?;
+                Ok(json!(format!("0x{}", signature)))
+            }
+            "eth_signTransaction" => {
+                let tx: TransactionRequest = serde_json::from_value(params[0].clone())
+                    .map_err(|e| WalletConnectError::Signing(e.to_string()))?;
+                let signed = self.wallet.sign_transaction(tx).await.map_err(signing)?;
+                Ok(json!(format!("0x{}", hex::encode(signed.rlp()))))
+            }
+            _ => Err(WalletConnectError::Protocol(format!(
+                "不支持的方法: {}",
+                method
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ApproveAll;
+
+    #[async_trait]
+    impl RequestApprover for ApproveAll {
+        async fn approve(&self, _request: &SessionRequest) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_pairing_uri_roundtrip() {
+        let sym_key = crypto::generate_sym_key();
+        let uri = PairingUri {
+            topic: crypto::topic_from_sym_key(&sym_key),
+            sym_key,
+            relay_protocol: RELAY_PROTOCOL.to_string(),
+            expiry: Some(1_700_000_000),
+        };
+        assert_eq!(PairingUri::parse(&uri.to_string()).unwrap(), uri);
+        assert!(PairingUri::parse("wc:abc@1?symKey=00").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_and_personal_sign() {
+        let relay = MemoryRelay::new();
+        let wallet_relay = relay.connect().await;
+        let timeout = Duration::from_secs(5);
+        let chain_id = 2023;
+
+        let wallet = FairWallet::from_private_key(
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+            chain_id,
+        )
+        .unwrap();
+        let address = wallet.address().await.unwrap();
+        let signer = WalletConnectSigner::new(
+            Arc::new(wallet_relay),
+            wallet,
+            Metadata::default(),
+            chain_id,
+        )
+        .with_approver(Arc::new(ApproveAll));
+
+        let mut dapp = WalletConnectDapp::new(Arc::new(relay), Metadata::default());
+        let required = HashMap::from([("eip155".to_string(), Namespace::eip155(chain_id, &[]))]);
+        let uri = dapp.connect(required).await.unwrap();
+
+        let proposal = signer.pair(&uri.to_string(), timeout).await.unwrap();
+        let wallet_session = signer.approve(&proposal).await.unwrap();
+        let dapp_session = dapp.wait_for_session(timeout).await.unwrap();
+        assert_eq!(dapp_session.topic, wallet_session.topic);
+        assert_eq!(dapp_session.accounts(), vec![address]);
+
+        let handle = tokio::spawn(async move {
+            let approved = signer
+                .handle_next_request(&wallet_session, timeout)
+                .await
+                .unwrap();
+            // 请求其他链时不经确认直接返回错误
+            let wrong_chain = signer.next_request(&wallet_session, timeout).await;
+            // 调用方自行确认时可以拒绝
+            let request = signer.next_request(&wallet_session, timeout).await.unwrap();
+            signer
+                .respond(&wallet_session, &request, false)
+                .await
+                .unwrap();
+            (approved, wrong_chain.is_err(), request.method)
+        });
+        let signature = dapp
+            .personal_sign(&dapp_session, chain_id, b"hello", timeout)
+            .await
+            .unwrap();
+        assert!(matches!(
+            dapp.personal_sign(&dapp_session, chain_id + 1, b"hello", timeout)
+                .await,
+            Err(WalletConnectError::Rejected(_))
+        ));
+        assert!(matches!(
+            dapp.personal_sign(&dapp_session, chain_id, b"again", timeout)
+                .await,
+            Err(WalletConnectError::Rejected(_))
+        ));
+        assert_eq!(
+            handle.await.unwrap(),
+            (
+                "personal_sign".to_string(),
+                true,
+                "personal_sign".to_string()
+            )
+        );
+
+        let signature: ethers::types::Signature = signature.parse().unwrap();
+        assert!(signature.verify("hello", address).is_ok());
+    }
+}
@@ -1,11 +1,16 @@
 use ethers::types::{H160, H256, U256};
-use rand::Rng;
+use fair_vm_core::rng::{self, RngCore};
+use fair_vm_core::serde_hex::{self, HexError};
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
 use std::fmt;
+use std::str::FromStr;
 
-/// 账户地址类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
-pub struct Address(pub [u8; 20]);
+/// 账户地址类型，JSON 中编码为 `0x` 前缀的十六进制字符串
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct Address(#[serde(with = "serde_hex::array")] pub [u8; 20]);
 
 impl Address {
     /// 创建新的地址
@@ -25,9 +30,26 @@ impl Address {
 
     /// 生成随机地址
     pub fn random() -> Self {
-        let mut rng = rand::rng();
+        rng::with_rng(|rng| Self::random_with(rng))
+    }
+
+    /// 用指定的随机数源生成随机地址
+    pub fn random_with<R: RngCore + ?Sized>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 20];
+        rng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// EIP-55 校验和十六进制
+    pub fn to_checksum(&self) -> String {
+        serde_hex::to_checksum(&self.0)
+    }
+
+    /// 由 secp256k1 公钥推导地址
+    pub fn from_public_key(public_key: &secp256k1::PublicKey) -> Self {
+        let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
         let mut bytes = [0u8; 20];
-        rng.fill(&mut bytes);
+        bytes.copy_from_slice(&hash[12..]);
         Self(bytes)
     }
 }
@@ -50,6 +72,15 @@ impl fmt::Display for Address {
     }
 }
 
+impl FromStr for Address {
+    type Err = HexError;
+
+    /// 解析十六进制地址，大小写混合时校验 EIP-55 校验和
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_hex::decode_address(s).map(Self)
+    }
+}
+
 /// 账户类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Account {
@@ -107,6 +138,18 @@ mod tests {
         assert_eq!(h160.0, addr.0);
     }
 
+    #[test]
+    fn test_address_random_seeded() {
+        let seeded = || {
+            let _guard = rng::seed(42);
+            (Address::random(), Address::random())
+        };
+        let (first, second) = seeded();
+        assert_eq!(seeded(), (first, second));
+        assert_ne!(first, second);
+        assert_eq!(first, Address::random_with(&mut rng::seeded(42)));
+    }
+
     #[test]
     fn test_address_display() {
         let addr = Address([1; 20]);
@@ -115,6 +158,24 @@ mod tests {
         assert_eq!(s.len(), 42);
     }
 
+    #[test]
+    fn test_address_hex() {
+        let addr = Address::random();
+        assert_eq!(addr.to_string().parse::<Address>().unwrap(), addr);
+        assert!("0x1234".parse::<Address>().is_err());
+        assert!("0xzz".parse::<Address>().is_err());
+        let checksummed = addr.to_checksum();
+        assert_eq!(checksummed.parse::<Address>().unwrap(), addr);
+        assert_eq!(checksummed.to_lowercase().parse::<Address>().unwrap(), addr);
+
+        let json = serde_json::to_string(&addr).unwrap();
+        assert_eq!(json, format!("\"{}\"", addr));
+        assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), addr);
+        // 旧版本写出的字节数组仍可读取
+        let legacy = serde_json::to_string(&addr.0.to_vec()).unwrap();
+        assert_eq!(serde_json::from_str::<Address>(&legacy).unwrap(), addr);
+    }
+
     #[test]
     fn test_account_new() {
         let addr = Address::random();
@@ -0,0 +1,282 @@
+//! 账户转账历史索引
+//!
+//! 区块执行时为每笔交易的发送方和接收方（创建合约时为新合约）各记录一条转账，连同区块时间、
+//! 手续费和交易执行后该账户的余额，供对账报表按账户和区块范围查询。余额在交易执行后立即读取，
+//! 相邻两条记录之间的余额差还可能来自区块奖励、合约内部转账等不经本账户交易的变动。
+//!
+//! 手续费为收据的 gas 用量 × 实际 gas 价格，是交易应付的金额；当前交易执行不从余额中扣除
+//! gas 费用，因此记录的余额不含手续费的扣减，对账时不能用手续费核对余额差。
+//!
+//! 使用数据目录时，每个区块的转账在区块状态提交前追加到 `account_history.jsonl`，启动时加载
+//! 不晚于最后接受区块的记录；临时模式下只保存在内存中。
+
+use crate::receipt::Receipt;
+use crate::transaction::Transaction;
+use ethers::types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+/// 单次查询允许的最大区块范围
+pub const MAX_HISTORY_RANGE: u64 = 10_000;
+
+/// 数据目录中的转账历史日志文件名
+pub const HISTORY_FILE_NAME: &str = "account_history.jsonl";
+
+/// 账户的一条转账记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountTransfer {
+    pub transaction_hash: H256,
+    pub block_number: u64,
+    /// 区块时间戳（秒）
+    pub timestamp: u64,
+    pub from: H160,
+    /// 接收方，创建合约时为新合约地址
+    pub to: Option<H160>,
+    /// 交易金额，执行失败时未转移
+    pub value: U256,
+    /// 交易应付的手续费，当前未从发送方余额中扣除
+    pub fee: U256,
+    pub status: bool,
+    /// 交易执行后该账户的余额
+    pub balance: U256,
+}
+
+impl AccountTransfer {
+    /// 由交易及其收据生成，余额由调用方按账户填写
+    pub fn new(tx: &Transaction, receipt: &Receipt, timestamp: u64) -> Self {
+        Self {
+            transaction_hash: tx.hash,
+            block_number: receipt.block_number,
+            timestamp,
+            from: H160(tx.from.0),
+            to: tx
+                .to
+                .or(receipt.contract_address)
+                .map(|address| H160(address.0)),
+            value: tx.value,
+            fee: U256::from(receipt.gas_used) * receipt.effective_gas_price,
+            status: receipt.status,
+            balance: U256::zero(),
+        }
+    }
+
+    /// 涉及的账户，发送方在前，转给自己时只出现一次
+    pub fn parties(&self) -> Vec<H160> {
+        let mut parties = vec![self.from];
+        if let Some(to) = self.to.filter(|to| *to != self.from) {
+            parties.push(to);
+        }
+        parties
+    }
+}
+
+/// 日志中一个区块的转账，每行一个区块
+#[derive(Debug, Serialize, Deserialize)]
+struct BlockTransfers {
+    block_number: u64,
+    transfers: Vec<(H160, AccountTransfer)>,
+}
+
+/// 按账户索引的转账历史
+#[derive(Debug, Clone, Default)]
+pub struct AccountHistory {
+    accounts: HashMap<H160, Vec<AccountTransfer>>,
+    /// 日志文件，临时模式下为空
+    journal: Option<PathBuf>,
+}
+
+impl AccountHistory {
+    /// 加载数据目录中的转账历史日志
+    ///
+    /// 只保留不晚于 `last_accepted` 的区块，之后的记录（状态未提交）和写了一半的行从文件中删除；
+    /// 区块重新导入时会再次写入，同一区块有多条记录时以最后一条为准。
+    pub async fn open(data_dir: &Path, last_accepted: Option<u64>) -> io::Result<Self> {
+        let path = data_dir.join(HISTORY_FILE_NAME);
+        let content = match fs::read(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let mut entries = Vec::new();
+        for line in content.split_inclusive(|byte| *byte == b'\n') {
+            let Some(entry) = line
+                .strip_suffix(b"\n")
+                .and_then(|line| serde_json::from_slice::<BlockTransfers>(line).ok())
+            else {
+                break;
+            };
+            match last_accepted {
+                Some(last) if entry.block_number <= last => entries.push(entry),
+                _ => break,
+            }
+        }
+        let mut seen = HashSet::new();
+        let mut kept: Vec<_> = entries
+            .into_iter()
+            .rev()
+            .filter(|entry| seen.insert(entry.block_number))
+            .collect();
+        kept.reverse();
+
+        let mut rewritten = Vec::new();
+        for entry in &kept {
+            rewritten.extend(journal_line(entry)?);
+        }
+        if rewritten != content {
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, &rewritten).await?;
+            fs::rename(&tmp, &path).await?;
+        }
+
+        let mut history = Self {
+            accounts: HashMap::new(),
+            journal: Some(path),
+        };
+        for entry in kept {
+            history.record_block(entry.transfers);
+        }
+        Ok(history)
+    }
+
+    /// 把区块的转账追加到日志并落盘，须在区块状态提交前调用；临时模式下不写入
+    pub async fn append_journal(
+        &self,
+        block_number: u64,
+        transfers: &[(H160, AccountTransfer)],
+    ) -> io::Result<()> {
+        let Some(path) = &self.journal else {
+            return Ok(());
+        };
+        if transfers.is_empty() {
+            return Ok(());
+        }
+        let line = journal_line(&BlockTransfers {
+            block_number,
+            transfers: transfers.to_vec(),
+        })?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(&line).await?;
+        file.sync_data().await
+    }
+
+    /// 记录区块的转账，同一账户的记录按执行顺序加入
+    pub fn record_block(&mut self, transfers: impl IntoIterator<Item = (H160, AccountTransfer)>) {
+        for (account, transfer) in transfers {
+            self.record(account, transfer);
+        }
+    }
+
+    /// 记录账户的一条转账，同一账户的记录按执行顺序加入
+    pub fn record(&mut self, account: H160, transfer: AccountTransfer) {
+        self.accounts.entry(account).or_default().push(transfer);
+    }
+
+    /// `[from, to]` 范围内账户的转账，按执行顺序
+    pub fn range(&self, account: &H160, from: u64, to: u64) -> Vec<AccountTransfer> {
+        let Some(transfers) = self.accounts.get(account) else {
+            return Vec::new();
+        };
+        let start = transfers.partition_point(|transfer| transfer.block_number < from);
+        transfers[start..]
+            .iter()
+            .take_while(|transfer| transfer.block_number <= to)
+            .cloned()
+            .collect()
+    }
+}
+
+fn journal_line(entry: &BlockTransfers) -> io::Result<Vec<u8>> {
+    let mut line =
+        serde_json::to_vec(entry).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(block_number: u64, from: u64, to: Option<u64>) -> AccountTransfer {
+        AccountTransfer {
+            transaction_hash: H256::from_low_u64_be(block_number),
+            block_number,
+            timestamp: block_number * 2,
+            from: H160::from_low_u64_be(from),
+            to: to.map(H160::from_low_u64_be),
+            value: U256::from(10),
+            fee: U256::from(1),
+            status: true,
+            balance: U256::zero(),
+        }
+    }
+
+    #[test]
+    fn test_parties() {
+        assert_eq!(
+            transfer(1, 1, Some(2)).parties(),
+            vec![H160::from_low_u64_be(1), H160::from_low_u64_be(2)]
+        );
+        assert_eq!(
+            transfer(1, 1, Some(1)).parties(),
+            vec![H160::from_low_u64_be(1)]
+        );
+        assert_eq!(
+            transfer(1, 1, None).parties(),
+            vec![H160::from_low_u64_be(1)]
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let mut history = AccountHistory::default();
+        let account = H160::from_low_u64_be(1);
+        for number in [1, 3, 3, 5, 8] {
+            history.record(account, transfer(number, 1, Some(2)));
+        }
+        let blocks = |from, to| -> Vec<u64> {
+            history
+                .range(&account, from, to)
+                .iter()
+                .map(|transfer| transfer.block_number)
+                .collect()
+        };
+        assert_eq!(blocks(3, 5), vec![3, 3, 5]);
+        assert_eq!(blocks(0, 100), vec![1, 3, 3, 5, 8]);
+        assert!(blocks(6, 7).is_empty());
+        assert!(history.range(&H160::from_low_u64_be(2), 0, 100).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_journal_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let account = H160::from_low_u64_be(1);
+        let history = AccountHistory::open(dir.path(), None).await.unwrap();
+        for number in [1, 2, 2, 3] {
+            history
+                .append_journal(number, &[(account, transfer(number, 1, Some(2)))])
+                .await
+                .unwrap();
+        }
+        let path = dir.path().join(HISTORY_FILE_NAME);
+        let mut file = OpenOptions::new().append(true).open(&path).await.unwrap();
+        file.write_all(b"{\"block_number\":4").await.unwrap();
+
+        // 区块 3 的状态未提交，重复写入的区块 2 只保留一条
+        let history = AccountHistory::open(dir.path(), Some(2)).await.unwrap();
+        assert_eq!(history.range(&account, 0, 100).len(), 2);
+        let reloaded = AccountHistory::open(dir.path(), Some(3)).await.unwrap();
+        assert_eq!(
+            reloaded.range(&account, 0, 100),
+            history.range(&account, 0, 100)
+        );
+    }
+}
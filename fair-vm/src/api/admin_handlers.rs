@@ -0,0 +1,135 @@
+use crate::api::VmExt;
+use crate::clock::ClockStatus;
+use crate::consensus::ValidatorChange;
+use crate::crash::DebugBundle;
+use crate::network::PeerInfo;
+use jsonrpc_core::{Error, Result};
+use jsonrpc_derive::rpc;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[rpc]
+pub trait AdminApi {
+    /// 收集调试信息包：最近的日志、脱敏后的节点配置和链头
+    #[rpc(name = "admin_collectDebugBundle")]
+    fn collect_debug_bundle(&self) -> Result<DebugBundle>;
+
+    /// 当前连接的对等节点：地址、连接方向、协议版本和流量统计
+    #[rpc(name = "admin_peers")]
+    fn peers(&self) -> Result<Vec<PeerInfo>>;
+
+    /// 本地时钟相对对等节点和区块时间戳的偏差
+    #[rpc(name = "admin_clockStatus")]
+    fn clock_status(&self) -> Result<ClockStatus>;
+
+    /// 变更验证人集合，返回变更生效的区块高度
+    #[rpc(name = "admin_updateValidators")]
+    fn update_validators(&self, change: ValidatorChange) -> Result<u64>;
+}
+
+/// 节点管理接口处理器，默认只在 IPC 上开放
+pub struct AdminHandlers {
+    vm: Arc<RwLock<dyn VmExt>>,
+}
+
+impl AdminHandlers {
+    pub fn new(vm: Arc<RwLock<dyn VmExt>>) -> Self {
+        Self { vm }
+    }
+}
+
+impl AdminApi for AdminHandlers {
+    fn collect_debug_bundle(&self) -> Result<DebugBundle> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm.collect_debug_bundle().await)
+        })
+    }
+
+    fn peers(&self) -> Result<Vec<PeerInfo>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm.get_peers().await)
+        })
+    }
+
+    fn clock_status(&self) -> Result<ClockStatus> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm.get_clock_status().await)
+        })
+    }
+
+    fn update_validators(&self, change: ValidatorChange) -> Result<u64> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            vm.update_validators(change)
+                .await
+                .map_err(|e| Error::invalid_params(e.to_string()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SkewLevel;
+    use crate::network::PeerDirection;
+    use crate::FairVM;
+
+    #[test]
+    fn test_collect_debug_bundle() {
+        let vm: Arc<RwLock<dyn VmExt>> = Arc::new(RwLock::new(FairVM::new()));
+        let bundle = AdminHandlers::new(vm).collect_debug_bundle().unwrap();
+        assert!(bundle.panic.is_none());
+        assert!(bundle.chain_tip.is_none());
+        assert_eq!(bundle.config["data_dir"], "data");
+    }
+
+    #[test]
+    fn test_peers() {
+        let fair_vm = FairVM::new();
+        let peers = fair_vm.peers();
+        let vm: Arc<RwLock<dyn VmExt>> = Arc::new(RwLock::new(fair_vm));
+        let handlers = AdminHandlers::new(vm);
+        assert!(handlers.peers().unwrap().is_empty());
+
+        peers.blocking_write().connected(
+            "node-a",
+            "10.0.0.1:9651",
+            PeerDirection::Outbound,
+            1_700_000_000,
+        );
+        peers.blocking_write().record_received("node-a", 42);
+        let result = handlers.peers().unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].address, "10.0.0.1:9651");
+        assert_eq!(result[0].traffic.bytes_received, 42);
+    }
+
+    #[test]
+    fn test_clock_status() {
+        let fair_vm = FairVM::new();
+        let clock = fair_vm.clock();
+        let vm: Arc<RwLock<dyn VmExt>> = Arc::new(RwLock::new(fair_vm));
+        let handlers = AdminHandlers::new(vm);
+        let status = handlers.clock_status().unwrap();
+        assert_eq!(status.level, SkewLevel::Ok);
+        assert!(status.peer_skew.is_none());
+
+        clock
+            .blocking_write()
+            .record_peer("node-a", status.local_time + 600, status.local_time);
+        let status = handlers.clock_status().unwrap();
+        assert_eq!(status.peer_samples, 1);
+        assert_eq!(status.level, SkewLevel::Critical);
+    }
+}
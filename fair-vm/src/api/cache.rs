@@ -0,0 +1,295 @@
+//! 不可变查询的响应缓存
+//!
+//! 已接受的区块不会再改变，浏览器反复轮询的历史区块、交易和收据查询可以直接返回缓存的响应。
+//! 只缓存 [`CACHEABLE_METHODS`] 中的方法，结果为空（区块或交易尚不存在）或交易尚未打包
+//! （`block_hash` 为空）时不缓存。缓存按条目数和总字节数淘汰最久未使用的响应，超过有效期的
+//! 响应在下次访问时丢弃；链头高度回退或同一高度的区块哈希变化时（例如替换本地链后重新启动
+//! 同步）清空全部缓存。
+
+use crate::api::VmExt;
+use ethers::types::H256;
+use fair_vm_core::config::RpcCacheConfig;
+use jsonrpc_core::{BoxFuture, Params, Result, RpcMethod, Value};
+use lru::LruCache;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// 结果只取决于已接受区块的方法
+pub const CACHEABLE_METHODS: &[&str] = &[
+    "chain_getBlockByNumber",
+    "chain_getBlockByHash",
+    "chain_getBlockTransactionCountByNumber",
+    "chain_getBlockTransactionCountByHash",
+    "chain_getTransactionByBlockNumberAndIndex",
+    "chain_getTransactionByBlockHashAndIndex",
+    "chain_getTransactionByHash",
+    "wallet_getTransactionReceipt",
+    "eth_getTransactionReceipt",
+    "eth_getBlockReceipts",
+    "fairvm_getInclusionProof",
+];
+
+/// 缓存统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Value,
+    bytes: usize,
+    inserted: Instant,
+}
+
+/// 响应是否不会再变化
+pub fn is_immutable_result(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Object(fields) => !matches!(fields.get("block_hash"), Some(Value::Null)),
+        _ => true,
+    }
+}
+
+/// RPC 响应缓存
+#[derive(Debug)]
+pub struct RpcCache {
+    config: RpcCacheConfig,
+    entries: LruCache<String, Entry>,
+    bytes: usize,
+    /// 最近观察到的链头高度和哈希
+    head: Option<(u64, H256)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl RpcCache {
+    pub fn new(config: RpcCacheConfig) -> Self {
+        Self {
+            config,
+            entries: LruCache::unbounded(),
+            bytes: 0,
+            head: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// 方法的结果是否可以缓存
+    pub fn is_cacheable(method: &str) -> bool {
+        CACHEABLE_METHODS.contains(&method)
+    }
+
+    /// 缓存键
+    pub fn key(method: &str, params: &Params) -> String {
+        let params = serde_json::to_string(params).unwrap_or_default();
+        format!("{}:{}", method, params)
+    }
+
+    /// 查询缓存，过期的响应被丢弃
+    pub fn get(&mut self, key: &str) -> Option<Value> {
+        let ttl = self.config.ttl_secs;
+        let expired = match self.entries.get(key) {
+            Some(entry) => ttl > 0 && entry.inserted.elapsed() >= Duration::from_secs(ttl),
+            None => {
+                self.misses += 1;
+                return None;
+            }
+        };
+        if expired {
+            self.remove(key);
+            self.misses += 1;
+            return None;
+        }
+        self.hits += 1;
+        self.entries.get(key).map(|entry| entry.value.clone())
+    }
+
+    /// 缓存不可变的响应，返回是否已缓存
+    pub fn insert(&mut self, key: String, value: &Value) -> bool {
+        if !is_immutable_result(value) {
+            return false;
+        }
+        let bytes = key.len() + value.to_string().len();
+        if bytes > self.config.max_bytes {
+            return false;
+        }
+        self.remove(&key);
+        self.bytes += bytes;
+        self.entries.put(
+            key,
+            Entry {
+                value: value.clone(),
+                bytes,
+                inserted: Instant::now(),
+            },
+        );
+        while self.entries.len() > self.config.max_entries || self.bytes > self.config.max_bytes {
+            match self.entries.pop_lru() {
+                Some((_, entry)) => self.bytes -= entry.bytes,
+                None => break,
+            }
+        }
+        true
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.pop(key) {
+            self.bytes -= entry.bytes;
+        }
+    }
+
+    /// 记录当前链头，链头回退或同一高度的区块变化时清空缓存
+    pub fn observe_head(&mut self, head: Option<(u64, H256)>) {
+        let rewound = match (self.head, head) {
+            (Some(_), None) => true,
+            (Some((number, hash)), Some((current, current_hash))) => {
+                current < number || (current == number && current_hash != hash)
+            }
+            _ => false,
+        };
+        if rewound {
+            self.clear();
+        }
+        self.head = head;
+    }
+
+    /// 清空缓存
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+
+    /// 淘汰最久未使用的响应直到不超过 `len` 条，返回淘汰的条目数
+    pub fn shrink_to(&mut self, len: usize) -> usize {
+        let mut evicted = 0;
+        while self.entries.len() > len {
+            match self.entries.pop_lru() {
+                Some((_, entry)) => self.bytes -= entry.bytes,
+                None => break,
+            }
+            evicted += 1;
+        }
+        evicted
+    }
+
+    /// 缓存统计
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            bytes: self.bytes,
+        }
+    }
+}
+
+/// 先查询缓存的 RPC 方法
+pub(crate) struct CachedMethod {
+    pub(crate) method: String,
+    pub(crate) inner: Arc<dyn RpcMethod<()>>,
+    pub(crate) cache: Arc<Mutex<RpcCache>>,
+    pub(crate) vm: Arc<RwLock<dyn VmExt>>,
+}
+
+impl RpcMethod<()> for CachedMethod {
+    fn call(&self, params: Params, meta: ()) -> BoxFuture<Result<Value>> {
+        let key = RpcCache::key(&self.method, &params);
+        let inner = self.inner.clone();
+        let cache = self.cache.clone();
+        let vm = self.vm.clone();
+        Box::pin(async move {
+            let head = {
+                let vm = vm.read().await;
+                let blockchain = vm.get_blockchain().await;
+                let blockchain = blockchain.read().await;
+                blockchain
+                    .latest_block()
+                    .map(|block| (block.header.number, block.hash()))
+            };
+            {
+                let mut cache = cache.lock().await;
+                cache.observe_head(head);
+                if let Some(value) = cache.get(&key) {
+                    return Ok(value);
+                }
+            }
+            let value = inner.call(params, meta).await?;
+            cache.lock().await.insert(key, &value);
+            Ok(value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> RpcCacheConfig {
+        RpcCacheConfig {
+            enabled: true,
+            max_entries: 2,
+            max_bytes: 1024,
+            ttl_secs: 0,
+        }
+    }
+
+    #[test]
+    fn test_immutable_results() {
+        assert!(!is_immutable_result(&Value::Null));
+        assert!(!is_immutable_result(
+            &json!({"hash": "0x1", "block_hash": null})
+        ));
+        assert!(is_immutable_result(
+            &json!({"hash": "0x1", "block_hash": "0x2"})
+        ));
+        assert!(is_immutable_result(&json!(3)));
+    }
+
+    #[test]
+    fn test_eviction_and_invalidation() {
+        let mut cache = RpcCache::new(config());
+        assert!(!cache.insert("a".into(), &Value::Null));
+        assert!(cache.insert("a".into(), &json!(1)));
+        assert!(cache.insert("b".into(), &json!(2)));
+        assert_eq!(cache.get("a"), Some(json!(1)));
+        // 超过条目数时淘汰最久未使用的 b
+        assert!(cache.insert("c".into(), &json!(3)));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.stats().entries, 2);
+        assert_eq!(cache.stats().hits, 1);
+
+        cache.observe_head(Some((5, H256::repeat_byte(5))));
+        cache.observe_head(Some((6, H256::repeat_byte(6))));
+        assert_eq!(cache.stats().entries, 2);
+        // 同一高度的区块变化视为链被替换
+        cache.observe_head(Some((6, H256::repeat_byte(7))));
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().bytes, 0);
+
+        let mut small = RpcCache::new(RpcCacheConfig {
+            max_bytes: 8,
+            ..config()
+        });
+        assert!(!small.insert("key".into(), &json!("a long response")));
+    }
+
+    #[test]
+    fn test_ttl() {
+        let mut cache = RpcCache::new(RpcCacheConfig {
+            ttl_secs: 1,
+            ..config()
+        });
+        cache.insert("a".into(), &json!(1));
+        cache.entries.get_mut("a").unwrap().inserted -= Duration::from_secs(2);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.stats().bytes, 0);
+    }
+}
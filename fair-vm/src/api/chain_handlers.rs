@@ -1,27 +1,53 @@
 use crate::{
     account::Address as AccountAddress,
-    api::VmExt,
+    api::graphql::MAX_LOG_BLOCK_RANGE,
+    api::{filter_logs, VmExt},
+    blockchain::Block,
+    log_filter::FilterKind,
+    log_index::SECTION_SIZE,
     transaction::{Transaction, TransactionType},
     types::{Hash, U256},
 };
-use ethers::types::{H160, H256};
+use ethers::types::{
+    BlockNumber, Bytes, Filter, FilterBlockOption, Log, NameOrAddress, TransactionReceipt,
+    TransactionRequest as CallRequest, ValueOrArray, H160, H256,
+};
 use fair_vm_core::types::Transaction as CoreTransaction;
-use jsonrpc_core::{Error, Result};
+use fair_vm_core::vm::ExecutionResult;
+use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::RwLock;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BlockResponse {
     pub number: u64,
     pub hash: String,
     pub parent_hash: String,
     pub timestamp: u64,
-    pub transactions: Vec<TransactionResponse>,
+    pub transactions_root: String,
+    pub state_root: String,
+    pub transactions: BlockTransactions,
+    /// 出块验证人的节点 ID
+    pub proposer: Option<String>,
+    /// 本节点接受区块的时间（Unix 秒）
+    pub accepted_at: Option<u64>,
+    /// 区块生效的 gas 上限
+    pub gas_limit: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 区块中的交易，根据 `fullTransactions` 返回哈希或完整交易
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum BlockTransactions {
+    Hashes(Vec<String>),
+    Full(Vec<TransactionResponse>),
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TransactionResponse {
     pub hash: String,
     pub from: String,
@@ -31,6 +57,75 @@ pub struct TransactionResponse {
     pub nonce: u64,
     pub gas_price: String,
     pub gas_limit: u64,
+    pub block_hash: Option<String>,
+    pub block_number: Option<u64>,
+    pub transaction_index: Option<u64>,
+}
+
+impl BlockResponse {
+    pub(crate) fn from_block(block: &Block, full_transactions: bool) -> Self {
+        let hash = block.hash();
+        let transactions = if full_transactions {
+            BlockTransactions::Full(
+                block
+                    .transactions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, tx)| TransactionResponse::in_block(tx, block, hash, index))
+                    .collect(),
+            )
+        } else {
+            BlockTransactions::Hashes(
+                block
+                    .transactions
+                    .iter()
+                    .map(|tx| format!("{:?}", tx.hash))
+                    .collect(),
+            )
+        };
+
+        Self {
+            number: block.header.number,
+            hash: format!("{:?}", hash),
+            parent_hash: format!("{:?}", block.header.parent_hash),
+            timestamp: block.header.timestamp,
+            transactions_root: format!("{:?}", block.header.transactions_root),
+            state_root: format!("{:?}", block.header.state_root),
+            transactions,
+            proposer: block.header.proposer.clone(),
+            accepted_at: block
+                .acceptance
+                .as_ref()
+                .map(|acceptance| acceptance.accepted_at),
+            gas_limit: block
+                .acceptance
+                .as_ref()
+                .and_then(|acceptance| acceptance.gas_limit),
+        }
+    }
+}
+
+impl TransactionResponse {
+    pub(crate) fn in_block(
+        tx: &Transaction,
+        block: &Block,
+        block_hash: H256,
+        index: usize,
+    ) -> Self {
+        Self {
+            hash: format!("{:?}", tx.hash),
+            from: format!("0x{}", hex::encode(tx.from.0)),
+            to: tx.to.as_ref().map(|to| format!("0x{}", hex::encode(to.0))),
+            value: format!("0x{:x}", tx.value),
+            data: format!("0x{}", hex::encode(&tx.data)),
+            nonce: tx.nonce,
+            gas_price: format!("0x{:x}", tx.gas_price.unwrap_or_default()),
+            gas_limit: tx.gas_limit,
+            block_hash: Some(format!("{:?}", block_hash)),
+            block_number: Some(block.header.number),
+            transaction_index: Some(index as u64),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,15 +161,155 @@ impl ChainHandlers {
             hex::decode(&address).map_err(|_| Error::invalid_params("Invalid address"))?;
         Ok(AccountAddress::from(H160::from_slice(&address_bytes)))
     }
+
+    fn parse_hash(&self, hash: &str) -> Result<H256> {
+        let hash_bytes = hex::decode(hash.trim_start_matches("0x"))
+            .map_err(|_| Error::invalid_params("Invalid hash"))?;
+        if hash_bytes.len() != 32 {
+            return Err(Error::invalid_params("Invalid hash"));
+        }
+        Ok(H256::from_slice(&hash_bytes))
+    }
+
+    /// 在最新区块之后安装过滤器
+    fn install_filter(&self, kind: FilterKind) -> Result<U256> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let latest = latest_block_number(&*vm).await;
+            let id = vm
+                .get_log_filters()
+                .await
+                .write()
+                .await
+                .install(kind, latest, Instant::now());
+            id.ok_or_else(|| Error::invalid_params("过滤器数量已达上限"))
+        })
+    }
+
+    /// 在区块链上查询区块并转换结果
+    fn with_block<T>(
+        &self,
+        selector: BlockSelector,
+        f: impl FnOnce(&Block) -> T,
+    ) -> Result<Option<T>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let blockchain = vm.get_blockchain().await;
+            let blockchain = blockchain.read().await;
+            let block = match selector {
+                BlockSelector::Number(number) => blockchain.get_block(number),
+                BlockSelector::Hash(hash) => blockchain.get_block_by_hash(&hash),
+            };
+            Ok(block.map(f))
+        })
+    }
+}
+
+/// 按地址或主题过滤时 `eth_getLogs` 允许的最大区块范围，布隆索引可跳过无关区块
+pub const MAX_FILTERED_LOG_RANGE: u64 = 16 * SECTION_SIZE;
+
+/// `eth_getFilterChanges` 的结果，日志过滤器返回日志，区块过滤器返回区块哈希
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FilterChanges {
+    Logs(Vec<Log>),
+    Hashes(Vec<H256>),
+}
+
+/// 区块查询条件
+enum BlockSelector {
+    Number(u64),
+    Hash(H256),
+}
+
+/// 将区块标签解析为高度，`latest`、`pending` 等均视为最新区块
+fn resolve_block_number(number: Option<BlockNumber>, latest: u64) -> u64 {
+    match number {
+        Some(BlockNumber::Number(number)) => number.as_u64(),
+        Some(BlockNumber::Earliest) => 0,
+        _ => latest,
+    }
+}
+
+/// 明确给出的区块高度，`latest` 等标签返回 `None`
+fn explicit_block_number(number: Option<BlockNumber>) -> Option<u64> {
+    match number {
+        Some(BlockNumber::Number(number)) => Some(number.as_u64()),
+        Some(BlockNumber::Earliest) => Some(0),
+        _ => None,
+    }
+}
+
+/// 过滤条件中的合约地址，为空表示任意
+fn filter_addresses(filter: &Filter) -> Vec<H160> {
+    match &filter.address {
+        None => Vec::new(),
+        Some(ValueOrArray::Value(address)) => vec![*address],
+        Some(ValueOrArray::Array(addresses)) => addresses.clone(),
+    }
+}
+
+/// 过滤条件中的主题，每个位置为空表示任意
+fn filter_topics(filter: &Filter) -> Vec<Vec<H256>> {
+    let mut topics: Vec<Vec<H256>> = filter
+        .topics
+        .iter()
+        .map(|topic| match topic {
+            None | Some(ValueOrArray::Value(None)) => Vec::new(),
+            Some(ValueOrArray::Value(Some(topic))) => vec![*topic],
+            // 数组中含 null 时该位置匹配任意主题
+            Some(ValueOrArray::Array(topics)) => topics
+                .iter()
+                .copied()
+                .collect::<Option<Vec<_>>>()
+                .unwrap_or_default(),
+        })
+        .collect();
+    while topics.last().is_some_and(Vec::is_empty) {
+        topics.pop();
+    }
+    topics
 }
 
 #[rpc]
 pub trait ChainApi {
     #[rpc(name = "chain_getBlockByNumber")]
-    fn get_block_by_number(&self, number: u64) -> Result<Option<BlockResponse>>;
+    fn get_block_by_number(
+        &self,
+        number: u64,
+        full_transactions: Option<bool>,
+    ) -> Result<Option<BlockResponse>>;
 
     #[rpc(name = "chain_getBlockByHash")]
-    fn get_block_by_hash(&self, hash: String) -> Result<Option<BlockResponse>>;
+    fn get_block_by_hash(
+        &self,
+        hash: String,
+        full_transactions: Option<bool>,
+    ) -> Result<Option<BlockResponse>>;
+
+    #[rpc(name = "chain_getBlockTransactionCountByNumber")]
+    fn get_block_transaction_count_by_number(&self, number: u64) -> Result<Option<u64>>;
+
+    #[rpc(name = "chain_getBlockTransactionCountByHash")]
+    fn get_block_transaction_count_by_hash(&self, hash: String) -> Result<Option<u64>>;
+
+    #[rpc(name = "chain_getTransactionByBlockNumberAndIndex")]
+    fn get_transaction_by_block_number_and_index(
+        &self,
+        number: u64,
+        index: u64,
+    ) -> Result<Option<TransactionResponse>>;
+
+    #[rpc(name = "chain_getTransactionByBlockHashAndIndex")]
+    fn get_transaction_by_block_hash_and_index(
+        &self,
+        hash: String,
+        index: u64,
+    ) -> Result<Option<TransactionResponse>>;
 
     #[rpc(name = "chain_getTransactionByHash")]
     fn get_transaction_by_hash(&self, hash: String) -> Result<Option<TransactionResponse>>;
@@ -87,40 +322,122 @@ pub trait ChainApi {
 
     #[rpc(name = "chain_getAccount")]
     fn get_account(&self, address: String) -> Result<AccountResponse>;
+
+    #[rpc(name = "eth_chainId")]
+    fn chain_id(&self) -> Result<String>;
+
+    #[rpc(name = "eth_sendRawTransaction")]
+    fn send_raw_transaction(&self, raw: String) -> Result<String>;
+
+    /// 在最新状态上执行调用，只支持最新区块
+    ///
+    /// 执行回滚时错误码为 3，`data` 为十六进制编码的回滚数据。
+    #[rpc(name = "eth_call")]
+    fn call(&self, request: CallRequest, block: Option<BlockNumber>) -> Result<Bytes>;
+
+    #[rpc(name = "eth_getTransactionReceipt")]
+    fn get_transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>>;
+
+    /// 区块内全部交易的收据，区块不存在时返回 null
+    #[rpc(name = "eth_getBlockReceipts")]
+    fn get_block_receipts(&self, block: BlockNumber) -> Result<Option<Vec<TransactionReceipt>>>;
+
+    #[rpc(name = "eth_getLogs")]
+    fn get_logs(&self, filter: Filter) -> Result<Vec<Log>>;
+
+    #[rpc(name = "eth_newFilter")]
+    fn new_filter(&self, filter: Filter) -> Result<U256>;
+
+    #[rpc(name = "eth_newBlockFilter")]
+    fn new_block_filter(&self) -> Result<U256>;
+
+    #[rpc(name = "eth_getFilterChanges")]
+    fn get_filter_changes(&self, id: U256) -> Result<FilterChanges>;
+
+    #[rpc(name = "eth_getFilterLogs")]
+    fn get_filter_logs(&self, id: U256) -> Result<Vec<Log>>;
+
+    #[rpc(name = "eth_uninstallFilter")]
+    fn uninstall_filter(&self, id: U256) -> Result<bool>;
 }
 
 impl ChainApi for ChainHandlers {
-    fn get_block_by_number(&self, _number: u64) -> Result<Option<BlockResponse>> {
-        let vm = self.vm.clone();
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        let result: Result<Option<BlockResponse>> = runtime.block_on(async {
-            let _vm = vm.read().await;
-            // TODO: 实现获取区块的逻辑
-            Ok(None)
-        });
-        result.map_err(|_e| Error::internal_error())
+    fn get_block_by_number(
+        &self,
+        number: u64,
+        full_transactions: Option<bool>,
+    ) -> Result<Option<BlockResponse>> {
+        let full = full_transactions.unwrap_or(false);
+        self.with_block(BlockSelector::Number(number), |block| {
+            BlockResponse::from_block(block, full)
+        })
     }
 
-    fn get_block_by_hash(&self, _hash: String) -> Result<Option<BlockResponse>> {
-        let vm = self.vm.clone();
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        let result: Result<Option<BlockResponse>> = runtime.block_on(async {
-            let _vm = vm.read().await;
-            // TODO: 实现获取区块的逻辑
-            Ok(None)
-        });
-        result.map_err(|_e| Error::internal_error())
+    fn get_block_by_hash(
+        &self,
+        hash: String,
+        full_transactions: Option<bool>,
+    ) -> Result<Option<BlockResponse>> {
+        let hash = self.parse_hash(&hash)?;
+        let full = full_transactions.unwrap_or(false);
+        self.with_block(BlockSelector::Hash(hash), |block| {
+            BlockResponse::from_block(block, full)
+        })
     }
 
-    fn get_transaction_by_hash(&self, _hash: String) -> Result<Option<TransactionResponse>> {
+    fn get_block_transaction_count_by_number(&self, number: u64) -> Result<Option<u64>> {
+        self.with_block(BlockSelector::Number(number), |block| {
+            block.transactions.len() as u64
+        })
+    }
+
+    fn get_block_transaction_count_by_hash(&self, hash: String) -> Result<Option<u64>> {
+        let hash = self.parse_hash(&hash)?;
+        self.with_block(BlockSelector::Hash(hash), |block| {
+            block.transactions.len() as u64
+        })
+    }
+
+    fn get_transaction_by_block_number_and_index(
+        &self,
+        number: u64,
+        index: u64,
+    ) -> Result<Option<TransactionResponse>> {
+        self.with_block(BlockSelector::Number(number), |block| {
+            transaction_at(block, index)
+        })
+        .map(Option::flatten)
+    }
+
+    fn get_transaction_by_block_hash_and_index(
+        &self,
+        hash: String,
+        index: u64,
+    ) -> Result<Option<TransactionResponse>> {
+        let hash = self.parse_hash(&hash)?;
+        self.with_block(BlockSelector::Hash(hash), |block| {
+            transaction_at(block, index)
+        })
+        .map(Option::flatten)
+    }
+
+    fn get_transaction_by_hash(&self, hash: String) -> Result<Option<TransactionResponse>> {
+        let hash = self.parse_hash(&hash)?;
         let vm = self.vm.clone();
         let runtime = tokio::runtime::Runtime::new().unwrap();
-        let result: Result<Option<TransactionResponse>> = runtime.block_on(async {
-            let _vm = vm.read().await;
-            // TODO: 实现获取交易的逻辑
-            Ok(None)
-        });
-        result.map_err(|_e| Error::internal_error())
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let blockchain = vm.get_blockchain().await;
+            let blockchain = blockchain.read().await;
+            Ok(blockchain.find_transaction(&hash).map(|(block, index)| {
+                TransactionResponse::in_block(
+                    &block.transactions[index],
+                    block,
+                    block.hash(),
+                    index,
+                )
+            }))
+        })
     }
 
     fn send_transaction(&self, transaction: TransactionRequest) -> Result<String> {
@@ -159,7 +476,7 @@ impl ChainApi for ChainHandlers {
                 data,
                 signature: Vec::new(),
                 transaction_type: TransactionType::Legacy,
-                chain_id: 1,
+                chain_id: vm.get_chain_config().await.chain_id,
                 max_fee_per_gas: Some(gas_price * U256::from(2)),
                 max_priority_fee_per_gas: Some(gas_price),
             };
@@ -216,6 +533,291 @@ impl ChainApi for ChainHandlers {
         });
         result
     }
+
+    fn chain_id(&self) -> Result<String> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(format!("0x{:x}", vm.get_chain_config().await.chain_id))
+        })
+    }
+
+    fn send_raw_transaction(&self, raw: String) -> Result<String> {
+        let raw = hex::decode(raw.trim_start_matches("0x"))
+            .map_err(|_| Error::invalid_params("Invalid raw transaction"))?;
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let hash = vm.submit_raw_transaction(&raw).await?;
+            Ok(format!("{:?}", hash))
+        })
+    }
+
+    fn call(&self, request: CallRequest, block: Option<BlockNumber>) -> Result<Bytes> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            if let Some(number) = explicit_block_number(block) {
+                if number != latest_block_number(&*vm).await {
+                    return Err(Error::invalid_params("仅支持在最新状态上执行调用"));
+                }
+            }
+            let to = match request.to {
+                Some(NameOrAddress::Address(to)) => Some(AccountAddress::from(to)),
+                Some(NameOrAddress::Name(_)) => {
+                    return Err(Error::invalid_params("不支持 ENS 名称"));
+                }
+                None => None,
+            };
+            let from = AccountAddress::from(request.from.unwrap_or_default());
+            let nonce = match request.nonce {
+                Some(nonce) => nonce.as_u64(),
+                None => vm
+                    .get_account(&from)
+                    .await
+                    .map(|account| account.nonce)
+                    .unwrap_or(0),
+            };
+            let gas_limit = request.gas.map_or(CALL_GAS_CAP, |gas| {
+                gas.min(U256::from(CALL_GAS_CAP)).as_u64()
+            });
+            let tx = Transaction::new(
+                H256::zero(),
+                from,
+                to,
+                request.value.unwrap_or_default(),
+                nonce,
+                gas_limit,
+                Some(U256::zero()),
+                request.data.map(|data| data.to_vec()).unwrap_or_default(),
+                Vec::new(),
+                TransactionType::Legacy,
+                vm.get_chain_config().await.chain_id,
+                None,
+                None,
+            );
+            let result = vm.call(&tx).await;
+            if result.status {
+                Ok(Bytes::from(result.return_data))
+            } else {
+                Err(execution_error(&result))
+            }
+        })
+    }
+
+    fn get_transaction_receipt(&self, hash: H256) -> Result<Option<TransactionReceipt>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let receipt = vm.get_transaction_receipt(hash.as_bytes()).await;
+            Ok(receipt.as_ref().map(TransactionReceipt::from))
+        })
+    }
+
+    fn get_block_receipts(&self, block: BlockNumber) -> Result<Option<Vec<TransactionReceipt>>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let number = resolve_block_number(Some(block), latest_block_number(&*vm).await);
+            let blockchain = vm.get_blockchain().await;
+            if blockchain.read().await.get_block(number).is_none() {
+                return Ok(None);
+            }
+            let receipts = vm.get_block_receipts(number).await;
+            Ok(Some(
+                receipts.iter().map(TransactionReceipt::from).collect(),
+            ))
+        })
+    }
+
+    fn get_logs(&self, filter: Filter) -> Result<Vec<Log>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            query_logs(&*vm, &filter).await
+        })
+    }
+
+    fn new_filter(&self, filter: Filter) -> Result<U256> {
+        if matches!(filter.block_option, FilterBlockOption::AtBlockHash(_)) {
+            return Err(Error::invalid_params("过滤器不支持 blockHash"));
+        }
+        self.install_filter(FilterKind::Logs(filter))
+    }
+
+    fn new_block_filter(&self) -> Result<U256> {
+        self.install_filter(FilterKind::Blocks)
+    }
+
+    fn get_filter_changes(&self, id: U256) -> Result<FilterChanges> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let latest = latest_block_number(&*vm).await;
+            let poll = vm
+                .get_log_filters()
+                .await
+                .write()
+                .await
+                .poll(id, latest, Instant::now())
+                .ok_or_else(|| Error::invalid_params("过滤器不存在"))?;
+            match poll.kind {
+                FilterKind::Blocks => {
+                    let blockchain = vm.get_blockchain().await;
+                    let blockchain = blockchain.read().await;
+                    Ok(FilterChanges::Hashes(
+                        (poll.from..=poll.to)
+                            .filter_map(|number| blockchain.get_block(number))
+                            .map(|block| block.hash())
+                            .collect(),
+                    ))
+                }
+                FilterKind::Logs(filter) => {
+                    let FilterBlockOption::Range {
+                        from_block,
+                        to_block,
+                    } = &filter.block_option
+                    else {
+                        return Ok(FilterChanges::Logs(Vec::new()));
+                    };
+                    // 只有明确的区块高度限制新区块范围，标签视为不限
+                    let from = explicit_block_number(*from_block)
+                        .map_or(poll.from, |from| from.max(poll.from));
+                    let to = explicit_block_number(*to_block).map_or(poll.to, |to| to.min(poll.to));
+                    if to < from {
+                        return Ok(FilterChanges::Logs(Vec::new()));
+                    }
+                    let logs = filter_logs(
+                        &*vm,
+                        from,
+                        to,
+                        &filter_addresses(&filter),
+                        &filter_topics(&filter),
+                    )
+                    .await;
+                    Ok(FilterChanges::Logs(logs))
+                }
+            }
+        })
+    }
+
+    fn get_filter_logs(&self, id: U256) -> Result<Vec<Log>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let filter = vm
+                .get_log_filters()
+                .await
+                .write()
+                .await
+                .logs_filter(id, Instant::now())
+                .ok_or_else(|| Error::invalid_params("日志过滤器不存在"))?;
+            query_logs(&*vm, &filter).await
+        })
+    }
+
+    fn uninstall_filter(&self, id: U256) -> Result<bool> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let removed = vm.get_log_filters().await.write().await.uninstall(id);
+            Ok(removed)
+        })
+    }
+}
+
+/// `eth_call` 未指定 gas 时的 gas 上限，也是允许指定的最大值
+pub const CALL_GAS_CAP: u64 = 50_000_000;
+
+/// `eth_call` 执行失败的错误
+///
+/// 回滚时与以太坊节点保持一致：错误码 3，消息以 `execution reverted` 开头，`data` 为回滚数据，
+/// 钱包和合约工具据此识别并解码回滚原因。
+fn execution_error(result: &ExecutionResult) -> Error {
+    match result.revert_reason() {
+        Some(reason) => Error {
+            code: ErrorCode::ServerError(3),
+            message: format!("execution reverted: {}", reason),
+            data: Some(serde_json::Value::String(format!(
+                "0x{}",
+                hex::encode(&result.return_data)
+            ))),
+        },
+        None => Error {
+            code: ErrorCode::ServerError(-32000),
+            message: "执行失败".into(),
+            data: None,
+        },
+    }
+}
+
+/// 最新区块高度，没有区块时为 0
+async fn latest_block_number(vm: &dyn VmExt) -> u64 {
+    let blockchain = vm.get_blockchain().await;
+    let blockchain = blockchain.read().await;
+    blockchain
+        .latest_block()
+        .map(|block| block.header.number)
+        .unwrap_or(0)
+}
+
+/// 按 `eth_getLogs` 的规则查询日志
+async fn query_logs(vm: &dyn VmExt, filter: &Filter) -> Result<Vec<Log>> {
+    let addresses = filter_addresses(filter);
+    let topics = filter_topics(filter);
+    let (from, to) = {
+        let latest = latest_block_number(vm).await;
+        match filter.block_option {
+            FilterBlockOption::AtBlockHash(hash) => {
+                let blockchain = vm.get_blockchain().await;
+                let blockchain = blockchain.read().await;
+                match blockchain.get_block_by_hash(&hash) {
+                    Some(block) => (block.header.number, block.header.number),
+                    None => return Err(Error::invalid_params("区块不存在")),
+                }
+            }
+            FilterBlockOption::Range {
+                from_block,
+                to_block,
+            } => (
+                resolve_block_number(from_block, latest),
+                resolve_block_number(to_block, latest),
+            ),
+        }
+    };
+    if to < from {
+        return Err(Error::invalid_params("结束区块不能小于起始区块"));
+    }
+    let max_range = if addresses.is_empty() && topics.is_empty() {
+        MAX_LOG_BLOCK_RANGE
+    } else {
+        MAX_FILTERED_LOG_RANGE
+    };
+    if to - from >= max_range {
+        return Err(Error::invalid_params(format!(
+            "日志查询范围不能超过 {} 个区块",
+            max_range
+        )));
+    }
+    Ok(filter_logs(vm, from, to, &addresses, &topics).await)
+}
+
+/// 获取区块中指定索引的交易
+fn transaction_at(block: &Block, index: u64) -> Option<TransactionResponse> {
+    let index = usize::try_from(index).ok()?;
+    block
+        .transactions
+        .get(index)
+        .map(|tx| TransactionResponse::in_block(tx, block, block.hash(), index))
 }
 
 fn convert_transaction(tx: &Transaction) -> CoreTransaction {
@@ -0,0 +1,210 @@
+use crate::api::VmExt;
+use crate::evm::debugger::{DebugError, SessionState, StepResult, MAX_STEPS_PER_CALL};
+use crate::evm::selectors;
+use ethers::types::{Bytes, H256, U256};
+use jsonrpc_core::{Error, Result};
+use jsonrpc_derive::rpc;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 单次查询允许返回的最大内存字节数
+pub const MAX_MEMORY_READ: usize = 64 * 1024;
+
+#[rpc]
+pub trait DebugApi {
+    /// 为已打包的交易创建调试会话，执行停在第一条指令之前
+    #[rpc(name = "debug_startSession")]
+    fn start_session(&self, tx_hash: H256) -> Result<SessionState>;
+
+    /// 执行 `count` 条指令（默认 1 条），遇到断点提前停止
+    #[rpc(name = "debug_step")]
+    fn step(&self, session_id: u64, count: Option<usize>) -> Result<StepResult>;
+
+    /// 继续执行直到断点或执行结束
+    #[rpc(name = "debug_continue")]
+    fn resume(&self, session_id: u64) -> Result<StepResult>;
+
+    /// 当前栈，栈顶在前
+    #[rpc(name = "debug_stack")]
+    fn stack(&self, session_id: u64) -> Result<Vec<U256>>;
+
+    /// 读取内存，未指定区间时返回全部内存
+    #[rpc(name = "debug_memory")]
+    fn memory(
+        &self,
+        session_id: u64,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> Result<Bytes>;
+
+    /// 在指定位置设置或取消断点，返回断点是否处于设置状态
+    #[rpc(name = "debug_breakpoint")]
+    fn breakpoint(&self, session_id: u64, pc: usize) -> Result<bool>;
+
+    /// 当前会话状态
+    #[rpc(name = "debug_sessionState")]
+    fn session_state(&self, session_id: u64) -> Result<SessionState>;
+
+    /// 结束调试会话
+    #[rpc(name = "debug_endSession")]
+    fn end_session(&self, session_id: u64) -> Result<bool>;
+}
+
+impl From<DebugError> for Error {
+    fn from(e: DebugError) -> Self {
+        match e {
+            DebugError::Evm(_) => {
+                let mut err = Error::internal_error();
+                err.data = Some(serde_json::Value::String(e.to_string()));
+                err
+            }
+            _ => Error::invalid_params(e.to_string()),
+        }
+    }
+}
+
+/// 调试接口处理器
+pub struct DebugHandlers {
+    vm: Arc<RwLock<dyn VmExt>>,
+}
+
+impl DebugHandlers {
+    pub fn new(vm: Arc<RwLock<dyn VmExt>>) -> Self {
+        Self { vm }
+    }
+
+    fn run(&self, session_id: u64, count: usize) -> Result<StepResult> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let sessions = vm.get_debug_sessions().await;
+            let mut sessions = sessions.write().await;
+            Ok(sessions.get_mut(session_id)?.run(count).await)
+        })
+    }
+}
+
+impl DebugApi for DebugHandlers {
+    fn start_session(&self, tx_hash: H256) -> Result<SessionState> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let (tx, number) = {
+                let blockchain = vm.get_blockchain().await;
+                let blockchain = blockchain.read().await;
+                let (block, index) = blockchain
+                    .find_transaction(&tx_hash)
+                    .ok_or_else(|| Error::invalid_params("交易不存在"))?;
+                (block.transactions[index].clone(), block.header.number)
+            };
+            // 区块环境取自交易所在区块，状态为链头的最新状态
+            let env = vm
+                .get_block_environment(number)
+                .await
+                .ok_or_else(|| Error::invalid_params("交易所在区块不存在"))?;
+            let storage = vm.get_state().await.read().await.storage().clone();
+            let source = match tx.to {
+                Some(to) => vm.get_source_maps().await.read().await.get(&to),
+                None => None,
+            };
+            let function = match tx.to {
+                Some(_) => {
+                    let selectors = vm.get_selectors().await;
+                    selectors::decode(&selectors, &tx.data)
+                        .await
+                        .and_then(|decoded| decoded.signatures.into_iter().next())
+                }
+                None => None,
+            };
+            let sessions = vm.get_debug_sessions().await;
+            let mut sessions = sessions.write().await;
+            let id = sessions.start(&tx, storage, env, source, function).await?;
+            Ok(sessions.get(id)?.state())
+        })
+    }
+
+    fn step(&self, session_id: u64, count: Option<usize>) -> Result<StepResult> {
+        let count = count.unwrap_or(1);
+        if count == 0 || count > MAX_STEPS_PER_CALL {
+            return Err(Error::invalid_params(format!(
+                "步数必须在 1 到 {} 之间",
+                MAX_STEPS_PER_CALL
+            )));
+        }
+        self.run(session_id, count)
+    }
+
+    fn resume(&self, session_id: u64) -> Result<StepResult> {
+        self.run(session_id, MAX_STEPS_PER_CALL)
+    }
+
+    fn stack(&self, session_id: u64) -> Result<Vec<U256>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let sessions = vm.get_debug_sessions().await;
+            let mut sessions = sessions.write().await;
+            Ok(sessions.get(session_id)?.stack())
+        })
+    }
+
+    fn memory(
+        &self,
+        session_id: u64,
+        offset: Option<usize>,
+        length: Option<usize>,
+    ) -> Result<Bytes> {
+        let offset = offset.unwrap_or(0);
+        let length = length.unwrap_or(MAX_MEMORY_READ);
+        if length > MAX_MEMORY_READ {
+            return Err(Error::invalid_params(format!(
+                "单次最多读取 {} 字节",
+                MAX_MEMORY_READ
+            )));
+        }
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let sessions = vm.get_debug_sessions().await;
+            let mut sessions = sessions.write().await;
+            Ok(sessions.get(session_id)?.memory(offset, length).into())
+        })
+    }
+
+    fn breakpoint(&self, session_id: u64, pc: usize) -> Result<bool> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let sessions = vm.get_debug_sessions().await;
+            let mut sessions = sessions.write().await;
+            Ok(sessions.get_mut(session_id)?.toggle_breakpoint(pc))
+        })
+    }
+
+    fn session_state(&self, session_id: u64) -> Result<SessionState> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let sessions = vm.get_debug_sessions().await;
+            let mut sessions = sessions.write().await;
+            Ok(sessions.get(session_id)?.state())
+        })
+    }
+
+    fn end_session(&self, session_id: u64) -> Result<bool> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let sessions = vm.get_debug_sessions().await;
+            let mut sessions = sessions.write().await;
+            Ok(sessions.end(session_id))
+        })
+    }
+}
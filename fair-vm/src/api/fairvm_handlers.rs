@@ -0,0 +1,632 @@
+use crate::account_history::{AccountTransfer, MAX_HISTORY_RANGE};
+use crate::api::graphql::MAX_LOG_BLOCK_RANGE;
+use crate::api::{filter_logs, VmExt};
+use crate::avax_address::{self, AddressFormats};
+use crate::blockchain::BlockHeader;
+use crate::chain_metadata::ChainMetadata;
+use crate::checkpoint::{Checkpoint, CheckpointSnapshot, CheckpointSource};
+use crate::consensus::ValidatorInfo;
+use crate::evm::selectors::{self, DecodedSelector};
+use crate::evm::source_map::{ContractSource, SourceLocation};
+use crate::fee_stats::FeeStatsSummary;
+use crate::gas_limit::GasLimitStatus;
+use crate::gas_stats::GasBySelectorReport;
+use crate::inclusion::InclusionProof;
+use crate::nonce::{self, NonceSequence};
+use crate::oracle::PriceRound;
+use crate::staking::{self, ValidatorRewards};
+use crate::state_proof::{StateCommitment, StateProof, StorageProof};
+use crate::storage::Storage;
+use crate::supply::{SupplyStats, DEFAULT_TOP_HOLDERS, MAX_TOP_HOLDERS};
+use crate::uptime::{self, ValidatorStats};
+use ethers::types::{Bytes, Log, H160, H256};
+use fair_vm_core::params::ChainConfig;
+use jsonrpc_core::{Error, Result};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 单次查询允许的最大区块范围
+pub const MAX_FEE_STATS_RANGE: u64 = 1024;
+
+/// 单次查询允许返回的最大价格轮次数
+pub const MAX_PRICE_ROUNDS: usize = 256;
+
+/// 单次查询允许返回的最大区块头数
+pub const MAX_HEADERS: u64 = 512;
+
+/// 按时间范围查询日志的过滤条件，时间戳单位为秒
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeLogFilter {
+    pub from_timestamp: u64,
+    pub to_timestamp: u64,
+    /// 合约地址，为空表示任意
+    #[serde(default)]
+    pub addresses: Vec<H160>,
+    /// 按位置匹配的主题，每个位置内为“或”关系，空列表表示任意
+    #[serde(default)]
+    pub topics: Vec<Vec<H256>>,
+}
+
+/// 区块高度范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockRange {
+    pub from_block: u64,
+    pub to_block: u64,
+}
+
+#[rpc]
+pub trait FairVmApi {
+    #[rpc(name = "fairvm_feeStats")]
+    fn fee_stats(&self, from_block: u64, to_block: u64) -> Result<FeeStatsSummary>;
+
+    /// 区块范围内按合约和函数选择器汇总的 gas 使用，`limit` 限制返回的条目数
+    #[rpc(name = "fairvm_gasBySelector")]
+    fn gas_by_selector(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        limit: Option<usize>,
+    ) -> Result<GasBySelectorReport>;
+
+    /// 区块范围内账户的转账记录，含手续费和交易执行后的余额，按执行顺序
+    #[rpc(name = "fairvm_getAccountHistory")]
+    fn account_history(
+        &self,
+        address: H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<AccountTransfer>>;
+
+    /// 总供应量、累计销毁的基础费用和持币排行，`limit` 默认为 100
+    #[rpc(name = "fairvm_supplyStats")]
+    fn supply_stats(&self, limit: Option<usize>) -> Result<SupplyStats>;
+
+    /// 指定高度的活跃验证人，未指定时为当前高度
+    #[rpc(name = "fairvm_validatorSet")]
+    fn validator_set(&self, height: Option<u64>) -> Result<Vec<ValidatorInfo>>;
+
+    #[rpc(name = "fairvm_latestPrice")]
+    fn latest_price(&self, pair: String) -> Result<Option<PriceRound>>;
+
+    #[rpc(name = "fairvm_priceRounds")]
+    fn price_rounds(&self, pair: String, count: usize) -> Result<Vec<PriceRound>>;
+
+    #[rpc(name = "fairvm_chainMetadata")]
+    fn chain_metadata(&self) -> Result<ChainMetadata>;
+
+    #[rpc(name = "fairvm_chainConfig")]
+    fn chain_config(&self) -> Result<ChainConfig>;
+
+    /// 登记合约源码和部署代码的源码映射，用于将出错位置映射回源码
+    #[rpc(name = "fairvm_verifySourceMap")]
+    fn verify_source_map(&self, address: H160, source: ContractSource) -> Result<bool>;
+
+    /// 查询合约中指令位置对应的源码位置
+    #[rpc(name = "fairvm_sourceLocation")]
+    fn source_location(&self, address: H160, pc: usize) -> Result<Option<SourceLocation>>;
+
+    /// 解码调用数据的函数选择器，返回候选签名
+    #[rpc(name = "fairvm_decodeCalldata")]
+    fn decode_calldata(&self, data: Bytes) -> Result<Option<DecodedSelector>>;
+
+    /// 时间戳在 `[from_timestamp, to_timestamp]` 内的区块范围
+    #[rpc(name = "fairvm_blockRangeByTime")]
+    fn block_range_by_time(
+        &self,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> Result<Option<BlockRange>>;
+
+    /// 按时间范围查询日志
+    #[rpc(name = "fairvm_getLogsByTime")]
+    fn logs_by_time(&self, filter: TimeLogFilter) -> Result<Vec<Log>>;
+
+    /// 从 `from_block` 开始的连续区块头，供轻节点同步
+    #[rpc(name = "fairvm_getHeaders")]
+    fn headers(&self, from_block: u64, count: u64) -> Result<Vec<BlockHeader>>;
+
+    /// 账户在当前状态下的存在或不存在证明
+    #[rpc(name = "fairvm_getStateProof")]
+    fn state_proof(&self, address: H160) -> Result<StateProof>;
+
+    /// 已打包交易及其收据在区块交易根和收据根中的包含证明，交易不存在时为 null
+    #[rpc(name = "fairvm_getInclusionProof")]
+    fn inclusion_proof(&self, tx_hash: H256) -> Result<Option<InclusionProof>>;
+
+    /// 将十六进制或 Avalanche bech32 地址转换为各种显示格式，`hrp` 默认为 `avax`
+    #[rpc(name = "fairvm_convertAddress")]
+    fn convert_address(&self, address: String, hrp: Option<String>) -> Result<AddressFormats>;
+
+    /// 按节点 ID 或奖励地址查询验证人的待领取和已领取奖励
+    #[rpc(name = "fairvm_validatorRewards")]
+    fn validator_rewards(&self, validator: String) -> Result<Option<ValidatorRewards>>;
+
+    /// 验证人的出块统计，`validator` 为节点 ID 或奖励地址，省略时返回全部验证人
+    #[rpc(name = "fairvm_validatorStats")]
+    fn validator_stats(&self, validator: Option<String>) -> Result<Vec<ValidatorStats>>;
+
+    /// 下一个区块的 gas 上限、治理目标值和允许范围，未配置时为 null
+    #[rpc(name = "fairvm_gasLimit")]
+    fn gas_limit(&self) -> Result<Option<GasLimitStatus>>;
+
+    /// 账户在 nonce 通道 `key` 上的下一个序号和完整 nonce，通道 0 即账户 nonce
+    #[rpc(name = "fairvm_getNonceSequence")]
+    fn nonce_sequence(&self, address: H160, key: u64) -> Result<NonceSequence>;
+
+    /// 最近发布的签名检查点，尚未发布时为 null
+    #[rpc(name = "fairvm_getCheckpoint")]
+    fn checkpoint(&self) -> Result<Option<Checkpoint>>;
+
+    /// 账户在检查点状态下的证明
+    #[rpc(name = "fairvm_getCheckpointProof")]
+    fn checkpoint_proof(&self, address: H160) -> Result<StateProof>;
+
+    /// 检查点状态中 `after` 之后的账户地址，按地址升序
+    #[rpc(name = "fairvm_getCheckpointAccounts")]
+    fn checkpoint_accounts(&self, after: Option<H160>, limit: usize) -> Result<Vec<H160>>;
+
+    /// 检查点状态中按代码哈希查询的代码
+    #[rpc(name = "fairvm_getCheckpointCode")]
+    fn checkpoint_code(&self, code_hash: H256) -> Result<Option<Bytes>>;
+
+    /// 检查点状态中存储槽的证明，对照账户证明中的存储根验证
+    #[rpc(name = "fairvm_getCheckpointStorageProof")]
+    fn checkpoint_storage_proof(&self, address: H160, key: H256) -> Result<StorageProof>;
+
+    /// 检查点状态中账户 `after` 之后的存储槽，按键升序
+    #[rpc(name = "fairvm_getCheckpointStorage")]
+    fn checkpoint_storage(
+        &self,
+        address: H160,
+        after: Option<H256>,
+        limit: usize,
+    ) -> Result<Vec<(H256, H256)>>;
+}
+
+/// FairVM 扩展接口处理器
+pub struct FairVmHandlers {
+    vm: Arc<RwLock<dyn VmExt>>,
+}
+
+impl FairVmHandlers {
+    pub fn new(vm: Arc<RwLock<dyn VmExt>>) -> Self {
+        Self { vm }
+    }
+
+    /// 最近发布的检查点状态，尚未发布时返回错误
+    fn checkpoint_snapshot(&self) -> Result<Arc<CheckpointSnapshot>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime
+            .block_on(async { vm.read().await.get_checkpoint().await })
+            .ok_or_else(|| {
+                let mut err = Error::internal_error();
+                err.message = "尚未发布检查点".to_string();
+                err
+            })
+    }
+}
+
+impl FairVmApi for FairVmHandlers {
+    fn fee_stats(&self, from_block: u64, to_block: u64) -> Result<FeeStatsSummary> {
+        if to_block < from_block {
+            return Err(Error::invalid_params("结束区块不能小于起始区块"));
+        }
+        if to_block - from_block >= MAX_FEE_STATS_RANGE {
+            return Err(Error::invalid_params(format!(
+                "区块范围不能超过 {}",
+                MAX_FEE_STATS_RANGE
+            )));
+        }
+
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let fee_stats = vm.get_fee_stats().await;
+            let tracker = fee_stats.read().await;
+            Ok(tracker.range(from_block, to_block))
+        })
+    }
+
+    fn gas_by_selector(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        limit: Option<usize>,
+    ) -> Result<GasBySelectorReport> {
+        if to_block < from_block {
+            return Err(Error::invalid_params("结束区块不能小于起始区块"));
+        }
+        if to_block - from_block >= MAX_FEE_STATS_RANGE {
+            return Err(Error::invalid_params(format!(
+                "区块范围不能超过 {}",
+                MAX_FEE_STATS_RANGE
+            )));
+        }
+
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let gas_stats = vm.get_gas_stats().await;
+            let mut report = gas_stats.read().await.range(from_block, to_block, limit);
+            report.annotate(&*vm.get_selectors().await.read().await);
+            Ok(report)
+        })
+    }
+
+    fn account_history(
+        &self,
+        address: H160,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<AccountTransfer>> {
+        if to_block < from_block {
+            return Err(Error::invalid_params("结束区块不能小于起始区块"));
+        }
+        if to_block - from_block >= MAX_HISTORY_RANGE {
+            return Err(Error::invalid_params(format!(
+                "区块范围不能超过 {}",
+                MAX_HISTORY_RANGE
+            )));
+        }
+
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let history = vm.get_account_history().await;
+            let transfers = history.read().await.range(&address, from_block, to_block);
+            Ok(transfers)
+        })
+    }
+
+    fn supply_stats(&self, limit: Option<usize>) -> Result<SupplyStats> {
+        let limit = limit.unwrap_or(DEFAULT_TOP_HOLDERS);
+        if limit > MAX_TOP_HOLDERS {
+            return Err(Error::invalid_params(format!(
+                "持币排行数量不能超过 {}",
+                MAX_TOP_HOLDERS
+            )));
+        }
+
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let supply = vm.get_supply_tracker().await;
+            let stats = supply.read().await.stats(limit);
+            Ok(stats)
+        })
+    }
+
+    fn validator_set(&self, height: Option<u64>) -> Result<Vec<ValidatorInfo>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            vm.validators_at(height)
+                .await
+                .map_err(|e| Error::invalid_params(e.to_string()))
+        })
+    }
+
+    fn latest_price(&self, pair: String) -> Result<Option<PriceRound>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm.get_price_rounds(&pair, 1).await.pop())
+        })
+    }
+
+    fn price_rounds(&self, pair: String, count: usize) -> Result<Vec<PriceRound>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm
+                .get_price_rounds(&pair, count.min(MAX_PRICE_ROUNDS))
+                .await)
+        })
+    }
+
+    fn chain_metadata(&self) -> Result<ChainMetadata> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm.get_chain_metadata().await)
+        })
+    }
+
+    fn chain_config(&self) -> Result<ChainConfig> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm.get_chain_config().await)
+        })
+    }
+
+    fn verify_source_map(&self, address: H160, source: ContractSource) -> Result<bool> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let code = vm.get_code(&address).await?;
+            let source_maps = vm.get_source_maps().await;
+            let mut source_maps = source_maps.write().await;
+            source_maps
+                .register(address.into(), &code, source)
+                .map_err(|e| Error::invalid_params(e.to_string()))?;
+            Ok(true)
+        })
+    }
+
+    fn source_location(&self, address: H160, pc: usize) -> Result<Option<SourceLocation>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let source_maps = vm.get_source_maps().await;
+            let source_maps = source_maps.read().await;
+            Ok(source_maps.locate(&address.into(), pc))
+        })
+    }
+
+    fn decode_calldata(&self, data: Bytes) -> Result<Option<DecodedSelector>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let selectors = vm.get_selectors().await;
+            Ok(selectors::decode(&selectors, &data).await)
+        })
+    }
+
+    fn block_range_by_time(
+        &self,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> Result<Option<BlockRange>> {
+        if to_timestamp < from_timestamp {
+            return Err(Error::invalid_params("结束时间不能早于起始时间"));
+        }
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let blockchain = vm.get_blockchain().await;
+            let blockchain = blockchain.read().await;
+            Ok(blockchain
+                .block_range_by_time(from_timestamp, to_timestamp)
+                .map(|(from_block, to_block)| BlockRange {
+                    from_block,
+                    to_block,
+                }))
+        })
+    }
+
+    fn logs_by_time(&self, filter: TimeLogFilter) -> Result<Vec<Log>> {
+        let Some(range) = self.block_range_by_time(filter.from_timestamp, filter.to_timestamp)?
+        else {
+            return Ok(Vec::new());
+        };
+        if range.to_block - range.from_block >= MAX_LOG_BLOCK_RANGE {
+            return Err(Error::invalid_params(format!(
+                "时间范围覆盖的区块不能超过 {} 个",
+                MAX_LOG_BLOCK_RANGE
+            )));
+        }
+
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(filter_logs(
+                &*vm,
+                range.from_block,
+                range.to_block,
+                &filter.addresses,
+                &filter.topics,
+            )
+            .await)
+        })
+    }
+
+    fn headers(&self, from_block: u64, count: u64) -> Result<Vec<BlockHeader>> {
+        let count = count.min(MAX_HEADERS);
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let blockchain = vm.get_blockchain().await;
+            let blockchain = blockchain.read().await;
+            Ok((from_block..from_block.saturating_add(count))
+                .map_while(|number| blockchain.get_block(number))
+                .map(|block| block.header.clone())
+                .collect())
+        })
+    }
+
+    fn state_proof(&self, address: H160) -> Result<StateProof> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let storage = vm.get_state().await.read().await.storage().clone();
+            let commitment = StateCommitment::from_storage(&storage).await;
+            Ok(commitment.prove(&address.into()))
+        })
+    }
+
+    fn inclusion_proof(&self, tx_hash: H256) -> Result<Option<InclusionProof>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            Ok(vm.get_inclusion_proof(&tx_hash).await)
+        })
+    }
+
+    fn convert_address(&self, address: String, hrp: Option<String>) -> Result<AddressFormats> {
+        let parsed =
+            avax_address::parse_any(&address).map_err(|e| Error::invalid_params(e.to_string()))?;
+        let hrp = match hrp {
+            Some(hrp) => hrp,
+            // 未指定前缀时沿用 bech32 输入的前缀
+            None => avax_address::AvaxAddress::parse(address.trim())
+                .map(|address| address.hrp)
+                .unwrap_or_else(|_| avax_address::MAINNET_HRP.to_string()),
+        };
+        AddressFormats::new(&parsed, &hrp).map_err(|e| Error::invalid_params(e.to_string()))
+    }
+
+    fn validator_rewards(&self, validator: String) -> Result<Option<ValidatorRewards>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let config = match vm.get_staking_config().await {
+                Some(config) => config,
+                None => return Ok(None),
+            };
+            let stake = match config.find(validator.trim()) {
+                Some(stake) => stake,
+                None => return Ok(None),
+            };
+            let storage = vm.get_state().await.read().await.storage().clone();
+            Ok(Some(ValidatorRewards {
+                node_id: stake.node_id.clone(),
+                reward_address: stake.reward_address,
+                stake: stake.stake,
+                pending: staking::pending_of(&storage, &stake.reward_address).await,
+                claimed: staking::claimed_of(&storage, &stake.reward_address).await,
+            }))
+        })
+    }
+
+    fn validator_stats(&self, validator: Option<String>) -> Result<Vec<ValidatorStats>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let vm = vm.read().await;
+            let config = match vm.get_staking_config().await {
+                Some(config) => config,
+                None => return Ok(Vec::new()),
+            };
+            let node_ids: Vec<String> = match validator {
+                Some(validator) => config
+                    .find(validator.trim())
+                    .map(|stake| stake.node_id.clone())
+                    .into_iter()
+                    .collect(),
+                None => config
+                    .validators
+                    .iter()
+                    .map(|v| v.node_id.clone())
+                    .collect(),
+            };
+            let storage = vm.get_state().await.read().await.storage().clone();
+            let mut stats = Vec::with_capacity(node_ids.len());
+            for node_id in &node_ids {
+                stats.push(uptime::stats_of(&storage, node_id).await);
+            }
+            Ok(stats)
+        })
+    }
+
+    fn gas_limit(&self) -> Result<Option<GasLimitStatus>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async { Ok(vm.read().await.get_gas_limit_status().await) })
+    }
+
+    fn nonce_sequence(&self, address: H160, key: u64) -> Result<NonceSequence> {
+        if key > nonce::MAX_KEY {
+            return Err(Error::invalid_params(
+                nonce::NonceError::KeyOutOfRange(key).to_string(),
+            ));
+        }
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            Ok(vm
+                .read()
+                .await
+                .get_nonce_sequence(&address.into(), key)
+                .await)
+        })
+    }
+
+    fn checkpoint(&self) -> Result<Option<Checkpoint>> {
+        let vm = self.vm.clone();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let snapshot = vm.read().await.get_checkpoint().await;
+            Ok(snapshot.map(|snapshot| snapshot.checkpoint().clone()))
+        })
+    }
+
+    fn checkpoint_proof(&self, address: H160) -> Result<StateProof> {
+        Ok(self.checkpoint_snapshot()?.prove(&address.into()))
+    }
+
+    fn checkpoint_accounts(&self, after: Option<H160>, limit: usize) -> Result<Vec<H160>> {
+        let snapshot = self.checkpoint_snapshot()?;
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let addresses = snapshot
+                .accounts_after(after.map(Into::into), limit)
+                .await
+                .map_err(|e| Error::invalid_params(e.to_string()))?;
+            Ok(addresses.into_iter().map(H160::from).collect())
+        })
+    }
+
+    fn checkpoint_code(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        let snapshot = self.checkpoint_snapshot()?;
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let code = snapshot.storage().get_code_by_hash(&code_hash).await;
+            Ok(code.map(Bytes::from))
+        })
+    }
+
+    fn checkpoint_storage_proof(&self, address: H160, key: H256) -> Result<StorageProof> {
+        let snapshot = self.checkpoint_snapshot()?;
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            snapshot
+                .storage_proof(&address.into(), key.0)
+                .await
+                .map_err(|e| Error::invalid_params(e.to_string()))
+        })
+    }
+
+    fn checkpoint_storage(
+        &self,
+        address: H160,
+        after: Option<H256>,
+        limit: usize,
+    ) -> Result<Vec<(H256, H256)>> {
+        let snapshot = self.checkpoint_snapshot()?;
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let entries = snapshot
+                .storage_page(&address.into(), after.map(|key| key.0), limit)
+                .await
+                .map_err(|e| Error::invalid_params(e.to_string()))?;
+            Ok(entries
+                .into_iter()
+                .map(|(key, value)| (H256(key), H256(value)))
+                .collect())
+        })
+    }
+}
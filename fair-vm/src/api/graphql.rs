@@ -0,0 +1,558 @@
+//! GraphQL 查询接口
+//!
+//! 尽量沿用 geth GraphQL 的字段命名：区块、交易、账户与日志之间可以嵌套查询，
+//! 数值类型以十六进制字符串返回，区块列表查询支持分页。
+
+use crate::account::Address as AccountAddress;
+use crate::api::{filter_logs, VmExt};
+use crate::blockchain::{Block, Blockchain};
+use crate::transaction::Transaction;
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Error, InputObject, Object, Result, Schema,
+};
+use async_graphql_axum::GraphQL;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::Router;
+use ethers::types::{H160, H256};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// GraphQL 访问路径
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+/// 单次区块列表查询返回的最大区块数
+pub const MAX_BLOCKS_PER_PAGE: u64 = 100;
+
+/// 单次日志查询允许的最大区块范围
+pub const MAX_LOG_BLOCK_RANGE: u64 = 1024;
+
+/// 最大查询嵌套深度
+pub const MAX_QUERY_DEPTH: usize = 16;
+
+/// FairVM GraphQL Schema
+pub type FairVmSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+type SharedVm = Arc<RwLock<dyn VmExt>>;
+
+/// 构建 GraphQL Schema
+pub fn build_schema(vm: SharedVm) -> FairVmSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(vm)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish()
+}
+
+/// GraphQL 路由，POST 执行查询，GET 返回 GraphiQL 页面
+pub fn router(schema: FairVmSchema) -> Router {
+    Router::new().route(
+        GRAPHQL_PATH,
+        get(graphiql).post_service(GraphQL::new(schema)),
+    )
+}
+
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint(GRAPHQL_PATH).finish())
+}
+
+fn parse_hash(hash: &str) -> Result<H256> {
+    let bytes = hex::decode(hash.trim_start_matches("0x"))?;
+    if bytes.len() != 32 {
+        return Err(Error::new("哈希长度必须为 32 字节"));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+fn parse_address(address: &str) -> Result<H160> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))?;
+    if bytes.len() != 20 {
+        return Err(Error::new("地址长度必须为 20 字节"));
+    }
+    Ok(H160::from_slice(&bytes))
+}
+
+/// 在区块链上执行只读查询
+async fn with_blockchain<T>(ctx: &Context<'_>, f: impl FnOnce(&Blockchain) -> T) -> Result<T> {
+    let vm = ctx.data::<SharedVm>()?.read().await;
+    let blockchain = vm.get_blockchain().await;
+    let blockchain = blockchain.read().await;
+    Ok(f(&blockchain))
+}
+
+/// 查询根
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// 按高度或哈希查询区块，均未指定时返回最新区块
+    async fn block(
+        &self,
+        ctx: &Context<'_>,
+        number: Option<u64>,
+        hash: Option<String>,
+    ) -> Result<Option<BlockNode>> {
+        let hash = hash.as_deref().map(parse_hash).transpose()?;
+        with_blockchain(ctx, |chain| {
+            let block = match (number, hash) {
+                (_, Some(hash)) => chain.get_block_by_hash(&hash),
+                (Some(number), None) => chain.get_block(number),
+                (None, None) => chain.latest_block(),
+            };
+            block.cloned().map(BlockNode)
+        })
+        .await
+    }
+
+    /// 查询区块区间，`to` 为空时查询到最新区块，每页最多返回 `MAX_BLOCKS_PER_PAGE` 个
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        from: u64,
+        to: Option<u64>,
+        first: Option<u64>,
+    ) -> Result<Vec<BlockNode>> {
+        let limit = first
+            .unwrap_or(MAX_BLOCKS_PER_PAGE)
+            .min(MAX_BLOCKS_PER_PAGE) as usize;
+        with_blockchain(ctx, |chain| {
+            chain
+                .blocks()
+                .iter()
+                .filter(|block| block.header.number >= from)
+                .filter(|block| to.map_or(true, |to| block.header.number <= to))
+                .take(limit)
+                .cloned()
+                .map(BlockNode)
+                .collect()
+        })
+        .await
+    }
+
+    /// 按哈希查询交易
+    async fn transaction(
+        &self,
+        ctx: &Context<'_>,
+        hash: String,
+    ) -> Result<Option<TransactionNode>> {
+        let hash = parse_hash(&hash)?;
+        with_blockchain(ctx, |chain| {
+            chain
+                .find_transaction(&hash)
+                .map(|(block, index)| TransactionNode::in_block(block, index))
+        })
+        .await
+    }
+
+    /// 查询账户
+    async fn account(&self, address: String) -> Result<AccountNode> {
+        Ok(AccountNode(parse_address(&address)?))
+    }
+
+    /// 按过滤条件查询日志
+    async fn logs(&self, ctx: &Context<'_>, filter: FilterCriteria) -> Result<Vec<LogNode>> {
+        let (from, to) = if filter.from_timestamp.is_some() || filter.to_timestamp.is_some() {
+            if filter.from_block.is_some() || filter.to_block.is_some() {
+                return Err(Error::new("不能同时按区块和时间过滤"));
+            }
+            let from = filter.from_timestamp.unwrap_or(0);
+            let to = filter.to_timestamp.unwrap_or(u64::MAX);
+            match with_blockchain(ctx, |chain| chain.block_range_by_time(from, to)).await? {
+                Some(range) => range,
+                None => return Ok(Vec::new()),
+            }
+        } else {
+            let latest = with_blockchain(ctx, |chain| {
+                chain.latest_block().map(|b| b.header.number).unwrap_or(0)
+            })
+            .await?;
+            (
+                filter.from_block.unwrap_or(latest),
+                filter.to_block.unwrap_or(latest),
+            )
+        };
+        if to < from {
+            return Err(Error::new("结束区块不能小于起始区块"));
+        }
+        if to - from >= MAX_LOG_BLOCK_RANGE {
+            return Err(Error::new(format!(
+                "日志查询范围不能超过 {} 个区块",
+                MAX_LOG_BLOCK_RANGE
+            )));
+        }
+
+        let addresses = filter
+            .addresses
+            .unwrap_or_default()
+            .iter()
+            .map(|address| parse_address(address))
+            .collect::<Result<Vec<_>>>()?;
+        let topics = filter
+            .topics
+            .unwrap_or_default()
+            .iter()
+            .map(|position| position.iter().map(|t| parse_hash(t)).collect())
+            .collect::<Result<Vec<Vec<_>>>>()?;
+
+        let vm = ctx.data::<SharedVm>()?.read().await;
+        Ok(filter_logs(&*vm, from, to, &addresses, &topics)
+            .await
+            .into_iter()
+            .map(LogNode)
+            .collect())
+    }
+}
+
+/// 日志过滤条件
+#[derive(InputObject)]
+pub struct FilterCriteria {
+    /// 起始区块，默认最新区块
+    pub from_block: Option<u64>,
+    /// 结束区块，默认最新区块
+    pub to_block: Option<u64>,
+    /// 起始时间戳（秒），与区块范围互斥
+    pub from_timestamp: Option<u64>,
+    /// 结束时间戳（秒），与区块范围互斥
+    pub to_timestamp: Option<u64>,
+    /// 合约地址
+    pub addresses: Option<Vec<String>>,
+    /// 按位置匹配的主题，每个位置内为“或”关系，空列表表示任意
+    pub topics: Option<Vec<Vec<String>>>,
+}
+
+pub(crate) fn log_matches(
+    log: &ethers::types::Log,
+    addresses: &[H160],
+    topics: &[Vec<H256>],
+) -> bool {
+    if !addresses.is_empty() && !addresses.contains(&log.address) {
+        return false;
+    }
+    topics.iter().enumerate().all(|(i, allowed)| {
+        allowed.is_empty()
+            || log
+                .topics
+                .get(i)
+                .is_some_and(|topic| allowed.contains(topic))
+    })
+}
+
+async fn receipt(
+    ctx: &Context<'_>,
+    tx_hash: &H256,
+) -> Result<Option<ethers::types::TransactionReceipt>> {
+    let vm = ctx.data::<SharedVm>()?.read().await;
+    Ok(vm
+        .get_transaction_receipt(tx_hash.as_bytes())
+        .await
+        .map(|receipt| ethers::types::TransactionReceipt::from(&receipt)))
+}
+
+async fn receipt_logs(ctx: &Context<'_>, tx_hash: &H256) -> Result<Vec<ethers::types::Log>> {
+    Ok(receipt(ctx, tx_hash)
+        .await?
+        .map(|receipt| receipt.logs)
+        .unwrap_or_default())
+}
+
+/// 区块
+pub struct BlockNode(Block);
+
+#[Object(name = "Block")]
+impl BlockNode {
+    async fn number(&self) -> u64 {
+        self.0.header.number
+    }
+
+    async fn hash(&self) -> String {
+        format!("{:?}", self.0.hash())
+    }
+
+    /// 父区块
+    async fn parent(&self, ctx: &Context<'_>) -> Result<Option<BlockNode>> {
+        let parent_hash = self.0.header.parent_hash;
+        with_blockchain(ctx, |chain| {
+            chain
+                .get_block_by_hash(&parent_hash)
+                .cloned()
+                .map(BlockNode)
+        })
+        .await
+    }
+
+    async fn timestamp(&self) -> u64 {
+        self.0.header.timestamp
+    }
+
+    /// 出块验证人的节点 ID
+    async fn proposer(&self) -> Option<String> {
+        self.0.header.proposer.clone()
+    }
+
+    /// 本节点接受区块的时间（Unix 秒）
+    async fn accepted_at(&self) -> Option<u64> {
+        self.0
+            .acceptance
+            .as_ref()
+            .map(|acceptance| acceptance.accepted_at)
+    }
+
+    /// 区块生效的 gas 上限
+    async fn gas_limit(&self) -> Option<u64> {
+        self.0
+            .acceptance
+            .as_ref()
+            .and_then(|acceptance| acceptance.gas_limit)
+    }
+
+    async fn transactions_root(&self) -> String {
+        format!("{:?}", self.0.header.transactions_root)
+    }
+
+    async fn state_root(&self) -> String {
+        format!("{:?}", self.0.header.state_root)
+    }
+
+    async fn transaction_count(&self) -> u64 {
+        self.0.transactions.len() as u64
+    }
+
+    async fn transactions(&self) -> Vec<TransactionNode> {
+        (0..self.0.transactions.len())
+            .map(|index| TransactionNode::in_block(&self.0, index))
+            .collect()
+    }
+
+    async fn transaction_at(&self, index: u64) -> Option<TransactionNode> {
+        let index = usize::try_from(index).ok()?;
+        (index < self.0.transactions.len()).then(|| TransactionNode::in_block(&self.0, index))
+    }
+
+    /// 区块内所有交易的日志
+    async fn logs(&self, ctx: &Context<'_>) -> Result<Vec<LogNode>> {
+        let mut logs = Vec::new();
+        for tx in &self.0.transactions {
+            logs.extend(receipt_logs(ctx, &tx.hash).await?.into_iter().map(LogNode));
+        }
+        Ok(logs)
+    }
+}
+
+/// 交易
+pub struct TransactionNode {
+    tx: Transaction,
+    block_number: u64,
+    index: u64,
+}
+
+impl TransactionNode {
+    fn in_block(block: &Block, index: usize) -> Self {
+        Self {
+            tx: block.transactions[index].clone(),
+            block_number: block.header.number,
+            index: index as u64,
+        }
+    }
+}
+
+#[Object(name = "Transaction")]
+impl TransactionNode {
+    async fn hash(&self) -> String {
+        format!("{:?}", self.tx.hash)
+    }
+
+    async fn nonce(&self) -> u64 {
+        self.tx.nonce
+    }
+
+    async fn index(&self) -> u64 {
+        self.index
+    }
+
+    async fn from(&self) -> AccountNode {
+        AccountNode(H160(self.tx.from.0))
+    }
+
+    async fn to(&self) -> Option<AccountNode> {
+        self.tx.to.map(|to| AccountNode(H160(to.0)))
+    }
+
+    async fn value(&self) -> String {
+        format!("0x{:x}", self.tx.value)
+    }
+
+    async fn gas(&self) -> u64 {
+        self.tx.gas_limit
+    }
+
+    async fn gas_price(&self) -> Option<String> {
+        self.tx.gas_price.map(|price| format!("0x{:x}", price))
+    }
+
+    async fn max_fee_per_gas(&self) -> Option<String> {
+        self.tx.max_fee_per_gas.map(|fee| format!("0x{:x}", fee))
+    }
+
+    async fn max_priority_fee_per_gas(&self) -> Option<String> {
+        self.tx
+            .max_priority_fee_per_gas
+            .map(|fee| format!("0x{:x}", fee))
+    }
+
+    async fn input_data(&self) -> String {
+        format!("0x{}", hex::encode(&self.tx.data))
+    }
+
+    /// 调用的函数签名，合约创建或选择器未知时为空
+    async fn method(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        if self.tx.to.is_none() {
+            return Ok(None);
+        }
+        let vm = ctx.data::<SharedVm>()?.read().await;
+        let selectors = vm.get_selectors().await;
+        Ok(crate::evm::selectors::decode(&selectors, &self.tx.data)
+            .await
+            .and_then(|decoded| decoded.signatures.into_iter().next()))
+    }
+
+    /// 所在区块
+    async fn block(&self, ctx: &Context<'_>) -> Result<Option<BlockNode>> {
+        let number = self.block_number;
+        with_blockchain(ctx, |chain| chain.get_block(number).cloned().map(BlockNode)).await
+    }
+
+    /// 执行状态，1 为成功
+    async fn status(&self, ctx: &Context<'_>) -> Result<Option<u64>> {
+        Ok(receipt(ctx, &self.tx.hash)
+            .await?
+            .and_then(|receipt| receipt.status)
+            .map(|status| status.as_u64()))
+    }
+
+    async fn gas_used(&self, ctx: &Context<'_>) -> Result<Option<u64>> {
+        Ok(receipt(ctx, &self.tx.hash)
+            .await?
+            .and_then(|receipt| receipt.gas_used)
+            .map(|gas| gas.as_u64()))
+    }
+
+    async fn logs(&self, ctx: &Context<'_>) -> Result<Vec<LogNode>> {
+        Ok(receipt_logs(ctx, &self.tx.hash)
+            .await?
+            .into_iter()
+            .map(LogNode)
+            .collect())
+    }
+}
+
+/// 账户
+pub struct AccountNode(H160);
+
+#[Object(name = "Account")]
+impl AccountNode {
+    async fn address(&self) -> String {
+        format!("{:?}", self.0)
+    }
+
+    async fn balance(&self, ctx: &Context<'_>) -> Result<String> {
+        let vm = ctx.data::<SharedVm>()?.read().await;
+        let state = vm.get_state().await;
+        let balance = state
+            .read()
+            .await
+            .get_balance(&AccountAddress(self.0 .0))
+            .await;
+        Ok(format!("0x{:x}", balance))
+    }
+
+    async fn transaction_count(&self, ctx: &Context<'_>) -> Result<u64> {
+        let vm = ctx.data::<SharedVm>()?.read().await;
+        let state = vm.get_state().await;
+        let nonce = state
+            .read()
+            .await
+            .get_nonce(&AccountAddress(self.0 .0))
+            .await;
+        Ok(nonce)
+    }
+
+    async fn code(&self, ctx: &Context<'_>) -> Result<String> {
+        let vm = ctx.data::<SharedVm>()?.read().await;
+        let code = vm
+            .get_code(&self.0)
+            .await
+            .map_err(|e| Error::new(e.message))?;
+        Ok(format!("0x{}", hex::encode(code)))
+    }
+
+    async fn storage(&self, ctx: &Context<'_>, slot: String) -> Result<String> {
+        let slot = parse_hash(&slot)?;
+        let vm = ctx.data::<SharedVm>()?.read().await;
+        let value = vm
+            .get_storage(&self.0, &slot)
+            .await
+            .map_err(|e| Error::new(e.message))?;
+        Ok(format!("{:?}", value))
+    }
+}
+
+/// 日志
+pub struct LogNode(ethers::types::Log);
+
+#[Object(name = "Log")]
+impl LogNode {
+    async fn index(&self) -> Option<u64> {
+        self.0.log_index.map(|index| index.as_u64())
+    }
+
+    async fn account(&self) -> AccountNode {
+        AccountNode(self.0.address)
+    }
+
+    async fn topics(&self) -> Vec<String> {
+        self.0.topics.iter().map(|t| format!("{:?}", t)).collect()
+    }
+
+    async fn data(&self) -> String {
+        format!("0x{}", hex::encode(&self.0.data))
+    }
+
+    async fn transaction_hash(&self) -> Option<String> {
+        self.0.transaction_hash.map(|hash| format!("{:?}", hash))
+    }
+
+    async fn block_number(&self) -> Option<u64> {
+        self.0.block_number.map(|number| number.as_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_matches() {
+        let address = H160::from_low_u64_be(1);
+        let topic = H256::from_low_u64_be(7);
+        let log = ethers::types::Log {
+            address,
+            topics: vec![topic],
+            ..Default::default()
+        };
+
+        assert!(log_matches(&log, &[], &[]));
+        assert!(log_matches(&log, &[address], &[vec![topic]]));
+        assert!(log_matches(&log, &[], &[vec![]]));
+        assert!(!log_matches(&log, &[H160::zero()], &[]));
+        assert!(!log_matches(&log, &[], &[vec![], vec![topic]]));
+    }
+
+    #[test]
+    fn test_schema_sdl() {
+        let sdl = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .finish()
+            .sdl();
+        assert!(sdl.contains("type Block"));
+        assert!(sdl.contains("input FilterCriteria"));
+    }
+}
@@ -0,0 +1,103 @@
+//! HTTP JSON-RPC 传输
+//!
+//! 请求体为单个或批量 JSON-RPC 请求。客户端可在 [`CORRELATION_ID_HEADER`] 头中携带关联 ID，
+//! 节点把它和方法名、耗时一起写入日志，并在响应头中原样返回，便于跨服务排查同一次调用。
+
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, HeaderName, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use jsonrpc_core::IoHandler;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// 关联 ID 请求头
+pub const CORRELATION_ID_HEADER: &str = "x-fairvm-correlation-id";
+
+/// 在 `/` 上接收 JSON-RPC 请求的路由
+pub fn router(io: IoHandler) -> Router {
+    Router::new()
+        .route("/", post(handle))
+        .with_state(Arc::new(io))
+}
+
+/// 请求中的方法名，批量请求以逗号连接，无法解析时为 `-`
+fn request_methods(body: &str) -> String {
+    let method = |value: &serde_json::Value| {
+        value["method"]
+            .as_str()
+            .map_or_else(|| "-".to_string(), str::to_string)
+    };
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Array(requests)) => {
+            requests.iter().map(method).collect::<Vec<_>>().join(",")
+        }
+        Ok(request) => method(&request),
+        Err(_) => "-".to_string(),
+    }
+}
+
+async fn handle(State(io): State<Arc<IoHandler>>, headers: HeaderMap, body: String) -> Response {
+    let correlation_id = headers.get(CORRELATION_ID_HEADER).cloned();
+    let methods = request_methods(&body);
+    let started = Instant::now();
+    // 处理器内部会创建自己的运行时，需在阻塞线程池中执行
+    let response = tokio::task::spawn_blocking(move || io.handle_request_sync(&body))
+        .await
+        .ok()
+        .flatten();
+    let elapsed = started.elapsed();
+    match correlation_id.as_ref().and_then(|id| id.to_str().ok()) {
+        Some(id) => log::info!("RPC {} [关联 ID {}] 耗时 {:?}", methods, id, elapsed),
+        None => log::debug!("RPC {} 耗时 {:?}", methods, elapsed),
+    }
+
+    let mut response = match response {
+        Some(body) => ([(CONTENT_TYPE, "application/json")], body).into_response(),
+        // 只有通知的请求没有响应
+        None => StatusCode::NO_CONTENT.into_response(),
+    };
+    if let Some(id) = correlation_id {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(CORRELATION_ID_HEADER), id);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use serde_json::Value;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_correlation_id_echo() {
+        let mut io = IoHandler::new();
+        io.add_sync_method("test_echo", |params: jsonrpc_core::Params| {
+            Ok(params.parse::<Value>().unwrap_or(Value::Null))
+        });
+        let io = Arc::new(io);
+        let mut headers = HeaderMap::new();
+        headers.insert(CORRELATION_ID_HEADER, HeaderValue::from_static("req-42"));
+
+        let body = r#"{"jsonrpc":"2.0","method":"test_echo","params":[1],"id":1}"#;
+        let response = handle(State(io.clone()), headers, body.to_string()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CORRELATION_ID_HEADER).unwrap(),
+            "req-42"
+        );
+
+        let response = handle(State(io), HeaderMap::new(), body.to_string()).await;
+        assert!(response.headers().get(CORRELATION_ID_HEADER).is_none());
+
+        assert_eq!(
+            request_methods(r#"[{"method":"eth_chainId"},{"method":"net_version"}]"#),
+            "eth_chainId,net_version"
+        );
+        assert_eq!(request_methods("{"), "-");
+    }
+}
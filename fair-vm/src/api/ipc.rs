@@ -0,0 +1,188 @@
+//! IPC（Unix 域套接字）JSON-RPC 传输
+//!
+//! 与 geth 相同，连接上的请求是连续的 JSON 值，不要求分隔符，每个响应后追加换行。
+//! 套接字文件只允许节点所属用户访问，供同一主机上的命令行等工具使用。
+
+use jsonrpc_core::IoHandler;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinHandle;
+
+/// 单个连接上未完成请求的最大字节数
+pub const MAX_IPC_REQUEST_SIZE: usize = 5 * 1024 * 1024;
+
+/// 无法解析请求时返回的错误响应
+const PARSE_ERROR: &str =
+    r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+
+/// 从缓冲区中拆出完整的请求，返回请求和已消费的字节数，末尾不完整的请求留待后续数据
+pub(crate) fn split_requests(buf: &[u8]) -> Result<(Vec<String>, usize), serde_json::Error> {
+    let mut stream = serde_json::Deserializer::from_slice(buf).into_iter::<serde_json::Value>();
+    let mut requests = Vec::new();
+    let mut consumed = 0;
+    loop {
+        match stream.next() {
+            Some(Ok(value)) => {
+                requests.push(value.to_string());
+                consumed = stream.byte_offset();
+            }
+            Some(Err(e)) if e.is_eof() => break,
+            Some(Err(e)) => return Err(e),
+            None => {
+                consumed = buf.len();
+                break;
+            }
+        }
+    }
+    Ok((requests, consumed))
+}
+
+/// 处理单个连接，按收到的顺序依次响应
+async fn serve_connection(stream: UnixStream, io: Arc<IoHandler>) -> io::Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..read]);
+
+        let (requests, consumed) = match split_requests(&buf) {
+            Ok(split) => split,
+            Err(_) => {
+                writer.write_all(PARSE_ERROR.as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+                return Ok(());
+            }
+        };
+        buf.drain(..consumed);
+        if buf.len() > MAX_IPC_REQUEST_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "IPC 请求过大"));
+        }
+
+        for request in requests {
+            // 处理器内部会创建自己的运行时，需在阻塞线程池中执行
+            let io = io.clone();
+            let response = tokio::task::spawn_blocking(move || io.handle_request_sync(&request))
+                .await
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            if let Some(response) = response {
+                writer.write_all(response.as_bytes()).await?;
+                writer.write_all(b"\n").await?;
+            }
+        }
+    }
+}
+
+/// 绑定套接字，清理上次异常退出遗留的套接字文件，已有节点在监听时报错
+async fn bind(path: &Path) -> io::Result<UnixListener> {
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("IPC 套接字 {} 正在使用", path.display()),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// IPC 服务端，释放时停止监听并删除套接字文件
+#[derive(Debug)]
+pub struct IpcServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl IpcServer {
+    /// 在 `path` 上启动 IPC 服务
+    pub async fn start(path: impl Into<PathBuf>, io: IoHandler) -> io::Result<Self> {
+        let path = path.into();
+        let listener = bind(&path).await?;
+        let io = Arc::new(io);
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let io = io.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = serve_connection(stream, io).await {
+                                log::debug!("IPC 连接中断: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => log::warn!("接受 IPC 连接失败: {}", e),
+                }
+            }
+        });
+        log::info!("IPC 服务已启动: {}", path.display());
+        Ok(Self { path, task })
+    }
+
+    /// 套接字路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::Value;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    #[test]
+    fn test_split_requests() {
+        let (requests, consumed) = split_requests(br#"{"id":1} {"id":2}{"id""#).unwrap();
+        assert_eq!(requests, vec![r#"{"id":1}"#, r#"{"id":2}"#]);
+        assert_eq!(consumed, 17);
+        assert!(split_requests(b"{]").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ipc_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fairvm.ipc");
+        let mut io = IoHandler::new();
+        io.add_sync_method("test_echo", |params: jsonrpc_core::Params| {
+            Ok(params.parse::<Value>().unwrap_or(Value::Null))
+        });
+        let server = IpcServer::start(&path, io.clone()).await.unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(IpcServer::start(&path, io).await.is_err());
+
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(br#"{"jsonrpc":"2.0","method":"test_echo","params":[1],"id":1}{"jsonrpc":"2.0","method":"test_echo","params":[2],"id":2}"#)
+            .await
+            .unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let first = lines.next_line().await.unwrap().unwrap();
+        let second = lines.next_line().await.unwrap().unwrap();
+        assert!(first.contains(r#""result":[1]"#));
+        assert!(second.contains(r#""result":[2]"#));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
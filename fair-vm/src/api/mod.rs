@@ -1,22 +1,74 @@
+pub mod admin_handlers;
+pub mod cache;
 pub mod chain_handlers;
+pub mod debug_handlers;
+pub mod fairvm_handlers;
+pub mod graphql;
+pub mod http;
+#[cfg(unix)]
+pub mod ipc;
+pub mod rest;
 pub mod static_handlers;
 pub mod wallet_handlers;
 
 use crate::account::Address as AccountAddress;
+use crate::blockchain::Blockchain;
+use crate::chain_metadata::ChainMetadata;
 use crate::consensus::ConsensusEngineTrait;
+use crate::fee_stats::FeeStatsTracker;
+use crate::gas_stats::GasStatsTracker;
+use crate::oracle::PriceRound;
 use crate::state::State;
-use crate::storage::Storage;
+use crate::storage::StateHandle;
 use crate::transaction::{Transaction as LocalTransaction, TransactionType};
+use admin_handlers::AdminApi;
 use async_trait::async_trait;
+use chain_handlers::ChainApi;
+use debug_handlers::DebugApi;
 use ethers::types::{H160, H256, U256};
+use fair_vm_core::config::{method_namespace, RpcCacheConfig};
+use fair_vm_core::params::ChainConfig;
 use fair_vm_core::types::{
     Address as CoreAddress, Hash as CoreHash, Transaction as CoreTransaction,
 };
 use fair_vm_core::vm::Vm;
-use jsonrpc_core::Error;
+use fairvm_handlers::FairVmApi;
+use jsonrpc_core::{Error, IoHandler, RemoteProcedure};
 use serde_json;
+use static_handlers::StaticApi;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use wallet_handlers::WalletApi;
+
+/// 查询 `[from, to]` 范围内匹配的日志，先用布隆索引筛出候选区块再读取收据
+pub(crate) async fn filter_logs(
+    vm: &dyn VmExt,
+    from: u64,
+    to: u64,
+    addresses: &[H160],
+    topics: &[Vec<H256>],
+) -> Vec<ethers::types::Log> {
+    let candidates = vm
+        .get_log_index()
+        .await
+        .read()
+        .await
+        .candidate_blocks(from, to, addresses, topics);
+    let mut logs = Vec::new();
+    for number in candidates {
+        for receipt in vm.get_block_receipts(number).await {
+            let receipt = ethers::types::TransactionReceipt::from(&receipt);
+            logs.extend(
+                receipt
+                    .logs
+                    .into_iter()
+                    .filter(|log| graphql::log_matches(log, addresses, topics)),
+            );
+        }
+    }
+    logs
+}
 
 /// 将核心交易转换为本地交易
 pub fn convert_transaction(tx: &CoreTransaction) -> LocalTransaction {
@@ -61,8 +113,8 @@ pub fn convert_to_core_transaction(tx: &LocalTransaction) -> CoreTransaction {
 pub trait VmExt: Vm + Send + Sync {
     /// 获取状态
     async fn get_state(&self) -> Arc<RwLock<State>>;
-    /// 获取存储 (返回 Arc<RwLock<Box<dyn Storage + Send + Sync>>>)
-    async fn get_storage_arc(&self) -> Arc<RwLock<Box<dyn Storage + Send + Sync>>>;
+    /// 获取状态服务句柄
+    async fn get_storage_handle(&self) -> StateHandle;
     /// 获取共识引擎
     async fn get_consensus(&self) -> Option<Arc<RwLock<dyn ConsensusEngineTrait + Send + Sync>>>;
     /// 获取账户信息
@@ -76,10 +128,16 @@ pub trait VmExt: Vm + Send + Sync {
         address: &crate::account::Address,
     ) -> Vec<crate::transaction::Transaction>;
     /// 获取交易收据
-    async fn get_transaction_receipt(
+    async fn get_transaction_receipt(&self, tx_hash: &[u8]) -> Option<crate::receipt::Receipt>;
+    /// 获取区块内的全部收据，按交易序号排序
+    async fn get_block_receipts(&self, number: u64) -> Vec<crate::receipt::Receipt>;
+    /// 获取交易和收据在区块中的包含证明
+    async fn get_inclusion_proof(
         &self,
-        tx_hash: &[u8],
-    ) -> Option<ethers::types::TransactionReceipt>;
+        tx_hash: &ethers::types::H256,
+    ) -> Option<crate::inclusion::InclusionProof>;
+    /// 在最新状态上执行调用，状态修改不会提交
+    async fn call(&self, tx: &LocalTransaction) -> fair_vm_core::vm::ExecutionResult;
     /// 获取存储值 (根据 address 和 key 返回 H256)
     async fn get_storage(
         &self,
@@ -88,6 +146,64 @@ pub trait VmExt: Vm + Send + Sync {
     ) -> Result<ethers::types::H256, Error>;
     /// 获取合约代码
     async fn get_code(&self, address: &ethers::types::H160) -> Result<Vec<u8>, Error>;
+    /// 获取区块链
+    async fn get_blockchain(&self) -> Arc<RwLock<Blockchain>>;
+    /// 获取费用统计器
+    async fn get_fee_stats(&self) -> Arc<RwLock<FeeStatsTracker>>;
+    /// 获取按合约和函数选择器的 gas 统计器
+    async fn get_gas_stats(&self) -> Arc<RwLock<GasStatsTracker>>;
+    /// 按最新状态获取交易对最近的若干价格轮次（新到旧）
+    async fn get_price_rounds(&self, pair: &str, count: usize) -> Vec<PriceRound>;
+    /// 获取 EIP-3085 链元数据
+    async fn get_chain_metadata(&self) -> ChainMetadata;
+    /// 获取链参数
+    async fn get_chain_config(&self) -> ChainConfig;
+    /// 提交原始签名交易，返回交易哈希
+    async fn submit_raw_transaction(&self, raw: &[u8]) -> Result<H256, Error>;
+    /// 获取交互式调试会话
+    async fn get_debug_sessions(&self) -> Arc<RwLock<crate::evm::debugger::DebugSessions>>;
+    /// 获取区块执行时的 EVM 环境，区块不存在时为 None
+    async fn get_block_environment(&self, number: u64) -> Option<crate::evm::Environment>;
+    /// 获取已验证合约的源码映射
+    async fn get_source_maps(&self) -> Arc<RwLock<crate::evm::source_map::SourceRegistry>>;
+    /// 获取函数选择器数据库
+    async fn get_selectors(&self) -> Arc<RwLock<crate::evm::selectors::SelectorDatabase>>;
+    /// 获取质押奖励配置
+    async fn get_staking_config(&self) -> Option<crate::staking::StakingConfig>;
+    /// 获取区块 gas 上限状态，未配置时为 None
+    async fn get_gas_limit_status(&self) -> Option<crate::gas_limit::GasLimitStatus>;
+    /// 获取账户在 nonce 通道 `key` 上的下一个序号
+    async fn get_nonce_sequence(
+        &self,
+        address: &crate::account::Address,
+        key: u64,
+    ) -> crate::nonce::NonceSequence;
+    /// 获取日志布隆索引
+    async fn get_log_index(&self) -> Arc<RwLock<crate::log_index::LogIndex>>;
+    /// 获取账户转账历史索引
+    async fn get_account_history(&self) -> Arc<RwLock<crate::account_history::AccountHistory>>;
+    /// 获取供应量与持币排行统计
+    async fn get_supply_tracker(&self) -> Arc<RwLock<crate::supply::SupplyTracker>>;
+    /// 获取 RPC 安装的轮询式过滤器
+    async fn get_log_filters(&self) -> Arc<RwLock<crate::log_filter::FilterRegistry>>;
+    /// 获取当前连接的对等节点
+    async fn get_peers(&self) -> Vec<crate::network::PeerInfo>;
+    /// 获取本地时钟偏差状态
+    async fn get_clock_status(&self) -> crate::clock::ClockStatus;
+    /// 变更验证人集合，返回变更生效的区块高度
+    async fn update_validators(
+        &self,
+        change: crate::consensus::ValidatorChange,
+    ) -> Result<u64, crate::FairVMError>;
+    /// 指定高度的活跃验证人，未指定时为当前高度
+    async fn validators_at(
+        &self,
+        height: Option<u64>,
+    ) -> Result<Vec<crate::consensus::ValidatorInfo>, crate::FairVMError>;
+    /// 获取最近发布的检查点状态
+    async fn get_checkpoint(&self) -> Option<Arc<crate::checkpoint::CheckpointSnapshot>>;
+    /// 收集调试信息包
+    async fn collect_debug_bundle(&self) -> crate::crash::DebugBundle;
 }
 
 /// API 处理器 trait
@@ -96,7 +212,7 @@ pub trait ApiHandler: Send + Sync {
     /// 获取状态
     async fn get_state(&self) -> Arc<RwLock<State>>;
     /// 获取存储
-    async fn get_storage(&self) -> Arc<RwLock<Box<dyn Storage + Send + Sync>>>;
+    async fn get_storage(&self) -> StateHandle;
     /// 获取共识引擎
     async fn get_consensus(&self) -> Option<Arc<RwLock<dyn ConsensusEngineTrait + Send + Sync>>>;
     /// 获取虚拟机
@@ -105,17 +221,61 @@ pub trait ApiHandler: Send + Sync {
 
 pub struct ApiServer {
     vm: Arc<RwLock<dyn VmExt>>,
+    /// 不可变查询的响应缓存，未启用时为空
+    cache: Option<Arc<tokio::sync::Mutex<cache::RpcCache>>>,
 }
 
 impl ApiServer {
     pub fn new(vm: Arc<RwLock<dyn VmExt>>) -> Self {
-        Self { vm }
+        Self { vm, cache: None }
+    }
+
+    /// 按配置启用响应缓存，由同一服务创建的各传输共享缓存
+    pub fn with_cache(mut self, config: &RpcCacheConfig) -> Self {
+        self.cache = config.enabled.then(|| {
+            Arc::new(tokio::sync::Mutex::new(cache::RpcCache::new(
+                config.clone(),
+            )))
+        });
+        self
+    }
+
+    /// 响应缓存
+    pub fn cache(&self) -> Option<Arc<tokio::sync::Mutex<cache::RpcCache>>> {
+        self.cache.clone()
+    }
+
+    /// 把响应缓存登记到内存水位保护，内存紧张时收缩
+    pub fn register_reclaimers(&self, guard: &crate::resources::MemoryGuard) {
+        if let Some(cache) = &self.cache {
+            guard.register(cache.clone());
+        }
+    }
+
+    pub fn admin_handlers(&self) -> admin_handlers::AdminHandlers {
+        admin_handlers::AdminHandlers::new(self.vm.clone())
     }
 
     pub fn chain_handlers(&self) -> chain_handlers::ChainHandlers {
         chain_handlers::ChainHandlers::new(self.vm.clone())
     }
 
+    pub fn debug_handlers(&self) -> debug_handlers::DebugHandlers {
+        debug_handlers::DebugHandlers::new(self.vm.clone())
+    }
+
+    pub fn fairvm_handlers(&self) -> fairvm_handlers::FairVmHandlers {
+        fairvm_handlers::FairVmHandlers::new(self.vm.clone())
+    }
+
+    pub fn graphql_schema(&self) -> graphql::FairVmSchema {
+        graphql::build_schema(self.vm.clone())
+    }
+
+    pub fn rest_router(&self) -> axum::Router {
+        rest::router(self.vm.clone())
+    }
+
     pub fn static_handlers(&self) -> static_handlers::StaticHandlers {
         static_handlers::StaticHandlers::new(self.vm.clone())
     }
@@ -123,6 +283,84 @@ impl ApiServer {
     pub fn wallet_handlers(&self) -> wallet_handlers::WalletHandlers {
         wallet_handlers::WalletHandlers::new(self.vm.clone())
     }
+
+    /// 只包含 `namespaces` 中命名空间方法的 JSON-RPC 处理器，供各传输分别开放接口
+    pub fn io_handler(&self, namespaces: &[String]) -> IoHandler {
+        let delegates: [HashMap<String, RemoteProcedure<()>>; 6] = [
+            self.admin_handlers().to_delegate().into(),
+            self.chain_handlers().to_delegate().into(),
+            self.debug_handlers().to_delegate().into(),
+            self.fairvm_handlers().to_delegate().into(),
+            self.static_handlers().to_delegate().into(),
+            self.wallet_handlers().to_delegate().into(),
+        ];
+        let mut io = IoHandler::new();
+        for methods in delegates {
+            io.extend_with(
+                methods
+                    .into_iter()
+                    .filter(|(method, _)| {
+                        let namespace = method_namespace(method);
+                        namespaces.iter().any(|name| name == namespace)
+                    })
+                    .map(|(method, procedure)| {
+                        let procedure = match (&self.cache, procedure) {
+                            (Some(cache), RemoteProcedure::Method(inner))
+                                if cache::RpcCache::is_cacheable(&method) =>
+                            {
+                                RemoteProcedure::Method(Arc::new(cache::CachedMethod {
+                                    method: method.clone(),
+                                    inner,
+                                    cache: cache.clone(),
+                                    vm: self.vm.clone(),
+                                }))
+                            }
+                            (_, procedure) => procedure,
+                        };
+                        (method, procedure)
+                    }),
+            );
+        }
+        io
+    }
+
+    /// 接收 HTTP JSON-RPC 请求的路由，只开放 `namespaces` 中命名空间的方法
+    pub fn http_router(&self, namespaces: &[String]) -> axum::Router {
+        http::router(self.io_handler(namespaces))
+    }
+
+    /// 按配置启动 HTTP 端点，未启用时返回 `None`
+    pub async fn start_http(
+        &self,
+        config: &fair_vm_core::config::TransportConfig,
+    ) -> std::io::Result<Option<tokio::task::JoinHandle<()>>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let listener = tokio::net::TcpListener::bind(config.addr()).await?;
+        let router = self.http_router(&config.namespaces);
+        Ok(Some(tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                log::error!("HTTP 端点已退出: {}", e);
+            }
+        })))
+    }
+
+    /// 按配置在数据目录下启动 IPC 端点，未启用时返回 `None`
+    #[cfg(unix)]
+    pub async fn start_ipc(
+        &self,
+        config: &fair_vm_core::config::IpcConfig,
+        data_dir: &std::path::Path,
+    ) -> std::io::Result<Option<ipc::IpcServer>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let io = self.io_handler(&config.namespaces);
+        ipc::IpcServer::start(config.socket_path(data_dir), io)
+            .await
+            .map(Some)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -152,3 +390,82 @@ impl From<ApiError> for Error {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FairVM;
+
+    #[test]
+    fn test_io_handler_namespaces() {
+        let vm: Arc<RwLock<dyn VmExt>> = Arc::new(RwLock::new(FairVM::new()));
+        let io = ApiServer::new(vm).io_handler(&["eth".to_string()]);
+        let call = |method: &str| {
+            io.handle_request_sync(&format!(
+                r#"{{"jsonrpc":"2.0","method":"{}","params":[],"id":1}}"#,
+                method
+            ))
+            .unwrap()
+        };
+
+        assert!(call("eth_chainId").contains("\"result\""));
+        // 未开放的命名空间视为方法不存在
+        assert!(call("fairvm_chainConfig").contains("-32601"));
+        assert!(call("debug_endSession").contains("-32601"));
+    }
+
+    #[tokio::test]
+    async fn test_io_handler_cache() {
+        let fairvm = FairVM::new();
+        fairvm
+            .blockchain()
+            .write()
+            .await
+            .add_block(crate::blockchain::Block {
+                header: crate::blockchain::BlockHeader {
+                    parent_hash: H256::zero(),
+                    number: 1,
+                    timestamp: 1,
+                    transactions_root: H256::zero(),
+                    state_root: H256::zero(),
+                    receipts_root: H256::zero(),
+                    difficulty: 0,
+                    block_reward: 0,
+                    proposer: None,
+                    arrivals_root: H256::zero(),
+                },
+                transactions: Vec::new(),
+                arrivals: Vec::new(),
+                acceptance: None,
+            });
+        let vm: Arc<RwLock<dyn VmExt>> = Arc::new(RwLock::new(fairvm));
+        let server = ApiServer::new(vm).with_cache(&RpcCacheConfig::default());
+        let io = server.io_handler(&["chain".to_string()]);
+        let cache = server.cache().unwrap();
+        let call = |number: u64| {
+            let request = format!(
+                r#"{{"jsonrpc":"2.0","method":"chain_getBlockByNumber","params":[{}],"id":1}}"#,
+                number
+            );
+            let io = io.clone();
+            std::thread::spawn(move || io.handle_request_sync(&request).unwrap())
+                .join()
+                .unwrap()
+        };
+
+        let first = call(1);
+        assert_eq!(call(1), first);
+        // 尚不存在的区块不缓存
+        assert!(call(2).contains("null"));
+        let stats = cache.lock().await.stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.hits, 1);
+
+        let guard = crate::resources::MemoryGuard::default();
+        server.register_reclaimers(&guard);
+        assert_eq!(
+            guard.reclaim(crate::resources::MemoryPressure::Hard),
+            vec![("rpc_cache", 1)]
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! REST 网关
+//!
+//! 为不方便使用 JSON-RPC 的环境提供 REST 接口，接口定义同时生成 OpenAPI 文档，
+//! 可通过 `/openapi.json` 获取并用于生成其他语言的客户端。
+
+use crate::account::Address as AccountAddress;
+use crate::api::chain_handlers::{BlockResponse, BlockTransactions, TransactionResponse};
+use crate::api::VmExt;
+use crate::blockchain::{Block, Blockchain};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use ethers::types::{H160, H256};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+type SharedVm = Arc<RwLock<dyn VmExt>>;
+
+/// REST 接口错误
+#[derive(Debug, Error)]
+pub enum RestError {
+    #[error("无效参数: {0}")]
+    InvalidParams(String),
+
+    #[error("未找到: {0}")]
+    NotFound(String),
+}
+
+/// 错误响应
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+impl IntoResponse for RestError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            RestError::InvalidParams(_) => StatusCode::BAD_REQUEST,
+            RestError::NotFound(_) => StatusCode::NOT_FOUND,
+        };
+        let body = ErrorResponse {
+            error: self.to_string(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// 账户余额
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BalanceResponse {
+    pub address: String,
+    /// 十六进制余额（wei）
+    pub balance: String,
+}
+
+/// 账户 nonce
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NonceResponse {
+    pub address: String,
+    pub nonce: u64,
+}
+
+/// 区块查询参数
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct BlockQuery {
+    /// 是否返回完整交易
+    #[serde(default)]
+    pub full: bool,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "FairVM REST API", version = "1"),
+    paths(
+        latest_block,
+        block_by_number,
+        transaction_by_block_and_index,
+        transaction_by_hash,
+        account_balance,
+        account_nonce
+    ),
+    components(schemas(
+        BlockResponse,
+        BlockTransactions,
+        TransactionResponse,
+        BalanceResponse,
+        NonceResponse,
+        ErrorResponse
+    ))
+)]
+pub struct ApiDoc;
+
+/// REST 路由
+pub fn router(vm: SharedVm) -> Router {
+    Router::new()
+        .route("/v1/blocks/latest", get(latest_block))
+        .route("/v1/blocks/:number", get(block_by_number))
+        .route(
+            "/v1/blocks/:number/transactions/:index",
+            get(transaction_by_block_and_index),
+        )
+        .route("/v1/transactions/:hash", get(transaction_by_hash))
+        .route("/v1/accounts/:address/balance", get(account_balance))
+        .route("/v1/accounts/:address/nonce", get(account_nonce))
+        .route("/openapi.json", get(openapi))
+        .with_state(vm)
+}
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+fn parse_address(address: &str) -> Result<AccountAddress, RestError> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))
+        .map_err(|_| RestError::InvalidParams(format!("无效的地址: {}", address)))?;
+    if bytes.len() != 20 {
+        return Err(RestError::InvalidParams(format!("无效的地址: {}", address)));
+    }
+    Ok(AccountAddress::from(H160::from_slice(&bytes)))
+}
+
+fn parse_hash(hash: &str) -> Result<H256, RestError> {
+    let bytes = hex::decode(hash.trim_start_matches("0x"))
+        .map_err(|_| RestError::InvalidParams(format!("无效的哈希: {}", hash)))?;
+    if bytes.len() != 32 {
+        return Err(RestError::InvalidParams(format!("无效的哈希: {}", hash)));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+/// 在区块链上执行只读查询
+async fn with_blockchain<T>(vm: &SharedVm, f: impl FnOnce(&Blockchain) -> T) -> T {
+    let vm = vm.read().await;
+    let blockchain = vm.get_blockchain().await;
+    let blockchain = blockchain.read().await;
+    f(&blockchain)
+}
+
+fn block_not_found(number: u64) -> RestError {
+    RestError::NotFound(format!("区块 {}", number))
+}
+
+/// 获取最新区块
+#[utoipa::path(
+    get,
+    path = "/v1/blocks/latest",
+    params(BlockQuery),
+    responses(
+        (status = 200, body = BlockResponse),
+        (status = 404, body = ErrorResponse)
+    )
+)]
+async fn latest_block(
+    State(vm): State<SharedVm>,
+    Query(query): Query<BlockQuery>,
+) -> Result<Json<BlockResponse>, RestError> {
+    with_blockchain(&vm, |chain| {
+        chain
+            .latest_block()
+            .map(|block| BlockResponse::from_block(block, query.full))
+    })
+    .await
+    .map(Json)
+    .ok_or_else(|| RestError::NotFound("尚无区块".to_string()))
+}
+
+/// 按高度获取区块
+#[utoipa::path(
+    get,
+    path = "/v1/blocks/{number}",
+    params(("number" = u64, Path, description = "区块高度"), BlockQuery),
+    responses(
+        (status = 200, body = BlockResponse),
+        (status = 404, body = ErrorResponse)
+    )
+)]
+async fn block_by_number(
+    State(vm): State<SharedVm>,
+    Path(number): Path<u64>,
+    Query(query): Query<BlockQuery>,
+) -> Result<Json<BlockResponse>, RestError> {
+    with_blockchain(&vm, |chain| {
+        chain
+            .get_block(number)
+            .map(|block| BlockResponse::from_block(block, query.full))
+    })
+    .await
+    .map(Json)
+    .ok_or_else(|| block_not_found(number))
+}
+
+/// 按区块高度与索引获取交易
+#[utoipa::path(
+    get,
+    path = "/v1/blocks/{number}/transactions/{index}",
+    params(
+        ("number" = u64, Path, description = "区块高度"),
+        ("index" = usize, Path, description = "交易索引")
+    ),
+    responses(
+        (status = 200, body = TransactionResponse),
+        (status = 404, body = ErrorResponse)
+    )
+)]
+async fn transaction_by_block_and_index(
+    State(vm): State<SharedVm>,
+    Path((number, index)): Path<(u64, usize)>,
+) -> Result<Json<TransactionResponse>, RestError> {
+    let response = with_blockchain(&vm, |chain| {
+        chain.get_block(number).map(|block: &Block| {
+            block
+                .transactions
+                .get(index)
+                .map(|tx| TransactionResponse::in_block(tx, block, block.hash(), index))
+        })
+    })
+    .await
+    .ok_or_else(|| block_not_found(number))?;
+    response
+        .map(Json)
+        .ok_or_else(|| RestError::NotFound(format!("区块 {} 中的交易 {}", number, index)))
+}
+
+/// 按哈希获取交易
+#[utoipa::path(
+    get,
+    path = "/v1/transactions/{hash}",
+    params(("hash" = String, Path, description = "交易哈希")),
+    responses(
+        (status = 200, body = TransactionResponse),
+        (status = 400, body = ErrorResponse),
+        (status = 404, body = ErrorResponse)
+    )
+)]
+async fn transaction_by_hash(
+    State(vm): State<SharedVm>,
+    Path(hash): Path<String>,
+) -> Result<Json<TransactionResponse>, RestError> {
+    let tx_hash = parse_hash(&hash)?;
+    with_blockchain(&vm, |chain| {
+        chain.find_transaction(&tx_hash).map(|(block, index)| {
+            TransactionResponse::in_block(&block.transactions[index], block, block.hash(), index)
+        })
+    })
+    .await
+    .map(Json)
+    .ok_or_else(|| RestError::NotFound(format!("交易 {}", hash)))
+}
+
+/// 获取账户余额
+#[utoipa::path(
+    get,
+    path = "/v1/accounts/{address}/balance",
+    params(("address" = String, Path, description = "账户地址")),
+    responses(
+        (status = 200, body = BalanceResponse),
+        (status = 400, body = ErrorResponse)
+    )
+)]
+async fn account_balance(
+    State(vm): State<SharedVm>,
+    Path(address): Path<String>,
+) -> Result<Json<BalanceResponse>, RestError> {
+    let account = parse_address(&address)?;
+    let vm = vm.read().await;
+    let state = vm.get_state().await;
+    let balance = state.read().await.get_balance(&account).await;
+    Ok(Json(BalanceResponse {
+        address: format!("0x{}", hex::encode(account.0)),
+        balance: format!("0x{:x}", balance),
+    }))
+}
+
+/// 获取账户 nonce
+#[utoipa::path(
+    get,
+    path = "/v1/accounts/{address}/nonce",
+    params(("address" = String, Path, description = "账户地址")),
+    responses(
+        (status = 200, body = NonceResponse),
+        (status = 400, body = ErrorResponse)
+    )
+)]
+async fn account_nonce(
+    State(vm): State<SharedVm>,
+    Path(address): Path<String>,
+) -> Result<Json<NonceResponse>, RestError> {
+    let account = parse_address(&address)?;
+    let vm = vm.read().await;
+    let state = vm.get_state().await;
+    let nonce = state.read().await.get_nonce(&account).await;
+    Ok(Json(NonceResponse {
+        address: format!("0x{}", hex::encode(account.0)),
+        nonce,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_document() {
+        let doc = ApiDoc::openapi();
+        let json = serde_json::to_value(&doc).unwrap();
+        let paths = json["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/v1/blocks/{number}"));
+        assert!(paths.contains_key("/v1/accounts/{address}/balance"));
+        assert!(json["components"]["schemas"]
+            .as_object()
+            .unwrap()
+            .contains_key("BlockResponse"));
+    }
+
+    #[test]
+    fn test_parse_params() {
+        assert!(parse_address("0x0000000000000000000000000000000000000001").is_ok());
+        assert!(parse_address("0x01").is_err());
+        assert!(parse_hash(&format!("0x{}", "00".repeat(32))).is_ok());
+        assert!(parse_hash("zz").is_err());
+    }
+}
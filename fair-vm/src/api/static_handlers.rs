@@ -1,7 +1,8 @@
 use crate::account::{Account, Address};
 use crate::api::VmExt;
+use crate::receipt::Receipt;
 use crate::state::State;
-use crate::storage::Storage;
+use crate::storage::{Storage, StorageEntry};
 use async_trait::async_trait;
 use ethers::types::{H160, H256, U256};
 use hex;
@@ -54,6 +55,41 @@ impl Storage for StorageValue {
     }
 
     async fn set_storage_value(&mut self, _address: &Address, _key: [u8; 32], _value: [u8; 32]) {}
+
+    async fn get_code(&self, _address: &Address) -> Vec<u8> {
+        Vec::new()
+    }
+
+    async fn set_code(&mut self, _address: &Address, _code: Vec<u8>) -> H256 {
+        H256::zero()
+    }
+
+    async fn get_code_by_hash(&self, _code_hash: &H256) -> Option<Vec<u8>> {
+        None
+    }
+
+    async fn accounts_page(&self, _after: Option<Address>, _limit: usize) -> Vec<Account> {
+        Vec::new()
+    }
+
+    async fn storage_page(
+        &self,
+        _address: &Address,
+        _after: Option<[u8; 32]>,
+        _limit: usize,
+    ) -> Vec<StorageEntry> {
+        Vec::new()
+    }
+
+    async fn get_receipt(&self, _tx_hash: &H256) -> Option<Receipt> {
+        None
+    }
+
+    async fn block_receipts(&self, _number: u64) -> Vec<Receipt> {
+        Vec::new()
+    }
+
+    async fn set_receipt(&mut self, _receipt: &Receipt) {}
 }
 
 #[rpc]
@@ -110,11 +146,8 @@ impl StaticHandlers {
     /// 获取存储
     pub async fn get_storage(&self, address: &H160, key: &H256) -> Result<StorageValue> {
         let vm = self.vm.read().await;
-        let storage = vm.get_storage_arc().await;
-        let storage_guard = storage.read().await;
-        let value = storage_guard
-            .get_storage_value(&Address(address.0), key.0)
-            .await;
+        let storage = vm.get_storage_handle().await;
+        let value = storage.get_storage_value(&Address(address.0), key.0).await;
         Ok(StorageValue {
             value: hex::encode(value),
         })
@@ -138,12 +171,12 @@ impl StaticApi for StaticHandlers {
 
         let balance = runtime.block_on(state_guard.get_balance(&address));
         let nonce = runtime.block_on(state_guard.get_nonce(&address));
-        let code_hash = runtime.block_on(state_guard.get_code_hash(&address));
+        let code = runtime.block_on(Storage::get_code(&*state_guard, &address));
 
         Ok(StateResponse {
             balance: balance.to_string(),
             nonce,
-            code: hex::encode(code_hash.as_bytes()),
+            code: hex::encode(code),
             storage: vec![], // 这里需要实现存储的序列化
         })
     }
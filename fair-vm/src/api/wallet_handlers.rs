@@ -78,7 +78,9 @@ impl WalletHandlers {
     /// 获取交易收据
     pub async fn get_transaction_receipt(&self, tx_hash: &Hash) -> Option<TransactionReceipt> {
         let vm = self.vm.read().await;
-        vm.get_transaction_receipt(tx_hash.as_bytes()).await
+        vm.get_transaction_receipt(tx_hash.as_bytes())
+            .await
+            .map(|receipt| TransactionReceipt::from(&receipt))
     }
 
     fn parse_address(&self, address: String) -> Result<AccountAddress> {
@@ -225,7 +227,7 @@ impl WalletApi for WalletHandlers {
                 data,
                 signature: Vec::new(),
                 transaction_type: TransactionType::Legacy,
-                chain_id: 1,
+                chain_id: vm.get_chain_config().await.chain_id,
                 max_fee_per_gas: Some(gas_price * U256::from(2)),
                 max_priority_fee_per_gas: Some(gas_price),
             };
@@ -278,14 +280,20 @@ impl WalletApi for WalletHandlers {
         let vm = self.vm.blocking_read();
         let receipt = tokio::runtime::Runtime::new()
             .unwrap()
-            .block_on(vm.get_transaction_receipt(&hash.0));
+            .block_on(vm.get_transaction_receipt(&hash.0))
+            .map(|receipt| TransactionReceipt::from(&receipt));
         Ok(receipt.map(|r| TransactionReceiptResponse {
             transaction_hash: format!("0x{}", hex::encode(r.transaction_hash.0)),
             block_number: r.block_number.map(|n| n.as_u64()).unwrap_or(0),
             block_hash: format!("0x{}", hex::encode(r.block_hash.unwrap_or_default().0)),
             from: format!("0x{}", hex::encode(r.from.0)),
             to: r.to.map(|addr| format!("0x{}", hex::encode(addr.0))),
+            contract_address: r
+                .contract_address
+                .map(|addr| format!("0x{}", hex::encode(addr.0))),
             gas_used: r.gas_used.map(|g| g.as_u64()).unwrap_or(0),
+            cumulative_gas_used: r.cumulative_gas_used.as_u64(),
+            effective_gas_price: format!("0x{:x}", r.effective_gas_price.unwrap_or_default()),
             status: r.status.map(|s| s.as_u64() == 1).unwrap_or(false),
             logs: r
                 .logs
@@ -342,7 +350,10 @@ pub struct TransactionReceiptResponse {
     pub block_hash: String,
     pub from: String,
     pub to: Option<String>,
+    pub contract_address: Option<String>,
     pub gas_used: u64,
+    pub cumulative_gas_used: u64,
+    pub effective_gas_price: String,
     pub status: bool,
     pub logs: Vec<LogInfo>,
 }
@@ -0,0 +1,297 @@
+//! Avalanche bech32 地址与 FairVM 十六进制地址的互相转换
+//!
+//! 同一个 20 字节地址在 X/P 链上以 bech32 显示（如 `X-avax1...`），在 FairVM 上以
+//! EIP-55 校验和十六进制显示，两者之间的转换只改变编码，结果是确定的。
+//! 注意 Avalanche 由公钥的 `ripemd160(sha256(压缩公钥))` 得到地址，与 FairVM 的
+//! keccak 地址不同，已知公钥时可用 [`KeyAddresses`] 同时得到两条链上的地址。
+
+use crate::account::Address;
+use bech32::{FromBase32, ToBase32, Variant};
+use fair_vm_core::serde_hex;
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// 主网地址前缀
+pub const MAINNET_HRP: &str = "avax";
+
+/// Fuji 测试网地址前缀
+pub const FUJI_HRP: &str = "fuji";
+
+/// 本地网络地址前缀
+pub const LOCAL_HRP: &str = "local";
+
+/// 可以出现在 bech32 地址前的链别名
+pub const CHAIN_ALIASES: &[&str] = &["X", "P", "C"];
+
+/// 地址转换错误类型
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AddressError {
+    #[error("无效的 bech32 地址 {0}: {1}")]
+    InvalidBech32(String, String),
+
+    #[error("地址应为 20 字节，实际 {0} 字节")]
+    InvalidLength(usize),
+
+    #[error("未知的链别名: {0}")]
+    UnknownChain(String),
+
+    #[error("无效的十六进制地址: {0}")]
+    InvalidHex(String),
+
+    #[error("地址校验和不匹配: {0}")]
+    ChecksumMismatch(String),
+
+    #[error("无效的公钥: {0}")]
+    InvalidPublicKey(String),
+}
+
+fn to_address(bytes: &[u8]) -> Result<Address, AddressError> {
+    let bytes: [u8; 20] = bytes
+        .try_into()
+        .map_err(|_| AddressError::InvalidLength(bytes.len()))?;
+    Ok(Address(bytes))
+}
+
+/// 带链别名的 bech32 地址
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AvaxAddress {
+    /// 链别名，如 `X`、`P`
+    pub chain: Option<String>,
+    /// bech32 前缀，如 `avax`
+    pub hrp: String,
+    pub address: Address,
+}
+
+impl AvaxAddress {
+    /// 创建地址，链别名须为 [`CHAIN_ALIASES`] 之一
+    pub fn new(chain: Option<&str>, hrp: &str, address: Address) -> Result<Self, AddressError> {
+        if let Some(chain) = chain {
+            if !CHAIN_ALIASES.contains(&chain) {
+                return Err(AddressError::UnknownChain(chain.to_string()));
+            }
+        }
+        // 提前校验前缀，之后的编码不会失败
+        bech32::encode(hrp, address.0.to_base32(), Variant::Bech32)
+            .map_err(|e| AddressError::InvalidBech32(hrp.to_string(), e.to_string()))?;
+        Ok(Self {
+            chain: chain.map(str::to_string),
+            hrp: hrp.to_string(),
+            address,
+        })
+    }
+
+    /// 解析 `X-avax1...` 或 `avax1...` 形式的地址，同时校验 bech32 校验和
+    pub fn parse(s: &str) -> Result<Self, AddressError> {
+        let (chain, encoded) = match s.split_once('-') {
+            Some((chain, encoded)) => (Some(chain), encoded),
+            None => (None, s),
+        };
+        let (hrp, data, variant) = bech32::decode(encoded)
+            .map_err(|e| AddressError::InvalidBech32(s.to_string(), e.to_string()))?;
+        if variant != Variant::Bech32 {
+            return Err(AddressError::InvalidBech32(
+                s.to_string(),
+                "应使用 bech32 而非 bech32m".to_string(),
+            ));
+        }
+        let bytes = Vec::<u8>::from_base32(&data)
+            .map_err(|e| AddressError::InvalidBech32(s.to_string(), e.to_string()))?;
+        Self::new(chain, &hrp, to_address(&bytes)?)
+    }
+
+    /// 不带链别名的 bech32 编码
+    pub fn bech32(&self) -> String {
+        bech32::encode(&self.hrp, self.address.0.to_base32(), Variant::Bech32)
+            .expect("前缀已在创建时校验")
+    }
+}
+
+impl fmt::Display for AvaxAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.chain {
+            Some(chain) => write!(f, "{}-{}", chain, self.bech32()),
+            None => write!(f, "{}", self.bech32()),
+        }
+    }
+}
+
+impl FromStr for AvaxAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// EIP-55 校验和十六进制地址
+pub fn to_checksum_hex(address: &Address) -> String {
+    address.to_checksum()
+}
+
+/// 十六进制地址的校验和策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChecksumPolicy {
+    /// 大小写混合时必须符合 EIP-55 校验和
+    #[default]
+    Enforce,
+    /// 不校验大小写，用于明确知道地址来源的场景
+    Ignore,
+}
+
+/// 解析十六进制地址，大小写混合时校验 EIP-55 校验和
+pub fn parse_hex(s: &str) -> Result<Address, AddressError> {
+    parse_hex_with(s, ChecksumPolicy::Enforce)
+}
+
+/// 按指定校验和策略解析十六进制地址
+pub fn parse_hex_with(s: &str, policy: ChecksumPolicy) -> Result<Address, AddressError> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.len() != 40 {
+        return Err(AddressError::InvalidHex(s.to_string()));
+    }
+    let bytes = hex::decode(digits).map_err(|_| AddressError::InvalidHex(s.to_string()))?;
+    let address = to_address(&bytes)?;
+    if policy == ChecksumPolicy::Enforce
+        && serde_hex::is_mixed_case(digits)
+        && to_checksum_hex(&address)[2..] != *digits
+    {
+        return Err(AddressError::ChecksumMismatch(s.to_string()));
+    }
+    Ok(address)
+}
+
+/// 解析十六进制或 bech32 地址
+pub fn parse_any(s: &str) -> Result<Address, AddressError> {
+    parse_any_with(s, ChecksumPolicy::Enforce)
+}
+
+/// 按指定校验和策略解析十六进制或 bech32 地址，bech32 地址始终校验
+pub fn parse_any_with(s: &str, policy: ChecksumPolicy) -> Result<Address, AddressError> {
+    let s = s.trim();
+    if s.starts_with("0x") || (s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())) {
+        parse_hex_with(s, policy)
+    } else {
+        AvaxAddress::parse(s).map(|address| address.address)
+    }
+}
+
+/// 同一地址的各种显示格式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressFormats {
+    /// EIP-55 校验和十六进制
+    pub hex: String,
+    /// 不带链别名的 bech32
+    pub bech32: String,
+    pub x_chain: String,
+    pub p_chain: String,
+}
+
+impl AddressFormats {
+    /// 以 `hrp` 为前缀生成各种格式
+    pub fn new(address: &Address, hrp: &str) -> Result<Self, AddressError> {
+        let format = |chain: Option<&str>| {
+            AvaxAddress::new(chain, hrp, *address).map(|avax| avax.to_string())
+        };
+        Ok(Self {
+            hex: to_checksum_hex(address),
+            bech32: format(None)?,
+            x_chain: format(Some("X"))?,
+            p_chain: format(Some("P"))?,
+        })
+    }
+}
+
+/// 同一公钥在 Avalanche 与 FairVM 上派生的地址
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyAddresses {
+    /// `ripemd160(sha256(压缩公钥))`，X/P 链使用
+    pub avalanche: Address,
+    /// `keccak256(公钥)` 的后 20 字节，FairVM 使用
+    pub fairvm: Address,
+}
+
+impl KeyAddresses {
+    /// 由 33 字节压缩或 65 字节未压缩的 secp256k1 公钥派生
+    pub fn from_public_key(public_key: &[u8]) -> Result<Self, AddressError> {
+        let key = secp256k1::PublicKey::from_slice(public_key)
+            .map_err(|e| AddressError::InvalidPublicKey(e.to_string()))?;
+        let sha = Sha256::digest(key.serialize());
+        let short = Ripemd160::digest(sha);
+        Ok(Self {
+            avalanche: to_address(&short)?,
+            fairvm: Address::from_public_key(&key),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bech32_round_trip() {
+        let address = Address([0x3c; 20]);
+        let avax = AvaxAddress::new(Some("X"), MAINNET_HRP, address).unwrap();
+        let encoded = avax.to_string();
+        assert!(encoded.starts_with("X-avax1"));
+        assert_eq!(encoded.parse::<AvaxAddress>().unwrap(), avax);
+        assert_eq!(parse_any(&avax.bech32()).unwrap(), address);
+
+        // 改动一个字符后校验和失败
+        let mut tampered = encoded.clone().into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'q' { b'p' } else { b'q' };
+        assert!(AvaxAddress::parse(std::str::from_utf8(&tampered).unwrap()).is_err());
+        assert!(matches!(
+            AvaxAddress::new(Some("Z"), MAINNET_HRP, address),
+            Err(AddressError::UnknownChain(_))
+        ));
+        assert!(AvaxAddress::new(None, "", address).is_err());
+    }
+
+    #[test]
+    fn test_hex_checksum() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let address = parse_hex(checksummed).unwrap();
+        assert_eq!(to_checksum_hex(&address), checksummed);
+        assert_eq!(parse_hex(&checksummed.to_lowercase()).unwrap(), address);
+        assert!(matches!(
+            parse_hex("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            Err(AddressError::ChecksumMismatch(_))
+        ));
+        assert_eq!(
+            parse_hex_with(
+                "0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+                ChecksumPolicy::Ignore
+            )
+            .unwrap(),
+            address
+        );
+        assert!(parse_hex("0x1234").is_err());
+
+        let formats = AddressFormats::new(&address, FUJI_HRP).unwrap();
+        assert_eq!(formats.hex, checksummed);
+        assert_eq!(formats.x_chain, format!("X-{}", formats.bech32));
+        assert_eq!(parse_any(&formats.p_chain).unwrap(), address);
+    }
+
+    #[test]
+    fn test_key_addresses() {
+        let secp = secp256k1::Secp256k1::new();
+        let secret = secp256k1::SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret);
+
+        let compressed = KeyAddresses::from_public_key(&public_key.serialize()).unwrap();
+        let uncompressed =
+            KeyAddresses::from_public_key(&public_key.serialize_uncompressed()).unwrap();
+        assert_eq!(compressed, uncompressed);
+        assert_eq!(compressed.fairvm, Address::from_public_key(&public_key));
+        assert_ne!(compressed.avalanche, compressed.fairvm);
+        assert!(KeyAddresses::from_public_key(&[0u8; 10]).is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::types::{Address, Hash};
+use fair_vm_core::serde_hex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockHeader {
@@ -38,6 +40,7 @@ pub struct TransactionReceipt {
 pub struct Log {
     pub address: Address,
     pub topics: Vec<Hash>,
+    #[serde(with = "serde_hex::bytes")]
     pub data: Vec<u8>,
     pub block_number: u64,
     pub block_hash: Hash,
@@ -76,3 +79,16 @@ impl Block {
         self.receipts.insert(transaction_hash, receipt);
     }
 }
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} (parent {:?}, state root {:?}, {} txs)",
+            self.header.number,
+            self.header.parent_hash,
+            self.header.state_root,
+            self.transactions.len()
+        )
+    }
+}
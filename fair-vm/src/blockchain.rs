@@ -1,6 +1,10 @@
+use crate::account::Address;
+use crate::consensus::fcfs::{ArrivalTimestamp, FcfsError, FcfsOrdering};
 use crate::transaction::Transaction;
 use ethers::types::H256;
+use fair_vm_core::config::Config;
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
 
 /// 区块头
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,14 +15,36 @@ pub struct BlockHeader {
     pub number: u64,
     /// 时间戳
     pub timestamp: u64,
-    /// 交易根
+    /// 交易根，见 [`crate::merkle::transactions_root`]
     pub transactions_root: H256,
-    /// 状态根
+    /// 执行区块后的状态根，见 [`crate::state_proof::StateCommitment`]
     pub state_root: H256,
+    /// 收据根，见 [`crate::merkle::receipts_root`]
+    #[serde(default)]
+    pub receipts_root: H256,
     /// 区块难度
     pub difficulty: u64,
     /// 区块奖励
     pub block_reward: u64,
+    /// 出块验证人的节点 ID，如 `NodeID-...`，参与区块哈希，出块统计与纪元奖励以此为准
+    #[serde(default)]
+    pub proposer: Option<String>,
+    /// 区块携带的到达时间报告的根，见 [`crate::merkle::arrivals_root`]，不携带报告时为零
+    #[serde(default)]
+    pub arrivals_root: H256,
+}
+
+/// 区块被接受时记录的信息，不参与区块哈希
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockAcceptance {
+    /// 出块验证人的节点 ID，如 `NodeID-...`，未知时为空
+    pub proposer: Option<String>,
+    /// 接受时间（Unix 秒）
+    pub accepted_at: u64,
+    /// 区块生效的 gas 上限，未配置时为空
+    #[serde(default)]
+    pub gas_limit: Option<u64>,
 }
 
 /// 区块
@@ -28,6 +54,73 @@ pub struct Block {
     pub header: BlockHeader,
     /// 交易列表
     pub transactions: Vec<Transaction>,
+    /// 启用 FCFS 排序时区块交易的签名到达时间报告，以及随区块提交的冲突报告
+    #[serde(default)]
+    pub arrivals: Vec<ArrivalTimestamp>,
+    /// 接受信息，区块被本节点接受后填写
+    #[serde(default)]
+    pub acceptance: Option<BlockAcceptance>,
+}
+
+impl BlockHeader {
+    /// 计算区块头哈希
+    pub fn hash(&self) -> H256 {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.parent_hash.as_bytes());
+        hasher.update(self.number.to_be_bytes());
+        hasher.update(self.timestamp.to_be_bytes());
+        hasher.update(self.transactions_root.as_bytes());
+        hasher.update(self.state_root.as_bytes());
+        hasher.update(self.difficulty.to_be_bytes());
+        hasher.update(self.block_reward.to_be_bytes());
+        hasher.update(self.receipts_root.as_bytes());
+        if let Some(proposer) = &self.proposer {
+            hasher.update((proposer.len() as u64).to_be_bytes());
+            hasher.update(proposer.as_bytes());
+        }
+        if !self.arrivals_root.is_zero() {
+            hasher.update(self.arrivals_root.as_bytes());
+        }
+        H256::from_slice(&hasher.finalize())
+    }
+
+    /// 区块头中的根
+    pub fn root(&self, kind: RootKind) -> H256 {
+        match kind {
+            RootKind::Transactions => self.transactions_root,
+            RootKind::Receipts => self.receipts_root,
+            RootKind::State => self.state_root,
+            RootKind::Arrivals => self.arrivals_root,
+        }
+    }
+
+    /// 校验区块头中的根
+    ///
+    /// `required` 为假时（根承诺激活前的旧区块和创世区块）为零的根视为未承诺，不做校验。
+    pub fn verify_root(
+        &self,
+        kind: RootKind,
+        computed: H256,
+        required: bool,
+    ) -> Result<(), RootMismatch> {
+        let header = self.root(kind);
+        if (header.is_zero() && !required) || header == computed {
+            return Ok(());
+        }
+        Err(RootMismatch {
+            number: self.number,
+            kind,
+            header,
+            computed,
+        })
+    }
+}
+
+impl Block {
+    /// 区块哈希
+    pub fn hash(&self) -> H256 {
+        self.header.hash()
+    }
 }
 
 /// 区块链配置
@@ -47,6 +140,166 @@ pub struct BlockchainConfig {
     pub min_transactions: usize,
 }
 
+/// 区块时间戳校验错误
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TimestampError {
+    #[error("区块时间戳 {timestamp} 不晚于父区块时间戳 {parent}")]
+    NotIncreasing { timestamp: u64, parent: u64 },
+
+    #[error("区块间隔 {interval} 秒小于最小间隔 {min} 秒")]
+    TooSoon { interval: u64, min: u64 },
+
+    #[error("区块时间戳 {timestamp} 超前本地时间 {now} 超过 {max_drift} 秒")]
+    TooFarInFuture {
+        timestamp: u64,
+        now: u64,
+        max_drift: u64,
+    },
+}
+
+/// 区块未接在链头之后
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LinkError {
+    #[error("父区块 {0:?} 未知")]
+    UnknownParent(H256),
+
+    #[error("父区块 {parent:?} 不是链头 {head:?}")]
+    NotHead { parent: H256, head: H256 },
+
+    #[error("区块高度 {number} 不是预期的 {expected}")]
+    UnexpectedNumber { number: u64, expected: u64 },
+}
+
+/// 区块头中的根
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootKind {
+    Transactions,
+    Receipts,
+    State,
+    Arrivals,
+}
+
+impl std::fmt::Display for RootKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Transactions => "交易根",
+            Self::Receipts => "收据根",
+            Self::State => "状态根",
+            Self::Arrivals => "到达时间根",
+        })
+    }
+}
+
+/// 区块头中的根与重新计算的结果不一致
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("区块 {number} 的{kind}为 {header:?}，计算结果为 {computed:?}")]
+pub struct RootMismatch {
+    pub number: u64,
+    pub kind: RootKind,
+    pub header: H256,
+    pub computed: H256,
+}
+
+/// 区块时间戳校验规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampPolicy {
+    /// 相邻区块的最小时间间隔（秒）
+    pub min_block_interval: u64,
+    /// 允许超前本地时钟的最大秒数
+    pub max_drift: u64,
+}
+
+impl TimestampPolicy {
+    /// 从节点配置读取规则
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            min_block_interval: config.min_block_interval,
+            max_drift: config.max_timestamp_drift,
+        }
+    }
+
+    /// 校验区块时间戳：严格晚于父区块、满足最小间隔、不超前本地时间 `now` 太多
+    ///
+    /// 没有父区块时（链上第一个区块）只检查时钟偏差；父区块须先经 [`Blockchain::check_link`] 确认。
+    pub fn validate(
+        &self,
+        header: &BlockHeader,
+        parent: Option<&BlockHeader>,
+        now: u64,
+    ) -> Result<(), TimestampError> {
+        let timestamp = header.timestamp;
+        if let Some(parent) = parent {
+            if timestamp <= parent.timestamp {
+                return Err(TimestampError::NotIncreasing {
+                    timestamp,
+                    parent: parent.timestamp,
+                });
+            }
+            let interval = timestamp - parent.timestamp;
+            if interval < self.min_block_interval {
+                return Err(TimestampError::TooSoon {
+                    interval,
+                    min: self.min_block_interval,
+                });
+            }
+        }
+        if timestamp > now.saturating_add(self.max_drift) {
+            return Err(TimestampError::TooFarInFuture {
+                timestamp,
+                now,
+                max_drift: self.max_drift,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for TimestampPolicy {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+/// 区块时间戳索引，将时间范围映射为区块范围
+///
+/// 时间戳小于之前区块的区块按之前的最大时间戳索引，保证索引单调。
+#[derive(Debug, Clone, Default)]
+pub struct TimestampIndex {
+    /// 按区块顺序排列的（索引时间戳, 区块高度）
+    entries: Vec<(u64, u64)>,
+}
+
+impl TimestampIndex {
+    /// 记录新区块
+    pub fn insert(&mut self, number: u64, timestamp: u64) {
+        let timestamp = match self.entries.last() {
+            Some(&(last, _)) => timestamp.max(last),
+            None => timestamp,
+        };
+        self.entries.push((timestamp, number));
+    }
+
+    /// 时间戳在 `[from, to]` 内的区块高度范围，没有区块时返回 `None`
+    pub fn block_range(&self, from: u64, to: u64) -> Option<(u64, u64)> {
+        let start = self.entries.partition_point(|&(t, _)| t < from);
+        let end = self.entries.partition_point(|&(t, _)| t <= to);
+        if start >= end {
+            return None;
+        }
+        Some((self.entries[start].1, self.entries[end - 1].1))
+    }
+
+    /// 已索引的区块数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
 /// 区块链
 #[derive(Debug)]
 pub struct Blockchain {
@@ -57,6 +310,8 @@ pub struct Blockchain {
     current_block: Option<Block>,
     /// 区块历史
     blocks: Vec<Block>,
+    /// 时间戳索引
+    timestamps: TimestampIndex,
 }
 
 impl Blockchain {
@@ -66,6 +321,7 @@ impl Blockchain {
             config,
             current_block: None,
             blocks: Vec::new(),
+            timestamps: TimestampIndex::default(),
         }
     }
 
@@ -81,15 +337,82 @@ impl Blockchain {
 
     /// 添加新区块
     pub fn add_block(&mut self, block: Block) {
+        self.timestamps
+            .insert(block.header.number, block.header.timestamp);
         self.blocks.push(block.clone());
         self.current_block = Some(block);
     }
 
+    /// 按区块携带的到达时间报告验证交易顺序后添加新区块，`penalized` 为状态中已被惩罚的验证者
+    pub fn add_verified_block(
+        &mut self,
+        block: Block,
+        ordering: &FcfsOrdering,
+        penalized: &[Address],
+    ) -> Result<(), FcfsError> {
+        ordering.validate_block(&block.arrivals, &block.transactions, penalized)?;
+        self.add_block(block);
+        Ok(())
+    }
+
     /// 获取指定高度的区块
     pub fn get_block(&self, height: u64) -> Option<&Block> {
         self.blocks.iter().find(|b| b.header.number == height)
     }
 
+    /// 获取指定哈希的区块
+    pub fn get_block_by_hash(&self, hash: &H256) -> Option<&Block> {
+        self.blocks.iter().find(|b| b.hash() == *hash)
+    }
+
+    /// 检查区块接在链头之后，返回父区块头，链为空时返回 `None`
+    ///
+    /// 空链的创世区块不保存在链上，第一个区块须为高度 1。
+    pub fn check_link(&self, header: &BlockHeader) -> Result<Option<&BlockHeader>, LinkError> {
+        let Some(head) = self.latest_block() else {
+            if header.number != 1 {
+                return Err(LinkError::UnexpectedNumber {
+                    number: header.number,
+                    expected: 1,
+                });
+            }
+            return Ok(None);
+        };
+        let head_hash = head.hash();
+        if header.parent_hash != head_hash {
+            return Err(match self.get_block_by_hash(&header.parent_hash) {
+                Some(_) => LinkError::NotHead {
+                    parent: header.parent_hash,
+                    head: head_hash,
+                },
+                None => LinkError::UnknownParent(header.parent_hash),
+            });
+        }
+        if header.number != head.header.number + 1 {
+            return Err(LinkError::UnexpectedNumber {
+                number: header.number,
+                expected: head.header.number + 1,
+            });
+        }
+        Ok(Some(&head.header))
+    }
+
+    /// 根据交易哈希查找交易所在区块及其索引
+    pub fn find_transaction(&self, tx_hash: &H256) -> Option<(&Block, usize)> {
+        self.blocks.iter().find_map(|block| {
+            block
+                .transactions
+                .iter()
+                .position(|tx| tx.hash == *tx_hash)
+                .map(|index| (block, index))
+        })
+    }
+
+    /// 时间戳在 `[from, to]` 内的区块高度范围
+    pub fn block_range_by_time(&self, from: u64, to: u64) -> Option<(u64, u64)> {
+        self.timestamps.block_range(from, to)
+    }
+
     /// 获取最新区块
     pub fn latest_block(&self) -> Option<&Block> {
         self.blocks.last()
@@ -107,10 +430,15 @@ impl Default for Blockchain {
                         timestamp: 0,
                         transactions_root: H256::zero(),
                         state_root: H256::zero(),
+                        receipts_root: H256::zero(),
                         difficulty: 0,
                         block_reward: 0,
+                        proposer: None,
+                        arrivals_root: H256::zero(),
                     },
                     transactions: Vec::new(),
+                    arrivals: Vec::new(),
+                    acceptance: None,
                 },
                 block_time: 1,
                 max_block_size: 1024 * 1024,
@@ -120,6 +448,88 @@ impl Default for Blockchain {
             },
             current_block: None,
             blocks: Vec::new(),
+            timestamps: TimestampIndex::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_index() {
+        let mut index = TimestampIndex::default();
+        for (number, timestamp) in [(1, 100), (2, 110), (3, 110), (4, 105), (5, 130)] {
+            index.insert(number, timestamp);
+        }
+        assert_eq!(index.block_range(100, 110), Some((1, 4)));
+        assert_eq!(index.block_range(111, 129), None);
+        assert_eq!(index.block_range(0, 99), None);
+        assert_eq!(index.block_range(120, u64::MAX), Some((5, 5)));
+        assert_eq!(index.block_range(0, u64::MAX), Some((1, 5)));
+    }
+
+    fn header(timestamp: u64) -> BlockHeader {
+        BlockHeader {
+            parent_hash: H256::zero(),
+            number: 1,
+            timestamp,
+            transactions_root: H256::zero(),
+            state_root: H256::zero(),
+            receipts_root: H256::zero(),
+            difficulty: 0,
+            block_reward: 0,
+            proposer: None,
+            arrivals_root: H256::zero(),
+        }
+    }
+
+    #[test]
+    fn test_timestamp_policy() {
+        let policy = TimestampPolicy {
+            min_block_interval: 2,
+            max_drift: 15,
+        };
+        let parent = header(100);
+        let now = 200;
+
+        assert!(policy.validate(&header(102), Some(&parent), now).is_ok());
+        assert_eq!(
+            policy.validate(&header(100), Some(&parent), now),
+            Err(TimestampError::NotIncreasing {
+                timestamp: 100,
+                parent: 100
+            })
+        );
+        assert!(matches!(
+            policy.validate(&header(99), Some(&parent), now),
+            Err(TimestampError::NotIncreasing { .. })
+        ));
+        assert_eq!(
+            policy.validate(&header(101), Some(&parent), now),
+            Err(TimestampError::TooSoon {
+                interval: 1,
+                min: 2
+            })
+        );
+
+        // 时钟偏差边界
+        assert!(policy.validate(&header(215), Some(&parent), now).is_ok());
+        assert_eq!(
+            policy.validate(&header(216), Some(&parent), now),
+            Err(TimestampError::TooFarInFuture {
+                timestamp: 216,
+                now: 200,
+                max_drift: 15
+            })
+        );
+        assert!(policy.validate(&header(216), None, now).is_err());
+        assert!(policy.validate(&header(1), None, now).is_ok());
+
+        // 默认不限制最小间隔，但仍要求严格递增
+        let policy = TimestampPolicy::default();
+        assert!(policy.validate(&header(101), Some(&parent), now).is_ok());
+        assert!(policy.validate(&header(100), Some(&parent), now).is_err());
+    }
+}
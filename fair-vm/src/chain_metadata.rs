@@ -0,0 +1,166 @@
+//! 钱包接入用的链元数据
+//!
+//! 生成 EIP-3085 `wallet_addEthereumChain` 所需的参数，浏览器钱包（包括 MetaMask Snap）
+//! 可直接使用返回结果添加 FairVM 链。
+
+use fair_vm_core::config::Config;
+use serde::{Deserialize, Serialize};
+
+/// 默认原生代币名称
+pub const DEFAULT_CURRENCY_NAME: &str = "Fair";
+
+/// 默认原生代币符号
+pub const DEFAULT_CURRENCY_SYMBOL: &str = "FAIR";
+
+/// EIP-3085 要求的原生代币精度
+pub const NATIVE_CURRENCY_DECIMALS: u8 = 18;
+
+/// 原生代币信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NativeCurrency {
+    /// 名称
+    pub name: String,
+    /// 符号（2-6 个字符）
+    pub symbol: String,
+    /// 精度
+    pub decimals: u8,
+}
+
+impl Default for NativeCurrency {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_CURRENCY_NAME.to_string(),
+            symbol: DEFAULT_CURRENCY_SYMBOL.to_string(),
+            decimals: NATIVE_CURRENCY_DECIMALS,
+        }
+    }
+}
+
+/// 节点配置中与钱包展示相关的网络信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkMetadata {
+    /// 链名称
+    pub chain_name: String,
+    /// 原生代币
+    pub native_currency: NativeCurrency,
+    /// 对外公开的 RPC 地址，为空时使用节点监听地址
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
+    /// 区块浏览器地址
+    #[serde(default)]
+    pub block_explorer_urls: Vec<String>,
+    /// 图标地址
+    #[serde(default)]
+    pub icon_urls: Vec<String>,
+}
+
+impl Default for NetworkMetadata {
+    fn default() -> Self {
+        Self {
+            chain_name: "FairVM".to_string(),
+            native_currency: NativeCurrency::default(),
+            rpc_urls: Vec::new(),
+            block_explorer_urls: Vec::new(),
+            icon_urls: Vec::new(),
+        }
+    }
+}
+
+/// EIP-3085 `AddEthereumChainParameter`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainMetadata {
+    /// 十六进制链 ID，如 `0x7e7`
+    pub chain_id: String,
+    /// 链名称
+    pub chain_name: String,
+    /// RPC 地址
+    pub rpc_urls: Vec<String>,
+    /// 原生代币
+    pub native_currency: NativeCurrency,
+    /// 区块浏览器地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_explorer_urls: Option<Vec<String>>,
+    /// 图标地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_urls: Option<Vec<String>>,
+}
+
+impl ChainMetadata {
+    /// 根据节点配置生成链元数据
+    pub fn from_config(chain_id: u64, config: &Config, metadata: &NetworkMetadata) -> Self {
+        let rpc_urls = if metadata.rpc_urls.is_empty() {
+            vec![format!("http://{}", config.get_network_addr())]
+        } else {
+            metadata.rpc_urls.clone()
+        };
+        let non_empty = |urls: &Vec<String>| (!urls.is_empty()).then(|| urls.clone());
+
+        Self {
+            chain_id: format!("0x{:x}", chain_id),
+            chain_name: metadata.chain_name.clone(),
+            rpc_urls,
+            native_currency: metadata.native_currency.clone(),
+            block_explorer_urls: non_empty(&metadata.block_explorer_urls),
+            icon_urls: non_empty(&metadata.icon_urls),
+        }
+    }
+
+    /// 按 EIP-3085 的约束校验参数
+    pub fn validate(&self) -> Result<(), String> {
+        let chain_id = self
+            .chain_id
+            .strip_prefix("0x")
+            .and_then(|id| u64::from_str_radix(id, 16).ok())
+            .ok_or_else(|| format!("无效的链 ID: {}", self.chain_id))?;
+        if chain_id == 0 || self.chain_id.starts_with("0x0") {
+            return Err(format!("链 ID 必须为无前导零的正数: {}", self.chain_id));
+        }
+        if self.rpc_urls.is_empty() {
+            return Err("至少需要一个 RPC 地址".to_string());
+        }
+        let symbol_len = self.native_currency.symbol.chars().count();
+        if !(2..=6).contains(&symbol_len) {
+            return Err("代币符号长度必须为 2-6 个字符".to_string());
+        }
+        if self.native_currency.decimals != NATIVE_CURRENCY_DECIMALS {
+            return Err(format!("代币精度必须为 {}", NATIVE_CURRENCY_DECIMALS));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let config = Config::default();
+        let metadata = ChainMetadata::from_config(2023, &config, &NetworkMetadata::default());
+        assert_eq!(metadata.chain_id, "0x7e7");
+        assert_eq!(metadata.rpc_urls, vec!["http://127.0.0.1:8545".to_string()]);
+        assert!(metadata.block_explorer_urls.is_none());
+        assert!(metadata.validate().is_ok());
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["nativeCurrency"]["symbol"], "FAIR");
+        assert!(json.get("blockExplorerUrls").is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut network = NetworkMetadata {
+            rpc_urls: vec!["https://rpc.fairvm.example".to_string()],
+            block_explorer_urls: vec!["https://explorer.fairvm.example".to_string()],
+            ..Default::default()
+        };
+        let metadata = ChainMetadata::from_config(1337, &Config::default(), &network);
+        assert_eq!(metadata.rpc_urls, network.rpc_urls);
+        assert!(metadata.block_explorer_urls.is_some());
+
+        network.native_currency.symbol = "F".to_string();
+        let metadata = ChainMetadata::from_config(1337, &Config::default(), &network);
+        assert!(metadata.validate().is_err());
+    }
+}
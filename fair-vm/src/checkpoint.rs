@@ -0,0 +1,988 @@
+//! 从可信检查点启动节点
+//!
+//! 检查点由可信签名者对区块高度、区块哈希和状态根签名。新节点从配置的数据源获取检查点，
+//! 校验签名者属于可信列表、区块头与检查点一致后，直接以该区块为链头开始运行，无需重放
+//! 历史区块。检查点之前的状态由 [`LazyStorage`] 在首次访问时下载：账户附带状态证明，
+//! 对照检查点状态根验证；代码按代码哈希验证；存储槽附带存储证明，对照已验证账户的存储根
+//! 验证，整页读取时下载账户的全部存储槽并核对存储根。
+//! 提供检查点的节点用 [`CheckpointSnapshot`] 冻结当时的状态，保证之后出块不影响下载。
+
+use crate::account::{Account, Address};
+use crate::blockchain::BlockHeader;
+use crate::light::RpcLightSource;
+use crate::merkle;
+use crate::receipt::Receipt;
+use crate::signing;
+use crate::state_proof::{ProofError, StateCommitment, StateProof, StorageProof};
+use crate::storage::{code_hash, MemoryStorage, Storage, StorageEntry};
+use async_trait::async_trait;
+use ethers::types::{Bytes, H160, H256, U256};
+use futures::StreamExt;
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha3::{Digest, Keccak256};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+
+/// 数据源单次返回的最大条目数
+pub const MAX_CHECKPOINT_PAGE: usize = 1024;
+
+/// 检查点错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum CheckpointError {
+    #[error("未配置检查点同步")]
+    NotConfigured,
+
+    #[error("数据源错误: {0}")]
+    Source(String),
+
+    #[error("检查点签名无效: {0}")]
+    InvalidSignature(String),
+
+    #[error("检查点签名者 {0} 不在可信列表中")]
+    UntrustedSigner(Address),
+
+    #[error("区块头 {number} 与检查点不一致: {reason}")]
+    HeaderMismatch { number: u64, reason: String },
+
+    #[error("代码 {0:?} 与代码哈希不一致")]
+    CodeMismatch(H256),
+
+    #[error("账户 {0} 的存储槽与存储根不一致")]
+    StorageMismatch(Address),
+
+    #[error("账户证明无效: {0}")]
+    Proof(#[from] ProofError),
+}
+
+/// 签名检查点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub number: u64,
+    pub block_hash: H256,
+    pub state_root: H256,
+    /// 签名者对 [`Checkpoint::digest`] 的可恢复签名
+    pub signature: Bytes,
+}
+
+impl Checkpoint {
+    /// 签名摘要
+    pub fn digest(number: u64, block_hash: H256, state_root: H256) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(b"fairvm-checkpoint");
+        hasher.update(number.to_be_bytes());
+        hasher.update(block_hash.as_bytes());
+        hasher.update(state_root.as_bytes());
+        hasher.finalize().into()
+    }
+
+    /// 为区块头签发检查点
+    pub fn sign(header: &BlockHeader, secret_key: &SecretKey) -> Result<Self, CheckpointError> {
+        let digest = Self::digest(header.number, header.hash(), header.state_root);
+        let (signature, _) =
+            signing::sign_digest(secret_key, digest).map_err(CheckpointError::InvalidSignature)?;
+        Ok(Self {
+            number: header.number,
+            block_hash: header.hash(),
+            state_root: header.state_root,
+            signature: signature.into(),
+        })
+    }
+
+    /// 恢复签名者地址
+    pub fn signer(&self) -> Result<Address, CheckpointError> {
+        let digest = Self::digest(self.number, self.block_hash, self.state_root);
+        signing::recover_signer(digest, &self.signature).map_err(CheckpointError::InvalidSignature)
+    }
+
+    /// 校验签名者属于可信列表，返回签名者
+    pub fn verify(&self, trusted_signers: &[Address]) -> Result<Address, CheckpointError> {
+        let signer = self.signer()?;
+        if !trusted_signers.contains(&signer) {
+            return Err(CheckpointError::UntrustedSigner(signer));
+        }
+        Ok(signer)
+    }
+
+    /// 校验区块头与检查点一致
+    pub fn check_header(&self, header: &BlockHeader) -> Result<(), CheckpointError> {
+        let mismatch = |reason: &str| CheckpointError::HeaderMismatch {
+            number: header.number,
+            reason: reason.to_string(),
+        };
+        if header.number != self.number {
+            return Err(mismatch("高度不一致"));
+        }
+        if header.hash() != self.block_hash {
+            return Err(mismatch("区块哈希不一致"));
+        }
+        if header.state_root != self.state_root {
+            return Err(mismatch("状态根不一致"));
+        }
+        Ok(())
+    }
+}
+
+/// 解析配置中的可信签名者地址
+pub fn parse_signers(signers: &[String]) -> Result<Vec<Address>, CheckpointError> {
+    signers
+        .iter()
+        .map(|signer| {
+            signer
+                .trim()
+                .parse::<H160>()
+                .map(Address::from)
+                .map_err(|e| CheckpointError::Source(format!("无效的签名者地址 {}: {}", signer, e)))
+        })
+        .collect()
+}
+
+/// 检查点及其之前状态的数据源，返回的数据均对应检查点时的状态
+#[async_trait]
+pub trait CheckpointSource: Send + Sync + std::fmt::Debug {
+    /// 最新的签名检查点
+    async fn checkpoint(&self) -> Result<Checkpoint, CheckpointError>;
+
+    /// 检查点区块头
+    async fn header(&self, number: u64) -> Result<BlockHeader, CheckpointError>;
+
+    /// 账户证明
+    async fn account_proof(&self, address: &Address) -> Result<StateProof, CheckpointError>;
+
+    /// 按地址升序返回 `after` 之后（不含）的至多 `limit` 个账户地址
+    async fn accounts_after(
+        &self,
+        after: Option<Address>,
+        limit: usize,
+    ) -> Result<Vec<Address>, CheckpointError>;
+
+    /// 按代码哈希获取代码
+    async fn code(&self, code_hash: H256) -> Result<Option<Vec<u8>>, CheckpointError>;
+
+    /// 存储槽证明
+    async fn storage_proof(
+        &self,
+        address: &Address,
+        key: [u8; 32],
+    ) -> Result<StorageProof, CheckpointError>;
+
+    /// 按键升序返回账户中 `after` 之后（不含）的至多 `limit` 个存储槽
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Result<Vec<StorageEntry>, CheckpointError>;
+}
+
+/// 冻结的检查点状态，供其他节点下载
+#[derive(Debug)]
+pub struct CheckpointSnapshot {
+    checkpoint: Checkpoint,
+    header: BlockHeader,
+    storage: MemoryStorage,
+    commitment: StateCommitment,
+}
+
+impl CheckpointSnapshot {
+    /// 复制当前状态并为区块头签发检查点，区块头的状态根须承诺该状态
+    pub async fn capture(
+        storage: &(dyn Storage + Send + Sync),
+        header: BlockHeader,
+        secret_key: &SecretKey,
+    ) -> Result<Self, CheckpointError> {
+        let mut snapshot = MemoryStorage::default();
+        let mut accounts: Vec<Account> = storage.iter_accounts().collect().await;
+        for account in &mut accounts {
+            let entries: Vec<StorageEntry> = storage.iter_storage(&account.address).collect().await;
+            account.storage_root = merkle::storage_root(&entries);
+            snapshot.set_account(account).await;
+            let code = storage.get_code(&account.address).await;
+            if !code.is_empty() {
+                snapshot.set_code(&account.address, code).await;
+            }
+            for (key, value) in entries {
+                snapshot
+                    .set_storage_value(&account.address, key, value)
+                    .await;
+            }
+        }
+        let commitment = StateCommitment::new(accounts);
+        if commitment.root() != header.state_root {
+            return Err(CheckpointError::HeaderMismatch {
+                number: header.number,
+                reason: "区块头状态根未承诺当前状态".into(),
+            });
+        }
+        Ok(Self {
+            checkpoint: Checkpoint::sign(&header, secret_key)?,
+            header,
+            storage: snapshot,
+            commitment,
+        })
+    }
+
+    /// 签名检查点
+    pub fn checkpoint(&self) -> &Checkpoint {
+        &self.checkpoint
+    }
+
+    /// 检查点区块头
+    pub fn header(&self) -> &BlockHeader {
+        &self.header
+    }
+
+    /// 账户证明
+    pub fn prove(&self, address: &Address) -> StateProof {
+        self.commitment.prove(address)
+    }
+
+    /// 冻结的状态
+    pub fn storage(&self) -> &MemoryStorage {
+        &self.storage
+    }
+}
+
+#[async_trait]
+impl CheckpointSource for CheckpointSnapshot {
+    async fn checkpoint(&self) -> Result<Checkpoint, CheckpointError> {
+        Ok(self.checkpoint.clone())
+    }
+
+    async fn header(&self, number: u64) -> Result<BlockHeader, CheckpointError> {
+        if number != self.header.number {
+            return Err(CheckpointError::Source(format!("没有区块头 {}", number)));
+        }
+        Ok(self.header.clone())
+    }
+
+    async fn account_proof(&self, address: &Address) -> Result<StateProof, CheckpointError> {
+        Ok(self.prove(address))
+    }
+
+    async fn accounts_after(
+        &self,
+        after: Option<Address>,
+        limit: usize,
+    ) -> Result<Vec<Address>, CheckpointError> {
+        Ok(self
+            .storage
+            .accounts_page(after, limit.min(MAX_CHECKPOINT_PAGE))
+            .await
+            .into_iter()
+            .map(|account| account.address)
+            .collect())
+    }
+
+    async fn code(&self, code_hash: H256) -> Result<Option<Vec<u8>>, CheckpointError> {
+        Ok(self.storage.get_code_by_hash(&code_hash).await)
+    }
+
+    async fn storage_proof(
+        &self,
+        address: &Address,
+        key: [u8; 32],
+    ) -> Result<StorageProof, CheckpointError> {
+        let entries: Vec<StorageEntry> = self.storage.iter_storage(address).collect().await;
+        Ok(StorageProof::prove(&entries, key))
+    }
+
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Result<Vec<StorageEntry>, CheckpointError> {
+        Ok(self
+            .storage
+            .storage_page(address, after, limit.min(MAX_CHECKPOINT_PAGE))
+            .await)
+    }
+}
+
+/// 通过 JSON-RPC 访问提供检查点的节点
+#[derive(Debug, Clone)]
+pub struct RpcCheckpointSource {
+    rpc: RpcLightSource,
+}
+
+impl RpcCheckpointSource {
+    /// 创建数据源
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            rpc: RpcLightSource::new(url),
+        }
+    }
+
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, CheckpointError> {
+        self.rpc
+            .call(method, params)
+            .await
+            .map_err(|e| CheckpointError::Source(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl CheckpointSource for RpcCheckpointSource {
+    async fn checkpoint(&self) -> Result<Checkpoint, CheckpointError> {
+        let checkpoint: Option<Checkpoint> = self.call("fairvm_getCheckpoint", json!([])).await?;
+        checkpoint.ok_or_else(|| CheckpointError::Source("数据源尚未发布检查点".into()))
+    }
+
+    async fn header(&self, number: u64) -> Result<BlockHeader, CheckpointError> {
+        let headers: Vec<BlockHeader> = self.call("fairvm_getHeaders", json!([number, 1])).await?;
+        headers
+            .into_iter()
+            .next()
+            .ok_or_else(|| CheckpointError::Source(format!("没有区块头 {}", number)))
+    }
+
+    async fn account_proof(&self, address: &Address) -> Result<StateProof, CheckpointError> {
+        self.call("fairvm_getCheckpointProof", json!([H160::from(*address)]))
+            .await
+    }
+
+    async fn accounts_after(
+        &self,
+        after: Option<Address>,
+        limit: usize,
+    ) -> Result<Vec<Address>, CheckpointError> {
+        let addresses: Vec<H160> = self
+            .call(
+                "fairvm_getCheckpointAccounts",
+                json!([after.map(H160::from), limit]),
+            )
+            .await?;
+        Ok(addresses.into_iter().map(Address::from).collect())
+    }
+
+    async fn code(&self, code_hash: H256) -> Result<Option<Vec<u8>>, CheckpointError> {
+        let code: Option<Bytes> = self
+            .call("fairvm_getCheckpointCode", json!([code_hash]))
+            .await?;
+        Ok(code.map(|code| code.to_vec()))
+    }
+
+    async fn storage_proof(
+        &self,
+        address: &Address,
+        key: [u8; 32],
+    ) -> Result<StorageProof, CheckpointError> {
+        self.call(
+            "fairvm_getCheckpointStorageProof",
+            json!([H160::from(*address), H256(key)]),
+        )
+        .await
+    }
+
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Result<Vec<StorageEntry>, CheckpointError> {
+        let entries: Vec<(H256, H256)> = self
+            .call(
+                "fairvm_getCheckpointStorage",
+                json!([H160::from(*address), after.map(H256), limit]),
+            )
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(key, value)| (key.0, value.0))
+            .collect())
+    }
+}
+
+#[derive(Debug, Default)]
+struct LazyState {
+    local: MemoryStorage,
+    /// 已下载或已在本地写入的账户
+    accounts: HashSet<Address>,
+    /// 已下载或已在本地写入的存储槽
+    slots: HashSet<(Address, [u8; 32])>,
+    /// 已下载全部存储槽的账户
+    storages: HashSet<Address>,
+    /// 已验证的账户在检查点时的存储根
+    storage_roots: HashMap<Address, H256>,
+    /// 上次取出以来第一次下载失败的原因
+    read_error: Option<String>,
+}
+
+/// 按需从检查点数据源下载状态的存储
+///
+/// 下载失败时按本地状态返回并记录原因，下次访问时重试；执行区块时经
+/// [`Storage::take_read_error`] 取出原因并中止执行，不会用缺失的状态产生结果。
+#[derive(Debug)]
+pub struct LazyStorage<S: CheckpointSource> {
+    source: S,
+    state_root: H256,
+    state: RwLock<LazyState>,
+}
+
+impl<S: CheckpointSource> LazyStorage<S> {
+    /// 创建存储，下载的账户对照 `state_root` 验证
+    pub fn new(source: S, state_root: H256) -> Self {
+        Self {
+            source,
+            state_root,
+            state: RwLock::new(LazyState::default()),
+        }
+    }
+
+    /// 已下载或已写入的账户数量
+    pub async fn loaded_accounts(&self) -> usize {
+        self.state.read().await.accounts.len()
+    }
+
+    /// 下载全部账户，返回本次下载的账户数量
+    pub async fn download_all(&self) -> Result<usize, CheckpointError> {
+        let mut downloaded = 0;
+        let mut after = None;
+        loop {
+            let page = self
+                .source
+                .accounts_after(after, MAX_CHECKPOINT_PAGE)
+                .await?;
+            for address in &page {
+                if self.load_account(address).await? {
+                    downloaded += 1;
+                }
+            }
+            match page.last() {
+                Some(last) if page.len() >= MAX_CHECKPOINT_PAGE => after = Some(*last),
+                _ => return Ok(downloaded),
+            }
+        }
+    }
+
+    /// 下载账户证明并对照检查点状态根验证，同时记下账户的存储根
+    async fn verified_account(
+        &self,
+        address: &Address,
+    ) -> Result<Option<Account>, CheckpointError> {
+        let proof = self.source.account_proof(address).await?;
+        if proof.address != *address {
+            return Err(ProofError::AddressMismatch(proof.address).into());
+        }
+        let account = proof.verify(self.state_root)?;
+        let storage_root = account
+            .as_ref()
+            .map_or_else(H256::zero, |account| account.storage_root);
+        self.state
+            .write()
+            .await
+            .storage_roots
+            .insert(*address, storage_root);
+        Ok(account)
+    }
+
+    /// 账户在检查点时的存储根
+    async fn storage_root(&self, address: &Address) -> Result<H256, CheckpointError> {
+        if let Some(root) = self.state.read().await.storage_roots.get(address) {
+            return Ok(*root);
+        }
+        // 本地写入过的账户没有下载过证明
+        let account = self.verified_account(address).await?;
+        Ok(account.map_or_else(H256::zero, |account| account.storage_root))
+    }
+
+    /// 下载并验证账户，已在本地时返回 false
+    pub async fn load_account(&self, address: &Address) -> Result<bool, CheckpointError> {
+        if self.state.read().await.accounts.contains(address) {
+            return Ok(false);
+        }
+        let account = self.verified_account(address).await?;
+        let code = match &account {
+            Some(account) if !account.code_hash.is_zero() => {
+                let code = self
+                    .source
+                    .code(account.code_hash)
+                    .await?
+                    .unwrap_or_default();
+                if code_hash(&code) != account.code_hash {
+                    return Err(CheckpointError::CodeMismatch(account.code_hash));
+                }
+                code
+            }
+            _ => Vec::new(),
+        };
+
+        let mut state = self.state.write().await;
+        // 下载期间本地可能已写入该账户
+        if !state.accounts.insert(*address) {
+            return Ok(false);
+        }
+        if let Some(account) = account {
+            state.local.set_account(&account).await;
+            if !code.is_empty() {
+                state.local.set_code(address, code).await;
+            }
+        }
+        Ok(true)
+    }
+
+    /// 下载存储槽证明并对照账户的存储根验证
+    pub async fn load_slot(&self, address: &Address, key: [u8; 32]) -> Result<(), CheckpointError> {
+        if self.state.read().await.slots.contains(&(*address, key)) {
+            return Ok(());
+        }
+        let storage_root = self.storage_root(address).await?;
+        let proof = self.source.storage_proof(address, key).await?;
+        if proof.key.0 != key {
+            return Err(ProofError::Malformed("证明的存储槽与查询的键不一致".into()).into());
+        }
+        let value = proof.verify(storage_root)?;
+        let mut state = self.state.write().await;
+        if state.slots.insert((*address, key)) {
+            state.local.set_storage_value(address, key, value).await;
+        }
+        Ok(())
+    }
+
+    /// 下载账户的全部存储槽并核对存储根
+    pub async fn load_storage(&self, address: &Address) -> Result<(), CheckpointError> {
+        if self.state.read().await.storages.contains(address) {
+            return Ok(());
+        }
+        let storage_root = self.storage_root(address).await?;
+        let mut entries: Vec<StorageEntry> = Vec::new();
+        loop {
+            let after = entries.last().map(|entry| entry.0);
+            let page = self
+                .source
+                .storage_page(address, after, MAX_CHECKPOINT_PAGE)
+                .await?;
+            let done = page.len() < MAX_CHECKPOINT_PAGE;
+            entries.extend(page);
+            if done {
+                break;
+            }
+        }
+        if merkle::storage_root(&entries) != storage_root {
+            return Err(CheckpointError::StorageMismatch(*address));
+        }
+        let mut state = self.state.write().await;
+        if state.storages.insert(*address) {
+            for (key, value) in entries {
+                if state.slots.insert((*address, key)) {
+                    state.local.set_storage_value(address, key, value).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 记录下载失败的原因，保留第一次失败
+    async fn record_error(&self, error: String) {
+        log::warn!("{}", error);
+        self.state.write().await.read_error.get_or_insert(error);
+    }
+
+    async fn ensure_account(&self, address: &Address) {
+        if let Err(e) = self.load_account(address).await {
+            self.record_error(format!("下载账户 {} 失败: {}", address, e))
+                .await;
+        }
+    }
+
+    async fn ensure_slot(&self, address: &Address, key: [u8; 32]) {
+        if let Err(e) = self.load_slot(address, key).await {
+            self.record_error(format!("下载账户 {} 的存储槽失败: {}", address, e))
+                .await;
+        }
+    }
+}
+
+#[async_trait]
+impl<S: CheckpointSource> Storage for LazyStorage<S> {
+    async fn get_account(&self, address: &Address) -> Option<Account> {
+        self.ensure_account(address).await;
+        self.state.read().await.local.get_account(address).await
+    }
+
+    async fn set_account(&mut self, account: &Account) {
+        let state = self.state.get_mut();
+        state.accounts.insert(account.address);
+        state.local.set_account(account).await;
+    }
+
+    async fn get_balance(&self, address: &Address) -> U256 {
+        self.ensure_account(address).await;
+        self.state.read().await.local.get_balance(address).await
+    }
+
+    async fn set_balance(&mut self, address: &Address, balance: U256) {
+        self.ensure_account(address).await;
+        self.state
+            .get_mut()
+            .local
+            .set_balance(address, balance)
+            .await;
+    }
+
+    async fn get_nonce(&self, address: &Address) -> u64 {
+        self.ensure_account(address).await;
+        self.state.read().await.local.get_nonce(address).await
+    }
+
+    async fn set_nonce(&mut self, address: &Address, nonce: u64) {
+        self.ensure_account(address).await;
+        self.state.get_mut().local.set_nonce(address, nonce).await;
+    }
+
+    async fn get_code_hash(&self, address: &Address) -> H256 {
+        self.ensure_account(address).await;
+        self.state.read().await.local.get_code_hash(address).await
+    }
+
+    async fn set_code_hash(&mut self, address: &Address, code_hash: H256) {
+        self.ensure_account(address).await;
+        self.state
+            .get_mut()
+            .local
+            .set_code_hash(address, code_hash)
+            .await;
+    }
+
+    async fn get_storage_root(&self, address: &Address) -> H256 {
+        self.ensure_account(address).await;
+        self.state
+            .read()
+            .await
+            .local
+            .get_storage_root(address)
+            .await
+    }
+
+    async fn set_storage_root(&mut self, address: &Address, storage_root: H256) {
+        self.ensure_account(address).await;
+        self.state
+            .get_mut()
+            .local
+            .set_storage_root(address, storage_root)
+            .await;
+    }
+
+    async fn get_storage_value(&self, address: &Address, key: [u8; 32]) -> [u8; 32] {
+        self.ensure_slot(address, key).await;
+        self.state
+            .read()
+            .await
+            .local
+            .get_storage_value(address, key)
+            .await
+    }
+
+    async fn set_storage_value(&mut self, address: &Address, key: [u8; 32], value: [u8; 32]) {
+        let state = self.state.get_mut();
+        state.slots.insert((*address, key));
+        state.local.set_storage_value(address, key, value).await;
+    }
+
+    async fn get_code(&self, address: &Address) -> Vec<u8> {
+        self.ensure_account(address).await;
+        self.state.read().await.local.get_code(address).await
+    }
+
+    async fn set_code(&mut self, address: &Address, code: Vec<u8>) -> H256 {
+        self.ensure_account(address).await;
+        self.state.get_mut().local.set_code(address, code).await
+    }
+
+    async fn get_code_by_hash(&self, code_hash: &H256) -> Option<Vec<u8>> {
+        if let Some(code) = self
+            .state
+            .read()
+            .await
+            .local
+            .get_code_by_hash(code_hash)
+            .await
+        {
+            return Some(code);
+        }
+        match self.source.code(*code_hash).await {
+            Ok(Some(code)) if self::code_hash(&code) == *code_hash => Some(code),
+            Ok(_) => None,
+            Err(e) => {
+                self.record_error(format!("下载代码 {:?} 失败: {}", code_hash, e))
+                    .await;
+                None
+            }
+        }
+    }
+
+    async fn accounts_page(&self, after: Option<Address>, limit: usize) -> Vec<Account> {
+        // 先下载数据源中对应的一页账户，再与本地新建的账户合并分页
+        match self.source.accounts_after(after, limit).await {
+            Ok(addresses) => {
+                for address in &addresses {
+                    self.ensure_account(address).await;
+                }
+            }
+            Err(e) => self.record_error(format!("下载账户列表失败: {}", e)).await,
+        }
+        self.state
+            .read()
+            .await
+            .local
+            .accounts_page(after, limit)
+            .await
+    }
+
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Vec<StorageEntry> {
+        if let Err(e) = self.load_storage(address).await {
+            self.record_error(format!("下载账户 {} 的存储槽失败: {}", address, e))
+                .await;
+        }
+        self.state
+            .read()
+            .await
+            .local
+            .storage_page(address, after, limit)
+            .await
+    }
+
+    // 检查点只包含状态，收据只保存从检查点之后执行的区块
+    async fn get_receipt(&self, tx_hash: &H256) -> Option<Receipt> {
+        self.state.read().await.local.get_receipt(tx_hash).await
+    }
+
+    async fn block_receipts(&self, number: u64) -> Vec<Receipt> {
+        self.state.read().await.local.block_receipts(number).await
+    }
+
+    async fn set_receipt(&mut self, receipt: &Receipt) {
+        self.state.get_mut().local.set_receipt(receipt).await
+    }
+
+    async fn take_read_error(&self) -> Option<String> {
+        self.state.write().await.read_error.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_key() -> SecretKey {
+        SecretKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    fn signer() -> Address {
+        let secp = secp256k1::Secp256k1::new();
+        Address::from_public_key(&secp256k1::PublicKey::from_secret_key(&secp, &secret_key()))
+    }
+
+    async fn snapshot() -> CheckpointSnapshot {
+        let mut storage = MemoryStorage::default();
+        for i in 1..=3u8 {
+            let mut account = Account::new(Address([i; 20]));
+            account.balance = U256::from(i as u64 * 100);
+            storage.set_account(&account).await;
+        }
+        storage.set_code(&Address([2; 20]), vec![0x60, 0x00]).await;
+        storage
+            .set_storage_value(&Address([2; 20]), [1; 32], [9; 32])
+            .await;
+        let header = BlockHeader {
+            parent_hash: H256::repeat_byte(1),
+            number: 100,
+            timestamp: 1_000,
+            transactions_root: H256::zero(),
+            state_root: StateCommitment::from_storage(&storage).await.root(),
+            receipts_root: H256::zero(),
+            difficulty: 0,
+            block_reward: 0,
+            proposer: None,
+            arrivals_root: H256::zero(),
+        };
+        CheckpointSnapshot::capture(&storage, header, &secret_key())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_signature() {
+        let snapshot = snapshot().await;
+        let checkpoint = snapshot.checkpoint().clone();
+        assert_eq!(checkpoint.verify(&[signer()]).unwrap(), signer());
+        assert!(matches!(
+            checkpoint.verify(&[Address([1; 20])]),
+            Err(CheckpointError::UntrustedSigner(_))
+        ));
+        checkpoint.check_header(snapshot.header()).unwrap();
+
+        let mut forged = checkpoint.clone();
+        forged.state_root = H256::repeat_byte(2);
+        assert!(forged.verify(&[signer()]).is_err());
+        assert!(forged.check_header(snapshot.header()).is_err());
+        assert_eq!(
+            parse_signers(&[format!("{:?}", H160::from(signer()))]).unwrap(),
+            vec![signer()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lazy_storage() {
+        let snapshot = snapshot().await;
+        let root = snapshot.checkpoint().state_root;
+        let mut storage = LazyStorage::new(snapshot, root);
+        assert_eq!(storage.loaded_accounts().await, 0);
+
+        assert_eq!(
+            storage.get_balance(&Address([1; 20])).await,
+            U256::from(100)
+        );
+        assert_eq!(storage.get_code(&Address([2; 20])).await, vec![0x60, 0x00]);
+        assert_eq!(
+            storage.get_storage_value(&Address([2; 20]), [1; 32]).await,
+            [9; 32]
+        );
+        assert!(storage.get_account(&Address([9; 20])).await.is_none());
+        assert_eq!(storage.loaded_accounts().await, 3);
+
+        // 本地写入不会被之后的下载覆盖
+        storage.set_balance(&Address([3; 20]), U256::from(1)).await;
+        storage.set_account(&Account::new(Address([4; 20]))).await;
+        assert_eq!(storage.download_all().await.unwrap(), 0);
+        assert_eq!(storage.get_balance(&Address([3; 20])).await, U256::from(1));
+        assert_eq!(storage.accounts_page(None, 10).await.len(), 4);
+        // 整页读取下载账户全部存储槽并核对存储根
+        assert_eq!(
+            storage.storage_page(&Address([2; 20]), None, 10).await,
+            vec![([1; 32], [9; 32])]
+        );
+        assert!(storage.take_read_error().await.is_none());
+
+        // 状态根不一致的账户不会被接受
+        let other = LazyStorage::new(self::snapshot().await, H256::repeat_byte(5));
+        assert!(matches!(
+            other.load_account(&Address([1; 20])).await,
+            Err(CheckpointError::Proof(ProofError::RootMismatch { .. }))
+        ));
+        assert!(other.get_account(&Address([1; 20])).await.is_none());
+        // 存储槽同样须经账户的存储根验证，下载失败的原因留待执行区块时取出
+        assert!(matches!(
+            other.load_slot(&Address([2; 20]), [1; 32]).await,
+            Err(CheckpointError::Proof(ProofError::RootMismatch { .. }))
+        ));
+        assert_eq!(
+            other.get_storage_value(&Address([2; 20]), [1; 32]).await,
+            [0; 32]
+        );
+        assert!(other.take_read_error().await.is_some());
+        assert!(other.take_read_error().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_start_from_checkpoint() {
+        use crate::blockchain::Block;
+        use crate::FairVM;
+        use fair_vm_core::config::{CheckpointConfig, Config};
+
+        let vm = FairVM::new();
+        let funded = Address([1; 20]);
+        let state = vm.state();
+        state
+            .read()
+            .await
+            .set_balance(&funded, U256::from(100))
+            .await
+            .unwrap();
+        let header = BlockHeader {
+            parent_hash: H256::zero(),
+            number: 1,
+            timestamp: 1,
+            transactions_root: H256::zero(),
+            state_root: vm.state_commitment().await.root(),
+            receipts_root: H256::zero(),
+            difficulty: 0,
+            block_reward: 0,
+            proposer: None,
+            arrivals_root: H256::zero(),
+        };
+        vm.accept_block(
+            Block {
+                header: header.clone(),
+                transactions: Vec::new(),
+                arrivals: Vec::new(),
+                acceptance: None,
+            },
+            U256::zero(),
+        )
+        .await
+        .unwrap();
+        let checkpoint = vm.publish_checkpoint(&secret_key()).await.unwrap();
+        assert_eq!(checkpoint.block_hash, header.hash());
+
+        let storage = state.read().await.storage().clone();
+        let source = CheckpointSnapshot::capture(&storage, header, &secret_key())
+            .await
+            .unwrap();
+        // 发布后的状态变化不影响检查点
+        state
+            .read()
+            .await
+            .set_balance(&funded, U256::from(70))
+            .await
+            .unwrap();
+
+        let config = |signer: Address| Config {
+            checkpoint: Some(CheckpointConfig {
+                url: "http://127.0.0.1:8545".to_string(),
+                trusted_signers: vec![format!("{:?}", H160::from(signer))],
+            }),
+            ..Config::default()
+        };
+        let node = FairVM::from_checkpoint(config(signer()), source)
+            .await
+            .unwrap();
+        let latest = node
+            .blockchain()
+            .read()
+            .await
+            .latest_block()
+            .unwrap()
+            .hash();
+        assert_eq!(latest, checkpoint.block_hash);
+        assert_eq!(
+            node.state().read().await.get_balance(&funded).await,
+            U256::from(100)
+        );
+
+        let source = {
+            let header = vm
+                .blockchain()
+                .read()
+                .await
+                .latest_block()
+                .unwrap()
+                .header
+                .clone();
+            CheckpointSnapshot::capture(&storage, header, &secret_key()).await
+        };
+        // 状态已变化，区块头的状态根不再承诺当前状态
+        assert!(matches!(
+            source,
+            Err(CheckpointError::HeaderMismatch { .. })
+        ));
+        assert!(matches!(
+            FairVM::from_checkpoint(config(Address([1; 20])), snapshot().await).await,
+            Err(crate::FairVMError::Checkpoint(
+                CheckpointError::UntrustedSigner(_)
+            ))
+        ));
+    }
+}
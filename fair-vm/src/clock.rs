@@ -0,0 +1,287 @@
+//! 时钟偏差检测
+//!
+//! 区块时间戳校验依赖本地时钟，主机时钟未同步时区块会被判为来自未来而拒绝，表现为节点
+//! 莫名其妙地停止同步。[`ClockMonitor`] 收集对等节点握手时报告的时间和新区块的时间戳，
+//! 估计本地时钟的偏差；启动时和之后每 [`CLOCK_CHECK_INTERVAL`] 检查一次，偏差超过
+//! 阈值时输出警告，当前状态可通过 `admin_clockStatus` 查询。
+//!
+//! 区块总是在出块之后才到达，时间戳早于本地时间不能说明本地时钟超前，因此区块样本只
+//! 用于发现本地时钟落后；本地时钟超前只能由对等节点报告的时间发现。
+
+use crate::blockchain::TimestampPolicy;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+/// 定期检查的间隔
+pub const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 保留的区块样本数
+pub const MAX_BLOCK_SAMPLES: usize = 32;
+
+/// 保留的对等节点样本数
+pub const MAX_PEER_SAMPLES: usize = 64;
+
+/// 早于该时间（2024-01-01 UTC）的系统时间说明时钟从未同步过
+pub const MIN_SANE_TIMESTAMP: u64 = 1_704_067_200;
+
+/// 偏差级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkewLevel {
+    /// 没有样本或偏差在阈值内
+    Ok,
+    /// 偏差超过允许漂移的一半，继续增大会导致区块被拒绝
+    Warning,
+    /// 偏差超过允许的漂移，区块时间戳校验会失败
+    Critical,
+}
+
+/// 偏差阈值（秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkewThresholds {
+    pub warning: u64,
+    pub critical: u64,
+}
+
+impl SkewThresholds {
+    /// 按区块时间戳允许的漂移设置阈值
+    pub fn from_policy(policy: &TimestampPolicy) -> Self {
+        Self {
+            warning: policy.max_drift / 2,
+            critical: policy.max_drift,
+        }
+    }
+
+    /// 偏差对应的级别
+    pub fn level(&self, skew: i64) -> SkewLevel {
+        let skew = skew.unsigned_abs();
+        if skew > self.critical {
+            SkewLevel::Critical
+        } else if skew > self.warning {
+            SkewLevel::Warning
+        } else {
+            SkewLevel::Ok
+        }
+    }
+}
+
+/// 时钟状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockStatus {
+    /// 本地时间（Unix 秒）
+    pub local_time: u64,
+    /// 对等节点时间减本地时间的中位数（秒），正数表示本地时钟落后
+    pub peer_skew: Option<i64>,
+    pub peer_samples: usize,
+    /// 区块时间戳超前本地时间的中位数（秒）
+    pub block_skew: Option<i64>,
+    pub block_samples: usize,
+    pub level: SkewLevel,
+}
+
+impl ClockStatus {
+    /// 偏差的说明，级别为 [`SkewLevel::Ok`] 时为空
+    pub fn describe(&self) -> Option<String> {
+        if self.level == SkewLevel::Ok {
+            return None;
+        }
+        if self.local_time < MIN_SANE_TIMESTAMP {
+            return Some(format!(
+                "系统时间 {} 明显错误，请检查 NTP 同步",
+                self.local_time
+            ));
+        }
+        let skew = match (self.peer_skew, self.block_skew) {
+            (Some(peer), Some(block)) if block.abs() > peer.abs() => block,
+            (Some(peer), _) => peer,
+            (None, block) => block.unwrap_or(0),
+        };
+        Some(format!(
+            "本地时钟{} {} 秒，区块时间戳校验可能失败，请检查 NTP 同步",
+            if skew > 0 { "落后" } else { "超前" },
+            skew.unsigned_abs()
+        ))
+    }
+}
+
+/// 时钟样本
+#[derive(Debug)]
+pub struct ClockMonitor {
+    thresholds: SkewThresholds,
+    /// 各对等节点最近一次报告的偏差
+    peers: BTreeMap<String, i64>,
+    /// 最近区块超前本地时间的秒数，不超前时为 0
+    blocks: VecDeque<i64>,
+}
+
+impl ClockMonitor {
+    pub fn new(thresholds: SkewThresholds) -> Self {
+        Self {
+            thresholds,
+            peers: BTreeMap::new(),
+            blocks: VecDeque::new(),
+        }
+    }
+
+    /// 记录对等节点握手时报告的时间，`remote` 为 0 表示对方未报告
+    pub fn record_peer(&mut self, node_id: &str, remote: u64, now: u64) {
+        if remote == 0 {
+            return;
+        }
+        if !self.peers.contains_key(node_id) && self.peers.len() >= MAX_PEER_SAMPLES {
+            return;
+        }
+        self.peers.insert(node_id.to_string(), offset(remote, now));
+    }
+
+    /// 对等节点断开后不再计入
+    pub fn remove_peer(&mut self, node_id: &str) {
+        self.peers.remove(node_id);
+    }
+
+    /// 记录收到的区块时间戳
+    pub fn record_block(&mut self, timestamp: u64, now: u64) {
+        if self.blocks.len() >= MAX_BLOCK_SAMPLES {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(offset(timestamp, now).max(0));
+    }
+
+    /// 按当前样本估计偏差
+    pub fn status(&self, now: u64) -> ClockStatus {
+        let peer_skew = median(self.peers.values().copied().collect());
+        let block_skew = median(self.blocks.iter().copied().collect());
+        let level = if now < MIN_SANE_TIMESTAMP {
+            SkewLevel::Critical
+        } else {
+            [peer_skew, block_skew]
+                .into_iter()
+                .flatten()
+                .map(|skew| self.thresholds.level(skew))
+                .max()
+                .unwrap_or(SkewLevel::Ok)
+        };
+        ClockStatus {
+            local_time: now,
+            peer_skew,
+            peer_samples: self.peers.len(),
+            block_skew,
+            block_samples: self.blocks.len(),
+            level,
+        }
+    }
+}
+
+impl Default for ClockMonitor {
+    fn default() -> Self {
+        Self::new(SkewThresholds::from_policy(&TimestampPolicy::default()))
+    }
+}
+
+/// 当前 Unix 时间（秒）
+pub fn now() -> u64 {
+    u64::try_from(chrono::Utc::now().timestamp()).unwrap_or(0)
+}
+
+fn offset(remote: u64, local: u64) -> i64 {
+    (remote as i128 - local as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn median(mut values: Vec<i64>) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    Some(values[values.len() / 2])
+}
+
+/// 检查一次并输出警告，返回当前状态
+pub async fn check(monitor: &RwLock<ClockMonitor>) -> ClockStatus {
+    let status = monitor.read().await.status(now());
+    if let Some(message) = status.describe() {
+        match status.level {
+            SkewLevel::Critical => log::error!("{}", message),
+            _ => log::warn!("{}", message),
+        }
+    }
+    status
+}
+
+/// 启动定期检查任务，需要在 tokio 运行时中调用
+pub fn spawn_monitor(monitor: Arc<RwLock<ClockMonitor>>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CLOCK_CHECK_INTERVAL);
+        // 第一次 tick 立即返回，启动检查由调用方完成
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            check(&monitor).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_760_000_000;
+
+    fn monitor() -> ClockMonitor {
+        ClockMonitor::new(SkewThresholds {
+            warning: 5,
+            critical: 10,
+        })
+    }
+
+    #[test]
+    fn test_peer_skew() {
+        let mut monitor = monitor();
+        assert_eq!(monitor.status(NOW).level, SkewLevel::Ok);
+        assert!(monitor.status(NOW).peer_skew.is_none());
+
+        monitor.record_peer("a", NOW + 20, NOW);
+        monitor.record_peer("b", NOW + 21, NOW);
+        monitor.record_peer("c", NOW - 1, NOW);
+        // 未报告时间的节点不计入
+        monitor.record_peer("d", 0, NOW);
+        let status = monitor.status(NOW);
+        assert_eq!(status.peer_samples, 3);
+        assert_eq!(status.peer_skew, Some(20));
+        assert_eq!(status.level, SkewLevel::Critical);
+        assert!(status.describe().unwrap().contains("落后 20 秒"));
+
+        monitor.remove_peer("b");
+        monitor.record_peer("a", NOW - 7, NOW);
+        let status = monitor.status(NOW);
+        assert_eq!(status.peer_skew, Some(-1));
+        assert_eq!(status.level, SkewLevel::Ok);
+        assert!(status.describe().is_none());
+    }
+
+    #[test]
+    fn test_block_skew() {
+        let mut monitor = monitor();
+        // 区块晚到不说明本地时钟超前
+        monitor.record_block(NOW - 30, NOW);
+        assert_eq!(monitor.status(NOW).block_skew, Some(0));
+        for _ in 0..MAX_BLOCK_SAMPLES {
+            monitor.record_block(NOW + 7, NOW);
+        }
+        let status = monitor.status(NOW);
+        assert_eq!(status.block_samples, MAX_BLOCK_SAMPLES);
+        assert_eq!(status.block_skew, Some(7));
+        assert_eq!(status.level, SkewLevel::Warning);
+    }
+
+    #[test]
+    fn test_insane_local_time() {
+        let status = monitor().status(86_400);
+        assert_eq!(status.level, SkewLevel::Critical);
+        assert!(status.describe().unwrap().contains("明显错误"));
+    }
+}
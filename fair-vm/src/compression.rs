@@ -0,0 +1,271 @@
+//! 交易和区块数据压缩
+//!
+//! 网络消息和落盘数据在序列化后按帧格式压缩：1 字节魔数、1 字节格式版本、1 字节压缩算法，
+//! 其后为压缩后的数据。不以魔数开头的数据视为旧版本的未压缩 JSON，保证升级前写入的数据
+//! 仍可读取。节点握手时交换各自支持的算法，按本地偏好选出双方都支持的算法；过小的数据
+//! 不压缩。
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 帧魔数，JSON 数据不会以该字节开头
+pub const FRAME_MAGIC: u8 = 0xfa;
+
+/// 当前帧格式版本
+pub const FORMAT_VERSION: u8 = 1;
+
+/// 帧头字节数
+const HEADER_SIZE: usize = 3;
+
+/// 小于该字节数的数据不压缩
+pub const MIN_COMPRESS_SIZE: usize = 128;
+
+/// zstd 压缩级别
+const ZSTD_LEVEL: i32 = 3;
+
+/// 压缩错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum CompressionError {
+    #[error("不支持的帧格式版本 {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("未知的压缩算法 {0}")]
+    UnknownAlgorithm(u8),
+
+    #[error("数据不完整")]
+    Truncated,
+
+    #[error("解压失败: {0}")]
+    Decompress(String),
+
+    #[error("序列化失败: {0}")]
+    Serialization(String),
+}
+
+/// 压缩算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Snappy,
+    Zstd,
+}
+
+/// 默认支持的算法，按偏好排序
+pub fn default_preference() -> Vec<Compression> {
+    vec![Compression::Zstd, Compression::Snappy, Compression::None]
+}
+
+impl Compression {
+    /// 帧头中的算法编号
+    pub fn id(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Snappy => 1,
+            Compression::Zstd => 2,
+        }
+    }
+
+    /// 由算法编号解析
+    pub fn from_id(id: u8) -> Result<Self, CompressionError> {
+        match id {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Snappy),
+            2 => Ok(Compression::Zstd),
+            _ => Err(CompressionError::UnknownAlgorithm(id)),
+        }
+    }
+}
+
+/// 按帧格式压缩数据
+pub fn compress(data: &[u8], compression: Compression) -> Vec<u8> {
+    let compression = if data.len() < MIN_COMPRESS_SIZE {
+        Compression::None
+    } else {
+        compression
+    };
+    let payload = match compression {
+        Compression::None => None,
+        Compression::Snappy => snap::raw::Encoder::new().compress_vec(data).ok(),
+        Compression::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL).ok(),
+    };
+    // 压缩失败时退回不压缩
+    let (compression, payload) = match payload {
+        Some(payload) => (compression, payload),
+        None => (Compression::None, data.to_vec()),
+    };
+    let mut frame = Vec::with_capacity(HEADER_SIZE + payload.len());
+    frame.extend_from_slice(&[FRAME_MAGIC, FORMAT_VERSION, compression.id()]);
+    frame.extend_from_slice(&payload);
+    frame
+}
+
+/// 解压帧，非帧数据原样返回
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    if bytes.first() != Some(&FRAME_MAGIC) {
+        return Ok(bytes.to_vec());
+    }
+    if bytes.len() < HEADER_SIZE {
+        return Err(CompressionError::Truncated);
+    }
+    if bytes[1] != FORMAT_VERSION {
+        return Err(CompressionError::UnsupportedVersion(bytes[1]));
+    }
+    let payload = &bytes[HEADER_SIZE..];
+    match Compression::from_id(bytes[2])? {
+        Compression::None => Ok(payload.to_vec()),
+        Compression::Snappy => snap::raw::Decoder::new()
+            .decompress_vec(payload)
+            .map_err(|e| CompressionError::Decompress(e.to_string())),
+        Compression::Zstd => zstd::stream::decode_all(payload)
+            .map_err(|e| CompressionError::Decompress(e.to_string())),
+    }
+}
+
+/// 序列化并压缩
+pub fn encode<T: Serialize>(
+    value: &T,
+    compression: Compression,
+) -> Result<Vec<u8>, CompressionError> {
+    let data =
+        serde_json::to_vec(value).map_err(|e| CompressionError::Serialization(e.to_string()))?;
+    Ok(compress(&data, compression))
+}
+
+/// 解压并反序列化
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CompressionError> {
+    let data = decompress(bytes)?;
+    serde_json::from_slice(&data).map_err(|e| CompressionError::Serialization(e.to_string()))
+}
+
+/// 按本地偏好选出双方都支持的算法，没有共同算法时不压缩
+pub fn negotiate(local: &[Compression], remote: &[Compression]) -> Compression {
+    local
+        .iter()
+        .copied()
+        .find(|compression| remote.contains(compression))
+        .unwrap_or(Compression::None)
+}
+
+/// 各对等节点协商出的压缩算法
+#[derive(Debug, Clone)]
+pub struct PeerCompression {
+    local: Vec<Compression>,
+    peers: HashMap<String, Compression>,
+}
+
+impl PeerCompression {
+    pub fn new(local: Vec<Compression>) -> Self {
+        Self {
+            local,
+            peers: HashMap::new(),
+        }
+    }
+
+    /// 本地支持的算法
+    pub fn local(&self) -> &[Compression] {
+        &self.local
+    }
+
+    /// 处理对等节点的握手，格式版本不同时不压缩，返回协商结果
+    pub fn handshake(&mut self, node_id: &str, version: u8, remote: &[Compression]) -> Compression {
+        let compression = if version == FORMAT_VERSION {
+            negotiate(&self.local, remote)
+        } else {
+            Compression::None
+        };
+        self.peers.insert(node_id.to_string(), compression);
+        compression
+    }
+
+    /// 对等节点断开
+    pub fn remove(&mut self, node_id: &str) {
+        self.peers.remove(node_id);
+    }
+
+    /// 发往对等节点使用的算法，尚未握手时不压缩
+    pub fn for_peer(&self, node_id: &str) -> Compression {
+        self.peers
+            .get(node_id)
+            .copied()
+            .unwrap_or(Compression::None)
+    }
+}
+
+impl Default for PeerCompression {
+    fn default() -> Self {
+        Self::new(default_preference())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> Vec<u8> {
+        // 类似 NFT 批量铸造的重复元数据
+        serde_json::to_vec(&vec!["ipfs://QmFairVmMetadata/1.json"; 64]).unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let data = payload();
+        for compression in [Compression::None, Compression::Snappy, Compression::Zstd] {
+            let frame = compress(&data, compression);
+            assert_eq!(frame[2], compression.id());
+            assert_eq!(decompress(&frame).unwrap(), data);
+            if compression != Compression::None {
+                assert!(frame.len() < data.len());
+            }
+        }
+        // 过小的数据不压缩
+        assert_eq!(
+            compress(b"{}", Compression::Zstd)[2],
+            Compression::None.id()
+        );
+    }
+
+    #[test]
+    fn test_legacy_and_invalid_frames() {
+        let value: Vec<u32> = decode(b"[1,2,3]").unwrap();
+        assert_eq!(value, vec![1, 2, 3]);
+        assert!(matches!(
+            decompress(&[FRAME_MAGIC, FORMAT_VERSION + 1, 0]),
+            Err(CompressionError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            decompress(&[FRAME_MAGIC, FORMAT_VERSION, 9]),
+            Err(CompressionError::UnknownAlgorithm(9))
+        ));
+        assert!(matches!(
+            decompress(&[FRAME_MAGIC]),
+            Err(CompressionError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let mut peers = PeerCompression::default();
+        assert_eq!(peers.for_peer("a"), Compression::None);
+        assert_eq!(
+            peers.handshake(
+                "a",
+                FORMAT_VERSION,
+                &[Compression::Snappy, Compression::Zstd]
+            ),
+            Compression::Zstd
+        );
+        assert_eq!(
+            peers.handshake("b", FORMAT_VERSION, &[Compression::Snappy]),
+            Compression::Snappy
+        );
+        assert_eq!(
+            peers.handshake("c", FORMAT_VERSION + 1, &[Compression::Zstd]),
+            Compression::None
+        );
+        assert_eq!(peers.for_peer("a"), Compression::Zstd);
+        peers.remove("a");
+        assert_eq!(peers.for_peer("a"), Compression::None);
+    }
+}
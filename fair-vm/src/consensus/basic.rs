@@ -1,4 +1,8 @@
 use crate::account::Address;
+use crate::blockchain::Block;
+use crate::consensus::validator_set::{
+    ValidatorChange, ValidatorInfo, ValidatorSet, ValidatorSetError,
+};
 use crate::state::State;
 use crate::transaction::Transaction as ConsensusTransaction;
 use async_trait::async_trait;
@@ -23,8 +27,17 @@ pub trait ConsensusEngine: Send + Sync {
     /// 提交交易
     async fn submit_transaction(&mut self, tx: ConsensusTransaction) -> Result<(), ConsensusError>;
 
+    /// 提交本节点生成的区块，返回成功后区块才会应用到本地链
+    async fn submit_block(&mut self, block: &Block) -> Result<(), ConsensusError>;
+
     /// 获取共识状态
     async fn get_consensus_state(&self) -> Result<ConsensusState, ConsensusError>;
+
+    /// 变更验证人集合，返回变更生效的区块高度
+    async fn update_validators(&mut self, change: ValidatorChange) -> Result<u64, ConsensusError>;
+
+    /// 指定高度的活跃验证人
+    async fn validators_at(&self, height: u64) -> Result<Vec<ValidatorInfo>, ConsensusError>;
 }
 
 /// 共识错误类型
@@ -53,6 +66,9 @@ pub enum ConsensusError {
 
     #[error("共识引擎已初始化")]
     AlreadyInitialized,
+
+    #[error("验证人集合错误: {0}")]
+    Validator(#[from] ValidatorSetError),
 }
 
 /// 共识参数
@@ -122,6 +138,8 @@ pub struct BasicConsensus {
     engine_state: ConsensusState,
     /// 是否已启动
     is_started: bool,
+    /// 按生效高度保存的验证人集合
+    validator_set: ValidatorSet,
 }
 
 impl Default for BasicConsensus {
@@ -136,6 +154,7 @@ impl Default for BasicConsensus {
                 last_commit_hash: H256::zero(),
             },
             is_started: false,
+            validator_set: ValidatorSet::default(),
         }
     }
 }
@@ -153,6 +172,15 @@ impl BasicConsensus {
             ..Default::default()
         }
     }
+
+    /// 使用创世验证人创建基本共识引擎实例
+    pub fn with_validators(validators: Vec<ValidatorInfo>) -> Result<Self, ConsensusError> {
+        let validator_set = ValidatorSet::new(validators)?;
+        let mut consensus = Self::default();
+        consensus.engine_state.validators = validator_set.addresses_at(0);
+        consensus.validator_set = validator_set;
+        Ok(consensus)
+    }
 }
 
 #[async_trait]
@@ -197,20 +225,57 @@ impl ConsensusEngine for BasicConsensus {
         Ok(())
     }
 
+    async fn submit_block(&mut self, block: &Block) -> Result<(), ConsensusError> {
+        if self.state.is_none() {
+            return Err(ConsensusError::NotInitialized);
+        }
+        if !self.is_started {
+            return Err(ConsensusError::NotStarted);
+        }
+        if block.header.number <= self.engine_state.height {
+            return Err(ConsensusError::StateError(format!(
+                "区块高度 {} 不高于已提交高度 {}",
+                block.header.number, self.engine_state.height
+            )));
+        }
+
+        // 单节点引擎直接确认区块
+        self.engine_state.height = block.header.number;
+        self.engine_state.last_commit_time = block.header.timestamp;
+        self.engine_state.last_commit_hash = block.hash();
+        self.engine_state.validators = self.validator_set.addresses_at(block.header.number);
+        Ok(())
+    }
+
     async fn get_consensus_state(&self) -> Result<ConsensusState, ConsensusError> {
         if self.state.is_none() {
             return Err(ConsensusError::NotInitialized);
         }
         Ok(self.engine_state.clone())
     }
+
+    async fn update_validators(&mut self, change: ValidatorChange) -> Result<u64, ConsensusError> {
+        if self.state.is_none() {
+            return Err(ConsensusError::NotInitialized);
+        }
+        let height = self.engine_state.height + 1;
+        self.validator_set.apply(change, height)?;
+        Ok(height)
+    }
+
+    async fn validators_at(&self, height: u64) -> Result<Vec<ValidatorInfo>, ConsensusError> {
+        if self.state.is_none() {
+            return Err(ConsensusError::NotInitialized);
+        }
+        Ok(self.validator_set.active_at(height))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::evm::EvmContext;
-    use crate::storage::MemoryStorage;
-    use crate::storage::Storage;
+    use crate::storage::{MemoryStorage, StateService};
     use tokio::test;
 
     #[test]
@@ -245,9 +310,7 @@ mod tests {
     #[test]
     async fn test_basic_consensus_lifecycle() {
         let mut consensus = BasicConsensus::new();
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-        ));
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = Arc::new(RwLock::new(State::new(storage, EvmContext::default())));
 
         // 测试初始化
@@ -273,9 +336,7 @@ mod tests {
         // 测试未初始化就停止
         assert_eq!(consensus.stop().await, Err(ConsensusError::AlreadyStopped));
 
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-        ));
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = Arc::new(RwLock::new(State::new(storage, EvmContext::default())));
 
         // 测试初始化
@@ -297,9 +358,7 @@ mod tests {
     #[test]
     async fn test_basic_consensus_state() {
         let mut consensus = BasicConsensus::new();
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-        ));
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = Arc::new(RwLock::new(State::new(storage, EvmContext::default())));
 
         // 测试未初始化就获取状态
@@ -318,11 +377,120 @@ mod tests {
     }
 
     #[test]
-    async fn test_basic_consensus_already_initialized() {
+    async fn test_basic_consensus_submit_block() {
         let mut consensus = BasicConsensus::new();
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
+        let state = Arc::new(RwLock::new(State::new(storage, EvmContext::default())));
+        let block = |number| Block {
+            header: crate::blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number,
+                timestamp: number * 2,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+
+        consensus.initialize(state).await.unwrap();
+        assert_eq!(
+            consensus.submit_block(&block(1)).await,
+            Err(ConsensusError::NotStarted)
+        );
+        consensus.start().await.unwrap();
+        assert!(consensus.submit_block(&block(1)).await.is_ok());
+        let state = consensus.get_consensus_state().await.unwrap();
+        assert_eq!(
+            (state.height, state.last_commit_time, state.last_commit_hash),
+            (1, 2, block(1).hash())
+        );
+        assert!(matches!(
+            consensus.submit_block(&block(1)).await,
+            Err(ConsensusError::StateError(_))
         ));
+    }
+
+    #[test]
+    async fn test_basic_consensus_validators() {
+        let validator = |id: u8| ValidatorInfo {
+            address: Address([id; 20]),
+            public_key: vec![id; 33].into(),
+            voting_power: 1,
+        };
+        let mut consensus = BasicConsensus::with_validators(vec![validator(1)]).unwrap();
+        assert_eq!(
+            consensus
+                .update_validators(ValidatorChange::Remove {
+                    address: Address([1u8; 20])
+                })
+                .await,
+            Err(ConsensusError::NotInitialized)
+        );
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
+        let state = Arc::new(RwLock::new(State::new(storage, EvmContext::default())));
+        consensus.initialize(state).await.unwrap();
+        consensus.start().await.unwrap();
+
+        // 变更从下一个区块起生效
+        let height = consensus
+            .update_validators(ValidatorChange::Add {
+                validator: validator(2),
+            })
+            .await
+            .unwrap();
+        assert_eq!(height, 1);
+        assert_eq!(consensus.validators_at(0).await.unwrap().len(), 1);
+        assert_eq!(consensus.validators_at(1).await.unwrap().len(), 2);
+        assert_eq!(
+            consensus.get_consensus_state().await.unwrap().validators,
+            vec![Address([1u8; 20])]
+        );
+
+        let block = Block {
+            header: crate::blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 2,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        consensus.submit_block(&block).await.unwrap();
+        assert_eq!(
+            consensus.get_consensus_state().await.unwrap().validators,
+            vec![Address([1u8; 20]), Address([2u8; 20])]
+        );
+        assert!(matches!(
+            consensus
+                .update_validators(ValidatorChange::Add {
+                    validator: validator(2),
+                })
+                .await,
+            Err(ConsensusError::Validator(ValidatorSetError::AlreadyExists(
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    async fn test_basic_consensus_already_initialized() {
+        let mut consensus = BasicConsensus::new();
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = Arc::new(RwLock::new(State::new(storage, EvmContext::default())));
 
         // 第一次初始化
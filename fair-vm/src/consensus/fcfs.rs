@@ -0,0 +1,731 @@
+//! 先到先服务（FCFS）排序策略
+//!
+//! 验证者对收到交易的本地时间进行签名，区块内交易必须按照
+//! 各验证者报告的到达时间中位数排序。区块携带所用的全部签名报告并由区块头的到达时间根承诺，
+//! 校验只依据区块内容和状态中已被惩罚的验证者，与节点本地收集的报告无关。可证明的时间戳操纵
+//! （同一验证者对同一交易签发多个不同时间戳）以两份冲突报告的形式随区块提交，执行区块时在状态中
+//! 记录惩罚并扣除该验证者地址的待领取质押奖励。
+
+use crate::account::Address;
+use crate::consensus::ordering_record::{
+    CandidateInput, OrderingRecord, OrderingRecorder, ReportInput,
+};
+use crate::signing::{recover_signer, sign_digest};
+use crate::staking::{self, STAKING_ADDRESS};
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use crate::verification::SignatureVerifier;
+use ethers::types::{H256, U256};
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::{HashMap, HashSet};
+
+/// FCFS 错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum FcfsError {
+    #[error("无效的到达时间签名: {0}")]
+    InvalidSignature(String),
+
+    #[error("交易缺少到达时间报告: {0:?}")]
+    MissingReports(H256),
+
+    #[error("到达时间报告数量不足: 需要 {required}, 实际 {actual}")]
+    InsufficientReports { required: usize, actual: usize },
+
+    #[error("区块交易顺序违反 FCFS 规则: 索引 {0}")]
+    OrderViolation(usize),
+
+    #[error("区块携带了与区块交易无关的到达时间报告: {0:?}")]
+    UnexpectedReport(H256),
+}
+
+/// 验证者签名的交易到达时间
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArrivalTimestamp {
+    /// 交易哈希
+    pub tx_hash: H256,
+    /// 报告的验证者
+    pub validator: Address,
+    /// 到达时间（毫秒）
+    pub timestamp: u64,
+    /// 65 字节可恢复签名 (r || s || v)
+    pub signature: Vec<u8>,
+}
+
+impl ArrivalTimestamp {
+    /// 计算待签名的消息摘要
+    pub fn digest(tx_hash: &H256, timestamp: u64) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(b"fairvm-fcfs-arrival");
+        hasher.update(tx_hash.as_bytes());
+        hasher.update(timestamp.to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    /// 使用验证者私钥签发到达时间
+    pub fn sign(secret_key: &SecretKey, tx_hash: H256, timestamp: u64) -> Result<Self, FcfsError> {
+        let (signature, validator) = sign_digest(secret_key, Self::digest(&tx_hash, timestamp))
+            .map_err(FcfsError::InvalidSignature)?;
+        Ok(Self {
+            tx_hash,
+            validator,
+            timestamp,
+            signature,
+        })
+    }
+
+    /// 恢复签名者地址
+    pub fn recover(&self) -> Result<Address, FcfsError> {
+        recover_signer(Self::digest(&self.tx_hash, self.timestamp), &self.signature)
+            .map_err(FcfsError::InvalidSignature)
+    }
+
+    /// 验证签名是否由声明的验证者签发
+    pub fn verify(&self) -> Result<(), FcfsError> {
+        if self.recover()? != self.validator {
+            return Err(FcfsError::InvalidSignature(format!(
+                "签名者与验证者 {} 不匹配",
+                self.validator
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 可证明的时间戳操纵证据
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManipulationEvidence {
+    /// 作恶的验证者
+    pub validator: Address,
+    /// 第一份签名报告
+    pub first: ArrivalTimestamp,
+    /// 冲突的签名报告
+    pub second: ArrivalTimestamp,
+}
+
+/// FCFS 排序参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FcfsParams {
+    /// 每笔交易所需的最少报告数
+    pub min_reports: usize,
+    /// 每次可证明操纵的惩罚值，从验证者地址的待领取质押奖励中扣除
+    pub manipulation_penalty: u64,
+}
+
+impl Default for FcfsParams {
+    fn default() -> Self {
+        Self {
+            min_reports: 1,
+            manipulation_penalty: 100,
+        }
+    }
+}
+
+/// 区块携带的报告中同一验证者对同一交易的冲突报告，完全相同的报告不构成冲突
+pub fn conflicts(arrivals: &[ArrivalTimestamp]) -> Vec<ManipulationEvidence> {
+    let mut first: HashMap<(H256, Address), &ArrivalTimestamp> = HashMap::new();
+    let mut evidence = Vec::new();
+    for report in arrivals {
+        match first.get(&(report.tx_hash, report.validator)) {
+            Some(existing) if existing.timestamp != report.timestamp => {
+                evidence.push(ManipulationEvidence {
+                    validator: report.validator,
+                    first: (*existing).clone(),
+                    second: report.clone(),
+                });
+            }
+            Some(_) => {}
+            None => {
+                first.insert((report.tx_hash, report.validator), report);
+            }
+        }
+    }
+    evidence
+}
+
+fn median(mut timestamps: Vec<u64>) -> Option<u64> {
+    if timestamps.is_empty() {
+        return None;
+    }
+    timestamps.sort_unstable();
+    Some(timestamps[timestamps.len() / 2])
+}
+
+fn penalty_slot(validator: &Address) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"fairvm-fcfs-penalty");
+    hasher.update(validator.0);
+    hasher.finalize().into()
+}
+
+fn penalized_slot(index: u64) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"fairvm-fcfs-penalized");
+    hasher.update(index.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn penalized_count_slot() -> [u8; 32] {
+    Keccak256::digest(b"fairvm-fcfs-penalized-count").into()
+}
+
+async fn read_u64(storage: &(dyn Storage + Send + Sync), slot: [u8; 32]) -> u64 {
+    let word = storage.get_storage_value(&STAKING_ADDRESS, slot).await;
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&word[24..]);
+    u64::from_be_bytes(bytes)
+}
+
+async fn write_u64(storage: &mut (dyn Storage + Send + Sync), slot: [u8; 32], value: u64) {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    storage
+        .set_storage_value(&STAKING_ADDRESS, slot, word)
+        .await;
+}
+
+/// 状态中记录的验证者累计惩罚
+pub async fn penalty_of(storage: &(dyn Storage + Send + Sync), validator: &Address) -> u64 {
+    read_u64(storage, penalty_slot(validator)).await
+}
+
+/// 状态中已被惩罚的验证者，按被惩罚的先后排列
+pub async fn penalized(storage: &(dyn Storage + Send + Sync)) -> Vec<Address> {
+    let count = read_u64(storage, penalized_count_slot()).await;
+    let mut validators = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        let word = storage
+            .get_storage_value(&STAKING_ADDRESS, penalized_slot(index))
+            .await;
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&word[12..]);
+        validators.push(Address(address));
+    }
+    validators
+}
+
+/// 按区块携带的操纵证据在状态中惩罚验证者，返回本次新被惩罚的验证者
+///
+/// 每个验证者只惩罚一次：记录惩罚值，并从其地址的待领取质押奖励中扣除。
+pub async fn apply_penalties(
+    storage: &mut (dyn Storage + Send + Sync),
+    params: &FcfsParams,
+    evidence: &[ManipulationEvidence],
+) -> Vec<Address> {
+    let mut slashed = Vec::new();
+    for validator in evidence.iter().map(|e| e.validator) {
+        if slashed.contains(&validator) || penalty_of(storage, &validator).await > 0 {
+            continue;
+        }
+        log::warn!("验证者 {} 操纵交易到达时间", validator);
+        let penalty = params.manipulation_penalty.max(1);
+        write_u64(storage, penalty_slot(&validator), penalty).await;
+        let count = read_u64(storage, penalized_count_slot()).await;
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&validator.0);
+        storage
+            .set_storage_value(&STAKING_ADDRESS, penalized_slot(count), word)
+            .await;
+        write_u64(storage, penalized_count_slot(), count + 1).await;
+        staking::slash(storage, &validator, U256::from(penalty)).await;
+        slashed.push(validator);
+    }
+    slashed
+}
+
+/// FCFS 排序器
+///
+/// 本地收集的报告和证据只用于出块，收到的区块只按其携带的报告校验。
+#[derive(Debug, Default)]
+pub struct FcfsOrdering {
+    /// 排序参数
+    params: FcfsParams,
+    /// 验证者集合，为空时接受任意验证者
+    validators: Vec<Address>,
+    /// 每笔交易的到达时间报告（按验证者去重）
+    reports: HashMap<H256, HashMap<Address, ArrivalTimestamp>>,
+    /// 已收集、尚未随区块提交的操纵证据
+    evidence: Vec<ManipulationEvidence>,
+    /// 出块排序记录器，未启用时不记录
+    recorder: Option<OrderingRecorder>,
+    /// 签名验证池，设置后出块时剔除签名无效的候选交易
+    verifier: Option<SignatureVerifier>,
+}
+
+impl FcfsOrdering {
+    /// 创建新的 FCFS 排序器
+    pub fn new(params: FcfsParams, validators: Vec<Address>) -> Self {
+        Self {
+            params,
+            validators,
+            ..Default::default()
+        }
+    }
+
+    /// 排序参数
+    pub fn params(&self) -> &FcfsParams {
+        &self.params
+    }
+
+    fn check_report(&self, report: &ArrivalTimestamp) -> Result<(), FcfsError> {
+        report.verify()?;
+        if !self.validators.is_empty() && !self.validators.contains(&report.validator) {
+            return Err(FcfsError::InvalidSignature(format!(
+                "{} 不是验证者",
+                report.validator
+            )));
+        }
+        Ok(())
+    }
+
+    /// 记录一份到达时间报告
+    ///
+    /// 同一验证者对同一交易签发不同时间戳时，记录操纵证据，随下一个区块提交。
+    pub fn add_report(&mut self, report: ArrivalTimestamp) -> Result<(), FcfsError> {
+        self.check_report(&report)?;
+        let reports = self.reports.entry(report.tx_hash).or_default();
+        match reports.get(&report.validator) {
+            Some(existing) if existing.timestamp != report.timestamp => {
+                let evidence = ManipulationEvidence {
+                    validator: report.validator,
+                    first: existing.clone(),
+                    second: report,
+                };
+                log::warn!("收到验证者 {} 的冲突到达时间报告", evidence.validator);
+                self.evidence.push(evidence);
+            }
+            Some(_) => {}
+            None => {
+                reports.insert(report.validator, report);
+            }
+        }
+        Ok(())
+    }
+
+    /// 提交外部收集的操纵证据
+    pub fn submit_evidence(&mut self, evidence: ManipulationEvidence) -> Result<(), FcfsError> {
+        self.check_report(&evidence.first)?;
+        self.check_report(&evidence.second)?;
+        if evidence.first.validator != evidence.validator
+            || evidence.second.validator != evidence.validator
+            || evidence.first.tx_hash != evidence.second.tx_hash
+            || evidence.first.timestamp == evidence.second.timestamp
+        {
+            return Err(FcfsError::InvalidSignature("证据不构成冲突".into()));
+        }
+        if !self.evidence.contains(&evidence) {
+            self.evidence.push(evidence);
+        }
+        Ok(())
+    }
+
+    /// 获取交易的中位到达时间，已收集到操纵证据的验证者的报告不计入
+    pub fn median_arrival(&self, tx_hash: &H256) -> Option<u64> {
+        let reports = self.reports.get(tx_hash)?;
+        median(
+            reports
+                .iter()
+                .filter(|(validator, _)| !self.has_evidence(validator))
+                .map(|(_, report)| report.timestamp)
+                .collect(),
+        )
+    }
+
+    /// 按中位到达时间对交易排序，时间相同时按哈希排序保证确定性
+    pub fn order(&self, transactions: &mut [Transaction]) {
+        transactions.sort_by_key(|tx| (self.median_arrival(&tx.hash).unwrap_or(u64::MAX), tx.hash));
+    }
+
+    /// 启用出块排序记录
+    pub fn enable_recording(&mut self, recorder: OrderingRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// 获取排序记录器
+    pub fn recorder(&self) -> Option<&OrderingRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// 设置签名验证池，与交易池准入共用发送方缓存
+    pub fn set_verifier(&mut self, verifier: SignatureVerifier) {
+        self.verifier = Some(verifier);
+    }
+
+    /// 从候选交易中选出区块交易，返回区块交易和区块需携带的到达时间报告
+    ///
+    /// `penalized` 为状态中已被惩罚的验证者，与本地收集到证据的验证者一样不计入报告。
+    /// 携带的报告包括已打包交易的有效报告和全部待提交的操纵证据。排序结果由
+    /// [`OrderingRecord`] 计算，启用记录时保存该记录以便离线重放。
+    pub fn build_block(
+        &mut self,
+        block_number: u64,
+        candidates: Vec<Transaction>,
+        gas_limit: u64,
+        penalized: &[Address],
+    ) -> (Vec<Transaction>, Vec<ArrivalTimestamp>) {
+        let candidates = match &self.verifier {
+            Some(verifier) => {
+                let senders = verifier.recover_senders(&candidates);
+                candidates
+                    .into_iter()
+                    .zip(senders)
+                    .filter_map(|(tx, sender)| match sender {
+                        Ok(sender) if sender == tx.from => Some(tx),
+                        _ => {
+                            log::warn!("剔除签名无效的候选交易 {:?}", tx.hash);
+                            None
+                        }
+                    })
+                    .collect()
+            }
+            None => candidates,
+        };
+        let inputs = candidates
+            .iter()
+            .map(|tx| {
+                let mut reports: Vec<ReportInput> = self
+                    .reports
+                    .get(&tx.hash)
+                    .into_iter()
+                    .flat_map(HashMap::values)
+                    .map(|report| ReportInput {
+                        validator: report.validator,
+                        timestamp: report.timestamp,
+                    })
+                    .collect();
+                reports.sort_by_key(|report| report.validator);
+                CandidateInput::new(tx, reports)
+            })
+            .collect();
+        let mut excluded: Vec<Address> = penalized
+            .iter()
+            .copied()
+            .chain(self.evidence.iter().map(|e| e.validator))
+            .collect();
+        excluded.sort();
+        excluded.dedup();
+
+        let record = OrderingRecord::new(
+            block_number,
+            gas_limit,
+            self.params.min_reports,
+            excluded.clone(),
+            inputs,
+        );
+        let mut by_hash: HashMap<H256, Transaction> =
+            candidates.into_iter().map(|tx| (tx.hash, tx)).collect();
+        let block: Vec<Transaction> = record
+            .included
+            .iter()
+            .filter_map(|hash| by_hash.remove(hash))
+            .collect();
+        let mut arrivals = Vec::new();
+        for tx in &block {
+            let mut reports: Vec<&ArrivalTimestamp> = self
+                .reports
+                .get(&tx.hash)
+                .into_iter()
+                .flat_map(HashMap::values)
+                .filter(|report| !excluded.contains(&report.validator))
+                .collect();
+            reports.sort_by_key(|report| report.validator);
+            arrivals.extend(reports.into_iter().cloned());
+        }
+        for evidence in &self.evidence {
+            arrivals.push(evidence.first.clone());
+            arrivals.push(evidence.second.clone());
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(record);
+        }
+        (block, arrivals)
+    }
+
+    /// 按区块携带的到达时间报告验证区块内交易顺序，返回区块携带的操纵证据
+    ///
+    /// `penalized` 为执行区块前状态中已被惩罚的验证者（见 [`penalized`]），其报告和区块内
+    /// 冲突报告的验证者的报告都不计入。除冲突报告外，区块只能携带其交易的报告。
+    pub fn validate_block(
+        &self,
+        arrivals: &[ArrivalTimestamp],
+        transactions: &[Transaction],
+        penalized: &[Address],
+    ) -> Result<Vec<ManipulationEvidence>, FcfsError> {
+        for report in arrivals {
+            self.check_report(report)?;
+        }
+        let evidence = conflicts(arrivals);
+        let conflicting: HashSet<(H256, Address)> = evidence
+            .iter()
+            .map(|e| (e.first.tx_hash, e.validator))
+            .collect();
+        let included: HashSet<H256> = transactions.iter().map(|tx| tx.hash).collect();
+        let excluded: HashSet<Address> = penalized
+            .iter()
+            .copied()
+            .chain(evidence.iter().map(|e| e.validator))
+            .collect();
+
+        let mut reports: HashMap<H256, HashMap<Address, u64>> = HashMap::new();
+        for report in arrivals {
+            if !included.contains(&report.tx_hash) {
+                if conflicting.contains(&(report.tx_hash, report.validator)) {
+                    continue;
+                }
+                return Err(FcfsError::UnexpectedReport(report.tx_hash));
+            }
+            if excluded.contains(&report.validator) {
+                continue;
+            }
+            reports
+                .entry(report.tx_hash)
+                .or_default()
+                .entry(report.validator)
+                .or_insert(report.timestamp);
+        }
+
+        let mut previous: Option<(u64, H256)> = None;
+        for (index, tx) in transactions.iter().enumerate() {
+            let timestamps: Vec<u64> = reports
+                .get(&tx.hash)
+                .map(|reports| reports.values().copied().collect())
+                .unwrap_or_default();
+            if timestamps.is_empty() {
+                return Err(FcfsError::MissingReports(tx.hash));
+            }
+            if timestamps.len() < self.params.min_reports {
+                return Err(FcfsError::InsufficientReports {
+                    required: self.params.min_reports,
+                    actual: timestamps.len(),
+                });
+            }
+            let key = (
+                median(timestamps).ok_or(FcfsError::MissingReports(tx.hash))?,
+                tx.hash,
+            );
+            if previous.is_some_and(|prev| prev > key) {
+                return Err(FcfsError::OrderViolation(index));
+            }
+            previous = Some(key);
+        }
+        Ok(evidence)
+    }
+
+    /// 区块最终确定后清理已打包交易的报告和已随区块提交的证据
+    pub fn prune(&mut self, transactions: &[Transaction], arrivals: &[ArrivalTimestamp]) {
+        for tx in transactions {
+            self.reports.remove(&tx.hash);
+        }
+        self.evidence
+            .retain(|evidence| !arrivals.contains(&evidence.second));
+    }
+
+    /// 获取已收集、尚未随区块提交的操纵证据
+    pub fn evidence(&self) -> &[ManipulationEvidence] {
+        &self.evidence
+    }
+
+    fn has_evidence(&self, validator: &Address) -> bool {
+        self.evidence.iter().any(|e| &e.validator == validator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+
+    fn key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn tx(byte: u8) -> Transaction {
+        Transaction::new(
+            H256([byte; 32]),
+            Address([byte; 20]),
+            None,
+            U256::zero(),
+            0,
+            21000,
+            Some(U256::one()),
+            vec![],
+            vec![],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let report = ArrivalTimestamp::sign(&key(1), H256([9; 32]), 1000).unwrap();
+        assert!(report.verify().is_ok());
+
+        let mut forged = report.clone();
+        forged.timestamp = 999;
+        assert!(forged.verify().is_err());
+    }
+
+    #[test]
+    fn test_order_by_median_arrival() {
+        let mut fcfs = FcfsOrdering::default();
+        let (a, b) = (tx(1), tx(2));
+        let mut arrivals = Vec::new();
+        for (k, ta, tb) in [(1, 100, 90), (2, 300, 95), (3, 110, 400)] {
+            for report in [
+                ArrivalTimestamp::sign(&key(k), a.hash, ta).unwrap(),
+                ArrivalTimestamp::sign(&key(k), b.hash, tb).unwrap(),
+            ] {
+                fcfs.add_report(report.clone()).unwrap();
+                arrivals.push(report);
+            }
+        }
+        assert_eq!(fcfs.median_arrival(&a.hash), Some(110));
+        assert_eq!(fcfs.median_arrival(&b.hash), Some(95));
+
+        let mut txs = vec![a.clone(), b.clone()];
+        fcfs.order(&mut txs);
+        assert_eq!(txs[0].hash, b.hash);
+        // 校验只依据区块携带的报告，与本地收集的报告无关
+        let fresh = FcfsOrdering::default();
+        assert_eq!(fresh.validate_block(&arrivals, &txs, &[]), Ok(Vec::new()));
+        assert_eq!(
+            fresh.validate_block(&arrivals, &[a.clone(), b], &[]),
+            Err(FcfsError::OrderViolation(1))
+        );
+        assert_eq!(
+            fresh.validate_block(&[], std::slice::from_ref(&a), &[]),
+            Err(FcfsError::MissingReports(a.hash))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manipulation_penalty() {
+        let mut fcfs = FcfsOrdering::default();
+        let hash = H256([7; 32]);
+        let first = ArrivalTimestamp::sign(&key(1), hash, 10).unwrap();
+        let second = ArrivalTimestamp::sign(&key(1), hash, 20).unwrap();
+        fcfs.add_report(first.clone()).unwrap();
+        fcfs.add_report(second.clone()).unwrap();
+
+        let validator = first.validator;
+        assert_eq!(fcfs.evidence().len(), 1);
+        // 收集到证据的验证者的报告不再参与本地中位数计算
+        assert_eq!(fcfs.median_arrival(&hash), None);
+
+        // 证据随区块提交后在状态中惩罚，只惩罚一次
+        let evidence = conflicts(&[first.clone(), first.clone(), second.clone()]);
+        assert_eq!(evidence.len(), 1);
+        let mut storage = MemoryStorage::default();
+        let mut word = [0u8; 32];
+        U256::from(500).to_big_endian(&mut word);
+        storage
+            .set_storage_value(&STAKING_ADDRESS, staking::pending_slot(&validator), word)
+            .await;
+        let params = FcfsParams::default();
+        assert_eq!(
+            apply_penalties(&mut storage, &params, &evidence).await,
+            vec![validator]
+        );
+        assert!(apply_penalties(&mut storage, &params, &evidence)
+            .await
+            .is_empty());
+        assert_eq!(penalty_of(&storage, &validator).await, 100);
+        assert_eq!(penalized(&storage).await, vec![validator]);
+        assert_eq!(
+            staking::pending_of(&storage, &validator).await,
+            U256::from(400)
+        );
+        fcfs.prune(&[], &[first, second]);
+        assert!(fcfs.evidence().is_empty());
+
+        // 被惩罚验证者和区块内冲突报告的验证者的报告不计入报告数量
+        let fcfs = FcfsOrdering::new(
+            FcfsParams {
+                min_reports: 2,
+                ..FcfsParams::default()
+            },
+            Vec::new(),
+        );
+        let a = tx(1);
+        let mut arrivals: Vec<ArrivalTimestamp> = [1, 2]
+            .iter()
+            .map(|k| ArrivalTimestamp::sign(&key(*k), a.hash, 10).unwrap())
+            .collect();
+        let block = std::slice::from_ref(&a);
+        assert!(fcfs.validate_block(&arrivals, block, &[]).is_ok());
+        let insufficient = Err(FcfsError::InsufficientReports {
+            required: 2,
+            actual: 1,
+        });
+        assert_eq!(
+            fcfs.validate_block(&arrivals, block, &[arrivals[1].validator]),
+            insufficient
+        );
+        arrivals.push(ArrivalTimestamp::sign(&key(2), a.hash, 20).unwrap());
+        assert_eq!(fcfs.validate_block(&arrivals, block, &[]), insufficient);
+
+        // 冲突报告之外不能携带区块交易以外的报告
+        arrivals.push(ArrivalTimestamp::sign(&key(3), hash, 10).unwrap());
+        assert_eq!(
+            fcfs.validate_block(&arrivals, block, &[]),
+            Err(FcfsError::UnexpectedReport(hash))
+        );
+    }
+
+    #[test]
+    fn test_build_block_records_ordering() {
+        let mut fcfs = FcfsOrdering::default();
+        fcfs.enable_recording(OrderingRecorder::default());
+        let (a, b, c) = (tx(1), tx(2), tx(3));
+        fcfs.add_report(ArrivalTimestamp::sign(&key(1), a.hash, 200).unwrap())
+            .unwrap();
+        fcfs.add_report(ArrivalTimestamp::sign(&key(1), b.hash, 100).unwrap())
+            .unwrap();
+
+        let (block, arrivals) =
+            fcfs.build_block(1, vec![a.clone(), b.clone(), c.clone()], 30_000, &[]);
+        assert_eq!(block.len(), 1);
+        assert_eq!(block[0].hash, b.hash);
+        assert_eq!(arrivals.len(), 1);
+        assert!(FcfsOrdering::default()
+            .validate_block(&arrivals, &block, &[])
+            .is_ok());
+
+        let record = fcfs.recorder().unwrap().get(1).unwrap();
+        assert!(record.verify());
+        assert!(record.explain(&a.hash).unwrap().to_string().contains("gas"));
+        assert!(!record.explain(&c.hash).unwrap().to_string().is_empty());
+    }
+
+    #[test]
+    fn test_build_block_drops_invalid_signatures() {
+        let verifier = SignatureVerifier::new(1).unwrap();
+        let mut signed = tx(1);
+        let (signature, signer) = sign_digest(&key(1), signed.sighash().0).unwrap();
+        signed.from = signer;
+        signed.signature = signature;
+        let unsigned = tx(2);
+
+        let mut fcfs = FcfsOrdering::default();
+        fcfs.set_verifier(verifier.clone());
+        for t in [&signed, &unsigned] {
+            fcfs.add_report(ArrivalTimestamp::sign(&key(1), t.hash, 100).unwrap())
+                .unwrap();
+        }
+
+        let (block, _) = fcfs.build_block(1, vec![signed.clone(), unsigned], 100_000, &[]);
+        assert_eq!(block.len(), 1);
+        assert_eq!(block[0].hash, signed.hash);
+        // 出块时恢复的发送方写入共享缓存，区块校验直接命中
+        assert_eq!(
+            verifier
+                .sender_cache()
+                .get(&crate::verification::SenderCache::key(&signed)),
+            Some(signer)
+        );
+    }
+}
@@ -218,14 +218,12 @@ impl ConsensusEngine for BasicConsensus {
 mod tests {
     use super::*;
     use crate::evm::EvmContext;
-    use crate::storage::MemoryStorage;
+    use crate::storage::{MemoryStorage, StateService};
 
     #[tokio::test]
     async fn test_consensus_engine_lifecycle() {
         let mut engine = BasicConsensus::new();
-        let storage =
-            Arc::new(RwLock::new(Box::new(MemoryStorage::default())
-                as Box<dyn crate::storage::Storage + Send + Sync>));
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = Arc::new(RwLock::new(State::new(storage, EvmContext::default())));
 
         // 测试初始化
@@ -244,9 +242,13 @@ mod tests {
 }
 
 pub mod basic;
+pub mod fcfs;
+pub mod ordering_record;
+pub mod validator_set;
 
 pub use basic::{
     BasicConsensus as ConsensusBasic, ConsensusEngine as ConsensusEngineTrait,
     ConsensusError as ConsensusErrorType, ConsensusParams as ConsensusParamsType,
     ConsensusState as ConsensusStateType, Transaction as ConsensusTransaction,
 };
+pub use validator_set::{ValidatorChange, ValidatorInfo, ValidatorSet, ValidatorSetError};
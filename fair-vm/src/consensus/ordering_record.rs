@@ -0,0 +1,421 @@
+//! 出块排序决策记录与重放
+//!
+//! 记录每次出块排序的全部输入（候选交易、费用、到达时间报告、被惩罚的验证者），
+//! 排序结果完全由记录计算得出，因此可以离线重放并解释某笔交易为何被打包或未被打包。
+
+use crate::account::Address;
+use crate::transaction::Transaction;
+use ethers::types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 内存中默认保留的记录数
+pub const DEFAULT_RECORD_CAPACITY: usize = 256;
+
+/// 单个验证者的到达时间报告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportInput {
+    pub validator: Address,
+    /// 到达时间（毫秒）
+    pub timestamp: u64,
+}
+
+/// 候选交易
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandidateInput {
+    pub hash: H256,
+    pub from: Address,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    /// 愿意支付的最高 gas 价格
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// 全部到达时间报告，包括被惩罚验证者的报告
+    pub reports: Vec<ReportInput>,
+}
+
+impl CandidateInput {
+    pub fn new(tx: &Transaction, reports: Vec<ReportInput>) -> Self {
+        Self {
+            hash: tx.hash,
+            from: tx.from,
+            nonce: tx.nonce,
+            gas_limit: tx.gas_limit,
+            max_fee_per_gas: tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default(),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+            reports,
+        }
+    }
+}
+
+/// 单笔交易的排序决策
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Decision {
+    /// 被打包在指定位置
+    Included {
+        position: usize,
+        median_arrival: u64,
+    },
+    /// 有效到达时间报告不足
+    InsufficientReports { required: usize, actual: usize },
+    /// 轮到该交易时区块 gas 已不足
+    GasLimitExceeded {
+        median_arrival: u64,
+        gas_used: u64,
+        gas_limit: u64,
+    },
+}
+
+/// 一次出块排序的完整输入与结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderingRecord {
+    pub block_number: u64,
+    pub gas_limit: u64,
+    /// 每笔交易所需的最少有效报告数
+    pub min_reports: usize,
+    /// 排序时已被惩罚的验证者，其报告不参与中位数计算
+    pub penalized: Vec<Address>,
+    pub candidates: Vec<CandidateInput>,
+    /// 实际打包的交易，按区块内顺序
+    pub included: Vec<H256>,
+}
+
+impl OrderingRecord {
+    /// 创建记录，打包结果由 [`OrderingRecord::replay`] 计算
+    pub fn new(
+        block_number: u64,
+        gas_limit: u64,
+        min_reports: usize,
+        penalized: Vec<Address>,
+        candidates: Vec<CandidateInput>,
+    ) -> Self {
+        let mut record = Self {
+            block_number,
+            gas_limit,
+            min_reports,
+            penalized,
+            candidates,
+            included: Vec::new(),
+        };
+        record.included = record.replay();
+        record
+    }
+
+    /// 候选交易的有效到达时间中位数与有效报告数
+    fn median_arrival(&self, candidate: &CandidateInput) -> (Option<u64>, usize) {
+        let penalized: HashSet<&Address> = self.penalized.iter().collect();
+        let mut timestamps: Vec<u64> = candidate
+            .reports
+            .iter()
+            .filter(|report| !penalized.contains(&report.validator))
+            .map(|report| report.timestamp)
+            .collect();
+        timestamps.sort_unstable();
+        let median = timestamps.get(timestamps.len() / 2).copied();
+        (median, timestamps.len())
+    }
+
+    /// 根据记录的输入计算每笔候选交易的决策，顺序与候选交易一致
+    pub fn decide(&self) -> Vec<Decision> {
+        let required = self.min_reports.max(1);
+        let mut decisions: Vec<Option<Decision>> = vec![None; self.candidates.len()];
+        let mut eligible = Vec::new();
+
+        for (index, candidate) in self.candidates.iter().enumerate() {
+            match self.median_arrival(candidate) {
+                (Some(median), actual) if actual >= required => {
+                    eligible.push((median, candidate.hash, index))
+                }
+                (_, actual) => {
+                    decisions[index] = Some(Decision::InsufficientReports { required, actual })
+                }
+            }
+        }
+
+        // 按中位到达时间排序，时间相同时按哈希排序保证确定性
+        eligible.sort_unstable();
+        let mut gas_used = 0u64;
+        let mut position = 0;
+        for (median_arrival, _, index) in eligible {
+            let gas_limit = self.candidates[index].gas_limit;
+            decisions[index] = Some(match gas_used.checked_add(gas_limit) {
+                Some(total) if total <= self.gas_limit => {
+                    gas_used = total;
+                    position += 1;
+                    Decision::Included {
+                        position: position - 1,
+                        median_arrival,
+                    }
+                }
+                _ => Decision::GasLimitExceeded {
+                    median_arrival,
+                    gas_used,
+                    gas_limit: self.gas_limit,
+                },
+            });
+        }
+
+        decisions.into_iter().flatten().collect()
+    }
+
+    /// 重放排序，返回应打包的交易
+    pub fn replay(&self) -> Vec<H256> {
+        let mut included: Vec<(usize, H256)> = self
+            .decide()
+            .into_iter()
+            .zip(&self.candidates)
+            .filter_map(|(decision, candidate)| match decision {
+                Decision::Included { position, .. } => Some((position, candidate.hash)),
+                _ => None,
+            })
+            .collect();
+        included.sort_unstable();
+        included.into_iter().map(|(_, hash)| hash).collect()
+    }
+
+    /// 重放结果是否与记录一致
+    pub fn verify(&self) -> bool {
+        self.replay() == self.included
+    }
+
+    /// 解释某笔交易的排序决策
+    pub fn explain(&self, tx_hash: &H256) -> Option<Explanation> {
+        let index = self.candidates.iter().position(|c| &c.hash == tx_hash)?;
+        let candidate = &self.candidates[index];
+        let decision = self.decide().swap_remove(index);
+        let ignored_reports = candidate
+            .reports
+            .iter()
+            .filter(|report| self.penalized.contains(&report.validator))
+            .count();
+        let preceded_by = match decision {
+            Decision::Included { position, .. } if position > 0 => {
+                self.included.get(position - 1).copied()
+            }
+            _ => None,
+        };
+        Some(Explanation {
+            block_number: self.block_number,
+            hash: candidate.hash,
+            decision,
+            ignored_reports,
+            preceded_by,
+        })
+    }
+}
+
+/// 排序决策的解释
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Explanation {
+    pub block_number: u64,
+    pub hash: H256,
+    pub decision: Decision,
+    /// 被惩罚验证者的报告数，这些报告未参与计算
+    pub ignored_reports: usize,
+    /// 排在该交易之前的交易
+    pub preceded_by: Option<H256>,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "区块 {} 交易 {:?}: ", self.block_number, self.hash)?;
+        match &self.decision {
+            Decision::Included {
+                position,
+                median_arrival,
+            } => {
+                write!(
+                    f,
+                    "已打包在第 {} 位，到达时间中位数 {} ms",
+                    position, median_arrival
+                )?;
+                if let Some(previous) = &self.preceded_by {
+                    write!(f, "，排在更早到达的交易 {:?} 之后", previous)?;
+                }
+            }
+            Decision::InsufficientReports { required, actual } => write!(
+                f,
+                "未打包，有效到达时间报告 {} 份，至少需要 {} 份",
+                actual, required
+            )?,
+            Decision::GasLimitExceeded {
+                median_arrival,
+                gas_used,
+                gas_limit,
+            } => write!(
+                f,
+                "未打包，到达时间中位数 {} ms，轮到该交易时区块已使用 gas {} / {}",
+                median_arrival, gas_used, gas_limit
+            )?,
+        }
+        if self.ignored_reports > 0 {
+            write!(
+                f,
+                "（忽略了 {} 份来自被惩罚验证者的报告）",
+                self.ignored_reports
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 排序记录器
+///
+/// 在内存中保留最近的记录，配置目录后同时写入 `ordering-{区块高度}.json`。
+#[derive(Debug)]
+pub struct OrderingRecorder {
+    capacity: usize,
+    dir: Option<PathBuf>,
+    records: VecDeque<OrderingRecord>,
+}
+
+impl Default for OrderingRecorder {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECORD_CAPACITY)
+    }
+}
+
+impl OrderingRecorder {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            dir: None,
+            records: VecDeque::new(),
+        }
+    }
+
+    /// 将记录写入目录
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// 保存一条记录，写文件失败只记录日志，不影响出块
+    pub fn record(&mut self, record: OrderingRecord) {
+        if let Some(dir) = &self.dir {
+            if let Err(e) = write_record(dir, &record) {
+                log::warn!("写入区块 {} 的排序记录失败: {}", record.block_number, e);
+            }
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// 按区块高度获取记录
+    pub fn get(&self, block_number: u64) -> Option<&OrderingRecord> {
+        self.records
+            .iter()
+            .rev()
+            .find(|record| record.block_number == block_number)
+    }
+}
+
+fn write_record(dir: &Path, record: &OrderingRecord) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_vec_pretty(record).map_err(|e| e.to_string())?;
+    std::fs::write(
+        dir.join(format!("ordering-{}.json", record.block_number)),
+        content,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 从文件加载记录
+pub fn load_record(path: impl AsRef<Path>) -> Result<OrderingRecord, String> {
+    let content = std::fs::read(path.as_ref()).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(byte: u8, gas_limit: u64, reports: &[(u8, u64)]) -> CandidateInput {
+        CandidateInput {
+            hash: H256([byte; 32]),
+            from: Address([byte; 20]),
+            nonce: 0,
+            gas_limit,
+            max_fee_per_gas: U256::from(10),
+            max_priority_fee_per_gas: None,
+            reports: reports
+                .iter()
+                .map(|(validator, timestamp)| ReportInput {
+                    validator: Address([*validator; 20]),
+                    timestamp: *timestamp,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_replay_and_explain() {
+        let record = OrderingRecord::new(
+            5,
+            50_000,
+            2,
+            vec![Address([9u8; 20])],
+            vec![
+                candidate(1, 21_000, &[(1, 200), (2, 210)]),
+                candidate(2, 21_000, &[(1, 100), (2, 120), (9, 1)]),
+                candidate(3, 21_000, &[(1, 50), (9, 40)]),
+                candidate(4, 21_000, &[(1, 300), (2, 310)]),
+            ],
+        );
+
+        assert_eq!(record.included, vec![H256([2; 32]), H256([1; 32])]);
+        assert!(record.verify());
+
+        let first = record.explain(&H256([2; 32])).unwrap();
+        assert_eq!(
+            first.decision,
+            Decision::Included {
+                position: 0,
+                median_arrival: 120
+            }
+        );
+        assert_eq!(first.ignored_reports, 1);
+
+        let second = record.explain(&H256([1; 32])).unwrap();
+        assert_eq!(second.preceded_by, Some(H256([2; 32])));
+
+        // 被惩罚验证者的报告不计入，有效报告不足
+        assert_eq!(
+            record.explain(&H256([3; 32])).unwrap().decision,
+            Decision::InsufficientReports {
+                required: 2,
+                actual: 1
+            }
+        );
+        assert!(matches!(
+            record.explain(&H256([4; 32])).unwrap().decision,
+            Decision::GasLimitExceeded {
+                gas_used: 42_000,
+                ..
+            }
+        ));
+        assert!(record.explain(&H256([8; 32])).is_none());
+
+        let mut tampered = record.clone();
+        tampered.included.reverse();
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn test_recorder_persists_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recorder = OrderingRecorder::new(1).with_dir(dir.path());
+        for block_number in [1, 2] {
+            recorder.record(OrderingRecord::new(block_number, 0, 1, vec![], vec![]));
+        }
+        assert!(recorder.get(1).is_none());
+        assert!(recorder.get(2).is_some());
+
+        let loaded = load_record(dir.path().join("ordering-1.json")).unwrap();
+        assert_eq!(loaded.block_number, 1);
+    }
+}
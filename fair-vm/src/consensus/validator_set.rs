@@ -0,0 +1,298 @@
+//! 验证人集合管理
+//!
+//! 许可子网由运营方增删验证人、轮换签名公钥和调整投票权重。每次变更从下一个区块高度起生效，
+//! 集合按生效高度保存快照，可查询任意高度的活跃集合，便于校验历史区块时使用当时的验证人。
+
+use crate::account::Address;
+use ethers::types::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 验证人
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorInfo {
+    pub address: Address,
+    /// 签名公钥
+    pub public_key: Bytes,
+    /// 投票权重，必须大于 0
+    pub voting_power: u64,
+}
+
+/// 验证人集合变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ValidatorChange {
+    /// 加入验证人
+    Add { validator: ValidatorInfo },
+    /// 移除验证人
+    #[serde(rename_all = "camelCase")]
+    Remove { address: Address },
+    /// 轮换签名公钥
+    #[serde(rename_all = "camelCase")]
+    RotateKey { address: Address, public_key: Bytes },
+    /// 调整投票权重
+    #[serde(rename_all = "camelCase")]
+    SetVotingPower { address: Address, voting_power: u64 },
+}
+
+/// 验证人集合错误类型
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ValidatorSetError {
+    #[error("验证人 {0} 已存在")]
+    AlreadyExists(Address),
+
+    #[error("验证人 {0} 不存在")]
+    NotFound(Address),
+
+    #[error("公钥已被验证人 {0} 使用")]
+    DuplicateKey(Address),
+
+    #[error("公钥不能为空")]
+    EmptyKey,
+
+    #[error("投票权重必须大于 0")]
+    ZeroVotingPower,
+
+    #[error("不能移除最后一个验证人")]
+    LastValidator,
+}
+
+/// 按生效高度保存的验证人集合
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorSet {
+    /// 生效高度到该高度起的活跃集合，集合按地址排序
+    snapshots: BTreeMap<u64, Vec<ValidatorInfo>>,
+}
+
+impl ValidatorSet {
+    /// 以创世验证人初始化，高度 0 起生效
+    pub fn new(validators: Vec<ValidatorInfo>) -> Result<Self, ValidatorSetError> {
+        let mut set = Self::default();
+        for validator in validators {
+            set.apply(ValidatorChange::Add { validator }, 0)?;
+        }
+        Ok(set)
+    }
+
+    /// `height` 时的活跃集合
+    pub fn active_at(&self, height: u64) -> Vec<ValidatorInfo> {
+        self.snapshots
+            .range(..=height)
+            .next_back()
+            .map(|(_, validators)| validators.clone())
+            .unwrap_or_default()
+    }
+
+    /// `height` 时活跃验证人的地址
+    pub fn addresses_at(&self, height: u64) -> Vec<Address> {
+        self.active_at(height)
+            .into_iter()
+            .map(|validator| validator.address)
+            .collect()
+    }
+
+    /// `height` 时的总投票权重
+    pub fn total_voting_power(&self, height: u64) -> u64 {
+        self.active_at(height)
+            .iter()
+            .fold(0u64, |total, validator| {
+                total.saturating_add(validator.voting_power)
+            })
+    }
+
+    /// 应用变更，从 `height` 起生效
+    ///
+    /// 变更基于最新的集合，同一高度的多次变更依次叠加。
+    pub fn apply(&mut self, change: ValidatorChange, height: u64) -> Result<(), ValidatorSetError> {
+        let mut validators = self
+            .snapshots
+            .values()
+            .next_back()
+            .cloned()
+            .unwrap_or_default();
+        let position = |validators: &[ValidatorInfo], address: &Address| {
+            validators
+                .iter()
+                .position(|validator| validator.address == *address)
+                .ok_or(ValidatorSetError::NotFound(*address))
+        };
+        let check_key = |validators: &[ValidatorInfo], key: &Bytes| {
+            if key.is_empty() {
+                return Err(ValidatorSetError::EmptyKey);
+            }
+            match validators
+                .iter()
+                .find(|validator| validator.public_key == *key)
+            {
+                Some(owner) => Err(ValidatorSetError::DuplicateKey(owner.address)),
+                None => Ok(()),
+            }
+        };
+        match change {
+            ValidatorChange::Add { validator } => {
+                if position(&validators, &validator.address).is_ok() {
+                    return Err(ValidatorSetError::AlreadyExists(validator.address));
+                }
+                if validator.voting_power == 0 {
+                    return Err(ValidatorSetError::ZeroVotingPower);
+                }
+                check_key(&validators, &validator.public_key)?;
+                validators.push(validator);
+                validators.sort_by_key(|validator| validator.address);
+            }
+            ValidatorChange::Remove { address } => {
+                let index = position(&validators, &address)?;
+                if validators.len() == 1 {
+                    return Err(ValidatorSetError::LastValidator);
+                }
+                validators.remove(index);
+            }
+            ValidatorChange::RotateKey {
+                address,
+                public_key,
+            } => {
+                let index = position(&validators, &address)?;
+                check_key(&validators, &public_key)?;
+                validators[index].public_key = public_key;
+            }
+            ValidatorChange::SetVotingPower {
+                address,
+                voting_power,
+            } => {
+                let index = position(&validators, &address)?;
+                if voting_power == 0 {
+                    return Err(ValidatorSetError::ZeroVotingPower);
+                }
+                validators[index].voting_power = voting_power;
+            }
+        }
+        self.snapshots.insert(height, validators);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(id: u8, power: u64) -> ValidatorInfo {
+        ValidatorInfo {
+            address: Address([id; 20]),
+            public_key: Bytes::from(vec![id; 33]),
+            voting_power: power,
+        }
+    }
+
+    #[test]
+    fn test_validator_set() {
+        let mut set = ValidatorSet::new(vec![validator(2, 10), validator(1, 10)]).unwrap();
+        assert_eq!(
+            set.addresses_at(0),
+            vec![Address([1u8; 20]), Address([2u8; 20])]
+        );
+
+        set.apply(
+            ValidatorChange::Add {
+                validator: validator(3, 5),
+            },
+            5,
+        )
+        .unwrap();
+        set.apply(
+            ValidatorChange::SetVotingPower {
+                address: Address([1u8; 20]),
+                voting_power: 30,
+            },
+            8,
+        )
+        .unwrap();
+        set.apply(
+            ValidatorChange::Remove {
+                address: Address([2u8; 20]),
+            },
+            8,
+        )
+        .unwrap();
+
+        // 历史高度仍返回当时的集合
+        assert_eq!(set.total_voting_power(4), 20);
+        assert_eq!(set.total_voting_power(5), 25);
+        assert_eq!(set.total_voting_power(100), 35);
+        assert_eq!(
+            set.addresses_at(8),
+            vec![Address([1u8; 20]), Address([3u8; 20])]
+        );
+
+        let rotated = Bytes::from(vec![9u8; 33]);
+        set.apply(
+            ValidatorChange::RotateKey {
+                address: Address([3u8; 20]),
+                public_key: rotated.clone(),
+            },
+            9,
+        )
+        .unwrap();
+        assert_eq!(set.active_at(9)[1].public_key, rotated);
+        assert_eq!(
+            set.apply(
+                ValidatorChange::RotateKey {
+                    address: Address([1u8; 20]),
+                    public_key: rotated,
+                },
+                10,
+            ),
+            Err(ValidatorSetError::DuplicateKey(Address([3u8; 20])))
+        );
+    }
+
+    #[test]
+    fn test_invalid_changes() {
+        let mut set = ValidatorSet::new(vec![validator(1, 10)]).unwrap();
+        assert_eq!(
+            set.apply(
+                ValidatorChange::Add {
+                    validator: validator(1, 10)
+                },
+                1
+            ),
+            Err(ValidatorSetError::AlreadyExists(Address([1u8; 20])))
+        );
+        assert_eq!(
+            set.apply(
+                ValidatorChange::Add {
+                    validator: validator(2, 0)
+                },
+                1
+            ),
+            Err(ValidatorSetError::ZeroVotingPower)
+        );
+        assert_eq!(
+            set.apply(
+                ValidatorChange::Remove {
+                    address: Address([1u8; 20])
+                },
+                1
+            ),
+            Err(ValidatorSetError::LastValidator)
+        );
+        assert_eq!(
+            set.apply(
+                ValidatorChange::Remove {
+                    address: Address([2u8; 20])
+                },
+                1
+            ),
+            Err(ValidatorSetError::NotFound(Address([2u8; 20])))
+        );
+
+        let change: ValidatorChange = serde_json::from_value(serde_json::json!({
+            "type": "setVotingPower",
+            "address": Address([1u8; 20]),
+            "votingPower": 3,
+        }))
+        .unwrap();
+        set.apply(change, 2).unwrap();
+        assert_eq!(set.total_voting_power(2), 3);
+    }
+}
@@ -0,0 +1,311 @@
+//! 合约部署许可
+//!
+//! 企业子网常只允许登记过的地址部署合约。创世配置 `deployment` 选择名单模式：白名单模式下
+//! 只有名单内的地址可以部署，黑名单模式下名单内的地址不能部署。配置了 `governor` 时，治理地址
+//! 可向部署许可系统地址发送 `setDeployer(address,bool)` 交易增删名单，修改记录在系统地址的
+//! 存储槽中，随区块状态一起提交。
+//!
+//! 交易池和区块校验阶段由 [`DeploymentPolicy`] 拒绝无权部署的创建交易；合约内的 CREATE、CREATE2
+//! 在解释器中按交易发送方（ORIGIN）检查，无权部署时以 `Error(string)` 回滚。
+
+use crate::account::Address;
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use crate::tx_validator::{TxValidator, ValidationContext};
+use async_trait::async_trait;
+use ethers::types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// 部署许可系统合约地址
+pub const DEPLOYMENT_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x07,
+]);
+
+/// 系统合约占位代码（实际逻辑由 VM 原生执行）
+pub const DEPLOYMENT_CODE: &[u8] = &[0x00];
+
+/// 修改名单交易消耗的 gas
+pub const SET_DEPLOYER_GAS: u64 = 30_000;
+
+/// 存储槽中的名单标记，0 表示未经治理修改，按创世名单判断
+const LISTED: u64 = 1;
+const UNLISTED: u64 = 2;
+
+/// 名单模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeploymentMode {
+    /// 只有名单内的地址可以部署合约
+    Allowlist,
+    /// 名单内的地址不能部署合约
+    Denylist,
+}
+
+/// 合约部署许可配置（来自创世文件）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentConfig {
+    pub mode: DeploymentMode,
+    /// 创世名单
+    #[serde(default)]
+    pub deployers: Vec<Address>,
+    /// 可修改名单的治理地址，未配置时名单固定
+    #[serde(default)]
+    pub governor: Option<Address>,
+}
+
+/// 部署许可错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum DeploymentError {
+    #[error("{0} 无权部署合约")]
+    NotAllowed(Address),
+
+    #[error("{0} 不是部署许可治理地址")]
+    NotGovernor(Address),
+
+    #[error("无效的治理交易: {0}")]
+    InvalidTransaction(String),
+}
+
+/// `setDeployer(address,bool)` 的函数选择器
+pub fn set_deployer_selector() -> [u8; 4] {
+    let hash = ethers::utils::keccak256("setDeployer(address,bool)");
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// 判断交易是否发送至部署许可系统合约
+pub fn is_deployment_transaction(tx: &Transaction) -> bool {
+    tx.to == Some(DEPLOYMENT_ADDRESS)
+}
+
+fn slot(deployer: &Address) -> [u8; 32] {
+    let mut preimage = b"fairvm-deployer".to_vec();
+    preimage.extend_from_slice(&deployer.0);
+    ethers::utils::keccak256(preimage)
+}
+
+/// 地址是否在名单中，治理修改优先于创世名单
+pub async fn is_listed(
+    storage: &(dyn Storage + Send + Sync),
+    config: &DeploymentConfig,
+    deployer: &Address,
+) -> bool {
+    let word = storage
+        .get_storage_value(&DEPLOYMENT_ADDRESS, slot(deployer))
+        .await;
+    match U256::from_big_endian(&word).low_u64() {
+        LISTED => true,
+        UNLISTED => false,
+        _ => config.deployers.contains(deployer),
+    }
+}
+
+/// 地址是否可以部署合约
+pub async fn is_allowed(
+    storage: &(dyn Storage + Send + Sync),
+    config: &DeploymentConfig,
+    deployer: &Address,
+) -> bool {
+    let listed = is_listed(storage, config, deployer).await;
+    match config.mode {
+        DeploymentMode::Allowlist => listed,
+        DeploymentMode::Denylist => !listed,
+    }
+}
+
+/// 无权部署时合约创建的回滚数据
+pub fn revert_data(deployer: Address) -> Vec<u8> {
+    crate::evm::RevertReason::Error(DeploymentError::NotAllowed(deployer).to_string()).encode()
+}
+
+/// 执行治理交易增删名单，返回产生的日志
+pub async fn set_deployer(
+    storage: &mut (dyn Storage + Send + Sync),
+    config: &DeploymentConfig,
+    tx: &Transaction,
+) -> Result<Vec<(Address, Vec<H256>, Vec<u8>)>, DeploymentError> {
+    if !is_deployment_transaction(tx) {
+        return Err(DeploymentError::InvalidTransaction(
+            "目标地址不是部署许可系统合约".into(),
+        ));
+    }
+    if config.governor != Some(tx.from) {
+        return Err(DeploymentError::NotGovernor(tx.from));
+    }
+    if tx.data.len() != 68 || tx.data[..4] != set_deployer_selector() {
+        return Err(DeploymentError::InvalidTransaction(
+            "需要 setDeployer(address,bool) 调用".into(),
+        ));
+    }
+    let (address, flag) = (&tx.data[4..36], &tx.data[36..68]);
+    if address[..12].iter().any(|byte| *byte != 0) {
+        return Err(DeploymentError::InvalidTransaction("地址参数不合法".into()));
+    }
+    let listed = U256::from_big_endian(flag);
+    if listed > U256::one() {
+        return Err(DeploymentError::InvalidTransaction("布尔参数不合法".into()));
+    }
+    let listed = listed == U256::one();
+    let mut deployer = [0u8; 20];
+    deployer.copy_from_slice(&address[12..]);
+    let deployer = Address(deployer);
+
+    let mut word = [0u8; 32];
+    U256::from(if listed { LISTED } else { UNLISTED }).to_big_endian(&mut word);
+    storage
+        .set_storage_value(&DEPLOYMENT_ADDRESS, slot(&deployer), word)
+        .await;
+
+    let mut topic = [0u8; 32];
+    topic[12..].copy_from_slice(&deployer.0);
+    Ok(vec![(
+        DEPLOYMENT_ADDRESS,
+        vec![
+            H256(ethers::utils::keccak256("DeployerChanged(address,bool)")),
+            H256(topic),
+        ],
+        flag.to_vec(),
+    )])
+}
+
+/// 按部署许可拒绝创建交易的校验规则，配置了 `deployment` 时自动注册
+#[derive(Debug, Clone)]
+pub struct DeploymentPolicy {
+    config: DeploymentConfig,
+}
+
+impl DeploymentPolicy {
+    pub fn new(config: DeploymentConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl TxValidator for DeploymentPolicy {
+    fn name(&self) -> &str {
+        "deployment-policy"
+    }
+
+    async fn validate(
+        &self,
+        tx: &Transaction,
+        context: &ValidationContext<'_>,
+    ) -> Result<(), String> {
+        if tx.to.is_some() || is_allowed(context.storage, &self.config, &tx.from).await {
+            Ok(())
+        } else {
+            Err(DeploymentError::NotAllowed(tx.from).to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+    use crate::tx_validator::ValidationStage;
+
+    fn config(mode: DeploymentMode) -> DeploymentConfig {
+        DeploymentConfig {
+            mode,
+            deployers: vec![Address([1u8; 20])],
+            governor: Some(Address([7u8; 20])),
+        }
+    }
+
+    fn transaction(from: Address, to: Option<Address>, data: Vec<u8>) -> Transaction {
+        Transaction::new(
+            H256::from_low_u64_be(1),
+            from,
+            to,
+            U256::zero(),
+            0,
+            SET_DEPLOYER_GAS,
+            Some(U256::zero()),
+            data,
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    fn vote(from: Address, deployer: Address, listed: bool) -> Transaction {
+        let mut data = set_deployer_selector().to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(&deployer.0);
+        let mut flag = [0u8; 32];
+        flag[31] = listed as u8;
+        data.extend_from_slice(&flag);
+        transaction(from, Some(DEPLOYMENT_ADDRESS), data)
+    }
+
+    #[tokio::test]
+    async fn test_governance() {
+        let config = config(DeploymentMode::Allowlist);
+        let mut storage = MemoryStorage::default();
+        let (listed, other) = (Address([1u8; 20]), Address([2u8; 20]));
+        assert!(is_allowed(&storage, &config, &listed).await);
+        assert!(!is_allowed(&storage, &config, &other).await);
+
+        assert_eq!(
+            set_deployer(&mut storage, &config, &vote(other, other, true)).await,
+            Err(DeploymentError::NotGovernor(other))
+        );
+        let governor = Address([7u8; 20]);
+        let logs = set_deployer(&mut storage, &config, &vote(governor, other, true))
+            .await
+            .unwrap();
+        assert_eq!(logs[0].1[1], H256::from(ethers::types::H160(other.0)));
+        set_deployer(&mut storage, &config, &vote(governor, listed, false))
+            .await
+            .unwrap();
+        assert!(is_allowed(&storage, &config, &other).await);
+        // 治理移除优先于创世名单
+        assert!(!is_allowed(&storage, &config, &listed).await);
+
+        let denylist = DeploymentConfig {
+            mode: DeploymentMode::Denylist,
+            ..config
+        };
+        assert!(!is_allowed(&storage, &denylist, &other).await);
+        assert!(is_allowed(&storage, &denylist, &Address([3u8; 20])).await);
+    }
+
+    #[tokio::test]
+    async fn test_policy() {
+        let storage = MemoryStorage::default();
+        let context = ValidationContext {
+            stage: ValidationStage::Mempool,
+            block_number: 1,
+            storage: &storage,
+        };
+        let policy = DeploymentPolicy::new(config(DeploymentMode::Allowlist));
+        let other = Address([2u8; 20]);
+        assert!(policy
+            .validate(&transaction(Address([1u8; 20]), None, vec![0x00]), &context)
+            .await
+            .is_ok());
+        assert!(policy
+            .validate(
+                &transaction(other, Some(Address([9u8; 20])), Vec::new()),
+                &context
+            )
+            .await
+            .is_ok());
+        assert_eq!(
+            policy
+                .validate(&transaction(other, None, vec![0x00]), &context)
+                .await,
+            Err(DeploymentError::NotAllowed(other).to_string())
+        );
+        assert_eq!(
+            crate::evm::RevertReason::decode(&revert_data(other)),
+            Some(crate::evm::RevertReason::Error(
+                DeploymentError::NotAllowed(other).to_string()
+            ))
+        );
+    }
+}
@@ -46,15 +46,78 @@ pub enum EventType {
     Error {
         error: String,
     },
+    /// 交易进入内存池
+    TransactionReceived {
+        hash: H256,
+        from: Address,
+    },
+    /// 交易被打包进区块
+    TransactionIncluded {
+        hash: H256,
+        block_number: u64,
+        block_hash: H256,
+        index: u64,
+    },
+    /// 交易所在区块已最终确认
+    TransactionFinalized {
+        hash: H256,
+        block_number: u64,
+    },
+    /// 交易被丢弃
+    TransactionDropped {
+        hash: H256,
+        reason: String,
+    },
+    /// 纪元结束，质押奖励已记入待领取金额
+    EpochRewards {
+        epoch: u64,
+        total: U256,
+    },
+    /// 验证人在线率过低被监禁
+    ValidatorJailed {
+        node_id: String,
+        at: u64,
+    },
+    /// 验证人集合变更，从 `height` 起生效
+    ValidatorChange {
+        height: u64,
+        change: crate::consensus::ValidatorChange,
+    },
     BlockCreated,
     BlockFinalized,
-    TransactionReceived,
     TransactionProcessed,
     StateChanged,
     ConsensusStateChanged,
     NetworkMessage,
 }
 
+impl EventType {
+    /// 事件名称，用于订阅过滤
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventType::Block { .. } => "block",
+            EventType::Transaction { .. } => "transaction",
+            EventType::Account { .. } => "account",
+            EventType::NFT { .. } => "nft",
+            EventType::Consensus { .. } => "consensus",
+            EventType::Error { .. } => "error",
+            EventType::TransactionReceived { .. } => "transaction_received",
+            EventType::TransactionIncluded { .. } => "transaction_included",
+            EventType::TransactionFinalized { .. } => "transaction_finalized",
+            EventType::TransactionDropped { .. } => "transaction_dropped",
+            EventType::EpochRewards { .. } => "epoch_rewards",
+            EventType::ValidatorJailed { .. } => "validator_jailed",
+            EventType::ValidatorChange { .. } => "validator_change",
+            EventType::BlockCreated => "block_created",
+            EventType::BlockFinalized => "block_finalized",
+            EventType::TransactionProcessed => "transaction_processed",
+            EventType::StateChanged => "state_changed",
+            EventType::ConsensusStateChanged => "consensus_state_changed",
+            EventType::NetworkMessage => "network_message",
+        }
+    }
+}
+
 /// 事件
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
@@ -0,0 +1,518 @@
+//! 事件流水线输出
+//!
+//! 将链上事件发布到 Kafka（`kafka` 特性）或 NATS JetStream（`nats` 特性）。
+//! 负载为带版本号的 JSON 信封，发布失败时持续重试直到消息被确认，
+//! 因此投递语义为至少一次，消费者应按 `sequence` 去重。
+//!
+//! 事件先追加到队列目录中的 `events.jsonl` 再发布，内存中不缓存待发布的事件；已确认发布的
+//! 序号上界记录在 `published` 文件中。节点崩溃或重启后从第一个未确认的事件继续发布，序号
+//! 在上次的基础上递增，不会重复分配。
+
+use crate::event::{Event, EventHandler};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Notify;
+
+/// 事件负载的结构版本
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// 首次重试等待时间
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// 重试等待时间上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// 后台任务每次从队列文件读取的事件数
+const READ_BATCH: usize = 64;
+
+/// 队列文件名
+const EVENTS_FILE: &str = "events.jsonl";
+
+/// 已确认序号文件名
+const PUBLISHED_FILE: &str = "published";
+
+/// 事件输出错误
+#[derive(Debug, Error)]
+pub enum SinkError {
+    #[error("序列化失败: {0}")]
+    Serialization(String),
+
+    #[error("连接失败: {0}")]
+    Connection(String),
+
+    #[error("发布失败: {0}")]
+    Publish(String),
+
+    #[error("事件队列读写失败: {0}")]
+    Outbox(String),
+}
+
+impl From<io::Error> for SinkError {
+    fn from(e: io::Error) -> Self {
+        Self::Outbox(e.to_string())
+    }
+}
+
+/// 发布到消息系统的事件信封
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// 结构版本
+    pub schema_version: u32,
+    /// 节点内单调递增的序号，跨重启延续，用于消费端去重
+    pub sequence: u64,
+    /// 事件名称
+    pub event_name: String,
+    /// 事件内容
+    pub event: Event,
+}
+
+/// 消息系统发布接口
+#[async_trait]
+pub trait SinkPublisher: Send + Sync {
+    /// 发布消息，返回成功表示消息已被服务端确认
+    async fn publish(&self, topic: &str, key: &str, payload: &[u8]) -> Result<(), SinkError>;
+}
+
+/// 事件输出配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkConfig {
+    /// 主题前缀，实际主题为 `{prefix}.{event_name}`
+    pub topic_prefix: String,
+    /// 订阅的事件名称，为空表示全部事件
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl Default for SinkConfig {
+    fn default() -> Self {
+        Self {
+            topic_prefix: "fairvm.events".to_string(),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl SinkConfig {
+    /// 事件对应的主题
+    pub fn topic(&self, event_name: &str) -> String {
+        format!("{}.{}", self.topic_prefix, event_name)
+    }
+
+    /// 事件是否需要输出
+    pub fn matches(&self, event: &Event) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event.event_type.name())
+    }
+}
+
+/// 待发布事件的磁盘队列
+///
+/// 全部事件确认发布后清空队列文件；清空前先写入已确认序号，崩溃后重新打开时跳过序号小于
+/// 该值的事件。文件末尾写了一半的事件在打开时截掉，该事件未分配出去，序号会被重新使用。
+struct Outbox {
+    dir: PathBuf,
+    file: File,
+    /// 已确认发布的序号上界（不含）
+    published: u64,
+    /// 下一个分配的序号
+    next: u64,
+    /// 下一个待发布事件在文件中的偏移
+    offset: u64,
+    /// 文件中已完整写入的长度
+    len: u64,
+}
+
+impl Outbox {
+    /// 打开队列目录，不存在时创建
+    fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let published = match fs::read_to_string(dir.join(PUBLISHED_FILE)) {
+            Ok(content) => content
+                .trim()
+                .parse()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        let path = dir.join(EVENTS_FILE);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let (mut offset, mut len, mut next) = (0, 0, published);
+        let mut reader = BufReader::new(File::open(&path)?);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)? as u64;
+            let Some(envelope) = line
+                .strip_suffix('\n')
+                .and_then(|line| serde_json::from_str::<EventEnvelope>(line).ok())
+            else {
+                break;
+            };
+            len += read;
+            if envelope.sequence < published {
+                offset = len;
+            }
+            next = next.max(envelope.sequence + 1);
+        }
+        file.set_len(len)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            published,
+            next,
+            offset,
+            len,
+        })
+    }
+
+    /// 分配序号并追加事件，写入磁盘后返回
+    fn append(&mut self, event: &Event) -> Result<(), SinkError> {
+        let envelope = EventEnvelope {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: self.next,
+            event_name: event.event_type.name().to_string(),
+            event: event.clone(),
+        };
+        let mut line =
+            serde_json::to_vec(&envelope).map_err(|e| SinkError::Serialization(e.to_string()))?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        self.len += line.len() as u64;
+        self.next += 1;
+        Ok(())
+    }
+
+    /// 读取至多 `limit` 个待发布事件及其在文件中的结束偏移
+    fn pending(&self, limit: usize) -> io::Result<Vec<(EventEnvelope, u64)>> {
+        let mut reader = BufReader::new(File::open(self.dir.join(EVENTS_FILE))?);
+        reader.seek(SeekFrom::Start(self.offset))?;
+        let mut pending = Vec::new();
+        let mut end = self.offset;
+        let mut line = String::new();
+        while pending.len() < limit && end < self.len {
+            line.clear();
+            end += reader.read_line(&mut line)? as u64;
+            let envelope = serde_json::from_str(line.trim_end())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            pending.push((envelope, end));
+        }
+        Ok(pending)
+    }
+
+    /// 记录序号为 `sequence`、结束偏移为 `end` 的事件已确认发布
+    fn ack(&mut self, sequence: u64, end: u64) -> io::Result<()> {
+        self.published = sequence + 1;
+        let tmp = self.dir.join(format!("{}.tmp", PUBLISHED_FILE));
+        fs::write(&tmp, self.published.to_string())?;
+        fs::rename(&tmp, self.dir.join(PUBLISHED_FILE))?;
+        self.offset = end;
+        if self.offset == self.len {
+            self.file.set_len(0)?;
+            self.offset = 0;
+            self.len = 0;
+        }
+        Ok(())
+    }
+}
+
+/// 事件输出处理器
+///
+/// 注册到事件管理器后把事件写入磁盘队列，由后台任务按顺序发布，不等待消息系统确认。
+pub struct EventSink {
+    /// 输出配置
+    config: SinkConfig,
+    /// 待发布事件队列
+    outbox: Arc<Mutex<Outbox>>,
+    /// 唤醒后台发布任务
+    notify: Arc<Notify>,
+}
+
+impl EventSink {
+    /// 打开 `dir` 中的事件队列并启动后台发布任务，需要在 tokio 运行时中调用
+    ///
+    /// 同一目录只能由一个事件输出使用；上次未确认的事件会先于新事件发布。
+    pub fn spawn(
+        config: SinkConfig,
+        dir: impl AsRef<Path>,
+        publisher: Arc<dyn SinkPublisher>,
+    ) -> Result<Arc<Self>, SinkError> {
+        let outbox = Arc::new(Mutex::new(Outbox::open(dir.as_ref())?));
+        let notify = Arc::new(Notify::new());
+        let worker_config = config.clone();
+        let worker_outbox = outbox.clone();
+        let worker_notify = notify.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let pending = lock(&worker_outbox).pending(READ_BATCH);
+                let pending = match pending {
+                    Ok(pending) if pending.is_empty() => {
+                        worker_notify.notified().await;
+                        continue;
+                    }
+                    Ok(pending) => pending,
+                    Err(e) => {
+                        log::error!("{}，事件输出任务停止", SinkError::from(e));
+                        return;
+                    }
+                };
+                for (envelope, end) in pending {
+                    publish_until_acked(&worker_config, publisher.as_ref(), &envelope).await;
+                    if let Err(e) = lock(&worker_outbox).ack(envelope.sequence, end) {
+                        log::error!("记录事件 {} 已发布失败: {}", envelope.sequence, e);
+                    }
+                }
+            }
+        });
+
+        Ok(Arc::new(Self {
+            config,
+            outbox,
+            notify,
+        }))
+    }
+}
+
+fn lock(outbox: &Mutex<Outbox>) -> MutexGuard<'_, Outbox> {
+    // 队列状态只在写入磁盘成功后更新，锁中毒时继续使用
+    outbox.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl EventHandler for EventSink {
+    fn handle_event(&self, event: &Event) {
+        if !self.config.matches(event) {
+            return;
+        }
+        if let Err(e) = lock(&self.outbox).append(event) {
+            log::error!("事件 {} 写入队列失败: {}", event.event_type.name(), e);
+            return;
+        }
+        self.notify.notify_one();
+    }
+}
+
+/// 持续重试直到消息被确认
+async fn publish_until_acked(
+    config: &SinkConfig,
+    publisher: &dyn SinkPublisher,
+    envelope: &EventEnvelope,
+) {
+    let payload = match serde_json::to_vec(envelope) {
+        Ok(payload) => payload,
+        Err(e) => {
+            log::error!("{}", SinkError::Serialization(e.to_string()));
+            return;
+        }
+    };
+    let topic = config.topic(&envelope.event_name);
+    let key = envelope.sequence.to_string();
+
+    let mut delay = INITIAL_RETRY_DELAY;
+    while let Err(e) = publisher.publish(&topic, &key, &payload).await {
+        log::warn!("事件 {} 发布到 {} 失败，稍后重试: {}", key, topic, e);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    }
+}
+
+/// Kafka 发布者
+#[cfg(feature = "kafka")]
+pub mod kafka {
+    use super::{SinkError, SinkPublisher};
+    use async_trait::async_trait;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+    use std::time::Duration;
+
+    /// 发送超时
+    const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// 基于 rdkafka 的发布者，启用幂等生产与全副本确认
+    pub struct KafkaPublisher {
+        producer: FutureProducer,
+    }
+
+    impl KafkaPublisher {
+        /// 连接 Kafka 集群
+        pub fn new(brokers: &str) -> Result<Self, SinkError> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("acks", "all")
+                .set("enable.idempotence", "true")
+                .create()
+                .map_err(|e| SinkError::Connection(e.to_string()))?;
+            Ok(Self { producer })
+        }
+    }
+
+    #[async_trait]
+    impl SinkPublisher for KafkaPublisher {
+        async fn publish(&self, topic: &str, key: &str, payload: &[u8]) -> Result<(), SinkError> {
+            self.producer
+                .send(
+                    FutureRecord::to(topic).key(key).payload(payload),
+                    SEND_TIMEOUT,
+                )
+                .await
+                .map(|_| ())
+                .map_err(|(e, _)| SinkError::Publish(e.to_string()))
+        }
+    }
+}
+
+/// NATS JetStream 发布者
+#[cfg(feature = "nats")]
+pub mod nats {
+    use super::{SinkError, SinkPublisher};
+    use async_trait::async_trait;
+
+    /// 基于 JetStream 的发布者，等待服务端确认
+    pub struct NatsPublisher {
+        jetstream: async_nats::jetstream::Context,
+    }
+
+    impl NatsPublisher {
+        /// 连接 NATS 服务器
+        pub async fn connect(url: &str) -> Result<Self, SinkError> {
+            let client = async_nats::connect(url)
+                .await
+                .map_err(|e| SinkError::Connection(e.to_string()))?;
+            Ok(Self {
+                jetstream: async_nats::jetstream::new(client),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl SinkPublisher for NatsPublisher {
+        async fn publish(&self, topic: &str, _key: &str, payload: &[u8]) -> Result<(), SinkError> {
+            self.jetstream
+                .publish(topic.to_string(), bytes::Bytes::copy_from_slice(payload))
+                .await
+                .map_err(|e| SinkError::Publish(e.to_string()))?
+                .await
+                .map(|_| ())
+                .map_err(|e| SinkError::Publish(e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventType;
+    use chrono::Utc;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    fn event(event_type: EventType) -> Event {
+        Event {
+            event_type,
+            data: json!({}),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// 前几次发布失败的内存发布者
+    struct FlakyPublisher {
+        failures: AtomicUsize,
+        published: Mutex<Vec<(String, EventEnvelope)>>,
+    }
+
+    #[async_trait]
+    impl SinkPublisher for FlakyPublisher {
+        async fn publish(&self, topic: &str, _key: &str, payload: &[u8]) -> Result<(), SinkError> {
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(SinkError::Publish("broker unavailable".to_string()));
+            }
+            let envelope = serde_json::from_slice(payload).unwrap();
+            self.published
+                .lock()
+                .await
+                .push((topic.to_string(), envelope));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_at_least_once_delivery() {
+        let publisher = Arc::new(FlakyPublisher {
+            failures: AtomicUsize::new(2),
+            published: Mutex::new(Vec::new()),
+        });
+        let config = SinkConfig {
+            events: vec!["block_finalized".to_string()],
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let sink = EventSink::spawn(config, dir.path(), publisher.clone()).unwrap();
+
+        for event_type in [EventType::BlockCreated, EventType::BlockFinalized] {
+            sink.handle_event(&event(event_type));
+        }
+
+        for _ in 0..50 {
+            if !publisher.published.lock().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let published = publisher.published.lock().await;
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].0, "fairvm.events.block_finalized");
+        assert_eq!(published[0].1.schema_version, EVENT_SCHEMA_VERSION);
+        assert_eq!(published[0].1.sequence, 0);
+    }
+
+    #[test]
+    fn test_outbox_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut outbox = Outbox::open(dir.path()).unwrap();
+        for _ in 0..3 {
+            outbox.append(&event(EventType::BlockCreated)).unwrap();
+        }
+        let pending = outbox.pending(READ_BATCH).unwrap();
+        assert_eq!(pending.len(), 3);
+        outbox.ack(0, pending[0].1).unwrap();
+
+        // 重启后从第一个未确认的事件继续，序号不重复分配
+        let mut outbox = Outbox::open(dir.path()).unwrap();
+        let sequences: Vec<u64> = outbox
+            .pending(READ_BATCH)
+            .unwrap()
+            .iter()
+            .map(|(envelope, _)| envelope.sequence)
+            .collect();
+        assert_eq!(sequences, vec![1, 2]);
+        outbox.append(&event(EventType::BlockFinalized)).unwrap();
+        let pending = outbox.pending(READ_BATCH).unwrap();
+        assert_eq!(pending[2].0.sequence, 3);
+
+        // 全部确认后清空队列文件，序号继续递增
+        outbox.ack(3, pending[2].1).unwrap();
+        assert!(outbox.pending(READ_BATCH).unwrap().is_empty());
+        assert_eq!(fs::metadata(dir.path().join(EVENTS_FILE)).unwrap().len(), 0);
+        let mut outbox = Outbox::open(dir.path()).unwrap();
+        outbox.append(&event(EventType::BlockCreated)).unwrap();
+        assert_eq!(outbox.pending(READ_BATCH).unwrap()[0].0.sequence, 4);
+
+        // 写了一半的事件在打开时截掉
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(EVENTS_FILE))
+            .unwrap();
+        file.write_all(b"{\"schema_version\"").unwrap();
+        let outbox = Outbox::open(dir.path()).unwrap();
+        assert_eq!(outbox.pending(READ_BATCH).unwrap().len(), 1);
+        assert_eq!(outbox.next, 5);
+    }
+}
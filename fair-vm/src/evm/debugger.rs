@@ -0,0 +1,487 @@
+//! 交互式调试会话
+//!
+//! 调试会话在当前状态之上重放交易，写入只记录在会话自己的写缓冲中，
+//! 不会影响链上状态。远程调试界面通过会话 ID 单步执行、设置断点，
+//! 并查看解释器的栈和内存。合约已登记源码映射时，会话状态附带当前指令
+//! 或出错指令对应的源码位置。
+//!
+//! 区块环境（高度、时间戳、基础费用、链 ID、出块地址）取自交易所在区块，与区块执行时相同；
+//! 状态则是链头的最新状态，而不是该交易执行前的状态，交易之后被修改过的账户和存储槽读到的是
+//! 修改后的值，重放结果可能与收据不同。空闲超过 [`DEBUG_SESSION_IDLE_TTL`] 的会话被回收。
+
+use super::interpreter::{Environment, EvmError, ExecutionStatus, Interpreter, Message, Step};
+use super::source_map::{SourceLocation, VerifiedContract};
+use crate::account::Address;
+use crate::storage::{OverlayStorage, StateHandle, Storage};
+use crate::transaction::Transaction;
+use ethers::types::{Bytes, H160, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 同时存在的调试会话上限
+pub const MAX_DEBUG_SESSIONS: usize = 16;
+
+/// 调试会话空闲超过该时长后被回收
+pub const DEBUG_SESSION_IDLE_TTL: Duration = Duration::from_secs(600);
+
+/// 单次继续执行的最大步数，防止死循环占用节点
+pub const MAX_STEPS_PER_CALL: usize = 100_000;
+
+/// 调试错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum DebugError {
+    #[error("调试会话 {0} 不存在")]
+    SessionNotFound(u64),
+
+    #[error("调试会话数量已达上限 {0}")]
+    TooManySessions(usize),
+
+    #[error("执行错误: {0}")]
+    Evm(#[from] EvmError),
+}
+
+/// 调试会话状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionState {
+    pub session_id: u64,
+    pub tx_hash: H256,
+    /// 执行代码的账户地址
+    pub address: Address,
+    /// 调用的函数签名，合约创建或选择器未知时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// 下一条要执行的指令位置
+    pub pc: usize,
+    /// 下一条要执行的指令，执行结束时为空
+    pub next_opcode: Option<String>,
+    pub gas_used: u64,
+    pub gas_remaining: u64,
+    pub stack_depth: usize,
+    pub memory_size: usize,
+    pub status: ExecutionStatus,
+    pub return_data: Bytes,
+    pub breakpoints: Vec<usize>,
+    /// 当前指令的源码位置，执行结束后为结束指令的位置
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+/// 单次执行调用的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepResult {
+    /// 本次执行的指令
+    pub steps: Vec<Step>,
+    /// 是否停在断点上
+    pub hit_breakpoint: bool,
+    pub state: SessionState,
+}
+
+/// 调试会话
+#[derive(Debug)]
+pub struct DebugSession {
+    id: u64,
+    tx_hash: H256,
+    interpreter: Interpreter,
+    storage: OverlayStorage,
+    breakpoints: BTreeSet<usize>,
+    source: Option<Arc<VerifiedContract>>,
+    function: Option<String>,
+    /// 最近一次访问的时间
+    last_used: Instant,
+}
+
+impl DebugSession {
+    /// 在 `base` 之上创建交易的调试会话，合约创建交易执行其初始化代码
+    ///
+    /// `env` 为交易所在区块的执行环境，发送方和 gas 价格按交易填写；`function` 为选择器数据库
+    /// 解析出的被调用函数签名。
+    pub async fn new(
+        id: u64,
+        tx: &Transaction,
+        base: StateHandle,
+        env: Environment,
+        source: Option<Arc<VerifiedContract>>,
+        function: Option<String>,
+    ) -> Self {
+        let storage = OverlayStorage::new(base);
+        let (address, code, data) = match tx.to {
+            Some(to) => (to, storage.get_code(&to).await, tx.data.clone()),
+            None => (
+                Address::from(ethers::utils::get_contract_address(
+                    H160(tx.from.0),
+                    tx.nonce,
+                )),
+                tx.data.clone(),
+                Vec::new(),
+            ),
+        };
+        let message = Message {
+            caller: tx.from,
+            address,
+            value: tx.value,
+            data,
+            is_static: false,
+            depth: 0,
+        };
+        let env = Environment {
+            origin: tx.from,
+            gas_price: tx.effective_gas_price(env.base_fee),
+            ..env
+        };
+        Self {
+            id,
+            tx_hash: tx.hash,
+            interpreter: Interpreter::with_message(message, code, tx.gas_limit, Arc::new(env)),
+            storage,
+            breakpoints: BTreeSet::new(),
+            source,
+            function,
+            last_used: Instant::now(),
+        }
+    }
+
+    /// 会话 ID
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 解释器
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    /// 执行一条指令，不检查断点
+    pub async fn step(&mut self) -> Result<Step, DebugError> {
+        Ok(self.interpreter.step(&mut self.storage).await?)
+    }
+
+    /// 执行至多 `count` 条指令，遇到断点、执行结束或执行异常时提前停止
+    ///
+    /// 当前位置上的断点不会阻止第一步，便于从断点处继续执行。
+    pub async fn run(&mut self, count: usize) -> StepResult {
+        let mut steps = Vec::new();
+        let mut hit_breakpoint = false;
+        while steps.len() < count && !self.interpreter.is_halted() {
+            match self.interpreter.step(&mut self.storage).await {
+                Ok(step) => steps.push(step),
+                Err(_) => break,
+            }
+            if self.breakpoints.contains(&self.interpreter.pc()) {
+                hit_breakpoint = true;
+                break;
+            }
+        }
+        StepResult {
+            steps,
+            hit_breakpoint,
+            state: self.state(),
+        }
+    }
+
+    /// 设置或取消断点，返回断点是否处于设置状态
+    pub fn toggle_breakpoint(&mut self, pc: usize) -> bool {
+        if self.breakpoints.remove(&pc) {
+            false
+        } else {
+            self.breakpoints.insert(pc);
+            true
+        }
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> SessionState {
+        let interpreter = &self.interpreter;
+        SessionState {
+            session_id: self.id,
+            tx_hash: self.tx_hash,
+            address: interpreter.address(),
+            function: self.function.clone(),
+            pc: interpreter.pc(),
+            next_opcode: (!interpreter.is_halted()).then(|| {
+                super::interpreter::opcode_name(
+                    interpreter
+                        .code()
+                        .get(interpreter.pc())
+                        .copied()
+                        .unwrap_or(0x00),
+                )
+            }),
+            gas_used: interpreter.gas_used(),
+            gas_remaining: interpreter.gas_remaining(),
+            stack_depth: interpreter.stack().len(),
+            memory_size: interpreter.memory().len(),
+            status: interpreter.status().clone(),
+            return_data: interpreter.return_data().to_vec().into(),
+            breakpoints: self.breakpoints.iter().copied().collect(),
+            location: self.source.as_ref().and_then(|source| {
+                source.locate(interpreter.halted_at().unwrap_or(interpreter.pc()))
+            }),
+        }
+    }
+
+    /// 栈，栈顶在前
+    pub fn stack(&self) -> Vec<U256> {
+        self.interpreter.stack().iter().rev().copied().collect()
+    }
+
+    /// 内存区间，超出已分配内存的部分截断
+    pub fn memory(&self, offset: usize, length: usize) -> Vec<u8> {
+        let memory = self.interpreter.memory();
+        let start = offset.min(memory.len());
+        let end = offset.saturating_add(length).min(memory.len());
+        memory[start..end].to_vec()
+    }
+}
+
+/// 调试会话管理器
+#[derive(Debug, Default)]
+pub struct DebugSessions {
+    sessions: HashMap<u64, DebugSession>,
+    next_id: u64,
+}
+
+impl DebugSessions {
+    /// 创建调试会话，返回会话 ID，先回收空闲超时的会话
+    pub async fn start(
+        &mut self,
+        tx: &Transaction,
+        base: StateHandle,
+        env: Environment,
+        source: Option<Arc<VerifiedContract>>,
+        function: Option<String>,
+    ) -> Result<u64, DebugError> {
+        self.expire_idle(Instant::now());
+        if self.sessions.len() >= MAX_DEBUG_SESSIONS {
+            return Err(DebugError::TooManySessions(MAX_DEBUG_SESSIONS));
+        }
+        self.next_id += 1;
+        let id = self.next_id;
+        self.sessions.insert(
+            id,
+            DebugSession::new(id, tx, base, env, source, function).await,
+        );
+        Ok(id)
+    }
+
+    /// 获取调试会话，访问会刷新空闲计时
+    pub fn get(&mut self, id: u64) -> Result<&DebugSession, DebugError> {
+        self.get_mut(id).map(|session| &*session)
+    }
+
+    /// 获取可变调试会话，访问会刷新空闲计时
+    pub fn get_mut(&mut self, id: u64) -> Result<&mut DebugSession, DebugError> {
+        let now = Instant::now();
+        self.expire_idle(now);
+        let session = self
+            .sessions
+            .get_mut(&id)
+            .ok_or(DebugError::SessionNotFound(id))?;
+        session.last_used = now;
+        Ok(session)
+    }
+
+    /// 回收截至 `now` 空闲超过 [`DEBUG_SESSION_IDLE_TTL`] 的会话，返回回收的数量
+    pub fn expire_idle(&mut self, now: Instant) -> usize {
+        let before = self.sessions.len();
+        self.sessions.retain(|_, session| {
+            now.saturating_duration_since(session.last_used) <= DEBUG_SESSION_IDLE_TTL
+        });
+        before - self.sessions.len()
+    }
+
+    /// 结束调试会话
+    pub fn end(&mut self, id: u64) -> bool {
+        self.sessions.remove(&id).is_some()
+    }
+
+    /// 会话数量
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// 是否没有会话
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStorage, StateService};
+    use crate::transaction::TransactionType;
+
+    #[tokio::test]
+    async fn test_debug_session() {
+        let contract = Address([7u8; 20]);
+        let mut base = MemoryStorage::new();
+        // 槽 0 写入 1，再将 0x2a 写入内存
+        base.set_code(
+            &contract,
+            vec![
+                0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x2a, 0x60, 0x00, 0x52, 0x00,
+            ],
+        )
+        .await;
+        let base = StateService::spawn(Box::new(base));
+        let tx = Transaction::new(
+            H256::from_low_u64_be(1),
+            Address([1u8; 20]),
+            Some(contract),
+            U256::zero(),
+            0,
+            100_000,
+            Some(U256::one()),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+
+        let mut sessions = DebugSessions::default();
+        let id = sessions
+            .start(&tx, base.clone(), Environment::default(), None, None)
+            .await
+            .unwrap();
+        let session = sessions.get_mut(id).unwrap();
+        assert_eq!(session.state().next_opcode.as_deref(), Some("PUSH1"));
+
+        let step = session.step().await.unwrap();
+        assert_eq!(step.name, "PUSH1");
+        assert_eq!(session.stack(), vec![U256::one()]);
+
+        assert!(session.toggle_breakpoint(7));
+        let result = session.run(MAX_STEPS_PER_CALL).await;
+        assert!(result.hit_breakpoint);
+        assert_eq!(result.state.pc, 7);
+        assert_eq!(session.stack(), vec![U256::from(0x2a)]);
+
+        let result = session.run(MAX_STEPS_PER_CALL).await;
+        assert!(!result.hit_breakpoint);
+        assert_eq!(result.state.status, ExecutionStatus::Stopped);
+        assert_eq!(session.memory(31, 10), vec![0x2a]);
+        assert!(matches!(
+            session.step().await,
+            Err(DebugError::Evm(EvmError::Halted))
+        ));
+
+        // 会话中的写入不影响底层存储
+        let slot = base.get_storage_value(&contract, [0u8; 32]).await;
+        assert_eq!(slot, [0u8; 32]);
+
+        assert!(sessions.end(id));
+        assert!(matches!(
+            sessions.get(id),
+            Err(DebugError::SessionNotFound(_))
+        ));
+
+        // 空闲超时的会话被回收
+        let id = sessions
+            .start(&tx, base.clone(), Environment::default(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(sessions.expire_idle(Instant::now()), 0);
+        let later = Instant::now() + DEBUG_SESSION_IDLE_TTL + Duration::from_secs(1);
+        assert_eq!(sessions.expire_idle(later), 1);
+        assert!(matches!(
+            sessions.get(id),
+            Err(DebugError::SessionNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_block_environment() {
+        let contract = Address([7u8; 20]);
+        // NUMBER, BASEFEE, STOP
+        let mut base = MemoryStorage::new();
+        base.set_code(&contract, vec![0x43, 0x48, 0x00]).await;
+        let base = StateService::spawn(Box::new(base));
+        let tx = Transaction::new(
+            H256::from_low_u64_be(3),
+            Address([1u8; 20]),
+            Some(contract),
+            U256::zero(),
+            0,
+            100_000,
+            Some(U256::from(10)),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        let mut env = Environment {
+            base_fee: U256::from(7),
+            ..Environment::default()
+        };
+        env.block.block_number = 42;
+
+        let mut session = DebugSession::new(1, &tx, base, env, None, None).await;
+        session.run(MAX_STEPS_PER_CALL).await;
+        assert_eq!(session.stack(), vec![U256::from(7), U256::from(42)]);
+    }
+
+    #[tokio::test]
+    async fn test_revert_location() {
+        use crate::evm::source_map::{ContractSource, SourceFile};
+
+        let contract = Address([7u8; 20]);
+        // PUSH1 00, DUP1, REVERT
+        let code = vec![0x60, 0x00, 0x80, 0xfd];
+        let content = "contract A {\n    function f() public {\n        revert();\n    }\n}\n";
+        let offset = content.find("revert").unwrap();
+        let source = VerifiedContract::new(
+            &code,
+            ContractSource {
+                source_map: format!("0:10:0;;{}:8:0", offset),
+                sources: vec![SourceFile {
+                    name: "A.sol".to_string(),
+                    content: content.to_string(),
+                }],
+            },
+        )
+        .unwrap();
+
+        let mut base = MemoryStorage::new();
+        base.set_code(&contract, code).await;
+        let base = StateService::spawn(Box::new(base));
+        let tx = Transaction::new(
+            H256::from_low_u64_be(2),
+            Address([1u8; 20]),
+            Some(contract),
+            U256::zero(),
+            0,
+            100_000,
+            Some(U256::one()),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+
+        let mut session = DebugSession::new(
+            1,
+            &tx,
+            base,
+            Environment::default(),
+            Some(Arc::new(source)),
+            None,
+        )
+        .await;
+        assert_eq!(session.state().location.unwrap().line, 1);
+        let result = session.run(MAX_STEPS_PER_CALL).await;
+        assert_eq!(result.state.status, ExecutionStatus::Reverted);
+        let location = result.state.location.unwrap();
+        assert_eq!((location.file.as_str(), location.line), ("A.sol", 3));
+        assert_eq!(location.text, "revert();");
+    }
+}
@@ -0,0 +1,222 @@
+//! 合约交易执行
+//!
+//! 合约创建交易和目标账户有代码的调用交易在解释器中执行。执行前先扣除固有 gas
+//! （21000，创建交易另加 32000，调用数据每个零字节 4、非零字节 16），余下的 gas
+//! 交给顶层调用帧。执行失败时顶层调用帧撤销本交易的全部状态修改，发送方的 nonce
+//! 在执行前已经递增，不会回滚。执行结束后按 EIP-3529 退还 gas，退款不超过已用 gas
+//! 的 1/5，结果中的 `gas_used` 为扣除退款后的值。
+
+use super::interpreter::{Environment, Interpreter, LogEntry, Message, MAX_REFUND_QUOTIENT};
+use crate::account::Address;
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use ethers::types::H160;
+use fair_vm_core::vm::ExecutionResult;
+use std::sync::Arc;
+
+/// 交易的基础 gas
+pub const TX_GAS: u64 = 21_000;
+
+/// 合约创建交易额外的 gas
+pub const TX_CREATE_GAS: u64 = 32_000;
+
+const TX_DATA_ZERO_GAS: u64 = 4;
+const TX_DATA_NON_ZERO_GAS: u64 = 16;
+
+/// 交易的固有 gas
+pub fn intrinsic_gas(tx: &Transaction) -> u64 {
+    let data: u64 = tx
+        .data
+        .iter()
+        .map(|byte| {
+            if *byte == 0 {
+                TX_DATA_ZERO_GAS
+            } else {
+                TX_DATA_NON_ZERO_GAS
+            }
+        })
+        .sum();
+    let create = if tx.to.is_none() { TX_CREATE_GAS } else { 0 };
+    TX_GAS + create + data
+}
+
+/// 交易是否需要在解释器中执行：合约创建交易，或目标账户有代码
+pub async fn is_contract_transaction(
+    storage: &(dyn Storage + Send + Sync),
+    tx: &Transaction,
+) -> bool {
+    match tx.to {
+        Some(to) => !storage.get_code(&to).await.is_empty(),
+        None => true,
+    }
+}
+
+/// 在解释器中执行交易，返回执行结果和成功时产生的日志
+///
+/// `env` 为区块环境，交易相关的 ORIGIN 和 GASPRICE 按交易填写。
+/// gas 不足以支付固有 gas 时交易失败并耗尽全部 gas。
+pub async fn execute(
+    storage: &mut (dyn Storage + Send + Sync),
+    tx: &Transaction,
+    env: &Environment,
+) -> (ExecutionResult, Vec<LogEntry>) {
+    let intrinsic = intrinsic_gas(tx);
+    if tx.gas_limit < intrinsic {
+        let result = ExecutionResult {
+            gas_used: tx.gas_limit,
+            return_data: Vec::new(),
+            status: false,
+            gas_refunded: 0,
+        };
+        return (result, Vec::new());
+    }
+
+    let env = Arc::new(Environment {
+        origin: tx.from,
+        gas_price: tx.effective_gas_price(env.base_fee),
+        ..env.clone()
+    });
+    let (address, code, data) = match tx.to {
+        Some(to) => (to, storage.get_code(&to).await, tx.data.clone()),
+        None => (
+            Address::from(ethers::utils::get_contract_address(
+                H160(tx.from.0),
+                tx.nonce,
+            )),
+            tx.data.clone(),
+            Vec::new(),
+        ),
+    };
+    let message = Message {
+        caller: tx.from,
+        address,
+        value: tx.value,
+        data,
+        is_static: false,
+        depth: 0,
+    };
+    let mut interpreter = Interpreter::with_message(message, code, tx.gas_limit - intrinsic, env);
+    let status = match tx.to {
+        Some(_) => interpreter.call(storage).await,
+        None => interpreter.create(storage).await,
+    };
+    // 执行失败时退款计数随状态修改一起撤销，这里不必单独处理
+    let gas_used = intrinsic + interpreter.gas_used();
+    let gas_refunded = interpreter.gas_refund().min(gas_used / MAX_REFUND_QUOTIENT);
+    let result = ExecutionResult {
+        gas_used: gas_used - gas_refunded,
+        return_data: interpreter.return_data().to_vec(),
+        status,
+        gas_refunded,
+    };
+    (result, interpreter.take_logs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+    use ethers::types::{H256, U256};
+
+    fn transaction(to: Option<Address>, nonce: u64, value: u64, data: Vec<u8>) -> Transaction {
+        Transaction::new(
+            H256::from_low_u64_be(nonce + 1),
+            Address([1u8; 20]),
+            to,
+            U256::from(value),
+            nonce,
+            1_000_000,
+            Some(U256::zero()),
+            data,
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_intrinsic_gas() {
+        let tx = transaction(Some(Address([2u8; 20])), 0, 0, vec![0, 1]);
+        assert_eq!(intrinsic_gas(&tx), TX_GAS + 4 + 16);
+        let tx = transaction(None, 0, 0, Vec::new());
+        assert_eq!(intrinsic_gas(&tx), TX_GAS + TX_CREATE_GAS);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_and_call() {
+        let mut storage = MemoryStorage::new();
+        let sender = Address([1u8; 20]);
+        storage.set_balance(&sender, U256::from(1_000)).await;
+
+        // 初始化代码返回运行时代码，运行时代码记录 CALLVALUE 并返回 42
+        let runtime = vec![
+            0x34, 0x60, 0x00, 0x55, 0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ];
+        let mut init = vec![0x6d];
+        init.extend_from_slice(&runtime);
+        init.extend_from_slice(&[0x60, 0x00, 0x52, 0x60, 0x0e, 0x60, 0x12, 0xf3]);
+
+        let create = transaction(None, 0, 0, init);
+        assert!(is_contract_transaction(&storage, &create).await);
+        let (result, _) = execute(&mut storage, &create, &Environment::default()).await;
+        assert!(result.status);
+        assert!(result.gas_used > TX_GAS + TX_CREATE_GAS);
+        let contract = Address::from(ethers::utils::get_contract_address(H160(sender.0), 0u64));
+        assert_eq!(storage.get_code(&contract).await, runtime);
+
+        let call = transaction(Some(contract), 1, 300, Vec::new());
+        let (result, logs) = execute(&mut storage, &call, &Environment::default()).await;
+        assert!(result.status);
+        assert!(logs.is_empty());
+        assert_eq!(U256::from_big_endian(&result.return_data), U256::from(42));
+        assert_eq!(storage.get_balance(&contract).await, U256::from(300));
+        assert_eq!(storage.get_balance(&sender).await, U256::from(700));
+        let slot = storage.get_storage_value(&contract, [0u8; 32]).await;
+        assert_eq!(U256::from_big_endian(&slot), U256::from(300));
+
+        // 余额不足时转账和执行都不生效
+        let call = transaction(Some(contract), 2, 5_000, Vec::new());
+        let (result, _) = execute(&mut storage, &call, &Environment::default()).await;
+        assert!(!result.status);
+        assert_eq!(storage.get_balance(&contract).await, U256::from(300));
+
+        let mut short = transaction(Some(contract), 3, 0, Vec::new());
+        short.gas_limit = TX_GAS - 1;
+        let (result, _) = execute(&mut storage, &short, &Environment::default()).await;
+        assert_eq!((result.status, result.gas_used), (false, TX_GAS - 1));
+    }
+
+    #[tokio::test]
+    async fn test_refund() {
+        let mut storage = MemoryStorage::new();
+        let contract = Address([2u8; 20]);
+        // 清零槽 0 和槽 1
+        let code = vec![
+            0x60, 0x00, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x01, 0x55, 0x00,
+        ];
+        storage.set_code(&contract, code).await;
+        let mut one = [0u8; 32];
+        one[31] = 1;
+
+        // 只有槽 0 非零：冷写 2100 + 2900 和 2100 + 100，退款 4800 未达上限
+        storage.set_storage_value(&contract, [0u8; 32], one).await;
+        let call = transaction(Some(contract), 0, 0, Vec::new());
+        let (result, _) = execute(&mut storage, &call, &Environment::default()).await;
+        assert!(result.status);
+        let gas = TX_GAS + 12 + 5_000 + 2_200;
+        assert_eq!(result.gas_refunded, 4_800);
+        assert_eq!(result.gas_used, gas - 4_800);
+
+        // 两个槽都非零：退款 9600 超过已用 gas 的 1/5
+        storage.set_storage_value(&contract, [0u8; 32], one).await;
+        storage.set_storage_value(&contract, one, one).await;
+        let call = transaction(Some(contract), 1, 0, Vec::new());
+        let (result, _) = execute(&mut storage, &call, &Environment::default()).await;
+        let gas = TX_GAS + 12 + 2 * 5_000;
+        assert_eq!(result.gas_refunded, gas / MAX_REFUND_QUOTIENT);
+        assert_eq!(result.gas_used, gas - gas / MAX_REFUND_QUOTIENT);
+    }
+}
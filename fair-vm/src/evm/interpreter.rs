@@ -0,0 +1,1822 @@
+//! EVM 字节码解释器
+//!
+//! 解释器按指令单步执行，每一步之后都可以检查程序计数器、栈和内存，
+//! 调试会话和完整执行共用同一套执行逻辑。
+//!
+//! CALL、CREATE 等指令在子调用帧中递归执行。每个调用帧记录自己修改过的账户和
+//! 存储槽的原值，调用帧以 REVERT 或执行异常结束时按记录撤销修改并丢弃日志，
+//! 成功时把记录和日志合并到父调用帧。gas 按 Shanghai 规则计算：账户和存储槽在交易中
+//! 首次访问时按冷访问收费（EIP-2929），SSTORE 按存储槽的原值、当前值和新值计算消耗和
+//! 退款（EIP-2200、EIP-3529），访问记录和退款计数随调用帧一起撤销；子调用最多转发
+//! 剩余 gas 的 63/64（EIP-150）；SELFDESTRUCT 按 EIP-6780 只清除同一交易中创建的合约。
+//! 预编译合约尚未实现，调用其地址按无代码账户处理。
+
+use super::EvmContext;
+use crate::account::{Account, Address};
+use crate::deployment::{self, DeploymentConfig};
+use crate::storage::Storage;
+use crate::vesting::VestingSchedules;
+use ethers::types::{H160, H256, U256, U512};
+use ethers::utils::{get_contract_address, get_create2_address, keccak256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// 栈深度上限
+pub const STACK_LIMIT: usize = 1024;
+
+/// 内存大小上限，超过时按 gas 不足处理
+pub const MEMORY_LIMIT: usize = 32 * 1024 * 1024;
+
+/// 调用深度上限
+pub const CALL_DEPTH_LIMIT: usize = 1024;
+
+/// 部署代码的大小上限（EIP-170）
+pub const MAX_CODE_SIZE: usize = 24_576;
+
+/// 转账调用额外附带给被调用方的 gas
+const CALL_STIPEND: u64 = 2_300;
+
+/// 转账调用的额外 gas
+const CALL_VALUE_GAS: u64 = 9_000;
+
+/// 向空账户转账时创建账户的 gas
+const NEW_ACCOUNT_GAS: u64 = 25_000;
+
+/// 部署代码每字节的 gas
+const CODE_DEPOSIT_GAS: u64 = 200;
+
+/// 冷访问账户的 gas（EIP-2929）
+const COLD_ACCOUNT_ACCESS_GAS: u64 = 2_600;
+
+/// 冷访问存储槽的 gas
+const COLD_SLOAD_GAS: u64 = 2_100;
+
+/// 热访问账户或存储槽的 gas
+const WARM_ACCESS_GAS: u64 = 100;
+
+/// SSTORE 将零值原值改为非零值的 gas
+const SSTORE_SET_GAS: u64 = 20_000;
+
+/// SSTORE 修改非零原值的 gas，不含冷访问
+const SSTORE_RESET_GAS: u64 = 2_900;
+
+/// 清零存储槽的退款（EIP-3529）
+const SSTORE_CLEARS_REFUND: u64 = 4_800;
+
+/// 退款最多抵扣交易已用 gas 的 1/5（EIP-3529）
+pub const MAX_REFUND_QUOTIENT: u64 = 5;
+
+/// 预编译合约地址 0x01 到 0x09 始终为热访问
+const PRECOMPILE_COUNT: u64 = 9;
+
+/// 日志条目：合约地址、主题和数据
+pub type LogEntry = (Address, Vec<H256>, Vec<u8>);
+
+/// 解释器错误类型
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum EvmError {
+    #[error("栈下溢: pc {0}")]
+    StackUnderflow(usize),
+
+    #[error("栈溢出: pc {0}")]
+    StackOverflow(usize),
+
+    #[error("无效跳转目标: {0}")]
+    InvalidJump(U256),
+
+    #[error("无效操作码 0x{opcode:02x}: pc {pc}")]
+    InvalidOpcode { pc: usize, opcode: u8 },
+
+    #[error("gas 不足")]
+    OutOfGas,
+
+    #[error("执行已结束")]
+    Halted,
+
+    #[error("静态调用中不能修改状态: pc {0}")]
+    WriteProtection(usize),
+
+    #[error("返回数据越界")]
+    ReturnDataOutOfBounds,
+
+    #[error("余额不足")]
+    InsufficientBalance,
+
+    #[error("合约地址已被占用")]
+    AddressCollision,
+
+    #[error("部署代码 {0} 字节超过上限")]
+    CodeSizeLimit(usize),
+}
+
+/// 调用消息
+#[derive(Debug, Clone, Default)]
+pub struct Message {
+    /// 调用者，即 CALLER
+    pub caller: Address,
+    /// 执行代码的账户地址，存储读写和日志作用于该账户
+    pub address: Address,
+    /// 随调用转移的金额，即 CALLVALUE
+    pub value: U256,
+    /// 调用数据
+    pub data: Vec<u8>,
+    /// 是否处于 STATICCALL 中，不允许修改状态
+    pub is_static: bool,
+    /// 调用深度，交易的顶层调用为 0
+    pub depth: usize,
+}
+
+/// 交易和区块环境，同一交易的所有调用帧共享
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    /// 交易发送方，即 ORIGIN
+    pub origin: Address,
+    pub gas_price: U256,
+    pub chain_id: u64,
+    pub base_fee: U256,
+    pub block: EvmContext,
+    /// 最近 256 个区块的哈希，供 BLOCKHASH 使用
+    pub block_hashes: Arc<HashMap<u64, H256>>,
+    /// 合约部署许可，未配置时任何地址都可部署
+    pub deployment: Option<DeploymentConfig>,
+    /// 创世账户的锁仓计划，锁定部分不能转出
+    pub vesting: Arc<VestingSchedules>,
+}
+
+/// 状态修改记录，保存修改前的值
+#[derive(Debug, Clone)]
+enum JournalEntry {
+    Account(Address, Option<Account>),
+    Storage(Address, [u8; 32], [u8; 32]),
+    /// 首次访问的账户
+    AccessedAddress(Address),
+    /// 首次访问的存储槽
+    AccessedSlot(Address, [u8; 32]),
+    /// 修改前的退款计数
+    Refund(u64),
+}
+
+/// 同一交易的所有调用帧共享的状态，子调用执行期间交给子调用帧持有
+#[derive(Debug, Clone, Default)]
+struct Substate {
+    /// 本交易中创建的合约及其写过的存储槽
+    created: HashMap<Address, BTreeSet<[u8; 32]>>,
+    /// 已访问的账户
+    accessed_addresses: HashSet<Address>,
+    /// 已访问的存储槽
+    accessed_slots: HashSet<(Address, [u8; 32])>,
+    /// 写过的存储槽在交易开始时的值
+    original: HashMap<(Address, [u8; 32]), [u8; 32]>,
+    /// 退款计数
+    refund: u64,
+}
+
+/// 执行状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExecutionStatus {
+    /// 仍可继续执行
+    Running,
+    /// 执行到 STOP 或代码末尾
+    Stopped,
+    /// 执行到 RETURN
+    Returned,
+    /// 执行到 REVERT
+    Reverted,
+    /// 执行异常
+    Failed(String),
+}
+
+impl ExecutionStatus {
+    /// 是否执行成功
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Stopped | Self::Returned)
+    }
+}
+
+/// 单步执行记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Step {
+    /// 执行前的程序计数器
+    pub pc: usize,
+    /// 操作码
+    pub opcode: u8,
+    /// 操作码助记符
+    pub name: String,
+    /// 本步消耗的 gas
+    pub gas_cost: u64,
+    /// 执行后剩余 gas
+    pub gas_remaining: u64,
+    /// 执行后的栈深度
+    pub stack_depth: usize,
+}
+
+/// 操作码助记符
+pub fn opcode_name(opcode: u8) -> String {
+    let name = match opcode {
+        0x00 => "STOP",
+        0x01 => "ADD",
+        0x02 => "MUL",
+        0x03 => "SUB",
+        0x04 => "DIV",
+        0x05 => "SDIV",
+        0x06 => "MOD",
+        0x07 => "SMOD",
+        0x08 => "ADDMOD",
+        0x09 => "MULMOD",
+        0x0a => "EXP",
+        0x0b => "SIGNEXTEND",
+        0x10 => "LT",
+        0x11 => "GT",
+        0x12 => "SLT",
+        0x13 => "SGT",
+        0x14 => "EQ",
+        0x15 => "ISZERO",
+        0x16 => "AND",
+        0x17 => "OR",
+        0x18 => "XOR",
+        0x19 => "NOT",
+        0x1a => "BYTE",
+        0x1b => "SHL",
+        0x1c => "SHR",
+        0x1d => "SAR",
+        0x20 => "SHA3",
+        0x30 => "ADDRESS",
+        0x31 => "BALANCE",
+        0x32 => "ORIGIN",
+        0x33 => "CALLER",
+        0x34 => "CALLVALUE",
+        0x35 => "CALLDATALOAD",
+        0x36 => "CALLDATASIZE",
+        0x37 => "CALLDATACOPY",
+        0x38 => "CODESIZE",
+        0x39 => "CODECOPY",
+        0x3a => "GASPRICE",
+        0x3b => "EXTCODESIZE",
+        0x3c => "EXTCODECOPY",
+        0x3d => "RETURNDATASIZE",
+        0x3e => "RETURNDATACOPY",
+        0x3f => "EXTCODEHASH",
+        0x40 => "BLOCKHASH",
+        0x41 => "COINBASE",
+        0x42 => "TIMESTAMP",
+        0x43 => "NUMBER",
+        0x44 => "DIFFICULTY",
+        0x45 => "GASLIMIT",
+        0x46 => "CHAINID",
+        0x47 => "SELFBALANCE",
+        0x48 => "BASEFEE",
+        0x50 => "POP",
+        0x51 => "MLOAD",
+        0x52 => "MSTORE",
+        0x53 => "MSTORE8",
+        0x54 => "SLOAD",
+        0x55 => "SSTORE",
+        0x56 => "JUMP",
+        0x57 => "JUMPI",
+        0x58 => "PC",
+        0x59 => "MSIZE",
+        0x5a => "GAS",
+        0x5b => "JUMPDEST",
+        0x5f => "PUSH0",
+        0x60..=0x7f => return format!("PUSH{}", opcode - 0x5f),
+        0x80..=0x8f => return format!("DUP{}", opcode - 0x7f),
+        0x90..=0x9f => return format!("SWAP{}", opcode - 0x8f),
+        0xa0..=0xa4 => return format!("LOG{}", opcode - 0xa0),
+        0xf0 => "CREATE",
+        0xf1 => "CALL",
+        0xf2 => "CALLCODE",
+        0xf3 => "RETURN",
+        0xf4 => "DELEGATECALL",
+        0xf5 => "CREATE2",
+        0xfa => "STATICCALL",
+        0xfd => "REVERT",
+        0xfe => "INVALID",
+        0xff => "SELFDESTRUCT",
+        _ => return format!("0x{:02x}", opcode),
+    };
+    name.to_string()
+}
+
+/// 操作码的固定 gas 消耗，访问账户和存储槽的指令按热访问计，不支持的操作码返回 `None`
+fn static_gas(opcode: u8) -> Option<u64> {
+    let gas = match opcode {
+        0x00 | 0xf3 | 0xfd => 0,
+        0x5b => 1,
+        0x30 | 0x32..=0x34 | 0x36 | 0x38 | 0x3a | 0x3d | 0x41..=0x46 | 0x48 => 2,
+        0x58 | 0x59 | 0x5a | 0x5f | 0x50 => 2,
+        0x35 | 0x37 | 0x39 | 0x3e => 3,
+        0x01 | 0x03 | 0x10..=0x1d | 0x51..=0x53 | 0x60..=0x9f => 3,
+        0x02 | 0x04..=0x07 | 0x0b | 0x47 => 5,
+        0x08 | 0x09 | 0x56 => 8,
+        0x0a | 0x57 => 10,
+        0x40 => 20,
+        0x20 => 30,
+        0xa0..=0xa4 => 375,
+        0x31 | 0x3b | 0x3c | 0x3f | 0x54 | 0xf1 | 0xf2 | 0xf4 | 0xfa => 100,
+        0x55 => 0,
+        0xff => 5_000,
+        0xf0 | 0xf5 => 32_000,
+        _ => return None,
+    };
+    Some(gas)
+}
+
+/// 按字数计算内存占用的 gas
+fn memory_gas(words: u64) -> u64 {
+    3 * words + words * words / 512
+}
+
+/// 字节数对应的字数
+fn words(len: usize) -> u64 {
+    ((len + 31) / 32) as u64
+}
+
+fn is_negative(value: U256) -> bool {
+    value.bit(255)
+}
+
+fn negate(value: U256) -> U256 {
+    (!value).overflowing_add(U256::one()).0
+}
+
+fn abs(value: U256) -> U256 {
+    if is_negative(value) {
+        negate(value)
+    } else {
+        value
+    }
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+fn bool_word(value: bool) -> U256 {
+    if value {
+        U256::one()
+    } else {
+        U256::zero()
+    }
+}
+
+fn truncate(value: U512) -> U256 {
+    U256::try_from(value).expect("取模结果小于 2^256")
+}
+
+fn to_address(value: U256) -> Address {
+    Address::from(H160::from_slice(&word(value)[12..]))
+}
+
+fn address_word(address: Address) -> U256 {
+    U256::from_big_endian(&address.0)
+}
+
+/// 读取 `source[offset..offset + len]`，超出 `source` 的部分按 0 补齐
+fn padded(source: &[u8], offset: U256, len: usize) -> Vec<u8> {
+    let mut data = vec![0u8; len];
+    if offset < U256::from(source.len()) {
+        let start = offset.as_usize();
+        let end = (start + len).min(source.len());
+        data[..end - start].copy_from_slice(&source[start..end]);
+    }
+    data
+}
+
+/// 账户不存在或为空账户（EIP-161）
+fn is_empty(account: Option<&Account>) -> bool {
+    account.map_or(true, |account| {
+        account.nonce == 0 && account.balance.is_zero() && account.code_hash.is_zero()
+    })
+}
+
+/// EVM 解释器
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    message: Message,
+    env: Arc<Environment>,
+    code: Vec<u8>,
+    /// 合法跳转目标，PUSH 数据中的 0x5b 不算
+    jumpdests: Vec<bool>,
+    pc: usize,
+    stack: Vec<U256>,
+    memory: Vec<u8>,
+    gas_limit: u64,
+    gas_used: u64,
+    return_data: Vec<u8>,
+    /// 最近一次子调用返回的数据，即 RETURNDATA
+    sub_return_data: Vec<u8>,
+    status: ExecutionStatus,
+    /// 结束执行的指令位置
+    halted_at: Option<usize>,
+    journal: Vec<JournalEntry>,
+    logs: Vec<LogEntry>,
+    substate: Substate,
+}
+
+impl Interpreter {
+    /// 创建解释器，在空的交易和区块环境中执行 `address` 上的代码
+    pub fn new(address: Address, code: Vec<u8>, gas_limit: u64) -> Self {
+        let message = Message {
+            address,
+            ..Message::default()
+        };
+        Self::with_message(message, code, gas_limit, Arc::new(Environment::default()))
+    }
+
+    /// 创建执行调用消息的解释器
+    pub fn with_message(
+        message: Message,
+        code: Vec<u8>,
+        gas_limit: u64,
+        env: Arc<Environment>,
+    ) -> Self {
+        let mut jumpdests = vec![false; code.len()];
+        let mut pc = 0;
+        while pc < code.len() {
+            match code[pc] {
+                0x5b => jumpdests[pc] = true,
+                opcode @ 0x60..=0x7f => pc += (opcode - 0x5f) as usize,
+                _ => {}
+            }
+            pc += 1;
+        }
+        // 顶层调用帧的初始热访问账户：调用双方、预编译合约和出块地址（EIP-3651），
+        // 子调用帧改用父调用帧的共享状态
+        let mut accessed_addresses: HashSet<Address> = (1..=PRECOMPILE_COUNT)
+            .map(|index| Address::from(H160::from_low_u64_be(index)))
+            .collect();
+        accessed_addresses.extend([
+            message.caller,
+            message.address,
+            Address::from(env.block.miner),
+        ]);
+        Self {
+            message,
+            env,
+            code,
+            jumpdests,
+            pc: 0,
+            stack: Vec::new(),
+            memory: Vec::new(),
+            gas_limit,
+            gas_used: 0,
+            return_data: Vec::new(),
+            sub_return_data: Vec::new(),
+            status: ExecutionStatus::Running,
+            halted_at: None,
+            journal: Vec::new(),
+            logs: Vec::new(),
+            substate: Substate {
+                accessed_addresses,
+                ..Substate::default()
+            },
+        }
+    }
+
+    /// 执行代码的账户地址
+    pub fn address(&self) -> Address {
+        self.message.address
+    }
+
+    /// 调用消息
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    /// 字节码
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// 程序计数器
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// 栈，栈顶在末尾
+    pub fn stack(&self) -> &[U256] {
+        &self.stack
+    }
+
+    /// 内存
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// 已消耗的 gas
+    pub fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    /// 剩余 gas
+    pub fn gas_remaining(&self) -> u64 {
+        self.gas_limit - self.gas_used
+    }
+
+    /// RETURN 或 REVERT 返回的数据
+    pub fn return_data(&self) -> &[u8] {
+        &self.return_data
+    }
+
+    /// 退款计数，交易结束时按 [`MAX_REFUND_QUOTIENT`] 封顶
+    pub fn gas_refund(&self) -> u64 {
+        self.substate.refund
+    }
+
+    /// 已产生的日志，包括成功的子调用产生的日志
+    pub fn logs(&self) -> &[LogEntry] {
+        &self.logs
+    }
+
+    /// 执行状态
+    pub fn status(&self) -> &ExecutionStatus {
+        &self.status
+    }
+
+    /// 结束执行的指令位置，REVERT 或执行异常时即出错位置
+    pub fn halted_at(&self) -> Option<usize> {
+        self.halted_at
+    }
+
+    /// 是否已结束执行
+    pub fn is_halted(&self) -> bool {
+        self.status != ExecutionStatus::Running
+    }
+
+    /// 执行到结束
+    pub async fn run(&mut self, storage: &mut (dyn Storage + Send + Sync)) -> Result<(), EvmError> {
+        while !self.is_halted() {
+            self.step(storage).await?;
+        }
+        Ok(())
+    }
+
+    /// 子调用帧在父调用帧的指令中执行，需要装箱才能递归
+    fn run_nested<'a>(
+        &'a mut self,
+        storage: &'a mut (dyn Storage + Send + Sync),
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin(async move {
+            let _ = self.run(storage).await;
+        })
+    }
+
+    /// 执行消息调用：先从调用者向目标账户转入金额，再执行代码，返回是否成功
+    pub async fn call(&mut self, storage: &mut (dyn Storage + Send + Sync)) -> bool {
+        let (caller, address, value) = (
+            self.message.caller,
+            self.message.address,
+            self.message.value,
+        );
+        if !self.transfer(storage, caller, address, value).await {
+            self.fail(storage, EvmError::InsufficientBalance).await;
+            return false;
+        }
+        self.run_nested(storage).await;
+        self.status.is_success()
+    }
+
+    /// 执行合约创建：创建账户、转入金额、执行初始化代码并部署返回的代码，返回是否成功
+    ///
+    /// 目标地址已有代码或 nonce 时创建失败并耗尽全部 gas。交易发送方无权部署合约时以
+    /// `Error(string)` 回滚，工厂合约代为部署同样受限。
+    pub async fn create(&mut self, storage: &mut (dyn Storage + Send + Sync)) -> bool {
+        let (caller, address, value) = (
+            self.message.caller,
+            self.message.address,
+            self.message.value,
+        );
+        if let Some(config) = &self.env.deployment {
+            let origin = self.env.origin;
+            if !deployment::is_allowed(storage, config, &origin).await {
+                self.return_data = deployment::revert_data(origin);
+                self.status = ExecutionStatus::Reverted;
+                return false;
+            }
+        }
+        let existing = storage.get_account(&address).await;
+        if existing
+            .as_ref()
+            .is_some_and(|account| account.nonce > 0 || !account.code_hash.is_zero())
+        {
+            self.fail(storage, EvmError::AddressCollision).await;
+            return false;
+        }
+        self.journal
+            .push(JournalEntry::Account(address, existing.clone()));
+        let mut account = existing.unwrap_or_else(|| Account::new(address));
+        account.nonce = 1;
+        storage.set_account(&account).await;
+        self.substate.created.entry(address).or_default();
+        self.substate.accessed_addresses.insert(address);
+        if !self.transfer(storage, caller, address, value).await {
+            self.fail(storage, EvmError::InsufficientBalance).await;
+            return false;
+        }
+
+        self.run_nested(storage).await;
+        if !self.status.is_success() {
+            return false;
+        }
+        let code = self.return_data.clone();
+        let deposit = if code.len() > MAX_CODE_SIZE {
+            Err(EvmError::CodeSizeLimit(code.len()))
+        } else {
+            self.charge(CODE_DEPOSIT_GAS * code.len() as u64)
+        };
+        match deposit {
+            Ok(()) => {
+                self.journal_account(storage, address).await;
+                storage.set_code(&address, code).await;
+                true
+            }
+            Err(e) => {
+                self.fail(storage, e).await;
+                false
+            }
+        }
+    }
+
+    /// 取出已产生的日志
+    pub fn take_logs(&mut self) -> Vec<LogEntry> {
+        std::mem::take(&mut self.logs)
+    }
+
+    /// 执行一条指令
+    ///
+    /// 执行异常时状态变为 [`ExecutionStatus::Failed`] 并耗尽全部 gas。
+    /// REVERT 或执行异常时撤销本调用帧的状态修改。
+    pub async fn step(
+        &mut self,
+        storage: &mut (dyn Storage + Send + Sync),
+    ) -> Result<Step, EvmError> {
+        if self.is_halted() {
+            return Err(EvmError::Halted);
+        }
+        let pc = self.pc;
+        let opcode = self.code.get(pc).copied().unwrap_or(0x00);
+        let gas_before = self.gas_used;
+        let result = self.execute(opcode, storage).await;
+        if result.is_err() || self.is_halted() {
+            self.halted_at = Some(pc);
+        }
+        match result {
+            Ok(()) => {
+                if self.status == ExecutionStatus::Reverted {
+                    self.revert(storage).await;
+                }
+                Ok(Step {
+                    pc,
+                    opcode,
+                    name: opcode_name(opcode),
+                    gas_cost: self.gas_used.saturating_sub(gas_before),
+                    gas_remaining: self.gas_remaining(),
+                    stack_depth: self.stack.len(),
+                })
+            }
+            Err(e) => {
+                self.fail(storage, e.clone()).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// 以执行异常结束，耗尽全部 gas 并撤销本调用帧的状态修改
+    async fn fail(&mut self, storage: &mut (dyn Storage + Send + Sync), error: EvmError) {
+        self.gas_used = self.gas_limit;
+        self.status = ExecutionStatus::Failed(error.to_string());
+        self.return_data.clear();
+        self.revert(storage).await;
+    }
+
+    /// 按记录撤销本调用帧的状态修改，日志一并丢弃
+    async fn revert(&mut self, storage: &mut (dyn Storage + Send + Sync)) {
+        while let Some(entry) = self.journal.pop() {
+            match entry {
+                JournalEntry::Account(address, account) => {
+                    let account = account.unwrap_or_else(|| Account::new(address));
+                    storage.set_account(&account).await;
+                }
+                JournalEntry::Storage(address, key, value) => {
+                    storage.set_storage_value(&address, key, value).await;
+                }
+                JournalEntry::AccessedAddress(address) => {
+                    self.substate.accessed_addresses.remove(&address);
+                }
+                JournalEntry::AccessedSlot(address, key) => {
+                    self.substate.accessed_slots.remove(&(address, key));
+                }
+                JournalEntry::Refund(refund) => self.substate.refund = refund,
+            }
+        }
+        self.logs.clear();
+    }
+
+    async fn journal_account(&mut self, storage: &(dyn Storage + Send + Sync), address: Address) {
+        let account = storage.get_account(&address).await;
+        self.journal.push(JournalEntry::Account(address, account));
+    }
+
+    async fn set_storage(
+        &mut self,
+        storage: &mut (dyn Storage + Send + Sync),
+        address: Address,
+        key: [u8; 32],
+        value: [u8; 32],
+    ) {
+        let previous = storage.get_storage_value(&address, key).await;
+        self.journal
+            .push(JournalEntry::Storage(address, key, previous));
+        storage.set_storage_value(&address, key, value).await;
+        if let Some(slots) = self.substate.created.get_mut(&address) {
+            slots.insert(key);
+        }
+    }
+
+    /// 记录账户访问，返回是否为冷访问
+    fn access_address(&mut self, address: Address) -> bool {
+        let cold = self.substate.accessed_addresses.insert(address);
+        if cold {
+            self.journal.push(JournalEntry::AccessedAddress(address));
+        }
+        cold
+    }
+
+    /// 记录存储槽访问，返回是否为冷访问
+    fn access_slot(&mut self, address: Address, key: [u8; 32]) -> bool {
+        let cold = self.substate.accessed_slots.insert((address, key));
+        if cold {
+            self.journal.push(JournalEntry::AccessedSlot(address, key));
+        }
+        cold
+    }
+
+    /// 收取冷访问账户的附加 gas，热访问的部分已计入固定消耗
+    fn charge_account_access(&mut self, address: Address) -> Result<(), EvmError> {
+        if self.access_address(address) {
+            self.charge(COLD_ACCOUNT_ACCESS_GAS - WARM_ACCESS_GAS)?;
+        }
+        Ok(())
+    }
+
+    fn set_refund(&mut self, refund: u64) {
+        if refund != self.substate.refund {
+            self.journal
+                .push(JournalEntry::Refund(self.substate.refund));
+            self.substate.refund = refund;
+        }
+    }
+
+    /// SSTORE，按存储槽的原值、当前值和新值收取 gas 并调整退款计数
+    async fn sstore(
+        &mut self,
+        storage: &mut (dyn Storage + Send + Sync),
+        key: [u8; 32],
+        value: [u8; 32],
+    ) -> Result<(), EvmError> {
+        // 只有转账附带的 gas 时不能写存储（EIP-2200）
+        if self.gas_remaining() <= CALL_STIPEND {
+            return Err(EvmError::OutOfGas);
+        }
+        let address = self.message.address;
+        if self.access_slot(address, key) {
+            self.charge(COLD_SLOAD_GAS)?;
+        }
+        let current = storage.get_storage_value(&address, key).await;
+        let original = *self
+            .substate
+            .original
+            .entry((address, key))
+            .or_insert(current);
+        if current == value {
+            return self.charge(WARM_ACCESS_GAS);
+        }
+
+        let zero = [0u8; 32];
+        let mut refund = self.substate.refund;
+        if original == current {
+            self.charge(if original == zero {
+                SSTORE_SET_GAS
+            } else {
+                SSTORE_RESET_GAS
+            })?;
+            if value == zero {
+                refund += SSTORE_CLEARS_REFUND;
+            }
+        } else {
+            // 本交易中已经改过的存储槽只收热访问的 gas，并修正之前计入的退款
+            self.charge(WARM_ACCESS_GAS)?;
+            if original != zero {
+                if current == zero {
+                    refund = refund.saturating_sub(SSTORE_CLEARS_REFUND);
+                }
+                if value == zero {
+                    refund += SSTORE_CLEARS_REFUND;
+                }
+            }
+            if original == value {
+                let charged = if original == zero {
+                    SSTORE_SET_GAS
+                } else {
+                    SSTORE_RESET_GAS
+                };
+                refund += charged - WARM_ACCESS_GAS;
+            }
+        }
+        self.set_refund(refund);
+        self.set_storage(storage, address, key, value).await;
+        Ok(())
+    }
+
+    /// 转账并记录到本调用帧，余额不足或动用锁仓锁定部分时返回 `false`
+    async fn transfer(
+        &mut self,
+        storage: &mut (dyn Storage + Send + Sync),
+        from: Address,
+        to: Address,
+        value: U256,
+    ) -> bool {
+        if value.is_zero() {
+            return true;
+        }
+        let balance = storage.get_balance(&from).await;
+        if balance < value {
+            return false;
+        }
+        let timestamp = self.env.block.timestamp;
+        if self
+            .env
+            .vesting
+            .check(&from, balance, value, timestamp)
+            .is_err()
+        {
+            return false;
+        }
+        if from == to {
+            return true;
+        }
+        self.journal_account(storage, from).await;
+        self.journal_account(storage, to).await;
+        storage.set_balance(&from, balance - value).await;
+        let to_balance = storage.get_balance(&to).await;
+        storage
+            .set_balance(&to, to_balance.saturating_add(value))
+            .await;
+        true
+    }
+
+    /// 子调用结束后退还剩余 gas，子调用成功时合并其状态修改记录和日志
+    fn absorb(&mut self, child: &mut Interpreter) {
+        self.gas_used = self.gas_used.saturating_sub(child.gas_remaining());
+        self.substate = std::mem::take(&mut child.substate);
+        if child.status.is_success() {
+            self.journal.append(&mut child.journal);
+            self.logs.append(&mut child.logs);
+        }
+    }
+
+    fn charge(&mut self, gas: u64) -> Result<(), EvmError> {
+        match self.gas_used.checked_add(gas) {
+            Some(used) if used <= self.gas_limit => {
+                self.gas_used = used;
+                Ok(())
+            }
+            _ => Err(EvmError::OutOfGas),
+        }
+    }
+
+    fn pop(&mut self) -> Result<U256, EvmError> {
+        self.stack.pop().ok_or(EvmError::StackUnderflow(self.pc))
+    }
+
+    fn push(&mut self, value: U256) -> Result<(), EvmError> {
+        if self.stack.len() >= STACK_LIMIT {
+            return Err(EvmError::StackOverflow(self.pc));
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    /// 扩展内存以覆盖 `[offset, offset + len)`，按新增字数收取 gas
+    fn expand_memory(&mut self, offset: U256, len: usize) -> Result<usize, EvmError> {
+        if len == 0 {
+            return Ok(0);
+        }
+        if offset > U256::from(MEMORY_LIMIT) {
+            return Err(EvmError::OutOfGas);
+        }
+        let offset = offset.as_usize();
+        let end = offset + len;
+        if end > MEMORY_LIMIT {
+            return Err(EvmError::OutOfGas);
+        }
+        if end > self.memory.len() {
+            let old_words = (self.memory.len() / 32) as u64;
+            let new_words = ((end + 31) / 32) as u64;
+            self.charge(memory_gas(new_words) - memory_gas(old_words))?;
+            self.memory.resize(new_words as usize * 32, 0);
+        }
+        Ok(offset)
+    }
+
+    /// 扩展内存以覆盖区间，返回区间的起点和长度，长度超过上限时按 gas 不足处理
+    fn memory_region(&mut self, offset: U256, len: U256) -> Result<(usize, usize), EvmError> {
+        if len > U256::from(MEMORY_LIMIT) {
+            return Err(EvmError::OutOfGas);
+        }
+        let len = len.as_usize();
+        let offset = self.expand_memory(offset, len)?;
+        Ok((offset, len))
+    }
+
+    /// 读取内存区间
+    fn memory_range(&mut self, offset: U256, len: U256) -> Result<Vec<u8>, EvmError> {
+        let (offset, len) = self.memory_region(offset, len)?;
+        Ok(self.memory[offset..offset + len].to_vec())
+    }
+
+    /// 将 `source` 中的区间复制到内存，每字收取 3 gas
+    fn copy_to_memory(
+        &mut self,
+        dest: U256,
+        source: &[u8],
+        offset: U256,
+        len: U256,
+    ) -> Result<(), EvmError> {
+        let (dest, len) = self.memory_region(dest, len)?;
+        self.charge(3 * words(len))?;
+        self.memory[dest..dest + len].copy_from_slice(&padded(source, offset, len));
+        Ok(())
+    }
+
+    fn check_writable(&self) -> Result<(), EvmError> {
+        if self.message.is_static {
+            return Err(EvmError::WriteProtection(self.pc));
+        }
+        Ok(())
+    }
+
+    fn jump(&mut self, dest: U256) -> Result<(), EvmError> {
+        if dest >= U256::from(self.code.len()) || !self.jumpdests[dest.as_usize()] {
+            return Err(EvmError::InvalidJump(dest));
+        }
+        self.pc = dest.as_usize();
+        Ok(())
+    }
+
+    /// CALL、CALLCODE、DELEGATECALL 和 STATICCALL
+    ///
+    /// 调用深度超限或余额不足时不执行子调用，转发的 gas 全部退还并压入 0。
+    async fn call_contract(
+        &mut self,
+        opcode: u8,
+        storage: &mut (dyn Storage + Send + Sync),
+    ) -> Result<(), EvmError> {
+        let gas = self.pop()?;
+        let target = to_address(self.pop()?);
+        let value = if matches!(opcode, 0xf1 | 0xf2) {
+            self.pop()?
+        } else {
+            U256::zero()
+        };
+        let in_offset = self.pop()?;
+        let in_len = self.pop()?;
+        let out_offset = self.pop()?;
+        let out_len = self.pop()?;
+        if opcode == 0xf1 && !value.is_zero() {
+            self.check_writable()?;
+        }
+        self.charge_account_access(target)?;
+        let data = self.memory_range(in_offset, in_len)?;
+        let (out_offset, out_len) = self.memory_region(out_offset, out_len)?;
+        if !value.is_zero() {
+            self.charge(CALL_VALUE_GAS)?;
+            if opcode == 0xf1 && is_empty(storage.get_account(&target).await.as_ref()) {
+                self.charge(NEW_ACCOUNT_GAS)?;
+            }
+        }
+
+        // 最多转发剩余 gas 的 63/64，转账调用另外附带 2300
+        let available = self.gas_remaining() - self.gas_remaining() / 64;
+        let forwarded = if gas > U256::from(available) {
+            available
+        } else {
+            gas.as_u64()
+        };
+        self.charge(forwarded)?;
+        let gas_limit = if value.is_zero() {
+            forwarded
+        } else {
+            forwarded + CALL_STIPEND
+        };
+        self.sub_return_data.clear();
+        let address = self.message.address;
+        if self.message.depth >= CALL_DEPTH_LIMIT
+            || (!value.is_zero() && storage.get_balance(&address).await < value)
+        {
+            self.gas_used = self.gas_used.saturating_sub(gas_limit);
+            return self.push(U256::zero());
+        }
+
+        let depth = self.message.depth + 1;
+        let message = match opcode {
+            // CALLCODE 在当前账户的上下文中执行目标代码
+            0xf2 => Message {
+                caller: address,
+                address,
+                value,
+                data,
+                is_static: self.message.is_static,
+                depth,
+            },
+            // DELEGATECALL 沿用当前调用的调用者和金额
+            0xf4 => Message {
+                caller: self.message.caller,
+                address,
+                value: self.message.value,
+                data,
+                is_static: self.message.is_static,
+                depth,
+            },
+            _ => Message {
+                caller: address,
+                address: target,
+                value,
+                data,
+                is_static: self.message.is_static || opcode == 0xfa,
+                depth,
+            },
+        };
+        let code = storage.get_code(&target).await;
+        let mut child = Interpreter::with_message(message, code, gas_limit, self.env.clone());
+        child.substate = std::mem::take(&mut self.substate);
+        if opcode != 0xf4 {
+            let to = child.message.address;
+            child.transfer(storage, address, to, value).await;
+        }
+        child.run_nested(storage).await;
+
+        self.sub_return_data = std::mem::take(&mut child.return_data);
+        let len = out_len.min(self.sub_return_data.len());
+        self.memory[out_offset..out_offset + len].copy_from_slice(&self.sub_return_data[..len]);
+        self.absorb(&mut child);
+        self.push(bool_word(child.status.is_success()))
+    }
+
+    /// CREATE 和 CREATE2
+    async fn create_contract(
+        &mut self,
+        opcode: u8,
+        storage: &mut (dyn Storage + Send + Sync),
+    ) -> Result<(), EvmError> {
+        self.check_writable()?;
+        let value = self.pop()?;
+        let offset = self.pop()?;
+        let len = self.pop()?;
+        let salt = if opcode == 0xf5 {
+            Some(self.pop()?)
+        } else {
+            None
+        };
+        let init_code = self.memory_range(offset, len)?;
+        if salt.is_some() {
+            // CREATE2 需要对初始化代码求哈希
+            self.charge(6 * words(init_code.len()))?;
+        }
+
+        let gas_limit = self.gas_remaining() - self.gas_remaining() / 64;
+        self.charge(gas_limit)?;
+        self.sub_return_data.clear();
+        let creator = self.message.address;
+        if self.message.depth >= CALL_DEPTH_LIMIT || storage.get_balance(&creator).await < value {
+            self.gas_used -= gas_limit;
+            return self.push(U256::zero());
+        }
+        let nonce = storage.get_nonce(&creator).await;
+        self.journal_account(storage, creator).await;
+        storage.set_nonce(&creator, nonce + 1).await;
+        let address = Address::from(match salt {
+            Some(salt) => {
+                get_create2_address(H160(creator.0), word(salt).to_vec(), init_code.clone())
+            }
+            None => get_contract_address(H160(creator.0), nonce),
+        });
+
+        let message = Message {
+            caller: creator,
+            address,
+            value,
+            data: Vec::new(),
+            is_static: false,
+            depth: self.message.depth + 1,
+        };
+        let mut child = Interpreter::with_message(message, init_code, gas_limit, self.env.clone());
+        child.substate = std::mem::take(&mut self.substate);
+        let created = child.create(storage).await;
+        // 只有 REVERT 的返回数据对创建者可见
+        if child.status == ExecutionStatus::Reverted {
+            self.sub_return_data = std::mem::take(&mut child.return_data);
+        }
+        self.absorb(&mut child);
+        self.push(if created {
+            address_word(address)
+        } else {
+            U256::zero()
+        })
+    }
+
+    /// SELFDESTRUCT，余额转给受益人，本交易中创建的合约同时清除代码和存储
+    async fn selfdestruct(
+        &mut self,
+        storage: &mut (dyn Storage + Send + Sync),
+    ) -> Result<(), EvmError> {
+        self.check_writable()?;
+        let beneficiary = to_address(self.pop()?);
+        if self.access_address(beneficiary) {
+            self.charge(COLD_ACCOUNT_ACCESS_GAS)?;
+        }
+        let address = self.message.address;
+        let balance = storage.get_balance(&address).await;
+        if !balance.is_zero() && is_empty(storage.get_account(&beneficiary).await.as_ref()) {
+            self.charge(NEW_ACCOUNT_GAS)?;
+        }
+        self.transfer(storage, address, beneficiary, balance).await;
+        if let Some(slots) = self.substate.created.get(&address).cloned() {
+            for key in slots {
+                self.set_storage(storage, address, key, [0u8; 32]).await;
+            }
+            self.journal_account(storage, address).await;
+            storage.set_account(&Account::new(address)).await;
+        }
+        self.status = ExecutionStatus::Stopped;
+        Ok(())
+    }
+
+    async fn execute(
+        &mut self,
+        opcode: u8,
+        storage: &mut (dyn Storage + Send + Sync),
+    ) -> Result<(), EvmError> {
+        let gas = static_gas(opcode).ok_or(EvmError::InvalidOpcode {
+            pc: self.pc,
+            opcode,
+        })?;
+        self.charge(gas)?;
+        let mut next_pc = self.pc + 1;
+
+        match opcode {
+            0x00 => {
+                self.status = ExecutionStatus::Stopped;
+            }
+            0x01..=0x07 | 0x0b | 0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d => {
+                let a = self.pop()?;
+                let b = self.pop()?;
+                let value = match opcode {
+                    0x01 => a.overflowing_add(b).0,
+                    0x02 => a.overflowing_mul(b).0,
+                    0x03 => a.overflowing_sub(b).0,
+                    0x04 => a.checked_div(b).unwrap_or_default(),
+                    0x05 => {
+                        if b.is_zero() {
+                            U256::zero()
+                        } else {
+                            let quotient = abs(a) / abs(b);
+                            if is_negative(a) != is_negative(b) {
+                                negate(quotient)
+                            } else {
+                                quotient
+                            }
+                        }
+                    }
+                    0x06 => a.checked_rem(b).unwrap_or_default(),
+                    0x07 => {
+                        if b.is_zero() {
+                            U256::zero()
+                        } else {
+                            let remainder = abs(a) % abs(b);
+                            if is_negative(a) {
+                                negate(remainder)
+                            } else {
+                                remainder
+                            }
+                        }
+                    }
+                    0x0b => {
+                        if a < U256::from(31) {
+                            let bit = a.as_usize() * 8 + 7;
+                            let mask = (U256::one() << (bit + 1)) - U256::one();
+                            if b.bit(bit) {
+                                b | !mask
+                            } else {
+                                b & mask
+                            }
+                        } else {
+                            b
+                        }
+                    }
+                    0x10 => bool_word(a < b),
+                    0x11 => bool_word(a > b),
+                    0x12 | 0x13 => {
+                        let (a, b) = if opcode == 0x12 { (a, b) } else { (b, a) };
+                        bool_word(match (is_negative(a), is_negative(b)) {
+                            (true, false) => true,
+                            (false, true) => false,
+                            _ => a < b,
+                        })
+                    }
+                    0x14 => bool_word(a == b),
+                    0x16 => a & b,
+                    0x17 => a | b,
+                    0x18 => a ^ b,
+                    0x1a => {
+                        if a < U256::from(32) {
+                            U256::from(b.byte(31 - a.as_usize()))
+                        } else {
+                            U256::zero()
+                        }
+                    }
+                    0x1b => {
+                        if a < U256::from(256) {
+                            b << a.as_usize()
+                        } else {
+                            U256::zero()
+                        }
+                    }
+                    0x1c => {
+                        if a < U256::from(256) {
+                            b >> a.as_usize()
+                        } else {
+                            U256::zero()
+                        }
+                    }
+                    _ => {
+                        let negative = is_negative(b);
+                        if a >= U256::from(256) {
+                            if negative {
+                                U256::MAX
+                            } else {
+                                U256::zero()
+                            }
+                        } else if negative {
+                            !((!b) >> a.as_usize())
+                        } else {
+                            b >> a.as_usize()
+                        }
+                    }
+                };
+                self.push(value)?;
+            }
+            0x08 | 0x09 => {
+                let a = U512::from(self.pop()?);
+                let b = U512::from(self.pop()?);
+                let n = U512::from(self.pop()?);
+                let value = if n.is_zero() {
+                    U256::zero()
+                } else if opcode == 0x08 {
+                    truncate((a + b) % n)
+                } else {
+                    truncate((a * b) % n)
+                };
+                self.push(value)?;
+            }
+            0x0a => {
+                let base = self.pop()?;
+                let exponent = self.pop()?;
+                let bytes = (exponent.bits() as u64 + 7) / 8;
+                self.charge(50 * bytes)?;
+                self.push(base.overflowing_pow(exponent).0)?;
+            }
+            0x15 => {
+                let a = self.pop()?;
+                self.push(bool_word(a.is_zero()))?;
+            }
+            0x19 => {
+                let a = self.pop()?;
+                self.push(!a)?;
+            }
+            0x20 => {
+                let offset = self.pop()?;
+                let len = self.pop()?;
+                let data = self.memory_range(offset, len)?;
+                self.charge(6 * words(data.len()))?;
+                self.push(U256::from_big_endian(&keccak256(&data)))?;
+            }
+            0x30 => self.push(address_word(self.message.address))?,
+            0x31 => {
+                let address = to_address(self.pop()?);
+                self.charge_account_access(address)?;
+                let balance = storage.get_balance(&address).await;
+                self.push(balance)?;
+            }
+            0x32 => self.push(address_word(self.env.origin))?,
+            0x33 => self.push(address_word(self.message.caller))?,
+            0x34 => self.push(self.message.value)?,
+            0x35 => {
+                let offset = self.pop()?;
+                let value = U256::from_big_endian(&padded(&self.message.data, offset, 32));
+                self.push(value)?;
+            }
+            0x36 => self.push(U256::from(self.message.data.len()))?,
+            0x37 | 0x39 | 0x3e => {
+                let dest = self.pop()?;
+                let offset = self.pop()?;
+                let len = self.pop()?;
+                let source = match opcode {
+                    0x37 => self.message.data.clone(),
+                    0x39 => self.code.clone(),
+                    _ => {
+                        let available = U256::from(self.sub_return_data.len());
+                        if !offset.checked_add(len).is_some_and(|end| end <= available) {
+                            return Err(EvmError::ReturnDataOutOfBounds);
+                        }
+                        self.sub_return_data.clone()
+                    }
+                };
+                self.copy_to_memory(dest, &source, offset, len)?;
+            }
+            0x38 => self.push(U256::from(self.code.len()))?,
+            0x3a => self.push(self.env.gas_price)?,
+            0x3b => {
+                let address = to_address(self.pop()?);
+                self.charge_account_access(address)?;
+                let code = storage.get_code(&address).await;
+                self.push(U256::from(code.len()))?;
+            }
+            0x3c => {
+                let address = to_address(self.pop()?);
+                let dest = self.pop()?;
+                let offset = self.pop()?;
+                let len = self.pop()?;
+                self.charge_account_access(address)?;
+                let code = storage.get_code(&address).await;
+                self.copy_to_memory(dest, &code, offset, len)?;
+            }
+            0x3d => self.push(U256::from(self.sub_return_data.len()))?,
+            0x3f => {
+                let address = to_address(self.pop()?);
+                self.charge_account_access(address)?;
+                let account = storage.get_account(&address).await;
+                let hash = if is_empty(account.as_ref()) {
+                    H256::zero()
+                } else {
+                    account
+                        .map(|account| account.code_hash)
+                        .filter(|hash| !hash.is_zero())
+                        .unwrap_or_else(|| H256(keccak256(b"")))
+                };
+                self.push(U256::from_big_endian(hash.as_bytes()))?;
+            }
+            0x40 => {
+                let number = self.pop()?;
+                let current = U256::from(self.env.block.block_number);
+                // 只能查询最近 256 个区块，不含当前区块
+                let hash = if number < current && number + 256 >= current {
+                    self.env
+                        .block_hashes
+                        .get(&number.as_u64())
+                        .copied()
+                        .unwrap_or_default()
+                } else {
+                    H256::zero()
+                };
+                self.push(U256::from_big_endian(hash.as_bytes()))?;
+            }
+            0x41 => self.push(U256::from_big_endian(self.env.block.miner.as_bytes()))?,
+            0x42 => self.push(U256::from(self.env.block.timestamp))?,
+            0x43 => self.push(U256::from(self.env.block.block_number))?,
+            0x44 => self.push(self.env.block.difficulty)?,
+            0x45 => self.push(U256::from(self.env.block.gas_limit))?,
+            0x46 => self.push(U256::from(self.env.chain_id))?,
+            0x47 => {
+                let balance = storage.get_balance(&self.message.address).await;
+                self.push(balance)?;
+            }
+            0x48 => self.push(self.env.base_fee)?,
+            0x50 => {
+                self.pop()?;
+            }
+            0x51 => {
+                let offset = self.pop()?;
+                let offset = self.expand_memory(offset, 32)?;
+                let value = U256::from_big_endian(&self.memory[offset..offset + 32]);
+                self.push(value)?;
+            }
+            0x52 => {
+                let offset = self.pop()?;
+                let value = self.pop()?;
+                let offset = self.expand_memory(offset, 32)?;
+                self.memory[offset..offset + 32].copy_from_slice(&word(value));
+            }
+            0x53 => {
+                let offset = self.pop()?;
+                let value = self.pop()?;
+                let offset = self.expand_memory(offset, 1)?;
+                self.memory[offset] = value.byte(0);
+            }
+            0x54 => {
+                let key = word(self.pop()?);
+                let address = self.message.address;
+                if self.access_slot(address, key) {
+                    self.charge(COLD_SLOAD_GAS - WARM_ACCESS_GAS)?;
+                }
+                let value = storage.get_storage_value(&address, key).await;
+                self.push(U256::from_big_endian(&value))?;
+            }
+            0x55 => {
+                self.check_writable()?;
+                let key = self.pop()?;
+                let value = self.pop()?;
+                self.sstore(storage, word(key), word(value)).await?;
+            }
+            0x56 => {
+                let dest = self.pop()?;
+                self.jump(dest)?;
+                next_pc = self.pc;
+            }
+            0x57 => {
+                let dest = self.pop()?;
+                let condition = self.pop()?;
+                if !condition.is_zero() {
+                    self.jump(dest)?;
+                    next_pc = self.pc;
+                }
+            }
+            0x58 => self.push(U256::from(self.pc))?,
+            0x59 => self.push(U256::from(self.memory.len()))?,
+            0x5a => self.push(U256::from(self.gas_remaining()))?,
+            0x5b => {}
+            0x5f => self.push(U256::zero())?,
+            0x60..=0x7f => {
+                let size = (opcode - 0x5f) as usize;
+                let start = (self.pc + 1).min(self.code.len());
+                let end = (start + size).min(self.code.len());
+                // 代码末尾不足的字节按 0 补齐
+                let mut bytes = [0u8; 32];
+                bytes[32 - size..32 - size + (end - start)].copy_from_slice(&self.code[start..end]);
+                self.push(U256::from_big_endian(&bytes))?;
+                next_pc = self.pc + 1 + size;
+            }
+            0x80..=0x8f => {
+                let depth = (opcode - 0x7f) as usize;
+                if self.stack.len() < depth {
+                    return Err(EvmError::StackUnderflow(self.pc));
+                }
+                let value = self.stack[self.stack.len() - depth];
+                self.push(value)?;
+            }
+            0x90..=0x9f => {
+                let depth = (opcode - 0x8f) as usize;
+                if self.stack.len() <= depth {
+                    return Err(EvmError::StackUnderflow(self.pc));
+                }
+                let top = self.stack.len() - 1;
+                self.stack.swap(top, top - depth);
+            }
+            0xa0..=0xa4 => {
+                self.check_writable()?;
+                let offset = self.pop()?;
+                let len = self.pop()?;
+                let count = (opcode - 0xa0) as usize;
+                let mut topics = Vec::with_capacity(count);
+                for _ in 0..count {
+                    topics.push(H256(word(self.pop()?)));
+                }
+                let data = self.memory_range(offset, len)?;
+                self.charge(375 * count as u64 + 8 * data.len() as u64)?;
+                self.logs.push((self.message.address, topics, data));
+            }
+            0xf0 | 0xf5 => self.create_contract(opcode, storage).await?,
+            0xf1 | 0xf2 | 0xf4 | 0xfa => self.call_contract(opcode, storage).await?,
+            0xff => self.selfdestruct(storage).await?,
+            0xf3 | 0xfd => {
+                let offset = self.pop()?;
+                let len = self.pop()?;
+                self.return_data = self.memory_range(offset, len)?;
+                self.status = if opcode == 0xf3 {
+                    ExecutionStatus::Returned
+                } else {
+                    ExecutionStatus::Reverted
+                };
+            }
+            _ => {
+                return Err(EvmError::InvalidOpcode {
+                    pc: self.pc,
+                    opcode,
+                })
+            }
+        }
+
+        self.pc = next_pc;
+        if self.status == ExecutionStatus::Running && self.pc >= self.code.len() {
+            self.status = ExecutionStatus::Stopped;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    async fn run(code: Vec<u8>) -> (Interpreter, MemoryStorage) {
+        let mut storage = MemoryStorage::new();
+        let mut interpreter = Interpreter::new(Address([1u8; 20]), code, 1_000_000);
+        let _ = interpreter.run(&mut storage).await;
+        (interpreter, storage)
+    }
+
+    #[tokio::test]
+    async fn test_arithmetic_and_return() {
+        // (2 + 3) * 4，写入内存后返回
+        let code = vec![
+            0x60, 0x04, 0x60, 0x03, 0x60, 0x02, 0x01, 0x02, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60,
+            0x00, 0xf3,
+        ];
+        let (interpreter, _) = run(code).await;
+        assert_eq!(interpreter.status(), &ExecutionStatus::Returned);
+        assert_eq!(
+            U256::from_big_endian(interpreter.return_data()),
+            U256::from(20)
+        );
+        assert_eq!(interpreter.memory().len(), 32);
+    }
+
+    #[tokio::test]
+    async fn test_signed_arithmetic() {
+        // -6 / 4 = -1, -6 % 4 = -2
+        let minus_six = negate(U256::from(6));
+        let mut code = vec![0x60, 0x04, 0x7f];
+        code.extend_from_slice(&word(minus_six));
+        code.extend_from_slice(&[0x05, 0x60, 0x04, 0x7f]);
+        code.extend_from_slice(&word(minus_six));
+        code.push(0x07);
+        let (interpreter, _) = run(code).await;
+        assert_eq!(
+            interpreter.stack(),
+            &[negate(U256::one()), negate(U256::from(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_storage_and_jumps() {
+        // 循环 3 次，每次将槽 0 加 1
+        let code = vec![
+            0x60, 0x03, // counter
+            0x5b, // 2: loop
+            0x60, 0x00, 0x54, 0x60, 0x01, 0x01, 0x60, 0x00, 0x55, // slot0 += 1
+            0x60, 0x01, 0x90, 0x03, // counter - 1
+            0x80, 0x60, 0x02, 0x57, // jumpi loop
+            0x00,
+        ];
+        let (interpreter, storage) = run(code).await;
+        assert_eq!(interpreter.status(), &ExecutionStatus::Stopped);
+        let slot = storage
+            .get_storage_value(&Address([1u8; 20]), [0u8; 32])
+            .await;
+        assert_eq!(U256::from_big_endian(&slot), U256::from(3));
+    }
+
+    #[tokio::test]
+    async fn test_failures() {
+        let (interpreter, _) = run(vec![0x60, 0x03, 0x56, 0x00]).await;
+        assert!(matches!(interpreter.status(), ExecutionStatus::Failed(_)));
+        assert_eq!(interpreter.gas_remaining(), 0);
+
+        // PUSH 数据中的 0x5b 不是合法跳转目标
+        let (interpreter, _) = run(vec![0x60, 0x04, 0x56, 0x61, 0x5b, 0x00]).await;
+        assert!(!interpreter.status().is_success());
+
+        let (interpreter, _) = run(vec![0x01]).await;
+        assert_eq!(interpreter.halted_at(), Some(0));
+        assert_eq!(
+            interpreter.status(),
+            &ExecutionStatus::Failed(EvmError::StackUnderflow(0).to_string())
+        );
+
+        let mut storage = MemoryStorage::new();
+        let mut interpreter = Interpreter::new(Address::zero(), vec![0x60, 0x01, 0x00], 2);
+        assert_eq!(
+            interpreter.step(&mut storage).await.unwrap_err(),
+            EvmError::OutOfGas
+        );
+        assert_eq!(
+            interpreter.step(&mut storage).await.unwrap_err(),
+            EvmError::Halted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hash_and_calldata() {
+        let mut storage = MemoryStorage::new();
+        let message = Message {
+            data: vec![0xaa, 0xbb, 0xcc],
+            ..Message::default()
+        };
+        // 复制调用数据到内存并求哈希，再读取调用数据的第一个字
+        let code = vec![
+            0x36, 0x60, 0x00, 0x60, 0x00, 0x37, 0x36, 0x60, 0x00, 0x20, 0x60, 0x00, 0x35, 0x00,
+        ];
+        let mut interpreter =
+            Interpreter::with_message(message, code, 100_000, Arc::new(Environment::default()));
+        interpreter.run(&mut storage).await.unwrap();
+        let mut padded = [0u8; 32];
+        padded[..3].copy_from_slice(&[0xaa, 0xbb, 0xcc]);
+        assert_eq!(
+            interpreter.stack(),
+            &[
+                U256::from_big_endian(&keccak256([0xaa, 0xbb, 0xcc])),
+                U256::from_big_endian(&padded)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_logs() {
+        // 将 42 写入内存，以主题 7 记录日志
+        let code = vec![
+            0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x07, 0x60, 0x20, 0x60, 0x00, 0xa1, 0x00,
+        ];
+        let (interpreter, _) = run(code.clone()).await;
+        assert_eq!(
+            interpreter.logs(),
+            &[(
+                Address([1u8; 20]),
+                vec![H256::from_low_u64_be(7)],
+                word(U256::from(42)).to_vec()
+            )]
+        );
+
+        let mut storage = MemoryStorage::new();
+        let message = Message {
+            is_static: true,
+            ..Message::default()
+        };
+        let mut interpreter =
+            Interpreter::with_message(message, code, 100_000, Arc::new(Environment::default()));
+        assert_eq!(
+            interpreter.run(&mut storage).await.unwrap_err(),
+            EvmError::WriteProtection(11)
+        );
+        assert!(interpreter.logs().is_empty());
+    }
+
+    /// 以全部 gas 调用 `target`，返回数据写入内存 0 处，最后压入内存中的第一个字
+    fn call_code(target: Address) -> Vec<u8> {
+        let mut code = vec![
+            0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+        ];
+        code.extend_from_slice(&target.0);
+        code.extend_from_slice(&[0x5a, 0xf1, 0x60, 0x00, 0x51, 0x00]);
+        code
+    }
+
+    #[tokio::test]
+    async fn test_call_and_revert() {
+        let mut storage = MemoryStorage::new();
+        let caller = Address([1u8; 20]);
+        let callee = Address([2u8; 20]);
+        // 槽 0 写入 1，返回 CALLER
+        let returning = vec![
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x33, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ];
+        storage.set_code(&callee, returning).await;
+        let mut interpreter = Interpreter::new(caller, call_code(callee), 1_000_000);
+        interpreter.run(&mut storage).await.unwrap();
+        assert_eq!(interpreter.stack(), &[U256::one(), address_word(caller)]);
+        let slot = storage.get_storage_value(&callee, [0u8; 32]).await;
+        assert_eq!(U256::from_big_endian(&slot), U256::one());
+
+        // 写入槽 0 后 REVERT，写入被撤销
+        let reverting = vec![0x60, 0x02, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0xfd];
+        storage.set_code(&callee, reverting).await;
+        let mut interpreter = Interpreter::new(caller, call_code(callee), 1_000_000);
+        interpreter.run(&mut storage).await.unwrap();
+        assert_eq!(interpreter.stack()[0], U256::zero());
+        let slot = storage.get_storage_value(&callee, [0u8; 32]).await;
+        assert_eq!(U256::from_big_endian(&slot), U256::one());
+        // 未使用的 gas 退还给调用方
+        assert!(interpreter.gas_used() < 50_000);
+    }
+
+    #[tokio::test]
+    async fn test_create() {
+        let mut storage = MemoryStorage::new();
+        let creator = Address([1u8; 20]);
+        // 初始化代码返回 10 字节的运行时代码
+        let runtime = vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        let mut init = vec![0x69];
+        init.extend_from_slice(&runtime);
+        init.extend_from_slice(&[0x60, 0x00, 0x52, 0x60, 0x0a, 0x60, 0x16, 0xf3]);
+        let mut code = vec![0x72];
+        code.extend_from_slice(&init);
+        code.extend_from_slice(&[
+            0x60, 0x00, 0x52, 0x60, 0x13, 0x60, 0x0d, 0x60, 0x00, 0xf0, 0x00,
+        ]);
+
+        let mut interpreter = Interpreter::new(creator, code, 1_000_000);
+        interpreter.run(&mut storage).await.unwrap();
+        let address = Address::from(get_contract_address(H160(creator.0), 0u64));
+        assert_eq!(interpreter.stack(), &[address_word(address)]);
+        assert_eq!(storage.get_code(&address).await, runtime);
+        assert_eq!(storage.get_nonce(&creator).await, 1);
+        assert_eq!(storage.get_nonce(&address).await, 1);
+    }
+
+    /// 执行到结束，返回指定操作码每次执行消耗的 gas
+    async fn step_costs(
+        interpreter: &mut Interpreter,
+        storage: &mut MemoryStorage,
+        opcode: u8,
+    ) -> Vec<u64> {
+        let mut costs = Vec::new();
+        while !interpreter.is_halted() {
+            let step = interpreter.step(storage).await.unwrap();
+            if step.opcode == opcode {
+                costs.push(step.gas_cost);
+            }
+        }
+        costs
+    }
+
+    #[tokio::test]
+    async fn test_access_gas() {
+        let mut storage = MemoryStorage::new();
+        let other = Address([2u8; 20]);
+        // 两次 BALANCE 同一账户，再查询自身和预编译合约
+        let mut code = Vec::new();
+        for _ in 0..2 {
+            code.push(0x73);
+            code.extend_from_slice(&other.0);
+            code.push(0x31);
+        }
+        code.extend_from_slice(&[0x30, 0x31, 0x60, 0x01, 0x31, 0x00]);
+        let mut interpreter = Interpreter::new(Address([1u8; 20]), code, 100_000);
+        let costs = step_costs(&mut interpreter, &mut storage, 0x31).await;
+        assert_eq!(costs, vec![2_600, 100, 100, 100]);
+
+        // 两次 SLOAD 同一存储槽
+        let code = vec![0x60, 0x00, 0x54, 0x60, 0x00, 0x54, 0x00];
+        let mut interpreter = Interpreter::new(Address([1u8; 20]), code, 100_000);
+        let costs = step_costs(&mut interpreter, &mut storage, 0x54).await;
+        assert_eq!(costs, vec![2_100, 100]);
+    }
+
+    #[tokio::test]
+    async fn test_sstore_refund() {
+        let mut storage = MemoryStorage::new();
+        let address = Address([1u8; 20]);
+        storage
+            .set_storage_value(&address, [0u8; 32], word(U256::one()))
+            .await;
+        // 槽 0 从 1 清零再改回 1，槽 1 从 0 写入 1 再清零
+        let code = vec![
+            0x60, 0x00, 0x60, 0x00, 0x55, 0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x01, 0x60, 0x01,
+            0x55, 0x60, 0x00, 0x60, 0x01, 0x55, 0x00,
+        ];
+        let mut interpreter = Interpreter::new(address, code.clone(), 100_000);
+        let costs = step_costs(&mut interpreter, &mut storage, 0x55).await;
+        assert_eq!(costs, vec![2_100 + 2_900, 100, 2_100 + 20_000, 100]);
+        // 清零退款在改回原值时收回，改回原值分别退还 2800 和 19900
+        assert_eq!(interpreter.gas_refund(), 2_800 + 19_900);
+
+        // 调用帧 REVERT 时退款计数一并撤销
+        let mut reverting = code;
+        reverting.pop();
+        reverting.extend_from_slice(&[0x60, 0x00, 0x60, 0x00, 0xfd]);
+        let mut interpreter = Interpreter::new(address, reverting, 100_000);
+        interpreter.run(&mut storage).await.unwrap();
+        assert_eq!(interpreter.status(), &ExecutionStatus::Reverted);
+        assert_eq!(interpreter.gas_refund(), 0);
+
+        // 剩余 gas 不超过 2300 时不能写存储
+        let mut interpreter = Interpreter::new(address, vec![0x60, 0x01, 0x60, 0x00, 0x55], 2_306);
+        assert!(interpreter.run(&mut storage).await.is_err());
+        assert_eq!(
+            interpreter.status(),
+            &ExecutionStatus::Failed(EvmError::OutOfGas.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_step() {
+        let mut storage = MemoryStorage::new();
+        let mut interpreter = Interpreter::new(Address::zero(), vec![0x60, 0x2a, 0x80, 0x00], 100);
+        let step = interpreter.step(&mut storage).await.unwrap();
+        assert_eq!((step.pc, step.name.as_str()), (0, "PUSH1"));
+        assert_eq!(interpreter.pc(), 2);
+        let step = interpreter.step(&mut storage).await.unwrap();
+        assert_eq!((step.name.as_str(), step.stack_depth), ("DUP1", 2));
+        assert_eq!(step.gas_remaining, 94);
+        interpreter.step(&mut storage).await.unwrap();
+        assert!(interpreter.is_halted());
+        assert_eq!(interpreter.halted_at(), Some(3));
+    }
+}
@@ -1,5 +1,15 @@
 use ethers::types::{H160, U256};
 
+pub mod debugger;
+pub mod executor;
+pub mod interpreter;
+pub mod selectors;
+pub mod source_map;
+pub use fair_vm_core::vm::RevertReason;
+pub use interpreter::{
+    Environment, EvmError, ExecutionStatus, Interpreter, LogEntry, Message, Step,
+};
+
 /// EVM 上下文
 #[derive(Debug, Clone, Default)]
 pub struct EvmContext {
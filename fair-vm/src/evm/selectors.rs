@@ -0,0 +1,424 @@
+//! 函数选择器数据库
+//!
+//! 内置常见标准合约的函数签名，可选地在本地未命中时向 4byte 签名目录查询并缓存结果。
+//! 调试会话、交易解码和浏览器接口据此为未验证的合约显示可读的函数名。
+//! 不同签名可能产生相同的选择器，因此查询结果是候选签名列表。
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 4byte 签名目录的查询地址
+pub const FOUR_BYTE_URL: &str = "https://www.4byte.directory/api/v1/signatures/";
+
+/// 缓存的未命中选择器上限，超过后清空重新记录
+pub const MAX_CACHED_MISSES: usize = 4096;
+
+/// 内置的函数签名
+pub const BUNDLED_SIGNATURES: &[&str] = &[
+    // ERC-20
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "allowance(address,address)",
+    "balanceOf(address)",
+    "totalSupply()",
+    "name()",
+    "symbol()",
+    "decimals()",
+    "increaseAllowance(address,uint256)",
+    "decreaseAllowance(address,uint256)",
+    "mint(address,uint256)",
+    "burn(uint256)",
+    "burnFrom(address,uint256)",
+    // EIP-2612
+    "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+    "nonces(address)",
+    "DOMAIN_SEPARATOR()",
+    // ERC-721
+    "ownerOf(uint256)",
+    "safeTransferFrom(address,address,uint256)",
+    "safeTransferFrom(address,address,uint256,bytes)",
+    "setApprovalForAll(address,bool)",
+    "isApprovedForAll(address,address)",
+    "getApproved(uint256)",
+    "tokenURI(uint256)",
+    "supportsInterface(bytes4)",
+    // ERC-1155
+    "safeTransferFrom(address,address,uint256,uint256,bytes)",
+    "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+    "balanceOfBatch(address[],uint256[])",
+    "uri(uint256)",
+    // ERC-4626
+    "asset()",
+    "totalAssets()",
+    "deposit(uint256,address)",
+    "mint(uint256,address)",
+    "withdraw(uint256,address,address)",
+    "redeem(uint256,address,address)",
+    "convertToShares(uint256)",
+    "convertToAssets(uint256)",
+    "previewDeposit(uint256)",
+    "previewMint(uint256)",
+    "previewWithdraw(uint256)",
+    "previewRedeem(uint256)",
+    "maxWithdraw(address)",
+    // WETH
+    "deposit()",
+    "withdraw(uint256)",
+    // Ownable 与代理
+    "owner()",
+    "transferOwnership(address)",
+    "renounceOwnership()",
+    "upgradeTo(address)",
+    "upgradeToAndCall(address,bytes)",
+    "implementation()",
+    // Multicall
+    "multicall(bytes[])",
+    "aggregate((address,bytes)[])",
+    "tryAggregate(bool,(address,bytes)[])",
+    // Permit2
+    "permit(address,((address,uint160,uint48,uint48),address,uint256),bytes)",
+    "permitTransferFrom(((address,uint256),uint256,uint256),(address,uint256),address,bytes)",
+    "nonceBitmap(address,uint256)",
+    // Uniswap V2 路由
+    "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+    "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+    "addLiquidity(address,address,uint256,uint256,uint256,uint256,address,uint256)",
+    "removeLiquidity(address,address,uint256,uint256,uint256,address,uint256)",
+    "getReserves()",
+];
+
+/// 函数选择器
+pub type Selector = [u8; 4];
+
+/// 选择器数据库错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum SelectorError {
+    #[error("无效的函数签名: {0}")]
+    InvalidSignature(String),
+
+    #[error("查询签名目录失败: {0}")]
+    Fetch(String),
+}
+
+/// 计算函数签名的选择器
+pub fn selector(signature: &str) -> Selector {
+    let hash = Keccak256::digest(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// 调用数据的选择器，不足 4 字节时为空
+pub fn selector_of(data: &[u8]) -> Option<Selector> {
+    data.get(..4)
+        .map(|bytes| [bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// 函数签名中的函数名
+pub fn function_name(signature: &str) -> &str {
+    signature.split('(').next().unwrap_or(signature)
+}
+
+/// 签名须为 `name(types)` 形式，不含空白
+fn is_valid_signature(signature: &str) -> bool {
+    let name = function_name(signature);
+    !name.is_empty()
+        && name.len() < signature.len()
+        && signature.ends_with(')')
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !signature.chars().any(char::is_whitespace)
+}
+
+/// 解码后的选择器
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedSelector {
+    /// 十六进制选择器
+    pub selector: String,
+    /// 第一个候选签名的函数名，未知时为空
+    pub name: Option<String>,
+    /// 候选签名
+    pub signatures: Vec<String>,
+}
+
+impl DecodedSelector {
+    fn new(selector: Selector, signatures: Vec<String>) -> Self {
+        Self {
+            selector: format!("0x{}", hex::encode(selector)),
+            name: signatures
+                .first()
+                .map(|signature| function_name(signature).to_string()),
+            signatures,
+        }
+    }
+}
+
+/// 远程签名数据源
+#[async_trait]
+pub trait SelectorSource: std::fmt::Debug + Send + Sync {
+    /// 查询选择器对应的签名
+    async fn signatures(&self, selector: Selector) -> Result<Vec<String>, SelectorError>;
+}
+
+#[derive(Deserialize)]
+struct FourByteResponse {
+    results: Vec<FourByteSignature>,
+}
+
+#[derive(Deserialize)]
+struct FourByteSignature {
+    text_signature: String,
+}
+
+/// 4byte 签名目录
+#[derive(Debug, Clone)]
+pub struct FourByteDirectory {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl FourByteDirectory {
+    /// 创建签名目录数据源
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+}
+
+impl Default for FourByteDirectory {
+    fn default() -> Self {
+        Self::new(FOUR_BYTE_URL)
+    }
+}
+
+#[async_trait]
+impl SelectorSource for FourByteDirectory {
+    async fn signatures(&self, selector: Selector) -> Result<Vec<String>, SelectorError> {
+        let response: FourByteResponse = self
+            .client
+            .get(&self.url)
+            .query(&[("hex_signature", format!("0x{}", hex::encode(selector)))])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| SelectorError::Fetch(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| SelectorError::Fetch(e.to_string()))?;
+        Ok(response
+            .results
+            .into_iter()
+            .map(|result| result.text_signature)
+            .collect())
+    }
+}
+
+/// 选择器数据库
+#[derive(Debug)]
+pub struct SelectorDatabase {
+    signatures: HashMap<Selector, Vec<String>>,
+    source: Option<Arc<dyn SelectorSource>>,
+    /// 远程数据源也未收录的选择器，避免重复查询
+    misses: HashSet<Selector>,
+}
+
+impl SelectorDatabase {
+    /// 创建空数据库
+    pub fn new() -> Self {
+        Self {
+            signatures: HashMap::new(),
+            source: None,
+            misses: HashSet::new(),
+        }
+    }
+
+    /// 创建包含内置签名的数据库
+    pub fn bundled() -> Self {
+        let mut database = Self::new();
+        for signature in BUNDLED_SIGNATURES {
+            database.register(signature).expect("内置签名有效");
+        }
+        database
+    }
+
+    /// 本地未命中时向远程数据源查询
+    pub fn with_source(mut self, source: Arc<dyn SelectorSource>) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// 登记函数签名，返回其选择器
+    pub fn register(&mut self, signature: &str) -> Result<Selector, SelectorError> {
+        if !is_valid_signature(signature) {
+            return Err(SelectorError::InvalidSignature(signature.to_string()));
+        }
+        let selector = selector(signature);
+        let signatures = self.signatures.entry(selector).or_default();
+        if !signatures.iter().any(|known| known == signature) {
+            signatures.push(signature.to_string());
+        }
+        self.misses.remove(&selector);
+        Ok(selector)
+    }
+
+    /// 本地已知的候选签名
+    pub fn lookup(&self, selector: &Selector) -> &[String] {
+        self.signatures
+            .get(selector)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// 按本地数据解码调用数据的选择器
+    pub fn decode(&self, data: &[u8]) -> Option<DecodedSelector> {
+        let selector = selector_of(data)?;
+        Some(DecodedSelector::new(
+            selector,
+            self.lookup(&selector).to_vec(),
+        ))
+    }
+
+    /// 已登记的选择器数量
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// 是否没有登记任何选择器
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+}
+
+impl Default for SelectorDatabase {
+    fn default() -> Self {
+        Self::bundled()
+    }
+}
+
+/// 查询候选签名，本地未命中时向远程数据源查询并缓存，查询期间不持有锁
+pub async fn resolve(database: &RwLock<SelectorDatabase>, selector: Selector) -> Vec<String> {
+    let source = {
+        let database = database.read().await;
+        let known = database.lookup(&selector);
+        if !known.is_empty() || database.misses.contains(&selector) {
+            return known.to_vec();
+        }
+        match &database.source {
+            Some(source) => source.clone(),
+            None => return Vec::new(),
+        }
+    };
+
+    let fetched = match source.signatures(selector).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            log::debug!("查询选择器 0x{} 失败: {}", hex::encode(selector), e);
+            return Vec::new();
+        }
+    };
+    let mut database = database.write().await;
+    for signature in &fetched {
+        // 远程结果须与选择器一致
+        if self::selector(signature) == selector {
+            let _ = database.register(signature);
+        }
+    }
+    let known = database.lookup(&selector).to_vec();
+    if known.is_empty() {
+        if database.misses.len() >= MAX_CACHED_MISSES {
+            database.misses.clear();
+        }
+        database.misses.insert(selector);
+    }
+    known
+}
+
+/// 解码调用数据的选择器，必要时查询远程数据源
+pub async fn decode(database: &RwLock<SelectorDatabase>, data: &[u8]) -> Option<DecodedSelector> {
+    let selector = selector_of(data)?;
+    Some(DecodedSelector::new(
+        selector,
+        resolve(database, selector).await,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Default)]
+    struct MockSource {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SelectorSource for MockSource {
+        async fn signatures(&self, _selector: Selector) -> Result<Vec<String>, SelectorError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![
+                "customAction(uint256)".to_string(),
+                "mismatched()".to_string(),
+            ])
+        }
+    }
+
+    #[test]
+    fn test_bundled_signatures() {
+        let database = SelectorDatabase::default();
+        assert_eq!(
+            selector("transfer(address,uint256)"),
+            [0xa9, 0x05, 0x9c, 0xbb]
+        );
+
+        let decoded = database.decode(&[0xa9, 0x05, 0x9c, 0xbb, 0x00]).unwrap();
+        assert_eq!(decoded.selector, "0xa9059cbb");
+        assert_eq!(decoded.name.as_deref(), Some("transfer"));
+        assert_eq!(decoded.signatures, vec!["transfer(address,uint256)"]);
+
+        assert!(database.decode(&[0xa9, 0x05]).is_none());
+        assert!(database.decode(&[0, 0, 0, 0]).unwrap().name.is_none());
+    }
+
+    #[test]
+    fn test_register() {
+        let mut database = SelectorDatabase::new();
+        assert!(database.register("transfer (address)").is_err());
+        assert!(database.register("noParens").is_err());
+        assert!(database.register("1bad()").is_err());
+
+        let selector = database.register("foo(uint256)").unwrap();
+        database.register("foo(uint256)").unwrap();
+        assert_eq!(database.lookup(&selector).len(), 1);
+        assert_eq!(database.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_from_source() {
+        let source = Arc::new(MockSource::default());
+        let database = RwLock::new(SelectorDatabase::new().with_source(source.clone()));
+
+        let custom = selector("customAction(uint256)");
+        assert_eq!(
+            resolve(&database, custom).await,
+            vec!["customAction(uint256)"]
+        );
+        assert_eq!(resolve(&database, custom).await.len(), 1);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+
+        // 远程未收录的选择器只查询一次
+        assert!(resolve(&database, [1, 2, 3, 4]).await.is_empty());
+        assert!(resolve(&database, [1, 2, 3, 4]).await.is_empty());
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+    }
+}
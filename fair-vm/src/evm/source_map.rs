@@ -0,0 +1,290 @@
+//! Solidity 源码映射
+//!
+//! 合约通过验证登记源码和 solc 输出的 `deployedBytecode.sourceMap` 后，
+//! 执行出错的程序计数器可以映射回源码文件中的行列位置。
+
+use crate::account::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 源码映射错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum SourceMapError {
+    #[error("第 {index} 项源码映射无效: {reason}")]
+    InvalidEntry { index: usize, reason: String },
+
+    #[error("源码映射有 {entries} 项，超过字节码的 {instructions} 条指令")]
+    TooManyEntries { entries: usize, instructions: usize },
+
+    #[error("源码映射引用了不存在的源文件 {0}")]
+    UnknownSource(usize),
+
+    #[error("合约没有代码")]
+    NoCode,
+}
+
+/// 跳转类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JumpType {
+    /// 进入函数
+    In,
+    /// 从函数返回
+    Out,
+    /// 普通跳转
+    Regular,
+}
+
+/// 一条指令对应的源码区间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    /// 源码字节偏移
+    pub offset: usize,
+    /// 源码字节长度
+    pub length: usize,
+    /// 源文件索引，编译器生成的代码没有对应源文件
+    pub file: Option<usize>,
+    pub jump: JumpType,
+}
+
+/// 解析 solc 压缩格式的源码映射 `s:l:f:j;...`，省略的字段沿用上一项
+pub fn parse_source_map(source_map: &str) -> Result<Vec<SourceMapEntry>, SourceMapError> {
+    let mut entries = Vec::new();
+    if source_map.is_empty() {
+        return Ok(entries);
+    }
+    let mut current = SourceMapEntry {
+        offset: 0,
+        length: 0,
+        file: None,
+        jump: JumpType::Regular,
+    };
+    for (index, item) in source_map.split(';').enumerate() {
+        let invalid = |reason: &str| SourceMapError::InvalidEntry {
+            index,
+            reason: reason.to_string(),
+        };
+        for (field, value) in item.split(':').enumerate() {
+            if value.is_empty() {
+                continue;
+            }
+            match field {
+                0 => current.offset = value.parse().map_err(|_| invalid("偏移不是整数"))?,
+                1 => current.length = value.parse().map_err(|_| invalid("长度不是整数"))?,
+                2 => {
+                    let file: i64 = value.parse().map_err(|_| invalid("文件索引不是整数"))?;
+                    current.file = usize::try_from(file).ok();
+                }
+                3 => {
+                    current.jump = match value {
+                        "i" => JumpType::In,
+                        "o" => JumpType::Out,
+                        "-" => JumpType::Regular,
+                        _ => return Err(invalid("未知的跳转类型")),
+                    }
+                }
+                // 修饰器深度不影响定位
+                4 => {}
+                _ => return Err(invalid("字段过多")),
+            }
+        }
+        entries.push(current);
+    }
+    Ok(entries)
+}
+
+/// 按程序计数器索引指令序号，PUSH 数据所在位置为空
+pub fn instruction_indices(code: &[u8]) -> Vec<Option<usize>> {
+    let mut indices = vec![None; code.len()];
+    let mut pc = 0;
+    let mut index = 0;
+    while pc < code.len() {
+        indices[pc] = Some(index);
+        index += 1;
+        pc += match code[pc] {
+            opcode @ 0x60..=0x7f => (opcode - 0x5f) as usize + 1,
+            _ => 1,
+        };
+    }
+    indices
+}
+
+/// 源码文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceFile {
+    pub name: String,
+    pub content: String,
+}
+
+/// 合约源码验证信息，`sources` 按编译器的源文件索引排列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractSource {
+    pub source_map: String,
+    pub sources: Vec<SourceFile>,
+}
+
+/// 源码位置，行列从 1 开始
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// 所在行的源码
+    pub text: String,
+}
+
+/// 已验证的合约
+#[derive(Debug, Clone)]
+pub struct VerifiedContract {
+    entries: Vec<SourceMapEntry>,
+    sources: Vec<SourceFile>,
+    indices: Vec<Option<usize>>,
+}
+
+impl VerifiedContract {
+    /// 校验源码映射与部署代码一致
+    ///
+    /// 部署代码末尾的编译器元数据没有映射，因此映射项数只需不超过指令数。
+    pub fn new(code: &[u8], source: ContractSource) -> Result<Self, SourceMapError> {
+        if code.is_empty() {
+            return Err(SourceMapError::NoCode);
+        }
+        let entries = parse_source_map(&source.source_map)?;
+        let indices = instruction_indices(code);
+        let instructions = indices.iter().flatten().count();
+        if entries.len() > instructions {
+            return Err(SourceMapError::TooManyEntries {
+                entries: entries.len(),
+                instructions,
+            });
+        }
+        for entry in &entries {
+            match entry.file {
+                Some(file) if file >= source.sources.len() => {
+                    return Err(SourceMapError::UnknownSource(file))
+                }
+                _ => {}
+            }
+        }
+        Ok(Self {
+            entries,
+            sources: source.sources,
+            indices,
+        })
+    }
+
+    /// 程序计数器对应的源码位置，编译器生成的代码返回 `None`
+    pub fn locate(&self, pc: usize) -> Option<SourceLocation> {
+        let index = (*self.indices.get(pc)?)?;
+        let entry = self.entries.get(index)?;
+        let source = self.sources.get(entry.file?)?;
+        let offset = entry.offset.min(source.content.len());
+        let before = source.content.get(..offset)?;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source.content[offset..]
+            .find('\n')
+            .map_or(source.content.len(), |i| offset + i);
+        Some(SourceLocation {
+            file: source.name.clone(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: source.content[line_start..line_end].trim().to_string(),
+        })
+    }
+}
+
+/// 已验证合约的源码映射登记表
+#[derive(Debug, Default)]
+pub struct SourceRegistry {
+    contracts: HashMap<Address, Arc<VerifiedContract>>,
+}
+
+impl SourceRegistry {
+    /// 登记合约源码，重复登记时覆盖
+    pub fn register(
+        &mut self,
+        address: Address,
+        code: &[u8],
+        source: ContractSource,
+    ) -> Result<(), SourceMapError> {
+        let contract = VerifiedContract::new(code, source)?;
+        self.contracts.insert(address, Arc::new(contract));
+        Ok(())
+    }
+
+    /// 获取已验证的合约
+    pub fn get(&self, address: &Address) -> Option<Arc<VerifiedContract>> {
+        self.contracts.get(address).cloned()
+    }
+
+    /// 程序计数器对应的源码位置
+    pub fn locate(&self, address: &Address, pc: usize) -> Option<SourceLocation> {
+        self.contracts.get(address)?.locate(pc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str =
+        "contract A {\n    function f(uint x) public {\n        require(x > 1);\n    }\n}\n";
+
+    fn source(source_map: &str) -> ContractSource {
+        ContractSource {
+            source_map: source_map.to_string(),
+            sources: vec![SourceFile {
+                name: "A.sol".to_string(),
+                content: SOURCE.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_parse_source_map() {
+        let entries = parse_source_map("0:80:0:-;;51:14::i;:::o;1:2:-1").unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[1], entries[0]);
+        assert_eq!((entries[2].offset, entries[2].length), (51, 14));
+        assert_eq!(entries[2].file, Some(0));
+        assert_eq!(entries[2].jump, JumpType::In);
+        assert_eq!(entries[3].jump, JumpType::Out);
+        assert_eq!(entries[4].file, None);
+        assert!(matches!(
+            parse_source_map("0:x"),
+            Err(SourceMapError::InvalidEntry { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_locate() {
+        // PUSH1 00, DUP1, REVERT：REVERT 位于 pc 3，对应第三项映射
+        let code = [0x60, 0x00, 0x80, 0xfd];
+        let offset = SOURCE.find("require").unwrap();
+        let map = format!("0:80:0;0:80:0;{}:14:0", offset);
+        let contract = VerifiedContract::new(&code, source(&map)).unwrap();
+
+        let location = contract.locate(3).unwrap();
+        assert_eq!(location.file, "A.sol");
+        assert_eq!((location.line, location.column), (3, 9));
+        assert_eq!(location.text, "require(x > 1);");
+        assert_eq!(contract.locate(0).unwrap().line, 1);
+        // PUSH 数据不是指令
+        assert!(contract.locate(1).is_none());
+
+        assert_eq!(
+            VerifiedContract::new(&code, source("0:1:0;0:1:0;0:1:0;0:1:0;0:1:0")).unwrap_err(),
+            SourceMapError::TooManyEntries {
+                entries: 5,
+                instructions: 3
+            }
+        );
+        assert_eq!(
+            VerifiedContract::new(&code, source("0:1:2")).unwrap_err(),
+            SourceMapError::UnknownSource(2)
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! 测试网水龙头
+//!
+//! 从创世分配的资金账户向申请地址发放测试代币，同一地址在冷却时间内只能领取一次，
+//! 可选接入人机验证。通过 [`router`] 提供 HTTP 接口。
+
+use crate::account::{Account, Address};
+use crate::genesis::Genesis;
+use crate::state::State;
+use async_trait::async_trait;
+use axum::extract::State as AxumState;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use ethers::types::{H160, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+/// 默认冷却时间（秒）
+pub const DEFAULT_COOLDOWN_SECS: u64 = 24 * 60 * 60;
+
+/// 水龙头错误
+#[derive(Debug, Error)]
+pub enum FaucetError {
+    #[error("无效的地址: {0}")]
+    InvalidAddress(String),
+
+    #[error("领取过于频繁，请在 {retry_after_secs} 秒后重试")]
+    RateLimited { retry_after_secs: u64 },
+
+    #[error("人机验证未通过")]
+    CaptchaRejected,
+
+    #[error("人机验证服务错误: {0}")]
+    Captcha(String),
+
+    #[error("水龙头余额不足")]
+    InsufficientFunds,
+
+    #[error("配置错误: {0}")]
+    Config(String),
+}
+
+/// 水龙头配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetConfig {
+    /// 资金账户，必须在创世配置中分配余额
+    pub funder: Address,
+    /// 每次发放数量（wei）
+    pub amount: U256,
+    /// 同一地址两次领取的最小间隔（秒）
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_cooldown_secs() -> u64 {
+    DEFAULT_COOLDOWN_SECS
+}
+
+impl FaucetConfig {
+    pub fn new(funder: Address, amount: U256) -> Self {
+        Self {
+            funder,
+            amount,
+            cooldown_secs: DEFAULT_COOLDOWN_SECS,
+        }
+    }
+
+    /// 设置冷却时间
+    pub fn with_cooldown(mut self, cooldown_secs: u64) -> Self {
+        self.cooldown_secs = cooldown_secs;
+        self
+    }
+
+    /// 检查资金账户来自创世分配
+    pub fn validate(&self, genesis: &Genesis) -> Result<(), FaucetError> {
+        if self.amount.is_zero() {
+            return Err(FaucetError::Config("发放数量不能为 0".to_string()));
+        }
+        if !genesis.alloc.contains_key(&H160(self.funder.0)) {
+            return Err(FaucetError::Config(format!(
+                "资金账户 {} 不在创世分配中",
+                self.funder
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 人机验证接口
+#[async_trait]
+pub trait CaptchaVerifier: Send + Sync {
+    /// 校验请求携带的验证令牌
+    async fn verify(&self, token: Option<&str>, recipient: &Address) -> Result<bool, FaucetError>;
+}
+
+/// 发放结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaucetGrant {
+    pub recipient: Address,
+    pub amount: U256,
+    /// 领取后的账户余额
+    pub balance: U256,
+}
+
+/// 测试网水龙头
+pub struct Faucet {
+    config: FaucetConfig,
+    state: Arc<RwLock<State>>,
+    captcha: Option<Arc<dyn CaptchaVerifier>>,
+    /// 各地址最近一次领取时间（Unix 秒）
+    last_grants: RwLock<HashMap<Address, u64>>,
+}
+
+impl Faucet {
+    pub fn new(config: FaucetConfig, state: Arc<RwLock<State>>) -> Self {
+        Self {
+            config,
+            state,
+            captcha: None,
+            last_grants: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 启用人机验证
+    pub fn with_captcha(mut self, captcha: Arc<dyn CaptchaVerifier>) -> Self {
+        self.captcha = Some(captcha);
+        self
+    }
+
+    /// 向地址发放测试代币
+    pub async fn dispense(
+        &self,
+        recipient: Address,
+        captcha_token: Option<&str>,
+    ) -> Result<FaucetGrant, FaucetError> {
+        self.dispense_at(
+            recipient,
+            captcha_token,
+            chrono::Utc::now().timestamp() as u64,
+        )
+        .await
+    }
+
+    async fn dispense_at(
+        &self,
+        recipient: Address,
+        captcha_token: Option<&str>,
+        now: u64,
+    ) -> Result<FaucetGrant, FaucetError> {
+        if let Some(captcha) = &self.captcha {
+            if !captcha.verify(captcha_token, &recipient).await? {
+                return Err(FaucetError::CaptchaRejected);
+            }
+        }
+
+        // 持有写锁直到发放完成，避免同一地址并发领取
+        let mut last_grants = self.last_grants.write().await;
+        if let Some(last) = last_grants.get(&recipient) {
+            let next = last.saturating_add(self.config.cooldown_secs);
+            if now < next {
+                return Err(FaucetError::RateLimited {
+                    retry_after_secs: next - now,
+                });
+            }
+        }
+
+        let balance = self.transfer(recipient).await?;
+        last_grants.insert(recipient, now);
+        log::info!("水龙头向 {} 发放 {} wei", recipient, self.config.amount);
+
+        Ok(FaucetGrant {
+            recipient,
+            amount: self.config.amount,
+            balance,
+        })
+    }
+
+    /// 从资金账户转账，返回接收方余额
+    async fn transfer(&self, recipient: Address) -> Result<U256, FaucetError> {
+        let mut state = self.state.write().await;
+        let funder_balance = state.get_balance(&self.config.funder).await;
+        if funder_balance < self.config.amount {
+            return Err(FaucetError::InsufficientFunds);
+        }
+
+        if state.get_account(&recipient).await.is_none() {
+            state
+                .set_account(&Account::new(recipient))
+                .await
+                .map_err(FaucetError::Config)?;
+        }
+        let balance = state.get_balance(&recipient).await + self.config.amount;
+        state
+            .set_balance(&self.config.funder, funder_balance - self.config.amount)
+            .await
+            .map_err(FaucetError::Config)?;
+        state
+            .set_balance(&recipient, balance)
+            .await
+            .map_err(FaucetError::Config)?;
+        Ok(balance)
+    }
+}
+
+/// 领取请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetRequest {
+    pub address: String,
+    /// 人机验证令牌
+    #[serde(default)]
+    pub captcha: Option<String>,
+}
+
+/// 错误响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FaucetErrorResponse {
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+impl IntoResponse for FaucetError {
+    fn into_response(self) -> Response {
+        let (status, retry_after_secs) = match &self {
+            FaucetError::InvalidAddress(_) => (StatusCode::BAD_REQUEST, None),
+            FaucetError::RateLimited { retry_after_secs } => {
+                (StatusCode::TOO_MANY_REQUESTS, Some(*retry_after_secs))
+            }
+            FaucetError::CaptchaRejected => (StatusCode::FORBIDDEN, None),
+            FaucetError::InsufficientFunds => (StatusCode::SERVICE_UNAVAILABLE, None),
+            FaucetError::Captcha(_) | FaucetError::Config(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, None)
+            }
+        };
+        let body = FaucetErrorResponse {
+            error: self.to_string(),
+            retry_after_secs,
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// 水龙头 HTTP 路由，`POST /faucet`
+pub fn router(faucet: Arc<Faucet>) -> Router {
+    Router::new()
+        .route("/faucet", post(request_funds))
+        .with_state(faucet)
+}
+
+async fn request_funds(
+    AxumState(faucet): AxumState<Arc<Faucet>>,
+    Json(request): Json<FaucetRequest>,
+) -> Result<Json<FaucetGrant>, FaucetError> {
+    let recipient = parse_address(&request.address)?;
+    faucet
+        .dispense(recipient, request.captcha.as_deref())
+        .await
+        .map(Json)
+}
+
+fn parse_address(address: &str) -> Result<Address, FaucetError> {
+    let bytes = hex::decode(address.trim_start_matches("0x"))
+        .map_err(|_| FaucetError::InvalidAddress(address.to_string()))?;
+    if bytes.len() != 20 {
+        return Err(FaucetError::InvalidAddress(address.to_string()));
+    }
+    Ok(Address::from(H160::from_slice(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TokenCaptcha;
+
+    #[async_trait]
+    impl CaptchaVerifier for TokenCaptcha {
+        async fn verify(
+            &self,
+            token: Option<&str>,
+            _recipient: &Address,
+        ) -> Result<bool, FaucetError> {
+            Ok(token == Some("ok"))
+        }
+    }
+
+    async fn faucet(funds: u64) -> Faucet {
+        let funder = Address([1u8; 20]);
+        let mut state = State::default();
+        let mut account = Account::new(funder);
+        account.balance = U256::from(funds);
+        state.set_account(&account).await.unwrap();
+
+        let config = FaucetConfig::new(funder, U256::from(100)).with_cooldown(60);
+        Faucet::new(config, Arc::new(RwLock::new(state)))
+    }
+
+    #[tokio::test]
+    async fn test_dispense_with_rate_limit() {
+        let faucet = faucet(250).await;
+        let recipient = Address([2u8; 20]);
+
+        let grant = faucet.dispense_at(recipient, None, 1_000).await.unwrap();
+        assert_eq!(grant.balance, U256::from(100));
+
+        match faucet.dispense_at(recipient, None, 1_030).await {
+            Err(FaucetError::RateLimited { retry_after_secs }) => assert_eq!(retry_after_secs, 30),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let grant = faucet.dispense_at(recipient, None, 1_060).await.unwrap();
+        assert_eq!(grant.balance, U256::from(200));
+
+        // 资金账户只剩 50
+        assert!(matches!(
+            faucet.dispense_at(Address([3u8; 20]), None, 1_060).await,
+            Err(FaucetError::InsufficientFunds)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_captcha_hook() {
+        let faucet = faucet(1_000).await.with_captcha(Arc::new(TokenCaptcha));
+        let recipient = Address([2u8; 20]);
+        assert!(matches!(
+            faucet.dispense_at(recipient, Some("bad"), 0).await,
+            Err(FaucetError::CaptchaRejected)
+        ));
+        assert!(faucet.dispense_at(recipient, Some("ok"), 0).await.is_ok());
+    }
+
+    #[test]
+    fn test_validate_funder_in_genesis() {
+        let funder = Address([1u8; 20]);
+        let config = FaucetConfig::new(funder, U256::from(100));
+        let mut genesis = Genesis::default();
+        assert!(config.validate(&genesis).is_err());
+        genesis.add_account(H160(funder.0), 1_000);
+        assert!(config.validate(&genesis).is_ok());
+    }
+}
@@ -0,0 +1,293 @@
+//! 费用市场模拟
+//!
+//! 用合成或记录的负载逐块重放 [`FeesConfig`] 的基础费用规则，比较不同目标用量和变化分母下的
+//! 基础费用走势，帮助在创世前选定费用参数。每个区块的需求先加上积压的交易，按区块 gas 上限
+//! 打包，放不下的留到下一个区块；基础费用超过 `max_fee` 时当块需求被挤出，不再积压。
+
+use crate::fee_stats::FeeStatsSummary;
+use crate::genesis::FeesConfig;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// 合成负载，按区块序号给出 gas 需求
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyntheticLoad {
+    /// 每个区块需求相同
+    Constant(u64),
+    /// 需求在模拟区间内从 `from` 线性变化到 `to`
+    Ramp { from: u64, to: u64 },
+    /// 平时为 `base`，每 `period` 个区块的第一个区块为 `peak`
+    Spike { base: u64, peak: u64, period: u64 },
+}
+
+impl SyntheticLoad {
+    /// 生成 `blocks` 个区块的需求
+    pub fn generate(&self, blocks: usize) -> Vec<u64> {
+        (0..blocks as u64)
+            .map(|i| match *self {
+                Self::Constant(gas) => gas,
+                Self::Ramp { from, to } => {
+                    let span = (blocks as u64).saturating_sub(1).max(1);
+                    if to >= from {
+                        from + (to - from) * i / span
+                    } else {
+                        from - (from - to) * i / span
+                    }
+                }
+                Self::Spike { base, peak, period } => {
+                    if period > 0 && i % period == 0 {
+                        peak
+                    } else {
+                        base
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromStr for SyntheticLoad {
+    type Err = String;
+
+    /// 解析 `constant:GAS`、`ramp:FROM:TO` 或 `spike:BASE:PEAK:PERIOD`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let kind = parts.next().unwrap_or_default();
+        let numbers = parts
+            .map(|part| {
+                part.trim()
+                    .replace('_', "")
+                    .parse::<u64>()
+                    .map_err(|e| format!("负载参数 {} 无效: {}", part, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        match (kind, numbers.as_slice()) {
+            ("constant", [gas]) => Ok(Self::Constant(*gas)),
+            ("ramp", [from, to]) => Ok(Self::Ramp {
+                from: *from,
+                to: *to,
+            }),
+            ("spike", [base, peak, period]) => Ok(Self::Spike {
+                base: *base,
+                peak: *peak,
+                period: *period,
+            }),
+            _ => Err(format!(
+                "无法识别的负载 {}，可用 constant:GAS、ramp:FROM:TO、spike:BASE:PEAK:PERIOD",
+                s
+            )),
+        }
+    }
+}
+
+/// 从记录中读取每个区块的 gas 用量
+///
+/// 支持费用统计汇总（[`FeeStatsSummary`]）的 JSON，或每行一个数字的文本，`#` 开头的行为注释。
+pub fn parse_recorded_load(content: &str) -> Result<Vec<u64>, String> {
+    if let Ok(summary) = serde_json::from_str::<FeeStatsSummary>(content) {
+        return Ok(summary.blocks.iter().map(|block| block.gas_used).collect());
+    }
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.replace('_', "")
+                .parse::<u64>()
+                .map_err(|e| format!("gas 用量 {} 无效: {}", line, e))
+        })
+        .collect()
+}
+
+/// 模拟中的单个区块
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedBlock {
+    /// 区块序号，从 0 开始
+    pub number: u64,
+    /// 区块的基础费用
+    pub base_fee: U256,
+    /// 当块新增的 gas 需求
+    pub demand: u64,
+    /// 打包的 gas
+    pub gas_used: u64,
+    /// 留到下一个区块的 gas
+    pub backlog: u64,
+    /// 因基础费用超过 `max_fee` 被挤出的 gas
+    pub priced_out: u64,
+}
+
+/// 一组费用参数的模拟结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeSimulation {
+    /// 目标区块 gas 用量
+    pub gas_target: u64,
+    /// 基础费用变化分母
+    pub base_fee_change_denominator: u64,
+    pub blocks: Vec<SimulatedBlock>,
+}
+
+impl FeeSimulation {
+    /// 各区块的基础费用
+    pub fn base_fees(&self) -> Vec<U256> {
+        self.blocks.iter().map(|block| block.base_fee).collect()
+    }
+
+    pub fn min_base_fee(&self) -> U256 {
+        self.blocks
+            .iter()
+            .map(|block| block.base_fee)
+            .min()
+            .unwrap_or_default()
+    }
+
+    pub fn max_base_fee(&self) -> U256 {
+        self.blocks
+            .iter()
+            .map(|block| block.base_fee)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn final_base_fee(&self) -> U256 {
+        self.blocks
+            .last()
+            .map(|block| block.base_fee)
+            .unwrap_or_default()
+    }
+
+    /// 平均区块用量占目标用量的百分比
+    pub fn average_utilization(&self) -> f64 {
+        if self.blocks.is_empty() || self.gas_target == 0 {
+            return 0.0;
+        }
+        let used: u128 = self.blocks.iter().map(|block| block.gas_used as u128).sum();
+        used as f64 * 100.0 / (self.blocks.len() as f64 * self.gas_target as f64)
+    }
+
+    /// 被挤出的 gas 总量
+    pub fn priced_out(&self) -> u64 {
+        self.blocks
+            .iter()
+            .fold(0u64, |sum, block| sum.saturating_add(block.priced_out))
+    }
+}
+
+/// 以 `fees` 的参数重放负载
+pub fn simulate(fees: &FeesConfig, load: &[u64]) -> FeeSimulation {
+    let gas_limit = fees.block_gas_limit();
+    let max_fee = U256::from(fees.max_fee);
+    let mut base_fee = U256::from(fees.base_fee);
+    let mut backlog = 0u64;
+    let mut blocks = Vec::with_capacity(load.len());
+
+    for (number, demand) in load.iter().copied().enumerate() {
+        let (offered, priced_out) = if fees.max_fee > 0 && base_fee > max_fee {
+            (backlog, demand)
+        } else {
+            (backlog.saturating_add(demand), 0)
+        };
+        let gas_used = if gas_limit == 0 {
+            offered
+        } else {
+            offered.min(gas_limit)
+        };
+        backlog = offered - gas_used;
+        blocks.push(SimulatedBlock {
+            number: number as u64,
+            base_fee,
+            demand,
+            gas_used,
+            backlog,
+            priced_out,
+        });
+        base_fee = fees.next_base_fee(base_fee, gas_used);
+    }
+
+    FeeSimulation {
+        gas_target: fees.gas_target,
+        base_fee_change_denominator: fees.base_fee_change_denominator,
+        blocks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis::Genesis;
+
+    fn fees(gas_target: u64, denominator: u64) -> FeesConfig {
+        FeesConfig {
+            base_fee: 1_000,
+            max_fee: 0,
+            gas_target,
+            base_fee_change_denominator: denominator,
+            ..Genesis::default().fees
+        }
+    }
+
+    #[test]
+    fn test_next_base_fee() {
+        let fees = fees(100, 8);
+        assert_eq!(
+            fees.next_base_fee(U256::from(1_000), 100),
+            U256::from(1_000)
+        );
+        assert_eq!(
+            fees.next_base_fee(U256::from(1_000), 200),
+            U256::from(1_125)
+        );
+        assert_eq!(fees.next_base_fee(U256::from(1_000), 0), U256::from(875));
+        // 超过目标时至少增加 1
+        assert_eq!(fees.next_base_fee(U256::from(1), 101), U256::from(2));
+    }
+
+    #[test]
+    fn test_simulate_backlog_and_denominator() {
+        let load = SyntheticLoad::Constant(300).generate(3);
+        let fast = simulate(&fees(100, 8), &load);
+        assert_eq!(
+            fast.blocks.iter().map(|b| b.backlog).collect::<Vec<_>>(),
+            vec![100, 200, 300]
+        );
+        assert_eq!(
+            fast.base_fees(),
+            vec![U256::from(1_000), U256::from(1_125), U256::from(1_265)]
+        );
+        let slow = simulate(&fees(100, 16), &load);
+        assert!(slow.final_base_fee() < fast.final_base_fee());
+        assert_eq!(fast.average_utilization(), 200.0);
+    }
+
+    #[test]
+    fn test_priced_out() {
+        let fees = FeesConfig {
+            max_fee: 1_100,
+            ..fees(100, 8)
+        };
+        let simulation = simulate(&fees, &[200, 200, 200]);
+        // 第二个区块的基础费用超过 max_fee，需求被挤出后基础费用回落
+        assert_eq!(simulation.blocks[1].base_fee, U256::from(1_125));
+        assert_eq!(simulation.blocks[1].gas_used, 0);
+        assert_eq!(simulation.blocks[2].base_fee, U256::from(985));
+        assert_eq!(simulation.blocks[2].gas_used, 200);
+        assert_eq!(simulation.priced_out(), 200);
+    }
+
+    #[test]
+    fn test_loads() {
+        assert_eq!(
+            "ramp:0:30".parse::<SyntheticLoad>().unwrap().generate(4),
+            vec![0, 10, 20, 30]
+        );
+        assert_eq!(
+            "spike:1:9:2".parse::<SyntheticLoad>().unwrap().generate(4),
+            vec![9, 1, 9, 1]
+        );
+        assert!("wave:1".parse::<SyntheticLoad>().is_err());
+        assert_eq!(
+            parse_recorded_load("# gas\n100\n\n2_000\n").unwrap(),
+            vec![100, 2000]
+        );
+    }
+}
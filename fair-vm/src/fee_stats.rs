@@ -0,0 +1,216 @@
+use crate::blockchain::Block;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 默认保留的区块统计数量
+pub const DEFAULT_FEE_HISTORY: usize = 1024;
+
+/// 区块被视为“已满”的 gas 使用率（百分比）
+pub const FULL_BLOCK_THRESHOLD: u64 = 95;
+
+/// 单个区块的费用统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockFeeStats {
+    /// 区块高度
+    pub number: u64,
+    /// 基础费用
+    pub base_fee: U256,
+    /// 已用 gas
+    pub gas_used: u64,
+    /// 区块 gas 上限
+    pub gas_limit: u64,
+    /// 最小优先费
+    pub min_priority_fee: U256,
+    /// 优先费中位数
+    pub median_priority_fee: U256,
+    /// 最大优先费
+    pub max_priority_fee: U256,
+    /// 交易数量
+    pub transaction_count: usize,
+}
+
+impl BlockFeeStats {
+    /// 区块是否已满
+    pub fn is_full(&self) -> bool {
+        self.gas_limit > 0 && self.gas_used * 100 >= self.gas_limit * FULL_BLOCK_THRESHOLD
+    }
+}
+
+/// 区块范围内的费用汇总
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeStatsSummary {
+    /// 起始区块
+    pub from_block: u64,
+    /// 结束区块
+    pub to_block: u64,
+    /// 每个区块的统计
+    pub blocks: Vec<BlockFeeStats>,
+    /// 已满区块占比（百分比）
+    pub full_block_percentage: f64,
+}
+
+/// 增量费用统计器，在区块导入时更新
+#[derive(Debug)]
+pub struct FeeStatsTracker {
+    /// 按高度索引的区块统计
+    stats: BTreeMap<u64, BlockFeeStats>,
+    /// 最大保留区块数
+    capacity: usize,
+}
+
+impl FeeStatsTracker {
+    /// 创建新的费用统计器
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            stats: BTreeMap::new(),
+            capacity,
+        }
+    }
+
+    /// 记录导入的区块
+    pub fn record_block(&mut self, block: &Block, base_fee: U256, gas_used: u64, gas_limit: u64) {
+        let mut priority_fees: Vec<U256> = block
+            .transactions
+            .iter()
+            .filter_map(|tx| tx.effective_priority_fee(base_fee))
+            .collect();
+        priority_fees.sort_unstable();
+
+        let stats = BlockFeeStats {
+            number: block.header.number,
+            base_fee,
+            gas_used,
+            gas_limit,
+            min_priority_fee: priority_fees.first().copied().unwrap_or_default(),
+            median_priority_fee: priority_fees
+                .get(priority_fees.len() / 2)
+                .copied()
+                .unwrap_or_default(),
+            max_priority_fee: priority_fees.last().copied().unwrap_or_default(),
+            transaction_count: block.transactions.len(),
+        };
+        self.stats.insert(stats.number, stats);
+
+        while self.stats.len() > self.capacity {
+            let oldest = *self.stats.keys().next().expect("统计不为空");
+            self.stats.remove(&oldest);
+        }
+    }
+
+    /// 获取单个区块的统计
+    pub fn get(&self, number: u64) -> Option<&BlockFeeStats> {
+        self.stats.get(&number)
+    }
+
+    /// 获取最新记录的区块高度
+    pub fn latest(&self) -> Option<u64> {
+        self.stats.keys().next_back().copied()
+    }
+
+    /// 获取区块范围内的统计汇总
+    pub fn range(&self, from_block: u64, to_block: u64) -> FeeStatsSummary {
+        let blocks: Vec<BlockFeeStats> = if from_block > to_block {
+            Vec::new()
+        } else {
+            self.stats
+                .range(from_block..=to_block)
+                .map(|(_, stats)| stats.clone())
+                .collect()
+        };
+        let full = blocks.iter().filter(|stats| stats.is_full()).count();
+        let full_block_percentage = if blocks.is_empty() {
+            0.0
+        } else {
+            full as f64 * 100.0 / blocks.len() as f64
+        };
+
+        FeeStatsSummary {
+            from_block,
+            to_block,
+            blocks,
+            full_block_percentage,
+        }
+    }
+}
+
+impl Default for FeeStatsTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEE_HISTORY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Address;
+    use crate::blockchain::BlockHeader;
+    use crate::transaction::{Transaction, TransactionType};
+    use ethers::types::H256;
+
+    fn block(number: u64, tips: &[u64]) -> Block {
+        let transactions = tips
+            .iter()
+            .map(|tip| {
+                Transaction::new(
+                    H256::random(),
+                    Address::random(),
+                    None,
+                    U256::zero(),
+                    0,
+                    21000,
+                    None,
+                    vec![],
+                    vec![],
+                    TransactionType::EIP1559,
+                    1,
+                    Some(U256::from(100 + tip)),
+                    Some(U256::from(*tip)),
+                )
+            })
+            .collect();
+        Block {
+            header: BlockHeader {
+                parent_hash: H256::zero(),
+                number,
+                timestamp: 0,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions,
+            arrivals: Vec::new(),
+            acceptance: None,
+        }
+    }
+
+    #[test]
+    fn test_record_block() {
+        let mut tracker = FeeStatsTracker::default();
+        tracker.record_block(&block(1, &[5, 1, 3]), U256::from(100), 30000, 30000);
+
+        let stats = tracker.get(1).unwrap();
+        assert_eq!(stats.min_priority_fee, U256::from(1));
+        assert_eq!(stats.median_priority_fee, U256::from(3));
+        assert_eq!(stats.max_priority_fee, U256::from(5));
+        assert!(stats.is_full());
+    }
+
+    #[test]
+    fn test_range_and_capacity() {
+        let mut tracker = FeeStatsTracker::new(2);
+        tracker.record_block(&block(1, &[]), U256::from(100), 0, 30000);
+        tracker.record_block(&block(2, &[1]), U256::from(110), 30000, 30000);
+        tracker.record_block(&block(3, &[2]), U256::from(120), 10000, 30000);
+
+        assert!(tracker.get(1).is_none());
+        let summary = tracker.range(1, 3);
+        assert_eq!(summary.blocks.len(), 2);
+        assert_eq!(summary.full_block_percentage, 50.0);
+        assert_eq!(tracker.latest(), Some(3));
+    }
+}
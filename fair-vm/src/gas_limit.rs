@@ -0,0 +1,314 @@
+//! 区块 gas 上限治理
+//!
+//! 区块 gas 上限不再固定，而是像以太坊一样每个区块向目标值调整，单个区块的变化不超过父区块
+//! 上限的 1/1024，并始终保持在创世配置的 `min` 与 `max` 之间。目标值初始为创世配置的
+//! `target`，配置了 `governor` 时，治理地址可向 gas 上限系统地址发送 `setTarget(uint64)`
+//! 交易修改目标值。当前上限和目标值记录在系统地址的存储槽中，随区块状态一起提交。
+
+use crate::account::Address;
+use crate::genesis::GasLimitConfig;
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use ethers::types::{H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// gas 上限治理系统合约地址
+pub const GAS_LIMIT_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x05,
+]);
+
+/// 系统合约占位代码（实际逻辑由 VM 原生执行）
+pub const GAS_LIMIT_CODE: &[u8] = &[0x00];
+
+/// 修改目标值交易消耗的 gas
+pub const GAS_LIMIT_VOTE_GAS: u64 = 30_000;
+
+/// 单个区块的最大调整比例为父区块上限的 1/ADJUSTMENT_QUOTIENT
+pub const ADJUSTMENT_QUOTIENT: u64 = 1024;
+
+/// gas 上限治理错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum GasLimitError {
+    #[error("无效的 gas 上限配置: {0}")]
+    InvalidConfig(String),
+
+    #[error("{0} 不是 gas 上限治理地址")]
+    NotGovernor(Address),
+
+    #[error("目标值 {target} 超出范围 [{min}, {max}]")]
+    OutOfRange { target: u64, min: u64, max: u64 },
+
+    #[error("区块交易 gas 总量 {used} 超过区块上限 {limit}")]
+    BlockGasExceeded { used: u64, limit: u64 },
+
+    #[error("无效的治理交易: {0}")]
+    InvalidTransaction(String),
+}
+
+/// 当前 gas 上限状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasLimitStatus {
+    /// 下一个区块的 gas 上限
+    pub current: u64,
+    pub target: u64,
+    pub min: u64,
+    pub max: u64,
+    pub governor: Option<Address>,
+}
+
+impl GasLimitConfig {
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), GasLimitError> {
+        if self.min == 0 {
+            return Err(GasLimitError::InvalidConfig("最小值必须大于 0".into()));
+        }
+        if self.min > self.max {
+            return Err(GasLimitError::InvalidConfig("最小值不能大于最大值".into()));
+        }
+        Ok(())
+    }
+
+    /// 将值限制在 `[min, max]` 内
+    pub fn clamp(&self, value: u64) -> u64 {
+        value.max(self.min).min(self.max)
+    }
+
+    /// 由父区块上限向目标值调整，得到下一个区块的上限
+    pub fn next_limit(&self, parent: u64, target: u64) -> u64 {
+        let target = self.clamp(target);
+        let delta = (parent / ADJUSTMENT_QUOTIENT).max(1);
+        let next = if target > parent {
+            parent.saturating_add(delta).min(target)
+        } else {
+            parent.saturating_sub(delta).max(target)
+        };
+        self.clamp(next)
+    }
+}
+
+/// `setTarget(uint64)` 的函数选择器
+pub fn set_target_selector() -> [u8; 4] {
+    let hash = ethers::utils::keccak256("setTarget(uint64)");
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// 判断交易是否发送至 gas 上限治理系统合约
+pub fn is_gas_limit_transaction(tx: &Transaction) -> bool {
+    tx.to == Some(GAS_LIMIT_ADDRESS)
+}
+
+fn slot(tag: &[u8]) -> [u8; 32] {
+    ethers::utils::keccak256(tag)
+}
+
+async fn read_value(storage: &(dyn Storage + Send + Sync), tag: &[u8]) -> Option<u64> {
+    let word = storage
+        .get_storage_value(&GAS_LIMIT_ADDRESS, slot(tag))
+        .await;
+    let value = U256::from_big_endian(&word);
+    // 0 表示尚未写入
+    (!value.is_zero()).then_some(value.low_u64())
+}
+
+async fn write_value(storage: &mut (dyn Storage + Send + Sync), tag: &[u8], value: u64) {
+    let mut word = [0u8; 32];
+    U256::from(value).to_big_endian(&mut word);
+    storage
+        .set_storage_value(&GAS_LIMIT_ADDRESS, slot(tag), word)
+        .await;
+}
+
+/// 当前目标值，未经治理修改时为创世配置的目标值
+pub async fn target_of(storage: &(dyn Storage + Send + Sync), config: &GasLimitConfig) -> u64 {
+    config.clamp(
+        read_value(storage, b"fairvm-gas-limit-target")
+            .await
+            .unwrap_or(config.target),
+    )
+}
+
+/// 最近一个区块的上限，尚无区块时为创世目标值
+pub async fn last_limit(storage: &(dyn Storage + Send + Sync), config: &GasLimitConfig) -> u64 {
+    match read_value(storage, b"fairvm-gas-limit-current").await {
+        Some(limit) => limit,
+        None => config.clamp(config.target),
+    }
+}
+
+/// 下一个区块的上限
+pub async fn next_limit(storage: &(dyn Storage + Send + Sync), config: &GasLimitConfig) -> u64 {
+    let parent = last_limit(storage, config).await;
+    let target = target_of(storage, config).await;
+    config.next_limit(parent, target)
+}
+
+/// 当前状态
+pub async fn status(
+    storage: &(dyn Storage + Send + Sync),
+    config: &GasLimitConfig,
+) -> GasLimitStatus {
+    GasLimitStatus {
+        current: next_limit(storage, config).await,
+        target: target_of(storage, config).await,
+        min: config.min,
+        max: config.max,
+        governor: config.governor,
+    }
+}
+
+/// 区块执行前调整并记录本区块的上限，交易声明的 gas 总量超过上限时返回错误
+pub async fn apply_block(
+    storage: &mut (dyn Storage + Send + Sync),
+    config: &GasLimitConfig,
+    transactions: &[Transaction],
+) -> Result<u64, GasLimitError> {
+    let limit = next_limit(storage, config).await;
+    let used = transactions
+        .iter()
+        .fold(0u64, |total, tx| total.saturating_add(tx.gas_limit));
+    if used > limit {
+        return Err(GasLimitError::BlockGasExceeded { used, limit });
+    }
+    write_value(storage, b"fairvm-gas-limit-current", limit).await;
+    Ok(limit)
+}
+
+/// 执行治理交易修改目标值，返回产生的日志
+pub async fn set_target(
+    storage: &mut (dyn Storage + Send + Sync),
+    config: &GasLimitConfig,
+    tx: &Transaction,
+) -> Result<Vec<(Address, Vec<H256>, Vec<u8>)>, GasLimitError> {
+    if !is_gas_limit_transaction(tx) {
+        return Err(GasLimitError::InvalidTransaction(
+            "目标地址不是 gas 上限系统合约".into(),
+        ));
+    }
+    if config.governor != Some(tx.from) {
+        return Err(GasLimitError::NotGovernor(tx.from));
+    }
+    if tx.data.len() != 36 || tx.data[..4] != set_target_selector() {
+        return Err(GasLimitError::InvalidTransaction(
+            "需要 setTarget(uint64) 调用".into(),
+        ));
+    }
+    let target = U256::from_big_endian(&tx.data[4..]);
+    if target > U256::from(config.max) || target < U256::from(config.min) {
+        return Err(GasLimitError::OutOfRange {
+            target: if target > U256::from(u64::MAX) {
+                u64::MAX
+            } else {
+                target.low_u64()
+            },
+            min: config.min,
+            max: config.max,
+        });
+    }
+    let target = target.low_u64();
+    write_value(storage, b"fairvm-gas-limit-target", target).await;
+
+    let mut data = [0u8; 32];
+    U256::from(target).to_big_endian(&mut data);
+    Ok(vec![(
+        GAS_LIMIT_ADDRESS,
+        vec![H256(ethers::utils::keccak256(
+            "GasLimitTargetChanged(uint64)",
+        ))],
+        data.to_vec(),
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+
+    fn config() -> GasLimitConfig {
+        GasLimitConfig {
+            min: 1_000_000,
+            max: 20_000_000,
+            target: 8_000_000,
+            governor: Some(Address([7u8; 20])),
+        }
+    }
+
+    fn vote(from: Address, target: u64) -> Transaction {
+        let mut data = set_target_selector().to_vec();
+        let mut word = [0u8; 32];
+        U256::from(target).to_big_endian(&mut word);
+        data.extend_from_slice(&word);
+        Transaction::new(
+            H256::from_low_u64_be(1),
+            from,
+            Some(GAS_LIMIT_ADDRESS),
+            U256::zero(),
+            0,
+            GAS_LIMIT_VOTE_GAS,
+            Some(U256::zero()),
+            data,
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_next_limit() {
+        let config = config();
+        assert_eq!(config.next_limit(8_000_000, 8_000_000), 8_000_000);
+        assert_eq!(config.next_limit(8_192_000, 16_000_000), 8_200_000);
+        assert_eq!(config.next_limit(8_192_000, 1), 8_184_000);
+        // 接近目标时不越过目标
+        assert_eq!(config.next_limit(8_000_000, 8_000_100), 8_000_100);
+        // 目标超出范围时按范围截断
+        assert_eq!(config.next_limit(19_999_000, u64::MAX), 20_000_000);
+        assert!(GasLimitConfig {
+            min: 2,
+            max: 1,
+            ..config
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_governance() {
+        let config = config();
+        let mut storage = MemoryStorage::default();
+        assert_eq!(
+            apply_block(&mut storage, &config, &[]).await.unwrap(),
+            8_000_000
+        );
+
+        assert_eq!(
+            set_target(&mut storage, &config, &vote(Address([1u8; 20]), 10_000_000)).await,
+            Err(GasLimitError::NotGovernor(Address([1u8; 20])))
+        );
+        assert!(matches!(
+            set_target(&mut storage, &config, &vote(Address([7u8; 20]), 30_000_000)).await,
+            Err(GasLimitError::OutOfRange { .. })
+        ));
+        set_target(&mut storage, &config, &vote(Address([7u8; 20]), 10_000_000))
+            .await
+            .unwrap();
+
+        let first = apply_block(&mut storage, &config, &[]).await.unwrap();
+        assert_eq!(first, 8_000_000 + 8_000_000 / ADJUSTMENT_QUOTIENT);
+        let second = apply_block(&mut storage, &config, &[]).await.unwrap();
+        assert_eq!(second, first + first / ADJUSTMENT_QUOTIENT);
+
+        let status = status(&storage, &config).await;
+        assert_eq!(status.target, 10_000_000);
+        assert_eq!(status.current, second + second / ADJUSTMENT_QUOTIENT);
+
+        let heavy = vec![vote(Address([7u8; 20]), 10_000_000); 300];
+        assert!(matches!(
+            apply_block(&mut storage, &config, &heavy).await,
+            Err(GasLimitError::BlockGasExceeded { .. })
+        ));
+    }
+}
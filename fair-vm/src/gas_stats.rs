@@ -0,0 +1,319 @@
+use crate::account::Address;
+use crate::evm::selectors::{self, Selector, SelectorDatabase};
+use crate::receipt::Receipt;
+use crate::transaction::Transaction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 默认保留的区块统计数量
+pub const DEFAULT_GAS_HISTORY: usize = 1024;
+
+/// 合约和选择器，选择器为空表示无调用数据的转账或合约创建
+type GasKey = (Address, Option<Selector>);
+
+/// 单个合约函数的 gas 累计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct GasUsage {
+    calls: u64,
+    failed: u64,
+    total_gas: u64,
+    max_gas: u64,
+}
+
+impl GasUsage {
+    fn add(&mut self, gas_used: u64, status: bool) {
+        self.calls += 1;
+        if !status {
+            self.failed += 1;
+        }
+        self.total_gas += gas_used;
+        self.max_gas = self.max_gas.max(gas_used);
+    }
+
+    fn merge(&mut self, other: &GasUsage) {
+        self.calls += other.calls;
+        self.failed += other.failed;
+        self.total_gas += other.total_gas;
+        self.max_gas = self.max_gas.max(other.max_gas);
+    }
+}
+
+/// 合约函数的 gas 使用汇总
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectorGas {
+    /// 合约地址
+    pub contract: Address,
+    /// 十六进制函数选择器，无调用数据时为空
+    pub selector: Option<String>,
+    /// 选择器数据库中的第一个候选签名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// 调用次数
+    pub calls: u64,
+    /// 失败次数
+    pub failed: u64,
+    /// 已用 gas 总量
+    pub total_gas: u64,
+    /// 单次调用最大 gas
+    pub max_gas: u64,
+    /// 平均每次调用的 gas
+    pub average_gas: u64,
+}
+
+/// 区块范围内按合约和选择器的 gas 报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasBySelectorReport {
+    /// 起始区块
+    pub from_block: u64,
+    /// 结束区块
+    pub to_block: u64,
+    /// 范围内已用 gas 总量
+    pub total_gas: u64,
+    /// 按已用 gas 降序排列的条目
+    pub entries: Vec<SelectorGas>,
+}
+
+impl GasBySelectorReport {
+    /// 按选择器数据库填写各条目的函数签名
+    pub fn annotate(&mut self, database: &SelectorDatabase) {
+        for entry in &mut self.entries {
+            let selector = entry
+                .selector
+                .as_deref()
+                .and_then(|selector| hex::decode(selector.trim_start_matches("0x")).ok())
+                .and_then(|bytes| selectors::selector_of(&bytes));
+            entry.signature =
+                selector.and_then(|selector| database.lookup(&selector).first().cloned());
+        }
+    }
+}
+
+/// 交易的目标合约，合约创建时为新合约地址
+fn contract_of(tx: &Transaction, receipt: &Receipt) -> Option<Address> {
+    tx.to.or(receipt.contract_address)
+}
+
+/// 调用数据的函数选择器，合约创建的调用数据是初始化代码，不计选择器
+fn selector_of(tx: &Transaction) -> Option<Selector> {
+    if tx.to.is_none() {
+        return None;
+    }
+    selectors::selector_of(&tx.data)
+}
+
+/// 增量 gas 统计器，在区块执行后按合约和函数选择器累计
+#[derive(Debug)]
+pub struct GasStatsTracker {
+    /// 按高度索引的区块统计
+    blocks: BTreeMap<u64, HashMap<GasKey, GasUsage>>,
+    /// 最大保留区块数
+    capacity: usize,
+}
+
+impl GasStatsTracker {
+    /// 创建新的 gas 统计器
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            blocks: BTreeMap::new(),
+            capacity,
+        }
+    }
+
+    /// 记录区块内已执行的交易及其收据
+    pub fn record_block<'a>(
+        &mut self,
+        number: u64,
+        executed: impl IntoIterator<Item = (&'a Transaction, &'a Receipt)>,
+    ) {
+        let mut usage: HashMap<GasKey, GasUsage> = HashMap::new();
+        for (tx, receipt) in executed {
+            let Some(contract) = contract_of(tx, receipt) else {
+                continue;
+            };
+            usage
+                .entry((contract, selector_of(tx)))
+                .or_default()
+                .add(receipt.gas_used, receipt.status);
+        }
+        self.blocks.insert(number, usage);
+
+        while self.blocks.len() > self.capacity {
+            let oldest = *self.blocks.keys().next().expect("统计不为空");
+            self.blocks.remove(&oldest);
+        }
+    }
+
+    /// 获取最新记录的区块高度
+    pub fn latest(&self) -> Option<u64> {
+        self.blocks.keys().next_back().copied()
+    }
+
+    /// 汇总区块范围内的 gas 使用，`limit` 限制返回的条目数
+    pub fn range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        limit: Option<usize>,
+    ) -> GasBySelectorReport {
+        let mut totals: HashMap<GasKey, GasUsage> = HashMap::new();
+        if from_block <= to_block {
+            for usage in self
+                .blocks
+                .range(from_block..=to_block)
+                .map(|(_, usage)| usage)
+            {
+                for (key, gas) in usage {
+                    totals.entry(*key).or_default().merge(gas);
+                }
+            }
+        }
+
+        let total_gas = totals.values().map(|gas| gas.total_gas).sum();
+        let mut entries: Vec<SelectorGas> = totals
+            .into_iter()
+            .map(|((contract, selector), gas)| SelectorGas {
+                contract,
+                selector: selector.map(|selector| format!("0x{}", hex::encode(selector))),
+                signature: None,
+                calls: gas.calls,
+                failed: gas.failed,
+                total_gas: gas.total_gas,
+                max_gas: gas.max_gas,
+                average_gas: gas.total_gas / gas.calls.max(1),
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.total_gas
+                .cmp(&a.total_gas)
+                .then_with(|| a.contract.cmp(&b.contract))
+                .then_with(|| a.selector.cmp(&b.selector))
+        });
+        if let Some(limit) = limit {
+            entries.truncate(limit);
+        }
+
+        GasBySelectorReport {
+            from_block,
+            to_block,
+            total_gas,
+            entries,
+        }
+    }
+}
+
+impl Default for GasStatsTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_GAS_HISTORY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receipt::ReceiptContext;
+    use crate::transaction::TransactionType;
+    use ethers::types::{H256, U256};
+    use fair_vm_core::vm::ExecutionResult;
+
+    fn execution(
+        to: Option<Address>,
+        data: Vec<u8>,
+        gas_used: u64,
+        status: bool,
+    ) -> (Transaction, Receipt) {
+        let tx = Transaction::new(
+            H256::random(),
+            Address::random(),
+            to,
+            U256::zero(),
+            0,
+            100_000,
+            Some(U256::one()),
+            data,
+            vec![],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        let result = ExecutionResult {
+            gas_used,
+            return_data: Vec::new(),
+            status,
+            gas_refunded: 0,
+        };
+        let context = ReceiptContext {
+            block_hash: H256::zero(),
+            block_number: 1,
+            transaction_index: 0,
+            base_fee: U256::zero(),
+            cumulative_gas_before: 0,
+            log_index_before: 0,
+        };
+        let receipt = Receipt::from_execution(&tx, &result, &context, Vec::new(), 0);
+        (tx, receipt)
+    }
+
+    #[test]
+    fn test_gas_by_selector() {
+        let token = Address([1; 20]);
+        let transfer = vec![0xa9, 0x05, 0x9c, 0xbb, 0, 0];
+        let approve = vec![0x09, 0x5e, 0xa7, 0xb3];
+        let block1 = vec![
+            execution(Some(token), transfer.clone(), 50_000, true),
+            execution(Some(token), approve, 30_000, true),
+            execution(None, vec![0x60, 0x80, 0x60, 0x40], 200_000, true),
+        ];
+        let block2 = vec![
+            execution(Some(token), transfer, 70_000, false),
+            execution(Some(Address([2; 20])), vec![], 21_000, true),
+        ];
+
+        let mut tracker = GasStatsTracker::default();
+        tracker.record_block(1, block1.iter().map(|(tx, receipt)| (tx, receipt)));
+        tracker.record_block(2, block2.iter().map(|(tx, receipt)| (tx, receipt)));
+
+        let report = tracker.range(1, 2, None);
+        assert_eq!(report.total_gas, 371_000);
+        assert_eq!(report.entries.len(), 4);
+
+        // 合约创建归入新合约地址，不计选择器
+        let creation = &report.entries[0];
+        assert_eq!(creation.contract, block1[2].1.contract_address.unwrap());
+        assert_eq!(creation.selector, None);
+
+        let transfer = &report.entries[1];
+        assert_eq!(transfer.selector.as_deref(), Some("0xa9059cbb"));
+        assert_eq!(transfer.calls, 2);
+        assert_eq!(transfer.failed, 1);
+        assert_eq!(transfer.total_gas, 120_000);
+        assert_eq!(transfer.max_gas, 70_000);
+        assert_eq!(transfer.average_gas, 60_000);
+
+        let mut annotated = report.clone();
+        annotated.annotate(&SelectorDatabase::default());
+        assert_eq!(
+            annotated.entries[1].signature.as_deref(),
+            Some("transfer(address,uint256)")
+        );
+        assert_eq!(annotated.entries[0].signature, None);
+
+        let top = tracker.range(2, 2, Some(1));
+        assert_eq!(top.entries.len(), 1);
+        assert_eq!(top.entries[0].total_gas, 70_000);
+        assert!(tracker.range(3, 1, None).entries.is_empty());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut tracker = GasStatsTracker::new(2);
+        for number in 1..=3 {
+            let (tx, receipt) = execution(Some(Address([1; 20])), vec![1, 2, 3, 4], 1_000, true);
+            tracker.record_block(number, [(&tx, &receipt)]);
+        }
+        assert_eq!(tracker.latest(), Some(3));
+        assert_eq!(tracker.range(1, 3, None).total_gas, 2_000);
+    }
+}
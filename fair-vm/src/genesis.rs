@@ -1,4 +1,15 @@
+use crate::account::Account;
+use crate::deployment::{DeploymentConfig, DEPLOYMENT_ADDRESS, DEPLOYMENT_CODE};
+use crate::gas_limit::{GAS_LIMIT_ADDRESS, GAS_LIMIT_CODE};
+use crate::native_nft::{NativeNftPolicy, NATIVE_NFT_ADDRESS, NATIVE_NFT_CODE};
+use crate::nonce::NonceMode;
+use crate::oracle::{OracleConfig, ORACLE_CONTRACT_ADDRESS, ORACLE_CONTRACT_CODE};
+use crate::staking::{StakingConfig, STAKING_ADDRESS, STAKING_CODE};
+use crate::storage::Storage;
 use crate::types::{Address, Hash};
+use crate::vesting::VestingSchedule;
+use ethers::types::U256;
+use fair_vm_core::params::ChainConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -9,6 +20,23 @@ pub struct Genesis {
     pub gas_limit: GasLimitConfig,
     pub fees: FeesConfig,
     pub alloc: HashMap<Address, GenesisAccount>,
+    #[serde(default)]
+    pub oracle: Option<OracleConfig>,
+    /// 原生 NFT 转移策略
+    #[serde(default)]
+    pub native_nft: Option<NativeNftPolicy>,
+    /// 硬分叉激活区块，未配置时全部自创世区块起激活
+    #[serde(default)]
+    pub config: Option<ChainConfig>,
+    /// 质押奖励配置
+    #[serde(default)]
+    pub staking: Option<StakingConfig>,
+    /// 交易 nonce 模式，默认为顺序 nonce
+    #[serde(default)]
+    pub nonce_mode: NonceMode,
+    /// 合约部署许可，未配置时任何地址都可部署
+    #[serde(default)]
+    pub deployment: Option<DeploymentConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +44,9 @@ pub struct GenesisAccount {
     pub balance: u64,
     pub code: Option<Vec<u8>>,
     pub storage: HashMap<Hash, Hash>,
+    /// 锁仓计划，锁定部分不能转出
+    #[serde(default)]
+    pub vesting: Option<VestingSchedule>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,13 +54,63 @@ pub struct GasLimitConfig {
     pub min: u64,
     pub max: u64,
     pub target: u64,
+    /// 可修改目标值的治理地址，未配置时目标值固定
+    #[serde(default)]
+    pub governor: Option<crate::account::Address>,
 }
 
+/// 默认目标区块 gas 用量
+pub const DEFAULT_GAS_TARGET: u64 = 8_000_000;
+
+/// 默认基础费用变化分母
+pub const DEFAULT_BASE_FEE_CHANGE_DENOMINATOR: u64 = 8;
+
+/// 区块 gas 上限为目标用量的倍数
+pub const ELASTICITY_MULTIPLIER: u64 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeesConfig {
     pub base_fee: u64,
     pub max_priority_fee: u64,
     pub max_fee: u64,
+    /// 目标区块 gas 用量，基础费用按实际用量与目标的偏差调整，为 0 时基础费用固定
+    #[serde(default = "default_gas_target")]
+    pub gas_target: u64,
+    /// 基础费用变化分母，值越大单个区块的变化越小
+    #[serde(default = "default_base_fee_change_denominator")]
+    pub base_fee_change_denominator: u64,
+}
+
+fn default_gas_target() -> u64 {
+    DEFAULT_GAS_TARGET
+}
+
+fn default_base_fee_change_denominator() -> u64 {
+    DEFAULT_BASE_FEE_CHANGE_DENOMINATOR
+}
+
+impl FeesConfig {
+    /// 区块 gas 上限
+    pub fn block_gas_limit(&self) -> u64 {
+        self.gas_target.saturating_mul(ELASTICITY_MULTIPLIER)
+    }
+
+    /// 按 EIP-1559 规则计算父区块用量为 `parent_gas_used` 时下一个区块的基础费用
+    pub fn next_base_fee(&self, parent_base_fee: U256, parent_gas_used: u64) -> U256 {
+        let target = self.gas_target;
+        let denominator = self.base_fee_change_denominator;
+        if target == 0 || denominator == 0 || parent_gas_used == target {
+            return parent_base_fee;
+        }
+        let change = |delta: u64| {
+            parent_base_fee.saturating_mul(U256::from(delta)) / U256::from(target) / denominator
+        };
+        if parent_gas_used > target {
+            parent_base_fee.saturating_add(change(parent_gas_used - target).max(U256::one()))
+        } else {
+            parent_base_fee.saturating_sub(change(target - parent_gas_used))
+        }
+    }
 }
 
 impl Default for Genesis {
@@ -41,13 +122,22 @@ impl Default for Genesis {
                 min: 21000,
                 max: 8000000,
                 target: 15000000,
+                governor: None,
             },
             fees: FeesConfig {
                 base_fee: 1000000000,
                 max_priority_fee: 2000000000,
                 max_fee: 10000000000,
+                gas_target: DEFAULT_GAS_TARGET,
+                base_fee_change_denominator: DEFAULT_BASE_FEE_CHANGE_DENOMINATOR,
             },
             alloc: HashMap::new(),
+            oracle: None,
+            native_nft: None,
+            config: None,
+            staking: None,
+            nonce_mode: NonceMode::Sequential,
+            deployment: None,
         }
     }
 }
@@ -60,6 +150,14 @@ impl Genesis {
         }
     }
 
+    /// 链参数，链 ID 以创世配置的 `chain_id` 为准
+    pub fn chain_config(&self) -> ChainConfig {
+        ChainConfig {
+            chain_id: self.chain_id,
+            ..self.config.clone().unwrap_or_default()
+        }
+    }
+
     pub fn add_account(&mut self, address: Address, balance: u64) {
         self.alloc.insert(
             address,
@@ -67,6 +165,25 @@ impl Genesis {
                 balance,
                 code: None,
                 storage: HashMap::new(),
+                vesting: None,
+            },
+        );
+    }
+
+    /// 添加带锁仓计划的账户
+    pub fn add_vesting_account(
+        &mut self,
+        address: Address,
+        balance: u64,
+        schedule: VestingSchedule,
+    ) {
+        self.alloc.insert(
+            address,
+            GenesisAccount {
+                balance,
+                code: None,
+                storage: HashMap::new(),
+                vesting: Some(schedule),
             },
         );
     }
@@ -84,7 +201,81 @@ impl Genesis {
                 balance,
                 code: Some(code),
                 storage,
+                vesting: None,
             },
         );
     }
+
+    /// 启用价格预言机并在系统地址部署预言机合约
+    pub fn enable_oracle(&mut self, config: OracleConfig) {
+        self.add_contract(
+            ORACLE_CONTRACT_ADDRESS.into(),
+            0,
+            ORACLE_CONTRACT_CODE.to_vec(),
+            HashMap::new(),
+        );
+        self.oracle = Some(config);
+    }
+
+    /// 启用原生 NFT 转移策略，并在系统地址部署占位代码便于钱包识别为合约
+    pub fn enable_native_nft(&mut self, policy: NativeNftPolicy) {
+        self.add_contract(
+            NATIVE_NFT_ADDRESS.into(),
+            0,
+            NATIVE_NFT_CODE.to_vec(),
+            HashMap::new(),
+        );
+        self.native_nft = Some(policy);
+    }
+
+    /// 启用质押奖励，并在系统地址部署占位代码
+    pub fn enable_staking(&mut self, config: StakingConfig) {
+        self.add_contract(
+            STAKING_ADDRESS.into(),
+            0,
+            STAKING_CODE.to_vec(),
+            HashMap::new(),
+        );
+        self.staking = Some(config);
+    }
+
+    /// 允许治理地址修改区块 gas 上限目标值，并在系统地址部署占位代码
+    pub fn enable_gas_limit_governance(&mut self, governor: crate::account::Address) {
+        self.add_contract(
+            GAS_LIMIT_ADDRESS.into(),
+            0,
+            GAS_LIMIT_CODE.to_vec(),
+            HashMap::new(),
+        );
+        self.gas_limit.governor = Some(governor);
+    }
+
+    /// 启用合约部署许可，并在系统地址部署占位代码
+    pub fn enable_deployment_policy(&mut self, config: DeploymentConfig) {
+        self.add_contract(
+            DEPLOYMENT_ADDRESS.into(),
+            0,
+            DEPLOYMENT_CODE.to_vec(),
+            HashMap::new(),
+        );
+        self.deployment = Some(config);
+    }
+
+    /// 把创世分配的余额、代码和存储槽写入状态，按地址顺序写入
+    pub async fn write_alloc(&self, storage: &mut (dyn Storage + Send + Sync)) {
+        let mut alloc: Vec<_> = self.alloc.iter().collect();
+        alloc.sort_by_key(|(address, _)| **address);
+        for (address, account) in alloc {
+            let address = crate::account::Address(address.0);
+            let mut state = Account::new(address);
+            state.balance = U256::from(account.balance);
+            storage.set_account(&state).await;
+            if let Some(code) = &account.code {
+                storage.set_code(&address, code.clone()).await;
+            }
+            for (key, value) in &account.storage {
+                storage.set_storage_value(&address, key.0, value.0).await;
+            }
+        }
+    }
 }
@@ -0,0 +1,222 @@
+//! 交易与收据的包含证明
+//!
+//! 交易所等托管方确认充值时不必信任 RPC 节点：节点返回交易、收据以及二者在区块交易根和
+//! 收据根中的 Merkle 路径，托管方用自己验证过的区块头（例如由 [`crate::light`] 轻节点同步）
+//! 重新计算两个根，即可确认交易已被该区块包含并得到这份收据。交易和收据位于两棵树的同一
+//! 位置，收据叶子承诺了交易哈希，二者因此互相绑定。
+
+use crate::blockchain::{Block, BlockHeader, RootKind};
+use crate::merkle::{self, RECEIPTS_TAG, TRANSACTIONS_TAG};
+use crate::receipt::Receipt;
+use crate::state_proof::ProofError;
+use crate::transaction::Transaction;
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+
+/// 交易和收据的包含证明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProof {
+    pub block_hash: H256,
+    pub block_number: u64,
+    pub transaction: Transaction,
+    pub receipt: Receipt,
+    /// 交易在区块中的序号
+    pub index: u64,
+    /// 区块中的交易数量
+    pub leaf_count: u64,
+    /// 交易根中自底向上的兄弟节点
+    pub transaction_siblings: Vec<H256>,
+    /// 收据根中自底向上的兄弟节点
+    pub receipt_siblings: Vec<H256>,
+}
+
+impl InclusionProof {
+    /// 为区块中的交易生成证明，`receipts` 为区块的全部收据，交易不在区块中时返回 `None`
+    pub fn build(block: &Block, receipts: &[Receipt], tx_hash: &H256) -> Option<Self> {
+        let index = block
+            .transactions
+            .iter()
+            .position(|tx| tx.hash == *tx_hash)?;
+        if receipts.len() != block.transactions.len() {
+            return None;
+        }
+        let transaction_layers = merkle::layers(
+            block
+                .transactions
+                .iter()
+                .map(merkle::transaction_leaf)
+                .collect(),
+        );
+        let receipt_layers = merkle::layers(receipts.iter().map(merkle::receipt_leaf).collect());
+        Some(Self {
+            block_hash: block.hash(),
+            block_number: block.header.number,
+            transaction: block.transactions[index].clone(),
+            receipt: receipts[index].clone(),
+            index: index as u64,
+            leaf_count: block.transactions.len() as u64,
+            transaction_siblings: merkle::branch(&transaction_layers, index),
+            receipt_siblings: merkle::branch(&receipt_layers, index),
+        })
+    }
+
+    /// 对照可信的区块头验证证明
+    ///
+    /// 区块头必须承诺了交易根和收据根；验证通过后 `transaction` 和 `receipt` 可以直接使用。
+    pub fn verify(&self, header: &BlockHeader) -> Result<(), ProofError> {
+        if header.number != self.block_number || header.hash() != self.block_hash {
+            return Err(ProofError::Malformed("证明不属于该区块".into()));
+        }
+        if self.receipt.transaction_hash != self.transaction.hash
+            || self.receipt.transaction_index != self.index
+            || self.receipt.block_number != self.block_number
+        {
+            return Err(ProofError::Malformed("收据与交易不一致".into()));
+        }
+        let checks = [
+            (
+                RootKind::Transactions,
+                TRANSACTIONS_TAG,
+                merkle::transaction_leaf(&self.transaction),
+                &self.transaction_siblings,
+            ),
+            (
+                RootKind::Receipts,
+                RECEIPTS_TAG,
+                merkle::receipt_leaf(&self.receipt),
+                &self.receipt_siblings,
+            ),
+        ];
+        for (kind, tag, leaf, siblings) in checks {
+            let expected = header.root(kind);
+            if expected.is_zero() {
+                return Err(ProofError::Malformed(format!("区块头未承诺{}", kind)));
+            }
+            let tree_root = merkle::branch_root(leaf, self.index, self.leaf_count, siblings)?;
+            let actual = merkle::tagged_root(tag, tree_root, self.leaf_count);
+            if actual != expected {
+                return Err(ProofError::RootMismatch { expected, actual });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Address;
+    use crate::receipt::ReceiptContext;
+    use crate::transaction::TransactionType;
+    use ethers::types::U256;
+    use fair_vm_core::vm::ExecutionResult;
+
+    fn sample_block(count: u64) -> (Block, Vec<Receipt>) {
+        let transactions: Vec<Transaction> = (0..count)
+            .map(|i| {
+                Transaction::new(
+                    H256::from_low_u64_be(i + 1),
+                    Address([1u8; 20]),
+                    Some(Address([2u8; 20])),
+                    U256::from(i * 100),
+                    i,
+                    21_000,
+                    Some(U256::zero()),
+                    Vec::new(),
+                    vec![i as u8; 65],
+                    TransactionType::Legacy,
+                    1,
+                    None,
+                    None,
+                )
+            })
+            .collect();
+        let mut block = Block {
+            header: BlockHeader {
+                parent_hash: H256::zero(),
+                number: 7,
+                timestamp: 0,
+                transactions_root: merkle::transactions_root(&transactions),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions,
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        let mut context = ReceiptContext {
+            block_hash: H256::zero(),
+            block_number: 7,
+            transaction_index: 0,
+            base_fee: U256::zero(),
+            cumulative_gas_before: 0,
+            log_index_before: 0,
+        };
+        let result = ExecutionResult {
+            gas_used: 21_000,
+            return_data: Vec::new(),
+            status: true,
+            gas_refunded: 0,
+        };
+        let mut receipts: Vec<Receipt> = Vec::new();
+        for (index, tx) in block.transactions.iter().enumerate() {
+            context.transaction_index = index as u64;
+            let receipt = Receipt::from_execution(tx, &result, &context, Vec::new(), 0);
+            context.cumulative_gas_before = receipt.cumulative_gas_used;
+            receipts.push(receipt);
+        }
+        block.header.receipts_root = merkle::receipts_root(&receipts);
+        (block, receipts)
+    }
+
+    #[test]
+    fn test_verify() {
+        for count in 1..=5 {
+            let (block, receipts) = sample_block(count);
+            for tx in &block.transactions {
+                let proof = InclusionProof::build(&block, &receipts, &tx.hash).unwrap();
+                assert_eq!(proof.transaction.hash, tx.hash);
+                proof.verify(&block.header).unwrap();
+            }
+        }
+        let (block, receipts) = sample_block(3);
+        assert!(InclusionProof::build(&block, &receipts, &H256::repeat_byte(9)).is_none());
+    }
+
+    #[test]
+    fn test_tampered() {
+        let (block, receipts) = sample_block(3);
+        let proof = InclusionProof::build(&block, &receipts, &H256::from_low_u64_be(2)).unwrap();
+
+        let mut forged = proof.clone();
+        forged.receipt.status = false;
+        assert!(matches!(
+            forged.verify(&block.header),
+            Err(ProofError::RootMismatch { .. })
+        ));
+
+        let mut forged = proof.clone();
+        forged.transaction.value = U256::from(1_000_000);
+        assert!(forged.verify(&block.header).is_err());
+
+        // 同一区块中另一笔交易的收据不能冒充
+        let mut forged = proof.clone();
+        forged.receipt = receipts[0].clone();
+        assert!(forged.verify(&block.header).is_err());
+
+        let mut other = block.header.clone();
+        other.number += 1;
+        assert!(proof.verify(&other).is_err());
+
+        let mut uncommitted = block.clone();
+        uncommitted.header.receipts_root = H256::zero();
+        let proof =
+            InclusionProof::build(&uncommitted, &receipts, &H256::from_low_u64_be(2)).unwrap();
+        assert!(proof.verify(&uncommitted.header).is_err());
+    }
+}
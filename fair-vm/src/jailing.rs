@@ -0,0 +1,271 @@
+//! 验证人监禁
+//!
+//! 出块者取自区块头并须为活跃验证人，见 [`crate::FairVM::accept_block_from`]。
+//! 预定出块者错过槽位后，若其最近的在线率低于 `min_uptime` 则被监禁，监禁期间不参与出块轮换。
+//! 在线率只统计上次解禁以来的槽位，且至少有 `min_slots` 个预定槽位才会判定；最后一个
+//! 未被监禁的验证人不会被监禁。冷却期过后，验证人的奖励地址向质押系统地址发送 `unjail()`
+//! 交易即可解禁。
+
+use crate::account::Address;
+use crate::staking::{StakingConfig, StakingError, STAKING_ADDRESS};
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use crate::uptime::{self, UPTIME_BASIS_POINTS};
+use ethers::types::{H160, H256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// 监禁配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JailingConfig {
+    /// 最低在线率（基点）
+    pub min_uptime: u64,
+    /// 判定前至少需要的预定槽位数
+    pub min_slots: u64,
+    /// 监禁后可解禁前需等待的区块数
+    pub cooldown: u64,
+}
+
+impl JailingConfig {
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), StakingError> {
+        if self.min_uptime > UPTIME_BASIS_POINTS {
+            return Err(StakingError::InvalidConfig(format!(
+                "最低在线率不能超过 {}",
+                UPTIME_BASIS_POINTS
+            )));
+        }
+        if self.min_slots == 0 {
+            return Err(StakingError::InvalidConfig("判定槽位数必须大于 0".into()));
+        }
+        Ok(())
+    }
+
+    /// 按最近的预定槽位数和错过数判断是否应被监禁
+    pub fn should_jail(&self, expected: u64, missed: u64) -> bool {
+        expected >= self.min_slots
+            && (expected - missed) * UPTIME_BASIS_POINTS / expected < self.min_uptime
+    }
+}
+
+/// 解禁调用的函数选择器
+pub fn unjail_selector() -> [u8; 4] {
+    let hash = ethers::utils::keccak256("unjail()");
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// 判断交易是否为解禁调用
+pub fn is_unjail_call(tx: &Transaction) -> bool {
+    tx.to == Some(STAKING_ADDRESS) && tx.data == unjail_selector()
+}
+
+fn jail_slot(node_id: &str) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"fairvm-jail-");
+    hasher.update(node_id.as_bytes());
+    hasher.finalize().into()
+}
+
+/// 验证人被监禁时的区块高度，未被监禁时为 None
+pub async fn jailed_at(storage: &(dyn Storage + Send + Sync), node_id: &str) -> Option<u64> {
+    let word = storage
+        .get_storage_value(&STAKING_ADDRESS, jail_slot(node_id))
+        .await;
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&word[24..]);
+    // 高度加 1 保存，0 表示未被监禁
+    u64::from_be_bytes(bytes).checked_sub(1)
+}
+
+async fn set_jailed_at(
+    storage: &mut (dyn Storage + Send + Sync),
+    node_id: &str,
+    number: Option<u64>,
+) {
+    let mut word = [0u8; 32];
+    if let Some(number) = number {
+        word[24..].copy_from_slice(&number.saturating_add(1).to_be_bytes());
+    }
+    storage
+        .set_storage_value(&STAKING_ADDRESS, jail_slot(node_id), word)
+        .await;
+}
+
+/// 验证人错过槽位后检查其在线率，需要时在区块 `number` 监禁，返回是否被监禁
+pub async fn evaluate(
+    storage: &mut (dyn Storage + Send + Sync),
+    config: &StakingConfig,
+    node_id: &str,
+    number: u64,
+) -> bool {
+    let Some(jailing) = &config.jailing else {
+        return false;
+    };
+    if jailed_at(storage, node_id).await.is_some() {
+        return false;
+    }
+    let (expected, missed) = uptime::window_of(storage, node_id).await;
+    if !jailing.should_jail(expected, missed) {
+        return false;
+    }
+    if uptime::active_validators(storage, config).await.len() <= 1 {
+        return false;
+    }
+    set_jailed_at(storage, node_id, Some(number)).await;
+    true
+}
+
+/// 执行解禁交易，发送方须为已过冷却期的被监禁验证人的奖励地址，返回产生的日志
+pub async fn unjail(
+    storage: &mut (dyn Storage + Send + Sync),
+    config: &StakingConfig,
+    number: u64,
+    tx: &Transaction,
+) -> Result<Vec<(Address, Vec<H256>, Vec<u8>)>, StakingError> {
+    if !is_unjail_call(tx) {
+        return Err(StakingError::InvalidTransaction("不是解禁调用".into()));
+    }
+    if !tx.value.is_zero() {
+        return Err(StakingError::InvalidTransaction(
+            "解禁交易不能附带转账".into(),
+        ));
+    }
+    let validator = config
+        .validators
+        .iter()
+        .find(|v| v.reward_address == tx.from)
+        .ok_or(StakingError::NotValidator(tx.from))?;
+    let since = jailed_at(storage, &validator.node_id)
+        .await
+        .ok_or_else(|| StakingError::NotJailed(validator.node_id.clone()))?;
+    let cooldown = config.jailing.as_ref().map(|j| j.cooldown).unwrap_or(0);
+    let until = since.saturating_add(cooldown);
+    if number < until {
+        return Err(StakingError::JailCooldown {
+            node_id: validator.node_id.clone(),
+            until,
+        });
+    }
+
+    set_jailed_at(storage, &validator.node_id, None).await;
+    uptime::reset_window(storage, &validator.node_id).await;
+    Ok(vec![(
+        STAKING_ADDRESS,
+        vec![
+            H256(ethers::utils::keccak256("ValidatorUnjailed(address)")),
+            H256::from(H160::from(tx.from)),
+        ],
+        Vec::new(),
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staking::ValidatorStake;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+    use ethers::types::U256;
+
+    fn config() -> StakingConfig {
+        StakingConfig {
+            validators: ["NodeID-A", "NodeID-B"]
+                .iter()
+                .enumerate()
+                .map(|(i, node_id)| ValidatorStake {
+                    node_id: node_id.to_string(),
+                    reward_address: Address([i as u8 + 1; 20]),
+                    stake: U256::from(100),
+                })
+                .collect(),
+            epoch_length: 10,
+            epoch_reward: U256::zero(),
+            stake_weight_percent: 100,
+            jailing: Some(JailingConfig {
+                min_uptime: 5_000,
+                min_slots: 2,
+                cooldown: 10,
+            }),
+        }
+    }
+
+    fn unjail_tx(from: Address) -> Transaction {
+        Transaction::new(
+            H256::from_low_u64_be(1),
+            from,
+            Some(STAKING_ADDRESS),
+            U256::zero(),
+            0,
+            50_000,
+            Some(U256::zero()),
+            unjail_selector().to_vec(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_should_jail() {
+        let jailing = config().jailing.unwrap();
+        assert!(!jailing.should_jail(1, 1));
+        assert!(jailing.should_jail(2, 2));
+        assert!(!jailing.should_jail(2, 1));
+        assert!(jailing.should_jail(3, 2));
+        assert!(jailing.validate().is_ok());
+        assert!(JailingConfig {
+            min_uptime: UPTIME_BASIS_POINTS + 1,
+            ..jailing
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jail_and_unjail() {
+        let config = config();
+        let mut storage = MemoryStorage::default();
+        // B 连续错过两个槽位
+        for number in 1..=4 {
+            if let Some(missed) =
+                uptime::record_block(&mut storage, &config, number, "NodeID-A").await
+            {
+                evaluate(&mut storage, &config, &missed, number).await;
+            }
+        }
+        assert_eq!(jailed_at(&storage, "NodeID-B").await, Some(4));
+        // 监禁后只剩 A 参与轮换，A 不会被监禁
+        let active = uptime::active_validators(&storage, &config).await;
+        assert_eq!(active.len(), 1);
+        assert_eq!(
+            uptime::scheduled_proposer(&active, 5).unwrap().node_id,
+            "NodeID-A"
+        );
+
+        let tx = unjail_tx(Address([2; 20]));
+        assert_eq!(
+            unjail(&mut storage, &config, 10, &tx).await,
+            Err(StakingError::JailCooldown {
+                node_id: "NodeID-B".into(),
+                until: 14,
+            })
+        );
+        assert!(matches!(
+            unjail(&mut storage, &config, 14, &unjail_tx(Address([9; 20]))).await,
+            Err(StakingError::NotValidator(_))
+        ));
+        assert_eq!(
+            unjail(&mut storage, &config, 14, &tx).await.unwrap().len(),
+            1
+        );
+        assert_eq!(jailed_at(&storage, "NodeID-B").await, None);
+        assert_eq!(uptime::window_of(&storage, "NodeID-B").await, (0, 0));
+        assert!(matches!(
+            unjail(&mut storage, &config, 15, &tx).await,
+            Err(StakingError::NotJailed(_))
+        ));
+    }
+}
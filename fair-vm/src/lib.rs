@@ -10,44 +10,117 @@
 //! - 事件通知系统
 
 pub mod account;
+pub mod account_history;
 pub mod api;
+pub mod avax_address;
 pub mod block;
 pub mod blockchain;
+pub mod chain_metadata;
+pub mod checkpoint;
+pub mod clock;
+pub mod compression;
 pub mod consensus;
+pub mod crash;
+pub mod deployment;
 pub mod event;
+pub mod event_sink;
 pub mod evm;
+pub mod faucet;
+pub mod fee_market;
+pub mod fee_stats;
+pub mod gas_limit;
+pub mod gas_stats;
 pub mod genesis;
+pub mod inclusion;
+pub mod jailing;
+pub mod light;
+pub mod log_filter;
+pub mod log_index;
+pub mod merkle;
+pub mod native_multisig;
+pub mod native_nft;
 pub mod network;
 pub mod nft;
+pub mod nonce;
+pub mod oracle;
+pub mod plugin;
+pub mod producer;
+pub mod receipt;
+pub mod resources;
+pub mod signing;
+pub mod simulation;
+pub mod staking;
 pub mod state;
+pub mod state_proof;
+pub mod state_sync;
 pub mod storage;
+pub mod supply;
+pub mod telemetry;
 pub mod transaction;
+pub mod tx_validator;
+pub mod txpool;
 pub mod types;
+pub mod uptime;
+pub mod verification;
+pub mod vesting;
 pub mod vm;
+pub mod webhook;
 
 pub use account::{Account, Address};
+pub use account_history::{AccountHistory, AccountTransfer};
 pub use api::VmExt;
+pub use avax_address::{AddressFormats, AvaxAddress, KeyAddresses};
 pub use block::Block;
 pub use blockchain::*;
+pub use chain_metadata::{ChainMetadata, NativeCurrency, NetworkMetadata};
+pub use checkpoint::{Checkpoint, CheckpointSnapshot, CheckpointSource, LazyStorage};
+pub use compression::Compression;
 pub use consensus::basic;
 pub use consensus::{ConsensusEngine, ConsensusEngineTrait, ConsensusError, ConsensusState};
+pub use deployment::{DeploymentConfig, DeploymentMode, DeploymentPolicy};
 pub use event::{Event, EventHandler, EventHandlerManager, EventManager, EventType};
 pub use evm::*;
+pub use faucet::{Faucet, FaucetConfig};
+pub use fee_market::{FeeSimulation, SyntheticLoad};
+pub use fee_stats::{BlockFeeStats, FeeStatsSummary, FeeStatsTracker};
+pub use gas_limit::GasLimitStatus;
+pub use gas_stats::{GasBySelectorReport, GasStatsTracker, SelectorGas};
 pub use genesis::{FeesConfig, GasLimitConfig, Genesis};
+pub use light::{LightClient, LightClientConfig, LightSource};
+pub use native_multisig::{
+    MultisigConfig, MultisigProposal, NativeMultisigCall, NATIVE_MULTISIG_ADDRESS,
+};
+pub use native_nft::{NativeNftCall, NativeNftPolicy, NATIVE_NFT_ADDRESS};
 pub use network::*;
 pub use nft::NFTContract;
+pub use nonce::{NonceError, NonceMode, NonceSequence};
+pub use oracle::{OracleConfig, PriceOracle, PriceRound, PriceUpdate};
+pub use plugin::{ProtocolError, PROTOCOL_VERSION};
+pub use receipt::{Receipt, ReceiptContext, ReceiptLog};
+pub use staking::{EpochRewards, StakingConfig, ValidatorRewards, ValidatorStake};
 pub use state::*;
+pub use state_proof::{StateCommitment, StateProof};
+pub use state_sync::{SnapshotPeer, SnapshotPolicy};
 pub use storage::*;
+pub use supply::{SupplyStats, SupplyTracker};
 pub use transaction::{Transaction, TransactionType};
+pub use tx_validator::{TxValidator, ValidationContext, ValidationError, ValidationStage};
+pub use txpool::{AddOutcome, TxPool, TxPoolError};
+pub use uptime::ValidatorStats;
+pub use verification::{SenderCache, SignatureVerifier, VerificationError};
+pub use vesting::{VestingSchedule, VestingSchedules};
+pub use webhook::{WebhookConfig, WebhookDispatcher};
 
 use async_trait::async_trait;
 use chrono::Utc;
-use ethers::types::{H256, U256};
+use ethers::types::{H160, H256, U256};
 use fair_vm_core::config::Config;
+use fair_vm_core::params::ChainConfig;
 use fair_vm_core::types::Transaction as CoreTransaction;
 use fair_vm_core::vm::{ExecutionResult, State as StateTrait, Vm};
 use jsonrpc_core::Error;
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -69,6 +142,57 @@ pub enum FairVMError {
     #[error("NFT 错误: {0}")]
     NFTError(String),
 
+    #[error("区块时间戳无效: {0}")]
+    InvalidTimestamp(#[from] blockchain::TimestampError),
+
+    #[error("区块未接在链头之后: {0}")]
+    InvalidLink(#[from] blockchain::LinkError),
+
+    #[error("出块者 {0} 不是活跃验证人")]
+    InactiveProposer(String),
+
+    #[error("区块头出块者 {header:?} 与来源 {peer:?} 不一致")]
+    ProposerMismatch {
+        header: Option<String>,
+        peer: Option<String>,
+    },
+
+    #[error("区块根不匹配: {0}")]
+    RootMismatch(#[from] blockchain::RootMismatch),
+
+    #[error("区块 gas 上限错误: {0}")]
+    GasLimit(#[from] gas_limit::GasLimitError),
+
+    #[error("合约部署许可错误: {0}")]
+    Deployment(#[from] deployment::DeploymentError),
+
+    #[error("锁仓错误: {0}")]
+    Vesting(#[from] vesting::VestingError),
+
+    #[error("检查点同步错误: {0}")]
+    Checkpoint(#[from] checkpoint::CheckpointError),
+
+    #[error("快照同步错误: {0}")]
+    StateSync(#[from] state_sync::StateSyncError),
+
+    #[error("数据目录格式错误: {0}")]
+    Schema(#[from] storage::schema::SchemaError),
+
+    #[error("数据目录与当前插件不兼容: {0}")]
+    Marker(#[from] storage::marker::MarkerError),
+
+    #[error("数据目录不可用: {0}")]
+    Persistence(#[from] storage::PersistenceError),
+
+    #[error("资源限制: {0}")]
+    Resource(#[from] resources::ResourceError),
+
+    #[error("交易校验未通过: {0}")]
+    Validation(#[from] tx_validator::ValidationError),
+
+    #[error("交易顺序校验未通过: {0}")]
+    Fcfs(#[from] consensus::fcfs::FcfsError),
+
     #[error("其他错误: {0}")]
     Other(String),
 }
@@ -101,8 +225,10 @@ impl From<basic::ConsensusState> for consensus::ConsensusState {
 pub struct FairVM {
     /// 状态实例
     state: Arc<RwLock<State>>,
-    /// 存储实例
-    storage: Arc<RwLock<Box<dyn Storage + Send + Sync>>>,
+    /// 状态服务句柄
+    storage: StateHandle,
+    /// 交易池
+    tx_pool: Arc<RwLock<TxPool>>,
     /// 共识引擎
     consensus: Option<Arc<RwLock<dyn ConsensusEngineTrait + Send + Sync>>>,
     /// 事件管理器
@@ -110,50 +236,224 @@ pub struct FairVM {
     /// 事件处理器管理器
     #[allow(dead_code)]
     event_handler_manager: Arc<RwLock<EventHandlerManager>>,
+    /// 区块链
+    blockchain: Arc<RwLock<Blockchain>>,
+    /// 费用统计器
+    fee_stats: Arc<RwLock<FeeStatsTracker>>,
+    /// 按合约和函数选择器的 gas 统计器
+    gas_stats: Arc<RwLock<GasStatsTracker>>,
+    /// 价格预言机
+    oracle: Arc<RwLock<PriceOracle>>,
+    /// 原生 NFT 元数据
+    native_nfts: Arc<RwLock<NFTContract>>,
+    /// 原生 NFT 转移策略
+    native_nft_policy: Arc<RwLock<NativeNftPolicy>>,
+    /// 质押奖励配置，未配置时不分配奖励
+    staking: Arc<RwLock<Option<StakingConfig>>>,
+    /// 区块 gas 上限配置，应用创世配置后启用
+    gas_limit: Arc<RwLock<Option<GasLimitConfig>>>,
+    /// 交易 nonce 模式
+    nonce_mode: NonceMode,
+    /// 创世配置的费用参数，出块时计算基础费用
+    fees: Option<FeesConfig>,
+    /// 创世配置，链上还没有区块时启动节点把创世分配写入状态
+    genesis: Option<Genesis>,
+    /// 合约部署许可，未配置时任何地址都可部署
+    deployment: Option<deployment::DeploymentConfig>,
+    /// 创世账户的锁仓计划
+    vesting: Arc<vesting::VestingSchedules>,
+    log_index: Arc<RwLock<log_index::LogIndex>>,
+    /// 账户转账历史索引
+    account_history: Arc<RwLock<account_history::AccountHistory>>,
+    /// 供应量与持币排行
+    supply: Arc<RwLock<supply::SupplyTracker>>,
+    /// 链运营方注册的交易校验规则
+    tx_validators: Arc<RwLock<tx_validator::TxValidators>>,
+    /// FCFS 排序规则，启用后接受区块时校验交易顺序
+    fcfs: Arc<RwLock<Option<consensus::fcfs::FcfsOrdering>>>,
+    /// RPC 安装的轮询式过滤器
+    log_filters: Arc<RwLock<log_filter::FilterRegistry>>,
+    /// 当前连接的对等节点
+    peers: Arc<RwLock<network::PeerTable>>,
+    /// 最近发布的检查点状态，供其他节点从检查点启动
+    checkpoint: Arc<RwLock<Option<Arc<CheckpointSnapshot>>>>,
+    /// 周期快照策略，为空时不自动冻结快照
+    snapshot_policy: Arc<RwLock<Option<SnapshotPolicy>>>,
+    /// 节点配置
+    config: Config,
+    /// 钱包展示用的网络信息
+    network_metadata: Arc<RwLock<NetworkMetadata>>,
+    /// 区块状态写入的预写日志，未配置数据目录时为空
+    wal: Option<WriteAheadLog>,
+    /// 数据目录访问，写入磁盘的内容都经由它取得路径
+    persistence: storage::Persistence,
     /// 是否正在运行
     is_running: bool,
-    /// 链ID
-    chain_id: u64,
+    /// 链参数
+    chain_config: ChainConfig,
+    /// 签名验证池
+    verifier: SignatureVerifier,
+    /// 交互式调试会话
+    debug_sessions: Arc<RwLock<evm::debugger::DebugSessions>>,
+    /// 已验证合约的源码映射
+    source_maps: Arc<RwLock<evm::source_map::SourceRegistry>>,
+    /// 函数选择器数据库
+    selectors: Arc<RwLock<evm::selectors::SelectorDatabase>>,
+    /// 遥测上报任务，未启用时为空
+    telemetry: Option<tokio::task::JoinHandle<()>>,
+    /// 内存水位保护
+    memory_guard: resources::MemoryGuard,
+    /// 内存水位检查任务，未配置水位时为空
+    resource_monitor: Option<tokio::task::JoinHandle<()>>,
+    /// 本地时钟偏差样本
+    clock: Arc<RwLock<clock::ClockMonitor>>,
+    /// 时钟偏差检查任务，运行时不为空
+    clock_monitor: Option<tokio::task::JoinHandle<()>>,
+    /// 临时模式的自动停止计时，未设置运行时长时为空
+    shutdown_timer: Option<tokio::task::JoinHandle<()>>,
+    /// 自动停止信号，到期后置为 true
+    shutdown: Arc<tokio::sync::watch::Sender<bool>>,
+}
+
+/// 区块执行结果
+struct BlockOutcome {
+    /// 区块生效的 gas 上限，未配置时为 None
+    gas_limit: Option<u64>,
+    /// 区块内交易使用的 gas
+    gas_used: u64,
+    receipts_root: H256,
+    /// 试执行时为计算出的状态根，否则为区块头中的状态根
+    state_root: H256,
 }
 
 impl FairVM {
     /// 创建新的 FairVM 实例
     pub fn new() -> Self {
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-        ));
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = Arc::new(RwLock::new(State::default()));
         let event_manager = Arc::new(RwLock::new(EventManager::default()));
         let event_handler_manager = Arc::new(RwLock::new(EventHandlerManager::default()));
+        let verifier = SignatureVerifier::default();
+        let memory_guard = resources::MemoryGuard::default();
+        memory_guard.register(Arc::new(verifier.sender_cache().clone()));
 
         Self {
             state,
             storage,
+            tx_pool: Arc::new(RwLock::new(TxPool::default())),
             consensus: None,
             event_manager,
             event_handler_manager,
+            blockchain: Arc::new(RwLock::new(Blockchain::default())),
+            fee_stats: Arc::new(RwLock::new(FeeStatsTracker::default())),
+            gas_stats: Arc::new(RwLock::new(GasStatsTracker::default())),
+            oracle: Arc::new(RwLock::new(PriceOracle::default())),
+            native_nfts: Arc::new(RwLock::new(native_nft::native_collection())),
+            native_nft_policy: Arc::new(RwLock::new(NativeNftPolicy::default())),
+            staking: Arc::new(RwLock::new(None)),
+            gas_limit: Arc::new(RwLock::new(None)),
+            nonce_mode: NonceMode::Sequential,
+            fees: None,
+            genesis: None,
+            deployment: None,
+            vesting: Arc::new(vesting::VestingSchedules::default()),
+            log_index: Arc::new(RwLock::new(log_index::LogIndex::default())),
+            account_history: Arc::new(RwLock::new(account_history::AccountHistory::default())),
+            supply: Arc::new(RwLock::new(supply::SupplyTracker::default())),
+            tx_validators: Arc::new(RwLock::new(tx_validator::TxValidators::default())),
+            fcfs: Arc::new(RwLock::new(None)),
+            log_filters: Arc::new(RwLock::new(log_filter::FilterRegistry::default())),
+            peers: Arc::new(RwLock::new(network::PeerTable::default())),
+            checkpoint: Arc::new(RwLock::new(None)),
+            snapshot_policy: Arc::new(RwLock::new(None)),
+            config: Config::default(),
+            network_metadata: Arc::new(RwLock::new(NetworkMetadata::default())),
+            wal: None,
+            persistence: storage::Persistence::Ephemeral,
             is_running: false,
-            chain_id: 1,
+            chain_config: ChainConfig::default(),
+            verifier,
+            debug_sessions: Arc::new(RwLock::new(evm::debugger::DebugSessions::default())),
+            source_maps: Arc::new(RwLock::new(evm::source_map::SourceRegistry::default())),
+            selectors: Arc::new(RwLock::new(evm::selectors::SelectorDatabase::default())),
+            telemetry: None,
+            memory_guard,
+            resource_monitor: None,
+            clock: Arc::new(RwLock::new(clock::ClockMonitor::default())),
+            clock_monitor: None,
+            shutdown_timer: None,
+            shutdown: Arc::new(tokio::sync::watch::channel(false).0),
         }
     }
 
     /// 使用自定义配置创建 FairVM 实例
-    pub fn with_config(_config: Config) -> Self {
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-        ));
+    pub fn with_config(config: Config) -> Self {
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = Arc::new(RwLock::new(State::default()));
         let event_manager = Arc::new(RwLock::new(EventManager::default()));
         let event_handler_manager = Arc::new(RwLock::new(EventHandlerManager::default()));
+        let verifier = SignatureVerifier::new(config.verification_threads).unwrap_or_else(|e| {
+            log::warn!("{}，使用默认签名验证线程数", e);
+            SignatureVerifier::default()
+        });
+        let memory_guard = resources::MemoryGuard::default();
+        memory_guard.register(Arc::new(verifier.sender_cache().clone()));
 
         Self {
             state,
             storage,
+            tx_pool: Arc::new(RwLock::new(TxPool::from_config(&config))),
             consensus: None,
             event_manager,
             event_handler_manager,
+            blockchain: Arc::new(RwLock::new(Blockchain::default())),
+            fee_stats: Arc::new(RwLock::new(FeeStatsTracker::default())),
+            gas_stats: Arc::new(RwLock::new(GasStatsTracker::default())),
+            oracle: Arc::new(RwLock::new(PriceOracle::default())),
+            native_nfts: Arc::new(RwLock::new(native_nft::native_collection())),
+            native_nft_policy: Arc::new(RwLock::new(NativeNftPolicy::default())),
+            staking: Arc::new(RwLock::new(None)),
+            gas_limit: Arc::new(RwLock::new(None)),
+            nonce_mode: NonceMode::Sequential,
+            fees: None,
+            genesis: None,
+            deployment: None,
+            vesting: Arc::new(vesting::VestingSchedules::default()),
+            log_index: Arc::new(RwLock::new(log_index::LogIndex::default())),
+            account_history: Arc::new(RwLock::new(account_history::AccountHistory::default())),
+            supply: Arc::new(RwLock::new(supply::SupplyTracker::default())),
+            tx_validators: Arc::new(RwLock::new(tx_validator::TxValidators::default())),
+            fcfs: Arc::new(RwLock::new(None)),
+            log_filters: Arc::new(RwLock::new(log_filter::FilterRegistry::default())),
+            peers: Arc::new(RwLock::new(network::PeerTable::default())),
+            checkpoint: Arc::new(RwLock::new(None)),
+            snapshot_policy: Arc::new(RwLock::new(None)),
+            wal: storage::Persistence::from_config(&config).wal(),
+            persistence: storage::Persistence::from_config(&config),
+            chain_config: config.chain_config.clone(),
+            clock: Arc::new(RwLock::new(clock::ClockMonitor::new(
+                clock::SkewThresholds::from_policy(&blockchain::TimestampPolicy::from_config(
+                    &config,
+                )),
+            ))),
+            verifier,
+            debug_sessions: Arc::new(RwLock::new(evm::debugger::DebugSessions::default())),
+            source_maps: Arc::new(RwLock::new(evm::source_map::SourceRegistry::default())),
+            selectors: Arc::new(RwLock::new(match &config.selector_lookup_url {
+                Some(url) => evm::selectors::SelectorDatabase::bundled().with_source(Arc::new(
+                    evm::selectors::FourByteDirectory::new(url.clone()),
+                )),
+                None => evm::selectors::SelectorDatabase::bundled(),
+            })),
+            config,
+            network_metadata: Arc::new(RwLock::new(NetworkMetadata::default())),
             is_running: false,
-            chain_id: 1,
+            telemetry: None,
+            memory_guard,
+            resource_monitor: None,
+            clock_monitor: None,
+            shutdown_timer: None,
+            shutdown: Arc::new(tokio::sync::watch::channel(false).0),
         }
     }
 
@@ -162,11 +462,36 @@ impl FairVM {
         self.state.clone()
     }
 
-    /// 获取存储实例
-    pub fn storage(&self) -> Arc<RwLock<Box<dyn Storage + Send + Sync>>> {
+    /// 获取区块链实例
+    pub fn blockchain(&self) -> Arc<RwLock<Blockchain>> {
+        self.blockchain.clone()
+    }
+
+    /// 获取交易池
+    pub fn tx_pool(&self) -> Arc<RwLock<TxPool>> {
+        self.tx_pool.clone()
+    }
+
+    /// 获取对等节点表，网络层在连接变化和收发消息时更新
+    pub fn peers(&self) -> Arc<RwLock<network::PeerTable>> {
+        self.peers.clone()
+    }
+
+    /// 获取时钟偏差样本，网络层在握手时记录对等节点报告的时间
+    pub fn clock(&self) -> Arc<RwLock<clock::ClockMonitor>> {
+        self.clock.clone()
+    }
+
+    /// 获取状态服务句柄
+    pub fn storage(&self) -> StateHandle {
         self.storage.clone()
     }
 
+    /// 获取内存水位保护，缓存和交易池的持有者可以登记自己的数据
+    pub fn memory_guard(&self) -> resources::MemoryGuard {
+        self.memory_guard.clone()
+    }
+
     /// 设置共识引擎
     pub async fn set_consensus(
         &mut self,
@@ -188,6 +513,58 @@ impl FairVM {
         if self.is_running {
             return Err(FairVMError::Other("FairVM 已经在运行".into()));
         }
+        self.config
+            .resources
+            .validate()
+            .map_err(FairVMError::Other)?;
+        self.config
+            .block_producer
+            .validate()
+            .map_err(FairVMError::Other)?;
+        self.config
+            .ephemeral
+            .validate()
+            .map_err(FairVMError::Other)?;
+
+        // 只有使用数据目录的实例写崩溃报告和检查磁盘格式，格式检查必须在重放日志之前完成
+        if let storage::Persistence::Disk(data_dir) = &self.persistence {
+            if self.config.resources.min_open_files > 0 {
+                resources::check_open_files(self.config.resources.min_open_files)?;
+            }
+            crash::install_panic_hook(
+                data_dir.clone(),
+                self.config.clone(),
+                self.blockchain.clone(),
+            );
+            let plan = storage::schema::migrate(data_dir, !self.config.auto_migrate).await?;
+            if !plan.is_empty() {
+                log::info!("数据目录已从版本 {} 迁移到 {}", plan.from, plan.to);
+            }
+            self.take_over_data_dir().await?;
+        }
+
+        if let Some(number) = self.recover_state().await? {
+            log::info!("已从预写日志恢复区块 {} 的状态写入", number);
+        }
+        self.write_genesis_state().await;
+        self.rebuild_supply().await?;
+        self.load_account_history().await?;
+
+        // 启动时用最新区块检查本地时钟，时钟明显落后时后续区块都会因时间戳超前被拒绝
+        let latest_timestamp = self
+            .blockchain
+            .read()
+            .await
+            .latest_block()
+            .map(|block| block.header.timestamp);
+        if let Some(timestamp) = latest_timestamp {
+            self.clock
+                .write()
+                .await
+                .record_block(timestamp, clock::now());
+        }
+        clock::check(&self.clock).await;
+        self.clock_monitor = Some(clock::spawn_monitor(self.clock.clone()));
 
         if let Some(consensus) = &self.consensus {
             consensus.write().await.start().await?;
@@ -206,10 +583,95 @@ impl FairVM {
             }
         }
 
+        if self.config.telemetry.enabled {
+            self.start_telemetry().await?;
+        }
+
+        if self.config.resources.memory_guard_enabled() {
+            self.resource_monitor = Some(
+                self.memory_guard
+                    .spawn_monitor(self.config.resources.clone()),
+            );
+        }
+
+        if let Some(after) = self.config.ephemeral.shutdown_after() {
+            log::info!("临时模式，{} 秒后自动停止", after.as_secs());
+            let shutdown = self.shutdown.clone();
+            self.shutdown_timer = Some(tokio::spawn(async move {
+                tokio::time::sleep(after).await;
+                log::info!("临时模式运行时长已到，请求停止节点");
+                shutdown.send_replace(true);
+            }));
+        }
+
         self.is_running = true;
         Ok(())
     }
 
+    /// 自动停止信号，临时模式的运行时长到期后变为 true，节点进程收到后调用 [`FairVM::stop`]
+    pub fn shutdown_signal(&self) -> tokio::sync::watch::Receiver<bool> {
+        self.shutdown.subscribe()
+    }
+
+    /// 核对数据目录的状态标记，不兼容时拒绝启动，必须在重放预写日志之前完成
+    async fn take_over_data_dir(&self) -> Result<(), FairVMError> {
+        let pending = match &self.wal {
+            Some(wal) => wal
+                .recover()
+                .await
+                .map_err(|e| FairVMError::StateError(e.to_string()))?
+                .map(|record| storage::marker::AcceptedBlock {
+                    number: record.block_number,
+                    hash: record.block_hash,
+                }),
+            None => None,
+        };
+        let data_dir = self.persistence.data_dir("状态标记")?;
+        let local = match storage::marker::StateMarker::load(data_dir).await? {
+            Some(storage::marker::StateMarker {
+                last_accepted: Some(last),
+                ..
+            }) => self
+                .blockchain
+                .read()
+                .await
+                .get_block(last.number)
+                .map(|block| block.hash()),
+            _ => None,
+        };
+        storage::marker::take_over(
+            data_dir,
+            self.chain_config.chain_id,
+            pending.as_ref(),
+            local,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// 启动遥测上报任务
+    async fn start_telemetry(&mut self) -> Result<(), FairVMError> {
+        let config = self.config.telemetry.clone();
+        config.validate().map_err(FairVMError::Other)?;
+        // 临时模式下不保存节点标识，每次启动使用新的标识
+        let node_id = match self.persistence.data_dir("遥测节点标识") {
+            Ok(data_dir) => telemetry::node_id(data_dir)
+                .await
+                .map_err(|e| FairVMError::Other(format!("读取遥测节点标识失败: {}", e)))?,
+            Err(_) => telemetry::random_node_id(),
+        };
+        log::info!("已启用遥测上报: {}", config.endpoint);
+        let reporter = telemetry::TelemetryReporter::new(
+            config,
+            node_id,
+            self.chain_config.chain_id,
+            self.config.peers.len(),
+            self.blockchain.clone(),
+        );
+        self.telemetry = Some(reporter.spawn());
+        Ok(())
+    }
+
     /// 停止 FairVM
     pub async fn stop(&mut self) -> Result<(), FairVMError> {
         if !self.is_running {
@@ -220,6 +682,22 @@ impl FairVM {
             consensus.write().await.stop().await?;
         }
 
+        if let Some(telemetry) = self.telemetry.take() {
+            telemetry.abort();
+        }
+
+        if let Some(monitor) = self.resource_monitor.take() {
+            monitor.abort();
+        }
+
+        if let Some(monitor) = self.clock_monitor.take() {
+            monitor.abort();
+        }
+
+        if let Some(timer) = self.shutdown_timer.take() {
+            timer.abort();
+        }
+
         self.is_running = false;
         Ok(())
     }
@@ -230,6 +708,50 @@ impl FairVM {
         event_manager.add_handler(handler);
     }
 
+    /// 注册交易校验规则，在交易进入交易池和校验区块时执行
+    pub async fn add_tx_validator(&self, validator: Arc<dyn tx_validator::TxValidator>) {
+        self.tx_validators.write().await.add(validator);
+    }
+
+    /// 启用或关闭 FCFS 排序校验
+    pub async fn set_fcfs_ordering(&self, ordering: Option<consensus::fcfs::FcfsOrdering>) {
+        *self.fcfs.write().await = ordering;
+    }
+
+    /// 记录验证者签名的交易到达时间，未启用 FCFS 排序时返回错误
+    pub async fn add_arrival_report(
+        &self,
+        report: consensus::fcfs::ArrivalTimestamp,
+    ) -> Result<(), FairVMError> {
+        match self.fcfs.write().await.as_mut() {
+            Some(ordering) => Ok(ordering.add_report(report)?),
+            None => Err(FairVMError::Other("未启用 FCFS 排序".into())),
+        }
+    }
+
+    /// 按注册的规则校验交易，`block_number` 为交易将被打包或所在的区块高度
+    async fn validate_transactions(
+        &self,
+        transactions: &[Transaction],
+        stage: tx_validator::ValidationStage,
+        block_number: u64,
+    ) -> Result<(), FairVMError> {
+        let validators = self.tx_validators.read().await.clone();
+        if validators.is_empty() {
+            return Ok(());
+        }
+        let state = self.state.read().await;
+        let context = tx_validator::ValidationContext {
+            stage,
+            block_number,
+            storage: state.storage(),
+        };
+        for tx in transactions {
+            validators.validate(tx, &context).await?;
+        }
+        Ok(())
+    }
+
     /// 移除事件处理器
     pub async fn remove_event_handler(&self, index: usize) {
         let mut event_manager = self.event_manager.write().await;
@@ -242,6 +764,18 @@ impl FairVM {
         event_manager.publish(event).map_err(FairVMError::Other)
     }
 
+    /// 发布交易与区块流水线事件，没有订阅者时忽略
+    async fn emit_event(&self, event_type: EventType, data: serde_json::Value) {
+        let event = Event {
+            event_type,
+            timestamp: Utc::now(),
+            data,
+        };
+        if let Err(e) = self.publish_event(event).await {
+            log::debug!("事件未送达订阅者: {}", e);
+        }
+    }
+
     /// 启动事件处理
     pub async fn start_event_handling(&self) {
         let event_manager = self.event_manager.read().await;
@@ -255,211 +789,1748 @@ impl FairVM {
         });
     }
 
-    /// 提交交易
-    pub async fn submit_transaction(&self, tx: Transaction) -> Result<(), FairVMError> {
-        if !self.is_running {
-            return Err(FairVMError::Other("FairVM 未运行".into()));
-        }
+    /// 区块导入时更新费用统计
+    pub async fn record_block_fees(
+        &self,
+        block: &blockchain::Block,
+        base_fee: U256,
+        gas_used: u64,
+        gas_limit: u64,
+    ) {
+        let mut fee_stats = self.fee_stats.write().await;
+        fee_stats.record_block(block, base_fee, gas_used, gas_limit);
+    }
 
-        let tx_type = tx.transaction_type;
+    /// 根据创世配置启用价格预言机
+    pub async fn set_oracle_config(&self, config: OracleConfig) {
+        *self.oracle.write().await = PriceOracle::new(config);
+    }
 
-        if let Some(consensus) = &self.consensus {
-            let consensus_tx = ConsensusTransaction {
-                hash: H256(tx.hash.0),
-                from: tx.from,
-                to: tx.to,
-                value: tx.value,
-                nonce: tx.nonce,
-                gas_limit: tx.gas_limit,
-                gas_price: tx.gas_price,
-                data: tx.data,
-                signature: tx.signature,
-                transaction_type: tx_type,
-                chain_id: tx.chain_id,
-                max_fee_per_gas: tx.max_fee_per_gas,
-                max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
-            };
-            consensus
-                .write()
-                .await
-                .submit_transaction(consensus_tx)
-                .await?;
-            Ok(())
-        } else {
-            Err(FairVMError::Other("未设置共识引擎".into()))
-        }
+    /// 设置原生 NFT 转移策略，供治理调整，从下一个区块起生效
+    pub async fn set_native_nft_policy(&self, policy: NativeNftPolicy) {
+        *self.native_nft_policy.write().await = policy;
     }
 
-    /// 获取账户信息
-    pub async fn get_account(&self, address: &account::Address) -> Option<Account> {
-        let state = self.state.read().await;
-        state.get_account(address).await
+    /// 当前原生 NFT 转移策略
+    pub async fn native_nft_policy(&self) -> NativeNftPolicy {
+        self.native_nft_policy.read().await.clone()
     }
 
-    /// 获取NFT合约信息
-    pub async fn get_nft_contract(&self, _address: &account::Address) -> Option<NFTContract> {
-        None // TODO: 实现NFT合约查询
+    /// 设置钱包展示用的网络信息
+    pub async fn set_network_metadata(&self, metadata: NetworkMetadata) {
+        *self.network_metadata.write().await = metadata;
     }
 
-    /// 获取共识状态
-    pub async fn get_consensus_state(&self) -> Result<ConsensusState, FairVMError> {
-        if let Some(consensus) = &self.consensus {
-            consensus
-                .read()
-                .await
-                .get_consensus_state()
-                .await
-                .map(|state| state.into())
-                .map_err(FairVMError::from)
-        } else {
-            Err(FairVMError::Other("未设置共识引擎".into()))
-        }
+    /// 签名验证池，克隆后与交易池准入和区块校验共用发送方缓存
+    pub fn signature_verifier(&self) -> SignatureVerifier {
+        self.verifier.clone()
     }
 
-    /// 获取账户nonce
-    pub async fn get_nonce(&self, address: account::Address) -> Result<u64, FairVMError> {
-        let state = self.state.read().await;
-        let account = state.get_account(&address).await;
-        Ok(account.map_or(0, |acc| acc.nonce))
+    /// 链 ID
+    pub fn chain_id(&self) -> u64 {
+        self.chain_config.chain_id
     }
 
-    /// 创建新交易
-    pub async fn create_transaction(
-        &self,
-        from: account::Address,
-        to: Option<account::Address>,
-        value: U256,
-        data: Vec<u8>,
-    ) -> Result<Transaction, FairVMError> {
-        let nonce = self.get_nonce(from).await?;
-        let gas_limit = U256::from(21000); // 基本 gas 限制
-        let gas_price = U256::from(1); // 基本 gas 价格
-        let chain_id = self.chain_id;
+    /// 链参数
+    pub fn chain_config(&self) -> &ChainConfig {
+        &self.chain_config
+    }
 
-        let transaction = Transaction::new(
-            H256::zero(), // 临时哈希，将在签名后更新
-            from,
-            to,
-            value,
-            nonce,
-            gas_limit.as_u64(),
-            Some(gas_price),
-            data,
-            Vec::new(), // 临时签名
-            TransactionType::Legacy,
-            chain_id,
-            None,
-            None,
-        );
+    /// 使用创世配置中的链参数、价格预言机、原生 NFT 转移策略、质押奖励配置、费用参数、nonce 模式、合约部署许可、锁仓计划和创世供应量
+    pub fn apply_genesis(&mut self, genesis: &Genesis) -> Result<(), FairVMError> {
+        let chain_config = genesis.chain_config();
+        chain_config.validate().map_err(FairVMError::Other)?;
+        self.config.chain_config = chain_config.clone();
+        self.chain_config = chain_config;
+        if let Some(config) = &genesis.oracle {
+            self.oracle = Arc::new(RwLock::new(PriceOracle::new(config.clone())));
+        }
+        if let Some(policy) = &genesis.native_nft {
+            self.native_nft_policy = Arc::new(RwLock::new(policy.clone()));
+        }
+        if let Some(staking) = &genesis.staking {
+            staking
+                .validate()
+                .map_err(|e| FairVMError::Other(e.to_string()))?;
+            self.staking = Arc::new(RwLock::new(Some(staking.clone())));
+        }
+        genesis
+            .gas_limit
+            .validate()
+            .map_err(|e| FairVMError::Other(e.to_string()))?;
+        self.gas_limit = Arc::new(RwLock::new(Some(genesis.gas_limit.clone())));
+        self.nonce_mode = genesis.nonce_mode;
+        self.fees = Some(genesis.fees.clone());
+        self.vesting = Arc::new(vesting::VestingSchedules::from_genesis(genesis)?);
+        self.genesis = Some(genesis.clone());
+        if let Some(config) = &genesis.deployment {
+            self.tx_validators
+                .try_write()
+                .map_err(|_| FairVMError::Other("交易校验规则正在使用".into()))?
+                .add(Arc::new(deployment::DeploymentPolicy::new(config.clone())));
+            self.deployment = Some(config.clone());
+        }
+        self.tx_pool = Arc::new(RwLock::new(
+            TxPool::from_config(&self.config).with_nonce_mode(genesis.nonce_mode),
+        ));
+        Ok(())
+    }
 
-        Ok(transaction)
+    /// 生成 EIP-3085 链元数据
+    pub async fn chain_metadata(&self) -> ChainMetadata {
+        let metadata = self.network_metadata.read().await;
+        ChainMetadata::from_config(self.chain_id(), &self.config, &metadata)
     }
-}
 
-impl Default for FairVM {
-    fn default() -> Self {
-        Self::new()
+    /// 提交原始签名交易，在验证池中恢复发送方后进入交易池
+    pub async fn submit_raw_transaction(&self, raw: &[u8]) -> Result<H256, FairVMError> {
+        let mut tx = Transaction::from_raw(raw).map_err(FairVMError::TransactionError)?;
+        tx.from = self
+            .verifier
+            .recover_sender(tx.clone())
+            .await
+            .map_err(|e| FairVMError::TransactionError(e.to_string()))?;
+        let hash = tx.hash;
+        self.admit_transaction(tx).await?;
+        Ok(hash)
     }
-}
 
-pub type ConsensusTransaction = Transaction;
+    /// 提交交易
+    pub async fn submit_transaction(&self, tx: Transaction) -> Result<(), FairVMError> {
+        let sender = self
+            .verifier
+            .recover_sender(tx.clone())
+            .await
+            .map_err(|e| FairVMError::TransactionError(e.to_string()))?;
+        if sender != tx.from {
+            return Err(FairVMError::TransactionError(format!(
+                "交易签名者 {} 与发送方 {} 不匹配",
+                sender, tx.from
+            )));
+        }
+        self.admit_transaction(tx).await
+    }
 
-#[async_trait]
-impl Vm for FairVM {
-    async fn execute_transaction(
-        &self,
-        transaction: &CoreTransaction,
-        _state: &dyn StateTrait,
-    ) -> Result<ExecutionResult, Box<dyn std::error::Error>> {
-        // 将 CoreTransaction 转换为内部 Transaction 类型
-        let _tx = Transaction {
-            hash: H256(transaction.hash.0.into()),
-            from: Address(transaction.from.0.into()),
-            to: transaction.to.map(|addr| Address(addr.0.into())),
-            value: transaction.value,
-            nonce: transaction.nonce,
-            gas_limit: transaction.gas_limit,
-            gas_price: Some(transaction.gas_price),
-            data: transaction.data.clone(),
-            signature: vec![], // 暂时为空
-            transaction_type: transaction::TransactionType::Legacy,
-            chain_id: self.chain_id,
-            max_fee_per_gas: None,
-            max_priority_fee_per_gas: None,
+    /// 将已验证签名的交易送入交易池
+    async fn admit_transaction(&self, tx: Transaction) -> Result<(), FairVMError> {
+        if !self.is_running {
+            return Err(FairVMError::Other("FairVM 未运行".into()));
+        }
+
+        if tx.chain_id != self.chain_id() {
+            return Err(FairVMError::TransactionError(format!(
+                "链 ID 不匹配: 期望 {}, 实际 {}",
+                self.chain_id(),
+                tx.chain_id
+            )));
+        }
+
+        let (latest_number, latest_timestamp) = self
+            .blockchain
+            .read()
+            .await
+            .latest_block()
+            .map_or((0, 0), |block| {
+                (block.header.number, block.header.timestamp)
+            });
+        let next_number = latest_number + 1;
+        if !self.vesting.is_empty() {
+            // 锁定金额只随时间减少，按最新区块时间检查不会放过超额交易
+            let balance = self.state.read().await.get_balance(&tx.from).await;
+            let fee_cap = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+            let required = tx
+                .value
+                .saturating_add(U256::from(tx.gas_limit).saturating_mul(fee_cap));
+            self.vesting
+                .check(&tx.from, balance, required, latest_timestamp)?;
+        }
+        self.validate_transactions(
+            std::slice::from_ref(&tx),
+            tx_validator::ValidationStage::Mempool,
+            next_number,
+        )
+        .await?;
+
+        // 预言机状态只在区块执行时更新，准入时仅检查价格更新的格式和签名
+        if oracle::is_oracle_transaction(&tx) {
+            PriceUpdate::from_transaction(&tx)
+                .and_then(|update| update.verify())
+                .map_err(|e| FairVMError::TransactionError(e.to_string()))?;
+        }
+
+        let tx_hash = tx.hash;
+        self.emit_event(
+            EventType::TransactionReceived {
+                hash: tx_hash,
+                from: tx.from,
+            },
+            json!({}),
+        )
+        .await;
+
+        let Some(consensus) = &self.consensus else {
+            self.emit_event(
+                EventType::TransactionDropped {
+                    hash: tx_hash,
+                    reason: "未设置共识引擎".to_string(),
+                },
+                json!({}),
+            )
+            .await;
+            return Err(FairVMError::Other("未设置共识引擎".into()));
         };
 
-        // TODO: 实现实际的交易执行逻辑
-        Ok(ExecutionResult {
-            gas_used: 0,
-            return_data: vec![],
-            status: true,
-        })
-    }
-}
+        // 交易先进入交易池，nonce 连续后才交给共识引擎
+        let account_nonce = {
+            let state = self.state.read().await;
+            nonce::expected_nonce(&*state, self.nonce_mode, &tx.from, tx.nonce).await
+        };
+        let outcome = match self.tx_pool.write().await.add(tx, account_nonce) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.emit_event(
+                    EventType::TransactionDropped {
+                        hash: tx_hash,
+                        reason: e.to_string(),
+                    },
+                    json!({}),
+                )
+                .await;
+                return Err(FairVMError::TransactionError(e.to_string()));
+            }
+        };
 
-#[async_trait]
-impl VmExt for FairVM {
-    async fn get_state(&self) -> Arc<RwLock<State>> {
-        self.state.clone()
-    }
+        if let Some(replaced) = outcome.replaced {
+            self.emit_event(
+                EventType::TransactionDropped {
+                    hash: replaced.hash,
+                    reason: format!("被交易 {:?} 替换", tx_hash),
+                },
+                json!({}),
+            )
+            .await;
+        }
 
-    async fn get_storage_arc(&self) -> Arc<RwLock<Box<dyn Storage + Send + Sync>>> {
-        self.storage.clone()
+        // 提交失败的交易移出交易池，同一通道中依赖它的后续交易留在池中排队，等待缺口补齐
+        let mut result = Ok(());
+        let mut blocked = HashSet::new();
+        for consensus_tx in outcome.promoted {
+            let hash = consensus_tx.hash;
+            let lane = (consensus_tx.from, self.nonce_mode.lane(consensus_tx.nonce));
+            if blocked.contains(&lane) {
+                continue;
+            }
+            let submitted = consensus
+                .write()
+                .await
+                .submit_transaction(consensus_tx)
+                .await;
+            if let Err(e) = submitted {
+                self.tx_pool.write().await.remove(&hash);
+                blocked.insert(lane);
+                self.emit_event(
+                    EventType::TransactionDropped {
+                        hash,
+                        reason: e.to_string(),
+                    },
+                    json!({}),
+                )
+                .await;
+                if hash == tx_hash {
+                    result = Err(e.into());
+                } else {
+                    log::warn!("提交交易 {:?} 失败: {}", hash, e);
+                }
+            }
+        }
+        result
     }
 
-    async fn get_storage(
+    /// 执行区块中合约交易的 EVM 环境
+    async fn evm_environment(
         &self,
-        address: &ethers::types::H160,
-        key: &ethers::types::H256,
-    ) -> Result<ethers::types::H256, Error> {
-        let state = self.state.read().await;
-        let storage = state.storage().read().await;
-        let value = storage.get_storage_value(&Address(address.0), key.0).await;
-        Ok(ethers::types::H256(value))
+        block: &blockchain::Block,
+        base_fee: U256,
+        gas_limit: Option<u64>,
+    ) -> evm::Environment {
+        let number = block.header.number;
+        let blockchain = self.blockchain.read().await;
+        let block_hashes = (number.saturating_sub(256)..number)
+            .filter_map(|n| blockchain.get_block(n).map(|ancestor| (n, ancestor.hash())))
+            .collect();
+        evm::Environment {
+            chain_id: self.chain_id(),
+            base_fee,
+            block: evm::EvmContext {
+                timestamp: block.header.timestamp,
+                block_number: number,
+                difficulty: U256::from(block.header.difficulty),
+                miner: H160::zero(),
+                gas_limit: gas_limit.unwrap_or(self.config.gas_limit),
+            },
+            block_hashes: Arc::new(block_hashes),
+            deployment: self.deployment.clone(),
+            vesting: self.vesting.clone(),
+            ..evm::Environment::default()
+        }
     }
 
-    async fn get_consensus(&self) -> Option<Arc<RwLock<dyn ConsensusEngineTrait + Send + Sync>>> {
-        self.consensus.clone()
+    /// 区块 `number` 执行时的 EVM 环境，基础费用和 gas 上限取自记录的区块费用统计
+    pub async fn block_environment(&self, number: u64) -> Option<evm::Environment> {
+        let block = self.blockchain.read().await.get_block(number).cloned()?;
+        let fees = self.fee_stats.read().await.get(number).cloned();
+        let base_fee = fees.as_ref().map_or_else(U256::zero, |fees| fees.base_fee);
+        let gas_limit = fees.map(|fees| fees.gas_limit);
+        Some(self.evm_environment(&block, base_fee, gas_limit).await)
     }
 
-    async fn get_account(&self, address: &account::Address) -> Option<Account> {
+    /// 执行区块内的交易并生成收据
+    ///
+    /// 交易在一批写入内执行，全部完成后经预写日志一次性提交，崩溃或执行失败时不会留下半个区块的状态。
+    /// `seal` 为真时是出块前的试执行：不校验区块头中的根，算出收据根和状态根后丢弃全部修改。
+    async fn execute_block(
+        &self,
+        block: &blockchain::Block,
+        base_fee: U256,
+        seal: bool,
+    ) -> Result<BlockOutcome, FairVMError> {
         let state = self.state.read().await;
-        state.get_account(address).await
+        let batch = StorageBatch::begin(state.storage().clone());
+        let staged = State::new(batch.storage(), state.context().clone());
+        // 丢弃执行前其他读取留下的失败，执行结束后只检查本区块的读取
+        staged.storage().take_read_error().await;
+
+        let gas_limit_config = self.gas_limit.read().await.clone();
+        let block_gas_limit = match &gas_limit_config {
+            Some(config) => {
+                let mut storage = staged.storage().clone();
+                match gas_limit::apply_block(&mut storage, config, &block.transactions).await {
+                    Ok(limit) => Some(limit),
+                    Err(e) => {
+                        batch.rollback().await;
+                        return Err(e.into());
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut context = ReceiptContext {
+            block_hash: block.hash(),
+            block_number: block.header.number,
+            transaction_index: 0,
+            base_fee,
+            cumulative_gas_before: 0,
+            log_index_before: 0,
+        };
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        let mut transfers = Vec::with_capacity(block.transactions.len());
+        // 领取质押奖励新增的余额，计算销毁量时不算作转入
+        let mut minted = U256::zero();
+
+        let mut nft_effects = Vec::new();
+        let nft_policy = self.native_nft_policy.read().await.clone();
+        let price_oracle = self.oracle.read().await.clone();
+        // 区块中出现合约交易时才创建 EVM 环境
+        let mut evm_env = None;
+
+        for (index, tx) in block.transactions.iter().enumerate() {
+            context.transaction_index = index as u64;
+            let nonce_result = {
+                let mut storage = staged.storage().clone();
+                nonce::advance(&mut storage, self.nonce_mode, tx).await
+            };
+            let (result, logs) = if let Err(e) = nonce_result {
+                log::warn!("交易 {:?} 的 nonce 无效: {}", tx.hash, e);
+                (
+                    ExecutionResult {
+                        gas_used: 0,
+                        return_data: Vec::new(),
+                        status: false,
+                        gas_refunded: 0,
+                    },
+                    Vec::new(),
+                )
+            } else if native_nft::is_native_nft_transaction(tx) {
+                let mut storage = staged.storage().clone();
+                let gas_used = tx.gas_limit.min(native_nft::NATIVE_NFT_GAS);
+                match native_nft::execute(&mut storage, tx, &nft_policy).await {
+                    Ok(effect) => {
+                        let logs = effect.logs();
+                        nft_effects.push(effect);
+                        (
+                            ExecutionResult {
+                                gas_used,
+                                return_data: Vec::new(),
+                                status: true,
+                                gas_refunded: 0,
+                            },
+                            logs,
+                        )
+                    }
+                    Err(e) => {
+                        log::warn!("原生 NFT 交易 {:?} 执行失败: {}", tx.hash, e);
+                        (
+                            ExecutionResult {
+                                gas_used,
+                                return_data: Vec::new(),
+                                status: false,
+                                gas_refunded: 0,
+                            },
+                            Vec::new(),
+                        )
+                    }
+                }
+            } else if native_multisig::is_native_multisig_transaction(tx) {
+                let mut storage = staged.storage().clone();
+                let gas_used = tx.gas_limit.min(native_multisig::NATIVE_MULTISIG_GAS);
+                match native_multisig::execute(&mut storage, tx, self.chain_id()).await {
+                    Ok(logs) => (
+                        ExecutionResult {
+                            gas_used,
+                            return_data: Vec::new(),
+                            status: true,
+                            gas_refunded: 0,
+                        },
+                        logs,
+                    ),
+                    Err(e) => {
+                        log::warn!("原生多签交易 {:?} 执行失败: {}", tx.hash, e);
+                        (
+                            ExecutionResult {
+                                gas_used,
+                                return_data: Vec::new(),
+                                status: false,
+                                gas_refunded: 0,
+                            },
+                            Vec::new(),
+                        )
+                    }
+                }
+            } else if oracle::is_oracle_transaction(tx) {
+                let mut storage = staged.storage().clone();
+                let gas_used = tx.gas_limit.min(oracle::ORACLE_TX_GAS_LIMIT);
+                let status = match price_oracle.apply_transaction(&mut storage, tx).await {
+                    Ok(_) => true,
+                    Err(e) => {
+                        log::warn!("预言机交易 {:?} 执行失败: {}", tx.hash, e);
+                        false
+                    }
+                };
+                (
+                    ExecutionResult {
+                        gas_used,
+                        return_data: Vec::new(),
+                        status,
+                        gas_refunded: 0,
+                    },
+                    Vec::new(),
+                )
+            } else if gas_limit::is_gas_limit_transaction(tx) {
+                let mut storage = staged.storage().clone();
+                let gas_used = tx.gas_limit.min(gas_limit::GAS_LIMIT_VOTE_GAS);
+                let result = match &gas_limit_config {
+                    Some(config) => gas_limit::set_target(&mut storage, config, tx).await,
+                    None => Err(gas_limit::GasLimitError::InvalidConfig(
+                        "未配置区块 gas 上限".into(),
+                    )),
+                };
+                match result {
+                    Ok(logs) => (
+                        ExecutionResult {
+                            gas_used,
+                            return_data: Vec::new(),
+                            status: true,
+                            gas_refunded: 0,
+                        },
+                        logs,
+                    ),
+                    Err(e) => {
+                        log::warn!("gas 上限治理交易 {:?} 执行失败: {}", tx.hash, e);
+                        (
+                            ExecutionResult {
+                                gas_used,
+                                return_data: Vec::new(),
+                                status: false,
+                                gas_refunded: 0,
+                            },
+                            Vec::new(),
+                        )
+                    }
+                }
+            } else if deployment::is_deployment_transaction(tx) {
+                let mut storage = staged.storage().clone();
+                let gas_used = tx.gas_limit.min(deployment::SET_DEPLOYER_GAS);
+                let result = match &self.deployment {
+                    Some(config) => deployment::set_deployer(&mut storage, config, tx).await,
+                    None => Err(deployment::DeploymentError::InvalidTransaction(
+                        "未配置合约部署许可".into(),
+                    )),
+                };
+                match result {
+                    Ok(logs) => (
+                        ExecutionResult {
+                            gas_used,
+                            return_data: Vec::new(),
+                            status: true,
+                            gas_refunded: 0,
+                        },
+                        logs,
+                    ),
+                    Err(e) => {
+                        log::warn!("部署许可治理交易 {:?} 执行失败: {}", tx.hash, e);
+                        (
+                            ExecutionResult {
+                                gas_used,
+                                return_data: Vec::new(),
+                                status: false,
+                                gas_refunded: 0,
+                            },
+                            Vec::new(),
+                        )
+                    }
+                }
+            } else if staking::is_staking_transaction(tx) {
+                let mut storage = staged.storage().clone();
+                let gas_used = tx.gas_limit.min(staking::STAKING_CLAIM_GAS);
+                let result = if jailing::is_unjail_call(tx) {
+                    match self.staking.read().await.as_ref() {
+                        Some(config) => {
+                            jailing::unjail(&mut storage, config, block.header.number, tx).await
+                        }
+                        None => Err(staking::StakingError::InvalidConfig("未启用质押".into())),
+                    }
+                } else {
+                    let balance = storage.get_balance(&tx.from).await;
+                    let result = staking::claim(&mut storage, tx).await;
+                    let claimed = storage.get_balance(&tx.from).await.saturating_sub(balance);
+                    minted = minted.saturating_add(claimed);
+                    result
+                };
+                match result {
+                    Ok(logs) => (
+                        ExecutionResult {
+                            gas_used,
+                            return_data: Vec::new(),
+                            status: true,
+                            gas_refunded: 0,
+                        },
+                        logs,
+                    ),
+                    Err(e) => {
+                        log::warn!("质押交易 {:?} 执行失败: {}", tx.hash, e);
+                        (
+                            ExecutionResult {
+                                gas_used,
+                                return_data: Vec::new(),
+                                status: false,
+                                gas_refunded: 0,
+                            },
+                            Vec::new(),
+                        )
+                    }
+                }
+            } else if evm::executor::is_contract_transaction(staged.storage(), tx).await {
+                if evm_env.is_none() {
+                    evm_env = Some(self.evm_environment(block, base_fee, block_gas_limit).await);
+                }
+                let env = evm_env.as_ref().expect("已创建 EVM 环境");
+                let mut storage = staged.storage().clone();
+                let (result, logs) = evm::executor::execute(&mut storage, tx, env).await;
+                if let Some(reason) = result.revert_reason() {
+                    log::debug!("交易 {:?} 回滚: {}", tx.hash, reason);
+                }
+                (result, logs)
+            } else {
+                let core_tx = api::convert_to_core_transaction(tx);
+                let result = match self.execute_transaction(&core_tx, &staged).await {
+                    Ok(result) => result,
+                    Err(e) => {
+                        log::warn!("交易 {:?} 执行失败: {}", tx.hash, e);
+                        ExecutionResult {
+                            gas_used: tx.gas_limit,
+                            return_data: Vec::new(),
+                            status: false,
+                            gas_refunded: 0,
+                        }
+                    }
+                };
+                (result, Vec::new())
+            };
+
+            let receipt = Receipt::from_execution(tx, &result, &context, logs, 0);
+            context.cumulative_gas_before = receipt.cumulative_gas_used;
+            context.log_index_before += receipt.logs.len() as u64;
+            let transfer = AccountTransfer::new(tx, &receipt, block.header.timestamp);
+            for party in transfer.parties() {
+                let balance = staged.get_balance(&Address(party.0)).await;
+                transfers.push((
+                    party,
+                    AccountTransfer {
+                        balance,
+                        ..transfer.clone()
+                    },
+                ));
+            }
+            receipts.push(receipt);
+        }
+
+        let jailed = self.record_uptime(block, &staged).await;
+        self.apply_fcfs_penalties(block, &staged).await;
+        let epoch_rewards = self.distribute_epoch_rewards(block, &staged).await;
+        // 销毁量为执行实际从余额中扣除且未转入其他账户的金额
+        let (debited, credited) = batch.balance_changes().await;
+        let burned = debited.saturating_sub(credited.saturating_sub(minted));
+        supply::record_burn(&mut staged.storage().clone(), burned).await;
+
+        // 状态根需要遍历全部账户，只在区块头承诺了状态根或试执行时计算
+        let receipts_root = merkle::receipts_root(&receipts);
+        let gas_used = receipts
+            .last()
+            .map_or(0, |receipt| receipt.cumulative_gas_used);
+        let required = self.chain_config.requires_roots(block.header.number);
+        let state_root = if seal || required || !block.header.state_root.is_zero() {
+            Some(StateCommitment::from_storage(&staged).await.root())
+        } else {
+            None
+        };
+        // 按需下载的状态读取失败时读到的是默认值，执行结果不可信
+        if let Some(e) = staged.storage().take_read_error().await {
+            batch.rollback().await;
+            return Err(FairVMError::StateError(format!("读取状态失败: {}", e)));
+        }
+        if seal {
+            batch.rollback().await;
+            return Ok(BlockOutcome {
+                gas_limit: block_gas_limit,
+                gas_used,
+                receipts_root,
+                state_root: state_root.unwrap_or_default(),
+            });
+        }
+        let mut roots = vec![(blockchain::RootKind::Receipts, receipts_root)];
+        roots.extend(state_root.map(|root| (blockchain::RootKind::State, root)));
+        for (kind, computed) in roots {
+            if let Err(e) = block.header.verify_root(kind, computed, required) {
+                batch.rollback().await;
+                return Err(e.into());
+            }
+        }
+
+        // 收据与状态写入一起经预写日志提交，区块应用后即可按交易哈希和区块高度查询
+        let mut ops = batch.into_ops().await;
+        let balances: Vec<(H160, U256)> = ops
+            .iter()
+            .filter_map(|op| match op {
+                WalOp::SetAccount(account) => Some((H160(account.address.0), account.balance)),
+                _ => None,
+            })
+            .collect();
+        ops.extend(receipts.iter().cloned().map(WalOp::SetReceipt));
+        let record = WalRecord {
+            block_number: block.header.number,
+            block_hash: context.block_hash,
+            ops,
+        };
+        // 转账历史先于状态写入日志，加载时丢弃状态未提交的区块
+        self.account_history
+            .read()
+            .await
+            .append_journal(block.header.number, &transfers)
+            .await
+            .map_err(|e| FairVMError::StateError(format!("写入转账历史失败: {}", e)))?;
+        self.commit_state(&state, &record).await?;
+
+        let mut native_nfts = self.native_nfts.write().await;
+        for effect in nft_effects {
+            if let Err(e) = effect.apply(&mut native_nfts) {
+                log::warn!("更新原生 NFT 元数据失败: {}", e);
+            }
+        }
+
+        if let Some(node_id) = jailed {
+            self.emit_event(
+                EventType::ValidatorJailed {
+                    node_id: node_id.clone(),
+                    at: block.header.number,
+                },
+                json!({ "nodeId": node_id, "at": block.header.number }),
+            )
+            .await;
+        }
+
+        if let Some(rewards) = epoch_rewards {
+            self.emit_event(
+                EventType::EpochRewards {
+                    epoch: rewards.epoch,
+                    total: rewards.total(),
+                },
+                serde_json::to_value(&rewards).unwrap_or_default(),
+            )
+            .await;
+        }
+
+        self.gas_stats.write().await.record_block(
+            block.header.number,
+            block.transactions.iter().zip(receipts.iter()),
+        );
+
+        let bloom = log_index::logs_bloom(receipts.iter().flat_map(|receipt| {
+            receipt
+                .logs
+                .iter()
+                .map(|log| (H160(log.address.0), log.topics.as_slice()))
+        }));
+        self.log_index
+            .write()
+            .await
+            .record_block(block.header.number, bloom);
+
+        self.account_history.write().await.record_block(transfers);
+        self.supply
+            .write()
+            .await
+            .record_block(block.header.number, burned, balances);
+        Ok(BlockOutcome {
+            gas_limit: block_gas_limit,
+            gas_used,
+            receipts_root,
+            state_root: block.header.state_root,
+        })
+    }
+
+    /// 启用质押时区块头记录的出块者须为活跃验证人，避免以任意名义出块使预定出块者被记为错过并监禁
+    async fn check_proposer(&self, header: &blockchain::BlockHeader) -> Result<(), FairVMError> {
+        let (Some(config), Some(proposer)) = (self.staking.read().await.clone(), &header.proposer)
+        else {
+            return Ok(());
+        };
+        let storage = self.state.read().await.storage().clone();
+        if uptime::active_validators(&storage, &config)
+            .await
+            .iter()
+            .any(|v| &v.node_id == proposer)
+        {
+            return Ok(());
+        }
+        Err(FairVMError::InactiveProposer(proposer.clone()))
+    }
+
+    /// 按区块头记录的出块者更新验证人出块统计，出块者未知时不计入；返回本区块被监禁的验证人
+    async fn record_uptime(&self, block: &blockchain::Block, staged: &State) -> Option<String> {
+        let config = self.staking.read().await.clone()?;
+        let proposer = block.header.proposer.as_deref()?;
+        let number = block.header.number;
+        let mut storage = staged.storage().clone();
+        let missed = uptime::record_block(&mut storage, &config, number, proposer).await?;
+        jailing::evaluate(&mut storage, &config, &missed, number)
+            .await
+            .then_some(missed)
+    }
+
+    /// 启用 FCFS 排序时按区块携带的冲突报告在状态中惩罚操纵到达时间的验证者
+    async fn apply_fcfs_penalties(&self, block: &blockchain::Block, staged: &State) {
+        let evidence = consensus::fcfs::conflicts(&block.arrivals);
+        if evidence.is_empty() {
+            return;
+        }
+        let Some(params) = self
+            .fcfs
+            .read()
+            .await
+            .as_ref()
+            .map(|ordering| ordering.params().clone())
+        else {
+            return;
+        };
+        let mut storage = staged.storage().clone();
+        consensus::fcfs::apply_penalties(&mut storage, &params, &evidence).await;
+    }
+
+    /// 纪元的最后一个区块执行完交易后，按纪元内区块头记录的出块者将奖励记入待领取金额
+    async fn distribute_epoch_rewards(
+        &self,
+        block: &blockchain::Block,
+        staged: &State,
+    ) -> Option<EpochRewards> {
+        let config = self.staking.read().await.clone()?;
+        let number = block.header.number;
+        if !config.is_epoch_end(number) {
+            return None;
+        }
+        let epoch = config.epoch_of(number);
+        let (from, to) = config.epoch_range(epoch);
+
+        let mut proposals: HashMap<String, u64> = HashMap::new();
+        {
+            let blockchain = self.blockchain.read().await;
+            let epoch_blocks = blockchain
+                .blocks()
+                .iter()
+                .filter(|b| (from..to).contains(&b.header.number))
+                .chain(std::iter::once(block));
+            for proposer in epoch_blocks.filter_map(|b| b.header.proposer.clone()) {
+                *proposals.entry(proposer).or_default() += 1;
+            }
+        }
+
+        let rewards = config.epoch_rewards(epoch, &proposals);
+        let mut storage = staged.storage().clone();
+        staking::credit(&mut storage, &rewards).await;
+        Some(rewards)
+    }
+
+    /// 写入预写日志并落盘后应用到存储，应用完成后清空日志
+    async fn commit_state(&self, state: &State, record: &WalRecord) -> Result<(), FairVMError> {
+        if let Some(wal) = &self.wal {
+            wal.append(record)
+                .await
+                .map_err(|e| FairVMError::StateError(e.to_string()))?;
+        }
+        // 整批写入在状态服务的一次请求内完成，读取方不会看到应用了一半的区块
+        state.storage().apply(record.ops.clone()).await;
+        if let Some(wal) = &self.wal {
+            wal.clear()
+                .await
+                .map_err(|e| FairVMError::StateError(e.to_string()))?;
+            let last_accepted = storage::marker::AcceptedBlock {
+                number: record.block_number,
+                hash: record.block_hash,
+            };
+            storage::marker::StateMarker::new(self.chain_config.chain_id, Some(last_accepted))
+                .store(self.persistence.data_dir("状态标记")?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 链上还没有区块且状态为空时把创世分配写入状态
+    async fn write_genesis_state(&self) {
+        let Some(genesis) = &self.genesis else {
+            return;
+        };
+        let head = self
+            .blockchain
+            .read()
+            .await
+            .latest_block()
+            .map_or(0, |block| block.header.number);
+        let mut storage = self.state.read().await.storage().clone();
+        if head > 0 || !storage.accounts_page(None, 1).await.is_empty() {
+            return;
+        }
+        genesis.write_alloc(&mut storage).await;
+        log::info!("已将 {} 个创世账户写入状态", genesis.alloc.len());
+    }
+
+    /// 遍历状态中的全部账户重建供应量统计
+    ///
+    /// 启动和快速同步后调用；从检查点启动时遍历会下载数据源的全部账户（不含存储槽）。
+    pub async fn rebuild_supply(&self) -> Result<(), FairVMError> {
+        let number = self
+            .blockchain
+            .read()
+            .await
+            .latest_block()
+            .map_or(0, |block| block.header.number);
+        let storage = self.state.read().await.storage().clone();
+        let tracker = supply::SupplyTracker::from_storage(&storage, number).await;
+        if let Some(e) = storage.take_read_error().await {
+            return Err(FairVMError::StateError(format!(
+                "重建供应量统计失败: {}",
+                e
+            )));
+        }
+        *self.supply.write().await = tracker;
+        Ok(())
+    }
+
+    /// 使用数据目录时加载转账历史日志中已提交区块的记录
+    async fn load_account_history(&self) -> Result<(), FairVMError> {
+        let Ok(data_dir) = self.persistence.data_dir("转账历史") else {
+            return Ok(());
+        };
+        let last_accepted = storage::marker::StateMarker::load(data_dir)
+            .await?
+            .and_then(|marker| marker.last_accepted)
+            .map(|block| block.number);
+        let history = account_history::AccountHistory::open(data_dir, last_accepted)
+            .await
+            .map_err(|e| FairVMError::StateError(format!("加载转账历史失败: {}", e)))?;
+        *self.account_history.write().await = history;
+        Ok(())
+    }
+
+    /// 重放预写日志中已落盘但未确认应用完成的区块状态，返回重放的区块高度
+    pub async fn recover_state(&self) -> Result<Option<u64>, FairVMError> {
+        let wal = match &self.wal {
+            Some(wal) => wal,
+            None => return Ok(None),
+        };
+        let record = match wal
+            .recover()
+            .await
+            .map_err(|e| FairVMError::StateError(e.to_string()))?
+        {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+
+        let state = self.state.read().await;
+        self.commit_state(&state, &record).await?;
+        Ok(Some(record.block_number))
+    }
+
+    /// 接受新区块，执行交易生成收据并发布区块与交易打包事件
+    ///
+    /// 执行前按配置校验区块时间戳，违反规则的区块被拒绝。
+    pub async fn accept_block(
+        &self,
+        block: blockchain::Block,
+        base_fee: U256,
+    ) -> Result<(), FairVMError> {
+        self.accept_block_from(block, base_fee, None).await
+    }
+
+    /// 接受由 `proposer` 提出的区块，出块者与接受时间随区块保存
+    ///
+    /// 出块者以区块头为准，`proposer` 为传输层得知的来源，与区块头不一致时拒绝区块。
+    pub async fn accept_block_from(
+        &self,
+        block: blockchain::Block,
+        base_fee: U256,
+        proposer: Option<String>,
+    ) -> Result<(), FairVMError> {
+        if proposer.is_some() && proposer != block.header.proposer {
+            return Err(FairVMError::ProposerMismatch {
+                header: block.header.proposer,
+                peer: proposer,
+            });
+        }
+        let now = clock::now();
+        // 被拒绝的区块也计入样本，时间戳校验失败时能从时钟状态看出原因
+        self.clock
+            .write()
+            .await
+            .record_block(block.header.timestamp, now);
+        {
+            let blockchain = self.blockchain.read().await;
+            let parent = blockchain.check_link(&block.header)?;
+            blockchain::TimestampPolicy::from_config(&self.config).validate(
+                &block.header,
+                parent,
+                now,
+            )?;
+        }
+        let transactions = self
+            .verifier
+            .verify_block(block.transactions)
+            .await
+            .map_err(|e| FairVMError::TransactionError(e.to_string()))?;
+        block.header.verify_root(
+            blockchain::RootKind::Transactions,
+            merkle::transactions_root(&transactions),
+            self.chain_config.requires_roots(block.header.number),
+        )?;
+        block.header.verify_root(
+            blockchain::RootKind::Arrivals,
+            merkle::arrivals_root(&block.arrivals),
+            true,
+        )?;
+        if let Some(ordering) = self.fcfs.read().await.as_ref() {
+            let storage = self.state.read().await.storage().clone();
+            let penalized = consensus::fcfs::penalized(&storage).await;
+            ordering.validate_block(&block.arrivals, &transactions, &penalized)?;
+        }
+        self.validate_transactions(
+            &transactions,
+            tx_validator::ValidationStage::Block,
+            block.header.number,
+        )
+        .await?;
+        self.check_proposer(&block.header).await?;
+        let proposer = block.header.proposer.clone();
+        let mut block = blockchain::Block {
+            header: block.header,
+            transactions,
+            arrivals: block.arrivals,
+            acceptance: Some(blockchain::BlockAcceptance {
+                proposer,
+                accepted_at: now,
+                gas_limit: None,
+            }),
+        };
+        let block_hash = block.hash();
+        let number = block.header.number;
+
+        let outcome = self.execute_block(&block, base_fee, false).await?;
+        let gas_limit = outcome.gas_limit;
+        {
+            let mut tx_pool = self.tx_pool.write().await;
+            tx_pool.set_base_fee(base_fee);
+            for tx in &block.transactions {
+                tx_pool.prune(&tx.from, tx.nonce + 1);
+            }
+        }
+        if let Some(acceptance) = block.acceptance.as_mut() {
+            acceptance.gas_limit = gas_limit;
+        }
+        self.record_block_fees(
+            &block,
+            base_fee,
+            outcome.gas_used,
+            gas_limit.unwrap_or(self.config.gas_limit),
+        )
+        .await;
+
+        self.emit_event(
+            EventType::Block {
+                number,
+                hash: block_hash,
+                timestamp: block.header.timestamp,
+            },
+            json!({
+                "transactions": block.transactions.len(),
+                "proposer": block.header.proposer.clone(),
+            }),
+        )
+        .await;
+        for (index, tx) in block.transactions.iter().enumerate() {
+            self.emit_event(
+                EventType::TransactionIncluded {
+                    hash: tx.hash,
+                    block_number: number,
+                    block_hash,
+                    index: index as u64,
+                },
+                json!({}),
+            )
+            .await;
+        }
+
+        if let Some(ordering) = self.fcfs.write().await.as_mut() {
+            ordering.prune(&block.transactions, &block.arrivals);
+        }
+        self.blockchain.write().await.add_block(block);
+        self.maybe_publish_snapshot(number).await;
+        Ok(())
+    }
+
+    /// 下一个区块的基础费用，按父区块的费用统计和创世费用参数计算，未配置费用参数时为 0
+    async fn next_base_fee(&self, parent: u64) -> U256 {
+        let Some(fees) = &self.fees else {
+            return U256::zero();
+        };
+        match self.fee_stats.read().await.get(parent) {
+            Some(stats) => fees.next_base_fee(stats.base_fee, stats.gas_used),
+            None => U256::from(fees.base_fee),
+        }
+    }
+
+    /// 从交易池打包下一个区块，返回区块和区块的基础费用
+    ///
+    /// 交易的挑选规则见 [`producer::select_transactions`]，未通过区块阶段校验规则的交易不打包。
+    /// 区块在待定状态上试执行后填写收据根和状态根。没有可打包的交易且配置跳过空区块，或距父区块
+    /// 不足最小出块间隔时返回 `None`。
+    pub async fn build_block(&self) -> Result<Option<(blockchain::Block, U256)>, FairVMError> {
+        let config = &self.config.block_producer;
+        let (parent_hash, parent_number, parent_timestamp) = {
+            let blockchain = self.blockchain.read().await;
+            match blockchain.latest_block() {
+                Some(parent) => (
+                    parent.hash(),
+                    parent.header.number,
+                    Some(parent.header.timestamp),
+                ),
+                None => (H256::zero(), 0, None),
+            }
+        };
+        let number = parent_number + 1;
+        // 区块时间戳以秒计且必须晚于父区块，距父区块不足最小间隔时本轮不出块
+        let timestamp = clock::now();
+        if parent_timestamp
+            .is_some_and(|parent| timestamp < parent + self.config.min_block_interval.max(1))
+        {
+            return Ok(None);
+        }
+        let base_fee = self.next_base_fee(parent_number).await;
+        let gas_limit = match self.gas_limit.read().await.clone() {
+            Some(gas_limit_config) => {
+                let storage = self.state.read().await.storage().clone();
+                gas_limit::next_limit(&storage, &gas_limit_config).await
+            }
+            None => self.config.gas_limit,
+        };
+
+        let ready = {
+            let tx_pool = self.tx_pool.read().await;
+            tx_pool.ready(tx_pool.len())
+        };
+        let mut rejected = HashSet::new();
+        for tx in &ready {
+            if let Err(e) = self
+                .validate_transactions(
+                    std::slice::from_ref(tx),
+                    tx_validator::ValidationStage::Block,
+                    number,
+                )
+                .await
+            {
+                log::debug!("{}", e);
+                rejected.insert(tx.hash);
+            }
+        }
+        let transactions = producer::select_transactions(
+            ready,
+            &rejected,
+            base_fee,
+            gas_limit,
+            config.max_transactions,
+        );
+        // 启用 FCFS 排序时按到达时间重新挑选并排序，区块携带所用的报告
+        let (transactions, arrivals) = match self.fcfs.write().await.as_mut() {
+            Some(ordering) => {
+                let storage = self.state.read().await.storage().clone();
+                let penalized = consensus::fcfs::penalized(&storage).await;
+                ordering.build_block(number, transactions, gas_limit, &penalized)
+            }
+            None => (transactions, Vec::new()),
+        };
+        if transactions.is_empty() && config.skip_empty {
+            return Ok(None);
+        }
+
+        let mut block = blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash,
+                number,
+                timestamp,
+                transactions_root: merkle::transactions_root(&transactions),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: config.proposer.clone(),
+                arrivals_root: merkle::arrivals_root(&arrivals),
+            },
+            transactions,
+            arrivals,
+            acceptance: None,
+        };
+        let outcome = self.execute_block(&block, base_fee, true).await?;
+        block.header.receipts_root = outcome.receipts_root;
+        block.header.state_root = outcome.state_root;
+        Ok(Some((block, base_fee)))
+    }
+
+    /// 打包下一个区块并提交给共识引擎，共识引擎接受后应用到本地链，返回新区块的哈希
+    pub async fn produce_block(&self) -> Result<Option<H256>, FairVMError> {
+        if !self.is_running {
+            return Err(FairVMError::Other("FairVM 未运行".into()));
+        }
+        let Some(consensus) = &self.consensus else {
+            return Err(FairVMError::Other("未设置共识引擎".into()));
+        };
+        let Some((block, base_fee)) = self.build_block().await? else {
+            return Ok(None);
+        };
+        consensus.write().await.submit_block(&block).await?;
+        let hash = block.hash();
+        self.accept_block_from(block, base_fee, self.config.block_producer.proposer.clone())
+            .await?;
+        Ok(Some(hash))
+    }
+
+    /// 标记区块最终确认并发布交易确认事件
+    pub async fn finalize_block(&self, number: u64) -> Result<(), FairVMError> {
+        let (block_hash, tx_hashes) = {
+            let blockchain = self.blockchain.read().await;
+            let block = blockchain
+                .get_block(number)
+                .ok_or_else(|| FairVMError::Other(format!("区块 {} 不存在", number)))?;
+            let tx_hashes: Vec<H256> = block.transactions.iter().map(|tx| tx.hash).collect();
+            (block.hash(), tx_hashes)
+        };
+
+        self.emit_event(
+            EventType::BlockFinalized,
+            json!({ "number": number, "hash": block_hash }),
+        )
+        .await;
+        for hash in tx_hashes {
+            self.emit_event(
+                EventType::TransactionFinalized {
+                    hash,
+                    block_number: number,
+                },
+                json!({}),
+            )
+            .await;
+        }
+        Ok(())
+    }
+
+    /// 获取账户信息
+    pub async fn get_account(&self, address: &account::Address) -> Option<Account> {
+        let state = self.state.read().await;
+        state.get_account(address).await
+    }
+
+    /// 已提交状态的只读快照，读取不会等待区块执行；从检查点启动的节点没有快照
+    pub async fn snapshot(&self) -> Option<StorageSnapshot> {
+        self.state.read().await.storage().snapshot()
+    }
+
+    /// 当前账户状态的承诺，出块者将其状态根写入区块头供轻节点验证
+    pub async fn state_commitment(&self) -> state_proof::StateCommitment {
+        let storage = self.state.read().await.storage().clone();
+        state_proof::StateCommitment::from_storage(&storage).await
+    }
+
+    /// 获取账户在当前状态下的证明
+    pub async fn state_proof(&self, address: &account::Address) -> state_proof::StateProof {
+        self.state_commitment().await.prove(address)
+    }
+
+    /// 获取已打包交易及其收据的包含证明，交易不存在或尚未打包时返回 `None`
+    pub async fn inclusion_proof(&self, tx_hash: &H256) -> Option<inclusion::InclusionProof> {
+        let state = self.state.read().await;
+        let receipt = state.get_transaction_receipt(tx_hash.as_bytes()).await?;
+        let receipts = state.block_receipts(receipt.block_number).await;
+        let blockchain = self.blockchain.read().await;
+        let block = blockchain.get_block(receipt.block_number)?;
+        inclusion::InclusionProof::build(block, &receipts, tx_hash)
+    }
+
+    /// 在最新状态上执行调用，状态修改不会提交
+    ///
+    /// 供 `eth_call` 使用，区块环境取最新区块，不扣除 gas 费用。
+    pub async fn call(&self, tx: &Transaction) -> ExecutionResult {
+        let mut storage = OverlayStorage::new(self.state.read().await.storage().clone());
+        let latest = self.blockchain.read().await.latest_block().cloned();
+        let env = match latest {
+            Some(block) => self.evm_environment(&block, U256::zero(), None).await,
+            None => evm::Environment {
+                chain_id: self.chain_id(),
+                deployment: self.deployment.clone(),
+                vesting: self.vesting.clone(),
+                ..evm::Environment::default()
+            },
+        };
+        evm::executor::execute(&mut storage, tx, &env).await.0
+    }
+
+    /// 从可信检查点启动节点，检查点之前的状态在首次访问时从数据源下载
+    ///
+    /// 检查点须由 `config.checkpoint` 中的可信签名者签发；链参数仍通过 [`FairVM::apply_genesis`] 设置。
+    pub async fn from_checkpoint<S: CheckpointSource + 'static>(
+        config: Config,
+        source: S,
+    ) -> Result<Self, FairVMError> {
+        let trusted = match &config.checkpoint {
+            Some(checkpoint) => checkpoint::parse_signers(&checkpoint.trusted_signers)?,
+            None => return Err(checkpoint::CheckpointError::NotConfigured.into()),
+        };
+        let checkpoint = source.checkpoint().await?;
+        checkpoint.verify(&trusted)?;
+        let header = source.header(checkpoint.number).await?;
+        checkpoint.check_header(&header)?;
+
+        let mut vm = Self::with_config(config);
+        let storage =
+            StateService::spawn(Box::new(LazyStorage::new(source, checkpoint.state_root)));
+        vm.state = Arc::new(RwLock::new(State::new(
+            storage.clone(),
+            evm::EvmContext::default(),
+        )));
+        vm.storage = storage;
+        vm.blockchain.write().await.add_block(blockchain::Block {
+            header,
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        });
+        Ok(vm)
+    }
+
+    /// 从配置的数据源获取检查点并启动节点
+    pub async fn sync_from_checkpoint(config: Config) -> Result<Self, FairVMError> {
+        let url = config
+            .checkpoint
+            .as_ref()
+            .ok_or(checkpoint::CheckpointError::NotConfigured)?
+            .url
+            .clone();
+        Self::from_checkpoint(config, checkpoint::RpcCheckpointSource::new(url)).await
+    }
+
+    /// 冻结当前状态并为最新区块签发检查点，供其他节点从该检查点启动
+    pub async fn publish_checkpoint(
+        &self,
+        secret_key: &secp256k1::SecretKey,
+    ) -> Result<Checkpoint, FairVMError> {
+        let header = self
+            .blockchain
+            .read()
+            .await
+            .latest_block()
+            .map(|block| block.header.clone())
+            .ok_or_else(|| FairVMError::Other("尚无区块，无法发布检查点".into()))?;
+        let storage = self.state.read().await.storage().clone();
+        let snapshot = CheckpointSnapshot::capture(&storage, header, secret_key).await?;
+        let checkpoint = snapshot.checkpoint().clone();
+        *self.checkpoint.write().await = Some(Arc::new(snapshot));
+        Ok(checkpoint)
+    }
+
+    /// 设置周期快照策略，为空时停止自动冻结快照
+    pub async fn set_snapshot_policy(&self, policy: Option<SnapshotPolicy>) {
+        *self.snapshot_policy.write().await = policy;
+    }
+
+    /// 按快照策略在区块 `number` 导入后冻结状态，失败只记录日志，不影响区块导入
+    async fn maybe_publish_snapshot(&self, number: u64) {
+        let policy = self.snapshot_policy.read().await;
+        let Some(policy) = policy.as_ref().filter(|policy| policy.is_due(number)) else {
+            return;
+        };
+        if let Err(e) = self.publish_checkpoint(&policy.secret_key).await {
+            log::warn!("冻结区块 {} 的状态快照失败: {}", number, e);
+        }
+    }
+
+    /// 响应对等节点的同步请求：区块、最新快照清单和快照分块，其他消息返回 `None`
+    pub async fn handle_sync_request(&self, message: &NetworkMessage) -> Option<NetworkMessage> {
+        match message {
+            NetworkMessage::GetBlock(number) => Some(NetworkMessage::BlockResponse(
+                self.blockchain.read().await.get_block(*number).cloned(),
+            )),
+            NetworkMessage::GetSnapshot => {
+                let snapshot = self.checkpoint.read().await.clone();
+                let manifest = match snapshot {
+                    Some(snapshot) => Some(state_sync::SnapshotManifest {
+                        checkpoint: snapshot.checkpoint().clone(),
+                        header: snapshot.header().clone(),
+                        fees: self
+                            .fee_stats
+                            .read()
+                            .await
+                            .get(snapshot.header().number)
+                            .cloned(),
+                    }),
+                    None => None,
+                };
+                Some(NetworkMessage::SnapshotManifest(manifest))
+            }
+            NetworkMessage::GetSnapshotChunk { number, after } => {
+                let snapshot = self.checkpoint.read().await.clone();
+                // 快照已被更新的快照替换时返回空，请求方需重新获取清单
+                let chunk = match snapshot {
+                    Some(snapshot) if snapshot.header().number == *number => {
+                        Some(state_sync::chunk(&snapshot, *after).await)
+                    }
+                    _ => None,
+                };
+                Some(NetworkMessage::SnapshotChunk(chunk))
+            }
+            _ => None,
+        }
+    }
+
+    /// 从对等节点的最新快照启动节点，快照须由 `config.checkpoint` 中的可信签名者签发
+    ///
+    /// 与 [`FairVM::from_checkpoint`] 按需下载不同，启动前下载并验证完整状态；之后用
+    /// [`FairVM::catch_up`] 追赶快照之后的区块。
+    pub async fn fast_sync(config: Config, peer: &dyn SnapshotPeer) -> Result<Self, FairVMError> {
+        let trusted = match &config.checkpoint {
+            Some(checkpoint) => checkpoint::parse_signers(&checkpoint.trusted_signers)?,
+            None => return Err(checkpoint::CheckpointError::NotConfigured.into()),
+        };
+        let (manifest, storage) = state_sync::download(peer, &trusted).await?;
+
+        let mut vm = Self::with_config(config);
+        let storage = StateService::spawn(Box::new(storage));
+        vm.state = Arc::new(RwLock::new(State::new(
+            storage.clone(),
+            evm::EvmContext::default(),
+        )));
+        vm.storage = storage;
+        let block = blockchain::Block {
+            header: manifest.header,
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        if let Some(fees) = manifest.fees {
+            vm.record_block_fees(&block, fees.base_fee, fees.gas_used, fees.gas_limit)
+                .await;
+        }
+        vm.blockchain.write().await.add_block(block);
+        vm.rebuild_supply().await?;
+        Ok(vm)
+    }
+
+    /// 逐个请求并导入本地链头之后的区块，直到对等节点没有更新的区块，返回导入的区块数
+    pub async fn catch_up(&self, peer: &dyn SnapshotPeer) -> Result<u64, FairVMError> {
+        let mut imported = 0;
+        loop {
+            let (latest, head_hash) = self
+                .blockchain
+                .read()
+                .await
+                .latest_block()
+                .map(|block| (block.header.number, Some(block.hash())))
+                .unwrap_or_default();
+            let block = match peer.request(NetworkMessage::GetBlock(latest + 1)).await? {
+                NetworkMessage::BlockResponse(Some(block)) => block,
+                NetworkMessage::BlockResponse(None) => return Ok(imported),
+                other => {
+                    return Err(state_sync::StateSyncError::UnexpectedResponse(format!(
+                        "{:?}",
+                        other
+                    ))
+                    .into())
+                }
+            };
+            if block.header.number != latest + 1 {
+                return Err(state_sync::StateSyncError::UnexpectedBlock {
+                    number: block.header.number,
+                    expected: latest + 1,
+                }
+                .into());
+            }
+            if head_hash.is_some_and(|hash| block.header.parent_hash != hash) {
+                return Err(state_sync::StateSyncError::UnlinkedBlock(block.header.number).into());
+            }
+            let base_fee = self.next_base_fee(latest).await;
+            self.accept_block(block, base_fee).await?;
+            imported += 1;
+        }
+    }
+
+    /// 获取NFT合约信息，目前仅支持原生 NFT 合约
+    pub async fn get_nft_contract(&self, address: &account::Address) -> Option<NFTContract> {
+        if *address == NATIVE_NFT_ADDRESS {
+            return Some(self.native_nfts.read().await.clone());
+        }
+        None
+    }
+
+    /// 获取共识状态
+    pub async fn get_consensus_state(&self) -> Result<ConsensusState, FairVMError> {
+        if let Some(consensus) = &self.consensus {
+            consensus
+                .read()
+                .await
+                .get_consensus_state()
+                .await
+                .map(|state| state.into())
+                .map_err(FairVMError::from)
+        } else {
+            Err(FairVMError::Other("未设置共识引擎".into()))
+        }
+    }
+
+    /// 变更验证人集合，返回变更生效的区块高度
+    pub async fn update_validators(
+        &self,
+        change: consensus::ValidatorChange,
+    ) -> Result<u64, FairVMError> {
+        let consensus = self
+            .consensus
+            .as_ref()
+            .ok_or_else(|| FairVMError::Other("未设置共识引擎".into()))?;
+        let height = consensus
+            .write()
+            .await
+            .update_validators(change.clone())
+            .await?;
+        let data = serde_json::to_value(&change).unwrap_or_default();
+        self.emit_event(EventType::ValidatorChange { height, change }, data)
+            .await;
+        Ok(height)
+    }
+
+    /// 指定高度的活跃验证人，未指定时为当前高度
+    pub async fn validators_at(
+        &self,
+        height: Option<u64>,
+    ) -> Result<Vec<consensus::ValidatorInfo>, FairVMError> {
+        let consensus = self
+            .consensus
+            .as_ref()
+            .ok_or_else(|| FairVMError::Other("未设置共识引擎".into()))?;
+        let consensus = consensus.read().await;
+        let height = match height {
+            Some(height) => height,
+            None => consensus.get_consensus_state().await?.height,
+        };
+        Ok(consensus.validators_at(height).await?)
+    }
+
+    /// 获取账户nonce
+    pub async fn get_nonce(&self, address: account::Address) -> Result<u64, FairVMError> {
+        let state = self.state.read().await;
+        let account = state.get_account(&address).await;
+        Ok(account.map_or(0, |acc| acc.nonce))
+    }
+
+    /// 创建新交易
+    pub async fn create_transaction(
+        &self,
+        from: account::Address,
+        to: Option<account::Address>,
+        value: U256,
+        data: Vec<u8>,
+    ) -> Result<Transaction, FairVMError> {
+        let nonce = self.get_nonce(from).await?;
+        let gas_limit = U256::from(21000); // 基本 gas 限制
+        let gas_price = U256::from(1); // 基本 gas 价格
+        let chain_id = self.chain_id();
+
+        let transaction = Transaction::new(
+            H256::zero(), // 临时哈希，将在签名后更新
+            from,
+            to,
+            value,
+            nonce,
+            gas_limit.as_u64(),
+            Some(gas_price),
+            data,
+            Vec::new(), // 临时签名
+            TransactionType::Legacy,
+            chain_id,
+            None,
+            None,
+        );
+
+        Ok(transaction)
+    }
+}
+
+impl Default for FairVM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type ConsensusTransaction = Transaction;
+
+/// 同进程内的节点直接作为快照同步的对等节点
+#[async_trait]
+impl SnapshotPeer for FairVM {
+    async fn request(
+        &self,
+        message: NetworkMessage,
+    ) -> Result<NetworkMessage, state_sync::StateSyncError> {
+        self.handle_sync_request(&message).await.ok_or_else(|| {
+            state_sync::StateSyncError::Peer(format!("不支持的同步请求: {:?}", message))
+        })
+    }
+}
+
+#[async_trait]
+impl Vm for FairVM {
+    async fn execute_transaction(
+        &self,
+        transaction: &CoreTransaction,
+        _state: &dyn StateTrait,
+    ) -> Result<ExecutionResult, Box<dyn std::error::Error>> {
+        // 将 CoreTransaction 转换为内部 Transaction 类型
+        let _tx = Transaction {
+            hash: H256(transaction.hash.0.into()),
+            from: Address(transaction.from.0.into()),
+            to: transaction.to.map(|addr| Address(addr.0.into())),
+            value: transaction.value,
+            nonce: transaction.nonce,
+            gas_limit: transaction.gas_limit,
+            gas_price: Some(transaction.gas_price),
+            data: transaction.data.clone(),
+            signature: vec![], // 暂时为空
+            transaction_type: transaction::TransactionType::Legacy,
+            chain_id: self.chain_id(),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+        };
+
+        // TODO: 实现实际的交易执行逻辑
+        Ok(ExecutionResult {
+            gas_used: 0,
+            return_data: vec![],
+            status: true,
+            gas_refunded: 0,
+        })
+    }
+}
+
+#[async_trait]
+impl VmExt for FairVM {
+    async fn get_state(&self) -> Arc<RwLock<State>> {
+        self.state.clone()
+    }
+
+    async fn get_storage_handle(&self) -> StateHandle {
+        self.storage.clone()
+    }
+
+    async fn get_storage(
+        &self,
+        address: &ethers::types::H160,
+        key: &ethers::types::H256,
+    ) -> Result<ethers::types::H256, Error> {
+        let storage = self.state.read().await.storage().clone();
+        let value = storage.get_storage_value(&Address(address.0), key.0).await;
+        Ok(ethers::types::H256(value))
+    }
+
+    async fn get_consensus(&self) -> Option<Arc<RwLock<dyn ConsensusEngineTrait + Send + Sync>>> {
+        self.consensus.clone()
+    }
+
+    async fn get_account(&self, address: &account::Address) -> Option<Account> {
+        let state = self.state.read().await;
+        state.get_account(address).await
+    }
+
+    async fn get_account_transactions(&self, address: &account::Address) -> Vec<Transaction> {
+        let state = self.state.read().await;
+        state.get_account_transactions(address).await
+    }
+
+    async fn get_transaction_receipt(&self, tx_hash: &[u8]) -> Option<receipt::Receipt> {
+        let state = self.state.read().await;
+        state.get_transaction_receipt(tx_hash).await
+    }
+
+    async fn get_block_receipts(&self, number: u64) -> Vec<receipt::Receipt> {
+        let state = self.state.read().await;
+        state.block_receipts(number).await
+    }
+
+    async fn get_inclusion_proof(&self, tx_hash: &H256) -> Option<inclusion::InclusionProof> {
+        self.inclusion_proof(tx_hash).await
+    }
+
+    async fn call(&self, tx: &Transaction) -> ExecutionResult {
+        FairVM::call(self, tx).await
+    }
+
+    async fn get_code(&self, address: &ethers::types::H160) -> Result<Vec<u8>, Error> {
+        let state = self.state.read().await;
+        let account = state.get_account(&Address(address.0)).await;
+        match account {
+            Some(_acc) => Ok(state.storage().get_code(&Address(address.0)).await),
+            None => Err(Error::internal_error()),
+        }
+    }
+
+    async fn get_blockchain(&self) -> Arc<RwLock<Blockchain>> {
+        self.blockchain.clone()
+    }
+
+    async fn get_fee_stats(&self) -> Arc<RwLock<FeeStatsTracker>> {
+        self.fee_stats.clone()
+    }
+
+    async fn get_gas_stats(&self) -> Arc<RwLock<GasStatsTracker>> {
+        self.gas_stats.clone()
+    }
+
+    async fn get_staking_config(&self) -> Option<StakingConfig> {
+        self.staking.read().await.clone()
+    }
+
+    async fn get_log_index(&self) -> Arc<RwLock<log_index::LogIndex>> {
+        self.log_index.clone()
+    }
+
+    async fn get_account_history(&self) -> Arc<RwLock<account_history::AccountHistory>> {
+        self.account_history.clone()
+    }
+
+    async fn get_supply_tracker(&self) -> Arc<RwLock<supply::SupplyTracker>> {
+        self.supply.clone()
+    }
+
+    async fn get_log_filters(&self) -> Arc<RwLock<log_filter::FilterRegistry>> {
+        self.log_filters.clone()
+    }
+
+    async fn get_peers(&self) -> Vec<network::PeerInfo> {
+        self.peers.read().await.peers()
+    }
+
+    async fn get_clock_status(&self) -> clock::ClockStatus {
+        self.clock.read().await.status(clock::now())
+    }
+
+    async fn update_validators(
+        &self,
+        change: consensus::ValidatorChange,
+    ) -> Result<u64, FairVMError> {
+        FairVM::update_validators(self, change).await
+    }
+
+    async fn validators_at(
+        &self,
+        height: Option<u64>,
+    ) -> Result<Vec<consensus::ValidatorInfo>, FairVMError> {
+        FairVM::validators_at(self, height).await
+    }
+
+    async fn get_checkpoint(&self) -> Option<Arc<CheckpointSnapshot>> {
+        self.checkpoint.read().await.clone()
+    }
+
+    async fn collect_debug_bundle(&self) -> crash::DebugBundle {
+        let chain_tip = crash::ChainTip::of(&*self.blockchain.read().await);
+        crash::DebugBundle::collect(&self.config, chain_tip, None)
+    }
+
+    async fn get_gas_limit_status(&self) -> Option<GasLimitStatus> {
+        let config = self.gas_limit.read().await.clone()?;
+        let storage = self.state.read().await.storage().clone();
+        Some(gas_limit::status(&storage, &config).await)
+    }
+
+    async fn get_nonce_sequence(&self, address: &Address, key: u64) -> nonce::NonceSequence {
+        let storage = self.state.read().await.storage().clone();
+        nonce::NonceSequence::of(&storage, self.nonce_mode, address, key).await
+    }
+
+    async fn get_price_rounds(&self, pair: &str, count: usize) -> Vec<PriceRound> {
+        let storage = self.state.read().await.storage().clone();
+        oracle::recent_rounds(&storage, pair, count).await
+    }
+
+    async fn get_chain_metadata(&self) -> ChainMetadata {
+        self.chain_metadata().await
+    }
+
+    async fn get_chain_config(&self) -> ChainConfig {
+        self.chain_config.clone()
+    }
+
+    async fn submit_raw_transaction(&self, raw: &[u8]) -> Result<H256, Error> {
+        FairVM::submit_raw_transaction(self, raw)
+            .await
+            .map_err(|e| Error::invalid_params(e.to_string()))
+    }
+
+    async fn get_debug_sessions(&self) -> Arc<RwLock<evm::debugger::DebugSessions>> {
+        self.debug_sessions.clone()
     }
 
-    async fn get_account_transactions(&self, address: &account::Address) -> Vec<Transaction> {
-        let state = self.state.read().await;
-        state.get_account_transactions(address).await
+    async fn get_block_environment(&self, number: u64) -> Option<evm::Environment> {
+        self.block_environment(number).await
     }
 
-    async fn get_transaction_receipt(
-        &self,
-        tx_hash: &[u8],
-    ) -> Option<ethers::types::TransactionReceipt> {
-        let state = self.state.read().await;
-        state.get_transaction_receipt(tx_hash).await
+    async fn get_source_maps(&self) -> Arc<RwLock<evm::source_map::SourceRegistry>> {
+        self.source_maps.clone()
     }
 
-    async fn get_code(&self, address: &ethers::types::H160) -> Result<Vec<u8>, Error> {
-        let state = self.state.read().await;
-        let account = state.get_account(&Address(address.0)).await;
-        match account {
-            Some(_acc) => {
-                let storage = state.storage().read().await;
-                // 使用 Storage trait 的 get_code_hash 方法
-                let code_hash = storage.get_code_hash(&Address(address.0)).await;
-                if code_hash.is_zero() {
-                    Ok(Vec::new())
-                } else {
-                    // 从存储中获取代码
-                    let code = storage
-                        .get_storage_value(&Address(address.0), code_hash.0)
-                        .await;
-                    Ok(code.to_vec())
-                }
-            }
-            None => Err(Error::internal_error()),
-        }
+    async fn get_selectors(&self) -> Arc<RwLock<evm::selectors::SelectorDatabase>> {
+        self.selectors.clone()
     }
 }
 
@@ -467,6 +2538,14 @@ mod tests {
     use super::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
+    /// 用测试私钥签名交易并设置发送方
+    fn sign(tx: &mut Transaction) {
+        let secret_key = secp256k1::SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let (signature, signer) = signing::sign_digest(&secret_key, tx.sighash().0).unwrap();
+        tx.from = signer;
+        tx.signature = signature;
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
@@ -497,7 +2576,7 @@ mod tests {
 
         fairvm.start().await.unwrap();
 
-        let tx = Transaction {
+        let mut tx = Transaction {
             from: Address([0u8; 20]),
             to: Some(Address([1u8; 20])),
             value: U256::from(100),
@@ -513,7 +2592,22 @@ mod tests {
             max_priority_fee_per_gas: None,
         };
 
-        fairvm.submit_transaction(tx).await.unwrap();
+        // 未签名的交易被拒绝
+        assert!(fairvm.submit_transaction(tx.clone()).await.is_err());
+
+        sign(&mut tx);
+        fairvm.submit_transaction(tx.clone()).await.unwrap();
+        assert!(fairvm.tx_pool().read().await.contains(&tx.hash));
+
+        // nonce 有缺口的交易留在交易池排队
+        tx.nonce = 2;
+        tx.hash = H256::from_low_u64_be(2);
+        sign(&mut tx);
+        fairvm.submit_transaction(tx.clone()).await.unwrap();
+        let tx_pool = fairvm.tx_pool();
+        let tx_pool = tx_pool.read().await;
+        assert_eq!(tx_pool.pending()[&tx.from].len(), 1);
+        assert_eq!(tx_pool.queued()[&tx.from].len(), 1);
     }
 
     #[derive(Debug)]
@@ -566,4 +2660,1143 @@ mod tests {
         fairvm.publish_event(event).await.unwrap();
         assert_eq!(handler.count(), 1);
     }
+
+    #[tokio::test]
+    async fn test_transaction_lifecycle_events() {
+        let fairvm = FairVM::new();
+        let handler = Arc::new(TestEventHandler::new());
+        fairvm.add_event_handler(handler.clone()).await;
+
+        let mut tx = Transaction {
+            from: Address([0u8; 20]),
+            to: Some(Address([1u8; 20])),
+            value: U256::from(100),
+            data: vec![],
+            nonce: 0,
+            gas_price: Some(U256::from(1)),
+            gas_limit: 21000,
+            signature: Vec::new(),
+            transaction_type: TransactionType::Legacy,
+            hash: H256::from_low_u64_be(1),
+            chain_id: 1,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+        };
+        sign(&mut tx);
+        let block = blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 0,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: vec![tx],
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+
+        // 区块事件 + 交易打包事件
+        fairvm.accept_block(block, U256::zero()).await.unwrap();
+        assert_eq!(handler.count(), 2);
+        let receipt = fairvm
+            .state
+            .read()
+            .await
+            .get_transaction_receipt(H256::from_low_u64_be(1).as_bytes())
+            .await
+            .unwrap();
+        assert!(receipt.status);
+        assert_eq!(receipt.block_number, 1);
+        assert_eq!(receipt.effective_gas_price, U256::from(1));
+        // 收据按区块高度索引
+        let receipt_gas_used = receipt.gas_used;
+        assert_eq!(fairvm.get_block_receipts(1).await, vec![receipt]);
+        assert!(fairvm.get_block_receipts(2).await.is_empty());
+        let proof = fairvm
+            .inclusion_proof(&H256::from_low_u64_be(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            (proof.block_number, proof.index, proof.leaf_count),
+            (1, 0, 1)
+        );
+        assert!(fairvm
+            .inclusion_proof(&H256::from_low_u64_be(2))
+            .await
+            .is_none());
+        // 发送方和接收方各记录一条转账
+        let history = fairvm.account_history.read().await;
+        let received = history.range(&H160([1u8; 20]), 0, 10);
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].from, H160::zero());
+        assert_eq!(received[0].value, U256::from(100));
+        assert_eq!(received[0].fee, U256::from(receipt_gas_used));
+        assert_eq!(
+            received[0].balance,
+            fairvm
+                .state
+                .read()
+                .await
+                .get_balance(&Address([1u8; 20]))
+                .await
+        );
+        assert_eq!(history.range(&H160::zero(), 0, 10).len(), 1);
+        assert!(history.range(&H160::zero(), 2, 10).is_empty());
+        drop(history);
+        assert_eq!(fairvm.supply.read().await.stats(10).block_number, 1);
+
+        // 区块确认事件 + 交易确认事件
+        fairvm.finalize_block(1).await.unwrap();
+        assert_eq!(handler.count(), 4);
+        assert!(fairvm.finalize_block(2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tx_validator_rejects_block() {
+        let fairvm = FairVM::new();
+        fairvm
+            .add_tx_validator(Arc::new(tx_validator::SenderAllowlist::new([Address(
+                [7u8; 20],
+            )])))
+            .await;
+        let mut tx = Transaction::new(
+            H256::from_low_u64_be(1),
+            Address::zero(),
+            Some(Address([1u8; 20])),
+            U256::from(100),
+            0,
+            21000,
+            Some(U256::from(1)),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        sign(&mut tx);
+        let block = blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 0,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: vec![tx],
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        let error = fairvm.accept_block(block, U256::zero()).await.unwrap_err();
+        assert!(matches!(
+            error,
+            FairVMError::Validation(tx_validator::ValidationError {
+                stage: ValidationStage::Block,
+                ..
+            })
+        ));
+        assert!(fairvm.get_block_receipts(1).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fcfs_rejects_unreported_block() {
+        let fairvm = FairVM::new();
+        let report = |tx: &Transaction| {
+            let key = secp256k1::SecretKey::from_slice(&[2u8; 32]).unwrap();
+            consensus::fcfs::ArrivalTimestamp::sign(&key, tx.hash, 100).unwrap()
+        };
+        let mut tx = Transaction::new(
+            H256::from_low_u64_be(1),
+            Address::zero(),
+            Some(Address([1u8; 20])),
+            U256::from(100),
+            0,
+            21000,
+            Some(U256::from(1)),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        sign(&mut tx);
+        assert!(fairvm.add_arrival_report(report(&tx)).await.is_err());
+
+        fairvm
+            .set_fcfs_ordering(Some(consensus::fcfs::FcfsOrdering::default()))
+            .await;
+        let block = |arrivals: Vec<consensus::fcfs::ArrivalTimestamp>| blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 0,
+                transactions_root: merkle::transactions_root(std::slice::from_ref(&tx)),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: merkle::arrivals_root(&arrivals),
+            },
+            transactions: vec![tx.clone()],
+            arrivals,
+            acceptance: None,
+        };
+        let error = fairvm
+            .accept_block(block(Vec::new()), U256::zero())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            FairVMError::Fcfs(consensus::fcfs::FcfsError::MissingReports(_))
+        ));
+        assert!(fairvm.blockchain.read().await.get_block(1).is_none());
+
+        // 区块头须承诺区块携带的报告
+        let mut stripped = block(vec![report(&tx)]);
+        stripped.arrivals.clear();
+        assert!(matches!(
+            fairvm.accept_block(stripped, U256::zero()).await,
+            Err(FairVMError::RootMismatch(_))
+        ));
+
+        // 只依据区块携带的报告校验，冲突报告的验证者在状态中被惩罚
+        let cheater = secp256k1::SecretKey::from_slice(&[3u8; 32]).unwrap();
+        let conflicting = [100, 200]
+            .map(|t| consensus::fcfs::ArrivalTimestamp::sign(&cheater, tx.hash, t).unwrap());
+        let cheater = conflicting[0].validator;
+        let mut arrivals = vec![report(&tx)];
+        arrivals.extend(conflicting);
+        fairvm
+            .accept_block(block(arrivals), U256::zero())
+            .await
+            .unwrap();
+        let storage = fairvm.state.read().await.storage().clone();
+        assert_eq!(consensus::fcfs::penalized(&storage).await, vec![cheater]);
+    }
+
+    #[tokio::test]
+    async fn test_produce_block() {
+        let mut fairvm = FairVM::new();
+        fairvm
+            .set_consensus(basic::BasicConsensus::new())
+            .await
+            .unwrap();
+        assert!(fairvm.produce_block().await.is_err());
+        fairvm.start().await.unwrap();
+        // 交易池为空时跳过
+        assert_eq!(fairvm.produce_block().await.unwrap(), None);
+
+        let mut tx = Transaction::new(
+            H256::from_low_u64_be(1),
+            Address::zero(),
+            Some(Address([1u8; 20])),
+            U256::from(100),
+            0,
+            21000,
+            Some(U256::from(1)),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        sign(&mut tx);
+        fairvm.submit_transaction(tx.clone()).await.unwrap();
+
+        let hash = fairvm.produce_block().await.unwrap().unwrap();
+        let block = fairvm
+            .blockchain
+            .read()
+            .await
+            .get_block(1)
+            .cloned()
+            .unwrap();
+        assert_eq!(block.hash(), hash);
+        assert_eq!(block.transactions[0].hash, tx.hash);
+        assert!(!block.header.receipts_root.is_zero());
+        assert_eq!(
+            block.header.state_root,
+            fairvm.state_commitment().await.root()
+        );
+        assert!(fairvm.tx_pool().read().await.is_empty());
+        assert!(fairvm.get_block_receipts(1).await[0].status);
+        assert_eq!(fairvm.get_consensus_state().await.unwrap().height, 1);
+    }
+
+    #[tokio::test]
+    async fn test_oracle_transaction_applied_in_block() {
+        let key = secp256k1::SecretKey::from_slice(&[3u8; 32]).unwrap();
+        let reporter = Address::from_public_key(&secp256k1::PublicKey::from_secret_key(
+            &secp256k1::Secp256k1::new(),
+            &key,
+        ));
+        let mut genesis = Genesis::new(1);
+        genesis.enable_oracle(OracleConfig {
+            reporters: vec![reporter],
+            quorum: 1,
+        });
+        let mut fairvm = FairVM::new();
+        fairvm.apply_genesis(&genesis).unwrap();
+        fairvm
+            .set_consensus(basic::BasicConsensus::new())
+            .await
+            .unwrap();
+        fairvm.start().await.unwrap();
+
+        let mut pusher = oracle::OracleReporter::new(key, fairvm.chain_id());
+        let gas_price = U256::from(genesis.fees.base_fee) * 2;
+        let tx = pusher
+            .build_transaction("FAIR/USD", 42.into(), 8, 0, 0, gas_price)
+            .unwrap();
+        fairvm.submit_transaction(tx).await.unwrap();
+        // 准入时不更新预言机
+        assert!(fairvm.get_price_rounds("FAIR/USD", 1).await.is_empty());
+
+        fairvm.produce_block().await.unwrap().unwrap();
+        assert!(fairvm.get_block_receipts(1).await[0].status);
+        // 轮次写入预言机系统地址的存储槽，随区块状态提交
+        let storage = fairvm.state.read().await.storage().clone();
+        assert_eq!(
+            oracle::latest_round(&storage, "FAIR/USD")
+                .await
+                .unwrap()
+                .price,
+            U256::from(42)
+        );
+        assert_eq!(fairvm.get_price_rounds("FAIR/USD", 1).await[0].round_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_genesis_alloc_written_on_start() {
+        let holder = H160::from_low_u64_be(7);
+        let mut genesis = Genesis::new(1);
+        genesis.add_account(holder, 1_000);
+        genesis.enable_oracle(OracleConfig {
+            reporters: Vec::new(),
+            quorum: 1,
+        });
+        let mut fairvm = FairVM::new();
+        fairvm.apply_genesis(&genesis).unwrap();
+        fairvm.start().await.unwrap();
+
+        let storage = fairvm.state.read().await.storage().clone();
+        assert_eq!(
+            storage.get_balance(&Address(holder.0)).await,
+            U256::from(1_000)
+        );
+        assert_eq!(
+            storage.get_code(&oracle::ORACLE_CONTRACT_ADDRESS).await,
+            oracle::ORACLE_CONTRACT_CODE
+        );
+        // 统计从状态重建，与创世分配一致
+        let stats = fairvm.supply.read().await.stats(10);
+        assert_eq!(stats.total_supply, U256::from(1_000));
+        assert_eq!(stats.burned_fees, U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_call_revert_reason() {
+        let fairvm = FairVM::new();
+        let contract = Address([5u8; 20]);
+        // 写入 slot 0 后以 Error("nope") 回滚
+        let mut payload = RevertReason::Error("nope".into()).encode();
+        let length = payload.len() as u8;
+        payload.resize(128, 0);
+        let mut code = vec![0x60, 0x01, 0x60, 0x00, 0x55];
+        for (index, chunk) in payload.chunks(32).enumerate() {
+            code.push(0x7f);
+            code.extend_from_slice(chunk);
+            code.extend_from_slice(&[0x60, index as u8 * 32, 0x52]);
+        }
+        code.extend_from_slice(&[0x60, length, 0x60, 0x00, 0xfd]);
+        let mut storage = fairvm.state.read().await.storage().clone();
+        storage.set_code(&contract, code).await;
+
+        let tx = Transaction::new(
+            H256::zero(),
+            Address([1u8; 20]),
+            Some(contract),
+            U256::zero(),
+            0,
+            100_000,
+            Some(U256::zero()),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        let result = fairvm.call(&tx).await;
+        assert!(!result.status);
+        assert_eq!(
+            result.revert_reason(),
+            Some(RevertReason::Error("nope".into()))
+        );
+        assert_eq!(
+            storage.get_storage_value(&contract, [0u8; 32]).await,
+            [0u8; 32]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vesting_transfer() {
+        let mut fairvm = FairVM::new();
+        let (holder, contract) = (Address([1u8; 20]), Address([5u8; 20]));
+        let mut genesis = Genesis::default();
+        genesis.add_vesting_account(
+            holder.into(),
+            1_000,
+            VestingSchedule {
+                amount: 600,
+                start: 0,
+                cliff: 0,
+                duration: 100,
+            },
+        );
+        fairvm.apply_genesis(&genesis).unwrap();
+        let mut storage = fairvm.state.read().await.storage().clone();
+        storage.set_balance(&holder, U256::from(1_000)).await;
+        storage.set_code(&contract, vec![0x00]).await;
+
+        let transfer = |value: u64| {
+            Transaction::new(
+                H256::from_low_u64_be(value),
+                holder,
+                Some(contract),
+                U256::from(value),
+                0,
+                100_000,
+                Some(U256::zero()),
+                Vec::new(),
+                Vec::new(),
+                TransactionType::Legacy,
+                1,
+                None,
+                None,
+            )
+        };
+        // 尚无区块时按时间 0 计算，锁定 600
+        assert!(fairvm.call(&transfer(400)).await.status);
+        assert!(!fairvm.call(&transfer(401)).await.status);
+    }
+
+    #[tokio::test]
+    async fn test_deployment_policy() {
+        let mut fairvm = FairVM::new();
+        let mut genesis = Genesis::default();
+        genesis.enable_deployment_policy(deployment::DeploymentConfig {
+            mode: deployment::DeploymentMode::Allowlist,
+            deployers: vec![Address([1u8; 20])],
+            governor: None,
+        });
+        fairvm.apply_genesis(&genesis).unwrap();
+        let deploy = |from: Address| {
+            Transaction::new(
+                H256::from_low_u64_be(from.0[0] as u64),
+                from,
+                None,
+                U256::zero(),
+                0,
+                100_000,
+                Some(U256::zero()),
+                // 初始化代码返回空代码
+                vec![0x60, 0x00, 0x60, 0x00, 0xf3],
+                Vec::new(),
+                TransactionType::Legacy,
+                1,
+                None,
+                None,
+            )
+        };
+
+        assert!(fairvm.call(&deploy(Address([1u8; 20]))).await.status);
+        let outsider = Address([2u8; 20]);
+        let result = fairvm.call(&deploy(outsider)).await;
+        assert!(!result.status);
+        assert_eq!(
+            result.revert_reason(),
+            Some(RevertReason::Error(
+                deployment::DeploymentError::NotAllowed(outsider).to_string()
+            ))
+        );
+
+        let error = fairvm
+            .validate_transactions(
+                &[deploy(outsider)],
+                tx_validator::ValidationStage::Mempool,
+                1,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("deployment-policy"));
+    }
+
+    #[tokio::test]
+    async fn test_block_timestamp_validation() {
+        let fairvm = FairVM::new();
+        let block = |parent_hash: H256, number: u64, timestamp: u64| blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash,
+                number,
+                timestamp,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        assert!(matches!(
+            fairvm
+                .accept_block(block(H256::zero(), 2, 100), U256::zero())
+                .await,
+            Err(FairVMError::InvalidLink(
+                blockchain::LinkError::UnexpectedNumber { expected: 1, .. }
+            ))
+        ));
+        let first = block(H256::zero(), 1, 100);
+        let parent = first.hash();
+        fairvm.accept_block(first, U256::zero()).await.unwrap();
+
+        // 未知父区块不能绕过时间戳检查
+        assert!(matches!(
+            fairvm
+                .accept_block(block(H256::repeat_byte(1), 2, 100), U256::zero())
+                .await,
+            Err(FairVMError::InvalidLink(
+                blockchain::LinkError::UnknownParent(_)
+            ))
+        ));
+        assert!(matches!(
+            fairvm
+                .accept_block(block(parent, 3, 101), U256::zero())
+                .await,
+            Err(FairVMError::InvalidLink(
+                blockchain::LinkError::UnexpectedNumber { expected: 2, .. }
+            ))
+        ));
+        assert!(matches!(
+            fairvm
+                .accept_block(block(parent, 2, 100), U256::zero())
+                .await,
+            Err(FairVMError::InvalidTimestamp(
+                blockchain::TimestampError::NotIncreasing { .. }
+            ))
+        ));
+        let future = Utc::now().timestamp() as u64 + 3600;
+        assert!(matches!(
+            fairvm
+                .accept_block(block(parent, 2, future), U256::zero())
+                .await,
+            Err(FairVMError::InvalidTimestamp(
+                blockchain::TimestampError::TooFarInFuture { .. }
+            ))
+        ));
+        let proposer = "NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg".to_string();
+        // 区块头未记录出块者时，来源不能代为指定
+        assert!(matches!(
+            fairvm
+                .accept_block_from(block(parent, 2, 101), U256::zero(), Some(proposer.clone()))
+                .await,
+            Err(FairVMError::ProposerMismatch { header: None, .. })
+        ));
+        let mut second = block(parent, 2, 101);
+        second.header.proposer = Some(proposer.clone());
+        fairvm
+            .accept_block_from(second, U256::zero(), Some(proposer))
+            .await
+            .unwrap();
+        assert!(matches!(
+            fairvm
+                .accept_block(block(parent, 2, 102), U256::zero())
+                .await,
+            Err(FairVMError::InvalidLink(
+                blockchain::LinkError::NotHead { .. }
+            ))
+        ));
+        let blockchain = fairvm.blockchain.read().await;
+        assert_eq!(blockchain.blocks().len(), 2);
+        let acceptance = blockchain.get_block(2).unwrap().acceptance.clone().unwrap();
+        assert_eq!(
+            acceptance.proposer.as_deref(),
+            Some("NodeID-7Xhw2mDxuDS44j42TCB6U5579esbSt3Lg")
+        );
+        assert!(acceptance.accepted_at >= 101);
+        assert!(blockchain
+            .get_block(1)
+            .unwrap()
+            .acceptance
+            .as_ref()
+            .unwrap()
+            .proposer
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_block_roots() {
+        let fairvm = FairVM::new();
+        let block = |state_root: H256, receipts_root: H256| blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 100,
+                transactions_root: H256::zero(),
+                state_root,
+                receipts_root,
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        let wrong = H256::repeat_byte(1);
+        assert!(matches!(
+            fairvm
+                .accept_block(block(wrong, H256::zero()), U256::zero())
+                .await,
+            Err(FairVMError::RootMismatch(blockchain::RootMismatch {
+                kind: blockchain::RootKind::State,
+                ..
+            }))
+        ));
+        // 空区块没有收据，收据根为零
+        assert!(matches!(
+            fairvm
+                .accept_block(block(H256::zero(), wrong), U256::zero())
+                .await,
+            Err(FairVMError::RootMismatch(blockchain::RootMismatch {
+                kind: blockchain::RootKind::Receipts,
+                ..
+            }))
+        ));
+        assert!(fairvm.blockchain.read().await.blocks().is_empty());
+
+        let state_root = fairvm.state_commitment().await.root();
+
+        // 根承诺激活后，为零的状态根不再视为未承诺
+        let committed = FairVM::with_config(Config {
+            chain_config: ChainConfig {
+                root_commitment_block: Some(1),
+                ..ChainConfig::default()
+            },
+            ..Config::default()
+        });
+        assert!(matches!(
+            committed
+                .accept_block(block(H256::zero(), H256::zero()), U256::zero())
+                .await,
+            Err(FairVMError::RootMismatch(blockchain::RootMismatch {
+                kind: blockchain::RootKind::State,
+                ..
+            }))
+        ));
+        committed
+            .accept_block(block(state_root, H256::zero()), U256::zero())
+            .await
+            .unwrap();
+
+        fairvm
+            .accept_block(block(state_root, H256::zero()), U256::zero())
+            .await
+            .unwrap();
+        assert_eq!(fairvm.state.read().await.get_state_root().await, state_root);
+    }
+
+    #[tokio::test]
+    async fn test_epoch_rewards() {
+        let mut claim = Transaction::new(
+            H256::from_low_u64_be(3),
+            Address::zero(),
+            Some(staking::STAKING_ADDRESS),
+            U256::zero(),
+            0,
+            staking::STAKING_CLAIM_GAS,
+            Some(U256::zero()),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        sign(&mut claim);
+        let reward_address = claim.from;
+
+        let mut genesis = Genesis::new(1);
+        genesis.enable_staking(StakingConfig {
+            validators: vec![
+                ValidatorStake {
+                    node_id: "NodeID-A".to_string(),
+                    reward_address,
+                    stake: U256::from(100),
+                },
+                ValidatorStake {
+                    node_id: "NodeID-B".to_string(),
+                    reward_address: Address([9u8; 20]),
+                    stake: U256::from(100),
+                },
+            ],
+            epoch_length: 2,
+            epoch_reward: U256::from(1_000),
+            stake_weight_percent: 50,
+            jailing: None,
+        });
+        let mut fairvm = FairVM::new();
+        fairvm.apply_genesis(&genesis).unwrap();
+        let handler = Arc::new(TestEventHandler::new());
+        fairvm.add_event_handler(handler.clone()).await;
+
+        let block = |parent_hash: H256, number: u64, transactions, proposer: Option<&str>| {
+            blockchain::Block {
+                header: blockchain::BlockHeader {
+                    parent_hash,
+                    number,
+                    timestamp: number,
+                    transactions_root: H256::zero(),
+                    state_root: H256::zero(),
+                    receipts_root: H256::zero(),
+                    difficulty: 0,
+                    block_reward: 0,
+                    proposer: proposer.map(str::to_string),
+                    arrivals_root: H256::zero(),
+                },
+                transactions,
+                arrivals: Vec::new(),
+                acceptance: None,
+            }
+        };
+        let mut parent = H256::zero();
+        for (number, proposer) in [(1, "NodeID-A"), (2, "NodeID-A")] {
+            let next = block(parent, number, Vec::new(), Some(proposer));
+            parent = next.hash();
+            // 本地记录的出块者与区块头一致
+            fairvm
+                .accept_block_from(next, U256::zero(), Some(proposer.to_string()))
+                .await
+                .unwrap();
+        }
+        // 两个区块事件 + 一个纪元奖励事件
+        assert_eq!(handler.count(), 3);
+
+        let storage = fairvm.state.read().await.storage().clone();
+        // 质押部分 500 平分，出块部分 500 全归 A
+        assert_eq!(
+            staking::pending_of(&storage, &reward_address).await,
+            U256::from(750)
+        );
+        assert_eq!(
+            staking::pending_of(&storage, &Address([9u8; 20])).await,
+            U256::from(250)
+        );
+        // B 错过了第 2 个槽位
+        let stats = uptime::stats_of(&storage, "NodeID-B").await;
+        assert_eq!((stats.proposed, stats.expected, stats.missed), (0, 1, 1));
+
+        assert!(matches!(
+            fairvm
+                .accept_block(block(parent, 3, Vec::new(), Some("NodeID-X")), U256::zero())
+                .await,
+            Err(FairVMError::InactiveProposer(_))
+        ));
+        fairvm
+            .accept_block(block(parent, 3, vec![claim], None), U256::zero())
+            .await
+            .unwrap();
+        assert_eq!(storage.get_balance(&reward_address).await, U256::from(750));
+        assert!(staking::pending_of(&storage, &reward_address)
+            .await
+            .is_zero());
+        assert_eq!(
+            staking::claimed_of(&storage, &reward_address).await,
+            U256::from(750)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_native_nft_mint() {
+        let fairvm = FairVM::new();
+        let call = NativeNftCall::Mint {
+            token_id: 1,
+            to: Address([2u8; 20]),
+            uri: "ipfs://token/1".to_string(),
+            metadata: nft::NFTMetadata {
+                name: "Fair #1".to_string(),
+                description: String::new(),
+                image: String::new(),
+                attributes: Vec::new(),
+            },
+            soulbound: false,
+            royalty: None,
+        };
+        let mut tx = Transaction::new(
+            H256::from_low_u64_be(9),
+            Address::zero(),
+            Some(NATIVE_NFT_ADDRESS),
+            U256::zero(),
+            0,
+            native_nft::NATIVE_NFT_GAS,
+            Some(U256::one()),
+            call.to_transaction_data(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        sign(&mut tx);
+        let block = blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 0,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: vec![tx],
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        fairvm.accept_block(block, U256::zero()).await.unwrap();
+
+        let receipt = fairvm
+            .state
+            .read()
+            .await
+            .get_transaction_receipt(H256::from_low_u64_be(9).as_bytes())
+            .await
+            .unwrap();
+        assert!(receipt.status);
+        assert_eq!(receipt.logs.len(), 1);
+
+        let storage = fairvm.state.read().await.storage().clone();
+        assert_eq!(
+            native_nft::owner_of(&storage, 1).await,
+            Some(Address([2u8; 20]))
+        );
+        let collection = fairvm.get_nft_contract(&NATIVE_NFT_ADDRESS).await.unwrap();
+        assert_eq!(collection.get_token(1).unwrap().metadata.name, "Fair #1");
+    }
+
+    #[tokio::test]
+    async fn test_gas_limit_governance() {
+        let mut data = gas_limit::set_target_selector().to_vec();
+        data.extend_from_slice(&[0u8; 24]);
+        data.extend_from_slice(&16_000_000u64.to_be_bytes());
+        let mut vote = Transaction::new(
+            H256::from_low_u64_be(5),
+            Address::zero(),
+            Some(gas_limit::GAS_LIMIT_ADDRESS),
+            U256::zero(),
+            0,
+            gas_limit::GAS_LIMIT_VOTE_GAS,
+            Some(U256::zero()),
+            data,
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        sign(&mut vote);
+
+        let mut genesis = Genesis::new(1);
+        genesis.gas_limit = GasLimitConfig {
+            min: 1_000_000,
+            max: 20_000_000,
+            target: 8_000_000,
+            governor: None,
+        };
+        genesis.enable_gas_limit_governance(vote.from);
+        let mut fairvm = FairVM::new();
+        fairvm.apply_genesis(&genesis).unwrap();
+
+        let block = blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 1,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: vec![vote],
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        fairvm.accept_block(block, U256::zero()).await.unwrap();
+
+        let blockchain = fairvm.blockchain.read().await;
+        let accepted = blockchain.get_block(1).unwrap();
+        assert_eq!(
+            accepted.acceptance.as_ref().unwrap().gas_limit,
+            Some(8_000_000)
+        );
+        let status = fairvm.get_gas_limit_status().await.unwrap();
+        assert_eq!(status.target, 16_000_000);
+        // 治理交易的日志进入布隆索引
+        let log_index = fairvm.log_index.read().await;
+        let governance = H160(gas_limit::GAS_LIMIT_ADDRESS.0);
+        assert_eq!(
+            log_index.candidate_blocks(0, 1, &[governance], &[]),
+            vec![1]
+        );
+        assert_eq!(
+            status.current,
+            8_000_000 + 8_000_000 / gas_limit::ADJUSTMENT_QUOTIENT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_validators() {
+        let mut fairvm = FairVM::new();
+        let validator = |id: u8| consensus::ValidatorInfo {
+            address: Address([id; 20]),
+            public_key: vec![id; 33].into(),
+            voting_power: 10,
+        };
+        assert!(fairvm
+            .update_validators(consensus::ValidatorChange::Remove {
+                address: Address([1u8; 20])
+            })
+            .await
+            .is_err());
+        fairvm
+            .set_consensus(basic::BasicConsensus::with_validators(vec![validator(1)]).unwrap())
+            .await
+            .unwrap();
+        fairvm.start().await.unwrap();
+
+        let mut subscriber = fairvm.event_manager.read().await.subscribe();
+        let change = consensus::ValidatorChange::Add {
+            validator: validator(2),
+        };
+        assert_eq!(fairvm.update_validators(change.clone()).await.unwrap(), 1);
+        let event = subscriber.recv().await.unwrap();
+        assert!(matches!(
+            event.event_type,
+            EventType::ValidatorChange { height: 1, change: ref emitted } if *emitted == change
+        ));
+
+        assert_eq!(fairvm.validators_at(None).await.unwrap().len(), 1);
+        assert_eq!(fairvm.validators_at(Some(1)).await.unwrap().len(), 2);
+        // 非法变更不发布事件
+        assert!(fairvm
+            .update_validators(consensus::ValidatorChange::SetVotingPower {
+                address: Address([3u8; 20]),
+                voting_power: 1,
+            })
+            .await
+            .is_err());
+        assert!(subscriber.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ephemeral_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.data_dir = dir.path().join("data");
+        config.ephemeral.enabled = true;
+        config.ephemeral.shutdown_after_secs = Some(1);
+        let mut fairvm = FairVM::with_config(config);
+        let mut shutdown = fairvm.shutdown_signal();
+        fairvm.start().await.unwrap();
+
+        let block = blockchain::Block {
+            header: blockchain::BlockHeader {
+                parent_hash: H256::zero(),
+                number: 1,
+                timestamp: 1,
+                transactions_root: H256::zero(),
+                state_root: H256::zero(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: vec![],
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        fairvm.accept_block(block, U256::zero()).await.unwrap();
+        assert_eq!(
+            fairvm
+                .blockchain
+                .read()
+                .await
+                .latest_block()
+                .map(|block| block.header.number),
+            Some(1)
+        );
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            shutdown.wait_for(|stop| *stop),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        fairvm.stop().await.unwrap();
+        // 数据目录从未被创建
+        assert!(!dir.path().join("data").exists());
+    }
+
+    #[tokio::test]
+    async fn test_fast_sync_from_snapshot() {
+        use fair_vm_core::config::CheckpointConfig;
+
+        let secret_key = secp256k1::SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let signer = Address::from_public_key(&secp256k1::PublicKey::from_secret_key(
+            &secp256k1::Secp256k1::new(),
+            &secret_key,
+        ));
+        let source = FairVM::new();
+        source
+            .set_snapshot_policy(Some(SnapshotPolicy::new(2, secret_key).unwrap()))
+            .await;
+        let funded = Address([1; 20]);
+        source
+            .state()
+            .read()
+            .await
+            .set_balance(&funded, U256::from(100))
+            .await
+            .unwrap();
+
+        let mut parent_hash = H256::zero();
+        for number in 1..=3 {
+            let header = blockchain::BlockHeader {
+                parent_hash,
+                number,
+                timestamp: number,
+                transactions_root: H256::zero(),
+                state_root: source.state_commitment().await.root(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            };
+            parent_hash = header.hash();
+            let block = blockchain::Block {
+                header,
+                transactions: Vec::new(),
+                arrivals: Vec::new(),
+                acceptance: None,
+            };
+            source.accept_block(block, U256::zero()).await.unwrap();
+        }
+        // 策略只在第 2 个区块冻结了快照
+        assert_eq!(source.get_checkpoint().await.unwrap().header().number, 2);
+
+        let config = |signer: Address| Config {
+            checkpoint: Some(CheckpointConfig {
+                url: String::new(),
+                trusted_signers: vec![format!("{:?}", H160::from(signer))],
+            }),
+            ..Config::default()
+        };
+        let node = FairVM::fast_sync(config(signer), &source).await.unwrap();
+        assert_eq!(
+            node.blockchain
+                .read()
+                .await
+                .latest_block()
+                .unwrap()
+                .header
+                .number,
+            2
+        );
+        assert_eq!(
+            node.state().read().await.get_balance(&funded).await,
+            U256::from(100)
+        );
+
+        // 对等节点篡改返回的区块时拒绝导入
+        struct Tampered<'a>(&'a FairVM, fn(&mut blockchain::BlockHeader));
+        #[async_trait]
+        impl SnapshotPeer for Tampered<'_> {
+            async fn request(
+                &self,
+                message: NetworkMessage,
+            ) -> Result<NetworkMessage, state_sync::StateSyncError> {
+                let mut response = self.0.request(message).await?;
+                if let NetworkMessage::BlockResponse(Some(block)) = &mut response {
+                    (self.1)(&mut block.header);
+                }
+                Ok(response)
+            }
+        }
+        assert!(matches!(
+            node.catch_up(&Tampered(&source, |header| header.number = 4))
+                .await,
+            Err(FairVMError::StateSync(
+                state_sync::StateSyncError::UnexpectedBlock {
+                    number: 4,
+                    expected: 3
+                }
+            ))
+        ));
+        assert!(matches!(
+            node.catch_up(&Tampered(&source, |header| {
+                header.parent_hash = H256::repeat_byte(1)
+            }))
+            .await,
+            Err(FairVMError::StateSync(
+                state_sync::StateSyncError::UnlinkedBlock(3)
+            ))
+        ));
+
+        assert_eq!(node.catch_up(&source).await.unwrap(), 1);
+        assert_eq!(
+            node.blockchain.read().await.latest_block().unwrap().hash(),
+            parent_hash
+        );
+        assert_eq!(node.catch_up(&source).await.unwrap(), 0);
+
+        assert!(matches!(
+            FairVM::fast_sync(config(Address([2; 20])), &source).await,
+            Err(FairVMError::Checkpoint(
+                checkpoint::CheckpointError::UntrustedSigner(_)
+            ))
+        ));
+        assert!(matches!(
+            FairVM::fast_sync(config(signer), &FairVM::new()).await,
+            Err(FairVMError::StateSync(
+                state_sync::StateSyncError::NoSnapshot
+            ))
+        ));
+    }
 }
@@ -0,0 +1,524 @@
+//! 仅同步区块头的轻节点
+//!
+//! 轻节点只下载并校验区块头（父哈希链接、高度连续、时间戳规则），
+//! 关注账户的状态通过状态证明获取，并对照最新区块头中的状态根验证。
+//! 适合资源受限的监控部署和跨链桥。
+//!
+//! 第一个区块头必须链接到可信锚点（起始区块的父区块哈希），数据源无法替换整条区块头链。
+//! 启用 `Config.light` 后 `node start` 以轻节点运行，由 [`spawn`] 按间隔同步。
+
+use crate::account::{Account, Address};
+use crate::blockchain::{BlockHeader, TimestampPolicy};
+use crate::state_proof::{ProofError, StateProof};
+use crate::FairVM;
+use async_trait::async_trait;
+use chrono::Utc;
+use ethers::types::{H160, H256};
+use fair_vm_core::config::LightConfig;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+/// 轻节点错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum LightClientError {
+    #[error("数据源错误: {0}")]
+    Source(String),
+
+    #[error("区块头 {number} 无效: {reason}")]
+    InvalidHeader { number: u64, reason: String },
+
+    #[error("区块头 {0} 未承诺状态根，无法验证账户")]
+    MissingStateRoot(u64),
+
+    #[error("账户证明无效: {0}")]
+    Proof(#[from] ProofError),
+
+    #[error("轻节点配置无效: {0}")]
+    Config(String),
+}
+
+/// 轻节点配置
+#[derive(Debug, Clone)]
+pub struct LightClientConfig {
+    /// 关注的账户
+    pub watched: Vec<Address>,
+    /// 第一个同步的区块高度
+    pub start_block: u64,
+    /// 可信锚点：第一个区块头的父哈希
+    pub trusted_parent: H256,
+    /// 每次请求的区块头数量
+    pub batch_size: u64,
+    /// 最多保留的区块头数量，超出后丢弃最旧的区块头
+    pub max_headers: usize,
+    /// 区块时间戳校验规则
+    pub timestamp_policy: TimestampPolicy,
+}
+
+impl LightClientConfig {
+    /// 从区块 1 开始同步，`trusted_parent` 为创世区块之后第一个区块的父哈希
+    pub fn new(trusted_parent: H256) -> Self {
+        Self {
+            watched: Vec::new(),
+            start_block: 1,
+            trusted_parent,
+            batch_size: 128,
+            max_headers: 10_000,
+            timestamp_policy: TimestampPolicy::default(),
+        }
+    }
+
+    /// 由节点配置生成
+    pub fn from_config(config: &LightConfig) -> Result<Self, LightClientError> {
+        config.validate().map_err(LightClientError::Config)?;
+        let anchor = config.trusted_parent.as_deref().unwrap_or_default();
+        let trusted_parent = anchor
+            .trim()
+            .parse::<H256>()
+            .map_err(|e| LightClientError::Config(format!("无效的可信锚点 {}: {}", anchor, e)))?;
+        let watched = config
+            .watched
+            .iter()
+            .map(|address| {
+                address
+                    .trim()
+                    .parse::<H160>()
+                    .map(Address::from)
+                    .map_err(|e| {
+                        LightClientError::Config(format!("无效的账户地址 {}: {}", address, e))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            watched,
+            start_block: config.start_block,
+            ..Self::new(trusted_parent)
+        })
+    }
+}
+
+/// 轻节点的数据源
+#[async_trait]
+pub trait LightSource: Send + Sync {
+    /// 从 `from` 开始的至多 `count` 个连续区块头
+    async fn headers(&self, from: u64, count: u64) -> Result<Vec<BlockHeader>, LightClientError>;
+
+    /// 账户在数据源当前状态下的证明
+    async fn state_proof(&self, address: &Address) -> Result<StateProof, LightClientError>;
+}
+
+#[async_trait]
+impl LightSource for FairVM {
+    async fn headers(&self, from: u64, count: u64) -> Result<Vec<BlockHeader>, LightClientError> {
+        let blockchain = self.blockchain();
+        let blockchain = blockchain.read().await;
+        Ok((from..from.saturating_add(count))
+            .map_while(|number| blockchain.get_block(number))
+            .map(|block| block.header.clone())
+            .collect())
+    }
+
+    async fn state_proof(&self, address: &Address) -> Result<StateProof, LightClientError> {
+        Ok(FairVM::state_proof(self, address).await)
+    }
+}
+
+/// 通过 JSON-RPC 访问全节点的数据源
+#[derive(Debug, Clone)]
+pub struct RpcLightSource {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl RpcLightSource {
+    /// 创建数据源
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+
+    pub(crate) async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, LightClientError> {
+        let response: serde_json::Value = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await
+            .map_err(|e| LightClientError::Source(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| LightClientError::Source(e.to_string()))?;
+        if let Some(error) = response.get("error") {
+            return Err(LightClientError::Source(error.to_string()));
+        }
+        serde_json::from_value(response["result"].clone())
+            .map_err(|e| LightClientError::Source(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl LightSource for RpcLightSource {
+    async fn headers(&self, from: u64, count: u64) -> Result<Vec<BlockHeader>, LightClientError> {
+        self.call("fairvm_getHeaders", json!([from, count])).await
+    }
+
+    async fn state_proof(&self, address: &Address) -> Result<StateProof, LightClientError> {
+        self.call("fairvm_getStateProof", json!([H160::from(*address)]))
+            .await
+    }
+}
+
+/// 同步结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncReport {
+    /// 新验证的区块头数量
+    pub new_headers: u64,
+    /// 最新区块高度
+    pub height: Option<u64>,
+    /// 已验证的关注账户数量
+    pub accounts: usize,
+}
+
+/// 轻节点
+pub struct LightClient<S: LightSource> {
+    source: S,
+    config: LightClientConfig,
+    headers: VecDeque<BlockHeader>,
+    /// 最新区块头对应的关注账户状态，账户不存在时为空
+    accounts: HashMap<Address, Option<Account>>,
+}
+
+impl<S: LightSource> LightClient<S> {
+    /// 创建轻节点
+    pub fn new(source: S, config: LightClientConfig) -> Self {
+        Self {
+            source,
+            config,
+            headers: VecDeque::new(),
+            accounts: HashMap::new(),
+        }
+    }
+
+    /// 添加关注的账户，下次同步时获取其状态
+    pub fn watch(&mut self, address: Address) {
+        if !self.config.watched.contains(&address) {
+            self.config.watched.push(address);
+        }
+    }
+
+    /// 最新的已验证区块头
+    pub fn latest_header(&self) -> Option<&BlockHeader> {
+        self.headers.back()
+    }
+
+    /// 最新区块高度
+    pub fn height(&self) -> Option<u64> {
+        self.latest_header().map(|header| header.number)
+    }
+
+    /// 指定高度的已验证区块头，已丢弃或尚未同步时为空
+    pub fn header(&self, number: u64) -> Option<&BlockHeader> {
+        let first = self.headers.front()?.number;
+        self.headers
+            .get(usize::try_from(number.checked_sub(first)?).ok()?)
+    }
+
+    /// 已验证的关注账户状态，外层为空表示尚未验证，内层为空表示账户不存在
+    pub fn account(&self, address: &Address) -> Option<Option<&Account>> {
+        self.accounts.get(address).map(Option::as_ref)
+    }
+
+    /// 同步新的区块头并刷新关注账户的状态
+    ///
+    /// 数据源的状态可能比已同步的区块头新，证明与状态根不一致时再同步一次区块头后重试。
+    pub async fn sync(&mut self) -> Result<SyncReport, LightClientError> {
+        let mut new_headers = self.sync_headers().await?;
+        if let Err(error) = self.refresh_accounts().await {
+            if !matches!(
+                error,
+                LightClientError::Proof(ProofError::RootMismatch { .. })
+            ) {
+                return Err(error);
+            }
+            new_headers += self.sync_headers().await?;
+            self.refresh_accounts().await?;
+        }
+        Ok(SyncReport {
+            new_headers,
+            height: self.height(),
+            accounts: self.accounts.len(),
+        })
+    }
+
+    /// 同步到数据源的最新区块头，返回新验证的区块头数量
+    pub async fn sync_headers(&mut self) -> Result<u64, LightClientError> {
+        let mut synced = 0;
+        loop {
+            let from = self.height().map_or(self.config.start_block, |h| h + 1);
+            let batch = self.source.headers(from, self.config.batch_size).await?;
+            if batch.is_empty() {
+                return Ok(synced);
+            }
+            let now = u64::try_from(Utc::now().timestamp()).unwrap_or(0);
+            for (offset, header) in batch.into_iter().enumerate() {
+                self.verify_header(&header, from + offset as u64, now)?;
+                self.headers.push_back(header);
+                if self.headers.len() > self.config.max_headers.max(1) {
+                    self.headers.pop_front();
+                }
+                synced += 1;
+            }
+        }
+    }
+
+    fn verify_header(
+        &self,
+        header: &BlockHeader,
+        expected: u64,
+        now: u64,
+    ) -> Result<(), LightClientError> {
+        let invalid = |reason: String| LightClientError::InvalidHeader {
+            number: header.number,
+            reason,
+        };
+        if header.number != expected {
+            return Err(invalid(format!("期望高度 {}", expected)));
+        }
+        let parent = self.latest_header();
+        let parent_hash = parent.map_or(self.config.trusted_parent, BlockHeader::hash);
+        if header.parent_hash != parent_hash {
+            return Err(invalid(format!("父哈希应为 {:?}", parent_hash)));
+        }
+        self.config
+            .timestamp_policy
+            .validate(header, parent, now)
+            .map_err(|e| invalid(e.to_string()))
+    }
+
+    /// 获取关注账户的证明并对照最新区块头的状态根验证
+    pub async fn refresh_accounts(&mut self) -> Result<(), LightClientError> {
+        let Some(header) = self.latest_header() else {
+            return Ok(());
+        };
+        let (number, root) = (header.number, header.state_root);
+        let mut accounts = HashMap::new();
+        for address in &self.config.watched {
+            let proof = self.source.state_proof(address).await?;
+            if proof.address != *address {
+                return Err(ProofError::AddressMismatch(proof.address).into());
+            }
+            if root.is_zero() && proof.leaf_count > 0 {
+                return Err(LightClientError::MissingStateRoot(number));
+            }
+            accounts.insert(*address, proof.verify(root)?);
+        }
+        self.accounts = accounts;
+        Ok(())
+    }
+}
+
+/// 启动轻节点同步任务，需要在 tokio 运行时中调用
+///
+/// 同步失败只记录日志，下一轮从已验证的最新区块头继续。
+pub fn spawn<S: LightSource + 'static>(
+    client: Arc<RwLock<LightClient<S>>>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match client.write().await.sync().await {
+                Ok(report) if report.new_headers > 0 => log::info!(
+                    "轻节点已同步到区块 {:?}，新验证 {} 个区块头",
+                    report.height,
+                    report.new_headers
+                ),
+                Ok(_) => {}
+                Err(e) => log::warn!("轻节点同步失败: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::Block;
+    use ethers::types::U256;
+
+    /// 以当前状态根出块
+    async fn produce(vm: &FairVM, timestamp: u64) {
+        let parent = vm
+            .blockchain()
+            .read()
+            .await
+            .latest_block()
+            .map(|block| (block.hash(), block.header.number));
+        let (parent_hash, number) = parent.map_or((H256::zero(), 1), |(hash, n)| (hash, n + 1));
+        let block = Block {
+            header: BlockHeader {
+                parent_hash,
+                number,
+                timestamp,
+                transactions_root: H256::zero(),
+                state_root: vm.state_commitment().await.root(),
+                receipts_root: H256::zero(),
+                difficulty: 0,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        };
+        vm.accept_block(block, U256::zero()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_light_sync() {
+        let vm = FairVM::new();
+        let watched = Address([1; 20]);
+        let missing = Address([9; 20]);
+        let state = vm.state();
+        state
+            .read()
+            .await
+            .set_balance(&watched, U256::from(100))
+            .await
+            .unwrap();
+        state
+            .read()
+            .await
+            .set_balance(&Address([2; 20]), U256::from(5))
+            .await
+            .unwrap();
+        for timestamp in 1..=5 {
+            produce(&vm, timestamp).await;
+        }
+
+        let mut client = LightClient::new(
+            vm,
+            LightClientConfig {
+                watched: vec![watched, missing],
+                batch_size: 2,
+                max_headers: 3,
+                ..LightClientConfig::new(H256::zero())
+            },
+        );
+        let report = client.sync().await.unwrap();
+        assert_eq!(report.new_headers, 5);
+        assert_eq!(report.height, Some(5));
+        assert!(client.header(2).is_none());
+        assert_eq!(client.header(4).unwrap().number, 4);
+        assert_eq!(
+            client.account(&watched).unwrap().unwrap().balance,
+            U256::from(100)
+        );
+        assert_eq!(client.account(&missing), Some(None));
+
+        // 状态变化后同步新区块头，账户状态随之更新
+        state
+            .read()
+            .await
+            .set_balance(&watched, U256::from(70))
+            .await
+            .unwrap();
+        produce(&client.source, 6).await;
+        assert_eq!(client.sync().await.unwrap().new_headers, 1);
+        assert_eq!(
+            client.account(&watched).unwrap().unwrap().balance,
+            U256::from(70)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unverifiable_state() {
+        let vm = FairVM::new();
+        let watched = Address([1; 20]);
+        vm.state()
+            .read()
+            .await
+            .set_balance(&watched, U256::from(1))
+            .await
+            .unwrap();
+        // 区块头未承诺状态根
+        vm.accept_block(
+            Block {
+                header: BlockHeader {
+                    parent_hash: H256::zero(),
+                    number: 1,
+                    timestamp: 1,
+                    transactions_root: H256::zero(),
+                    state_root: H256::zero(),
+                    receipts_root: H256::zero(),
+                    difficulty: 0,
+                    block_reward: 0,
+                    proposer: None,
+                    arrivals_root: H256::zero(),
+                },
+                transactions: Vec::new(),
+                arrivals: Vec::new(),
+                acceptance: None,
+            },
+            U256::zero(),
+        )
+        .await
+        .unwrap();
+
+        let mut client = LightClient::new(
+            vm,
+            LightClientConfig {
+                watched: vec![watched],
+                ..LightClientConfig::new(H256::zero())
+            },
+        );
+        assert!(matches!(
+            client.sync().await,
+            Err(LightClientError::MissingStateRoot(1))
+        ));
+
+        // 第一个区块头与可信锚点不匹配时拒绝
+        let mut client =
+            LightClient::new(client.source, LightClientConfig::new(H256::repeat_byte(1)));
+        assert!(matches!(
+            client.sync_headers().await,
+            Err(LightClientError::InvalidHeader { number: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_config_requires_anchor() {
+        let mut config = LightConfig {
+            enabled: true,
+            source_url: "http://127.0.0.1:8545".to_string(),
+            watched: vec![format!("0x{}", "01".repeat(20))],
+            ..LightConfig::default()
+        };
+        assert!(matches!(
+            LightClientConfig::from_config(&config),
+            Err(LightClientError::Config(_))
+        ));
+        config.trusted_parent = Some(format!("0x{}", "02".repeat(32)));
+        let client_config = LightClientConfig::from_config(&config).unwrap();
+        assert_eq!(client_config.trusted_parent, H256::repeat_byte(2));
+        assert_eq!(client_config.watched, vec![Address([1; 20])]);
+    }
+}
@@ -0,0 +1,155 @@
+//! 轮询式过滤器
+//!
+//! `eth_newFilter` / `eth_newBlockFilter` 安装的过滤器保存在节点内存中，每个过滤器记录下一次
+//! 轮询的起始区块，`eth_getFilterChanges` 只返回上次轮询之后的新区块中的日志或区块哈希。
+//! 超过 [`FILTER_TIMEOUT`] 未轮询的过滤器会被移除，与 geth 的行为一致。
+
+use ethers::types::{Filter, U256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// 过滤器未被轮询的最长时间
+pub const FILTER_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// 同时安装的过滤器上限
+pub const MAX_FILTERS: usize = 1024;
+
+/// 过滤器类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterKind {
+    /// 日志过滤器
+    Logs(Filter),
+    /// 新区块过滤器
+    Blocks,
+}
+
+#[derive(Debug, Clone)]
+struct InstalledFilter {
+    kind: FilterKind,
+    /// 下一次轮询的起始区块
+    next_block: u64,
+    last_poll: Instant,
+}
+
+/// 一次轮询需要检查的新区块，`from > to` 时没有新区块
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterPoll {
+    pub kind: FilterKind,
+    pub from: u64,
+    pub to: u64,
+}
+
+/// 已安装的过滤器
+#[derive(Debug, Default)]
+pub struct FilterRegistry {
+    filters: HashMap<U256, InstalledFilter>,
+    next_id: u64,
+}
+
+impl FilterRegistry {
+    /// 安装过滤器，只关注 `latest` 之后的区块，过滤器已满时返回 `None`
+    pub fn install(&mut self, kind: FilterKind, latest: u64, now: Instant) -> Option<U256> {
+        self.prune(now);
+        if self.filters.len() >= MAX_FILTERS {
+            return None;
+        }
+        self.next_id += 1;
+        let id = U256::from(self.next_id);
+        self.filters.insert(
+            id,
+            InstalledFilter {
+                kind,
+                next_block: latest + 1,
+                last_poll: now,
+            },
+        );
+        Some(id)
+    }
+
+    /// 轮询过滤器，返回上次轮询之后到 `latest` 的区块范围，过滤器不存在或已过期时返回 `None`
+    pub fn poll(&mut self, id: U256, latest: u64, now: Instant) -> Option<FilterPoll> {
+        self.prune(now);
+        let filter = self.filters.get_mut(&id)?;
+        let poll = FilterPoll {
+            kind: filter.kind.clone(),
+            from: filter.next_block,
+            to: latest,
+        };
+        filter.next_block = filter.next_block.max(latest + 1);
+        filter.last_poll = now;
+        Some(poll)
+    }
+
+    /// 日志过滤器的条件，用于 `eth_getFilterLogs`
+    pub fn logs_filter(&mut self, id: U256, now: Instant) -> Option<Filter> {
+        self.prune(now);
+        let filter = self.filters.get_mut(&id)?;
+        filter.last_poll = now;
+        match &filter.kind {
+            FilterKind::Logs(filter) => Some(filter.clone()),
+            FilterKind::Blocks => None,
+        }
+    }
+
+    /// 卸载过滤器，返回过滤器是否存在
+    pub fn uninstall(&mut self, id: U256) -> bool {
+        self.filters.remove(&id).is_some()
+    }
+
+    /// 移除超时未轮询的过滤器
+    pub fn prune(&mut self, now: Instant) {
+        self.filters
+            .retain(|_, filter| now.saturating_duration_since(filter.last_poll) < FILTER_TIMEOUT);
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_ranges() {
+        let mut registry = FilterRegistry::default();
+        let now = Instant::now();
+        let id = registry
+            .install(FilterKind::Logs(Filter::new()), 10, now)
+            .unwrap();
+        let blocks = registry.install(FilterKind::Blocks, 10, now).unwrap();
+        assert_ne!(id, blocks);
+
+        // 没有新区块
+        let poll = registry.poll(id, 10, now).unwrap();
+        assert_eq!((poll.from, poll.to), (11, 10));
+        let poll = registry.poll(id, 13, now).unwrap();
+        assert_eq!((poll.from, poll.to), (11, 13));
+        let poll = registry.poll(id, 14, now).unwrap();
+        assert_eq!((poll.from, poll.to), (14, 14));
+
+        assert!(registry.logs_filter(id, now).is_some());
+        assert!(registry.logs_filter(blocks, now).is_none());
+        assert!(registry.uninstall(id));
+        assert!(!registry.uninstall(id));
+        assert!(registry.poll(id, 14, now).is_none());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let mut registry = FilterRegistry::default();
+        let start = Instant::now();
+        let id = registry.install(FilterKind::Blocks, 0, start).unwrap();
+        let later = start + FILTER_TIMEOUT / 2;
+        assert!(registry.poll(id, 1, later).is_some());
+        // 轮询会刷新过期时间
+        assert!(registry.poll(id, 1, later + FILTER_TIMEOUT / 2).is_some());
+        assert!(registry.poll(id, 1, later + FILTER_TIMEOUT * 2).is_none());
+        assert!(registry.is_empty());
+    }
+}
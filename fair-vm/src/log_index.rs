@@ -0,0 +1,165 @@
+//! 日志布隆索引
+//!
+//! 每个区块按其全部日志的地址和主题生成 2048 位布隆过滤器，每 4096 个区块再合并为一个段
+//! 过滤器。按地址或主题查询日志时先用段过滤器跳过整段无关区块，再用区块过滤器筛出
+//! 可能包含匹配日志的区块，只读取这些区块的收据。布隆过滤器可能误报但不会漏报。
+
+use ethers::types::{Bloom, BloomInput, H160, H256};
+use std::collections::BTreeMap;
+
+/// 每个段包含的区块数
+pub const SECTION_SIZE: u64 = 4096;
+
+/// 区块所在的段
+pub fn section_of(number: u64) -> u64 {
+    number / SECTION_SIZE
+}
+
+/// 日志的布隆过滤器
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = (H160, &'a [H256])>) -> Bloom {
+    let mut bloom = Bloom::default();
+    for (address, topics) in logs {
+        bloom.accrue(BloomInput::Raw(address.as_bytes()));
+        for topic in topics {
+            bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+        }
+    }
+    bloom
+}
+
+/// 过滤器可能匹配条件：地址任一命中，且每个主题位置任一命中，空条件表示任意
+pub fn bloom_matches(bloom: &Bloom, addresses: &[H160], topics: &[Vec<H256>]) -> bool {
+    let address_match = addresses.is_empty()
+        || addresses
+            .iter()
+            .any(|address| bloom.contains_input(BloomInput::Raw(address.as_bytes())));
+    address_match
+        && topics.iter().all(|position| {
+            position.is_empty()
+                || position
+                    .iter()
+                    .any(|topic| bloom.contains_input(BloomInput::Raw(topic.as_bytes())))
+        })
+}
+
+/// 区块和段的布隆索引，只保存包含日志的区块
+#[derive(Debug, Clone, Default)]
+pub struct LogIndex {
+    blocks: BTreeMap<u64, Bloom>,
+    sections: BTreeMap<u64, Bloom>,
+}
+
+impl LogIndex {
+    /// 记录区块的日志过滤器
+    pub fn record_block(&mut self, number: u64, bloom: Bloom) {
+        if bloom == Bloom::default() {
+            return;
+        }
+        self.sections
+            .entry(section_of(number))
+            .or_default()
+            .accrue_bloom(&bloom);
+        self.blocks.insert(number, bloom);
+    }
+
+    /// 区块的日志过滤器，没有日志时为 None
+    pub fn block_bloom(&self, number: u64) -> Option<&Bloom> {
+        self.blocks.get(&number)
+    }
+
+    /// 段的日志过滤器，段内没有日志时为 None
+    pub fn section_bloom(&self, section: u64) -> Option<&Bloom> {
+        self.sections.get(&section)
+    }
+
+    /// `[from, to]` 范围内可能包含匹配日志的区块
+    pub fn candidate_blocks(
+        &self,
+        from: u64,
+        to: u64,
+        addresses: &[H160],
+        topics: &[Vec<H256>],
+    ) -> Vec<u64> {
+        if to < from {
+            return Vec::new();
+        }
+        let mut candidates = Vec::new();
+        for (&section, section_bloom) in self.sections.range(section_of(from)..=section_of(to)) {
+            if !bloom_matches(section_bloom, addresses, topics) {
+                continue;
+            }
+            let start = (section * SECTION_SIZE).max(from);
+            let end = section
+                .saturating_mul(SECTION_SIZE)
+                .saturating_add(SECTION_SIZE - 1)
+                .min(to);
+            candidates.extend(
+                self.blocks
+                    .range(start..=end)
+                    .filter(|(_, bloom)| bloom_matches(bloom, addresses, topics))
+                    .map(|(&number, _)| number),
+            );
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(n: u64) -> H256 {
+        H256::from_low_u64_be(n)
+    }
+
+    fn bloom(address: u8, topics: &[H256]) -> Bloom {
+        logs_bloom([(H160([address; 20]), topics)])
+    }
+
+    #[test]
+    fn test_bloom_matches() {
+        let bloom = bloom(1, &[topic(1), topic(2)]);
+        assert!(bloom_matches(&bloom, &[], &[]));
+        assert!(bloom_matches(&bloom, &[H160([1; 20])], &[]));
+        assert!(!bloom_matches(&bloom, &[H160([2; 20])], &[]));
+        assert!(bloom_matches(
+            &bloom,
+            &[H160([2; 20]), H160([1; 20])],
+            &[vec![], vec![topic(2)]]
+        ));
+        assert!(!bloom_matches(&bloom, &[], &[vec![topic(3)]]));
+    }
+
+    #[test]
+    fn test_candidate_blocks() {
+        let mut index = LogIndex::default();
+        index.record_block(5, bloom(1, &[topic(1)]));
+        index.record_block(10, bloom(2, &[topic(1)]));
+        index.record_block(SECTION_SIZE + 1, bloom(3, &[topic(2)]));
+        index.record_block(SECTION_SIZE + 2, Bloom::default());
+        assert!(index.block_bloom(SECTION_SIZE + 2).is_none());
+        assert!(index.section_bloom(2).is_none());
+
+        let to = SECTION_SIZE * 2;
+        assert_eq!(
+            index.candidate_blocks(0, to, &[], &[]),
+            vec![5, 10, SECTION_SIZE + 1]
+        );
+        assert_eq!(
+            index.candidate_blocks(0, to, &[H160([2; 20])], &[]),
+            vec![10]
+        );
+        assert_eq!(
+            index.candidate_blocks(0, to, &[], &[vec![topic(2)]]),
+            vec![SECTION_SIZE + 1]
+        );
+        assert_eq!(
+            index.candidate_blocks(6, to, &[], &[vec![topic(1)]]),
+            vec![10]
+        );
+        assert!(index
+            .candidate_blocks(0, to, &[H160([9; 20])], &[])
+            .is_empty());
+        assert!(index.candidate_blocks(10, 5, &[], &[]).is_empty());
+    }
+}
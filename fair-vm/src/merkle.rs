@@ -0,0 +1,293 @@
+//! 有序列表的二叉 Merkle 根
+//!
+//! 区块头的交易根、收据根、到达时间根以及账户的存储根都是有序列表的二叉 Merkle 根：叶子按顺序两两哈希，
+//! 层宽为奇数时最后一个节点直接升到上一层，根同时承诺叶子数量和列表类型，空列表的根为零。
+//! 节点哈希与状态根（[`crate::state_proof`]）相同，叶子与内部节点用前缀字节区分。
+
+use crate::account::Address;
+use crate::consensus::fcfs::ArrivalTimestamp;
+use crate::receipt::Receipt;
+use crate::state_proof::ProofError;
+use crate::storage::{Storage, StorageEntry, ITER_PAGE_SIZE};
+use crate::transaction::Transaction;
+use ethers::types::H256;
+use sha3::{Digest, Keccak256};
+
+/// 交易根的类型标签
+pub const TRANSACTIONS_TAG: &[u8] = b"fairvm-transactions";
+
+/// 收据根的类型标签
+pub const RECEIPTS_TAG: &[u8] = b"fairvm-receipts";
+
+/// 存储根的类型标签
+pub const STORAGE_TAG: &[u8] = b"fairvm-storage";
+
+/// 到达时间根的类型标签
+pub const ARRIVALS_TAG: &[u8] = b"fairvm-arrivals";
+
+/// 内部节点哈希
+pub fn node_hash(left: &H256, right: &H256) -> H256 {
+    let mut hasher = Keccak256::new();
+    hasher.update([1u8]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    H256(hasher.finalize().into())
+}
+
+/// 自底向上的各层节点，第 0 层为叶子
+pub fn layers(leaves: Vec<H256>) -> Vec<Vec<H256>> {
+    let mut layers = vec![leaves];
+    while layers.last().is_some_and(|layer| layer.len() > 1) {
+        let next = layers
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node_hash(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+        layers.push(next);
+    }
+    layers
+}
+
+/// 第 `index` 个叶子自底向上的兄弟节点，层宽为奇数时最后一个节点没有兄弟
+pub fn branch(layers: &[Vec<H256>], index: usize) -> Vec<H256> {
+    let mut siblings = Vec::new();
+    let mut position = index;
+    for layer in &layers[..layers.len().saturating_sub(1)] {
+        let sibling = position ^ 1;
+        if sibling < layer.len() {
+            siblings.push(layer[sibling]);
+        }
+        position /= 2;
+    }
+    siblings
+}
+
+/// 由叶子和兄弟节点计算树根
+pub fn branch_root(
+    leaf: H256,
+    index: u64,
+    leaf_count: u64,
+    siblings: &[H256],
+) -> Result<H256, ProofError> {
+    if index >= leaf_count {
+        return Err(ProofError::Malformed("叶子序号超出范围".into()));
+    }
+    let mut hash = leaf;
+    let mut index = index;
+    let mut width = leaf_count;
+    let mut siblings = siblings.iter();
+    while width > 1 {
+        if index % 2 == 1 {
+            let sibling = siblings
+                .next()
+                .ok_or_else(|| ProofError::Malformed("兄弟节点不足".into()))?;
+            hash = node_hash(sibling, &hash);
+        } else if index + 1 < width {
+            let sibling = siblings
+                .next()
+                .ok_or_else(|| ProofError::Malformed("兄弟节点不足".into()))?;
+            hash = node_hash(&hash, sibling);
+        }
+        index /= 2;
+        width = (width + 1) / 2;
+    }
+    if siblings.next().is_some() {
+        return Err(ProofError::Malformed("兄弟节点过多".into()));
+    }
+    Ok(hash)
+}
+
+/// 由树根和叶子数量计算带类型标签的根，空列表的根为零
+pub fn tagged_root(tag: &[u8], tree_root: H256, leaf_count: u64) -> H256 {
+    if leaf_count == 0 {
+        return H256::zero();
+    }
+    let mut hasher = Keccak256::new();
+    hasher.update(tag);
+    hasher.update(tree_root.as_bytes());
+    hasher.update(leaf_count.to_be_bytes());
+    H256(hasher.finalize().into())
+}
+
+/// 有序叶子的根
+pub fn root(tag: &[u8], leaves: Vec<H256>) -> H256 {
+    let count = leaves.len() as u64;
+    let tree_root = layers(leaves)
+        .last()
+        .and_then(|layer| layer.first())
+        .copied()
+        .unwrap_or_default();
+    tagged_root(tag, tree_root, count)
+}
+
+fn leaf(parts: &[&[u8]]) -> H256 {
+    let mut hasher = Keccak256::new();
+    hasher.update([0u8]);
+    for part in parts {
+        hasher.update(part);
+    }
+    H256(hasher.finalize().into())
+}
+
+/// 交易叶子，承诺签名内容和签名
+pub fn transaction_leaf(tx: &Transaction) -> H256 {
+    leaf(&[tx.sighash().as_bytes(), &tx.signature])
+}
+
+/// 收据叶子
+///
+/// 不包含区块哈希和高度，收据根写入区块头前即可计算。
+pub fn receipt_leaf(receipt: &Receipt) -> H256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(receipt.transaction_hash.as_bytes());
+    hasher.update([receipt.status as u8]);
+    hasher.update(receipt.gas_used.to_be_bytes());
+    hasher.update(receipt.cumulative_gas_used.to_be_bytes());
+    hasher.update(
+        receipt
+            .contract_address
+            .map(|address| address.0)
+            .unwrap_or_default(),
+    );
+    hasher.update((receipt.logs.len() as u64).to_be_bytes());
+    for log in &receipt.logs {
+        hasher.update(log.address.0);
+        hasher.update((log.topics.len() as u64).to_be_bytes());
+        for topic in &log.topics {
+            hasher.update(topic.as_bytes());
+        }
+        hasher.update((log.data.len() as u64).to_be_bytes());
+        hasher.update(&log.data);
+    }
+    let body: [u8; 32] = hasher.finalize().into();
+    leaf(&[&body])
+}
+
+/// 到达时间报告叶子，承诺报告内容和签名
+pub fn arrival_leaf(report: &ArrivalTimestamp) -> H256 {
+    leaf(&[
+        report.tx_hash.as_bytes(),
+        &report.validator.0,
+        &report.timestamp.to_be_bytes(),
+        &report.signature,
+    ])
+}
+
+/// 存储槽叶子
+pub fn storage_leaf(entry: &StorageEntry) -> H256 {
+    leaf(&[&entry.0, &entry.1])
+}
+
+/// 区块交易根
+pub fn transactions_root(transactions: &[Transaction]) -> H256 {
+    root(
+        TRANSACTIONS_TAG,
+        transactions.iter().map(transaction_leaf).collect(),
+    )
+}
+
+/// 区块收据根，收据按交易顺序排列
+pub fn receipts_root<'a>(receipts: impl IntoIterator<Item = &'a Receipt>) -> H256 {
+    root(
+        RECEIPTS_TAG,
+        receipts.into_iter().map(receipt_leaf).collect(),
+    )
+}
+
+/// 区块携带的到达时间报告的根，报告按区块内顺序排列
+pub fn arrivals_root(arrivals: &[ArrivalTimestamp]) -> H256 {
+    root(ARRIVALS_TAG, arrivals.iter().map(arrival_leaf).collect())
+}
+
+/// 按键升序排列的存储槽的根，值为零的槽视为不存在
+pub fn storage_root(entries: &[StorageEntry]) -> H256 {
+    root(
+        STORAGE_TAG,
+        entries
+            .iter()
+            .filter(|(_, value)| *value != [0u8; 32])
+            .map(storage_leaf)
+            .collect(),
+    )
+}
+
+/// 读取账户的全部存储槽计算存储根
+pub async fn account_storage_root(
+    storage: &(dyn Storage + Send + Sync),
+    address: &Address,
+) -> H256 {
+    let mut entries: Vec<StorageEntry> = Vec::new();
+    loop {
+        let after = entries.last().map(|(key, _)| *key);
+        let page = storage.storage_page(address, after, ITER_PAGE_SIZE).await;
+        let done = page.len() < ITER_PAGE_SIZE;
+        entries.extend(page);
+        if done {
+            break;
+        }
+    }
+    storage_root(&entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::State;
+
+    #[test]
+    fn test_roots() {
+        assert_eq!(root(TRANSACTIONS_TAG, Vec::new()), H256::zero());
+        let leaves: Vec<H256> = (1..=3u64).map(H256::from_low_u64_be).collect();
+        let expected = tagged_root(
+            TRANSACTIONS_TAG,
+            node_hash(&node_hash(&leaves[0], &leaves[1]), &leaves[2]),
+            3,
+        );
+        assert_eq!(root(TRANSACTIONS_TAG, leaves.clone()), expected);
+        // 顺序和类型标签都参与承诺
+        let mut reversed = leaves.clone();
+        reversed.reverse();
+        assert_ne!(root(TRANSACTIONS_TAG, reversed), expected);
+        assert_ne!(root(RECEIPTS_TAG, leaves), expected);
+    }
+
+    #[test]
+    fn test_branches() {
+        for count in 1..=9u64 {
+            let leaves: Vec<H256> = (0..count).map(H256::from_low_u64_be).collect();
+            let layers = layers(leaves.clone());
+            let tree_root = layers.last().unwrap()[0];
+            for (index, leaf) in leaves.iter().enumerate() {
+                let siblings = branch(&layers, index);
+                let computed = branch_root(*leaf, index as u64, count, &siblings).unwrap();
+                assert_eq!(computed, tree_root);
+            }
+        }
+        let layers = layers(vec![H256::zero(), H256::repeat_byte(1)]);
+        let siblings = branch(&layers, 0);
+        assert!(branch_root(H256::zero(), 2, 2, &siblings).is_err());
+        assert!(branch_root(H256::zero(), 0, 2, &[]).is_err());
+        assert!(branch_root(H256::zero(), 0, 1, &siblings).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_storage_root() {
+        let mut state = State::default();
+        let address = Address([7; 20]);
+        assert_eq!(account_storage_root(&state, &address).await, H256::zero());
+
+        state.set_storage_value(&address, [1; 32], [2; 32]).await;
+        let root = account_storage_root(&state, &address).await;
+        assert_eq!(root, storage_root(&[([1; 32], [2; 32])]));
+        // 清零的槽与从未写入的槽相同
+        state.set_storage_value(&address, [3; 32], [9; 32]).await;
+        assert_ne!(account_storage_root(&state, &address).await, root);
+        state.set_storage_value(&address, [3; 32], [0; 32]).await;
+        assert_eq!(account_storage_root(&state, &address).await, root);
+    }
+}
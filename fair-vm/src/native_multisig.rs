@@ -0,0 +1,495 @@
+//! VM 原生多签钱包
+//!
+//! 多签钱包由持有者列表、门限和盐值唯一确定，钱包地址由配置哈希派生。
+//! 持有者对提案摘要做 EIP-191 个人消息签名，签名可离线收集；任何人都可以
+//! 携带达到门限的签名向原生多签系统地址提交执行交易，由 VM 验证签名后从
+//! 钱包余额转出。钱包配置哈希和 nonce 记录在系统地址的存储槽中。
+
+use crate::account::Address;
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use ethers::types::{Bytes, Signature, H160, H256, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::BTreeSet;
+
+/// 原生多签系统合约地址
+pub const NATIVE_MULTISIG_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x03,
+]);
+
+/// 原生多签交易消耗的 gas
+pub const NATIVE_MULTISIG_GAS: u64 = 100_000;
+
+/// 多签钱包持有者数量上限
+pub const MAX_OWNERS: usize = 32;
+
+/// 原生多签错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum NativeMultisigError {
+    #[error("无效的原生多签交易: {0}")]
+    InvalidTransaction(String),
+
+    #[error("无效的多签配置: {0}")]
+    InvalidConfig(String),
+
+    #[error("多签钱包 {0} 已存在")]
+    WalletExists(Address),
+
+    #[error("多签钱包 {0} 不存在")]
+    WalletNotFound(Address),
+
+    #[error("提案 nonce 不匹配: 期望 {expected}, 实际 {actual}")]
+    NonceMismatch { expected: u64, actual: u64 },
+
+    #[error("提案链 ID 不匹配: 期望 {expected}, 实际 {actual}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+
+    #[error("无效签名: {0}")]
+    InvalidSignature(String),
+
+    #[error("{0} 不是多签钱包持有者")]
+    NotOwner(Address),
+
+    #[error("签名数不足: 需要 {required}, 实际 {actual}")]
+    ThresholdNotMet { required: usize, actual: usize },
+
+    #[error("钱包余额不足: 需要 {required}, 可用 {available}")]
+    InsufficientBalance { required: U256, available: U256 },
+}
+
+/// 多签钱包配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigConfig {
+    pub owners: Vec<Address>,
+    pub threshold: usize,
+    /// 相同持有者和门限可用不同盐值创建多个钱包
+    #[serde(default)]
+    pub salt: u64,
+}
+
+impl MultisigConfig {
+    /// 校验持有者和门限
+    pub fn validate(&self) -> Result<(), NativeMultisigError> {
+        let invalid = |reason: &str| Err(NativeMultisigError::InvalidConfig(reason.into()));
+        if self.owners.is_empty() || self.owners.len() > MAX_OWNERS {
+            return invalid("持有者数量必须在 1 到 32 之间");
+        }
+        if self.threshold == 0 || self.threshold > self.owners.len() {
+            return invalid("门限必须在 1 到持有者数量之间");
+        }
+        if self.owners.contains(&Address::zero()) {
+            return invalid("持有者不能为零地址");
+        }
+        if self.owners.iter().collect::<BTreeSet<_>>().len() != self.owners.len() {
+            return invalid("持有者重复");
+        }
+        Ok(())
+    }
+
+    /// 配置哈希，与持有者顺序无关
+    pub fn hash(&self) -> H256 {
+        let mut owners = self.owners.clone();
+        owners.sort();
+        let mut hasher = Keccak256::new();
+        hasher.update(b"fairvm-multisig");
+        for owner in &owners {
+            hasher.update(owner.0);
+        }
+        hasher.update((self.threshold as u64).to_be_bytes());
+        hasher.update(self.salt.to_be_bytes());
+        H256(hasher.finalize().into())
+    }
+
+    /// 钱包地址
+    pub fn address(&self) -> Address {
+        let mut address = Address::zero();
+        address.0.copy_from_slice(&self.hash()[12..]);
+        address
+    }
+}
+
+/// 多签转账提案
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigProposal {
+    pub chain_id: u64,
+    pub wallet: MultisigConfig,
+    pub to: Address,
+    pub value: U256,
+    /// 钱包当前 nonce，执行后加 1，防止签名被重放
+    pub nonce: u64,
+}
+
+impl MultisigProposal {
+    /// 持有者签名的提案摘要
+    pub fn digest(&self) -> H256 {
+        let mut value = [0u8; 32];
+        self.value.to_big_endian(&mut value);
+        let mut hasher = Keccak256::new();
+        hasher.update(b"fairvm-multisig-proposal");
+        hasher.update(self.chain_id.to_be_bytes());
+        hasher.update(self.wallet.address().0);
+        hasher.update(self.to.0);
+        hasher.update(value);
+        hasher.update(self.nonce.to_be_bytes());
+        H256(hasher.finalize().into())
+    }
+
+    /// 恢复签名者，要求每个签名者都是持有者且不重复
+    pub fn signers(&self, signatures: &[Bytes]) -> Result<Vec<Address>, NativeMultisigError> {
+        let digest = self.digest();
+        let mut signers = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            let signature = Signature::try_from(signature.as_ref())
+                .map_err(|e| NativeMultisigError::InvalidSignature(e.to_string()))?;
+            let signer = Address::from(
+                signature
+                    .recover(digest.as_bytes())
+                    .map_err(|e| NativeMultisigError::InvalidSignature(e.to_string()))?,
+            );
+            if !self.wallet.owners.contains(&signer) {
+                return Err(NativeMultisigError::NotOwner(signer));
+            }
+            if signers.contains(&signer) {
+                return Err(NativeMultisigError::InvalidSignature(format!(
+                    "{} 重复签名",
+                    signer
+                )));
+            }
+            signers.push(signer);
+        }
+        Ok(signers)
+    }
+
+    /// 校验签名达到门限
+    pub fn verify(&self, signatures: &[Bytes]) -> Result<Vec<Address>, NativeMultisigError> {
+        self.wallet.validate()?;
+        let signers = self.signers(signatures)?;
+        if signers.len() < self.wallet.threshold {
+            return Err(NativeMultisigError::ThresholdNotMet {
+                required: self.wallet.threshold,
+                actual: signers.len(),
+            });
+        }
+        Ok(signers)
+    }
+}
+
+/// 原生多签调用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "camelCase")]
+pub enum NativeMultisigCall {
+    /// 登记多签钱包
+    Create { wallet: MultisigConfig },
+    /// 执行达到门限的提案
+    Execute {
+        proposal: MultisigProposal,
+        signatures: Vec<Bytes>,
+    },
+}
+
+impl NativeMultisigCall {
+    /// 编码为原生多签交易数据
+    pub fn to_transaction_data(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("原生多签调用序列化失败")
+    }
+
+    /// 从原生多签交易中解析调用
+    pub fn from_transaction(tx: &Transaction) -> Result<Self, NativeMultisigError> {
+        if !is_native_multisig_transaction(tx) {
+            return Err(NativeMultisigError::InvalidTransaction(
+                "目标地址不是原生多签合约".into(),
+            ));
+        }
+        serde_json::from_slice(&tx.data)
+            .map_err(|e| NativeMultisigError::InvalidTransaction(e.to_string()))
+    }
+}
+
+/// 判断交易是否发送至原生多签合约
+pub fn is_native_multisig_transaction(tx: &Transaction) -> bool {
+    tx.to == Some(NATIVE_MULTISIG_ADDRESS)
+}
+
+fn wallet_slot(tag: &[u8], wallet: &Address) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(tag);
+    hasher.update(wallet.0);
+    hasher.finalize().into()
+}
+
+/// 钱包配置哈希存储槽
+pub fn config_slot(wallet: &Address) -> [u8; 32] {
+    wallet_slot(b"fairvm-multisig-config", wallet)
+}
+
+/// 钱包 nonce 存储槽
+pub fn nonce_slot(wallet: &Address) -> [u8; 32] {
+    wallet_slot(b"fairvm-multisig-nonce", wallet)
+}
+
+/// 查询钱包配置哈希，未登记时返回 `None`
+pub async fn config_hash_of(
+    storage: &(dyn Storage + Send + Sync),
+    wallet: &Address,
+) -> Option<H256> {
+    let word = storage
+        .get_storage_value(&NATIVE_MULTISIG_ADDRESS, config_slot(wallet))
+        .await;
+    (word != [0u8; 32]).then_some(H256(word))
+}
+
+/// 查询钱包 nonce
+pub async fn nonce_of(storage: &(dyn Storage + Send + Sync), wallet: &Address) -> u64 {
+    let word = storage
+        .get_storage_value(&NATIVE_MULTISIG_ADDRESS, nonce_slot(wallet))
+        .await;
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&word[24..]);
+    u64::from_be_bytes(bytes)
+}
+
+fn address_topic(address: &Address) -> H256 {
+    H256::from(H160::from(*address))
+}
+
+/// 执行原生多签交易，返回产生的日志
+pub async fn execute(
+    storage: &mut (dyn Storage + Send + Sync),
+    tx: &Transaction,
+    chain_id: u64,
+) -> Result<Vec<(Address, Vec<H256>, Vec<u8>)>, NativeMultisigError> {
+    match NativeMultisigCall::from_transaction(tx)? {
+        NativeMultisigCall::Create { wallet } => {
+            wallet.validate()?;
+            let address = wallet.address();
+            if config_hash_of(storage, &address).await.is_some() {
+                return Err(NativeMultisigError::WalletExists(address));
+            }
+            storage
+                .set_storage_value(
+                    &NATIVE_MULTISIG_ADDRESS,
+                    config_slot(&address),
+                    wallet.hash().0,
+                )
+                .await;
+            Ok(vec![(
+                NATIVE_MULTISIG_ADDRESS,
+                vec![
+                    H256(ethers::utils::keccak256("MultisigCreated(address)")),
+                    address_topic(&address),
+                ],
+                Vec::new(),
+            )])
+        }
+        NativeMultisigCall::Execute {
+            proposal,
+            signatures,
+        } => {
+            if proposal.chain_id != chain_id {
+                return Err(NativeMultisigError::ChainIdMismatch {
+                    expected: chain_id,
+                    actual: proposal.chain_id,
+                });
+            }
+            let wallet = proposal.wallet.address();
+            if config_hash_of(storage, &wallet).await != Some(proposal.wallet.hash()) {
+                return Err(NativeMultisigError::WalletNotFound(wallet));
+            }
+            let nonce = nonce_of(storage, &wallet).await;
+            if proposal.nonce != nonce {
+                return Err(NativeMultisigError::NonceMismatch {
+                    expected: nonce,
+                    actual: proposal.nonce,
+                });
+            }
+            proposal.verify(&signatures)?;
+
+            let available = storage.get_balance(&wallet).await;
+            if available < proposal.value {
+                return Err(NativeMultisigError::InsufficientBalance {
+                    required: proposal.value,
+                    available,
+                });
+            }
+            storage
+                .set_balance(&wallet, available - proposal.value)
+                .await;
+            let balance = storage.get_balance(&proposal.to).await;
+            storage
+                .set_balance(&proposal.to, balance.saturating_add(proposal.value))
+                .await;
+            let mut next = [0u8; 32];
+            next[24..].copy_from_slice(&(nonce + 1).to_be_bytes());
+            storage
+                .set_storage_value(&NATIVE_MULTISIG_ADDRESS, nonce_slot(&wallet), next)
+                .await;
+
+            let mut value = [0u8; 32];
+            proposal.value.to_big_endian(&mut value);
+            Ok(vec![(
+                NATIVE_MULTISIG_ADDRESS,
+                vec![
+                    H256(ethers::utils::keccak256(
+                        "MultisigExecuted(address,address,uint256,uint256)",
+                    )),
+                    address_topic(&wallet),
+                    address_topic(&proposal.to),
+                    H256::from_low_u64_be(nonce),
+                ],
+                value.to_vec(),
+            )])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+    use ethers::signers::{LocalWallet, Signer};
+
+    fn call_tx(call: &NativeMultisigCall) -> Transaction {
+        Transaction::new(
+            H256::zero(),
+            Address([9u8; 20]),
+            Some(NATIVE_MULTISIG_ADDRESS),
+            U256::zero(),
+            0,
+            NATIVE_MULTISIG_GAS,
+            Some(U256::one()),
+            call.to_transaction_data(),
+            vec![],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    fn signer(seed: u8) -> LocalWallet {
+        LocalWallet::from_bytes(&[seed; 32]).unwrap()
+    }
+
+    fn sign(wallet: &LocalWallet, proposal: &MultisigProposal) -> Bytes {
+        let signature = wallet
+            .sign_hash(ethers::utils::hash_message(proposal.digest()))
+            .unwrap();
+        signature.to_vec().into()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_execute() {
+        let owners: Vec<LocalWallet> = (1..=3).map(signer).collect();
+        let config = MultisigConfig {
+            owners: owners.iter().map(|o| Address::from(o.address())).collect(),
+            threshold: 2,
+            salt: 0,
+        };
+        let wallet = config.address();
+        let recipient = Address([7u8; 20]);
+        let mut storage = MemoryStorage::new();
+        storage.set_balance(&wallet, U256::from(1_000)).await;
+
+        let create = NativeMultisigCall::Create {
+            wallet: config.clone(),
+        };
+        execute(&mut storage, &call_tx(&create), 1).await.unwrap();
+        assert_eq!(
+            execute(&mut storage, &call_tx(&create), 1)
+                .await
+                .unwrap_err(),
+            NativeMultisigError::WalletExists(wallet)
+        );
+
+        let proposal = MultisigProposal {
+            chain_id: 1,
+            wallet: config.clone(),
+            to: recipient,
+            value: U256::from(400),
+            nonce: 0,
+        };
+        let one = vec![sign(&owners[0], &proposal)];
+        let two = vec![sign(&owners[0], &proposal), sign(&owners[2], &proposal)];
+        let execute_call = |signatures: Vec<Bytes>| NativeMultisigCall::Execute {
+            proposal: proposal.clone(),
+            signatures,
+        };
+
+        assert_eq!(
+            execute(&mut storage, &call_tx(&execute_call(one.clone())), 1)
+                .await
+                .unwrap_err(),
+            NativeMultisigError::ThresholdNotMet {
+                required: 2,
+                actual: 1
+            }
+        );
+        let outsider = vec![sign(&owners[0], &proposal), sign(&signer(9), &proposal)];
+        assert!(matches!(
+            execute(&mut storage, &call_tx(&execute_call(outsider)), 1).await,
+            Err(NativeMultisigError::NotOwner(_))
+        ));
+        assert!(matches!(
+            execute(&mut storage, &call_tx(&execute_call(two.clone())), 2).await,
+            Err(NativeMultisigError::ChainIdMismatch { .. })
+        ));
+
+        let logs = execute(&mut storage, &call_tx(&execute_call(two.clone())), 1)
+            .await
+            .unwrap();
+        assert_eq!(logs[0].1[1], address_topic(&wallet));
+        assert_eq!(storage.get_balance(&wallet).await, U256::from(600));
+        assert_eq!(storage.get_balance(&recipient).await, U256::from(400));
+        assert_eq!(nonce_of(&storage, &wallet).await, 1);
+
+        // 已执行的签名不能重放
+        assert_eq!(
+            execute(&mut storage, &call_tx(&execute_call(two)), 1)
+                .await
+                .unwrap_err(),
+            NativeMultisigError::NonceMismatch {
+                expected: 1,
+                actual: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_config() {
+        let (a, b) = (Address([1u8; 20]), Address([2u8; 20]));
+        let config = MultisigConfig {
+            owners: vec![a, b],
+            threshold: 2,
+            salt: 0,
+        };
+        let reordered = MultisigConfig {
+            owners: vec![b, a],
+            ..config.clone()
+        };
+        assert_eq!(config.address(), reordered.address());
+        assert_ne!(
+            config.address(),
+            MultisigConfig {
+                salt: 1,
+                ..config.clone()
+            }
+            .address()
+        );
+        assert!(MultisigConfig {
+            threshold: 3,
+            ..config.clone()
+        }
+        .validate()
+        .is_err());
+        assert!(MultisigConfig {
+            owners: vec![a, a],
+            threshold: 1,
+            salt: 0
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,611 @@
+//! VM 原生 NFT
+//!
+//! 原生 NFT 合约位于固定的系统地址，发送到该地址的交易（数据为 JSON 编码的
+//! [`NativeNftCall`]）由 VM 直接执行，无需部署 ERC-721 合约。所有权和余额记录在
+//! 系统地址的存储槽中，随区块状态一起提交；元数据保存在 [`NFTContract`] 中。
+//! 执行时产生标准 ERC-721 `Transfer` 日志，钱包和索引器可直接识别。
+//!
+//! 转移时 VM 依次执行 [`NativeNftPolicy`] 中的转移钩子：灵魂绑定检查、接收方
+//! 白名单检查和版税支付。策略由创世配置或治理设置，代币级的灵魂绑定标记和
+//! 版税在铸造时写入存储。
+
+use crate::account::Address;
+use crate::nft::{NFTContract, NFTMetadata, NFTStandard, NFTToken};
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use ethers::types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// 原生 NFT 系统合约地址
+pub const NATIVE_NFT_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x02,
+]);
+
+/// 系统合约占位代码（实际逻辑由 VM 原生执行）
+pub const NATIVE_NFT_CODE: &[u8] = &[0x00];
+
+/// 原生 NFT 交易消耗的 gas
+pub const NATIVE_NFT_GAS: u64 = 100_000;
+
+/// 原生 NFT 集合名称
+pub const NATIVE_NFT_NAME: &str = "FairVM Native NFT";
+
+/// 原生 NFT 集合符号
+pub const NATIVE_NFT_SYMBOL: &str = "FNFT";
+
+/// 原生 NFT 错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum NativeNftError {
+    #[error("无效的原生 NFT 交易: {0}")]
+    InvalidTransaction(String),
+
+    #[error("代币 {0} 已存在")]
+    TokenExists(u64),
+
+    #[error("代币 {0} 不存在")]
+    TokenNotFound(u64),
+
+    #[error("{sender} 不是代币 {token_id} 的持有者")]
+    NotOwner { token_id: u64, sender: Address },
+
+    #[error("代币 {0} 不可转移")]
+    Soulbound(u64),
+
+    #[error("接收方 {0} 不在转移白名单中")]
+    RecipientNotAllowed(Address),
+
+    #[error("余额不足以支付版税: 需要 {required}, 可用 {available}")]
+    InsufficientRoyalty { required: U256, available: U256 },
+}
+
+/// 版税，每次转移由发送方支付给接收者
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Royalty {
+    pub recipient: Address,
+    pub amount: U256,
+}
+
+/// 原生 NFT 转移策略
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NativeNftPolicy {
+    /// 集合级版税，与代币级版税叠加收取
+    #[serde(default)]
+    pub royalty: Option<Royalty>,
+    /// 所有代币均不可转移
+    #[serde(default)]
+    pub soulbound: bool,
+    /// 接收方白名单，为空时不限制
+    #[serde(default)]
+    pub allowlist: Vec<Address>,
+}
+
+impl NativeNftPolicy {
+    /// 接收方是否允许接收转移
+    pub fn allows(&self, to: &Address) -> bool {
+        self.allowlist.is_empty() || self.allowlist.contains(to)
+    }
+}
+
+/// 原生 NFT 调用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "camelCase")]
+pub enum NativeNftCall {
+    /// 铸造代币
+    Mint {
+        token_id: u64,
+        to: Address,
+        uri: String,
+        metadata: NFTMetadata,
+        /// 代币不可转移
+        #[serde(default)]
+        soulbound: bool,
+        /// 代币级版税
+        #[serde(default)]
+        royalty: Option<Royalty>,
+    },
+    /// 将发送者持有的代币转给他人
+    Transfer { token_id: u64, to: Address },
+}
+
+impl NativeNftCall {
+    /// 编码为原生 NFT 交易数据
+    pub fn to_transaction_data(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("原生 NFT 调用序列化失败")
+    }
+
+    /// 从原生 NFT 交易中解析调用
+    pub fn from_transaction(tx: &Transaction) -> Result<Self, NativeNftError> {
+        if !is_native_nft_transaction(tx) {
+            return Err(NativeNftError::InvalidTransaction(
+                "目标地址不是原生 NFT 合约".into(),
+            ));
+        }
+        serde_json::from_slice(&tx.data)
+            .map_err(|e| NativeNftError::InvalidTransaction(e.to_string()))
+    }
+}
+
+/// 判断交易是否发送至原生 NFT 合约
+pub fn is_native_nft_transaction(tx: &Transaction) -> bool {
+    tx.to == Some(NATIVE_NFT_ADDRESS)
+}
+
+/// 原生 NFT 集合，用于保存元数据
+pub fn native_collection() -> NFTContract {
+    NFTContract::new(
+        NATIVE_NFT_ADDRESS,
+        NATIVE_NFT_NAME.to_string(),
+        NATIVE_NFT_SYMBOL.to_string(),
+        NFTStandard::ERC721,
+    )
+}
+
+/// 代币持有者存储槽
+pub fn owner_slot(token_id: u64) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"fairvm-nft-owner");
+    hasher.update(token_id.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// 持有数量存储槽
+pub fn balance_slot(owner: &Address) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"fairvm-nft-balance");
+    hasher.update(owner.0);
+    hasher.finalize().into()
+}
+
+/// 代币属性存储槽
+fn token_slot(tag: &[u8], token_id: u64) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(tag);
+    hasher.update(token_id.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// 代币灵魂绑定标记存储槽
+pub fn soulbound_slot(token_id: u64) -> [u8; 32] {
+    token_slot(b"fairvm-nft-soulbound", token_id)
+}
+
+/// 代币版税接收者存储槽
+pub fn royalty_recipient_slot(token_id: u64) -> [u8; 32] {
+    token_slot(b"fairvm-nft-royalty-recipient", token_id)
+}
+
+/// 代币版税金额存储槽
+pub fn royalty_amount_slot(token_id: u64) -> [u8; 32] {
+    token_slot(b"fairvm-nft-royalty-amount", token_id)
+}
+
+fn word_address(word: &[u8; 32]) -> Address {
+    let mut address = Address::zero();
+    address.0.copy_from_slice(&word[12..]);
+    address
+}
+
+fn address_word(address: &Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&address.0);
+    word
+}
+
+fn u64_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn word_u64(word: &[u8; 32]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&word[24..]);
+    u64::from_be_bytes(bytes)
+}
+
+/// 查询代币持有者
+pub async fn owner_of(storage: &(dyn Storage + Send + Sync), token_id: u64) -> Option<Address> {
+    let word = storage
+        .get_storage_value(&NATIVE_NFT_ADDRESS, owner_slot(token_id))
+        .await;
+    let owner = word_address(&word);
+    (owner != Address::zero()).then_some(owner)
+}
+
+/// 查询代币是否不可转移
+pub async fn is_soulbound(storage: &(dyn Storage + Send + Sync), token_id: u64) -> bool {
+    word_u64(
+        &storage
+            .get_storage_value(&NATIVE_NFT_ADDRESS, soulbound_slot(token_id))
+            .await,
+    ) != 0
+}
+
+/// 查询代币级版税
+pub async fn royalty_of(storage: &(dyn Storage + Send + Sync), token_id: u64) -> Option<Royalty> {
+    let recipient = word_address(
+        &storage
+            .get_storage_value(&NATIVE_NFT_ADDRESS, royalty_recipient_slot(token_id))
+            .await,
+    );
+    if recipient == Address::zero() {
+        return None;
+    }
+    let amount = storage
+        .get_storage_value(&NATIVE_NFT_ADDRESS, royalty_amount_slot(token_id))
+        .await;
+    Some(Royalty {
+        recipient,
+        amount: U256::from_big_endian(&amount),
+    })
+}
+
+/// 查询地址持有的代币数
+pub async fn balance_of(storage: &(dyn Storage + Send + Sync), owner: &Address) -> u64 {
+    word_u64(
+        &storage
+            .get_storage_value(&NATIVE_NFT_ADDRESS, balance_slot(owner))
+            .await,
+    )
+}
+
+/// 原生 NFT 交易的执行结果，区块提交后应用到元数据集合
+#[derive(Debug, Clone)]
+pub enum NativeNftEffect {
+    Minted(NFTToken),
+    Transferred {
+        token_id: u64,
+        from: Address,
+        to: Address,
+    },
+}
+
+impl NativeNftEffect {
+    /// 对应的 ERC-721 `Transfer` 日志
+    pub fn logs(&self) -> Vec<(Address, Vec<H256>, Vec<u8>)> {
+        let (from, to, token_id) = match self {
+            Self::Minted(token) => (Address::zero(), token.owner, token.token_id),
+            Self::Transferred { token_id, from, to } => (*from, *to, *token_id),
+        };
+        vec![(
+            NATIVE_NFT_ADDRESS,
+            vec![
+                H256(ethers::utils::keccak256(
+                    "Transfer(address,address,uint256)",
+                )),
+                H256(address_word(&from)),
+                H256(address_word(&to)),
+                H256(u64_word(token_id)),
+            ],
+            Vec::new(),
+        )]
+    }
+
+    /// 应用到元数据集合
+    pub fn apply(self, collection: &mut NFTContract) -> Result<(), String> {
+        match self {
+            Self::Minted(token) => {
+                collection.mint(token.token_id, token.owner, token.metadata, token.uri)
+            }
+            Self::Transferred { token_id, from, to } => collection.transfer(token_id, from, to),
+        }
+    }
+}
+
+/// 执行原生 NFT 交易，所有权写入存储
+///
+/// 转移钩子全部通过后才写入存储，任一钩子拒绝时状态不变。
+pub async fn execute(
+    storage: &mut (dyn Storage + Send + Sync),
+    tx: &Transaction,
+    policy: &NativeNftPolicy,
+) -> Result<NativeNftEffect, NativeNftError> {
+    match NativeNftCall::from_transaction(tx)? {
+        NativeNftCall::Mint {
+            token_id,
+            to,
+            uri,
+            metadata,
+            soulbound,
+            royalty,
+        } => {
+            if to == Address::zero() {
+                return Err(NativeNftError::InvalidTransaction(
+                    "不能铸造到零地址".into(),
+                ));
+            }
+            if owner_of(storage, token_id).await.is_some() {
+                return Err(NativeNftError::TokenExists(token_id));
+            }
+            if royalty
+                .as_ref()
+                .is_some_and(|royalty| royalty.recipient == Address::zero())
+            {
+                return Err(NativeNftError::InvalidTransaction(
+                    "版税接收者不能为零地址".into(),
+                ));
+            }
+            set_owner(storage, token_id, None, to).await;
+            if soulbound {
+                storage
+                    .set_storage_value(&NATIVE_NFT_ADDRESS, soulbound_slot(token_id), u64_word(1))
+                    .await;
+            }
+            if let Some(royalty) = royalty {
+                let mut amount = [0u8; 32];
+                royalty.amount.to_big_endian(&mut amount);
+                storage
+                    .set_storage_value(
+                        &NATIVE_NFT_ADDRESS,
+                        royalty_recipient_slot(token_id),
+                        address_word(&royalty.recipient),
+                    )
+                    .await;
+                storage
+                    .set_storage_value(&NATIVE_NFT_ADDRESS, royalty_amount_slot(token_id), amount)
+                    .await;
+            }
+            Ok(NativeNftEffect::Minted(NFTToken {
+                token_id,
+                owner: to,
+                metadata,
+                uri,
+            }))
+        }
+        NativeNftCall::Transfer { token_id, to } => {
+            if to == Address::zero() {
+                return Err(NativeNftError::InvalidTransaction("不能转给零地址".into()));
+            }
+            let owner = owner_of(storage, token_id)
+                .await
+                .ok_or(NativeNftError::TokenNotFound(token_id))?;
+            if owner != tx.from {
+                return Err(NativeNftError::NotOwner {
+                    token_id,
+                    sender: tx.from,
+                });
+            }
+            if policy.soulbound || is_soulbound(storage, token_id).await {
+                return Err(NativeNftError::Soulbound(token_id));
+            }
+            if !policy.allows(&to) {
+                return Err(NativeNftError::RecipientNotAllowed(to));
+            }
+            let royalties: Vec<Royalty> = policy
+                .royalty
+                .iter()
+                .cloned()
+                .chain(royalty_of(storage, token_id).await)
+                .collect();
+            pay_royalties(storage, &owner, &royalties).await?;
+            set_owner(storage, token_id, Some(owner), to).await;
+            Ok(NativeNftEffect::Transferred {
+                token_id,
+                from: owner,
+                to,
+            })
+        }
+    }
+}
+
+/// 从发送方余额中支付版税
+async fn pay_royalties(
+    storage: &mut (dyn Storage + Send + Sync),
+    payer: &Address,
+    royalties: &[Royalty],
+) -> Result<(), NativeNftError> {
+    let required = royalties
+        .iter()
+        .try_fold(U256::zero(), |total, royalty| {
+            total.checked_add(royalty.amount)
+        })
+        .ok_or_else(|| NativeNftError::InvalidTransaction("版税金额溢出".into()))?;
+    let available = storage.get_balance(payer).await;
+    if available < required {
+        return Err(NativeNftError::InsufficientRoyalty {
+            required,
+            available,
+        });
+    }
+    for royalty in royalties {
+        let balance = storage.get_balance(payer).await;
+        storage.set_balance(payer, balance - royalty.amount).await;
+        let balance = storage.get_balance(&royalty.recipient).await;
+        storage
+            .set_balance(&royalty.recipient, balance.saturating_add(royalty.amount))
+            .await;
+    }
+    Ok(())
+}
+
+async fn set_owner(
+    storage: &mut (dyn Storage + Send + Sync),
+    token_id: u64,
+    from: Option<Address>,
+    to: Address,
+) {
+    if let Some(from) = from {
+        let balance = balance_of(storage, &from).await;
+        storage
+            .set_storage_value(
+                &NATIVE_NFT_ADDRESS,
+                balance_slot(&from),
+                u64_word(balance.saturating_sub(1)),
+            )
+            .await;
+    }
+    let balance = balance_of(storage, &to).await;
+    storage
+        .set_storage_value(
+            &NATIVE_NFT_ADDRESS,
+            balance_slot(&to),
+            u64_word(balance + 1),
+        )
+        .await;
+    storage
+        .set_storage_value(&NATIVE_NFT_ADDRESS, owner_slot(token_id), address_word(&to))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+    use ethers::types::U256;
+
+    fn call_tx(from: Address, call: &NativeNftCall) -> Transaction {
+        Transaction::new(
+            H256::zero(),
+            from,
+            Some(NATIVE_NFT_ADDRESS),
+            U256::zero(),
+            0,
+            NATIVE_NFT_GAS,
+            Some(U256::one()),
+            call.to_transaction_data(),
+            vec![],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    fn mint(token_id: u64, to: Address) -> NativeNftCall {
+        NativeNftCall::Mint {
+            token_id,
+            to,
+            uri: format!("ipfs://token/{}", token_id),
+            metadata: NFTMetadata {
+                name: format!("Fair #{}", token_id),
+                description: String::new(),
+                image: String::new(),
+                attributes: Vec::new(),
+            },
+            soulbound: false,
+            royalty: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mint_and_transfer() {
+        let mut storage = MemoryStorage::new();
+        let mut collection = native_collection();
+        let policy = NativeNftPolicy::default();
+        let (alice, bob) = (Address([1u8; 20]), Address([2u8; 20]));
+
+        let effect = execute(&mut storage, &call_tx(alice, &mint(7, alice)), &policy)
+            .await
+            .unwrap();
+        let logs = effect.logs();
+        assert_eq!(logs[0].0, NATIVE_NFT_ADDRESS);
+        assert_eq!(logs[0].1[1], H256::zero());
+        assert_eq!(logs[0].1[3], H256::from_low_u64_be(7));
+        effect.apply(&mut collection).unwrap();
+
+        assert_eq!(owner_of(&storage, 7).await, Some(alice));
+        assert_eq!(balance_of(&storage, &alice).await, 1);
+        assert_eq!(collection.get_token(7).unwrap().metadata.name, "Fair #7");
+        assert_eq!(
+            execute(&mut storage, &call_tx(bob, &mint(7, bob)), &policy)
+                .await
+                .unwrap_err(),
+            NativeNftError::TokenExists(7)
+        );
+
+        let transfer = NativeNftCall::Transfer {
+            token_id: 7,
+            to: bob,
+        };
+        assert!(matches!(
+            execute(&mut storage, &call_tx(bob, &transfer), &policy).await,
+            Err(NativeNftError::NotOwner { .. })
+        ));
+        execute(&mut storage, &call_tx(alice, &transfer), &policy)
+            .await
+            .unwrap()
+            .apply(&mut collection)
+            .unwrap();
+        assert_eq!(owner_of(&storage, 7).await, Some(bob));
+        assert_eq!(balance_of(&storage, &alice).await, 0);
+        assert_eq!(balance_of(&storage, &bob).await, 1);
+        assert_eq!(collection.get_token(7).unwrap().owner, bob);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_hooks() {
+        let mut storage = MemoryStorage::new();
+        let (alice, bob, carol) = (Address([1u8; 20]), Address([2u8; 20]), Address([3u8; 20]));
+        let (creator, treasury) = (Address([4u8; 20]), Address([5u8; 20]));
+        let mut policy = NativeNftPolicy {
+            royalty: Some(Royalty {
+                recipient: treasury,
+                amount: U256::from(10),
+            }),
+            soulbound: false,
+            allowlist: vec![bob],
+        };
+
+        let mut royalty_token = mint(1, alice);
+        if let NativeNftCall::Mint { royalty, .. } = &mut royalty_token {
+            *royalty = Some(Royalty {
+                recipient: creator,
+                amount: U256::from(5),
+            });
+        }
+        let mut soulbound_token = mint(2, alice);
+        if let NativeNftCall::Mint { soulbound, .. } = &mut soulbound_token {
+            *soulbound = true;
+        }
+        for call in [royalty_token, soulbound_token] {
+            execute(&mut storage, &call_tx(alice, &call), &policy)
+                .await
+                .unwrap();
+        }
+        assert!(is_soulbound(&storage, 2).await);
+        assert_eq!(royalty_of(&storage, 1).await.unwrap().recipient, creator);
+
+        let transfer = |token_id, to| NativeNftCall::Transfer { token_id, to };
+        assert_eq!(
+            execute(&mut storage, &call_tx(alice, &transfer(2, bob)), &policy)
+                .await
+                .unwrap_err(),
+            NativeNftError::Soulbound(2)
+        );
+        assert_eq!(
+            execute(&mut storage, &call_tx(alice, &transfer(1, carol)), &policy)
+                .await
+                .unwrap_err(),
+            NativeNftError::RecipientNotAllowed(carol)
+        );
+        assert_eq!(
+            execute(&mut storage, &call_tx(alice, &transfer(1, bob)), &policy)
+                .await
+                .unwrap_err(),
+            NativeNftError::InsufficientRoyalty {
+                required: U256::from(15),
+                available: U256::zero(),
+            }
+        );
+        assert_eq!(owner_of(&storage, 1).await, Some(alice));
+
+        storage.set_balance(&alice, U256::from(100)).await;
+        execute(&mut storage, &call_tx(alice, &transfer(1, bob)), &policy)
+            .await
+            .unwrap();
+        assert_eq!(owner_of(&storage, 1).await, Some(bob));
+        assert_eq!(storage.get_balance(&alice).await, U256::from(85));
+        assert_eq!(storage.get_balance(&treasury).await, U256::from(10));
+        assert_eq!(storage.get_balance(&creator).await, U256::from(5));
+
+        policy.soulbound = true;
+        assert_eq!(
+            execute(&mut storage, &call_tx(bob, &transfer(1, bob)), &policy)
+                .await
+                .unwrap_err(),
+            NativeNftError::Soulbound(1)
+        );
+    }
+}
@@ -1,7 +1,11 @@
+use crate::account::Address;
 use crate::blockchain::Block;
+use crate::compression::{self, Compression, CompressionError};
+use crate::state_sync::{SnapshotChunk, SnapshotManifest};
 use crate::transaction::Transaction;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// 网络配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,11 +20,22 @@ pub struct NetworkConfig {
     pub max_connections: usize,
     /// 最小连接数
     pub min_connections: usize,
+    /// 支持的压缩算法，按偏好排序，握手时与对等节点协商
+    #[serde(default = "compression::default_preference")]
+    pub compression: Vec<Compression>,
 }
 
 /// 网络消息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NetworkMessage {
+    /// 握手，交换帧格式版本、支持的压缩算法和本地时间
+    Handshake {
+        version: u8,
+        compression: Vec<Compression>,
+        /// 发送方的 Unix 时间（秒），用于检测时钟偏差，旧版本节点不发送时为 0
+        #[serde(default)]
+        timestamp: u64,
+    },
     /// 新区块
     NewBlock(Block),
     /// 新交易
@@ -33,6 +48,35 @@ pub enum NetworkMessage {
     GetTransaction(String),
     /// 获取交易响应
     TransactionResponse(Option<Transaction>),
+    /// 获取最新快照清单请求
+    GetSnapshot,
+    /// 快照清单响应，没有快照时为空
+    SnapshotManifest(Option<SnapshotManifest>),
+    /// 获取快照分块请求，返回 `after` 之后（不含）的账户
+    GetSnapshotChunk { number: u64, after: Option<Address> },
+    /// 快照分块响应，快照已不再提供时为空
+    SnapshotChunk(Option<SnapshotChunk>),
+}
+
+impl NetworkMessage {
+    /// 本节点的握手消息
+    pub fn handshake(config: &NetworkConfig) -> Self {
+        NetworkMessage::Handshake {
+            version: compression::FORMAT_VERSION,
+            compression: config.compression.clone(),
+            timestamp: crate::clock::now(),
+        }
+    }
+
+    /// 按协商的算法编码消息
+    pub fn encode(&self, compression: Compression) -> Result<Vec<u8>, CompressionError> {
+        compression::encode(self, compression)
+    }
+
+    /// 解码消息，帧头记录了所用的算法
+    pub fn decode(bytes: &[u8]) -> Result<Self, CompressionError> {
+        compression::decode(bytes)
+    }
 }
 
 /// 网络接口
@@ -50,3 +94,155 @@ pub trait NetworkExt: Send + Sync {
     /// 发送消息到指定节点
     async fn send_to(&self, node_id: &str, message: NetworkMessage) -> Result<(), String>;
 }
+
+/// 连接方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerDirection {
+    /// 对方发起的连接
+    Inbound,
+    /// 本节点发起的连接
+    Outbound,
+}
+
+/// 与对等节点之间的流量统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerTraffic {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// 已连接的对等节点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerInfo {
+    /// 节点 ID
+    pub node_id: String,
+    /// 对方地址
+    pub address: String,
+    pub direction: PeerDirection,
+    /// 握手交换的帧格式版本，握手完成前为空
+    pub protocol_version: Option<u8>,
+    /// 协商出的压缩算法，握手完成前为空
+    pub compression: Option<Compression>,
+    /// 建立连接的 Unix 时间戳（秒）
+    pub connected_at: u64,
+    pub traffic: PeerTraffic,
+}
+
+/// 当前连接的对等节点表，由网络层在连接、握手、收发消息时更新
+#[derive(Debug, Default)]
+pub struct PeerTable {
+    peers: BTreeMap<String, PeerInfo>,
+}
+
+impl PeerTable {
+    /// 记录新连接，同一节点重连时替换旧记录
+    pub fn connected(
+        &mut self,
+        node_id: &str,
+        address: &str,
+        direction: PeerDirection,
+        connected_at: u64,
+    ) {
+        self.peers.insert(
+            node_id.to_string(),
+            PeerInfo {
+                node_id: node_id.to_string(),
+                address: address.to_string(),
+                direction,
+                protocol_version: None,
+                compression: None,
+                connected_at,
+                traffic: PeerTraffic::default(),
+            },
+        );
+    }
+
+    /// 记录握手结果
+    pub fn handshake(&mut self, node_id: &str, version: u8, compression: Compression) {
+        if let Some(peer) = self.peers.get_mut(node_id) {
+            peer.protocol_version = Some(version);
+            peer.compression = Some(compression);
+        }
+    }
+
+    /// 记录发往对等节点的一条消息
+    pub fn record_sent(&mut self, node_id: &str, bytes: usize) {
+        if let Some(peer) = self.peers.get_mut(node_id) {
+            peer.traffic.messages_sent += 1;
+            peer.traffic.bytes_sent += bytes as u64;
+        }
+    }
+
+    /// 记录从对等节点收到的一条消息
+    pub fn record_received(&mut self, node_id: &str, bytes: usize) {
+        if let Some(peer) = self.peers.get_mut(node_id) {
+            peer.traffic.messages_received += 1;
+            peer.traffic.bytes_received += bytes as u64;
+        }
+    }
+
+    /// 对等节点断开，返回断开前的记录
+    pub fn disconnected(&mut self, node_id: &str) -> Option<PeerInfo> {
+        self.peers.remove(node_id)
+    }
+
+    /// 按节点 ID 排序的对等节点列表
+    pub fn peers(&self) -> Vec<PeerInfo> {
+        self.peers.values().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_table() {
+        let mut table = PeerTable::default();
+        table.connected("node-b", "10.0.0.2:9651", PeerDirection::Inbound, 100);
+        table.connected("node-a", "10.0.0.1:9651", PeerDirection::Outbound, 200);
+        table.handshake("node-a", compression::FORMAT_VERSION, Compression::Zstd);
+        table.record_sent("node-a", 120);
+        table.record_sent("node-a", 30);
+        table.record_received("node-a", 64);
+        // 未连接的节点不记录
+        table.record_received("node-c", 64);
+
+        let peers = table.peers();
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].node_id, "node-a");
+        assert_eq!(peers[0].protocol_version, Some(compression::FORMAT_VERSION));
+        assert_eq!(peers[0].compression, Some(Compression::Zstd));
+        assert_eq!(
+            peers[0].traffic,
+            PeerTraffic {
+                messages_sent: 2,
+                messages_received: 1,
+                bytes_sent: 150,
+                bytes_received: 64,
+            }
+        );
+        assert_eq!(peers[1].direction, PeerDirection::Inbound);
+        assert!(peers[1].protocol_version.is_none());
+
+        // 重连后统计清零
+        table.connected("node-a", "10.0.0.1:9651", PeerDirection::Inbound, 300);
+        assert_eq!(table.peers()[0].traffic, PeerTraffic::default());
+        assert!(table.disconnected("node-a").is_some());
+        assert!(table.disconnected("node-a").is_none());
+        assert_eq!(table.len(), 1);
+    }
+}
@@ -0,0 +1,265 @@
+//! 二维 nonce
+//!
+//! 智能账户需要并行发送互不依赖的交易，单一递增的 nonce 会让它们彼此排队。创世配置
+//! `nonce_mode` 为 `twoDimensional` 时，交易的 64 位 nonce 拆成高 [`NONCE_KEY_BITS`] 位的通道
+//! key 和低位的序号：每个 key 是一条独立的 nonce 通道，同一通道内的序号必须连续，不同通道互不
+//! 阻塞。key 为 0 的通道就是账户原有的 nonce，普通交易不受影响；其他通道的序号记录在 nonce
+//! 管理系统地址的存储槽中，随区块状态一起提交。
+
+use crate::account::Address;
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// nonce 管理系统地址
+pub const NONCE_MANAGER_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x06,
+]);
+
+/// 通道 key 占用 nonce 的高位数
+pub const NONCE_KEY_BITS: u32 = 16;
+
+/// 序号占用的位数
+pub const SEQUENCE_BITS: u32 = 64 - NONCE_KEY_BITS;
+
+/// 单个通道的最大序号
+pub const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+
+/// 最大通道 key
+pub const MAX_KEY: u64 = (1 << NONCE_KEY_BITS) - 1;
+
+/// 二维 nonce 错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NonceError {
+    #[error("账户 {account} 通道 {key} 的序号应为 {expected}，交易序号为 {sequence}")]
+    SequenceMismatch {
+        account: Address,
+        key: u64,
+        expected: u64,
+        sequence: u64,
+    },
+
+    #[error("通道 key {0} 超出范围，最大为 {MAX_KEY}")]
+    KeyOutOfRange(u64),
+
+    #[error("账户 {account} 通道 {key} 的序号已用尽")]
+    SequenceExhausted { account: Address, key: u64 },
+}
+
+/// nonce 模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NonceMode {
+    /// 每个账户一个递增的 nonce
+    #[default]
+    Sequential,
+    /// nonce 高位为通道 key，低位为通道内的序号
+    TwoDimensional,
+}
+
+impl NonceMode {
+    /// 交易 nonce 所在的通道，顺序模式下只有通道 0
+    pub fn lane(&self, nonce: u64) -> u64 {
+        match self {
+            Self::Sequential => 0,
+            Self::TwoDimensional => key_of(nonce),
+        }
+    }
+}
+
+/// 账户在一个 nonce 通道上的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceSequence {
+    pub mode: NonceMode,
+    pub key: u64,
+    /// 通道内的下一个序号
+    pub sequence: u64,
+    /// 下一笔交易应使用的完整 nonce
+    pub nonce: u64,
+}
+
+impl NonceSequence {
+    /// 读取账户在通道 `key` 上的状态，`key` 不能超过 [`MAX_KEY`]
+    pub async fn of(
+        storage: &(dyn Storage + Send + Sync),
+        mode: NonceMode,
+        address: &Address,
+        key: u64,
+    ) -> Self {
+        let sequence = sequence(storage, address, key).await;
+        let nonce = if key == 0 {
+            sequence
+        } else {
+            (key << SEQUENCE_BITS) | (sequence & MAX_SEQUENCE)
+        };
+        Self {
+            mode,
+            key,
+            sequence,
+            nonce,
+        }
+    }
+}
+
+/// nonce 的通道 key
+pub fn key_of(nonce: u64) -> u64 {
+    nonce >> SEQUENCE_BITS
+}
+
+/// nonce 的通道内序号
+pub fn sequence_of(nonce: u64) -> u64 {
+    nonce & MAX_SEQUENCE
+}
+
+/// 由通道 key 和序号组成 nonce
+pub fn compose(key: u64, sequence: u64) -> Result<u64, NonceError> {
+    if key > MAX_KEY {
+        return Err(NonceError::KeyOutOfRange(key));
+    }
+    Ok((key << SEQUENCE_BITS) | (sequence & MAX_SEQUENCE))
+}
+
+fn slot(address: &Address, key: u64) -> [u8; 32] {
+    let mut preimage = b"nonce.sequence".to_vec();
+    preimage.extend_from_slice(&address.0);
+    preimage.extend_from_slice(&key.to_be_bytes());
+    ethers::utils::keccak256(preimage)
+}
+
+/// 账户在通道 `key` 上的下一个序号
+///
+/// 通道 0 即账户 nonce；其他通道在顺序模式下从未使用，序号为 0。
+pub async fn sequence(storage: &(dyn Storage + Send + Sync), address: &Address, key: u64) -> u64 {
+    if key == 0 {
+        return storage.get_nonce(address).await;
+    }
+    let word = storage
+        .get_storage_value(&NONCE_MANAGER_ADDRESS, slot(address, key))
+        .await;
+    U256::from_big_endian(&word).low_u64()
+}
+
+/// 与交易 `nonce` 同一通道的下一个可用 nonce
+pub async fn expected_nonce(
+    storage: &(dyn Storage + Send + Sync),
+    mode: NonceMode,
+    address: &Address,
+    nonce: u64,
+) -> u64 {
+    match mode.lane(nonce) {
+        0 => storage.get_nonce(address).await,
+        key => (key << SEQUENCE_BITS) | sequence(storage, address, key).await,
+    }
+}
+
+/// 校验交易在非零通道上的序号并前进一位
+///
+/// 通道 0 的交易沿用账户 nonce 的原有处理，这里不做改动。
+pub async fn advance(
+    storage: &mut (dyn Storage + Send + Sync),
+    mode: NonceMode,
+    tx: &Transaction,
+) -> Result<(), NonceError> {
+    let key = mode.lane(tx.nonce);
+    if key == 0 {
+        return Ok(());
+    }
+    let expected = sequence(storage, &tx.from, key).await;
+    if sequence_of(tx.nonce) != expected {
+        return Err(NonceError::SequenceMismatch {
+            account: tx.from,
+            key,
+            expected,
+            sequence: sequence_of(tx.nonce),
+        });
+    }
+    if expected >= MAX_SEQUENCE {
+        return Err(NonceError::SequenceExhausted {
+            account: tx.from,
+            key,
+        });
+    }
+    let mut word = [0u8; 32];
+    U256::from(expected + 1).to_big_endian(&mut word);
+    storage
+        .set_storage_value(&NONCE_MANAGER_ADDRESS, slot(&tx.from, key), word)
+        .await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::State;
+    use crate::transaction::TransactionType;
+
+    fn tx(nonce: u64) -> Transaction {
+        Transaction {
+            hash: Default::default(),
+            from: Address([1u8; 20]),
+            to: Some(Address([2u8; 20])),
+            value: U256::zero(),
+            nonce,
+            gas_limit: 21000,
+            gas_price: Some(U256::one()),
+            data: vec![],
+            signature: vec![],
+            transaction_type: TransactionType::Legacy,
+            chain_id: 1,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        let nonce = compose(3, 7).unwrap();
+        assert_eq!((key_of(nonce), sequence_of(nonce)), (3, 7));
+        assert_eq!(compose(0, 5).unwrap(), 5);
+        assert_eq!(
+            compose(MAX_KEY + 1, 0),
+            Err(NonceError::KeyOutOfRange(MAX_KEY + 1))
+        );
+        assert_eq!(NonceMode::Sequential.lane(nonce), 0);
+        assert_eq!(NonceMode::TwoDimensional.lane(nonce), 3);
+    }
+
+    #[tokio::test]
+    async fn test_advance() {
+        let mut state = State::default();
+        let account = Address([1u8; 20]);
+        let mode = NonceMode::TwoDimensional;
+
+        advance(&mut state, mode, &tx(compose(3, 0).unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(sequence(&state, &account, 3).await, 1);
+        assert_eq!(
+            expected_nonce(&state, mode, &account, compose(3, 9).unwrap()).await,
+            compose(3, 1).unwrap()
+        );
+        // 其他通道互不影响
+        assert_eq!(sequence(&state, &account, 4).await, 0);
+        assert!(matches!(
+            advance(&mut state, mode, &tx(compose(3, 0).unwrap())).await,
+            Err(NonceError::SequenceMismatch {
+                key: 3,
+                expected: 1,
+                ..
+            })
+        ));
+
+        // 顺序模式下只有通道 0，由账户 nonce 处理
+        advance(
+            &mut state,
+            NonceMode::Sequential,
+            &tx(compose(5, 0).unwrap()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(sequence(&state, &account, 5).await, 0);
+    }
+}
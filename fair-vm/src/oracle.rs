@@ -0,0 +1,733 @@
+//! 价格预言机系统合约
+//!
+//! 预言机合约位于固定的系统地址，合约代码只是占位，逻辑由 VM 原生执行。白名单中的报告者
+//! 通过发送到该地址的特殊交易提交签名价格，同一轮次的报告达到法定数量后取中位数形成最新价格。
+//! 待聚合的报告和已聚合的轮次保存在该地址的存储槽中，查询接口按最新状态读取。
+
+use crate::account::Address;
+use crate::signing::{recover_signer, sign_digest};
+use crate::storage::Storage;
+use crate::transaction::{Transaction, TransactionType};
+use ethers::types::{H256, U256};
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+
+/// 预言机系统合约地址
+pub const ORACLE_CONTRACT_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x01,
+]);
+
+/// 系统合约占位代码（实际逻辑由 VM 原生执行）
+pub const ORACLE_CONTRACT_CODE: &[u8] = &[0x00];
+
+/// 预言机交易的 gas 上限
+pub const ORACLE_TX_GAS_LIMIT: u64 = 100_000;
+
+/// 每个交易对保留的历史轮次数
+pub const MAX_ROUND_HISTORY: usize = 256;
+
+/// 预言机错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum OracleError {
+    #[error("无效的价格签名: {0}")]
+    InvalidSignature(String),
+
+    #[error("未授权的报告者: {0}")]
+    UnauthorizedReporter(Address),
+
+    #[error("过期的轮次: 当前 {current}, 提交 {submitted}")]
+    StaleRound { current: u64, submitted: u64 },
+
+    #[error("重复的价格报告")]
+    DuplicateReport,
+
+    #[error("无效的预言机交易: {0}")]
+    InvalidTransaction(String),
+}
+
+/// 预言机配置（来自创世文件）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleConfig {
+    /// 白名单报告者
+    pub reporters: Vec<Address>,
+    /// 每轮所需的最少报告数
+    pub quorum: usize,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            reporters: Vec::new(),
+            quorum: 1,
+        }
+    }
+}
+
+/// 报告者签名的价格更新
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceUpdate {
+    /// 交易对，例如 "FAIR/USD"
+    pub pair: String,
+    /// 轮次编号
+    pub round_id: u64,
+    /// 价格（按 decimals 缩放）
+    pub price: U256,
+    /// 价格精度
+    pub decimals: u8,
+    /// 报告时间戳（秒）
+    pub timestamp: u64,
+    /// 报告者
+    pub reporter: Address,
+    /// 65 字节签名
+    pub signature: Vec<u8>,
+}
+
+impl PriceUpdate {
+    /// 计算待签名的消息摘要
+    pub fn digest(
+        pair: &str,
+        round_id: u64,
+        price: U256,
+        decimals: u8,
+        timestamp: u64,
+    ) -> [u8; 32] {
+        let mut price_bytes = [0u8; 32];
+        price.to_big_endian(&mut price_bytes);
+
+        let mut hasher = Keccak256::new();
+        hasher.update(b"fairvm-oracle-price");
+        hasher.update(pair.as_bytes());
+        hasher.update(round_id.to_be_bytes());
+        hasher.update(price_bytes);
+        hasher.update([decimals]);
+        hasher.update(timestamp.to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    /// 使用报告者私钥签发价格更新
+    pub fn sign(
+        secret_key: &SecretKey,
+        pair: &str,
+        round_id: u64,
+        price: U256,
+        decimals: u8,
+        timestamp: u64,
+    ) -> Result<Self, OracleError> {
+        let digest = Self::digest(pair, round_id, price, decimals, timestamp);
+        let (signature, reporter) =
+            sign_digest(secret_key, digest).map_err(OracleError::InvalidSignature)?;
+        Ok(Self {
+            pair: pair.to_string(),
+            round_id,
+            price,
+            decimals,
+            timestamp,
+            reporter,
+            signature,
+        })
+    }
+
+    /// 验证签名
+    pub fn verify(&self) -> Result<(), OracleError> {
+        let digest = Self::digest(
+            &self.pair,
+            self.round_id,
+            self.price,
+            self.decimals,
+            self.timestamp,
+        );
+        let signer =
+            recover_signer(digest, &self.signature).map_err(OracleError::InvalidSignature)?;
+        if signer != self.reporter {
+            return Err(OracleError::InvalidSignature(format!(
+                "签名者与报告者 {} 不匹配",
+                self.reporter
+            )));
+        }
+        Ok(())
+    }
+
+    /// 编码为预言机交易数据
+    pub fn to_transaction_data(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("价格更新序列化失败")
+    }
+
+    /// 从预言机交易中解析价格更新
+    pub fn from_transaction(tx: &Transaction) -> Result<Self, OracleError> {
+        if !is_oracle_transaction(tx) {
+            return Err(OracleError::InvalidTransaction(
+                "目标地址不是预言机合约".into(),
+            ));
+        }
+        let update: Self = serde_json::from_slice(&tx.data)
+            .map_err(|e| OracleError::InvalidTransaction(e.to_string()))?;
+        if update.reporter != tx.from {
+            return Err(OracleError::InvalidTransaction(
+                "交易发送者与报告者不一致".into(),
+            ));
+        }
+        Ok(update)
+    }
+}
+
+/// 判断交易是否发送至预言机合约
+pub fn is_oracle_transaction(tx: &Transaction) -> bool {
+    tx.to == Some(ORACLE_CONTRACT_ADDRESS)
+}
+
+/// 已聚合的价格轮次
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceRound {
+    /// 交易对
+    pub pair: String,
+    /// 轮次编号
+    pub round_id: u64,
+    /// 中位价格
+    pub price: U256,
+    /// 价格精度
+    pub decimals: u8,
+    /// 最新报告时间戳
+    pub updated_at: u64,
+    /// 参与报告者
+    pub reporters: Vec<Address>,
+}
+
+const ROUND_COUNT_TAG: &[u8] = b"fairvm-oracle-rounds";
+const HISTORY_TAG: &[u8] = b"fairvm-oracle-history";
+const ROUND_TAG: &[u8] = b"fairvm-oracle-round";
+const PENDING_TAG: &[u8] = b"fairvm-oracle-pending";
+
+fn slot(tag: &[u8], pair: &str, keys: &[u64]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(tag);
+    hasher.update((pair.len() as u64).to_be_bytes());
+    hasher.update(pair.as_bytes());
+    for key in keys {
+        hasher.update(key.to_be_bytes());
+    }
+    hasher.finalize().into()
+}
+
+fn u64_at(word: &[u8; 32], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&word[offset..offset + 8]);
+    u64::from_be_bytes(bytes)
+}
+
+fn address_at(word: &[u8; 32], offset: usize) -> Address {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&word[offset..offset + 20]);
+    Address(address)
+}
+
+async fn read(storage: &(dyn Storage + Send + Sync), slot: [u8; 32]) -> [u8; 32] {
+    storage
+        .get_storage_value(&ORACLE_CONTRACT_ADDRESS, slot)
+        .await
+}
+
+async fn write(storage: &mut (dyn Storage + Send + Sync), slot: [u8; 32], word: [u8; 32]) {
+    storage
+        .set_storage_value(&ORACLE_CONTRACT_ADDRESS, slot, word)
+        .await;
+}
+
+fn count_word(count: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&count.to_be_bytes());
+    word
+}
+
+/// 读取已聚合的轮次
+///
+/// 轮次占用若干连续的存储槽：价格、元数据（精度、更新时间、报告者数量）和每个报告者一个槽。
+async fn load_round(
+    storage: &(dyn Storage + Send + Sync),
+    pair: &str,
+    round_id: u64,
+) -> Option<PriceRound> {
+    let meta = read(storage, slot(ROUND_TAG, pair, &[round_id, 1])).await;
+    let count = u64_at(&meta, 24);
+    if count == 0 {
+        return None;
+    }
+    let price = U256::from_big_endian(&read(storage, slot(ROUND_TAG, pair, &[round_id, 0])).await);
+    let mut reporters = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        let word = read(storage, slot(ROUND_TAG, pair, &[round_id, 2 + index])).await;
+        reporters.push(address_at(&word, 12));
+    }
+    Some(PriceRound {
+        pair: pair.to_string(),
+        round_id,
+        price,
+        decimals: meta[0],
+        updated_at: u64_at(&meta, 8),
+        reporters,
+    })
+}
+
+async fn store_round(storage: &mut (dyn Storage + Send + Sync), round: &PriceRound) {
+    let (pair, round_id) = (round.pair.as_str(), round.round_id);
+    let mut price = [0u8; 32];
+    round.price.to_big_endian(&mut price);
+    write(storage, slot(ROUND_TAG, pair, &[round_id, 0]), price).await;
+    let mut meta = count_word(round.reporters.len() as u64);
+    meta[0] = round.decimals;
+    meta[8..16].copy_from_slice(&round.updated_at.to_be_bytes());
+    write(storage, slot(ROUND_TAG, pair, &[round_id, 1]), meta).await;
+    for (index, reporter) in round.reporters.iter().enumerate() {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(&reporter.0);
+        write(
+            storage,
+            slot(ROUND_TAG, pair, &[round_id, 2 + index as u64]),
+            word,
+        )
+        .await;
+    }
+}
+
+async fn clear_round(storage: &mut (dyn Storage + Send + Sync), pair: &str, round_id: u64) {
+    let meta = read(storage, slot(ROUND_TAG, pair, &[round_id, 1])).await;
+    for index in 0..2 + u64_at(&meta, 24) {
+        write(
+            storage,
+            slot(ROUND_TAG, pair, &[round_id, index]),
+            [0u8; 32],
+        )
+        .await;
+    }
+}
+
+/// 读取待聚合的报告，每份报告占两个槽：价格，以及报告者、精度和时间戳
+async fn load_pending(
+    storage: &(dyn Storage + Send + Sync),
+    pair: &str,
+    round_id: u64,
+) -> Vec<PriceUpdate> {
+    let count = u64_at(
+        &read(storage, slot(PENDING_TAG, pair, &[round_id, 0])).await,
+        24,
+    );
+    let mut reports = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        let price = read(storage, slot(PENDING_TAG, pair, &[round_id, 1 + 2 * index])).await;
+        let meta = read(storage, slot(PENDING_TAG, pair, &[round_id, 2 + 2 * index])).await;
+        reports.push(PriceUpdate {
+            pair: pair.to_string(),
+            round_id,
+            price: U256::from_big_endian(&price),
+            decimals: meta[20],
+            timestamp: u64_at(&meta, 24),
+            reporter: address_at(&meta, 0),
+            signature: Vec::new(),
+        });
+    }
+    reports
+}
+
+async fn push_pending(storage: &mut (dyn Storage + Send + Sync), update: &PriceUpdate, index: u64) {
+    let (pair, round_id) = (update.pair.as_str(), update.round_id);
+    let mut price = [0u8; 32];
+    update.price.to_big_endian(&mut price);
+    write(
+        storage,
+        slot(PENDING_TAG, pair, &[round_id, 1 + 2 * index]),
+        price,
+    )
+    .await;
+    let mut meta = [0u8; 32];
+    meta[..20].copy_from_slice(&update.reporter.0);
+    meta[20] = update.decimals;
+    meta[24..].copy_from_slice(&update.timestamp.to_be_bytes());
+    write(
+        storage,
+        slot(PENDING_TAG, pair, &[round_id, 2 + 2 * index]),
+        meta,
+    )
+    .await;
+    write(
+        storage,
+        slot(PENDING_TAG, pair, &[round_id, 0]),
+        count_word(index + 1),
+    )
+    .await;
+}
+
+async fn clear_pending(
+    storage: &mut (dyn Storage + Send + Sync),
+    pair: &str,
+    round_id: u64,
+    count: u64,
+) {
+    for index in 0..1 + 2 * count {
+        write(
+            storage,
+            slot(PENDING_TAG, pair, &[round_id, index]),
+            [0u8; 32],
+        )
+        .await;
+    }
+}
+
+/// 交易对已聚合的轮次数
+async fn round_count(storage: &(dyn Storage + Send + Sync), pair: &str) -> u64 {
+    u64_at(&read(storage, slot(ROUND_COUNT_TAG, pair, &[])).await, 24)
+}
+
+/// 获取最新价格轮次
+pub async fn latest_round(storage: &(dyn Storage + Send + Sync), pair: &str) -> Option<PriceRound> {
+    recent_rounds(storage, pair, 1).await.pop()
+}
+
+/// 获取指定轮次，超出保留历史的轮次返回 None
+pub async fn get_round(
+    storage: &(dyn Storage + Send + Sync),
+    pair: &str,
+    round_id: u64,
+) -> Option<PriceRound> {
+    load_round(storage, pair, round_id).await
+}
+
+/// 获取最近的若干轮次（新到旧）
+pub async fn recent_rounds(
+    storage: &(dyn Storage + Send + Sync),
+    pair: &str,
+    count: usize,
+) -> Vec<PriceRound> {
+    let total = round_count(storage, pair).await;
+    let retained = total.min(MAX_ROUND_HISTORY as u64);
+    let mut rounds = Vec::new();
+    for index in (total - retained.min(count as u64)..total).rev() {
+        let word = read(
+            storage,
+            slot(HISTORY_TAG, pair, &[index % MAX_ROUND_HISTORY as u64]),
+        )
+        .await;
+        if let Some(round) = load_round(storage, pair, u64_at(&word, 24)).await {
+            rounds.push(round);
+        }
+    }
+    rounds
+}
+
+/// 价格预言机
+///
+/// 只保存创世配置。待聚合的报告和已聚合的轮次保存在预言机系统地址的存储槽中，随区块状态
+/// 一起提交，因此进入状态根、预写日志和快照。每个交易对保留最近 [`MAX_ROUND_HISTORY`] 个轮次，
+/// 更早轮次的存储槽在写入新轮次时清除。
+#[derive(Debug, Clone, Default)]
+pub struct PriceOracle {
+    /// 预言机配置
+    config: OracleConfig,
+}
+
+impl PriceOracle {
+    /// 创建新的预言机
+    pub fn new(config: OracleConfig) -> Self {
+        Self { config }
+    }
+
+    /// 获取配置
+    pub fn config(&self) -> &OracleConfig {
+        &self.config
+    }
+
+    /// 提交价格更新，达到法定数量时返回新聚合的轮次
+    ///
+    /// 校验全部通过后才写入存储，失败的提交不修改状态。
+    pub async fn submit(
+        &self,
+        storage: &mut (dyn Storage + Send + Sync),
+        update: PriceUpdate,
+    ) -> Result<Option<PriceRound>, OracleError> {
+        update.verify()?;
+        if !self.config.reporters.contains(&update.reporter) {
+            return Err(OracleError::UnauthorizedReporter(update.reporter));
+        }
+        if let Some(latest) = latest_round(storage, &update.pair).await {
+            if update.round_id <= latest.round_id {
+                return Err(OracleError::StaleRound {
+                    current: latest.round_id,
+                    submitted: update.round_id,
+                });
+            }
+        }
+
+        let (pair, round_id) = (update.pair.clone(), update.round_id);
+        let mut reports = load_pending(storage, &pair, round_id).await;
+        if reports.iter().any(|r| r.reporter == update.reporter) {
+            return Err(OracleError::DuplicateReport);
+        }
+        if reports.len() + 1 < self.config.quorum.max(1) {
+            push_pending(storage, &update, reports.len() as u64).await;
+            return Ok(None);
+        }
+
+        // 更早轮次的待聚合报告此后提交会因轮次过期被拒绝，不再读取
+        clear_pending(storage, &pair, round_id, reports.len() as u64).await;
+        reports.push(update);
+        let round = Self::aggregate(&pair, round_id, &reports);
+        let total = round_count(storage, &pair).await;
+        let history = slot(HISTORY_TAG, &pair, &[total % MAX_ROUND_HISTORY as u64]);
+        if total >= MAX_ROUND_HISTORY as u64 {
+            let evicted = u64_at(&read(storage, history).await, 24);
+            clear_round(storage, &pair, evicted).await;
+        }
+        store_round(storage, &round).await;
+        write(storage, history, count_word(round_id)).await;
+        write(
+            storage,
+            slot(ROUND_COUNT_TAG, &pair, &[]),
+            count_word(total + 1),
+        )
+        .await;
+        Ok(Some(round))
+    }
+
+    /// 处理预言机交易
+    pub async fn apply_transaction(
+        &self,
+        storage: &mut (dyn Storage + Send + Sync),
+        tx: &Transaction,
+    ) -> Result<Option<PriceRound>, OracleError> {
+        self.submit(storage, PriceUpdate::from_transaction(tx)?)
+            .await
+    }
+
+    fn aggregate(pair: &str, round_id: u64, reports: &[PriceUpdate]) -> PriceRound {
+        let mut prices: Vec<U256> = reports.iter().map(|r| r.price).collect();
+        prices.sort_unstable();
+        PriceRound {
+            pair: pair.to_string(),
+            round_id,
+            price: prices[prices.len() / 2],
+            decimals: reports[0].decimals,
+            updated_at: reports.iter().map(|r| r.timestamp).max().unwrap_or(0),
+            reporters: reports.iter().map(|r| r.reporter).collect(),
+        }
+    }
+}
+
+/// 节点侧价格推送器，为白名单报告者构造预言机交易
+#[derive(Debug)]
+pub struct OracleReporter {
+    /// 报告者私钥
+    secret_key: SecretKey,
+    /// 链 ID
+    chain_id: u64,
+    /// 每个交易对的下一轮次
+    next_rounds: HashMap<String, u64>,
+}
+
+impl OracleReporter {
+    /// 创建新的价格推送器
+    pub fn new(secret_key: SecretKey, chain_id: u64) -> Self {
+        Self {
+            secret_key,
+            chain_id,
+            next_rounds: HashMap::new(),
+        }
+    }
+
+    /// 根据链上最新轮次同步下一轮次编号
+    pub fn sync_round(&mut self, pair: &str, latest_round: u64) {
+        let next = self.next_rounds.entry(pair.to_string()).or_insert(0);
+        *next = (*next).max(latest_round + 1);
+    }
+
+    /// 构造签名后的预言机交易
+    ///
+    /// 交易数据携带签名的价格更新，交易本身另用同一私钥对签名哈希签名，以便节点恢复发送方。
+    pub fn build_transaction(
+        &mut self,
+        pair: &str,
+        price: U256,
+        decimals: u8,
+        timestamp: u64,
+        nonce: u64,
+        gas_price: U256,
+    ) -> Result<Transaction, OracleError> {
+        let round_id = *self.next_rounds.entry(pair.to_string()).or_insert(1);
+        let update =
+            PriceUpdate::sign(&self.secret_key, pair, round_id, price, decimals, timestamp)?;
+
+        let mut tx = Transaction::new(
+            H256::zero(),
+            update.reporter,
+            Some(ORACLE_CONTRACT_ADDRESS),
+            U256::zero(),
+            nonce,
+            ORACLE_TX_GAS_LIMIT,
+            Some(gas_price),
+            update.to_transaction_data(),
+            Vec::new(),
+            TransactionType::Legacy,
+            self.chain_id,
+            None,
+            None,
+        );
+        let sighash = tx.sighash();
+        let (signature, _) =
+            sign_digest(&self.secret_key, sighash.0).map_err(OracleError::InvalidSignature)?;
+        let mut hasher = Keccak256::new();
+        hasher.update(sighash.as_bytes());
+        hasher.update(&signature);
+        tx.hash = H256::from_slice(&hasher.finalize());
+        tx.signature = signature;
+        self.next_rounds.insert(pair.to_string(), round_id + 1);
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn reporter(byte: u8) -> Address {
+        PriceUpdate::sign(&key(byte), "X", 0, U256::zero(), 0, 0)
+            .unwrap()
+            .reporter
+    }
+
+    #[tokio::test]
+    async fn test_quorum_aggregation() {
+        let oracle = PriceOracle::new(OracleConfig {
+            reporters: vec![reporter(1), reporter(2), reporter(3)],
+            quorum: 3,
+        });
+        let mut storage = MemoryStorage::default();
+
+        for (k, price) in [(1, 100u64), (2, 300)] {
+            let update = PriceUpdate::sign(&key(k), "FAIR/USD", 1, price.into(), 8, 10).unwrap();
+            assert_eq!(oracle.submit(&mut storage, update).await.unwrap(), None);
+        }
+        let duplicate = PriceUpdate::sign(&key(2), "FAIR/USD", 1, 1.into(), 8, 10).unwrap();
+        assert_eq!(
+            oracle.submit(&mut storage, duplicate).await,
+            Err(OracleError::DuplicateReport)
+        );
+        let update = PriceUpdate::sign(&key(3), "FAIR/USD", 1, 200.into(), 8, 12).unwrap();
+        let round = oracle.submit(&mut storage, update).await.unwrap().unwrap();
+        assert_eq!(round.price, U256::from(200));
+        assert_eq!(round.updated_at, 12);
+        // 轮次从存储读回
+        assert_eq!(
+            latest_round(&storage, "FAIR/USD").await,
+            Some(round.clone())
+        );
+        assert_eq!(get_round(&storage, "FAIR/USD", 1).await, Some(round));
+        assert!(load_pending(&storage, "FAIR/USD", 1).await.is_empty());
+
+        let stale = PriceUpdate::sign(&key(1), "FAIR/USD", 1, 1.into(), 8, 13).unwrap();
+        assert!(matches!(
+            oracle.submit(&mut storage, stale).await,
+            Err(OracleError::StaleRound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_round_history() {
+        let oracle = PriceOracle::new(OracleConfig {
+            reporters: vec![reporter(1)],
+            quorum: 1,
+        });
+        let mut storage = MemoryStorage::default();
+        let rounds = MAX_ROUND_HISTORY as u64 + 2;
+        for round_id in 1..=rounds {
+            let update =
+                PriceUpdate::sign(&key(1), "FAIR/USD", round_id, round_id.into(), 8, 0).unwrap();
+            oracle.submit(&mut storage, update).await.unwrap();
+        }
+        let recent = recent_rounds(&storage, "FAIR/USD", 3).await;
+        let ids: Vec<u64> = recent.iter().map(|r| r.round_id).collect();
+        assert_eq!(ids, vec![rounds, rounds - 1, rounds - 2]);
+        assert_eq!(
+            recent_rounds(&storage, "FAIR/USD", usize::MAX).await.len(),
+            MAX_ROUND_HISTORY
+        );
+        // 超出保留历史的轮次被清除
+        assert_eq!(get_round(&storage, "FAIR/USD", 2).await, None);
+        assert!(get_round(&storage, "FAIR/USD", 3).await.is_some());
+        assert!(recent_rounds(&storage, "OTHER", 3).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_reporter() {
+        let oracle = PriceOracle::new(OracleConfig {
+            reporters: vec![reporter(1)],
+            quorum: 1,
+        });
+        let mut storage = MemoryStorage::default();
+        let update = PriceUpdate::sign(&key(9), "FAIR/USD", 1, 1.into(), 8, 0).unwrap();
+        assert_eq!(
+            oracle.submit(&mut storage, update.clone()).await,
+            Err(OracleError::UnauthorizedReporter(update.reporter))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oracle_transaction() {
+        let oracle = PriceOracle::new(OracleConfig {
+            reporters: vec![reporter(1)],
+            quorum: 1,
+        });
+        let mut storage = MemoryStorage::default();
+        let update = PriceUpdate::sign(&key(1), "FAIR/USD", 7, 42.into(), 8, 0).unwrap();
+        let tx = Transaction::new(
+            H256::zero(),
+            update.reporter,
+            Some(ORACLE_CONTRACT_ADDRESS),
+            U256::zero(),
+            0,
+            100_000,
+            Some(U256::one()),
+            update.to_transaction_data(),
+            vec![],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        let round = oracle
+            .apply_transaction(&mut storage, &tx)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(round.price, U256::from(42));
+    }
+
+    #[tokio::test]
+    async fn test_reporter_builds_transactions() {
+        let oracle = PriceOracle::new(OracleConfig {
+            reporters: vec![reporter(1)],
+            quorum: 1,
+        });
+        let mut storage = MemoryStorage::default();
+        let mut pusher = OracleReporter::new(key(1), 1);
+        pusher.sync_round("FAIR/USD", 4);
+
+        let tx = pusher
+            .build_transaction("FAIR/USD", 10.into(), 8, 0, 0, U256::one())
+            .unwrap();
+        assert!(is_oracle_transaction(&tx));
+        assert_eq!(tx.recover_sender().unwrap(), reporter(1));
+        assert_eq!(
+            oracle
+                .apply_transaction(&mut storage, &tx)
+                .await
+                .unwrap()
+                .unwrap()
+                .round_id,
+            5
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! 插件协议版本握手
+//!
+//! AvalancheGo 与 VM 插件之间的 rpcchainvm 协议版本必须一致，否则插件启动后只会得到
+//! 难以理解的 gRPC 错误。启动前通过节点的 `info.getNodeVersion` 查询协议版本，
+//! 不一致时直接报错并列出兼容的 AvalancheGo 版本。
+
+use serde::Deserialize;
+
+/// 插件实现的 rpcchainvm 协议版本
+pub const PROTOCOL_VERSION: u32 = 39;
+
+/// 各协议版本对应的 AvalancheGo 版本，与 AvalancheGo 的 version/compatibility.json 一致
+pub const COMPATIBILITY: &[(u32, &[&str])] = &[
+    (39, &["v1.12.2", "v1.13.0"]),
+    (38, &["v1.11.13", "v1.12.0", "v1.12.1"]),
+    (37, &["v1.11.10", "v1.11.11", "v1.11.12"]),
+];
+
+/// 协议握手错误类型
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProtocolError {
+    #[error(
+        "AvalancheGo {node_version} 使用 rpcchainvm 协议 {node_protocol}，插件需要协议 {plugin_protocol}，兼容的 AvalancheGo 版本: {}",
+        compatible_releases(PROTOCOL_VERSION).join(", ")
+    )]
+    Mismatch {
+        node_version: String,
+        node_protocol: u32,
+        plugin_protocol: u32,
+    },
+
+    #[error("无法获取节点版本: {0}")]
+    Query(String),
+}
+
+/// 与指定协议版本兼容的 AvalancheGo 版本
+pub fn compatible_releases(protocol: u32) -> &'static [&'static str] {
+    COMPATIBILITY
+        .iter()
+        .find(|(version, _)| *version == protocol)
+        .map(|(_, releases)| *releases)
+        .unwrap_or(&[])
+}
+
+/// 按 AvalancheGo 版本查协议版本，不在兼容表中时返回 None
+pub fn release_protocol(release: &str) -> Option<u32> {
+    let release = release.trim_start_matches("avalanchego/");
+    let release = release.strip_prefix('v').unwrap_or(release);
+    COMPATIBILITY
+        .iter()
+        .find(|(_, releases)| releases.iter().any(|r| r[1..] == *release))
+        .map(|(version, _)| *version)
+}
+
+/// 检查节点的协议版本与插件是否一致
+pub fn check_protocol_version(node_version: &str, node_protocol: u32) -> Result<(), ProtocolError> {
+    if node_protocol != PROTOCOL_VERSION {
+        return Err(ProtocolError::Mismatch {
+            node_version: node_version.to_string(),
+            node_protocol,
+            plugin_protocol: PROTOCOL_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// `info.getNodeVersion` 的结果
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeVersion {
+    /// 如 `avalanchego/1.13.0`
+    pub version: String,
+    /// 协议版本，节点以字符串返回
+    pub rpc_protocol_version: String,
+}
+
+impl NodeVersion {
+    /// 检查节点与插件是否兼容
+    pub fn check(&self) -> Result<(), ProtocolError> {
+        let protocol = self.rpc_protocol_version.parse().map_err(|_| {
+            ProtocolError::Query(format!("无效的协议版本: {}", self.rpc_protocol_version))
+        })?;
+        check_protocol_version(&self.version, protocol)
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeVersionResponse {
+    result: Option<NodeVersion>,
+    error: Option<serde_json::Value>,
+}
+
+/// 查询节点版本
+pub async fn node_version(node_url: &str) -> Result<NodeVersion, ProtocolError> {
+    let url = format!("{}/ext/info", node_url.trim_end_matches('/'));
+    let response: NodeVersionResponse = reqwest::Client::new()
+        .post(&url)
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "info.getNodeVersion",
+        }))
+        .send()
+        .await
+        .map_err(|e| ProtocolError::Query(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| ProtocolError::Query(e.to_string()))?;
+    match (response.result, response.error) {
+        (Some(version), _) => Ok(version),
+        (None, error) => Err(ProtocolError::Query(
+            error.map(|e| e.to_string()).unwrap_or_default(),
+        )),
+    }
+}
+
+/// 启动前握手：查询节点协议版本，不兼容时返回错误
+pub async fn handshake(node_url: &str) -> Result<NodeVersion, ProtocolError> {
+    let version = node_version(node_url).await?;
+    version.check()?;
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_protocol_version() {
+        assert!(check_protocol_version("avalanchego/1.13.0", PROTOCOL_VERSION).is_ok());
+
+        let err = check_protocol_version("avalanchego/1.11.11", 37).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("avalanchego/1.11.11"));
+        for release in compatible_releases(PROTOCOL_VERSION) {
+            assert!(message.contains(release));
+        }
+    }
+
+    #[test]
+    fn test_release_protocol() {
+        assert_eq!(release_protocol("v1.13.0"), Some(39));
+        assert_eq!(release_protocol("avalanchego/1.12.0"), Some(38));
+        assert_eq!(release_protocol("1.0.0"), None);
+        assert!(compatible_releases(1).is_empty());
+    }
+
+    #[test]
+    fn test_node_version() {
+        let version: NodeVersion = serde_json::from_value(serde_json::json!({
+            "version": "avalanchego/1.12.1",
+            "databaseVersion": "v1.4.5",
+            "rpcProtocolVersion": "38",
+            "gitCommit": "",
+            "vmVersions": {},
+        }))
+        .unwrap();
+        assert!(matches!(
+            version.check(),
+            Err(ProtocolError::Mismatch {
+                node_protocol: 38,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+//! 自动出块
+//!
+//! 启用 `Config.block_producer` 后，节点每隔 `interval_ms` 毫秒调用 [`FairVM::produce_block`]：
+//! 从交易池取出可执行交易，在待定状态上试执行得到收据根和状态根，封装为区块交给共识引擎，
+//! 共识引擎接受后与其他节点的区块一样经 `accept_block_from` 校验并应用。
+
+use crate::account::Address;
+use crate::transaction::Transaction;
+use crate::FairVM;
+use ethers::types::{H256, U256};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+/// 按打包顺序挑选区块交易
+///
+/// `ready` 为交易池给出的打包顺序。费用上限低于 `base_fee`、在 `rejected` 中或放入后 gas 总量
+/// 超过 `gas_limit` 的交易不打包，同一发送方之后的交易因 nonce 不连续也一并跳过。
+pub fn select_transactions(
+    ready: Vec<Transaction>,
+    rejected: &HashSet<H256>,
+    base_fee: U256,
+    gas_limit: u64,
+    max_transactions: usize,
+) -> Vec<Transaction> {
+    let mut skipped: HashSet<Address> = HashSet::new();
+    let mut gas_used = 0u64;
+    let mut selected = Vec::new();
+    for tx in ready {
+        if selected.len() >= max_transactions {
+            break;
+        }
+        if skipped.contains(&tx.from) {
+            continue;
+        }
+        let fee_cap = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+        let fits = gas_used
+            .checked_add(tx.gas_limit)
+            .is_some_and(|total| total <= gas_limit);
+        if rejected.contains(&tx.hash) || fee_cap < base_fee || !fits {
+            skipped.insert(tx.from);
+            continue;
+        }
+        gas_used += tx.gas_limit;
+        selected.push(tx);
+    }
+    selected
+}
+
+/// 启动出块任务，需要在 tokio 运行时中调用
+///
+/// 出块失败只记录日志，下一轮重试。
+pub fn spawn(vm: Arc<RwLock<FairVM>>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match vm.read().await.produce_block().await {
+                Ok(Some(hash)) => log::debug!("已出块 {:?}", hash),
+                Ok(None) => {}
+                Err(e) => log::warn!("出块失败: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionType;
+
+    fn transaction(from: u8, nonce: u64, gas_price: u64, gas_limit: u64) -> Transaction {
+        Transaction::new(
+            H256::from_low_u64_be(from as u64 * 100 + nonce),
+            Address([from; 20]),
+            Some(Address([9u8; 20])),
+            U256::zero(),
+            nonce,
+            gas_limit,
+            Some(U256::from(gas_price)),
+            Vec::new(),
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_select_transactions() {
+        let ready = vec![
+            transaction(1, 0, 10, 21_000),
+            transaction(2, 0, 8, 50_000),
+            transaction(1, 1, 10, 21_000),
+            transaction(3, 0, 1, 21_000),
+            transaction(4, 0, 6, 21_000),
+            transaction(4, 1, 6, 21_000),
+        ];
+        let hashes = |txs: &[Transaction]| -> Vec<u64> {
+            txs.iter().map(|tx| tx.hash.to_low_u64_be()).collect()
+        };
+        let none = HashSet::new();
+
+        // 账户 3 的价格低于基础费用
+        let selected = select_transactions(ready.clone(), &none, U256::from(5), 200_000, 10);
+        assert_eq!(hashes(&selected), vec![100, 200, 101, 400, 401]);
+
+        // 账户 2 的交易放不下，后面的小交易仍可打包
+        let selected = select_transactions(ready.clone(), &none, U256::from(5), 60_000, 10);
+        assert_eq!(hashes(&selected), vec![100, 101]);
+
+        let selected = select_transactions(ready.clone(), &none, U256::from(5), 200_000, 2);
+        assert_eq!(hashes(&selected), vec![100, 200]);
+
+        // 被规则拒绝的交易之后同一账户的交易也跳过
+        let rejected = HashSet::from([H256::from_low_u64_be(400)]);
+        let selected = select_transactions(ready, &rejected, U256::from(5), 200_000, 10);
+        assert_eq!(hashes(&selected), vec![100, 200, 101]);
+    }
+}
@@ -0,0 +1,227 @@
+//! 交易收据
+//!
+//! 由交易执行结果生成，只在 API 边界转换为 ethers 的收据类型。
+
+use crate::account::Address;
+use crate::transaction::Transaction;
+use ethers::types::{H160, H256, U256, U64};
+use fair_vm_core::serde_hex;
+use fair_vm_core::vm::ExecutionResult;
+use serde::{Deserialize, Serialize};
+
+/// 收据日志
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptLog {
+    /// 合约地址
+    pub address: Address,
+    /// 主题
+    pub topics: Vec<H256>,
+    /// 数据
+    #[serde(with = "serde_hex::bytes")]
+    pub data: Vec<u8>,
+    /// 在区块内的索引
+    pub log_index: u64,
+}
+
+/// 交易在区块中的位置
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiptContext {
+    /// 区块哈希
+    pub block_hash: H256,
+    /// 区块高度
+    pub block_number: u64,
+    /// 交易索引
+    pub transaction_index: u64,
+    /// 区块基础费用
+    pub base_fee: U256,
+    /// 本交易之前区块内已用的 gas
+    pub cumulative_gas_before: u64,
+    /// 本交易之前区块内的日志数量
+    pub log_index_before: u64,
+}
+
+/// 交易收据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Receipt {
+    /// 交易哈希
+    pub transaction_hash: H256,
+    /// 交易索引
+    pub transaction_index: u64,
+    /// 区块哈希
+    pub block_hash: H256,
+    /// 区块高度
+    pub block_number: u64,
+    /// 发送者
+    pub from: Address,
+    /// 接收者
+    pub to: Option<Address>,
+    /// 创建的合约地址
+    pub contract_address: Option<Address>,
+    /// 是否执行成功
+    pub status: bool,
+    /// 本交易使用的 gas
+    pub gas_used: u64,
+    /// 区块内累计使用的 gas
+    pub cumulative_gas_used: u64,
+    /// 实际支付的 gas 价格
+    pub effective_gas_price: U256,
+    /// 日志
+    pub logs: Vec<ReceiptLog>,
+    /// 公平性评分
+    pub fairness_score: u64,
+}
+
+impl Receipt {
+    /// 根据执行结果生成收据
+    pub fn from_execution(
+        tx: &Transaction,
+        result: &ExecutionResult,
+        context: &ReceiptContext,
+        logs: Vec<(Address, Vec<H256>, Vec<u8>)>,
+        fairness_score: u64,
+    ) -> Self {
+        let contract_address = match tx.to {
+            None if result.status => Some(Address(
+                ethers::utils::get_contract_address(H160(tx.from.0), tx.nonce).0,
+            )),
+            _ => None,
+        };
+        let logs = logs
+            .into_iter()
+            .enumerate()
+            .map(|(i, (address, topics, data))| ReceiptLog {
+                address,
+                topics,
+                data,
+                log_index: context.log_index_before + i as u64,
+            })
+            .collect();
+
+        Self {
+            transaction_hash: tx.hash,
+            transaction_index: context.transaction_index,
+            block_hash: context.block_hash,
+            block_number: context.block_number,
+            from: tx.from,
+            to: tx.to,
+            contract_address,
+            status: result.status,
+            gas_used: result.gas_used,
+            cumulative_gas_used: context.cumulative_gas_before + result.gas_used,
+            effective_gas_price: tx.effective_gas_price(context.base_fee),
+            logs,
+            fairness_score,
+        }
+    }
+}
+
+impl From<&Receipt> for ethers::types::TransactionReceipt {
+    fn from(receipt: &Receipt) -> Self {
+        let logs = receipt
+            .logs
+            .iter()
+            .map(|log| ethers::types::Log {
+                address: H160(log.address.0),
+                topics: log.topics.clone(),
+                data: log.data.clone().into(),
+                block_hash: Some(receipt.block_hash),
+                block_number: Some(U64::from(receipt.block_number)),
+                transaction_hash: Some(receipt.transaction_hash),
+                transaction_index: Some(U64::from(receipt.transaction_index)),
+                log_index: Some(U256::from(log.log_index)),
+                ..Default::default()
+            })
+            .collect();
+
+        Self {
+            transaction_hash: receipt.transaction_hash,
+            transaction_index: U64::from(receipt.transaction_index),
+            block_hash: Some(receipt.block_hash),
+            block_number: Some(U64::from(receipt.block_number)),
+            from: H160(receipt.from.0),
+            to: receipt.to.map(|to| H160(to.0)),
+            contract_address: receipt.contract_address.map(|addr| H160(addr.0)),
+            status: Some(U64::from(receipt.status as u64)),
+            gas_used: Some(U256::from(receipt.gas_used)),
+            cumulative_gas_used: U256::from(receipt.cumulative_gas_used),
+            effective_gas_price: Some(receipt.effective_gas_price),
+            logs,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionType;
+
+    fn transaction(to: Option<Address>) -> Transaction {
+        Transaction {
+            hash: H256::from_low_u64_be(1),
+            from: Address([1u8; 20]),
+            to,
+            value: U256::zero(),
+            nonce: 0,
+            gas_limit: 100_000,
+            gas_price: None,
+            data: vec![],
+            signature: Vec::new(),
+            transaction_type: TransactionType::EIP1559,
+            chain_id: 1,
+            max_fee_per_gas: Some(U256::from(100)),
+            max_priority_fee_per_gas: Some(U256::from(2)),
+        }
+    }
+
+    fn context() -> ReceiptContext {
+        ReceiptContext {
+            block_hash: H256::from_low_u64_be(9),
+            block_number: 3,
+            transaction_index: 1,
+            base_fee: U256::from(10),
+            cumulative_gas_before: 21_000,
+            log_index_before: 4,
+        }
+    }
+
+    #[test]
+    fn test_from_execution() {
+        let result = ExecutionResult {
+            gas_used: 50_000,
+            return_data: vec![],
+            status: true,
+            gas_refunded: 0,
+        };
+        let receipt = Receipt::from_execution(
+            &transaction(None),
+            &result,
+            &context(),
+            vec![(Address([2u8; 20]), vec![H256::zero()], vec![1])],
+            7,
+        );
+
+        assert_eq!(receipt.cumulative_gas_used, 71_000);
+        assert_eq!(receipt.effective_gas_price, U256::from(12));
+        assert!(receipt.contract_address.is_some());
+        assert_eq!(receipt.logs[0].log_index, 4);
+        assert_eq!(receipt.fairness_score, 7);
+
+        let ethers_receipt = ethers::types::TransactionReceipt::from(&receipt);
+        assert_eq!(ethers_receipt.status, Some(U64::from(1)));
+        assert_eq!(ethers_receipt.logs[0].log_index, Some(U256::from(4)));
+    }
+
+    #[test]
+    fn test_failed_creation_has_no_contract_address() {
+        let result = ExecutionResult {
+            gas_used: 21_000,
+            return_data: vec![],
+            status: false,
+            gas_refunded: 0,
+        };
+        let receipt = Receipt::from_execution(&transaction(None), &result, &context(), vec![], 0);
+        assert!(receipt.contract_address.is_none());
+        assert!(!receipt.status);
+    }
+}
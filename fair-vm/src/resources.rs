@@ -0,0 +1,289 @@
+//! 资源保护
+//!
+//! [`MemoryGuard`] 定期读取进程常驻内存（RSS），超过配置的软水位时让已登记的
+//! [`Reclaimer`]（缓存、交易池等可重建的数据）各自收缩一半，超过硬水位时全部清空，
+//! 以免长时间运行的节点被系统 OOM 终止。节点本身登记签名发送方缓存，RPC 响应缓存通过
+//! [`crate::api::ApiServer::register_reclaimers`] 登记，持有待打包交易的共识引擎等组件可以
+//! 通过 [`crate::FairVM::memory_guard`] 自行登记。
+//!
+//! 启动时用 [`check_open_files`] 检查可打开的文件数，不足时拒绝启动并给出调整方法。
+//! 内存和文件数都通过 `/proc/self` 读取，其他平台上跳过检查。
+
+use crate::api::cache::RpcCache;
+use crate::verification::SenderCache;
+use fair_vm_core::config::ResourceConfig;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+
+/// 资源检查错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ResourceError {
+    #[error(
+        "可打开的文件数上限为 {limit}，低于要求的 {required}；请执行 `ulimit -n {required}`，\
+         或在 systemd 单元中设置 LimitNOFILE={required} 后重启节点"
+    )]
+    OpenFiles { limit: u64, required: u64 },
+}
+
+/// 内存压力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryPressure {
+    /// 低于软水位
+    Normal,
+    /// 超过软水位，收缩可重建的数据
+    Soft,
+    /// 超过硬水位，清空可重建的数据
+    Hard,
+}
+
+impl MemoryPressure {
+    /// 按配置的水位判断常驻内存对应的压力
+    pub fn from_usage(rss_bytes: u64, config: &ResourceConfig) -> Self {
+        let exceeds = |limit_mb: u64| limit_mb > 0 && rss_bytes >= limit_mb.saturating_mul(1 << 20);
+        if exceeds(config.memory_hard_limit_mb) {
+            MemoryPressure::Hard
+        } else if exceeds(config.memory_soft_limit_mb) {
+            MemoryPressure::Soft
+        } else {
+            MemoryPressure::Normal
+        }
+    }
+}
+
+/// 可在内存压力下释放的数据
+pub trait Reclaimer: Send + Sync {
+    /// 用于日志的名称
+    fn name(&self) -> &'static str;
+
+    /// 按压力释放数据，返回释放的条目数
+    fn reclaim(&self, pressure: MemoryPressure) -> usize;
+}
+
+/// 软水位下保留的条目数
+fn retained(len: usize, pressure: MemoryPressure) -> usize {
+    match pressure {
+        MemoryPressure::Normal => len,
+        MemoryPressure::Soft => len / 2,
+        MemoryPressure::Hard => 0,
+    }
+}
+
+impl Reclaimer for SenderCache {
+    fn name(&self) -> &'static str {
+        "sender_cache"
+    }
+
+    fn reclaim(&self, pressure: MemoryPressure) -> usize {
+        self.shrink_to(retained(self.len(), pressure))
+    }
+}
+
+impl Reclaimer for tokio::sync::Mutex<RpcCache> {
+    fn name(&self) -> &'static str {
+        "rpc_cache"
+    }
+
+    fn reclaim(&self, pressure: MemoryPressure) -> usize {
+        // 正在处理请求时跳过，下一轮检查再收缩
+        match self.try_lock() {
+            Ok(mut cache) => {
+                let len = cache.stats().entries;
+                cache.shrink_to(retained(len, pressure))
+            }
+            Err(_) => 0,
+        }
+    }
+}
+
+/// 内存水位保护，克隆后共享同一组登记
+#[derive(Clone, Default)]
+pub struct MemoryGuard {
+    reclaimers: Arc<Mutex<Vec<Arc<dyn Reclaimer>>>>,
+}
+
+impl std::fmt::Debug for MemoryGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryGuard")
+            .field("reclaimers", &self.lock().len())
+            .finish()
+    }
+}
+
+impl MemoryGuard {
+    /// 登记可释放的数据
+    pub fn register(&self, reclaimer: Arc<dyn Reclaimer>) {
+        self.lock().push(reclaimer);
+    }
+
+    /// 按压力释放全部已登记的数据，返回各项释放的条目数
+    pub fn reclaim(&self, pressure: MemoryPressure) -> Vec<(&'static str, usize)> {
+        if pressure == MemoryPressure::Normal {
+            return Vec::new();
+        }
+        let reclaimers = self.lock().clone();
+        reclaimers
+            .iter()
+            .map(|reclaimer| (reclaimer.name(), reclaimer.reclaim(pressure)))
+            .collect()
+    }
+
+    /// 启动后台检查任务，需要在 tokio 运行时中调用；无法读取内存用量时任务直接退出
+    pub fn spawn_monitor(&self, config: ResourceConfig) -> JoinHandle<()> {
+        let guard = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(config.check_interval_secs));
+            loop {
+                ticker.tick().await;
+                let Some(rss) = resident_memory() else {
+                    log::warn!("无法读取进程内存用量，已停止内存水位检查");
+                    return;
+                };
+                let pressure = MemoryPressure::from_usage(rss, &config);
+                if pressure == MemoryPressure::Normal {
+                    continue;
+                }
+                let released = guard.reclaim(pressure);
+                log::warn!(
+                    "常驻内存 {} MiB 超过{}水位，已释放: {:?}",
+                    rss >> 20,
+                    if pressure == MemoryPressure::Hard {
+                        "硬"
+                    } else {
+                        "软"
+                    },
+                    released
+                );
+            }
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Arc<dyn Reclaimer>>> {
+        self.reclaimers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 进程常驻内存字节数
+pub fn resident_memory() -> Option<u64> {
+    parse_vm_rss(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
+
+/// 解析 `/proc/self/status` 中的 `VmRSS` 行（单位 kB）
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// 进程可打开的文件数（软限制），无限制时为 `u64::MAX`
+pub fn open_files_limit() -> Option<u64> {
+    parse_open_files(&std::fs::read_to_string("/proc/self/limits").ok()?)
+}
+
+/// 解析 `/proc/self/limits` 中 `Max open files` 行的软限制
+fn parse_open_files(limits: &str) -> Option<u64> {
+    let line = limits
+        .lines()
+        .find(|line| line.starts_with("Max open files"))?;
+    match line
+        .trim_start_matches("Max open files")
+        .split_whitespace()
+        .next()?
+    {
+        "unlimited" => Some(u64::MAX),
+        soft => soft.parse().ok(),
+    }
+}
+
+/// 检查可打开的文件数不低于 `required`，无法读取限制时跳过
+pub fn check_open_files(required: u64) -> Result<(), ResourceError> {
+    match open_files_limit() {
+        Some(limit) if limit < required => Err(ResourceError::OpenFiles { limit, required }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    #[test]
+    fn test_memory_pressure() {
+        let config = ResourceConfig {
+            memory_soft_limit_mb: 100,
+            memory_hard_limit_mb: 200,
+            ..Default::default()
+        };
+        assert_eq!(
+            MemoryPressure::from_usage(50 << 20, &config),
+            MemoryPressure::Normal
+        );
+        assert_eq!(
+            MemoryPressure::from_usage(150 << 20, &config),
+            MemoryPressure::Soft
+        );
+        assert_eq!(
+            MemoryPressure::from_usage(200 << 20, &config),
+            MemoryPressure::Hard
+        );
+        assert_eq!(
+            MemoryPressure::from_usage(u64::MAX, &ResourceConfig::default()),
+            MemoryPressure::Normal
+        );
+    }
+
+    #[test]
+    fn test_reclaim_sender_cache() {
+        let cache = SenderCache::new(16);
+        for i in 0..8 {
+            cache.insert(
+                H256::from_low_u64_be(i),
+                crate::account::Address([i as u8; 20]),
+            );
+        }
+        let guard = MemoryGuard::default();
+        guard.register(Arc::new(cache.clone()));
+
+        assert!(guard.reclaim(MemoryPressure::Normal).is_empty());
+        assert_eq!(
+            guard.reclaim(MemoryPressure::Soft),
+            vec![("sender_cache", 4)]
+        );
+        // 保留最近使用的条目
+        assert!(cache.get(&H256::from_low_u64_be(7)).is_some());
+        assert_eq!(
+            guard.reclaim(MemoryPressure::Hard),
+            vec![("sender_cache", 4)]
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_parse_proc() {
+        assert_eq!(
+            parse_vm_rss("Name:\tfairvm\nVmRSS:\t  2048 kB\n"),
+            Some(2 << 20)
+        );
+        let limits = "Limit                     Soft Limit           Hard Limit           Units\n\
+                      Max open files            1024                 524288               files\n";
+        assert_eq!(parse_open_files(limits), Some(1024));
+        assert_eq!(
+            parse_open_files(
+                "Max open files            unlimited            unlimited            files"
+            ),
+            Some(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_open_files_error() {
+        let err = ResourceError::OpenFiles {
+            limit: 256,
+            required: 1024,
+        };
+        assert!(err.to_string().contains("LimitNOFILE=1024"));
+        assert!(check_open_files(0).is_ok());
+    }
+}
@@ -0,0 +1,64 @@
+//! 节点侧消息签名工具
+//!
+//! 对 32 字节摘要进行 secp256k1 可恢复签名，签名格式为 65 字节 (r || s || v)。
+
+use crate::account::Address;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey, Verification};
+
+/// 可恢复签名长度
+pub const SIGNATURE_LENGTH: usize = 65;
+
+/// 对摘要签名，返回签名与签名者地址
+pub fn sign_digest(secret_key: &SecretKey, digest: [u8; 32]) -> Result<(Vec<u8>, Address), String> {
+    let secp = Secp256k1::new();
+    let message = Message::from_digest_slice(&digest).map_err(|e| e.to_string())?;
+    let (recovery_id, compact) = secp
+        .sign_ecdsa_recoverable(&message, secret_key)
+        .serialize_compact();
+
+    let mut signature = compact.to_vec();
+    signature.push(recovery_id.to_i32() as u8);
+
+    let signer = Address::from_public_key(&PublicKey::from_secret_key(&secp, secret_key));
+    Ok((signature, signer))
+}
+
+/// 从签名中恢复签名者地址
+pub fn recover_signer(digest: [u8; 32], signature: &[u8]) -> Result<Address, String> {
+    recover_signer_with(&Secp256k1::verification_only(), digest, signature)
+}
+
+/// 使用已有上下文恢复签名者地址，批量恢复时复用上下文可避免重复初始化
+pub fn recover_signer_with<C: Verification>(
+    secp: &Secp256k1<C>,
+    digest: [u8; 32],
+    signature: &[u8],
+) -> Result<Address, String> {
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(format!("签名长度必须为 {} 字节", SIGNATURE_LENGTH));
+    }
+    let recovery_id = RecoveryId::from_i32(i32::from(signature[64])).map_err(|e| e.to_string())?;
+    let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id)
+        .map_err(|e| e.to_string())?;
+    let message = Message::from_digest_slice(&digest).map_err(|e| e.to_string())?;
+    let public_key = secp
+        .recover_ecdsa(&message, &signature)
+        .map_err(|e| e.to_string())?;
+    Ok(Address::from_public_key(&public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_recover() {
+        let secret_key = SecretKey::from_slice(&[3u8; 32]).unwrap();
+        let (signature, signer) = sign_digest(&secret_key, [1u8; 32]).unwrap();
+        assert_eq!(signature.len(), SIGNATURE_LENGTH);
+        assert_eq!(recover_signer([1u8; 32], &signature).unwrap(), signer);
+        assert_ne!(recover_signer([2u8; 32], &signature).unwrap(), signer);
+        assert!(recover_signer([1u8; 32], &signature[..64]).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! 拜占庭行为注入
+//!
+//! 为模拟节点挂载作恶行为，出块和同步时由行为决定发布什么区块、发给谁。
+//! 诚实节点在导入区块时记录作恶证据，供共识和惩罚逻辑的测试使用。
+
+use super::network::NodeId;
+use crate::blockchain::Block;
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// 作恶证据
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Misbehavior {
+    /// 同一出块者在同一高度发布了两个不同的区块
+    Equivocation {
+        proposer: NodeId,
+        number: u64,
+        first: H256,
+        second: H256,
+    },
+    /// 区块状态根与执行结果不一致
+    InvalidStateRoot {
+        proposer: Option<NodeId>,
+        block: H256,
+        expected: H256,
+        actual: H256,
+    },
+}
+
+impl Misbehavior {
+    /// 作恶的出块者
+    pub fn offender(&self) -> Option<NodeId> {
+        match self {
+            Self::Equivocation { proposer, .. } => Some(*proposer),
+            Self::InvalidStateRoot { proposer, .. } => *proposer,
+        }
+    }
+}
+
+/// 出块者对新区块的发布方式
+#[derive(Debug, Clone, Default)]
+pub struct Publication {
+    /// 出块者自己导入的区块
+    pub own: Option<Block>,
+    /// 发给各节点的区块
+    pub sends: Vec<(NodeId, Block)>,
+}
+
+impl Publication {
+    /// 诚实发布：自己导入并发给所有节点
+    pub fn honest(block: Block, peers: &[NodeId]) -> Self {
+        Self {
+            sends: peers.iter().map(|&peer| (peer, block.clone())).collect(),
+            own: Some(block),
+        }
+    }
+}
+
+/// 拜占庭行为
+pub trait ByzantineBehavior: Send + Sync {
+    /// 行为名称
+    fn name(&self) -> &'static str;
+
+    /// 出块时调用，`block` 为诚实节点会发布的区块
+    fn on_propose(&mut self, block: Block, peers: &[NodeId]) -> Publication;
+
+    /// 每个时隙开始时调用，返回需要额外发送的区块
+    fn on_slot(&mut self, _slot: u64, _peers: &[NodeId]) -> Vec<(NodeId, Block)> {
+        Vec::new()
+    }
+
+    /// 是否回应其他节点的同步请求
+    fn serves_sync(&self) -> bool {
+        true
+    }
+}
+
+/// 双签：向一半节点发送原区块，向另一半发送时间戳不同的冲突区块
+#[derive(Debug, Default)]
+pub struct Equivocation;
+
+impl ByzantineBehavior for Equivocation {
+    fn name(&self) -> &'static str {
+        "equivocation"
+    }
+
+    fn on_propose(&mut self, block: Block, peers: &[NodeId]) -> Publication {
+        let mut conflicting = block.clone();
+        conflicting.header.timestamp += 1;
+        let (first, second) = peers.split_at(peers.len() / 2);
+        let mut sends: Vec<(NodeId, Block)> =
+            first.iter().map(|&peer| (peer, block.clone())).collect();
+        sends.extend(second.iter().map(|&peer| (peer, conflicting.clone())));
+        Publication {
+            own: Some(block),
+            sends,
+        }
+    }
+}
+
+/// 扣留区块：出块后不发布，到指定时隙再一次性发布，扣留期间也不回应同步请求
+#[derive(Debug, Default)]
+pub struct WithholdBlocks {
+    release_at: Option<u64>,
+    slot: u64,
+    withheld: Vec<Block>,
+}
+
+impl WithholdBlocks {
+    /// 在 `release_at` 时隙发布扣留的区块，之后恢复诚实；为空时永不发布
+    pub fn new(release_at: Option<u64>) -> Self {
+        Self {
+            release_at,
+            slot: 0,
+            withheld: Vec::new(),
+        }
+    }
+
+    fn released(&self) -> bool {
+        self.release_at.is_some_and(|at| self.slot >= at)
+    }
+}
+
+impl ByzantineBehavior for WithholdBlocks {
+    fn name(&self) -> &'static str {
+        "withhold_blocks"
+    }
+
+    fn on_propose(&mut self, block: Block, peers: &[NodeId]) -> Publication {
+        if self.released() {
+            return Publication::honest(block, peers);
+        }
+        self.withheld.push(block.clone());
+        Publication {
+            own: Some(block),
+            sends: Vec::new(),
+        }
+    }
+
+    fn on_slot(&mut self, slot: u64, peers: &[NodeId]) -> Vec<(NodeId, Block)> {
+        self.slot = slot;
+        if !self.released() {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.withheld)
+            .into_iter()
+            .flat_map(|block| peers.iter().map(move |&peer| (peer, block.clone())))
+            .collect()
+    }
+
+    fn serves_sync(&self) -> bool {
+        self.released()
+    }
+}
+
+/// 无效状态根：发布状态根被篡改的区块，自己不导入
+#[derive(Debug, Default)]
+pub struct InvalidStateRoot;
+
+impl ByzantineBehavior for InvalidStateRoot {
+    fn name(&self) -> &'static str {
+        "invalid_state_root"
+    }
+
+    fn on_propose(&mut self, mut block: Block, peers: &[NodeId]) -> Publication {
+        let mut hasher = Keccak256::new();
+        hasher.update(block.header.state_root.as_bytes());
+        hasher.update(block.header.number.to_be_bytes());
+        block.header.state_root = H256::from_slice(&hasher.finalize());
+        Publication {
+            own: None,
+            sends: peers.iter().map(|&peer| (peer, block.clone())).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{Simulation, SimulationConfig};
+
+    fn simulation() -> Simulation {
+        Simulation::new(SimulationConfig {
+            nodes: 4,
+            seed: 11,
+            ..SimulationConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_equivocation() {
+        let mut sim = simulation();
+        sim.set_behavior(0, Box::new(Equivocation));
+        sim.run_slots(10).await.unwrap();
+        sim.settle().await.unwrap();
+
+        assert!(sim.is_converged());
+        assert!(sim.witnesses(0) > 0);
+        let evidence = sim
+            .nodes()
+            .iter()
+            .flat_map(|node| node.evidence())
+            .find(|evidence| matches!(evidence, Misbehavior::Equivocation { .. }))
+            .unwrap();
+        assert_eq!(evidence.offender(), Some(0));
+        // 诚实节点不会被指控
+        assert!((1..4).all(|id| sim.witnesses(id) == 0));
+    }
+
+    #[tokio::test]
+    async fn test_withhold_blocks() {
+        let mut sim = simulation();
+        sim.set_behavior(0, Box::new(WithholdBlocks::new(Some(9))));
+        sim.run_slots(8).await.unwrap();
+        sim.settle().await.unwrap();
+        // 扣留期间其他节点看不到节点 0 的区块，链仍在增长
+        assert!(sim.node(1).blocks_by(0).is_empty());
+        assert!(sim.is_converged());
+        assert!(sim.node(1).height() >= 5);
+
+        sim.run_slots(3).await.unwrap();
+        sim.settle().await.unwrap();
+        assert_eq!(sim.node(1).blocks_by(0).len(), 2);
+        assert!(sim.is_converged());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_state_root() {
+        let mut sim = simulation();
+        sim.set_behavior(2, Box::new(InvalidStateRoot));
+        sim.run_slots(8).await.unwrap();
+        sim.settle().await.unwrap();
+
+        assert!(sim.is_converged());
+        assert_eq!(sim.witnesses(2), 3);
+        for id in [0, 1, 3] {
+            let node = sim.node(id);
+            assert!(node
+                .canonical_chain()
+                .iter()
+                .all(|block| crate::simulation::node::proposer_of(&block.header) != Some(2)));
+            assert!(node.blocks_by(2).is_empty());
+        }
+        assert_eq!(sim.node(0).height(), 6);
+    }
+}
@@ -0,0 +1,383 @@
+//! 多节点共识模拟
+//!
+//! 在同一进程内运行多个 FairVM 节点，节点之间通过内存网络交换区块，
+//! 网络延迟、丢包、分区和节点离线都可配置。模拟按虚拟时间推进，
+//! 出块者按时隙轮换，不依赖 avalanchego，可以在 CI 中确定性地测试
+//! 共识活性、分叉重组和区块同步。
+
+pub mod byzantine;
+pub mod network;
+pub mod node;
+
+pub use byzantine::{ByzantineBehavior, Misbehavior, Publication};
+pub use network::{Envelope, LinkConditions, NetworkStats, NodeId, SimNetwork};
+pub use node::{ImportOutcome, SimNode};
+
+use crate::blockchain::{Block, BlockHeader};
+use crate::network::NetworkMessage;
+use ethers::types::H256;
+use std::collections::BTreeMap;
+
+/// 模拟错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+    #[error("无效的模拟配置: {0}")]
+    InvalidConfig(String),
+
+    #[error("无效的区块: {0}")]
+    InvalidBlock(String),
+
+    #[error("节点执行区块失败: {0}")]
+    Vm(String),
+}
+
+/// 模拟配置
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// 节点数量
+    pub nodes: usize,
+    /// 时隙长度（毫秒），每个时隙由一个节点出块
+    pub slot_duration: u64,
+    /// 随机种子
+    pub seed: u64,
+    /// 默认链路条件
+    pub conditions: LinkConditions,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            nodes: 4,
+            slot_duration: 1000,
+            seed: 0,
+            conditions: LinkConditions::default(),
+        }
+    }
+}
+
+/// 多节点模拟
+pub struct Simulation {
+    config: SimulationConfig,
+    network: SimNetwork,
+    nodes: Vec<SimNode>,
+    /// 挂载了拜占庭行为的节点
+    behaviors: BTreeMap<NodeId, Box<dyn ByzantineBehavior>>,
+    /// 当前虚拟时间（毫秒）
+    now: u64,
+    /// 下一个时隙编号
+    slot: u64,
+}
+
+impl Simulation {
+    /// 创建模拟，所有节点共享同一个创世区块
+    pub fn new(config: SimulationConfig) -> Result<Self, SimulationError> {
+        if config.nodes == 0 {
+            return Err(SimulationError::InvalidConfig("节点数量必须大于 0".into()));
+        }
+        if config.slot_duration == 0 {
+            return Err(SimulationError::InvalidConfig("时隙长度必须大于 0".into()));
+        }
+        let genesis = genesis_block();
+        let nodes = (0..config.nodes)
+            .map(|id| SimNode::new(id, genesis.clone()))
+            .collect();
+        Ok(Self {
+            network: SimNetwork::new(config.nodes, config.seed, config.conditions.clone()),
+            config,
+            nodes,
+            behaviors: BTreeMap::new(),
+            now: 0,
+            slot: 1,
+        })
+    }
+
+    /// 当前虚拟时间（毫秒）
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// 网络
+    pub fn network(&self) -> &SimNetwork {
+        &self.network
+    }
+
+    /// 可修改的网络，用于注入分区和丢包
+    pub fn network_mut(&mut self) -> &mut SimNetwork {
+        &mut self.network
+    }
+
+    /// 节点
+    pub fn node(&self, id: NodeId) -> &SimNode {
+        &self.nodes[id]
+    }
+
+    /// 所有节点
+    pub fn nodes(&self) -> &[SimNode] {
+        &self.nodes
+    }
+
+    /// 为节点挂载拜占庭行为
+    pub fn set_behavior(&mut self, id: NodeId, behavior: Box<dyn ByzantineBehavior>) {
+        self.behaviors.insert(id, behavior);
+    }
+
+    /// 节点是否诚实
+    pub fn is_honest(&self, id: NodeId) -> bool {
+        !self.behaviors.contains_key(&id)
+    }
+
+    /// 诚实节点中记录了 `offender` 作恶证据的节点数
+    pub fn witnesses(&self, offender: NodeId) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| self.is_honest(node.id()))
+            .filter(|node| {
+                node.evidence()
+                    .iter()
+                    .any(|evidence| evidence.offender() == Some(offender))
+            })
+            .count()
+    }
+
+    /// 时隙的出块者
+    pub fn proposer(&self, slot: u64) -> NodeId {
+        (slot % self.config.nodes as u64) as NodeId
+    }
+
+    /// 各节点的主链高度
+    pub fn heights(&self) -> Vec<u64> {
+        self.nodes.iter().map(SimNode::height).collect()
+    }
+
+    /// 在线诚实节点的主链头是否一致
+    pub fn is_converged(&self) -> bool {
+        let mut heads = self
+            .nodes
+            .iter()
+            .filter(|node| self.network.is_online(node.id()) && self.is_honest(node.id()))
+            .map(SimNode::head_hash);
+        match heads.next() {
+            Some(first) => heads.all(|head| head == first),
+            None => true,
+        }
+    }
+
+    /// 推进 `duration` 毫秒，按时间顺序处理出块和消息投递
+    pub async fn run_for(&mut self, duration: u64) -> Result<(), SimulationError> {
+        let end = self.now + duration;
+        loop {
+            let slot_time = self.slot * self.config.slot_duration;
+            let next = match self.network.next_delivery() {
+                Some(delivery) => delivery.min(slot_time),
+                None => slot_time,
+            };
+            if next > end {
+                break;
+            }
+            self.now = next;
+            // 同一时刻先投递消息再出块
+            if let Some(envelope) = self.network.pop_due(self.now) {
+                self.deliver(envelope).await?;
+                continue;
+            }
+            if next == slot_time {
+                self.propose().await?;
+            }
+        }
+        self.now = end;
+        Ok(())
+    }
+
+    /// 推进若干个时隙
+    pub async fn run_slots(&mut self, slots: u64) -> Result<(), SimulationError> {
+        self.run_for(slots * self.config.slot_duration).await
+    }
+
+    /// 不再出块，投递完所有在途消息
+    pub async fn settle(&mut self) -> Result<(), SimulationError> {
+        while let Some(at) = self.network.next_delivery() {
+            self.now = self.now.max(at);
+            if let Some(envelope) = self.network.pop_due(self.now) {
+                self.deliver(envelope).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn propose(&mut self) -> Result<(), SimulationError> {
+        let slot = self.slot;
+        self.slot += 1;
+        let nodes = self.nodes.len();
+        for (&id, behavior) in self.behaviors.iter_mut() {
+            for (to, block) in behavior.on_slot(slot, &peers(nodes, id)) {
+                self.network
+                    .send(self.now, id, to, NetworkMessage::NewBlock(block));
+            }
+        }
+
+        let proposer = self.proposer(slot);
+        if !self.network.is_online(proposer) {
+            return Ok(());
+        }
+        let block = self.nodes[proposer].build_block(self.now / 1000);
+        let peers = peers(nodes, proposer);
+        let publication = match self.behaviors.get_mut(&proposer) {
+            Some(behavior) => behavior.on_propose(block, &peers),
+            None => Publication::honest(block, &peers),
+        };
+        if let Some(own) = publication.own {
+            self.nodes[proposer].import(own).await?;
+        }
+        for (to, block) in publication.sends {
+            self.network
+                .send(self.now, proposer, to, NetworkMessage::NewBlock(block));
+        }
+        Ok(())
+    }
+
+    async fn deliver(&mut self, envelope: Envelope) -> Result<(), SimulationError> {
+        let Envelope { from, to, .. } = envelope;
+        match envelope.message {
+            NetworkMessage::NewBlock(block) | NetworkMessage::BlockResponse(Some(block)) => {
+                let number = block.header.number;
+                if self.nodes[to].import(block).await? == ImportOutcome::Orphan {
+                    // 向发送方逐个回溯缺失的父区块
+                    self.network
+                        .send(self.now, to, from, NetworkMessage::GetBlock(number - 1));
+                }
+            }
+            NetworkMessage::GetBlock(number) => {
+                if self
+                    .behaviors
+                    .get(&to)
+                    .is_some_and(|behavior| !behavior.serves_sync())
+                {
+                    return Ok(());
+                }
+                let block = self.nodes[to].canonical_block(number).cloned();
+                self.network
+                    .send(self.now, to, from, NetworkMessage::BlockResponse(block));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// 除 `id` 外的所有节点
+fn peers(nodes: usize, id: NodeId) -> Vec<NodeId> {
+    (0..nodes).filter(|&peer| peer != id).collect()
+}
+
+/// 模拟使用的创世区块
+pub fn genesis_block() -> Block {
+    Block {
+        header: BlockHeader {
+            parent_hash: H256::zero(),
+            number: 0,
+            timestamp: 0,
+            transactions_root: H256::zero(),
+            state_root: H256::zero(),
+            receipts_root: H256::zero(),
+            difficulty: 0,
+            block_reward: 0,
+            proposer: None,
+            arrivals_root: H256::zero(),
+        },
+        transactions: Vec::new(),
+        arrivals: Vec::new(),
+        acceptance: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulation(nodes: usize, seed: u64) -> Simulation {
+        Simulation::new(SimulationConfig {
+            nodes,
+            seed,
+            ..SimulationConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_liveness() {
+        let mut sim = simulation(4, 1);
+        sim.run_slots(10).await.unwrap();
+        sim.settle().await.unwrap();
+        assert!(sim.is_converged());
+        assert_eq!(sim.heights(), vec![10; 4]);
+        let blockchain = sim.node(2).vm().blockchain();
+        assert_eq!(blockchain.read().await.blocks().len(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_partition_reorg() {
+        let mut sim = simulation(4, 2);
+        sim.run_slots(3).await.unwrap();
+        sim.network_mut().partition(&[vec![0, 1, 2], vec![3]]);
+        sim.run_slots(8).await.unwrap();
+        assert!(!sim.is_converged());
+        assert!(sim.node(0).height() > sim.node(3).height());
+
+        sim.network_mut().heal();
+        sim.run_slots(4).await.unwrap();
+        sim.settle().await.unwrap();
+        assert!(sim.is_converged());
+        assert!(sim.node(3).reorgs() > 0);
+        // 重组后节点的 FairVM 只包含新的主链
+        let blockchain = sim.node(3).vm().blockchain();
+        let latest = blockchain.read().await.latest_block().unwrap().hash();
+        assert_eq!(latest, sim.node(3).head_hash());
+    }
+
+    #[tokio::test]
+    async fn test_sync_after_drops_and_downtime() {
+        let mut sim = Simulation::new(SimulationConfig {
+            nodes: 4,
+            seed: 3,
+            conditions: LinkConditions {
+                drop_rate: 0.3,
+                ..LinkConditions::default()
+            },
+            ..SimulationConfig::default()
+        })
+        .unwrap();
+        sim.network_mut().set_online(1, false);
+        sim.run_slots(12).await.unwrap();
+        assert_eq!(sim.node(1).height(), 0);
+
+        sim.network_mut().set_online(1, true);
+        sim.network_mut().set_conditions(LinkConditions::default());
+        sim.run_slots(3).await.unwrap();
+        sim.settle().await.unwrap();
+        assert!(sim.is_converged());
+        assert_eq!(sim.node(1).orphan_count(), 0);
+        assert!(sim.network().stats().dropped > 0);
+    }
+
+    #[tokio::test]
+    async fn test_deterministic() {
+        let mut heads = Vec::new();
+        for _ in 0..2 {
+            let mut sim = simulation(5, 42);
+            sim.network_mut().set_conditions(LinkConditions {
+                min_latency: 100,
+                max_latency: 1500,
+                drop_rate: 0.1,
+            });
+            sim.run_slots(20).await.unwrap();
+            heads.push((
+                sim.nodes()
+                    .iter()
+                    .map(SimNode::head_hash)
+                    .collect::<Vec<_>>(),
+                sim.network().stats().clone(),
+            ));
+        }
+        assert_eq!(heads[0], heads[1]);
+    }
+}
@@ -0,0 +1,255 @@
+//! 内存网络：按虚拟时间投递消息，支持延迟、丢包和网络分区
+
+use crate::network::NetworkMessage;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+
+/// 模拟节点编号
+pub type NodeId = usize;
+
+/// 链路条件
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkConditions {
+    /// 最小延迟（毫秒）
+    pub min_latency: u64,
+    /// 最大延迟（毫秒）
+    pub max_latency: u64,
+    /// 丢包率，取值 0 到 1
+    pub drop_rate: f64,
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self {
+            min_latency: 10,
+            max_latency: 50,
+            drop_rate: 0.0,
+        }
+    }
+}
+
+/// 在途消息
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub from: NodeId,
+    pub to: NodeId,
+    /// 投递时间（毫秒）
+    pub deliver_at: u64,
+    pub message: NetworkMessage,
+}
+
+/// 网络统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkStats {
+    pub sent: u64,
+    pub delivered: u64,
+    /// 随机丢弃的消息
+    pub dropped: u64,
+    /// 因分区或节点离线无法送达的消息
+    pub blocked: u64,
+}
+
+/// 内存网络
+///
+/// 所有随机性来自固定种子，相同种子和相同操作序列产生相同的投递顺序。
+#[derive(Debug)]
+pub struct SimNetwork {
+    nodes: usize,
+    rng: StdRng,
+    conditions: LinkConditions,
+    links: HashMap<(NodeId, NodeId), LinkConditions>,
+    /// 每个节点所在的分区，为空时网络连通
+    partition: Option<Vec<usize>>,
+    offline: Vec<bool>,
+    /// 按（投递时间, 发送序号）排序的在途消息
+    queue: BTreeMap<(u64, u64), Envelope>,
+    seq: u64,
+    stats: NetworkStats,
+}
+
+impl SimNetwork {
+    /// 创建连接 `nodes` 个节点的网络
+    pub fn new(nodes: usize, seed: u64, conditions: LinkConditions) -> Self {
+        Self {
+            nodes,
+            rng: StdRng::seed_from_u64(seed),
+            conditions,
+            links: HashMap::new(),
+            partition: None,
+            offline: vec![false; nodes],
+            queue: BTreeMap::new(),
+            seq: 0,
+            stats: NetworkStats::default(),
+        }
+    }
+
+    /// 节点数量
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    /// 是否没有节点
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    /// 设置所有链路的默认条件
+    pub fn set_conditions(&mut self, conditions: LinkConditions) {
+        self.conditions = conditions;
+    }
+
+    /// 设置 `from` 到 `to` 方向的链路条件
+    pub fn set_link(&mut self, from: NodeId, to: NodeId, conditions: LinkConditions) {
+        self.links.insert((from, to), conditions);
+    }
+
+    /// 将网络划分为若干互不连通的分区，未列出的节点各自独立成区
+    pub fn partition(&mut self, groups: &[Vec<NodeId>]) {
+        let mut assignment: Vec<usize> = (0..self.nodes).map(|id| groups.len() + id).collect();
+        for (group, members) in groups.iter().enumerate() {
+            for &id in members {
+                assignment[id] = group;
+            }
+        }
+        self.partition = Some(assignment);
+    }
+
+    /// 恢复网络连通
+    pub fn heal(&mut self) {
+        self.partition = None;
+    }
+
+    /// 设置节点在线状态，离线节点既不收也不发消息
+    pub fn set_online(&mut self, id: NodeId, online: bool) {
+        self.offline[id] = !online;
+    }
+
+    /// 节点是否在线
+    pub fn is_online(&self, id: NodeId) -> bool {
+        !self.offline[id]
+    }
+
+    /// 两个节点当前能否通信
+    pub fn is_reachable(&self, from: NodeId, to: NodeId) -> bool {
+        if self.offline[from] || self.offline[to] {
+            return false;
+        }
+        match &self.partition {
+            Some(assignment) => assignment[from] == assignment[to],
+            None => true,
+        }
+    }
+
+    /// 发送消息，返回消息是否进入在途队列
+    pub fn send(&mut self, now: u64, from: NodeId, to: NodeId, message: NetworkMessage) -> bool {
+        self.stats.sent += 1;
+        if !self.is_reachable(from, to) {
+            self.stats.blocked += 1;
+            return false;
+        }
+        let conditions = self.links.get(&(from, to)).unwrap_or(&self.conditions);
+        let (min, max, drop_rate) = (
+            conditions.min_latency,
+            conditions.max_latency.max(conditions.min_latency),
+            conditions.drop_rate,
+        );
+        if drop_rate > 0.0 && self.rng.random_bool(drop_rate.min(1.0)) {
+            self.stats.dropped += 1;
+            return false;
+        }
+        let deliver_at = now + self.rng.random_range(min..=max);
+        self.seq += 1;
+        self.queue.insert(
+            (deliver_at, self.seq),
+            Envelope {
+                from,
+                to,
+                deliver_at,
+                message,
+            },
+        );
+        true
+    }
+
+    /// 向其他所有节点广播消息
+    pub fn broadcast(&mut self, now: u64, from: NodeId, message: NetworkMessage) {
+        for to in (0..self.nodes).filter(|&to| to != from) {
+            self.send(now, from, to, message.clone());
+        }
+    }
+
+    /// 下一条消息的投递时间
+    pub fn next_delivery(&self) -> Option<u64> {
+        self.queue.keys().next().map(|(at, _)| *at)
+    }
+
+    /// 取出 `now` 之前到期的下一条消息
+    ///
+    /// 投递时再次检查连通性，发送后才出现的分区同样会截断在途消息。
+    pub fn pop_due(&mut self, now: u64) -> Option<Envelope> {
+        loop {
+            let key = *self.queue.keys().next()?;
+            if key.0 > now {
+                return None;
+            }
+            let envelope = self.queue.remove(&key)?;
+            if self.is_reachable(envelope.from, envelope.to) {
+                self.stats.delivered += 1;
+                return Some(envelope);
+            }
+            self.stats.blocked += 1;
+        }
+    }
+
+    /// 在途消息数量
+    pub fn in_flight(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 网络统计
+    pub fn stats(&self) -> &NetworkStats {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delivery_and_partition() {
+        let mut network = SimNetwork::new(3, 7, LinkConditions::default());
+        network.broadcast(0, 0, NetworkMessage::GetBlock(1));
+        assert_eq!(network.in_flight(), 2);
+        assert!(network.pop_due(9).is_none());
+        let first = network.next_delivery().unwrap();
+        assert!((10..=50).contains(&first));
+
+        // 分区出现后在途消息不再送达
+        network.partition(&[vec![0, 1], vec![2]]);
+        let mut delivered = Vec::new();
+        while let Some(envelope) = network.pop_due(100) {
+            delivered.push(envelope.to);
+        }
+        assert_eq!(delivered, vec![1]);
+        assert!(!network.send(100, 0, 2, NetworkMessage::GetBlock(1)));
+        assert_eq!(network.stats().blocked, 2);
+
+        network.heal();
+        network.set_online(2, false);
+        assert!(!network.is_reachable(0, 2));
+        network.set_online(2, true);
+        network.set_link(
+            0,
+            2,
+            LinkConditions {
+                drop_rate: 1.0,
+                ..LinkConditions::default()
+            },
+        );
+        assert!(!network.send(100, 0, 2, NetworkMessage::GetBlock(1)));
+        assert_eq!(network.stats().dropped, 1);
+        assert!(network.send(100, 2, 0, NetworkMessage::GetBlock(1)));
+    }
+}
@@ -0,0 +1,317 @@
+//! 模拟节点：在 FairVM 之上维护区块树，按最长链规则选择主链
+
+use super::byzantine::Misbehavior;
+use super::network::NodeId;
+use super::SimulationError;
+use crate::blockchain::{Block, BlockHeader};
+use crate::FairVM;
+use ethers::types::{H256, U256};
+use std::collections::{HashMap, HashSet};
+
+/// 区块导入结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// 区块已存在
+    Known,
+    /// 区块延长了主链
+    Extended,
+    /// 区块所在分叉成为主链，`depth` 为回滚的区块数
+    Reorg { depth: u64 },
+    /// 区块位于较短的分叉上
+    SideChain,
+    /// 父区块未知，区块暂存等待同步
+    Orphan,
+    /// 区块或其祖先无效，已拒绝
+    Rejected,
+}
+
+/// 区块的出块者，模拟中出块者编号记录在难度字段
+pub fn proposer_of(header: &BlockHeader) -> Option<NodeId> {
+    header.difficulty.checked_sub(1).map(|id| id as NodeId)
+}
+
+/// 模拟节点
+pub struct SimNode {
+    id: NodeId,
+    vm: FairVM,
+    genesis: H256,
+    blocks: HashMap<H256, Block>,
+    head: H256,
+    /// 按父区块哈希索引的孤块
+    orphans: HashMap<H256, Vec<Block>>,
+    reorgs: u64,
+    /// 已拒绝的区块
+    invalid: HashSet<H256>,
+    /// 每个出块者在每个高度上最先见到的区块
+    proposals: HashMap<(NodeId, u64), H256>,
+    /// 观察到的作恶证据
+    evidence: Vec<Misbehavior>,
+}
+
+impl SimNode {
+    /// 以共同的创世区块创建节点
+    pub fn new(id: NodeId, genesis: Block) -> Self {
+        let hash = genesis.hash();
+        Self {
+            id,
+            vm: FairVM::new(),
+            genesis: hash,
+            blocks: HashMap::from([(hash, genesis)]),
+            head: hash,
+            orphans: HashMap::new(),
+            reorgs: 0,
+            invalid: HashSet::new(),
+            proposals: HashMap::new(),
+            evidence: Vec::new(),
+        }
+    }
+
+    /// 节点编号
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// 节点的 FairVM 实例，只包含主链区块
+    pub fn vm(&self) -> &FairVM {
+        &self.vm
+    }
+
+    /// 主链头
+    pub fn head(&self) -> &Block {
+        &self.blocks[&self.head]
+    }
+
+    /// 主链头哈希
+    pub fn head_hash(&self) -> H256 {
+        self.head
+    }
+
+    /// 主链高度
+    pub fn height(&self) -> u64 {
+        self.head().header.number
+    }
+
+    /// 已发生的重组次数
+    pub fn reorgs(&self) -> u64 {
+        self.reorgs
+    }
+
+    /// 观察到的作恶证据
+    pub fn evidence(&self) -> &[Misbehavior] {
+        &self.evidence
+    }
+
+    /// 是否已知区块
+    pub fn contains(&self, hash: &H256) -> bool {
+        self.blocks.contains_key(hash)
+    }
+
+    /// 已知的由 `proposer` 产生的区块，按高度排序
+    pub fn blocks_by(&self, proposer: NodeId) -> Vec<&Block> {
+        let mut blocks: Vec<&Block> = self
+            .blocks
+            .values()
+            .filter(|block| proposer_of(&block.header) == Some(proposer))
+            .collect();
+        blocks.sort_by_key(|block| (block.header.number, block.hash()));
+        blocks
+    }
+
+    /// 孤块数量
+    pub fn orphan_count(&self) -> usize {
+        self.orphans.values().map(Vec::len).sum()
+    }
+
+    /// 主链上从创世区块之后到链头的区块
+    pub fn canonical_chain(&self) -> Vec<&Block> {
+        let mut chain = self.ancestors(self.head);
+        chain.reverse();
+        chain
+    }
+
+    /// 主链上指定高度的区块
+    pub fn canonical_block(&self, number: u64) -> Option<&Block> {
+        if number > self.height() {
+            return None;
+        }
+        let mut block = self.head();
+        while block.header.number > number {
+            block = self.blocks.get(&block.header.parent_hash)?;
+        }
+        Some(block)
+    }
+
+    /// 在主链头上构建新区块
+    ///
+    /// 模拟中把出块者编号记录在难度字段，保证不同出块者在同一父区块上产生不同的区块。
+    /// 模拟区块不含交易，状态根沿用父区块。
+    pub fn build_block(&self, timestamp: u64) -> Block {
+        let parent = self.head();
+        Block {
+            header: BlockHeader {
+                parent_hash: self.head,
+                number: parent.header.number + 1,
+                timestamp,
+                transactions_root: H256::zero(),
+                state_root: parent.header.state_root,
+                receipts_root: H256::zero(),
+                difficulty: self.id as u64 + 1,
+                block_reward: 0,
+                proposer: None,
+                arrivals_root: H256::zero(),
+            },
+            transactions: Vec::new(),
+            arrivals: Vec::new(),
+            acceptance: None,
+        }
+    }
+
+    /// 导入区块，父区块到达后自动连接等待中的孤块
+    pub async fn import(&mut self, block: Block) -> Result<ImportOutcome, SimulationError> {
+        let hash = block.hash();
+        if self.invalid.contains(&hash) {
+            return Ok(ImportOutcome::Rejected);
+        }
+        if self.blocks.contains_key(&hash) {
+            return Ok(ImportOutcome::Known);
+        }
+        let parent = block.header.parent_hash;
+        match self.blocks.get(&parent) {
+            None if !self.invalid.contains(&parent) => {
+                let waiting = self.orphans.entry(parent).or_default();
+                if !waiting.iter().any(|b| b.hash() == hash) {
+                    waiting.push(block);
+                }
+                return Ok(ImportOutcome::Orphan);
+            }
+            Some(parent) if parent.header.number + 1 != block.header.number => {
+                return Err(SimulationError::InvalidBlock(format!(
+                    "区块 {:?} 高度 {} 与父区块不连续",
+                    hash, block.header.number
+                )));
+            }
+            _ => {}
+        }
+
+        let mut outcome = self.insert(block).await?;
+        // 连接以新区块为父区块的孤块
+        let mut pending = vec![hash];
+        while let Some(parent) = pending.pop() {
+            for child in self.orphans.remove(&parent).unwrap_or_default() {
+                pending.push(child.hash());
+                outcome = match (outcome, self.insert(child).await?) {
+                    (ImportOutcome::Rejected, _) => ImportOutcome::Rejected,
+                    (ImportOutcome::Reorg { depth }, ImportOutcome::Extended) => {
+                        ImportOutcome::Reorg { depth }
+                    }
+                    (_, next) => next,
+                };
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// 校验区块并记录作恶证据，返回区块是否有效
+    fn validate(&mut self, block: &Block) -> bool {
+        let hash = block.hash();
+        let Some(expected) = self
+            .blocks
+            .get(&block.header.parent_hash)
+            .map(|parent| parent.header.state_root)
+        else {
+            // 父区块已被拒绝
+            return false;
+        };
+        let proposer = proposer_of(&block.header);
+        if block.header.state_root != expected {
+            self.evidence.push(Misbehavior::InvalidStateRoot {
+                proposer,
+                block: hash,
+                expected,
+                actual: block.header.state_root,
+            });
+            return false;
+        }
+        if let Some(proposer) = proposer {
+            let first = *self
+                .proposals
+                .entry((proposer, block.header.number))
+                .or_insert(hash);
+            if first != hash {
+                self.evidence.push(Misbehavior::Equivocation {
+                    proposer,
+                    number: block.header.number,
+                    first,
+                    second: hash,
+                });
+            }
+        }
+        true
+    }
+
+    async fn insert(&mut self, block: Block) -> Result<ImportOutcome, SimulationError> {
+        let hash = block.hash();
+        if !self.validate(&block) {
+            self.invalid.insert(hash);
+            return Ok(ImportOutcome::Rejected);
+        }
+        let number = block.header.number;
+        let extends_head = block.header.parent_hash == self.head;
+        self.blocks.insert(hash, block);
+
+        if extends_head {
+            self.head = hash;
+            self.apply(hash).await?;
+            return Ok(ImportOutcome::Extended);
+        }
+        // 高度相同时保留先到的区块
+        if number <= self.height() {
+            return Ok(ImportOutcome::SideChain);
+        }
+
+        let depth = self.height() - self.common_ancestor(hash);
+        self.head = hash;
+        self.reorgs += 1;
+        self.rebuild().await?;
+        Ok(ImportOutcome::Reorg { depth })
+    }
+
+    /// 从 `hash` 回溯到创世区块（不含）的区块，链头在前
+    fn ancestors(&self, mut hash: H256) -> Vec<&Block> {
+        let mut chain = Vec::new();
+        while hash != self.genesis {
+            let block = &self.blocks[&hash];
+            chain.push(block);
+            hash = block.header.parent_hash;
+        }
+        chain
+    }
+
+    /// 分叉与当前主链的共同祖先高度
+    fn common_ancestor(&self, hash: H256) -> u64 {
+        self.ancestors(hash)
+            .into_iter()
+            .find(|block| {
+                self.canonical_block(block.header.number)
+                    .is_some_and(|canonical| canonical.hash() == block.hash())
+            })
+            .map_or(0, |block| block.header.number)
+    }
+
+    async fn apply(&self, hash: H256) -> Result<(), SimulationError> {
+        self.vm
+            .accept_block(self.blocks[&hash].clone(), U256::zero())
+            .await
+            .map_err(|e| SimulationError::Vm(e.to_string()))
+    }
+
+    /// FairVM 不支持回滚，重组时用新实例重放主链
+    async fn rebuild(&mut self) -> Result<(), SimulationError> {
+        self.vm = FairVM::new();
+        let chain: Vec<H256> = self.canonical_chain().iter().map(|b| b.hash()).collect();
+        for hash in chain {
+            self.apply(hash).await?;
+        }
+        Ok(())
+    }
+}
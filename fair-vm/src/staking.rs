@@ -0,0 +1,441 @@
+//! 验证人质押奖励
+//!
+//! 每个纪元（固定数量的区块）的最后一个区块执行完交易后，VM 以系统操作分配本纪元的奖励：
+//! `stake_weight_percent` 部分按质押比例分配，其余按纪元内的出块数比例分配，整除余下的
+//! 零头不发放。奖励先记为待领取，验证人的奖励地址向质押系统地址发送交易即可领取到余额。
+//! 待领取和已领取金额记录在系统地址的存储槽中，随区块状态一起提交。
+
+use crate::account::Address;
+use crate::jailing::JailingConfig;
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use ethers::types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::{HashMap, HashSet};
+
+/// 质押奖励系统合约地址
+pub const STAKING_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x04,
+]);
+
+/// 系统合约占位代码（实际逻辑由 VM 原生执行）
+pub const STAKING_CODE: &[u8] = &[0x00];
+
+/// 领取奖励交易消耗的 gas
+pub const STAKING_CLAIM_GAS: u64 = 50_000;
+
+/// 质押奖励错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum StakingError {
+    #[error("无效的质押配置: {0}")]
+    InvalidConfig(String),
+
+    #[error("{0} 没有待领取的奖励")]
+    NothingToClaim(Address),
+
+    #[error("无效的质押交易: {0}")]
+    InvalidTransaction(String),
+
+    #[error("{0} 不是验证人的奖励地址")]
+    NotValidator(Address),
+
+    #[error("验证人 {0} 未被监禁")]
+    NotJailed(String),
+
+    #[error("验证人 {node_id} 需等到区块 {until} 才能解禁")]
+    JailCooldown { node_id: String, until: u64 },
+}
+
+/// 验证人质押信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStake {
+    /// 节点 ID，与区块记录的出块者一致
+    pub node_id: String,
+    /// 奖励地址
+    pub reward_address: Address,
+    /// 质押量
+    pub stake: U256,
+}
+
+/// 质押奖励配置（来自创世文件）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingConfig {
+    pub validators: Vec<ValidatorStake>,
+    /// 每个纪元的区块数
+    pub epoch_length: u64,
+    /// 每个纪元发放的奖励
+    pub epoch_reward: U256,
+    /// 按质押比例分配的百分比，其余按出块数分配
+    pub stake_weight_percent: u8,
+    /// 监禁配置，未配置时不监禁
+    #[serde(default)]
+    pub jailing: Option<JailingConfig>,
+}
+
+impl StakingConfig {
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), StakingError> {
+        if self.epoch_length == 0 {
+            return Err(StakingError::InvalidConfig("纪元长度必须大于 0".into()));
+        }
+        if self.stake_weight_percent > 100 {
+            return Err(StakingError::InvalidConfig(
+                "质押权重百分比不能超过 100".into(),
+            ));
+        }
+        if self.validators.is_empty() {
+            return Err(StakingError::InvalidConfig("验证人列表为空".into()));
+        }
+        let mut node_ids = HashSet::new();
+        for validator in &self.validators {
+            if !node_ids.insert(validator.node_id.as_str()) {
+                return Err(StakingError::InvalidConfig(format!(
+                    "重复的验证人 {}",
+                    validator.node_id
+                )));
+            }
+        }
+        if self.total_stake().is_zero() {
+            return Err(StakingError::InvalidConfig("总质押量为 0".into()));
+        }
+        if let Some(jailing) = &self.jailing {
+            jailing.validate()?;
+        }
+        Ok(())
+    }
+
+    /// 总质押量
+    pub fn total_stake(&self) -> U256 {
+        self.validators
+            .iter()
+            .fold(U256::zero(), |total, v| total.saturating_add(v.stake))
+    }
+
+    /// 区块所在的纪元，纪元 0 从区块 1 开始
+    pub fn epoch_of(&self, number: u64) -> u64 {
+        number.saturating_sub(1) / self.epoch_length
+    }
+
+    /// 纪元包含的区块范围
+    pub fn epoch_range(&self, epoch: u64) -> (u64, u64) {
+        let from = epoch * self.epoch_length + 1;
+        (from, from + self.epoch_length - 1)
+    }
+
+    /// 区块是否为纪元的最后一个区块
+    pub fn is_epoch_end(&self, number: u64) -> bool {
+        number > 0 && number % self.epoch_length == 0
+    }
+
+    /// 按节点 ID 或奖励地址查找验证人
+    pub fn find(&self, validator: &str) -> Option<&ValidatorStake> {
+        let address = crate::avax_address::parse_any(validator).ok();
+        self.validators
+            .iter()
+            .find(|v| v.node_id == validator || Some(v.reward_address) == address)
+    }
+
+    /// 按质押量和出块数计算纪元奖励，`proposals` 为各节点在纪元内的出块数
+    pub fn epoch_rewards(&self, epoch: u64, proposals: &HashMap<String, u64>) -> EpochRewards {
+        let counts: Vec<u64> = self
+            .validators
+            .iter()
+            .map(|v| proposals.get(&v.node_id).copied().unwrap_or(0))
+            .collect();
+        let total_proposals: u64 = counts.iter().sum();
+        // 纪元内没有已知验证人出块时全部按质押分配
+        let stake_pool = if total_proposals == 0 {
+            self.epoch_reward
+        } else {
+            self.epoch_reward * U256::from(self.stake_weight_percent) / 100
+        };
+        let proposal_pool = self.epoch_reward - stake_pool;
+        let total_stake = self.total_stake();
+
+        let rewards = self
+            .validators
+            .iter()
+            .zip(counts)
+            .map(|(validator, proposals)| {
+                let mut reward = stake_pool * validator.stake / total_stake;
+                if total_proposals > 0 {
+                    reward += proposal_pool * U256::from(proposals) / total_proposals;
+                }
+                ValidatorReward {
+                    node_id: validator.node_id.clone(),
+                    reward_address: validator.reward_address,
+                    proposals,
+                    reward,
+                }
+            })
+            .collect();
+        let (from_block, to_block) = self.epoch_range(epoch);
+        EpochRewards {
+            epoch,
+            from_block,
+            to_block,
+            rewards,
+        }
+    }
+}
+
+/// 单个验证人的纪元奖励
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorReward {
+    pub node_id: String,
+    pub reward_address: Address,
+    /// 纪元内的出块数
+    pub proposals: u64,
+    pub reward: U256,
+}
+
+/// 纪元奖励分配结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochRewards {
+    pub epoch: u64,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub rewards: Vec<ValidatorReward>,
+}
+
+impl EpochRewards {
+    /// 本纪元实际分配的奖励总额
+    pub fn total(&self) -> U256 {
+        self.rewards
+            .iter()
+            .fold(U256::zero(), |total, r| total + r.reward)
+    }
+}
+
+/// 验证人奖励查询结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorRewards {
+    pub node_id: String,
+    pub reward_address: Address,
+    pub stake: U256,
+    pub pending: U256,
+    pub claimed: U256,
+}
+
+/// 判断交易是否发送至质押系统合约
+pub fn is_staking_transaction(tx: &Transaction) -> bool {
+    tx.to == Some(STAKING_ADDRESS)
+}
+
+fn reward_slot(tag: &[u8], address: &Address) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(tag);
+    hasher.update(address.0);
+    hasher.finalize().into()
+}
+
+/// 待领取奖励存储槽
+pub fn pending_slot(address: &Address) -> [u8; 32] {
+    reward_slot(b"fairvm-staking-pending", address)
+}
+
+/// 已领取奖励存储槽
+pub fn claimed_slot(address: &Address) -> [u8; 32] {
+    reward_slot(b"fairvm-staking-claimed", address)
+}
+
+async fn read_amount(storage: &(dyn Storage + Send + Sync), slot: [u8; 32]) -> U256 {
+    U256::from_big_endian(&storage.get_storage_value(&STAKING_ADDRESS, slot).await)
+}
+
+async fn write_amount(storage: &mut (dyn Storage + Send + Sync), slot: [u8; 32], amount: U256) {
+    let mut word = [0u8; 32];
+    amount.to_big_endian(&mut word);
+    storage
+        .set_storage_value(&STAKING_ADDRESS, slot, word)
+        .await;
+}
+
+/// 奖励地址的待领取奖励
+pub async fn pending_of(storage: &(dyn Storage + Send + Sync), address: &Address) -> U256 {
+    read_amount(storage, pending_slot(address)).await
+}
+
+/// 奖励地址的已领取奖励
+pub async fn claimed_of(storage: &(dyn Storage + Send + Sync), address: &Address) -> U256 {
+    read_amount(storage, claimed_slot(address)).await
+}
+
+/// 将纪元奖励记入各奖励地址的待领取金额
+pub async fn credit(storage: &mut (dyn Storage + Send + Sync), rewards: &EpochRewards) {
+    for reward in rewards.rewards.iter().filter(|r| !r.reward.is_zero()) {
+        let slot = pending_slot(&reward.reward_address);
+        let pending = read_amount(storage, slot).await;
+        write_amount(storage, slot, pending.saturating_add(reward.reward)).await;
+    }
+}
+
+/// 从奖励地址的待领取奖励中扣除罚金，不足时扣完为止，返回实际扣除的金额
+pub async fn slash(
+    storage: &mut (dyn Storage + Send + Sync),
+    address: &Address,
+    amount: U256,
+) -> U256 {
+    let slot = pending_slot(address);
+    let pending = read_amount(storage, slot).await;
+    let slashed = pending.min(amount);
+    write_amount(storage, slot, pending - slashed).await;
+    slashed
+}
+
+/// 执行领取交易，将发送方的待领取奖励转入余额，返回产生的日志
+pub async fn claim(
+    storage: &mut (dyn Storage + Send + Sync),
+    tx: &Transaction,
+) -> Result<Vec<(Address, Vec<H256>, Vec<u8>)>, StakingError> {
+    if !is_staking_transaction(tx) {
+        return Err(StakingError::InvalidTransaction(
+            "目标地址不是质押系统合约".into(),
+        ));
+    }
+    if !tx.value.is_zero() {
+        return Err(StakingError::InvalidTransaction(
+            "领取交易不能附带转账".into(),
+        ));
+    }
+    let pending = pending_of(storage, &tx.from).await;
+    if pending.is_zero() {
+        return Err(StakingError::NothingToClaim(tx.from));
+    }
+    write_amount(storage, pending_slot(&tx.from), U256::zero()).await;
+    let claimed = claimed_of(storage, &tx.from).await;
+    write_amount(
+        storage,
+        claimed_slot(&tx.from),
+        claimed.saturating_add(pending),
+    )
+    .await;
+    let balance = storage.get_balance(&tx.from).await;
+    storage
+        .set_balance(&tx.from, balance.saturating_add(pending))
+        .await;
+
+    let mut data = [0u8; 32];
+    pending.to_big_endian(&mut data);
+    Ok(vec![(
+        STAKING_ADDRESS,
+        vec![
+            H256(ethers::utils::keccak256("RewardsClaimed(address,uint256)")),
+            H256::from(H160::from(tx.from)),
+        ],
+        data.to_vec(),
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+
+    fn config() -> StakingConfig {
+        StakingConfig {
+            validators: vec![
+                ValidatorStake {
+                    node_id: "NodeID-A".to_string(),
+                    reward_address: Address([1u8; 20]),
+                    stake: U256::from(300),
+                },
+                ValidatorStake {
+                    node_id: "NodeID-B".to_string(),
+                    reward_address: Address([2u8; 20]),
+                    stake: U256::from(100),
+                },
+            ],
+            epoch_length: 10,
+            epoch_reward: U256::from(1_000),
+            stake_weight_percent: 50,
+            jailing: None,
+        }
+    }
+
+    fn claim_tx(from: Address) -> Transaction {
+        Transaction {
+            hash: H256::from_low_u64_be(1),
+            from,
+            to: Some(STAKING_ADDRESS),
+            value: U256::zero(),
+            nonce: 0,
+            gas_limit: STAKING_CLAIM_GAS,
+            gas_price: Some(U256::from(1)),
+            data: Vec::new(),
+            signature: Vec::new(),
+            transaction_type: TransactionType::Legacy,
+            chain_id: 1,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+        }
+    }
+
+    #[test]
+    fn test_epochs() {
+        let config = config();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.epoch_of(1), 0);
+        assert_eq!(config.epoch_of(10), 0);
+        assert_eq!(config.epoch_of(11), 1);
+        assert_eq!(config.epoch_range(1), (11, 20));
+        assert!(config.is_epoch_end(20));
+        assert!(!config.is_epoch_end(0));
+        assert_eq!(
+            config
+                .find("0x0202020202020202020202020202020202020202")
+                .unwrap()
+                .node_id,
+            "NodeID-B"
+        );
+
+        let mut invalid = config.clone();
+        invalid.validators.push(invalid.validators[0].clone());
+        assert!(invalid.validate().is_err());
+        invalid.epoch_length = 0;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_epoch_rewards() {
+        let config = config();
+        let proposals = HashMap::from([("NodeID-A".to_string(), 2), ("NodeID-B".to_string(), 8)]);
+        let rewards = config.epoch_rewards(0, &proposals);
+        // 质押部分 500 按 3:1，出块部分 500 按 2:8
+        assert_eq!(rewards.rewards[0].reward, U256::from(375 + 100));
+        assert_eq!(rewards.rewards[1].reward, U256::from(125 + 400));
+        assert_eq!(rewards.total(), U256::from(1_000));
+
+        let rewards = config.epoch_rewards(0, &HashMap::new());
+        assert_eq!(rewards.rewards[0].reward, U256::from(750));
+        assert_eq!(rewards.rewards[1].reward, U256::from(250));
+    }
+
+    #[tokio::test]
+    async fn test_credit_and_claim() {
+        let config = config();
+        let mut storage = MemoryStorage::default();
+        let rewards = config.epoch_rewards(0, &HashMap::new());
+        credit(&mut storage, &rewards).await;
+        credit(&mut storage, &rewards).await;
+        let address = Address([1u8; 20]);
+        assert_eq!(pending_of(&storage, &address).await, U256::from(1_500));
+
+        let logs = claim(&mut storage, &claim_tx(address)).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(storage.get_balance(&address).await, U256::from(1_500));
+        assert!(pending_of(&storage, &address).await.is_zero());
+        assert_eq!(claimed_of(&storage, &address).await, U256::from(1_500));
+        assert_eq!(
+            claim(&mut storage, &claim_tx(address)).await,
+            Err(StakingError::NothingToClaim(address))
+        );
+    }
+}
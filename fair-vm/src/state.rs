@@ -1,10 +1,11 @@
 use crate::account::Account;
 use crate::account::Address;
 use crate::evm::EvmContext;
-use crate::storage::{MemoryStorage, Storage};
+use crate::receipt::Receipt;
+use crate::storage::{MemoryStorage, StateHandle, StateService, Storage, StorageEntry};
 use crate::transaction::Transaction;
 use async_trait::async_trait;
-use ethers::types::{TransactionReceipt, H160, H256, U256};
+use ethers::types::{H160, H256, U256};
 use fair_vm_core::types::{Address as CoreAddress, Hash as CoreHash};
 use fair_vm_core::vm::State as StateTrait;
 use std::collections::HashMap;
@@ -15,24 +16,19 @@ use tokio::sync::RwLock;
 /// 状态类型
 #[derive(Debug, Clone)]
 pub struct State {
-    /// 存储
-    storage: Arc<RwLock<Box<dyn Storage + Send + Sync>>>,
+    /// 状态服务句柄
+    storage: StateHandle,
     context: EvmContext,
     /// 账户交易列表
     account_transactions: Arc<RwLock<HashMap<Address, Vec<Transaction>>>>,
-    /// 交易收据
-    transaction_receipts: Arc<RwLock<HashMap<H256, TransactionReceipt>>>,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
-            storage: Arc::new(RwLock::new(
-                Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-            )),
+            storage: StateService::spawn_with_snapshots(Box::new(MemoryStorage::default())),
             context: EvmContext::default(),
             account_transactions: Arc::new(RwLock::new(HashMap::new())),
-            transaction_receipts: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -40,133 +36,164 @@ impl Default for State {
 #[async_trait]
 impl Storage for State {
     async fn get_account(&self, address: &Address) -> Option<Account> {
-        let storage = self.storage.read().await;
-        storage.get_account(address).await
+        self.storage.get_account(address).await
     }
 
     async fn set_account(&mut self, account: &Account) {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_account(account).await;
     }
 
     async fn get_balance(&self, address: &Address) -> U256 {
-        let storage = self.storage.read().await;
-        storage.get_balance(address).await
+        self.storage.get_balance(address).await
     }
 
     async fn set_balance(&mut self, address: &Address, balance: U256) {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_balance(address, balance).await;
     }
 
     async fn get_nonce(&self, address: &Address) -> u64 {
-        let storage = self.storage.read().await;
-        storage.get_nonce(address).await
+        self.storage.get_nonce(address).await
     }
 
     async fn set_nonce(&mut self, address: &Address, nonce: u64) {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_nonce(address, nonce).await;
     }
 
     async fn get_code_hash(&self, address: &Address) -> H256 {
-        let storage = self.storage.read().await;
-        storage.get_code_hash(address).await
+        self.storage.get_code_hash(address).await
     }
 
     async fn set_code_hash(&mut self, address: &Address, code_hash: H256) {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_code_hash(address, code_hash).await;
     }
 
     async fn get_storage_root(&self, address: &Address) -> H256 {
-        let storage = self.storage.read().await;
-        storage.get_storage_root(address).await
+        self.storage.get_storage_root(address).await
     }
 
     async fn set_storage_root(&mut self, address: &Address, storage_root: H256) {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_storage_root(address, storage_root).await;
     }
 
     async fn set_storage_value(&mut self, address: &Address, key: [u8; 32], value: [u8; 32]) {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_storage_value(address, key, value).await;
     }
 
     async fn get_storage_value(&self, address: &Address, key: [u8; 32]) -> [u8; 32] {
-        let storage = self.storage.read().await;
-        storage.get_storage_value(address, key).await
+        self.storage.get_storage_value(address, key).await
+    }
+
+    async fn get_code(&self, address: &Address) -> Vec<u8> {
+        self.storage.get_code(address).await
+    }
+
+    async fn set_code(&mut self, address: &Address, code: Vec<u8>) -> H256 {
+        let mut storage = self.storage.clone();
+        storage.set_code(address, code).await
+    }
+
+    async fn get_code_by_hash(&self, code_hash: &H256) -> Option<Vec<u8>> {
+        self.storage.get_code_by_hash(code_hash).await
+    }
+
+    async fn accounts_page(&self, after: Option<Address>, limit: usize) -> Vec<Account> {
+        self.storage.accounts_page(after, limit).await
+    }
+
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Vec<StorageEntry> {
+        self.storage.storage_page(address, after, limit).await
+    }
+
+    async fn get_receipt(&self, tx_hash: &H256) -> Option<Receipt> {
+        self.storage.get_receipt(tx_hash).await
+    }
+
+    async fn block_receipts(&self, number: u64) -> Vec<Receipt> {
+        self.storage.block_receipts(number).await
+    }
+
+    async fn set_receipt(&mut self, receipt: &Receipt) {
+        let mut storage = self.storage.clone();
+        storage.set_receipt(receipt).await;
+    }
+
+    async fn take_read_error(&self) -> Option<String> {
+        self.storage.take_read_error().await
     }
 }
 
 impl State {
     /// 创建新状态实例
-    pub fn new(storage: Arc<RwLock<Box<dyn Storage + Send + Sync>>>, context: EvmContext) -> Self {
+    pub fn new(storage: StateHandle, context: EvmContext) -> Self {
         Self {
             storage,
             context,
             account_transactions: Arc::new(RwLock::new(HashMap::new())),
-            transaction_receipts: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
     /// 获取账户信息
     pub async fn get_account(&self, address: &Address) -> Option<Account> {
-        let storage = self.storage.read().await;
-        storage.get_account(address).await
+        self.storage.get_account(address).await
     }
 
     /// 设置账户信息
     pub async fn set_account(&mut self, account: &Account) -> Result<(), String> {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_account(account).await;
         Ok(())
     }
 
     /// 获取账户余额
     pub async fn get_balance(&self, address: &Address) -> U256 {
-        let storage = self.storage.read().await;
-        storage.get_balance(address).await
+        self.storage.get_balance(address).await
     }
 
     /// 设置账户余额
     pub async fn set_balance(&self, address: &Address, balance: U256) -> Result<(), String> {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_balance(address, balance).await;
         Ok(())
     }
 
     /// 获取账户 nonce
     pub async fn get_nonce(&self, address: &Address) -> u64 {
-        let storage = self.storage.read().await;
-        storage.get_nonce(address).await
+        self.storage.get_nonce(address).await
     }
 
     /// 设置账户 nonce
     pub async fn set_nonce(&self, address: &Address, nonce: u64) -> Result<(), String> {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_nonce(address, nonce).await;
         Ok(())
     }
 
     /// 获取账户代码哈希
     pub async fn get_code_hash(&self, address: &Address) -> H256 {
-        let storage = self.storage.read().await;
-        storage.get_code_hash(address).await
+        self.storage.get_code_hash(address).await
     }
 
     /// 设置账户代码哈希
     pub async fn set_code_hash(&self, address: &Address, code_hash: H256) -> Result<(), String> {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_code_hash(address, code_hash).await;
         Ok(())
     }
 
     /// 获取账户存储根
     pub async fn get_storage_root(&self, address: &Address) -> H256 {
-        let storage = self.storage.read().await;
-        storage.get_storage_root(address).await
+        self.storage.get_storage_root(address).await
     }
 
     /// 设置账户存储根
@@ -175,19 +202,21 @@ impl State {
         address: &Address,
         storage_root: H256,
     ) -> Result<(), String> {
-        let mut storage = self.storage.write().await;
+        let mut storage = self.storage.clone();
         storage.set_storage_root(address, storage_root).await;
         Ok(())
     }
 
-    /// 获取存储实例
-    pub fn storage(&self) -> &Arc<RwLock<Box<dyn Storage + Send + Sync>>> {
+    /// 获取状态服务句柄
+    pub fn storage(&self) -> &StateHandle {
         &self.storage
     }
 
     /// 获取状态根
-    pub fn get_state_root(&self) -> H256 {
-        H256::zero()
+    pub async fn get_state_root(&self) -> H256 {
+        crate::state_proof::StateCommitment::from_storage(self)
+            .await
+            .root()
     }
 
     pub fn context(&self) -> &EvmContext {
@@ -207,18 +236,12 @@ impl State {
         account_transactions.push(transaction);
     }
 
-    /// 获取交易收据
-    pub async fn get_transaction_receipt(&self, tx_hash: &[u8]) -> Option<TransactionReceipt> {
-        let mut hash = [0u8; 32];
-        hash.copy_from_slice(tx_hash);
-        let receipts = self.transaction_receipts.read().await;
-        receipts.get(&H256::from_slice(&hash)).cloned()
-    }
-
-    /// 添加交易收据
-    pub async fn add_transaction_receipt(&self, tx_hash: H256, receipt: TransactionReceipt) {
-        let mut receipts = self.transaction_receipts.write().await;
-        receipts.insert(tx_hash, receipt);
+    /// 获取交易收据，哈希长度无效时返回 None
+    pub async fn get_transaction_receipt(&self, tx_hash: &[u8]) -> Option<Receipt> {
+        if tx_hash.len() != 32 {
+            return None;
+        }
+        self.storage.get_receipt(&H256::from_slice(tx_hash)).await
     }
 }
 
@@ -242,14 +265,7 @@ impl StateTrait for State {
         let bytes = address.as_bytes();
         let h160 = H160::from_slice(bytes);
         let local_address = Address::from(h160);
-        let code_hash = self.get_code_hash(&local_address).await;
-        if code_hash.is_zero() {
-            Ok(Vec::new())
-        } else {
-            let storage = self.storage.read().await;
-            let code_bytes = storage.get_storage_value(&local_address, [0u8; 32]).await;
-            Ok(code_bytes.to_vec())
-        }
+        Ok(self.storage.get_code(&local_address).await)
     }
 
     async fn get_storage(
@@ -347,8 +363,8 @@ impl StateTrait for State {
         let bytes = address.as_bytes();
         let h160 = H160::from_slice(bytes);
         let local_address = Address::from(h160);
-        let code_hash = H256::from_slice(&code);
-        let _ = self.set_code_hash(&local_address, code_hash).await;
+        let mut storage = self.storage.clone();
+        storage.set_code(&local_address, code).await;
         Ok(())
     }
 }
@@ -360,18 +376,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_state_new() {
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-        ));
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let state = State::new(storage.clone(), EvmContext::default());
-        let _guard = state.storage().read().await;
+        assert_eq!(state.storage().get_nonce(&Address::default()).await, 0);
     }
 
     #[tokio::test]
     async fn test_state_account_operations() {
-        let storage = Arc::new(RwLock::new(
-            Box::new(MemoryStorage::default()) as Box<dyn Storage + Send + Sync>
-        ));
+        let storage = StateService::spawn(Box::new(MemoryStorage::default()));
         let mut state = State::new(storage.clone(), EvmContext::default());
         let address = Address::from(H160::zero());
         let account = Account::new(address);
@@ -0,0 +1,462 @@
+//! 账户状态承诺与证明
+//!
+//! 所有账户按地址排序后构成二叉 Merkle 树，状态根同时承诺叶子数量，
+//! 因此既能证明账户存在，也能用相邻的两个叶子证明账户不存在。
+//! 出块者把状态根写入区块头后，轻节点只需同步区块头即可验证关注账户的状态。
+//! 账户叶子中的存储根由账户的存储槽计算（见 [`crate::merkle::storage_root`]），
+//! 状态根因此同时承诺了合约存储；[`StorageProof`] 对照已验证账户的存储根证明单个存储槽。
+
+use crate::account::{Account, Address};
+use crate::merkle;
+use crate::storage::{Storage, StorageEntry};
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// 构建承诺时每次读取的账户数
+const ACCOUNTS_PAGE_SIZE: usize = 1024;
+
+/// 证明错误类型
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProofError {
+    #[error("证明的状态根 {actual:?} 与期望的 {expected:?} 不一致")]
+    RootMismatch { expected: H256, actual: H256 },
+
+    #[error("证明的账户 {0} 与查询地址不一致")]
+    AddressMismatch(Address),
+
+    #[error("无效的证明: {0}")]
+    Malformed(String),
+}
+
+/// 账户叶子哈希
+pub fn account_leaf(account: &Account) -> H256 {
+    let mut balance = [0u8; 32];
+    account.balance.to_big_endian(&mut balance);
+    let mut hasher = Keccak256::new();
+    hasher.update([0u8]);
+    hasher.update(account.address.0);
+    hasher.update(balance);
+    hasher.update(account.nonce.to_be_bytes());
+    hasher.update(account.code_hash.as_bytes());
+    hasher.update(account.storage_root.as_bytes());
+    H256(hasher.finalize().into())
+}
+
+/// 由树根和叶子数量计算状态根，空状态的状态根为零
+fn state_root(tree_root: H256, leaf_count: u64) -> H256 {
+    merkle::tagged_root(b"fairvm-state", tree_root, leaf_count)
+}
+
+/// 账户存在证明
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub account: Account,
+    /// 叶子序号
+    pub index: u64,
+    /// 自底向上的兄弟节点，层宽为奇数时最后一个节点没有兄弟
+    pub siblings: Vec<H256>,
+}
+
+impl AccountProof {
+    /// 由证明计算树根
+    fn tree_root(&self, leaf_count: u64) -> Result<H256, ProofError> {
+        merkle::branch_root(
+            account_leaf(&self.account),
+            self.index,
+            leaf_count,
+            &self.siblings,
+        )
+    }
+}
+
+/// 证明内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum ProofKind {
+    /// 账户存在
+    Present(AccountProof),
+    /// 账户不存在：地址两侧相邻的叶子，位于边界时对应一侧为空
+    Absent {
+        left: Option<AccountProof>,
+        right: Option<AccountProof>,
+    },
+}
+
+/// 账户状态证明
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateProof {
+    pub address: Address,
+    /// 状态中的账户数量
+    pub leaf_count: u64,
+    pub proof: ProofKind,
+}
+
+impl StateProof {
+    /// 验证证明，返回账户状态，账户不存在时返回 `None`
+    pub fn verify(&self, expected_root: H256) -> Result<Option<Account>, ProofError> {
+        let check = |proof: &AccountProof| -> Result<(), ProofError> {
+            let actual = state_root(proof.tree_root(self.leaf_count)?, self.leaf_count);
+            if actual != expected_root {
+                return Err(ProofError::RootMismatch {
+                    expected: expected_root,
+                    actual,
+                });
+            }
+            Ok(())
+        };
+        match &self.proof {
+            ProofKind::Present(proof) => {
+                if proof.account.address != self.address {
+                    return Err(ProofError::AddressMismatch(proof.account.address));
+                }
+                check(proof)?;
+                Ok(Some(proof.account.clone()))
+            }
+            ProofKind::Absent { left, right } => {
+                if let Some(left) = left {
+                    check(left)?;
+                    if left.account.address >= self.address {
+                        return Err(ProofError::Malformed("左侧叶子不在查询地址之前".into()));
+                    }
+                }
+                if let Some(right) = right {
+                    check(right)?;
+                    if right.account.address <= self.address {
+                        return Err(ProofError::Malformed("右侧叶子不在查询地址之后".into()));
+                    }
+                }
+                let adjacent = match (left, right) {
+                    (Some(left), Some(right)) => left.index + 1 == right.index,
+                    (Some(left), None) => left.index + 1 == self.leaf_count,
+                    (None, Some(right)) => right.index == 0,
+                    (None, None) => self.leaf_count == 0 && expected_root.is_zero(),
+                };
+                if !adjacent {
+                    return Err(ProofError::Malformed("两侧叶子不相邻".into()));
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// 账户状态承诺
+#[derive(Debug, Clone, Default)]
+pub struct StateCommitment {
+    accounts: Vec<Account>,
+    /// 自底向上的各层节点，第 0 层为叶子
+    layers: Vec<Vec<H256>>,
+}
+
+impl StateCommitment {
+    /// 由按地址排序的账户构建承诺
+    pub fn new(mut accounts: Vec<Account>) -> Self {
+        accounts.sort_by_key(|account| account.address);
+        let layers = merkle::layers(accounts.iter().map(account_leaf).collect());
+        Self { accounts, layers }
+    }
+
+    /// 读取存储中的全部账户构建承诺，账户的存储根按其存储槽重新计算
+    pub async fn from_storage(storage: &(dyn Storage + Send + Sync)) -> Self {
+        let mut accounts = Vec::new();
+        loop {
+            let after = accounts.last().map(|account: &Account| account.address);
+            let page = storage.accounts_page(after, ACCOUNTS_PAGE_SIZE).await;
+            let done = page.len() < ACCOUNTS_PAGE_SIZE;
+            accounts.extend(page);
+            if done {
+                break;
+            }
+        }
+        for account in &mut accounts {
+            account.storage_root = merkle::account_storage_root(storage, &account.address).await;
+        }
+        Self::new(accounts)
+    }
+
+    /// 账户数量
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// 是否没有账户
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// 状态根
+    pub fn root(&self) -> H256 {
+        let tree_root = self
+            .layers
+            .last()
+            .and_then(|layer| layer.first())
+            .copied()
+            .unwrap_or_default();
+        state_root(tree_root, self.accounts.len() as u64)
+    }
+
+    fn account_proof(&self, index: usize) -> AccountProof {
+        AccountProof {
+            account: self.accounts[index].clone(),
+            index: index as u64,
+            siblings: merkle::branch(&self.layers, index),
+        }
+    }
+
+    /// 生成账户的存在或不存在证明
+    pub fn prove(&self, address: &Address) -> StateProof {
+        let proof = match self
+            .accounts
+            .binary_search_by_key(address, |account| account.address)
+        {
+            Ok(index) => ProofKind::Present(self.account_proof(index)),
+            Err(index) => ProofKind::Absent {
+                left: index.checked_sub(1).map(|left| self.account_proof(left)),
+                right: (index < self.accounts.len()).then(|| self.account_proof(index)),
+            },
+        };
+        StateProof {
+            address: *address,
+            leaf_count: self.accounts.len() as u64,
+            proof,
+        }
+    }
+}
+
+/// 存储槽叶子证明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotProof {
+    pub key: H256,
+    pub value: H256,
+    /// 叶子序号
+    pub index: u64,
+    /// 自底向上的兄弟节点
+    pub siblings: Vec<H256>,
+}
+
+impl SlotProof {
+    /// 由证明计算存储根
+    fn storage_root(&self, leaf_count: u64) -> Result<H256, ProofError> {
+        let leaf = merkle::storage_leaf(&(self.key.0, self.value.0));
+        let tree_root = merkle::branch_root(leaf, self.index, leaf_count, &self.siblings)?;
+        Ok(merkle::tagged_root(
+            merkle::STORAGE_TAG,
+            tree_root,
+            leaf_count,
+        ))
+    }
+}
+
+/// 存储槽证明内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SlotProofKind {
+    /// 存储槽存在
+    Present(SlotProof),
+    /// 存储槽不存在或值为零：键两侧相邻的叶子，位于边界时对应一侧为空
+    Absent {
+        left: Option<SlotProof>,
+        right: Option<SlotProof>,
+    },
+}
+
+/// 存储槽证明，对照账户的存储根验证
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageProof {
+    pub key: H256,
+    /// 账户中值不为零的存储槽数量
+    pub leaf_count: u64,
+    pub proof: SlotProofKind,
+}
+
+impl StorageProof {
+    /// 由按键升序排列的存储槽生成 `key` 的证明，值为零的槽视为不存在
+    pub fn prove(entries: &[StorageEntry], key: [u8; 32]) -> Self {
+        let slots: Vec<&StorageEntry> = entries
+            .iter()
+            .filter(|(_, value)| *value != [0u8; 32])
+            .collect();
+        let layers = merkle::layers(
+            slots
+                .iter()
+                .map(|entry| merkle::storage_leaf(entry))
+                .collect(),
+        );
+        let slot_proof = |index: usize| SlotProof {
+            key: H256(slots[index].0),
+            value: H256(slots[index].1),
+            index: index as u64,
+            siblings: merkle::branch(&layers, index),
+        };
+        let proof = match slots.binary_search_by_key(&key, |entry| entry.0) {
+            Ok(index) => SlotProofKind::Present(slot_proof(index)),
+            Err(index) => SlotProofKind::Absent {
+                left: index.checked_sub(1).map(slot_proof),
+                right: (index < slots.len()).then(|| slot_proof(index)),
+            },
+        };
+        Self {
+            key: H256(key),
+            leaf_count: slots.len() as u64,
+            proof,
+        }
+    }
+
+    /// 验证证明，返回存储槽的值，槽不存在时为零
+    pub fn verify(&self, storage_root: H256) -> Result<[u8; 32], ProofError> {
+        let check = |proof: &SlotProof| -> Result<(), ProofError> {
+            let actual = proof.storage_root(self.leaf_count)?;
+            if actual != storage_root {
+                return Err(ProofError::RootMismatch {
+                    expected: storage_root,
+                    actual,
+                });
+            }
+            Ok(())
+        };
+        match &self.proof {
+            SlotProofKind::Present(proof) => {
+                if proof.key != self.key {
+                    return Err(ProofError::Malformed("证明的存储槽与查询的键不一致".into()));
+                }
+                check(proof)?;
+                Ok(proof.value.0)
+            }
+            SlotProofKind::Absent { left, right } => {
+                if let Some(left) = left {
+                    check(left)?;
+                    if left.key >= self.key {
+                        return Err(ProofError::Malformed("左侧叶子不在查询的键之前".into()));
+                    }
+                }
+                if let Some(right) = right {
+                    check(right)?;
+                    if right.key <= self.key {
+                        return Err(ProofError::Malformed("右侧叶子不在查询的键之后".into()));
+                    }
+                }
+                let adjacent = match (left, right) {
+                    (Some(left), Some(right)) => left.index + 1 == right.index,
+                    (Some(left), None) => left.index + 1 == self.leaf_count,
+                    (None, Some(right)) => right.index == 0,
+                    (None, None) => self.leaf_count == 0 && storage_root.is_zero(),
+                };
+                if !adjacent {
+                    return Err(ProofError::Malformed("两侧叶子不相邻".into()));
+                }
+                Ok([0u8; 32])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U256;
+
+    fn account(byte: u8, balance: u64) -> Account {
+        let mut account = Account::new(Address([byte; 20]));
+        account.balance = U256::from(balance);
+        account
+    }
+
+    #[test]
+    fn test_proofs() {
+        for count in 1..=7u8 {
+            let accounts: Vec<Account> = (0..count).map(|i| account(i * 2 + 2, i as u64)).collect();
+            let commitment = StateCommitment::new(accounts.clone());
+            let root = commitment.root();
+            assert!(!root.is_zero());
+
+            for account in &accounts {
+                let proof = commitment.prove(&account.address);
+                assert_eq!(proof.verify(root).unwrap().as_ref(), Some(account));
+            }
+            // 最小、中间、最大位置的不存在证明
+            for byte in [1, 3, 0xff] {
+                let proof = commitment.prove(&Address([byte; 20]));
+                assert_eq!(proof.verify(root).unwrap(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs() {
+        let commitment = StateCommitment::new(vec![account(2, 1), account(4, 2), account(6, 3)]);
+        let root = commitment.root();
+
+        let mut proof = commitment.prove(&Address([4; 20]));
+        if let ProofKind::Present(inner) = &mut proof.proof {
+            inner.account.balance = U256::from(100);
+        }
+        assert!(matches!(
+            proof.verify(root),
+            Err(ProofError::RootMismatch { .. })
+        ));
+
+        // 用不相邻的叶子伪造不存在证明
+        let forged = StateProof {
+            address: Address([4; 20]),
+            leaf_count: 3,
+            proof: ProofKind::Absent {
+                left: match commitment.prove(&Address([2; 20])).proof {
+                    ProofKind::Present(proof) => Some(proof),
+                    _ => None,
+                },
+                right: match commitment.prove(&Address([6; 20])).proof {
+                    ProofKind::Present(proof) => Some(proof),
+                    _ => None,
+                },
+            },
+        };
+        assert!(forged.verify(root).is_err());
+
+        let empty = StateCommitment::default();
+        assert!(empty.root().is_zero());
+        assert_eq!(
+            empty.prove(&Address([1; 20])).verify(H256::zero()),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_storage_proofs() {
+        let entries: Vec<StorageEntry> =
+            vec![([2; 32], [1; 32]), ([4; 32], [0; 32]), ([6; 32], [3; 32])];
+        let root = merkle::storage_root(&entries);
+        assert_eq!(
+            StorageProof::prove(&entries, [2; 32]).verify(root),
+            Ok([1; 32])
+        );
+        assert_eq!(
+            StorageProof::prove(&entries, [6; 32]).verify(root),
+            Ok([3; 32])
+        );
+        // 值为零的槽与不存在的槽都证明为零
+        for key in [[1; 32], [4; 32], [0xff; 32]] {
+            assert_eq!(StorageProof::prove(&entries, key).verify(root), Ok([0; 32]));
+        }
+        assert_eq!(
+            StorageProof::prove(&[], [1; 32]).verify(H256::zero()),
+            Ok([0; 32])
+        );
+
+        let mut proof = StorageProof::prove(&entries, [6; 32]);
+        if let SlotProofKind::Present(inner) = &mut proof.proof {
+            inner.value = H256::repeat_byte(9);
+        }
+        assert!(matches!(
+            proof.verify(root),
+            Err(ProofError::RootMismatch { .. })
+        ));
+        let mut proof = StorageProof::prove(&entries, [6; 32]);
+        proof.key = H256::repeat_byte(5);
+        assert!(proof.verify(root).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+//! 快照状态同步
+//!
+//! 新节点不必从创世区块重放全部区块：提供快照的节点按 [`SnapshotPolicy`] 每隔固定区块数冻结一次
+//! 完整状态并签发检查点（[`CheckpointSnapshot`]），通过网络层按账户分块提供下载。新节点向对等节点
+//! 请求最新快照的清单，校验检查点签名者和区块头后逐块下载：账户附带对照检查点状态根的证明，代码按
+//! 代码哈希验证，存储槽按账户的存储根验证，全部下载后再核对完整状态的状态根。之后以快照区块为
+//! 链头，逐个请求后续区块追赶到对等节点的最新高度。
+
+use crate::account::Address;
+use crate::blockchain::BlockHeader;
+use crate::checkpoint::{Checkpoint, CheckpointError, CheckpointSnapshot};
+use crate::fee_stats::BlockFeeStats;
+use crate::merkle;
+use crate::network::NetworkMessage;
+use crate::state_proof::{ProofError, StateCommitment, StateProof};
+use crate::storage::{code_hash, MemoryStorage, Storage, StorageEntry};
+use async_trait::async_trait;
+use ethers::types::{Bytes, H256};
+use futures::StreamExt;
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+
+/// 每个分块包含的最大账户数
+pub const SNAPSHOT_CHUNK_ACCOUNTS: usize = 128;
+
+/// 状态同步错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum StateSyncError {
+    #[error("对等节点请求失败: {0}")]
+    Peer(String),
+
+    #[error("对等节点没有可用的快照")]
+    NoSnapshot,
+
+    #[error("对等节点已不再提供高度 {0} 的快照")]
+    SnapshotGone(u64),
+
+    #[error("意外的响应: {0}")]
+    UnexpectedResponse(String),
+
+    #[error("对等节点返回的区块高度 {number} 不是请求的 {expected}")]
+    UnexpectedBlock { number: u64, expected: u64 },
+
+    #[error("对等节点返回的区块 {0} 未接在本地链头之后")]
+    UnlinkedBlock(u64),
+
+    #[error("账户 {0} 的存储槽与存储根不一致")]
+    StorageMismatch(Address),
+
+    #[error("下载的状态与检查点状态根不一致")]
+    IncompleteState,
+
+    #[error("检查点错误: {0}")]
+    Checkpoint(#[from] CheckpointError),
+
+    #[error("账户证明无效: {0}")]
+    Proof(#[from] ProofError),
+}
+
+/// 周期快照策略，每隔 `interval` 个区块冻结一次状态并用 `secret_key` 签发检查点
+pub struct SnapshotPolicy {
+    pub interval: u64,
+    pub secret_key: SecretKey,
+}
+
+impl SnapshotPolicy {
+    pub fn new(interval: u64, secret_key: SecretKey) -> Result<Self, String> {
+        if interval == 0 {
+            return Err("快照间隔必须大于 0".to_string());
+        }
+        Ok(Self {
+            interval,
+            secret_key,
+        })
+    }
+
+    /// 导入区块 `number` 后是否需要冻结快照
+    pub fn is_due(&self, number: u64) -> bool {
+        number % self.interval == 0
+    }
+}
+
+/// 快照清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotManifest {
+    pub checkpoint: Checkpoint,
+    pub header: BlockHeader,
+    /// 快照区块的费用统计，新节点据此计算下一个区块的基础费用
+    pub fees: Option<BlockFeeStats>,
+}
+
+/// 快照中的账户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotAccount {
+    pub proof: StateProof,
+    /// 账户代码，无代码时为空
+    pub code: Option<Bytes>,
+    /// 按键升序的全部存储槽
+    pub storage: Vec<StorageEntry>,
+}
+
+/// 快照分块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotChunk {
+    /// 快照区块高度
+    pub number: u64,
+    /// 按地址升序的账户
+    pub accounts: Vec<SnapshotAccount>,
+    /// 下一个分块的游标，最后一个分块为空
+    pub next: Option<Address>,
+}
+
+/// 快照中 `after` 之后（不含）的一个分块
+pub async fn chunk(snapshot: &CheckpointSnapshot, after: Option<Address>) -> SnapshotChunk {
+    let storage = snapshot.storage();
+    let page = storage.accounts_page(after, SNAPSHOT_CHUNK_ACCOUNTS).await;
+    let next = match page.last() {
+        Some(last) if page.len() >= SNAPSHOT_CHUNK_ACCOUNTS => Some(last.address),
+        _ => None,
+    };
+    let mut accounts = Vec::with_capacity(page.len());
+    for account in page {
+        let code = if account.code_hash.is_zero() {
+            None
+        } else {
+            storage
+                .get_code_by_hash(&account.code_hash)
+                .await
+                .map(Bytes::from)
+        };
+        accounts.push(SnapshotAccount {
+            proof: snapshot.prove(&account.address),
+            code,
+            storage: storage.iter_storage(&account.address).collect().await,
+        });
+    }
+    SnapshotChunk {
+        number: snapshot.header().number,
+        accounts,
+        next,
+    }
+}
+
+/// 提供快照和区块的对等节点
+#[async_trait]
+pub trait SnapshotPeer: Send + Sync {
+    /// 发送请求并等待响应
+    async fn request(&self, message: NetworkMessage) -> Result<NetworkMessage, StateSyncError>;
+}
+
+/// 下载并验证对等节点的最新快照，返回清单和完整状态
+pub async fn download(
+    peer: &dyn SnapshotPeer,
+    trusted_signers: &[Address],
+) -> Result<(SnapshotManifest, MemoryStorage), StateSyncError> {
+    let manifest = match peer.request(NetworkMessage::GetSnapshot).await? {
+        NetworkMessage::SnapshotManifest(Some(manifest)) => manifest,
+        NetworkMessage::SnapshotManifest(None) => return Err(StateSyncError::NoSnapshot),
+        other => return Err(unexpected(&other)),
+    };
+    manifest.checkpoint.verify(trusted_signers)?;
+    manifest.checkpoint.check_header(&manifest.header)?;
+    let number = manifest.header.number;
+    let state_root = manifest.checkpoint.state_root;
+
+    let mut storage = MemoryStorage::default();
+    let mut after = None;
+    loop {
+        let chunk = match peer
+            .request(NetworkMessage::GetSnapshotChunk { number, after })
+            .await?
+        {
+            NetworkMessage::SnapshotChunk(Some(chunk)) if chunk.number == number => chunk,
+            NetworkMessage::SnapshotChunk(_) => return Err(StateSyncError::SnapshotGone(number)),
+            other => return Err(unexpected(&other)),
+        };
+        for account in chunk.accounts {
+            import_account(&mut storage, state_root, account).await?;
+        }
+        match chunk.next {
+            // 游标必须前进，避免对等节点让下载无限循环
+            Some(next) if after.map_or(true, |after| next > after) => after = Some(next),
+            Some(_) => return Err(StateSyncError::UnexpectedResponse("分块游标未前进".into())),
+            None => break,
+        }
+    }
+    // 逐个账户的证明只说明账户属于该状态，核对状态根才能确认没有遗漏账户
+    if StateCommitment::from_storage(&storage).await.root() != state_root {
+        return Err(StateSyncError::IncompleteState);
+    }
+    Ok((manifest, storage))
+}
+
+/// 验证账户、代码和存储槽后写入本地存储
+async fn import_account(
+    storage: &mut MemoryStorage,
+    state_root: H256,
+    entry: SnapshotAccount,
+) -> Result<(), StateSyncError> {
+    let address = entry.proof.address;
+    let account = entry
+        .proof
+        .verify(state_root)?
+        .ok_or_else(|| StateSyncError::UnexpectedResponse(format!("快照中没有账户 {}", address)))?;
+    let code = entry.code.map(|code| code.to_vec()).unwrap_or_default();
+    if code_hash(&code) != account.code_hash {
+        return Err(CheckpointError::CodeMismatch(account.code_hash).into());
+    }
+    if merkle::storage_root(&entry.storage) != account.storage_root {
+        return Err(StateSyncError::StorageMismatch(address));
+    }
+
+    storage.set_account(&account).await;
+    if !code.is_empty() {
+        storage.set_code(&address, code).await;
+    }
+    for (key, value) in entry.storage {
+        storage.set_storage_value(&address, key, value).await;
+    }
+    Ok(())
+}
+
+fn unexpected(message: &NetworkMessage) -> StateSyncError {
+    StateSyncError::UnexpectedResponse(format!("{:?}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Account;
+    use ethers::types::{H160, U256};
+
+    fn secret_key() -> SecretKey {
+        SecretKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    fn signer() -> Address {
+        let secp = secp256k1::Secp256k1::new();
+        Address::from_public_key(&secp256k1::PublicKey::from_secret_key(&secp, &secret_key()))
+    }
+
+    /// 只提供快照的对等节点，`tamper` 修改返回的分块
+    struct Peer {
+        snapshot: CheckpointSnapshot,
+        tamper: fn(&mut SnapshotChunk),
+    }
+
+    #[async_trait]
+    impl SnapshotPeer for Peer {
+        async fn request(&self, message: NetworkMessage) -> Result<NetworkMessage, StateSyncError> {
+            match message {
+                NetworkMessage::GetSnapshot => {
+                    Ok(NetworkMessage::SnapshotManifest(Some(SnapshotManifest {
+                        checkpoint: self.snapshot.checkpoint().clone(),
+                        header: self.snapshot.header().clone(),
+                        fees: None,
+                    })))
+                }
+                NetworkMessage::GetSnapshotChunk { after, .. } => {
+                    let mut chunk = chunk(&self.snapshot, after).await;
+                    (self.tamper)(&mut chunk);
+                    Ok(NetworkMessage::SnapshotChunk(Some(chunk)))
+                }
+                other => Err(StateSyncError::Peer(format!("不支持的请求 {:?}", other))),
+            }
+        }
+    }
+
+    async fn snapshot() -> CheckpointSnapshot {
+        let mut storage = MemoryStorage::default();
+        // 超过一个分块的账户
+        for i in 0..SNAPSHOT_CHUNK_ACCOUNTS as u64 + 10 {
+            let mut account = Account::new(Address::from(H160::from_low_u64_be(i + 1)));
+            account.balance = U256::from(i * 100);
+            storage.set_account(&account).await;
+        }
+        let contract = Address::from(H160::from_low_u64_be(5));
+        storage.set_code(&contract, vec![0x60, 0x00]).await;
+        storage.set_storage_value(&contract, [1; 32], [9; 32]).await;
+        let header = BlockHeader {
+            parent_hash: H256::repeat_byte(1),
+            number: 100,
+            timestamp: 1_000,
+            transactions_root: H256::zero(),
+            state_root: StateCommitment::from_storage(&storage).await.root(),
+            receipts_root: H256::zero(),
+            difficulty: 0,
+            block_reward: 0,
+            proposer: None,
+            arrivals_root: H256::zero(),
+        };
+        CheckpointSnapshot::capture(&storage, header, &secret_key())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_download() {
+        let peer = Peer {
+            snapshot: snapshot().await,
+            tamper: |_| {},
+        };
+        let (manifest, storage) = download(&peer, &[signer()]).await.unwrap();
+        assert_eq!(manifest.header.number, 100);
+        let contract = Address::from(H160::from_low_u64_be(5));
+        assert_eq!(storage.get_code(&contract).await, vec![0x60, 0x00]);
+        assert_eq!(storage.get_storage_value(&contract, [1; 32]).await, [9; 32]);
+        assert_eq!(
+            storage
+                .accounts_page(None, SNAPSHOT_CHUNK_ACCOUNTS * 2)
+                .await
+                .len(),
+            SNAPSHOT_CHUNK_ACCOUNTS + 10
+        );
+        assert!(matches!(
+            download(&peer, &[Address([1; 20])]).await,
+            Err(StateSyncError::Checkpoint(
+                CheckpointError::UntrustedSigner(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_tampered_chunks() {
+        let storage_tampered = Peer {
+            snapshot: snapshot().await,
+            tamper: |chunk| {
+                for account in &mut chunk.accounts {
+                    for entry in &mut account.storage {
+                        entry.1 = [8; 32];
+                    }
+                }
+            },
+        };
+        assert!(matches!(
+            download(&storage_tampered, &[signer()]).await,
+            Err(StateSyncError::StorageMismatch(_))
+        ));
+
+        // 省略账户的分块无法通过状态根核对
+        let incomplete = Peer {
+            snapshot: snapshot().await,
+            tamper: |chunk| {
+                chunk.accounts.pop();
+            },
+        };
+        assert!(matches!(
+            download(&incomplete, &[signer()]).await,
+            Err(StateSyncError::IncompleteState)
+        ));
+    }
+}
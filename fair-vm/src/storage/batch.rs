@@ -0,0 +1,110 @@
+//! 批量写入
+//!
+//! [`StorageBatch`] 在底层存储之上开启一批写入：批内的读写经 [`StorageBatch::storage`] 返回的
+//! 句柄进行，读取能看到批内已写入的值，底层存储在提交前不变。[`StorageBatch::commit`] 在一次
+//! 请求内把整批写入应用到底层存储，[`StorageBatch::rollback`] 丢弃整批写入。区块执行在批内
+//! 执行全部交易，执行失败时回滚，成功时取出写操作经预写日志提交。
+
+use crate::storage::overlay::ChangeSet;
+use crate::storage::{OverlayStorage, StateHandle, StateService, Storage, WalOp};
+use ethers::types::U256;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 一批尚未提交的写入
+#[derive(Debug)]
+pub struct StorageBatch {
+    base: StateHandle,
+    staged: StateHandle,
+    changes: Arc<RwLock<ChangeSet>>,
+}
+
+impl StorageBatch {
+    /// 在底层存储之上开启一批写入
+    pub fn begin(base: StateHandle) -> Self {
+        let overlay = OverlayStorage::new(base.clone());
+        let changes = overlay.changes();
+        Self {
+            base,
+            staged: StateService::spawn(Box::new(overlay)),
+            changes,
+        }
+    }
+
+    /// 批内读写使用的句柄
+    pub fn storage(&self) -> StateHandle {
+        self.staged.clone()
+    }
+
+    /// 批内写入的账户相对底层存储的余额变化，返回（减少总额，增加总额）
+    pub async fn balance_changes(&self) -> (U256, U256) {
+        let accounts: Vec<_> = self.changes.read().await.accounts().cloned().collect();
+        let (mut debited, mut credited) = (U256::zero(), U256::zero());
+        for account in accounts {
+            let before = self.base.get_balance(&account.address).await;
+            if account.balance < before {
+                debited = debited.saturating_add(before - account.balance);
+            } else {
+                credited = credited.saturating_add(account.balance - before);
+            }
+        }
+        (debited, credited)
+    }
+
+    /// 取出批内的全部写操作，不应用到底层存储
+    pub async fn into_ops(self) -> Vec<WalOp> {
+        std::mem::take(&mut *self.changes.write().await).into_ops()
+    }
+
+    /// 把整批写入原子地应用到底层存储，返回应用的写操作数
+    pub async fn commit(self) -> usize {
+        let base = self.base.clone();
+        let ops = self.into_ops().await;
+        let count = ops.len();
+        base.apply(ops).await;
+        count
+    }
+
+    /// 丢弃整批写入，之后通过批内句柄写入的值也不会提交
+    pub async fn rollback(self) {
+        *self.changes.write().await = ChangeSet::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, Address};
+    use crate::storage::MemoryStorage;
+
+    #[tokio::test]
+    async fn test_commit_and_rollback() {
+        let address = Address([1u8; 20]);
+        let mut base = MemoryStorage::new();
+        base.set_account(&Account::new(address)).await;
+        let base = StateService::spawn(Box::new(base));
+
+        let batch = StorageBatch::begin(base.clone());
+        let mut staged = batch.storage();
+        staged.set_balance(&address, U256::from(5)).await;
+        staged
+            .set_storage_value(&address, [1u8; 32], [2u8; 32])
+            .await;
+        assert_eq!(staged.get_balance(&address).await, U256::from(5));
+        assert_eq!(base.get_balance(&address).await, U256::zero());
+        assert_eq!(batch.balance_changes().await, (U256::zero(), U256::from(5)));
+        batch.rollback().await;
+        assert_eq!(base.get_balance(&address).await, U256::zero());
+        assert_eq!(base.get_storage_value(&address, [1u8; 32]).await, [0u8; 32]);
+
+        let batch = StorageBatch::begin(base.clone());
+        let mut staged = batch.storage();
+        staged.set_balance(&address, U256::from(7)).await;
+        staged
+            .set_storage_value(&address, [1u8; 32], [3u8; 32])
+            .await;
+        assert_eq!(batch.commit().await, 2);
+        assert_eq!(base.get_balance(&address).await, U256::from(7));
+        assert_eq!(base.get_storage_value(&address, [1u8; 32]).await, [3u8; 32]);
+    }
+}
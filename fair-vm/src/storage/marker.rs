@@ -0,0 +1,264 @@
+//! 状态标记
+//!
+//! 数据目录中的 `STATE_MARKER` 文件记录写入该目录的插件版本、磁盘格式版本、链 ID 和最后接受的
+//! 区块。新版本插件接管已有数据目录时，先由 [`super::schema::migrate`] 完成格式迁移，再用
+//! [`take_over`] 核对标记：链 ID 不同、标记的格式版本高于当前版本、或预写日志中待重放的区块与
+//! 最后接受的区块接不上时拒绝启动，而不是在不兼容的数据上继续运行。核对通过后标记更新为当前版本，
+//! 之后每提交一个区块更新一次最后接受的区块。
+
+use crate::storage::schema::SCHEMA_VERSION;
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs;
+
+/// 状态标记文件名
+pub const STATE_MARKER_FILE_NAME: &str = "STATE_MARKER";
+
+/// 状态标记错误
+#[derive(Debug, Error)]
+pub enum MarkerError {
+    #[error("状态标记无效: {0}")]
+    Invalid(String),
+
+    #[error("数据目录属于链 {found}，当前配置的链为 {expected}")]
+    ChainMismatch { found: u64, expected: u64 },
+
+    #[error("数据目录由磁盘格式版本 {found} 的插件 {node_version} 写入，当前插件只支持到版本 {supported}")]
+    SchemaMismatch {
+        found: u32,
+        supported: u32,
+        node_version: String,
+    },
+
+    #[error("最后接受的区块为 {} ({:?})，与待恢复的区块 {} ({:?}) 接不上", last.number, last.hash, pending.number, pending.hash)]
+    Discontinuous {
+        last: AcceptedBlock,
+        pending: AcceptedBlock,
+    },
+
+    #[error("最后接受的区块 {} 的哈希为 {:?}，本地区块的哈希为 {local:?}", last.number, last.hash)]
+    LastAcceptedMismatch { last: AcceptedBlock, local: H256 },
+
+    #[error("IO 错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 已接受的区块
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptedBlock {
+    pub number: u64,
+    pub hash: H256,
+}
+
+/// 状态标记
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateMarker {
+    /// 磁盘格式版本
+    pub schema_version: u32,
+    /// 链 ID
+    pub chain_id: u64,
+    /// 最后写入数据目录的插件版本
+    pub node_version: String,
+    /// 最后接受的区块，尚未接受区块时为空
+    pub last_accepted: Option<AcceptedBlock>,
+}
+
+impl StateMarker {
+    /// 当前插件版本的标记
+    pub fn new(chain_id: u64, last_accepted: Option<AcceptedBlock>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            chain_id,
+            node_version: env!("CARGO_PKG_VERSION").to_string(),
+            last_accepted,
+        }
+    }
+
+    /// 读取数据目录中的标记，没有标记文件时为 None
+    pub async fn load(data_dir: &Path) -> Result<Option<Self>, MarkerError> {
+        match fs::read(marker_path(data_dir)).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| MarkerError::Invalid(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 写入标记，先写临时文件再重命名，崩溃时不会留下半个标记
+    pub async fn store(&self, data_dir: &Path) -> Result<(), MarkerError> {
+        fs::create_dir_all(data_dir).await?;
+        let path = marker_path(data_dir);
+        let tmp = path.with_extension("tmp");
+        let content =
+            serde_json::to_vec_pretty(self).map_err(|e| MarkerError::Invalid(e.to_string()))?;
+        fs::write(&tmp, content).await?;
+        fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    /// 核对当前插件能否接管标记对应的数据
+    ///
+    /// `pending` 为预写日志中待重放的区块，必须是最后接受的区块本身（应用后标记尚未更新）或其
+    /// 下一个区块；`local` 为本地链上与最后接受的区块同高度的区块哈希。
+    pub fn check(
+        &self,
+        chain_id: u64,
+        pending: Option<&AcceptedBlock>,
+        local: Option<H256>,
+    ) -> Result<(), MarkerError> {
+        if self.chain_id != chain_id {
+            return Err(MarkerError::ChainMismatch {
+                found: self.chain_id,
+                expected: chain_id,
+            });
+        }
+        if self.schema_version > SCHEMA_VERSION {
+            return Err(MarkerError::SchemaMismatch {
+                found: self.schema_version,
+                supported: SCHEMA_VERSION,
+                node_version: self.node_version.clone(),
+            });
+        }
+        let Some(last) = self.last_accepted else {
+            return Ok(());
+        };
+        if let Some(local) = local.filter(|local| *local != last.hash) {
+            return Err(MarkerError::LastAcceptedMismatch { last, local });
+        }
+        if let Some(pending) = pending {
+            let continues = if pending.number == last.number {
+                pending.hash == last.hash
+            } else {
+                pending.number == last.number + 1
+            };
+            if !continues {
+                return Err(MarkerError::Discontinuous {
+                    last,
+                    pending: *pending,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 标记文件路径
+pub fn marker_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STATE_MARKER_FILE_NAME)
+}
+
+/// 启动时接管数据目录：核对已有标记并更新为当前插件版本，没有标记时创建
+pub async fn take_over(
+    data_dir: &Path,
+    chain_id: u64,
+    pending: Option<&AcceptedBlock>,
+    local: Option<H256>,
+) -> Result<StateMarker, MarkerError> {
+    let marker = match StateMarker::load(data_dir).await? {
+        Some(previous) => {
+            previous.check(chain_id, pending, local)?;
+            if previous.node_version != env!("CARGO_PKG_VERSION") {
+                log::info!(
+                    "插件从 {} 升级到 {}，接管数据目录 {}",
+                    previous.node_version,
+                    env!("CARGO_PKG_VERSION"),
+                    data_dir.display()
+                );
+            }
+            StateMarker::new(chain_id, previous.last_accepted)
+        }
+        None => StateMarker::new(chain_id, None),
+    };
+    marker.store(data_dir).await?;
+    Ok(marker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64) -> AcceptedBlock {
+        AcceptedBlock {
+            number,
+            hash: H256::from_low_u64_be(number),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let marker = StateMarker::new(1, Some(block(5)));
+        assert!(marker.check(1, None, None).is_ok());
+        assert!(marker.check(1, Some(&block(5)), None).is_ok());
+        assert!(marker
+            .check(1, Some(&block(6)), Some(block(5).hash))
+            .is_ok());
+        assert!(matches!(
+            marker.check(2, None, None),
+            Err(MarkerError::ChainMismatch {
+                found: 1,
+                expected: 2
+            })
+        ));
+        assert!(matches!(
+            marker.check(1, Some(&block(7)), None),
+            Err(MarkerError::Discontinuous { .. })
+        ));
+        let forked = AcceptedBlock {
+            number: 5,
+            hash: H256::repeat_byte(9),
+        };
+        assert!(matches!(
+            marker.check(1, Some(&forked), None),
+            Err(MarkerError::Discontinuous { .. })
+        ));
+        assert!(matches!(
+            marker.check(1, None, Some(forked.hash)),
+            Err(MarkerError::LastAcceptedMismatch { .. })
+        ));
+
+        let newer = StateMarker {
+            schema_version: SCHEMA_VERSION + 1,
+            ..marker
+        };
+        assert!(matches!(
+            newer.check(1, None, None),
+            Err(MarkerError::SchemaMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_take_over() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(StateMarker::load(dir.path()).await.unwrap().is_none());
+        let created = take_over(dir.path(), 1, None, None).await.unwrap();
+        assert_eq!(created.last_accepted, None);
+
+        StateMarker {
+            node_version: "0.0.1".to_string(),
+            ..StateMarker::new(1, Some(block(3)))
+        }
+        .store(dir.path())
+        .await
+        .unwrap();
+        let upgraded = take_over(dir.path(), 1, Some(&block(4)), None)
+            .await
+            .unwrap();
+        assert_eq!(upgraded.node_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(upgraded.last_accepted, Some(block(3)));
+        assert_eq!(StateMarker::load(dir.path()).await.unwrap(), Some(upgraded));
+
+        // 不兼容时不改动已有标记
+        assert!(take_over(dir.path(), 2, None, None).await.is_err());
+        assert_eq!(
+            StateMarker::load(dir.path())
+                .await
+                .unwrap()
+                .unwrap()
+                .chain_id,
+            1
+        );
+    }
+}
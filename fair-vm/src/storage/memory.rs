@@ -1,16 +1,24 @@
 use crate::account::{Account, Address};
-use crate::storage::Storage;
+use crate::receipt::Receipt;
+use crate::storage::{code_hash, Storage, StorageEntry};
 use async_trait::async_trait;
 use ethers::types::{H256, U256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
 
 /// 内存存储实现
 #[derive(Debug, Default)]
 pub struct MemoryStorage {
-    /// 账户存储
-    accounts: HashMap<Address, Account>,
+    /// 账户存储，按地址有序以保证遍历顺序确定
+    accounts: BTreeMap<Address, Account>,
     /// 存储映射
-    storage: HashMap<Address, HashMap<[u8; 32], [u8; 32]>>,
+    storage: HashMap<Address, BTreeMap<[u8; 32], [u8; 32]>>,
+    /// 代码存储，按代码哈希寻址
+    codes: HashMap<H256, Vec<u8>>,
+    /// 收据，按交易哈希寻址
+    receipts: HashMap<H256, Receipt>,
+    /// 区块高度和交易序号到交易哈希的索引
+    block_receipts: BTreeMap<(u64, u64), H256>,
 }
 
 impl MemoryStorage {
@@ -104,8 +112,186 @@ impl Storage for MemoryStorage {
         );
         account_storage.insert(key, value);
     }
+
+    async fn get_code(&self, address: &Address) -> Vec<u8> {
+        let code_hash = self.get_code_hash(address).await;
+        self.codes.get(&code_hash).cloned().unwrap_or_default()
+    }
+
+    async fn set_code(&mut self, address: &Address, code: Vec<u8>) -> H256 {
+        let hash = code_hash(&code);
+        if !code.is_empty() {
+            self.codes.entry(hash).or_insert(code);
+        }
+        self.accounts
+            .entry(*address)
+            .or_insert_with(|| Account::new(*address))
+            .code_hash = hash;
+        hash
+    }
+
+    async fn get_code_by_hash(&self, code_hash: &H256) -> Option<Vec<u8>> {
+        self.codes.get(code_hash).cloned()
+    }
+
+    async fn accounts_page(&self, after: Option<Address>, limit: usize) -> Vec<Account> {
+        self.accounts
+            .range((lower_bound(after), Bound::Unbounded))
+            .take(limit)
+            .map(|(_, account)| account.clone())
+            .collect()
+    }
+
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Vec<StorageEntry> {
+        self.storage
+            .get(address)
+            .map(|slots| {
+                slots
+                    .range((lower_bound(after), Bound::Unbounded))
+                    .take(limit)
+                    .map(|(key, value)| (*key, *value))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    async fn get_receipt(&self, tx_hash: &H256) -> Option<Receipt> {
+        self.receipts.get(tx_hash).cloned()
+    }
+
+    async fn block_receipts(&self, number: u64) -> Vec<Receipt> {
+        // 重组后交易可能在其他区块重新执行，只返回仍属于该区块的收据
+        self.block_receipts
+            .range((number, 0)..=(number, u64::MAX))
+            .filter_map(|(_, hash)| self.receipts.get(hash))
+            .filter(|receipt| receipt.block_number == number)
+            .cloned()
+            .collect()
+    }
+
+    async fn set_receipt(&mut self, receipt: &Receipt) {
+        self.block_receipts.insert(
+            (receipt.block_number, receipt.transaction_index),
+            receipt.transaction_hash,
+        );
+        self.receipts
+            .insert(receipt.transaction_hash, receipt.clone());
+    }
+}
+
+/// 分页起点，不包含游标本身
+fn lower_bound<K>(after: Option<K>) -> Bound<K> {
+    after.map_or(Bound::Unbounded, Bound::Excluded)
 }
 
 // 手动实现 Send 和 Sync
 unsafe impl Send for MemoryStorage {}
 unsafe impl Sync for MemoryStorage {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_code_storage_is_deduplicated() {
+        let mut storage = MemoryStorage::new();
+        let first = Address([1u8; 20]);
+        let second = Address([2u8; 20]);
+        let code = vec![0x60, 0x00, 0x60, 0x00, 0xf3];
+
+        let hash = storage.set_code(&first, code.clone()).await;
+        assert_eq!(storage.set_code(&second, code.clone()).await, hash);
+        assert_eq!(storage.codes.len(), 1);
+        assert_eq!(storage.get_code(&first).await, code);
+        assert_eq!(storage.get_code_hash(&second).await, hash);
+        assert_eq!(storage.get_code_by_hash(&hash).await, Some(code));
+
+        // 代码与存储槽互不影响
+        storage.set_storage_value(&first, hash.0, [7u8; 32]).await;
+        assert_eq!(storage.get_code(&first).await.len(), 5);
+
+        assert_eq!(storage.set_code(&first, Vec::new()).await, H256::zero());
+        assert!(storage.get_code(&first).await.is_empty());
+        assert!(storage.get_code(&Address([3u8; 20])).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_iteration_is_ordered() {
+        use futures::StreamExt;
+
+        let mut storage = MemoryStorage::new();
+        let count = crate::storage::ITER_PAGE_SIZE + 10;
+        for i in (0..count).rev() {
+            let mut bytes = [0u8; 20];
+            bytes[18..].copy_from_slice(&(i as u16).to_be_bytes());
+            storage.set_account(&Account::new(Address(bytes))).await;
+        }
+        let owner = Address([1u8; 20]);
+        for key in [3u8, 1, 2] {
+            storage
+                .set_storage_value(&owner, [key; 32], [key; 32])
+                .await;
+        }
+
+        let accounts: Vec<_> = storage.iter_accounts().collect().await;
+        assert_eq!(accounts.len(), count);
+        assert!(accounts.windows(2).all(|w| w[0].address < w[1].address));
+
+        let slots: Vec<_> = storage.iter_storage(&owner).collect().await;
+        let keys: Vec<u8> = slots.iter().map(|(key, _)| key[0]).collect();
+        assert_eq!(keys, vec![1, 2, 3]);
+        assert_eq!(storage.iter_storage(&Address([9u8; 20])).count().await, 0);
+    }
+
+    fn receipt(hash: u64, block_number: u64, transaction_index: u64) -> Receipt {
+        Receipt {
+            transaction_hash: H256::from_low_u64_be(hash),
+            transaction_index,
+            block_hash: H256::from_low_u64_be(block_number),
+            block_number,
+            from: Address([1u8; 20]),
+            to: None,
+            contract_address: None,
+            status: true,
+            gas_used: 21_000,
+            cumulative_gas_used: 21_000 * (transaction_index + 1),
+            effective_gas_price: U256::one(),
+            logs: Vec::new(),
+            fairness_score: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_receipts_by_hash_and_block() {
+        let mut storage = MemoryStorage::new();
+        for (hash, block, index) in [(3, 2, 1), (1, 1, 0), (2, 2, 0)] {
+            storage.set_receipt(&receipt(hash, block, index)).await;
+        }
+        assert_eq!(
+            storage.get_receipt(&H256::from_low_u64_be(3)).await,
+            Some(receipt(3, 2, 1))
+        );
+        assert!(storage
+            .get_receipt(&H256::from_low_u64_be(9))
+            .await
+            .is_none());
+        let hashes = |receipts: Vec<Receipt>| {
+            receipts
+                .iter()
+                .map(|receipt| receipt.transaction_hash.to_low_u64_be())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hashes(storage.block_receipts(2).await), vec![2, 3]);
+
+        // 重组后交易在其他区块重新执行
+        storage.set_receipt(&receipt(3, 4, 0)).await;
+        assert_eq!(hashes(storage.block_receipts(2).await), vec![2]);
+        assert_eq!(hashes(storage.block_receipts(4).await), vec![3]);
+        assert!(storage.block_receipts(3).await.is_empty());
+    }
+}
@@ -1,10 +1,32 @@
 use crate::account::{Account, Address};
+use crate::receipt::Receipt;
 use async_trait::async_trait;
 use ethers::types::{H256, U256};
+use futures::stream::{self, BoxStream, StreamExt};
 use std::option::Option;
 
+pub mod batch;
+pub mod marker;
 pub mod memory;
+pub mod overlay;
+pub mod persistence;
+pub mod schema;
+pub mod service;
+pub mod snapshot;
+pub mod wal;
+pub use batch::StorageBatch;
 pub use memory::MemoryStorage;
+pub use overlay::OverlayStorage;
+pub use persistence::{Persistence, PersistenceError};
+pub use service::{StateHandle, StateService};
+pub use snapshot::StorageSnapshot;
+pub use wal::{WalError, WalOp, WalRecord, WriteAheadLog};
+
+/// 迭代时每次从存储读取的条目数
+pub const ITER_PAGE_SIZE: usize = 256;
+
+/// 存储槽键值对
+pub type StorageEntry = ([u8; 32], [u8; 32]);
 
 #[async_trait]
 pub trait Storage: Send + Sync + std::fmt::Debug {
@@ -20,4 +42,104 @@ pub trait Storage: Send + Sync + std::fmt::Debug {
     async fn set_storage_root(&mut self, address: &Address, storage_root: H256);
     async fn get_storage_value(&self, address: &Address, key: [u8; 32]) -> [u8; 32];
     async fn set_storage_value(&mut self, address: &Address, key: [u8; 32], value: [u8; 32]);
+    /// 获取账户代码，无代码时返回空
+    async fn get_code(&self, address: &Address) -> Vec<u8>;
+    /// 设置账户代码并返回代码哈希，相同代码只保存一份
+    async fn set_code(&mut self, address: &Address, code: Vec<u8>) -> H256;
+    /// 按代码哈希获取代码
+    async fn get_code_by_hash(&self, code_hash: &H256) -> Option<Vec<u8>>;
+    /// 按地址升序返回 `after` 之后（不含）的至多 `limit` 个账户
+    async fn accounts_page(&self, after: Option<Address>, limit: usize) -> Vec<Account>;
+    /// 按键升序返回账户中 `after` 之后（不含）的至多 `limit` 个存储槽
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Vec<StorageEntry>;
+    /// 按交易哈希获取收据
+    async fn get_receipt(&self, tx_hash: &H256) -> Option<Receipt>;
+    /// 按交易序号返回区块内的全部收据
+    async fn block_receipts(&self, number: u64) -> Vec<Receipt>;
+    /// 写入收据，按交易哈希和区块高度索引
+    async fn set_receipt(&mut self, receipt: &Receipt);
+
+    /// 取出上次调用以来读取失败的原因
+    ///
+    /// 按需下载状态的存储（[`crate::checkpoint::LazyStorage`]）下载失败时读取返回本地状态，
+    /// 执行区块前后检查此处，失败时中止执行。本地存储的读取不会失败，默认返回 None。
+    async fn take_read_error(&self) -> Option<String> {
+        None
+    }
+
+    /// 按顺序应用一批写操作
+    ///
+    /// 默认逐条写入；[`StateHandle`] 在状态服务的一次请求内完成整批写入，其他调用方不会看到
+    /// 写了一半的状态。需要缓冲写入并在失败时丢弃时使用 [`StorageBatch`]。
+    async fn write_batch(&mut self, ops: &[WalOp]) {
+        for op in ops {
+            match op {
+                WalOp::SetAccount(account) => self.set_account(account).await,
+                WalOp::SetCode { address, code } => {
+                    self.set_code(address, code.clone()).await;
+                }
+                WalOp::SetStorage {
+                    address,
+                    key,
+                    value,
+                } => self.set_storage_value(address, *key, *value).await,
+                WalOp::SetReceipt(receipt) => self.set_receipt(receipt).await,
+            }
+        }
+    }
+
+    /// 按地址升序遍历全部账户
+    ///
+    /// 流按需分页读取，消费方不拉取时不会继续读取存储。
+    fn iter_accounts(&self) -> BoxStream<'_, Account> {
+        paged(
+            move |after| self.accounts_page(after, ITER_PAGE_SIZE),
+            |account| account.address,
+        )
+    }
+
+    /// 按键升序遍历账户的全部存储槽
+    fn iter_storage<'a>(&'a self, address: &'a Address) -> BoxStream<'a, StorageEntry> {
+        paged(
+            move |after| self.storage_page(address, after, ITER_PAGE_SIZE),
+            |entry| entry.0,
+        )
+    }
+}
+
+/// 将分页查询转换为流，`cursor` 返回条目的排序键
+fn paged<'a, T, K, F, Fut>(fetch: F, cursor: fn(&T) -> K) -> BoxStream<'a, T>
+where
+    T: Send + 'a,
+    K: Copy + Send + 'a,
+    F: Fn(Option<K>) -> Fut + Send + 'a,
+    Fut: std::future::Future<Output = Vec<T>> + Send + 'a,
+{
+    stream::unfold(Some(None), move |after| {
+        let page = after.map(&fetch);
+        async move {
+            let page = page?.await;
+            let next = match page.last() {
+                Some(last) if page.len() >= ITER_PAGE_SIZE => Some(Some(cursor(last))),
+                _ => None,
+            };
+            Some((stream::iter(page), next))
+        }
+    })
+    .flatten()
+    .boxed()
+}
+
+/// 计算代码哈希，空代码的哈希为零
+pub fn code_hash(code: &[u8]) -> H256 {
+    if code.is_empty() {
+        H256::zero()
+    } else {
+        H256(ethers::utils::keccak256(code))
+    }
 }
@@ -0,0 +1,324 @@
+use crate::account::{Account, Address};
+use crate::receipt::Receipt;
+use crate::storage::wal::WalOp;
+use crate::storage::{code_hash, StateHandle, Storage, StorageEntry};
+use async_trait::async_trait;
+use ethers::types::{H256, U256};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 缓冲中的状态写入
+#[derive(Debug, Default)]
+pub struct ChangeSet {
+    accounts: BTreeMap<Address, Account>,
+    codes: BTreeMap<Address, Vec<u8>>,
+    slots: BTreeMap<(Address, [u8; 32]), [u8; 32]>,
+    receipts: BTreeMap<H256, Receipt>,
+}
+
+impl ChangeSet {
+    /// 缓冲中写入的账户
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.values()
+    }
+
+    /// 按确定顺序转换为预写日志操作
+    pub fn into_ops(self) -> Vec<WalOp> {
+        let accounts = self.accounts.into_values().map(WalOp::SetAccount);
+        let codes = self
+            .codes
+            .into_iter()
+            .map(|(address, code)| WalOp::SetCode { address, code });
+        let slots = self
+            .slots
+            .into_iter()
+            .map(|((address, key), value)| WalOp::SetStorage {
+                address,
+                key,
+                value,
+            });
+        let receipts = self.receipts.into_values().map(WalOp::SetReceipt);
+        accounts.chain(codes).chain(slots).chain(receipts).collect()
+    }
+}
+
+/// 写缓冲存储
+///
+/// 读取优先返回缓冲中的写入，否则穿透到底层存储；写入只记录在缓冲中，
+/// 由调用方决定何时提交到底层存储。
+#[derive(Debug)]
+pub struct OverlayStorage {
+    base: StateHandle,
+    changes: Arc<RwLock<ChangeSet>>,
+}
+
+impl OverlayStorage {
+    /// 在底层存储之上创建写缓冲
+    pub fn new(base: StateHandle) -> Self {
+        Self {
+            base,
+            changes: Arc::new(RwLock::new(ChangeSet::default())),
+        }
+    }
+
+    /// 缓冲写入的共享句柄，存储本身被装箱后仍可取出写入
+    pub fn changes(&self) -> Arc<RwLock<ChangeSet>> {
+        self.changes.clone()
+    }
+
+    /// 读取账户、修改后写入缓冲，账户不存在时不做修改
+    async fn update_account(&self, address: &Address, update: impl FnOnce(&mut Account) + Send) {
+        if let Some(mut account) = self.get_account(address).await {
+            update(&mut account);
+            self.changes
+                .write()
+                .await
+                .accounts
+                .insert(*address, account);
+        }
+    }
+}
+
+/// 分页起点，不包含游标本身
+fn after_bound<K>(after: Option<K>) -> Bound<K> {
+    after.map_or(Bound::Unbounded, Bound::Excluded)
+}
+
+#[async_trait]
+impl Storage for OverlayStorage {
+    async fn get_account(&self, address: &Address) -> Option<Account> {
+        if let Some(account) = self.changes.read().await.accounts.get(address) {
+            return Some(account.clone());
+        }
+        self.base.get_account(address).await
+    }
+
+    async fn set_account(&mut self, account: &Account) {
+        self.changes
+            .write()
+            .await
+            .accounts
+            .insert(account.address, account.clone());
+    }
+
+    async fn get_balance(&self, address: &Address) -> U256 {
+        self.get_account(address)
+            .await
+            .map(|account| account.balance)
+            .unwrap_or_else(U256::zero)
+    }
+
+    async fn set_balance(&mut self, address: &Address, balance: U256) {
+        self.update_account(address, |account| account.balance = balance)
+            .await;
+    }
+
+    async fn get_nonce(&self, address: &Address) -> u64 {
+        self.get_account(address)
+            .await
+            .map(|account| account.nonce)
+            .unwrap_or(0)
+    }
+
+    async fn set_nonce(&mut self, address: &Address, nonce: u64) {
+        self.update_account(address, |account| account.nonce = nonce)
+            .await;
+    }
+
+    async fn get_code_hash(&self, address: &Address) -> H256 {
+        self.get_account(address)
+            .await
+            .map(|account| account.code_hash)
+            .unwrap_or_else(H256::zero)
+    }
+
+    async fn set_code_hash(&mut self, address: &Address, code_hash: H256) {
+        self.update_account(address, |account| account.code_hash = code_hash)
+            .await;
+    }
+
+    async fn get_storage_root(&self, address: &Address) -> H256 {
+        self.get_account(address)
+            .await
+            .map(|account| account.storage_root)
+            .unwrap_or_else(H256::zero)
+    }
+
+    async fn set_storage_root(&mut self, address: &Address, storage_root: H256) {
+        self.update_account(address, |account| account.storage_root = storage_root)
+            .await;
+    }
+
+    async fn get_storage_value(&self, address: &Address, key: [u8; 32]) -> [u8; 32] {
+        if let Some(value) = self.changes.read().await.slots.get(&(*address, key)) {
+            return *value;
+        }
+        self.base.get_storage_value(address, key).await
+    }
+
+    async fn set_storage_value(&mut self, address: &Address, key: [u8; 32], value: [u8; 32]) {
+        self.changes
+            .write()
+            .await
+            .slots
+            .insert((*address, key), value);
+    }
+
+    async fn get_code(&self, address: &Address) -> Vec<u8> {
+        let hash = self.get_code_hash(address).await;
+        self.get_code_by_hash(&hash).await.unwrap_or_default()
+    }
+
+    async fn set_code(&mut self, address: &Address, code: Vec<u8>) -> H256 {
+        let hash = code_hash(&code);
+        let account = self
+            .get_account(address)
+            .await
+            .unwrap_or_else(|| Account::new(*address));
+        let mut changes = self.changes.write().await;
+        changes.accounts.insert(
+            *address,
+            Account {
+                code_hash: hash,
+                ..account
+            },
+        );
+        changes.codes.insert(*address, code);
+        hash
+    }
+
+    async fn get_code_by_hash(&self, hash: &H256) -> Option<Vec<u8>> {
+        let buffered = self
+            .changes
+            .read()
+            .await
+            .codes
+            .values()
+            .find(|code| !code.is_empty() && code_hash(code) == *hash)
+            .cloned();
+        match buffered {
+            Some(code) => Some(code),
+            None => self.base.get_code_by_hash(hash).await,
+        }
+    }
+
+    async fn accounts_page(&self, after: Option<Address>, limit: usize) -> Vec<Account> {
+        let mut merged: BTreeMap<Address, Account> = self
+            .base
+            .accounts_page(after, limit)
+            .await
+            .into_iter()
+            .map(|account| (account.address, account))
+            .collect();
+        let changes = self.changes.read().await;
+        for (address, account) in changes
+            .accounts
+            .range((after_bound(after), Bound::Unbounded))
+            .take(limit)
+        {
+            merged.insert(*address, account.clone());
+        }
+        merged.into_values().take(limit).collect()
+    }
+
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Vec<StorageEntry> {
+        let mut merged: BTreeMap<[u8; 32], [u8; 32]> = self
+            .base
+            .storage_page(address, after, limit)
+            .await
+            .into_iter()
+            .collect();
+        let changes = self.changes.read().await;
+        let lower = match after {
+            Some(key) => Bound::Excluded((*address, key)),
+            None => Bound::Included((*address, [0u8; 32])),
+        };
+        for ((_, key), value) in changes
+            .slots
+            .range((lower, Bound::Included((*address, [0xffu8; 32]))))
+            .take(limit)
+        {
+            merged.insert(*key, *value);
+        }
+        merged.into_iter().take(limit).collect()
+    }
+
+    async fn get_receipt(&self, tx_hash: &H256) -> Option<Receipt> {
+        if let Some(receipt) = self.changes.read().await.receipts.get(tx_hash) {
+            return Some(receipt.clone());
+        }
+        self.base.get_receipt(tx_hash).await
+    }
+
+    async fn block_receipts(&self, number: u64) -> Vec<Receipt> {
+        let mut merged: BTreeMap<u64, Receipt> = self
+            .base
+            .block_receipts(number)
+            .await
+            .into_iter()
+            .map(|receipt| (receipt.transaction_index, receipt))
+            .collect();
+        let changes = self.changes.read().await;
+        for receipt in changes
+            .receipts
+            .values()
+            .filter(|receipt| receipt.block_number == number)
+        {
+            merged.insert(receipt.transaction_index, receipt.clone());
+        }
+        merged.into_values().collect()
+    }
+
+    async fn set_receipt(&mut self, receipt: &Receipt) {
+        self.changes
+            .write()
+            .await
+            .receipts
+            .insert(receipt.transaction_hash, receipt.clone());
+    }
+
+    async fn take_read_error(&self) -> Option<String> {
+        self.base.take_read_error().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStorage, StateService};
+
+    #[tokio::test]
+    async fn test_writes_are_buffered() {
+        let address = Address([1u8; 20]);
+        let mut base = MemoryStorage::new();
+        base.set_account(&Account::new(address)).await;
+        let base = StateService::spawn(Box::new(base));
+
+        let mut overlay = OverlayStorage::new(base.clone());
+        overlay.set_balance(&address, U256::from(10)).await;
+        overlay
+            .set_storage_value(&address, [1u8; 32], [2u8; 32])
+            .await;
+        overlay.set_code(&address, vec![0x60, 0x00]).await;
+
+        assert_eq!(overlay.get_balance(&address).await, U256::from(10));
+        assert_eq!(overlay.get_code(&address).await, vec![0x60, 0x00]);
+        assert_eq!(base.get_balance(&address).await, U256::zero());
+        assert_eq!(base.get_storage_value(&address, [1u8; 32]).await, [0u8; 32]);
+
+        let changes = overlay.changes();
+        let ops = std::mem::take(&mut *changes.write().await).into_ops();
+        base.apply(ops).await;
+
+        assert_eq!(base.get_balance(&address).await, U256::from(10));
+        assert_eq!(base.get_storage_value(&address, [1u8; 32]).await, [2u8; 32]);
+        assert_eq!(base.get_code(&address).await, vec![0x60, 0x00]);
+    }
+}
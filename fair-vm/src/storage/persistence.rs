@@ -0,0 +1,75 @@
+//! 数据目录访问
+//!
+//! 节点写入数据目录的内容（预写日志、状态标记、格式版本、崩溃报告、遥测节点标识）都经由
+//! [`Persistence`] 取得路径。临时模式下取不到数据目录，从存储层保证节点不向磁盘写入任何内容。
+
+use crate::storage::wal::{WriteAheadLog, WAL_FILE_NAME};
+use fair_vm_core::config::Config;
+use std::path::{Path, PathBuf};
+
+/// 数据目录访问错误
+#[derive(Debug, thiserror::Error)]
+pub enum PersistenceError {
+    #[error("临时模式不写入磁盘，拒绝写入{0}")]
+    Ephemeral(&'static str),
+}
+
+/// 节点状态的保存方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Persistence {
+    /// 保存在数据目录中
+    Disk(PathBuf),
+    /// 只保存在内存中
+    Ephemeral,
+}
+
+impl Persistence {
+    pub fn from_config(config: &Config) -> Self {
+        if config.ephemeral.enabled {
+            Self::Ephemeral
+        } else {
+            Self::Disk(config.data_dir.clone())
+        }
+    }
+
+    pub fn is_ephemeral(&self) -> bool {
+        matches!(self, Self::Ephemeral)
+    }
+
+    /// 写入 `purpose` 所用的数据目录，临时模式下返回错误
+    pub fn data_dir(&self, purpose: &'static str) -> Result<&Path, PersistenceError> {
+        match self {
+            Self::Disk(data_dir) => Ok(data_dir),
+            Self::Ephemeral => Err(PersistenceError::Ephemeral(purpose)),
+        }
+    }
+
+    /// 数据目录中的预写日志，临时模式下为空
+    pub fn wal(&self) -> Option<WriteAheadLog> {
+        self.data_dir("预写日志")
+            .ok()
+            .map(|data_dir| WriteAheadLog::new(data_dir.join(WAL_FILE_NAME)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persistence() {
+        let mut config = Config::default();
+        let disk = Persistence::from_config(&config);
+        assert_eq!(disk.data_dir("状态标记").unwrap(), Path::new("data"));
+        assert!(disk.wal().is_some());
+
+        config.ephemeral.enabled = true;
+        let ephemeral = Persistence::from_config(&config);
+        assert!(ephemeral.is_ephemeral());
+        assert!(ephemeral.wal().is_none());
+        assert!(matches!(
+            ephemeral.data_dir("状态标记"),
+            Err(PersistenceError::Ephemeral("状态标记"))
+        ));
+    }
+}
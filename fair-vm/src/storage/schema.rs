@@ -0,0 +1,582 @@
+//! 持久化存储的键布局与版本
+//!
+//! 每个键以一字节表前缀开头，后接定长字段：
+//!
+//! | 前缀 | 表 | 键 | 值 |
+//! |------|----|----|----|
+//! | `0x00` | 元数据 | `"schema_version"` | 大端 u32 |
+//! | `0x01` | 账户 | 地址（20 字节） | 账户 |
+//! | `0x02` | 代码 | 代码哈希（32 字节） | 字节码 |
+//! | `0x03` | 存储槽 | 地址 + 槽位（52 字节） | 32 字节值 |
+//! | `0x10` | 区块头 | 大端区块高度（8 字节） | 区块头 |
+//! | `0x11` | 区块体 | 大端区块高度（8 字节） | 交易列表 |
+//! | `0x12` | 区块哈希索引 | 区块哈希（32 字节） | 大端区块高度 |
+//! | `0x13` | 收据 | 交易哈希（32 字节） | 收据 |
+//! | `0x14` | 交易索引 | 交易哈希（32 字节） | 区块高度 + 交易序号 |
+//! | `0x15` | 区块收据索引 | 大端区块高度 + 大端交易序号（16 字节） | 交易哈希 |
+//!
+//! 区块高度使用大端编码，按字节序扫描即按高度递增，[`block_range`] 给出高度区间对应的键区间。
+//! 数据目录中的 `SCHEMA` 文件记录磁盘格式版本，启动时由 [`migrate`] 逐级执行迁移到
+//! [`SCHEMA_VERSION`]，格式变化不必重新同步；没有该文件的旧数据目录视为版本 0。迁移登记在
+//! [`Migrations`] 中，每个版本提供升级和撤销两个方向，`fair-vm-cli db migrate` 可预览或手动执行。
+
+use crate::account::Address;
+use async_trait::async_trait;
+use ethers::types::H256;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs;
+
+/// 当前磁盘格式版本
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 记录磁盘格式版本的文件名
+pub const SCHEMA_FILE_NAME: &str = "SCHEMA";
+
+/// 元数据表中记录版本的键名
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+
+/// 键布局错误
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("键为空")]
+    EmptyKey,
+
+    #[error("未知的表前缀: {0:#04x}")]
+    UnknownTable(u8),
+
+    #[error("{table:?} 表的键长度无效: {len}")]
+    InvalidKey { table: Table, len: usize },
+
+    #[error("版本文件无效: {0}")]
+    InvalidVersion(String),
+
+    #[error("数据目录版本 {found} 高于当前支持的版本 {supported}，请升级节点")]
+    Unsupported { found: u32, supported: u32 },
+
+    #[error("缺少从版本 {0} 出发的迁移")]
+    MissingMigration(u32),
+
+    #[error("数据目录版本 {found} 需要迁移到 {target}，请运行 `fair-vm-cli db migrate`")]
+    MigrationRequired { found: u32, target: u32 },
+
+    #[error("迁移到版本 {version} 失败: {reason}")]
+    Migration { version: u32, reason: String },
+
+    #[error("IO 错误: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 表前缀
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Table {
+    Meta = 0x00,
+    Account = 0x01,
+    Code = 0x02,
+    Storage = 0x03,
+    BlockHeader = 0x10,
+    BlockBody = 0x11,
+    BlockHash = 0x12,
+    Receipt = 0x13,
+    TxLookup = 0x14,
+    BlockReceipt = 0x15,
+}
+
+impl TryFrom<u8> for Table {
+    type Error = SchemaError;
+
+    fn try_from(prefix: u8) -> Result<Self, Self::Error> {
+        Ok(match prefix {
+            0x00 => Table::Meta,
+            0x01 => Table::Account,
+            0x02 => Table::Code,
+            0x03 => Table::Storage,
+            0x10 => Table::BlockHeader,
+            0x11 => Table::BlockBody,
+            0x12 => Table::BlockHash,
+            0x13 => Table::Receipt,
+            0x14 => Table::TxLookup,
+            0x15 => Table::BlockReceipt,
+            other => return Err(SchemaError::UnknownTable(other)),
+        })
+    }
+}
+
+/// 类型化的存储键
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageKey {
+    SchemaVersion,
+    Account(Address),
+    Code(H256),
+    Storage(Address, [u8; 32]),
+    BlockHeader(u64),
+    BlockBody(u64),
+    BlockHash(H256),
+    Receipt(H256),
+    TxLookup(H256),
+    BlockReceipt(u64, u64),
+}
+
+impl StorageKey {
+    /// 键所在的表
+    pub fn table(&self) -> Table {
+        match self {
+            StorageKey::SchemaVersion => Table::Meta,
+            StorageKey::Account(_) => Table::Account,
+            StorageKey::Code(_) => Table::Code,
+            StorageKey::Storage(..) => Table::Storage,
+            StorageKey::BlockHeader(_) => Table::BlockHeader,
+            StorageKey::BlockBody(_) => Table::BlockBody,
+            StorageKey::BlockHash(_) => Table::BlockHash,
+            StorageKey::Receipt(_) => Table::Receipt,
+            StorageKey::TxLookup(_) => Table::TxLookup,
+            StorageKey::BlockReceipt(..) => Table::BlockReceipt,
+        }
+    }
+
+    /// 编码为字节键
+    pub fn encode(&self) -> Vec<u8> {
+        let mut key = vec![self.table() as u8];
+        match self {
+            StorageKey::SchemaVersion => key.extend_from_slice(SCHEMA_VERSION_KEY),
+            StorageKey::Account(address) => key.extend_from_slice(&address.0),
+            StorageKey::Storage(address, slot) => {
+                key.extend_from_slice(&address.0);
+                key.extend_from_slice(slot);
+            }
+            StorageKey::BlockHeader(number) | StorageKey::BlockBody(number) => {
+                key.extend_from_slice(&number.to_be_bytes())
+            }
+            StorageKey::BlockReceipt(number, index) => {
+                key.extend_from_slice(&number.to_be_bytes());
+                key.extend_from_slice(&index.to_be_bytes());
+            }
+            StorageKey::Code(hash)
+            | StorageKey::BlockHash(hash)
+            | StorageKey::Receipt(hash)
+            | StorageKey::TxLookup(hash) => key.extend_from_slice(hash.as_bytes()),
+        }
+        key
+    }
+
+    /// 从字节键解码
+    pub fn decode(key: &[u8]) -> Result<Self, SchemaError> {
+        let (&prefix, body) = key.split_first().ok_or(SchemaError::EmptyKey)?;
+        let table = Table::try_from(prefix)?;
+        let invalid = || SchemaError::InvalidKey {
+            table,
+            len: body.len(),
+        };
+        let hash = || (body.len() == 32).then(|| H256::from_slice(body));
+        let number = || {
+            <[u8; 8]>::try_from(body)
+                .map(u64::from_be_bytes)
+                .map_err(|_| invalid())
+        };
+        Ok(match table {
+            Table::Meta if body == SCHEMA_VERSION_KEY => StorageKey::SchemaVersion,
+            Table::Meta => return Err(invalid()),
+            Table::Account => StorageKey::Account(Address(body.try_into().map_err(|_| invalid())?)),
+            Table::Storage if body.len() == 52 => {
+                let mut address = [0u8; 20];
+                let mut slot = [0u8; 32];
+                address.copy_from_slice(&body[..20]);
+                slot.copy_from_slice(&body[20..]);
+                StorageKey::Storage(Address(address), slot)
+            }
+            Table::Storage => return Err(invalid()),
+            Table::BlockHeader => StorageKey::BlockHeader(number()?),
+            Table::BlockBody => StorageKey::BlockBody(number()?),
+            Table::Code => StorageKey::Code(hash().ok_or_else(invalid)?),
+            Table::BlockHash => StorageKey::BlockHash(hash().ok_or_else(invalid)?),
+            Table::Receipt => StorageKey::Receipt(hash().ok_or_else(invalid)?),
+            Table::TxLookup => StorageKey::TxLookup(hash().ok_or_else(invalid)?),
+            Table::BlockReceipt if body.len() == 16 => {
+                let (number, index) = body.split_at(8);
+                StorageKey::BlockReceipt(
+                    u64::from_be_bytes(number.try_into().map_err(|_| invalid())?),
+                    u64::from_be_bytes(index.try_into().map_err(|_| invalid())?),
+                )
+            }
+            Table::BlockReceipt => return Err(invalid()),
+        })
+    }
+}
+
+/// 区块高度 `[from, to)` 对应的区块头键区间，按字节序扫描即按高度递增
+pub fn block_range(from: u64, to: u64) -> (Vec<u8>, Vec<u8>) {
+    (
+        StorageKey::BlockHeader(from).encode(),
+        StorageKey::BlockHeader(to).encode(),
+    )
+}
+
+/// 版本文件路径
+pub fn schema_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SCHEMA_FILE_NAME)
+}
+
+/// 读取数据目录的磁盘格式版本，没有版本文件时为 None
+pub async fn read_version(data_dir: &Path) -> Result<Option<u32>, SchemaError> {
+    match fs::read_to_string(schema_path(data_dir)).await {
+        Ok(content) => content
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| SchemaError::InvalidVersion(content.trim().to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 写入磁盘格式版本，先写临时文件再重命名，崩溃时不会留下半个版本号
+pub async fn write_version(data_dir: &Path, version: u32) -> Result<(), SchemaError> {
+    fs::create_dir_all(data_dir).await?;
+    let path = schema_path(data_dir);
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, format!("{}\n", version)).await?;
+    fs::rename(&tmp, &path).await?;
+    Ok(())
+}
+
+/// 磁盘格式迁移，`up` 把数据目录从 `version() - 1` 升级到 `version()`，`down` 反向还原
+#[async_trait]
+pub trait Migration: Send + Sync {
+    /// 迁移后的版本
+    fn version(&self) -> u32;
+
+    /// 迁移说明
+    fn description(&self) -> &str;
+
+    /// 执行升级，重复执行应当是安全的
+    async fn up(&self, data_dir: &Path) -> Result<(), SchemaError>;
+
+    /// 撤销升级，重复执行应当是安全的
+    async fn down(&self, data_dir: &Path) -> Result<(), SchemaError>;
+}
+
+/// 版本 1：引入版本文件，数据布局与未记录版本的数据目录相同
+struct RecordSchemaVersion;
+
+#[async_trait]
+impl Migration for RecordSchemaVersion {
+    fn version(&self) -> u32 {
+        1
+    }
+
+    fn description(&self) -> &str {
+        "记录磁盘格式版本"
+    }
+
+    async fn up(&self, _data_dir: &Path) -> Result<(), SchemaError> {
+        Ok(())
+    }
+
+    async fn down(&self, _data_dir: &Path) -> Result<(), SchemaError> {
+        Ok(())
+    }
+}
+
+/// 迁移方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+/// 迁移计划中的一步
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationStep {
+    /// 迁移的版本，升级后到达该版本，降级后回到上一版本
+    pub version: u32,
+    pub direction: Direction,
+    pub description: String,
+}
+
+/// 迁移计划
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationPlan {
+    pub from: u32,
+    pub to: u32,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl MigrationPlan {
+    /// 数据目录已是目标版本
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// 迁移注册表
+pub struct Migrations {
+    migrations: Vec<Box<dyn Migration>>,
+}
+
+impl Migrations {
+    /// 由迁移列表创建注册表
+    pub fn new(mut migrations: Vec<Box<dyn Migration>>) -> Self {
+        migrations.sort_by_key(|migration| migration.version());
+        Self { migrations }
+    }
+
+    /// 内置迁移
+    pub fn builtin() -> Self {
+        Self::new(vec![Box::new(RecordSchemaVersion)])
+    }
+
+    /// 注册表能到达的最高版本
+    pub fn latest(&self) -> u32 {
+        self.migrations
+            .last()
+            .map_or(0, |migration| migration.version())
+    }
+
+    fn get(&self, version: u32) -> Option<&dyn Migration> {
+        self.migrations
+            .iter()
+            .find(|migration| migration.version() == version)
+            .map(|migration| migration.as_ref())
+    }
+
+    /// 从版本 `from` 到 `to` 需要执行的步骤
+    pub fn plan(&self, from: u32, to: u32) -> Result<MigrationPlan, SchemaError> {
+        let (versions, direction): (Vec<u32>, _) = if to >= from {
+            ((from + 1..=to).collect(), Direction::Up)
+        } else {
+            ((to + 1..=from).rev().collect(), Direction::Down)
+        };
+        let steps = versions
+            .into_iter()
+            .map(|version| {
+                let start = match direction {
+                    Direction::Up => version - 1,
+                    Direction::Down => version,
+                };
+                let migration = self
+                    .get(version)
+                    .ok_or(SchemaError::MissingMigration(start))?;
+                Ok(MigrationStep {
+                    version,
+                    direction,
+                    description: migration.description().to_string(),
+                })
+            })
+            .collect::<Result<_, SchemaError>>()?;
+        Ok(MigrationPlan { from, to, steps })
+    }
+
+    /// 将数据目录迁移到 `target` 并返回执行的计划，`dry_run` 时只生成计划
+    ///
+    /// 每完成一步立即写入版本文件，中途失败后重新执行会从失败的那一步继续；
+    /// 降级到版本 0 时删除版本文件，恢复未记录版本时的布局。
+    pub async fn run(
+        &self,
+        data_dir: &Path,
+        target: u32,
+        dry_run: bool,
+    ) -> Result<MigrationPlan, SchemaError> {
+        let found = read_version(data_dir).await?.unwrap_or(0);
+        if found > self.latest() {
+            return Err(SchemaError::Unsupported {
+                found,
+                supported: self.latest(),
+            });
+        }
+        let plan = self.plan(found, target)?;
+        if dry_run {
+            return Ok(plan);
+        }
+        for step in &plan.steps {
+            let migration = self
+                .get(step.version)
+                .ok_or(SchemaError::MissingMigration(step.version))?;
+            log::info!(
+                "{}数据目录版本 {}: {}",
+                match step.direction {
+                    Direction::Up => "升级到",
+                    Direction::Down => "撤销",
+                },
+                step.version,
+                step.description
+            );
+            match step.direction {
+                Direction::Up => {
+                    migration.up(data_dir).await?;
+                    write_version(data_dir, step.version).await?;
+                }
+                Direction::Down => {
+                    migration.down(data_dir).await?;
+                    match step.version - 1 {
+                        0 => remove_version(data_dir).await?,
+                        version => write_version(data_dir, version).await?,
+                    }
+                }
+            }
+        }
+        Ok(plan)
+    }
+}
+
+/// 删除版本文件
+async fn remove_version(data_dir: &Path) -> Result<(), SchemaError> {
+    match fs::remove_file(schema_path(data_dir)).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// 启动时执行的迁移：只升级不降级，数据目录比节点新时报错
+///
+/// `dry_run` 时不修改数据目录，仍需迁移则返回 [`SchemaError::MigrationRequired`]。
+pub async fn migrate(data_dir: &Path, dry_run: bool) -> Result<MigrationPlan, SchemaError> {
+    let plan = Migrations::builtin()
+        .run(data_dir, SCHEMA_VERSION, dry_run)
+        .await?;
+    if dry_run && !plan.is_empty() {
+        return Err(SchemaError::MigrationRequired {
+            found: plan.from,
+            target: plan.to,
+        });
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_round_trip_and_order() {
+        let keys = [
+            StorageKey::SchemaVersion,
+            StorageKey::Account(Address([1u8; 20])),
+            StorageKey::Code(H256::repeat_byte(2)),
+            StorageKey::Storage(Address([1u8; 20]), [3u8; 32]),
+            StorageKey::BlockHeader(7),
+            StorageKey::BlockBody(7),
+            StorageKey::BlockHash(H256::repeat_byte(4)),
+            StorageKey::Receipt(H256::repeat_byte(5)),
+            StorageKey::TxLookup(H256::repeat_byte(6)),
+            StorageKey::BlockReceipt(7, 2),
+        ];
+        for key in keys {
+            assert_eq!(StorageKey::decode(&key.encode()).unwrap(), key);
+        }
+
+        // 大端高度按字节序排序与按数值排序一致
+        let (from, to) = block_range(255, 65_536);
+        let middle = StorageKey::BlockHeader(256).encode();
+        assert!(from < middle && middle < to);
+
+        assert!(matches!(
+            StorageKey::decode(&[0x01, 1, 2]),
+            Err(SchemaError::InvalidKey {
+                table: Table::Account,
+                len: 2
+            })
+        ));
+        assert!(matches!(
+            StorageKey::decode(&[0x7f]),
+            Err(SchemaError::UnknownTable(0x7f))
+        ));
+    }
+
+    struct Touch(u32);
+
+    #[async_trait]
+    impl Migration for Touch {
+        fn version(&self) -> u32 {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "测试迁移"
+        }
+
+        async fn up(&self, data_dir: &Path) -> Result<(), SchemaError> {
+            fs::write(data_dir.join(format!("v{}", self.0)), b"").await?;
+            Ok(())
+        }
+
+        async fn down(&self, data_dir: &Path) -> Result<(), SchemaError> {
+            let _ = fs::remove_file(data_dir.join(format!("v{}", self.0))).await;
+            Ok(())
+        }
+    }
+
+    fn registry() -> Migrations {
+        Migrations::new(vec![
+            Box::new(RecordSchemaVersion),
+            Box::new(Touch(3)),
+            Box::new(Touch(2)),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_migrate() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_version(dir.path()).await.unwrap(), None);
+        // 预览不修改数据目录
+        assert!(matches!(
+            migrate(dir.path(), true).await,
+            Err(SchemaError::MigrationRequired {
+                found: 0,
+                target: 1
+            })
+        ));
+        assert_eq!(read_version(dir.path()).await.unwrap(), None);
+        assert_eq!(migrate(dir.path(), false).await.unwrap().from, 0);
+        assert_eq!(
+            read_version(dir.path()).await.unwrap(),
+            Some(SCHEMA_VERSION)
+        );
+        assert!(migrate(dir.path(), true).await.unwrap().is_empty());
+
+        let steps = registry();
+        assert_eq!(steps.latest(), 3);
+        assert_eq!(steps.run(dir.path(), 3, false).await.unwrap().from, 1);
+        assert!(dir.path().join("v2").exists() && dir.path().join("v3").exists());
+        assert_eq!(read_version(dir.path()).await.unwrap(), Some(3));
+
+        // 数据目录比节点新时拒绝启动
+        assert!(matches!(
+            migrate(dir.path(), false).await,
+            Err(SchemaError::Unsupported {
+                found: 3,
+                supported: SCHEMA_VERSION
+            })
+        ));
+        assert!(matches!(
+            steps.run(dir.path(), 5, false).await,
+            Err(SchemaError::MissingMigration(3))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let steps = registry();
+        steps.run(dir.path(), 3, false).await.unwrap();
+
+        let plan = steps.plan(3, 1).unwrap();
+        assert_eq!(
+            plan.steps
+                .iter()
+                .map(|step| (step.version, step.direction))
+                .collect::<Vec<_>>(),
+            vec![(3, Direction::Down), (2, Direction::Down)]
+        );
+        assert_eq!(steps.run(dir.path(), 1, true).await.unwrap(), plan);
+        assert!(dir.path().join("v3").exists());
+
+        steps.run(dir.path(), 1, false).await.unwrap();
+        assert!(!dir.path().join("v2").exists() && !dir.path().join("v3").exists());
+        assert_eq!(read_version(dir.path()).await.unwrap(), Some(1));
+        steps.run(dir.path(), 0, false).await.unwrap();
+        assert_eq!(read_version(dir.path()).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,556 @@
+//! 状态服务
+//!
+//! [`StateService`] 独占底层存储，调用方通过 [`StateHandle`] 发送请求并等待应答，不再共享
+//! `RwLock<Box<dyn Storage>>`。请求按到达顺序逐个执行，同一句柄先写后读总能读到写入的值；
+//! [`StateHandle::apply`] 在一次请求内应用一批写操作，其他调用方不会看到写了一半的状态。
+//! 服务在独立线程上运行自己的运行时，不依赖调用方的运行时，调用方阻塞等待时也不会饿死服务；
+//! 全部句柄释放后服务退出。启用快照时单点读取走 [`StorageSnapshot`]，不进入请求队列。
+
+use crate::account::{Account, Address};
+use crate::receipt::Receipt;
+use crate::storage::snapshot::SnapshotData;
+use crate::storage::wal::{self, WalOp};
+use crate::storage::{code_hash, Storage, StorageEntry, StorageSnapshot};
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use ethers::types::{H256, U256};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// 请求队列容量
+pub const STATE_SERVICE_QUEUE: usize = 1024;
+
+type Reply<T> = oneshot::Sender<T>;
+
+/// 状态服务请求
+enum Request {
+    GetAccount(Address, Reply<Option<Account>>),
+    SetAccount(Account, Reply<()>),
+    GetBalance(Address, Reply<U256>),
+    SetBalance(Address, U256, Reply<()>),
+    GetNonce(Address, Reply<u64>),
+    SetNonce(Address, u64, Reply<()>),
+    GetCodeHash(Address, Reply<H256>),
+    SetCodeHash(Address, H256, Reply<()>),
+    GetStorageRoot(Address, Reply<H256>),
+    SetStorageRoot(Address, H256, Reply<()>),
+    GetStorageValue(Address, [u8; 32], Reply<[u8; 32]>),
+    SetStorageValue(Address, [u8; 32], [u8; 32], Reply<()>),
+    GetCode(Address, Reply<Vec<u8>>),
+    SetCode(Address, Vec<u8>, Reply<H256>),
+    GetCodeByHash(H256, Reply<Option<Vec<u8>>>),
+    AccountsPage(Option<Address>, usize, Reply<Vec<Account>>),
+    StoragePage(Address, Option<[u8; 32]>, usize, Reply<Vec<StorageEntry>>),
+    GetReceipt(H256, Reply<Option<Receipt>>),
+    BlockReceipts(u64, Reply<Vec<Receipt>>),
+    SetReceipt(Receipt, Reply<()>),
+    TakeReadError(Reply<Option<String>>),
+    Apply(Vec<WalOp>, Reply<()>),
+}
+
+/// 一次写入涉及的状态，发布快照时从存储读回最终值
+#[derive(Default)]
+struct Changes {
+    accounts: Vec<Address>,
+    slots: Vec<(Address, [u8; 32])>,
+    codes: Vec<(H256, Vec<u8>)>,
+}
+
+impl Changes {
+    fn account(address: Address) -> Self {
+        Self {
+            accounts: vec![address],
+            ..Default::default()
+        }
+    }
+
+    fn from_ops(ops: &[WalOp]) -> Self {
+        let mut changes = Self::default();
+        for op in ops {
+            match op {
+                WalOp::SetAccount(account) => changes.accounts.push(account.address),
+                WalOp::SetCode { address, code } => {
+                    changes.accounts.push(*address);
+                    changes.codes.push((code_hash(code), code.clone()));
+                }
+                WalOp::SetStorage { address, key, .. } => changes.slots.push((*address, *key)),
+                // 收据不进入快照
+                WalOp::SetReceipt(_) => {}
+            }
+        }
+        changes
+    }
+}
+
+/// 独占存储的状态服务
+pub struct StateService {
+    storage: Box<dyn Storage + Send + Sync>,
+    requests: mpsc::Receiver<Request>,
+    /// 当前快照，未启用快照时为 None
+    snapshots: Option<Arc<ArcSwap<SnapshotData>>>,
+}
+
+impl StateService {
+    /// 启动状态服务并返回句柄
+    pub fn spawn(storage: Box<dyn Storage + Send + Sync>) -> StateHandle {
+        Self::start(storage, false)
+    }
+
+    /// 启动维护只读快照的状态服务
+    ///
+    /// 启动时复制存储的现有状态，之后每次写入都在应答前发布新快照，句柄的单点读取直接读快照。
+    /// 复制需要读取全部状态，不适合按需下载状态的存储和每个区块的写缓冲。
+    pub fn spawn_with_snapshots(storage: Box<dyn Storage + Send + Sync>) -> StateHandle {
+        Self::start(storage, true)
+    }
+
+    fn start(storage: Box<dyn Storage + Send + Sync>, snapshots: bool) -> StateHandle {
+        let (sender, requests) = mpsc::channel(STATE_SERVICE_QUEUE);
+        let snapshots = snapshots.then(|| Arc::new(ArcSwap::from_pointee(SnapshotData::default())));
+        let service = Self {
+            storage,
+            requests,
+            snapshots: snapshots.clone(),
+        };
+        let (ready, started) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .name("fairvm-state".into())
+            .spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("创建状态服务运行时失败")
+                    .block_on(async move {
+                        if let Some(current) = &service.snapshots {
+                            let snapshot = StorageSnapshot::capture(service.storage.as_ref()).await;
+                            current.store(snapshot.into_inner());
+                        }
+                        let _ = ready.send(());
+                        service.run().await
+                    })
+            })
+            .expect("启动状态服务线程失败");
+        // 等待初始快照就绪，句柄返回后读到的总是完整状态
+        started.recv().expect("状态服务启动失败");
+        StateHandle { sender, snapshots }
+    }
+
+    async fn run(mut self) {
+        while let Some(request) = self.requests.recv().await {
+            self.handle(request).await;
+        }
+    }
+
+    /// 执行请求，调用方已放弃等待时丢弃应答
+    ///
+    /// 写请求在应答前发布快照，调用方收到应答后从快照一定能读到自己的写入。
+    async fn handle(&mut self, request: Request) {
+        match request {
+            Request::GetAccount(address, reply) => {
+                let _ = reply.send(self.storage.get_account(&address).await);
+            }
+            Request::SetAccount(account, reply) => {
+                self.storage.set_account(&account).await;
+                self.publish(Changes::account(account.address)).await;
+                let _ = reply.send(());
+            }
+            Request::GetBalance(address, reply) => {
+                let _ = reply.send(self.storage.get_balance(&address).await);
+            }
+            Request::SetBalance(address, balance, reply) => {
+                self.storage.set_balance(&address, balance).await;
+                self.publish(Changes::account(address)).await;
+                let _ = reply.send(());
+            }
+            Request::GetNonce(address, reply) => {
+                let _ = reply.send(self.storage.get_nonce(&address).await);
+            }
+            Request::SetNonce(address, nonce, reply) => {
+                self.storage.set_nonce(&address, nonce).await;
+                self.publish(Changes::account(address)).await;
+                let _ = reply.send(());
+            }
+            Request::GetCodeHash(address, reply) => {
+                let _ = reply.send(self.storage.get_code_hash(&address).await);
+            }
+            Request::SetCodeHash(address, code_hash, reply) => {
+                self.storage.set_code_hash(&address, code_hash).await;
+                self.publish(Changes::account(address)).await;
+                let _ = reply.send(());
+            }
+            Request::GetStorageRoot(address, reply) => {
+                let _ = reply.send(self.storage.get_storage_root(&address).await);
+            }
+            Request::SetStorageRoot(address, storage_root, reply) => {
+                self.storage.set_storage_root(&address, storage_root).await;
+                self.publish(Changes::account(address)).await;
+                let _ = reply.send(());
+            }
+            Request::GetStorageValue(address, key, reply) => {
+                let _ = reply.send(self.storage.get_storage_value(&address, key).await);
+            }
+            Request::SetStorageValue(address, key, value, reply) => {
+                self.storage.set_storage_value(&address, key, value).await;
+                self.publish(Changes {
+                    slots: vec![(address, key)],
+                    ..Default::default()
+                })
+                .await;
+                let _ = reply.send(());
+            }
+            Request::GetCode(address, reply) => {
+                let _ = reply.send(self.storage.get_code(&address).await);
+            }
+            Request::SetCode(address, code, reply) => {
+                let snapshot_code = self.snapshots.is_some().then(|| code.clone());
+                let code_hash = self.storage.set_code(&address, code).await;
+                let mut changes = Changes::account(address);
+                changes
+                    .codes
+                    .extend(snapshot_code.map(|code| (code_hash, code)));
+                self.publish(changes).await;
+                let _ = reply.send(code_hash);
+            }
+            Request::GetCodeByHash(code_hash, reply) => {
+                let _ = reply.send(self.storage.get_code_by_hash(&code_hash).await);
+            }
+            Request::AccountsPage(after, limit, reply) => {
+                let _ = reply.send(self.storage.accounts_page(after, limit).await);
+            }
+            Request::StoragePage(address, after, limit, reply) => {
+                let _ = reply.send(self.storage.storage_page(&address, after, limit).await);
+            }
+            Request::GetReceipt(tx_hash, reply) => {
+                let _ = reply.send(self.storage.get_receipt(&tx_hash).await);
+            }
+            Request::BlockReceipts(number, reply) => {
+                let _ = reply.send(self.storage.block_receipts(number).await);
+            }
+            Request::SetReceipt(receipt, reply) => {
+                self.storage.set_receipt(&receipt).await;
+                let _ = reply.send(());
+            }
+            Request::TakeReadError(reply) => {
+                let _ = reply.send(self.storage.take_read_error().await);
+            }
+            Request::Apply(ops, reply) => {
+                wal::apply(self.storage.as_mut(), &ops).await;
+                if self.snapshots.is_some() {
+                    self.publish(Changes::from_ops(&ops)).await;
+                }
+                let _ = reply.send(());
+            }
+        }
+    }
+
+    /// 将改动合并进新快照并原子地替换当前快照
+    async fn publish(&self, changes: Changes) {
+        let Some(current) = &self.snapshots else {
+            return;
+        };
+        let mut next = StorageSnapshot::from_inner(current.load_full());
+        for (code_hash, code) in changes.codes {
+            next.put_code(code_hash, code);
+        }
+        for address in changes.accounts {
+            let account = self.storage.get_account(&address).await;
+            if let Some(account) = &account {
+                if !next.has_code(&account.code_hash) {
+                    if let Some(code) = self.storage.get_code_by_hash(&account.code_hash).await {
+                        next.put_code(account.code_hash, code);
+                    }
+                }
+            }
+            next.put_account(address, account);
+        }
+        for (address, key) in changes.slots {
+            let value = self.storage.get_storage_value(&address, key).await;
+            next.put_storage_value(address, key, value);
+        }
+        next.bump_version();
+        current.store(next.into_inner());
+    }
+}
+
+/// 状态服务句柄，克隆开销很小
+#[derive(Clone)]
+pub struct StateHandle {
+    sender: mpsc::Sender<Request>,
+    snapshots: Option<Arc<ArcSwap<SnapshotData>>>,
+}
+
+impl std::fmt::Debug for StateHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateHandle")
+            .field("pending", &(STATE_SERVICE_QUEUE - self.sender.capacity()))
+            .field("snapshot", &self.snapshot().map(|s| s.version()))
+            .finish()
+    }
+}
+
+impl StateHandle {
+    /// 发送请求并等待应答
+    ///
+    /// 服务只会在全部句柄释放或存储实现崩溃后退出，此时继续使用状态没有意义。
+    async fn request<T>(&self, request: impl FnOnce(Reply<T>) -> Request) -> T {
+        let (reply, response) = oneshot::channel();
+        self.sender
+            .send(request(reply))
+            .await
+            .expect("状态服务已停止");
+        response.await.expect("状态服务已停止")
+    }
+
+    /// 原子地应用一批写操作，启用快照时整批只发布一次
+    pub async fn apply(&self, ops: Vec<WalOp>) {
+        self.request(|reply| Request::Apply(ops, reply)).await
+    }
+
+    /// 当前快照，未启用快照时为 None
+    ///
+    /// 需要多次读取同一版本的状态时（例如分页遍历）应先取得快照再读取。
+    pub fn snapshot(&self) -> Option<StorageSnapshot> {
+        self.snapshots
+            .as_ref()
+            .map(|current| StorageSnapshot::from_inner(current.load_full()))
+    }
+}
+
+/// 启用快照时单点读取直接读快照，不进入请求队列；分页读取始终由服务执行
+#[async_trait]
+impl Storage for StateHandle {
+    async fn get_account(&self, address: &Address) -> Option<Account> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.account(address);
+        }
+        self.request(|reply| Request::GetAccount(*address, reply))
+            .await
+    }
+
+    async fn set_account(&mut self, account: &Account) {
+        self.request(|reply| Request::SetAccount(account.clone(), reply))
+            .await
+    }
+
+    async fn get_balance(&self, address: &Address) -> U256 {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.balance(address);
+        }
+        self.request(|reply| Request::GetBalance(*address, reply))
+            .await
+    }
+
+    async fn set_balance(&mut self, address: &Address, balance: U256) {
+        self.request(|reply| Request::SetBalance(*address, balance, reply))
+            .await
+    }
+
+    async fn get_nonce(&self, address: &Address) -> u64 {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.nonce(address);
+        }
+        self.request(|reply| Request::GetNonce(*address, reply))
+            .await
+    }
+
+    async fn set_nonce(&mut self, address: &Address, nonce: u64) {
+        self.request(|reply| Request::SetNonce(*address, nonce, reply))
+            .await
+    }
+
+    async fn get_code_hash(&self, address: &Address) -> H256 {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.code_hash(address);
+        }
+        self.request(|reply| Request::GetCodeHash(*address, reply))
+            .await
+    }
+
+    async fn set_code_hash(&mut self, address: &Address, code_hash: H256) {
+        self.request(|reply| Request::SetCodeHash(*address, code_hash, reply))
+            .await
+    }
+
+    async fn get_storage_root(&self, address: &Address) -> H256 {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.storage_root(address);
+        }
+        self.request(|reply| Request::GetStorageRoot(*address, reply))
+            .await
+    }
+
+    async fn set_storage_root(&mut self, address: &Address, storage_root: H256) {
+        self.request(|reply| Request::SetStorageRoot(*address, storage_root, reply))
+            .await
+    }
+
+    async fn get_storage_value(&self, address: &Address, key: [u8; 32]) -> [u8; 32] {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.storage_value(address, key);
+        }
+        self.request(|reply| Request::GetStorageValue(*address, key, reply))
+            .await
+    }
+
+    async fn set_storage_value(&mut self, address: &Address, key: [u8; 32], value: [u8; 32]) {
+        self.request(|reply| Request::SetStorageValue(*address, key, value, reply))
+            .await
+    }
+
+    async fn get_code(&self, address: &Address) -> Vec<u8> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.code(address);
+        }
+        self.request(|reply| Request::GetCode(*address, reply))
+            .await
+    }
+
+    async fn set_code(&mut self, address: &Address, code: Vec<u8>) -> H256 {
+        self.request(|reply| Request::SetCode(*address, code, reply))
+            .await
+    }
+
+    async fn get_code_by_hash(&self, code_hash: &H256) -> Option<Vec<u8>> {
+        // 快照只包含账户引用过的代码，未命中时仍向服务查询
+        if let Some(code) = self
+            .snapshot()
+            .and_then(|snapshot| snapshot.code_by_hash(code_hash))
+        {
+            return Some(code);
+        }
+        self.request(|reply| Request::GetCodeByHash(*code_hash, reply))
+            .await
+    }
+
+    async fn accounts_page(&self, after: Option<Address>, limit: usize) -> Vec<Account> {
+        self.request(|reply| Request::AccountsPage(after, limit, reply))
+            .await
+    }
+
+    async fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Vec<StorageEntry> {
+        self.request(|reply| Request::StoragePage(*address, after, limit, reply))
+            .await
+    }
+
+    async fn get_receipt(&self, tx_hash: &H256) -> Option<Receipt> {
+        self.request(|reply| Request::GetReceipt(*tx_hash, reply))
+            .await
+    }
+
+    async fn block_receipts(&self, number: u64) -> Vec<Receipt> {
+        self.request(|reply| Request::BlockReceipts(number, reply))
+            .await
+    }
+
+    async fn set_receipt(&mut self, receipt: &Receipt) {
+        self.request(|reply| Request::SetReceipt(receipt.clone(), reply))
+            .await
+    }
+
+    async fn take_read_error(&self) -> Option<String> {
+        self.request(Request::TakeReadError).await
+    }
+
+    async fn write_batch(&mut self, ops: &[WalOp]) {
+        self.apply(ops.to_vec()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[tokio::test]
+    async fn test_state_service() {
+        let mut handle = StateService::spawn(Box::new(MemoryStorage::default()));
+        let address = Address([1u8; 20]);
+        handle.set_balance(&address, U256::from(10)).await;
+        assert_eq!(handle.clone().get_balance(&address).await, U256::from(10));
+
+        let code_hash = handle.set_code(&address, vec![0x60, 0x00]).await;
+        assert_eq!(
+            handle.get_code_by_hash(&code_hash).await,
+            Some(vec![0x60, 0x00])
+        );
+
+        // 并发写入按到达顺序逐个执行，不会丢失
+        let writers: Vec<_> = (0..8u8)
+            .map(|i| {
+                let mut handle = handle.clone();
+                tokio::spawn(async move {
+                    handle
+                        .set_storage_value(&Address([2u8; 20]), [i; 32], [i; 32])
+                        .await
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        assert_eq!(
+            handle
+                .storage_page(&Address([2u8; 20]), None, 100)
+                .await
+                .len(),
+            8
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshots() {
+        let address = Address([4u8; 20]);
+        let mut base = MemoryStorage::new();
+        base.set_account(&Account::new(address)).await;
+        let mut handle = StateService::spawn_with_snapshots(Box::new(base));
+
+        let before = handle.snapshot().unwrap();
+        assert!(before.account(&address).is_some());
+        handle.set_balance(&address, U256::from(9)).await;
+        // 已取得的快照不变，写入应答后新快照立即可见
+        assert_eq!(before.balance(&address), U256::zero());
+        assert_eq!(handle.get_balance(&address).await, U256::from(9));
+
+        handle
+            .apply(vec![
+                WalOp::SetStorage {
+                    address,
+                    key: [1u8; 32],
+                    value: [2u8; 32],
+                },
+                WalOp::SetCode {
+                    address,
+                    code: vec![0x60, 0x00],
+                },
+            ])
+            .await;
+        let after = handle.snapshot().unwrap();
+        assert_eq!(after.version(), before.version() + 2);
+        assert_eq!(after.storage_value(&address, [1u8; 32]), [2u8; 32]);
+        assert_eq!(after.code(&address), vec![0x60, 0x00]);
+        assert_eq!(
+            after.storage_page(&address, None, 10),
+            handle.storage_page(&address, None, 10).await
+        );
+        assert!(StateService::spawn(Box::new(MemoryStorage::new()))
+            .snapshot()
+            .is_none());
+    }
+
+    #[test]
+    fn test_state_service_without_runtime() {
+        // 调用方阻塞等待时服务仍在自己的线程上运行
+        let handle = StateService::spawn(Box::new(MemoryStorage::default()));
+        let nonce = futures::executor::block_on(async {
+            let mut writer = handle.clone();
+            writer.set_nonce(&Address([3u8; 20]), 7).await;
+            handle.get_nonce(&Address([3u8; 20])).await
+        });
+        assert_eq!(nonce, 7);
+    }
+}
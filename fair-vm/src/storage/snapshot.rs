@@ -0,0 +1,224 @@
+//! 存储快照
+//!
+//! [`StorageSnapshot`] 是已提交状态的只读副本，克隆只增加引用计数，读取不经过状态服务的
+//! 请求队列，也不需要任何锁。状态服务每次写入后把改动合并进新快照并原子地替换当前快照
+//! （区块提交时整批合并一次），读取方拿到的快照在使用期间不会变化。存储槽按账户共享，
+//! 合并时只复制被改动账户的存储槽。
+
+use crate::account::{Account, Address};
+use crate::storage::{Storage, StorageEntry};
+use ethers::types::{H256, U256};
+use futures::StreamExt;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::sync::Arc;
+
+/// 快照数据，状态服务以原子指针持有当前版本
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SnapshotData {
+    /// 发布序号，每次发布加一
+    version: u64,
+    accounts: BTreeMap<Address, Account>,
+    slots: HashMap<Address, Arc<BTreeMap<[u8; 32], [u8; 32]>>>,
+    codes: HashMap<H256, Arc<Vec<u8>>>,
+}
+
+/// 已提交状态的只读快照
+#[derive(Debug, Clone, Default)]
+pub struct StorageSnapshot(Arc<SnapshotData>);
+
+impl StorageSnapshot {
+    /// 复制存储的全部账户、存储槽和账户引用的代码
+    pub async fn capture(storage: &(dyn Storage + Send + Sync)) -> Self {
+        let mut snapshot = Self::default();
+        let accounts: Vec<Account> = storage.iter_accounts().collect().await;
+        for account in accounts {
+            let slots: BTreeMap<_, _> = storage.iter_storage(&account.address).collect().await;
+            if !slots.is_empty() {
+                snapshot
+                    .data()
+                    .slots
+                    .insert(account.address, Arc::new(slots));
+            }
+            if !account.code_hash.is_zero() {
+                if let Some(code) = storage.get_code_by_hash(&account.code_hash).await {
+                    snapshot.put_code(account.code_hash, code);
+                }
+            }
+            snapshot.data().accounts.insert(account.address, account);
+        }
+        snapshot
+    }
+
+    /// 发布序号
+    pub fn version(&self) -> u64 {
+        self.0.version
+    }
+
+    pub fn account(&self, address: &Address) -> Option<Account> {
+        self.0.accounts.get(address).cloned()
+    }
+
+    pub fn balance(&self, address: &Address) -> U256 {
+        self.0
+            .accounts
+            .get(address)
+            .map_or_else(U256::zero, |account| account.balance)
+    }
+
+    pub fn nonce(&self, address: &Address) -> u64 {
+        self.0
+            .accounts
+            .get(address)
+            .map_or(0, |account| account.nonce)
+    }
+
+    pub fn code_hash(&self, address: &Address) -> H256 {
+        self.0
+            .accounts
+            .get(address)
+            .map_or_else(H256::zero, |account| account.code_hash)
+    }
+
+    pub fn storage_root(&self, address: &Address) -> H256 {
+        self.0
+            .accounts
+            .get(address)
+            .map_or_else(H256::zero, |account| account.storage_root)
+    }
+
+    pub fn storage_value(&self, address: &Address, key: [u8; 32]) -> [u8; 32] {
+        self.0
+            .slots
+            .get(address)
+            .and_then(|slots| slots.get(&key))
+            .copied()
+            .unwrap_or([0u8; 32])
+    }
+
+    /// 账户代码，无代码时返回空
+    pub fn code(&self, address: &Address) -> Vec<u8> {
+        self.code_by_hash(&self.code_hash(address))
+            .unwrap_or_default()
+    }
+
+    /// 按代码哈希获取代码，只包含快照中账户引用过的代码
+    pub fn code_by_hash(&self, code_hash: &H256) -> Option<Vec<u8>> {
+        self.0.codes.get(code_hash).map(|code| code.to_vec())
+    }
+
+    /// 按地址升序返回 `after` 之后（不含）的至多 `limit` 个账户
+    pub fn accounts_page(&self, after: Option<Address>, limit: usize) -> Vec<Account> {
+        self.0
+            .accounts
+            .range((after_bound(after), Bound::Unbounded))
+            .take(limit)
+            .map(|(_, account)| account.clone())
+            .collect()
+    }
+
+    /// 按键升序返回账户中 `after` 之后（不含）的至多 `limit` 个存储槽
+    pub fn storage_page(
+        &self,
+        address: &Address,
+        after: Option<[u8; 32]>,
+        limit: usize,
+    ) -> Vec<StorageEntry> {
+        self.0
+            .slots
+            .get(address)
+            .map(|slots| {
+                slots
+                    .range((after_bound(after), Bound::Unbounded))
+                    .take(limit)
+                    .map(|(key, value)| (*key, *value))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 可写的快照数据，仍被其他读取方共享时先复制
+    fn data(&mut self) -> &mut SnapshotData {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// 写入账户，`None` 表示账户已不存在
+    pub(crate) fn put_account(&mut self, address: Address, account: Option<Account>) {
+        match account {
+            Some(account) => self.data().accounts.insert(address, account),
+            None => self.data().accounts.remove(&address),
+        };
+    }
+
+    pub(crate) fn put_storage_value(&mut self, address: Address, key: [u8; 32], value: [u8; 32]) {
+        let slots = self.data().slots.entry(address).or_default();
+        Arc::make_mut(slots).insert(key, value);
+    }
+
+    pub(crate) fn put_code(&mut self, code_hash: H256, code: Vec<u8>) {
+        if !code.is_empty() {
+            self.data()
+                .codes
+                .entry(code_hash)
+                .or_insert_with(|| Arc::new(code));
+        }
+    }
+
+    pub(crate) fn has_code(&self, code_hash: &H256) -> bool {
+        code_hash.is_zero() || self.0.codes.contains_key(code_hash)
+    }
+
+    /// 递增发布序号
+    pub(crate) fn bump_version(&mut self) {
+        self.data().version += 1;
+    }
+
+    pub(crate) fn into_inner(self) -> Arc<SnapshotData> {
+        self.0
+    }
+
+    pub(crate) fn from_inner(data: Arc<SnapshotData>) -> Self {
+        Self(data)
+    }
+}
+
+/// 分页起点，不包含游标本身
+fn after_bound<K>(after: Option<K>) -> Bound<K> {
+    after.map_or(Bound::Unbounded, Bound::Excluded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[tokio::test]
+    async fn test_capture_and_copy_on_write() {
+        let mut storage = MemoryStorage::new();
+        let address = Address([1u8; 20]);
+        storage.set_account(&Account::new(address)).await;
+        storage.set_balance(&address, U256::from(5)).await;
+        let code_hash = storage.set_code(&address, vec![0x60, 0x00]).await;
+        storage
+            .set_storage_value(&address, [1u8; 32], [2u8; 32])
+            .await;
+
+        let snapshot = StorageSnapshot::capture(&storage).await;
+        assert_eq!(snapshot.balance(&address), U256::from(5));
+        assert_eq!(snapshot.code(&address), vec![0x60, 0x00]);
+        assert_eq!(snapshot.code_by_hash(&code_hash), Some(vec![0x60, 0x00]));
+        assert_eq!(snapshot.storage_value(&address, [1u8; 32]), [2u8; 32]);
+        assert_eq!(snapshot.accounts_page(None, 10).len(), 1);
+
+        // 修改副本不影响已发出的快照
+        let mut next = snapshot.clone();
+        next.put_storage_value(address, [1u8; 32], [3u8; 32]);
+        next.put_account(address, None);
+        next.bump_version();
+        assert_eq!(snapshot.storage_value(&address, [1u8; 32]), [2u8; 32]);
+        assert_eq!(snapshot.balance(&address), U256::from(5));
+        assert_eq!(next.storage_value(&address, [1u8; 32]), [3u8; 32]);
+        assert!(next.account(&address).is_none());
+        assert_eq!((snapshot.version(), next.version()), (0, 1));
+    }
+}
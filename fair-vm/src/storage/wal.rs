@@ -0,0 +1,253 @@
+//! 区块应用预写日志
+//!
+//! 区块执行产生的状态写入先完整写入日志并落盘，再应用到存储，应用完成后清空日志。
+//! 启动时若日志中有完整记录则重放（写入均为最终值，重放是幂等的）；
+//! 记录不完整说明崩溃发生在落盘之前，存储尚未改动，直接丢弃即可回滚。
+//! 记录默认以 zstd 压缩，升级前写入的未压缩记录仍可重放。
+
+use crate::account::{Account, Address};
+use crate::compression::{self, Compression};
+use crate::receipt::Receipt;
+use crate::storage::Storage;
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+/// 日志文件名
+pub const WAL_FILE_NAME: &str = "state.wal";
+
+/// 长度前缀字节数
+const LENGTH_SIZE: usize = 8;
+
+/// 校验和字节数
+const CHECKSUM_SIZE: usize = 32;
+
+/// 预写日志错误
+#[derive(Debug, Error)]
+pub enum WalError {
+    #[error("IO 错误: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("序列化失败: {0}")]
+    Serialization(String),
+}
+
+/// 单个状态写入
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WalOp {
+    /// 写入账户
+    SetAccount(Account),
+    /// 写入账户代码
+    SetCode { address: Address, code: Vec<u8> },
+    /// 写入存储槽
+    SetStorage {
+        address: Address,
+        key: [u8; 32],
+        value: [u8; 32],
+    },
+    /// 写入交易收据
+    SetReceipt(Receipt),
+}
+
+/// 一个区块的全部状态写入和收据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalRecord {
+    /// 区块高度
+    pub block_number: u64,
+    /// 区块哈希
+    pub block_hash: H256,
+    /// 状态写入
+    pub ops: Vec<WalOp>,
+}
+
+/// 将状态写入应用到存储
+pub async fn apply(storage: &mut (dyn Storage + Send + Sync), ops: &[WalOp]) {
+    storage.write_batch(ops).await
+}
+
+/// 预写日志
+///
+/// 文件格式为 8 字节小端长度、压缩帧格式的 JSON 记录和 32 字节 keccak 校验和。
+#[derive(Debug, Clone)]
+pub struct WriteAheadLog {
+    path: PathBuf,
+    compression: Compression,
+}
+
+impl WriteAheadLog {
+    /// 创建预写日志，文件在首次写入时创建
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            compression: Compression::Zstd,
+        }
+    }
+
+    /// 设置记录的压缩算法
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 写入记录并落盘
+    pub async fn append(&self, record: &WalRecord) -> Result<(), WalError> {
+        let payload = compression::encode(record, self.compression)
+            .map_err(|e| WalError::Serialization(e.to_string()))?;
+        let mut bytes = Vec::with_capacity(LENGTH_SIZE + payload.len() + CHECKSUM_SIZE);
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&ethers::utils::keccak256(&payload));
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let mut file = File::create(&self.path).await?;
+        file.write_all(&bytes).await?;
+        file.sync_all().await?;
+        Ok(())
+    }
+
+    /// 清空日志
+    pub async fn clear(&self) -> Result<(), WalError> {
+        match OpenOptions::new().write(true).open(&self.path).await {
+            Ok(file) => {
+                file.set_len(0).await?;
+                file.sync_all().await?;
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 读取需要重放的记录
+    ///
+    /// 日志为空或记录不完整时返回 `None`，不完整的记录会被清除。
+    pub async fn recover(&self) -> Result<Option<WalRecord>, WalError> {
+        let bytes = match fs::read(&self.path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+
+        match decode(&bytes) {
+            Some(record) => Ok(Some(record)),
+            None => {
+                log::warn!("预写日志 {:?} 中的记录不完整，已丢弃", self.path);
+                self.clear().await?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// 解析日志内容，长度或校验和不符时返回 `None`
+fn decode(bytes: &[u8]) -> Option<WalRecord> {
+    let length = u64::from_le_bytes(bytes.get(..LENGTH_SIZE)?.try_into().ok()?) as usize;
+    let end = LENGTH_SIZE.checked_add(length)?;
+    let payload = bytes.get(LENGTH_SIZE..end)?;
+    let checksum = bytes.get(end..end.checked_add(CHECKSUM_SIZE)?)?;
+    if checksum != ethers::utils::keccak256(payload) {
+        return None;
+    }
+    compression::decode(payload).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use tempfile::tempdir;
+
+    fn record() -> WalRecord {
+        let address = Address([1u8; 20]);
+        let mut account = Account::new(address);
+        account.nonce = 3;
+        WalRecord {
+            block_number: 7,
+            block_hash: H256::from_low_u64_be(7),
+            ops: vec![
+                WalOp::SetAccount(account),
+                WalOp::SetCode {
+                    address,
+                    code: vec![0x60, 0x00],
+                },
+                WalOp::SetStorage {
+                    address,
+                    key: [1u8; 32],
+                    value: [2u8; 32],
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recover_complete_record() {
+        let dir = tempdir().unwrap();
+        let wal = WriteAheadLog::new(dir.path().join(WAL_FILE_NAME));
+        assert!(wal.recover().await.unwrap().is_none());
+
+        wal.append(&record()).await.unwrap();
+        let recovered = wal.recover().await.unwrap().unwrap();
+        assert_eq!(recovered, record());
+
+        let mut storage = MemoryStorage::new();
+        apply(&mut storage, &recovered.ops).await;
+        // 重放是幂等的
+        apply(&mut storage, &recovered.ops).await;
+        let address = Address([1u8; 20]);
+        assert_eq!(storage.get_nonce(&address).await, 3);
+        assert_eq!(storage.get_code(&address).await, vec![0x60, 0x00]);
+        assert_eq!(
+            storage.get_storage_value(&address, [1u8; 32]).await,
+            [2u8; 32]
+        );
+
+        wal.clear().await.unwrap();
+        assert!(wal.recover().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_recover_uncompressed_record() {
+        let dir = tempdir().unwrap();
+        let wal =
+            WriteAheadLog::new(dir.path().join(WAL_FILE_NAME)).with_compression(Compression::None);
+        wal.append(&record()).await.unwrap();
+        assert_eq!(wal.recover().await.unwrap().unwrap(), record());
+
+        // 升级前写入的未加帧记录
+        let payload = serde_json::to_vec(&record()).unwrap();
+        let mut bytes = (payload.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&ethers::utils::keccak256(&payload));
+        fs::write(wal.path(), &bytes).await.unwrap();
+        assert_eq!(wal.recover().await.unwrap().unwrap(), record());
+    }
+
+    #[tokio::test]
+    async fn test_discard_torn_record() {
+        let dir = tempdir().unwrap();
+        let wal = WriteAheadLog::new(dir.path().join(WAL_FILE_NAME));
+        wal.append(&record()).await.unwrap();
+
+        // 模拟写入中途崩溃
+        let bytes = fs::read(wal.path()).await.unwrap();
+        fs::write(wal.path(), &bytes[..bytes.len() - 5])
+            .await
+            .unwrap();
+
+        assert!(wal.recover().await.unwrap().is_none());
+        assert!(fs::read(wal.path()).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! 供应量与持币排行
+//!
+//! 节点启动时遍历状态中的全部账户重建统计，区块导入时按本区块写入的账户余额增量更新总供应量
+//! 和持币排行，浏览器和看板通过 `fairvm_supplyStats` 查询，无需遍历全部状态。排行按余额有序
+//! 保存，查询前 N 名只需读取有序集合的末尾。
+//!
+//! 销毁量按执行实际从余额中扣除的金额计算：区块内账户余额的净减少量，加上同一区块领取的质押
+//! 奖励（领取时新增的余额）。累计销毁量记录在供应量系统地址的存储槽中，随区块状态一起提交，
+//! 重建时从状态读取。当前交易执行不从余额中扣除 gas 费用，销毁量保持为 0。
+
+use crate::account::Address;
+use crate::genesis::Genesis;
+use crate::storage::Storage;
+use ethers::types::{H160, U256};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::{BTreeSet, HashMap};
+
+/// 供应量系统地址，累计销毁量记录在该地址的存储槽中
+pub const SUPPLY_ADDRESS: Address = Address([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f, 0x08,
+]);
+
+/// 默认返回的持币排行数量
+pub const DEFAULT_TOP_HOLDERS: usize = 100;
+
+/// 单次查询允许返回的最大持币排行数量
+pub const MAX_TOP_HOLDERS: usize = 1000;
+
+/// 累计销毁量存储槽
+fn burned_slot() -> [u8; 32] {
+    Keccak256::digest(b"fairvm-supply-burned").into()
+}
+
+/// 状态中记录的累计销毁量
+pub async fn burned_fees(storage: &(dyn Storage + Send + Sync)) -> U256 {
+    U256::from_big_endian(
+        &storage
+            .get_storage_value(&SUPPLY_ADDRESS, burned_slot())
+            .await,
+    )
+}
+
+/// 在状态中累加区块的销毁量，为 0 时不写入
+pub async fn record_burn(storage: &mut (dyn Storage + Send + Sync), amount: U256) {
+    if amount.is_zero() {
+        return;
+    }
+    let total = burned_fees(storage).await.saturating_add(amount);
+    let mut word = [0u8; 32];
+    total.to_big_endian(&mut word);
+    storage
+        .set_storage_value(&SUPPLY_ADDRESS, burned_slot(), word)
+        .await;
+}
+
+/// 持币账户
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holder {
+    pub address: H160,
+    pub balance: U256,
+}
+
+/// 供应量统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupplyStats {
+    /// 统计截至的区块高度
+    pub block_number: u64,
+    /// 全部账户余额之和
+    pub total_supply: U256,
+    /// 累计销毁的基础费用
+    pub burned_fees: U256,
+    /// 余额不为 0 的账户数
+    pub holder_count: usize,
+    /// 按余额从高到低排列的账户
+    pub top_holders: Vec<Holder>,
+}
+
+/// 增量供应量统计器，在区块导入时更新
+#[derive(Debug, Clone, Default)]
+pub struct SupplyTracker {
+    balances: HashMap<H160, U256>,
+    /// 按 (余额, 地址) 排序，只包含余额不为 0 的账户
+    ranking: BTreeSet<(U256, H160)>,
+    total_supply: U256,
+    burned_fees: U256,
+    block_number: u64,
+}
+
+impl SupplyTracker {
+    /// 以创世分配为初始余额
+    pub fn from_genesis(genesis: &Genesis) -> Self {
+        let mut tracker = Self::default();
+        for (address, account) in &genesis.alloc {
+            tracker.set_balance(*address, U256::from(account.balance));
+        }
+        tracker
+    }
+
+    /// 遍历状态中的全部账户重建统计，`block_number` 为状态对应的区块高度
+    pub async fn from_storage(storage: &(dyn Storage + Send + Sync), block_number: u64) -> Self {
+        let mut tracker = Self::default();
+        let mut accounts = storage.iter_accounts();
+        while let Some(account) = accounts.next().await {
+            tracker.set_balance(H160(account.address.0), account.balance);
+        }
+        tracker.burned_fees = burned_fees(storage).await;
+        tracker.block_number = block_number;
+        tracker
+    }
+
+    /// 更新账户余额
+    pub fn set_balance(&mut self, address: H160, balance: U256) {
+        let previous = self.balances.remove(&address).unwrap_or_default();
+        self.ranking.remove(&(previous, address));
+        self.total_supply = self
+            .total_supply
+            .saturating_sub(previous)
+            .saturating_add(balance);
+        if !balance.is_zero() {
+            self.balances.insert(address, balance);
+            self.ranking.insert((balance, address));
+        }
+    }
+
+    /// 记录导入的区块，`burned` 为本区块的销毁量，`balances` 为本区块写入的账户余额，
+    /// 同一账户以最后一次写入为准
+    pub fn record_block(
+        &mut self,
+        number: u64,
+        burned: U256,
+        balances: impl IntoIterator<Item = (H160, U256)>,
+    ) {
+        for (address, balance) in balances {
+            self.set_balance(address, balance);
+        }
+        self.burned_fees = self.burned_fees.saturating_add(burned);
+        self.block_number = number;
+    }
+
+    /// 当前统计，`limit` 为返回的持币排行数量
+    pub fn stats(&self, limit: usize) -> SupplyStats {
+        SupplyStats {
+            block_number: self.block_number,
+            total_supply: self.total_supply,
+            burned_fees: self.burned_fees,
+            holder_count: self.ranking.len(),
+            top_holders: self
+                .ranking
+                .iter()
+                .rev()
+                .take(limit)
+                .map(|(balance, address)| Holder {
+                    address: *address,
+                    balance: *balance,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn test_supply_tracker() {
+        let mut genesis = Genesis::new(1);
+        let (a, b, c) = (
+            H160::from_low_u64_be(1),
+            H160::from_low_u64_be(2),
+            H160::from_low_u64_be(3),
+        );
+        genesis.add_account(a, 500);
+        genesis.add_account(b, 300);
+        let mut tracker = SupplyTracker::from_genesis(&genesis);
+        assert_eq!(tracker.stats(10).total_supply, U256::from(800));
+
+        // a 转给 c 400，b 的余额被后一次写入覆盖
+        tracker.record_block(
+            1,
+            U256::from(21),
+            [
+                (a, U256::from(100)),
+                (c, U256::from(400)),
+                (b, U256::from(250)),
+                (b, U256::from(200)),
+            ],
+        );
+        let stats = tracker.stats(2);
+        assert_eq!(stats.block_number, 1);
+        assert_eq!(stats.total_supply, U256::from(700));
+        assert_eq!(stats.burned_fees, U256::from(21));
+        assert_eq!(stats.holder_count, 3);
+        assert_eq!(
+            stats.top_holders,
+            vec![
+                Holder {
+                    address: c,
+                    balance: U256::from(400)
+                },
+                Holder {
+                    address: b,
+                    balance: U256::from(200)
+                },
+            ]
+        );
+
+        tracker.record_block(2, U256::zero(), [(a, U256::zero())]);
+        let stats = tracker.stats(10);
+        assert_eq!(stats.holder_count, 2);
+        assert_eq!(stats.total_supply, U256::from(600));
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_from_storage() {
+        let mut genesis = Genesis::new(1);
+        genesis.add_account(H160::from_low_u64_be(1), 500);
+        genesis.add_account(H160::from_low_u64_be(2), 300);
+        let mut storage = MemoryStorage::default();
+        genesis.write_alloc(&mut storage).await;
+        record_burn(&mut storage, U256::from(7)).await;
+        record_burn(&mut storage, U256::zero()).await;
+        record_burn(&mut storage, U256::from(3)).await;
+
+        let stats = SupplyTracker::from_storage(&storage, 5).await.stats(10);
+        assert_eq!(stats.block_number, 5);
+        assert_eq!(stats.total_supply, U256::from(800));
+        assert_eq!(stats.burned_fees, U256::from(10));
+        assert_eq!(stats.holder_count, 2);
+        assert_eq!(stats.top_holders[0].address, H160::from_low_u64_be(1));
+    }
+}
@@ -0,0 +1,225 @@
+//! 匿名遥测上报
+//!
+//! 仅在配置中显式启用（`telemetry.enabled = true` 且设置了 `telemetry.endpoint`）时运行，
+//! 按 `interval_secs` 定期把 [`TelemetryReport`] 以 JSON POST 到上报地址。上报失败只记录
+//! 日志，不重试，也不影响节点运行。
+//!
+//! 上报内容（`schema_version = 1`）：
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "node_id": "5f0c9a8e2b6d4e1f8a7b3c2d1e0f9a8b",
+//!   "chain_id": 12345,
+//!   "version": "0.1.0",
+//!   "block_height": 1024,
+//!   "peer_count": 3,
+//!   "timestamp": 1760486400
+//! }
+//! ```
+//!
+//! `node_id` 是首次上报时随机生成并保存在数据目录 `TELEMETRY_ID` 文件中的标识，只用于
+//! 对同一节点的多次上报去重，与密钥、地址和 IP 无关，删除该文件即可更换。上报不包含
+//! 账户、交易、对等节点地址等任何链上或网络身份信息。
+
+use crate::blockchain::Blockchain;
+use fair_vm_core::config::TelemetryConfig;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+/// 上报格式版本，字段变化时递增
+pub const TELEMETRY_SCHEMA_VERSION: u32 = 1;
+
+/// 数据目录中保存节点匿名标识的文件名
+pub const NODE_ID_FILE_NAME: &str = "TELEMETRY_ID";
+
+/// 单次请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 遥测错误
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("请求失败: {0}")]
+    Request(String),
+
+    #[error("服务端返回状态码 {0}")]
+    Status(u16),
+}
+
+/// 一次遥测上报
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryReport {
+    /// 上报格式版本
+    pub schema_version: u32,
+    /// 节点匿名标识
+    pub node_id: String,
+    /// 链 ID
+    pub chain_id: u64,
+    /// 节点版本
+    pub version: String,
+    /// 最新区块高度
+    pub block_height: u64,
+    /// 配置的对等节点数量
+    pub peer_count: usize,
+    /// 上报时间（Unix 秒）
+    pub timestamp: i64,
+}
+
+/// 读取数据目录中的节点匿名标识，不存在时生成并保存
+pub async fn node_id(data_dir: &Path) -> std::io::Result<String> {
+    let path = data_dir.join(NODE_ID_FILE_NAME);
+    match tokio::fs::read_to_string(&path).await {
+        Ok(id) if !id.trim().is_empty() => return Ok(id.trim().to_string()),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let id = random_node_id();
+    tokio::fs::create_dir_all(data_dir).await?;
+    tokio::fs::write(&path, &id).await?;
+    Ok(id)
+}
+
+/// 随机生成的节点匿名标识
+pub fn random_node_id() -> String {
+    hex::encode(rand::rng().random::<[u8; 16]>())
+}
+
+/// 遥测上报器
+pub struct TelemetryReporter {
+    config: TelemetryConfig,
+    client: reqwest::Client,
+    node_id: String,
+    chain_id: u64,
+    peer_count: usize,
+    blockchain: Arc<RwLock<Blockchain>>,
+}
+
+impl TelemetryReporter {
+    pub fn new(
+        config: TelemetryConfig,
+        node_id: String,
+        chain_id: u64,
+        peer_count: usize,
+        blockchain: Arc<RwLock<Blockchain>>,
+    ) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            node_id,
+            chain_id,
+            peer_count,
+            blockchain,
+        }
+    }
+
+    /// 采集当前的上报内容
+    pub async fn collect(&self) -> TelemetryReport {
+        let block_height = self
+            .blockchain
+            .read()
+            .await
+            .latest_block()
+            .map_or(0, |block| block.header.number);
+        TelemetryReport {
+            schema_version: TELEMETRY_SCHEMA_VERSION,
+            node_id: self.node_id.clone(),
+            chain_id: self.chain_id,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            block_height,
+            peer_count: self.peer_count,
+            timestamp: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// 发送一次上报
+    pub async fn send(&self, report: &TelemetryReport) -> Result<(), TelemetryError> {
+        let response = self
+            .client
+            .post(&self.config.endpoint)
+            .json(report)
+            .send()
+            .await
+            .map_err(|e| TelemetryError::Request(e.to_string()))?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(TelemetryError::Status(response.status().as_u16()))
+        }
+    }
+
+    /// 启动后台上报任务，启动时立即上报一次，需要在 tokio 运行时中调用
+    pub fn spawn(self) -> JoinHandle<()> {
+        let interval = Duration::from_secs(self.config.interval_secs);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let report = self.collect().await;
+                if let Err(e) = self.send(&report).await {
+                    log::debug!("遥测上报到 {} 失败: {}", self.config.endpoint, e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_node_id_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = node_id(dir.path()).await.unwrap();
+        assert_eq!(id.len(), 32);
+        assert_eq!(node_id(dir.path()).await.unwrap(), id);
+    }
+
+    #[tokio::test]
+    async fn test_collect_report() {
+        let reporter = TelemetryReporter::new(
+            TelemetryConfig::default(),
+            "node".to_string(),
+            12345,
+            3,
+            Arc::new(RwLock::new(Blockchain::default())),
+        );
+        let report = reporter.collect().await;
+        assert_eq!(report.schema_version, TELEMETRY_SCHEMA_VERSION);
+        assert_eq!(report.chain_id, 12345);
+        assert_eq!(report.block_height, 0);
+        assert_eq!(report.peer_count, 3);
+
+        // 上报字段固定，新增字段需要递增格式版本
+        let json = serde_json::to_value(&report).unwrap();
+        let mut keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "block_height",
+                "chain_id",
+                "node_id",
+                "peer_count",
+                "schema_version",
+                "timestamp",
+                "version"
+            ]
+        );
+    }
+}
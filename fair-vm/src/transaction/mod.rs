@@ -1,6 +1,14 @@
 use crate::account::Address;
-use ethers::types::{H256, U256};
+use crate::signing::{self, SIGNATURE_LENGTH};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::{AccessList, Eip2930TransactionRequest};
+use ethers::types::{Eip1559TransactionRequest, TransactionRequest, H160, H256, U256};
+use ethers::utils::rlp::Rlp;
+use fair_vm_core::serde_hex;
+use secp256k1::{Secp256k1, Verification};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TransactionType {
@@ -18,7 +26,9 @@ pub struct Transaction {
     pub nonce: u64,
     pub gas_limit: u64,
     pub gas_price: Option<U256>,
+    #[serde(with = "serde_hex::bytes")]
     pub data: Vec<u8>,
+    #[serde(with = "serde_hex::bytes")]
     pub signature: Vec<u8>,
     pub transaction_type: TransactionType,
     pub chain_id: u64,
@@ -113,11 +123,153 @@ impl Transaction {
         self.max_priority_fee_per_gas
     }
 
-    /// 验证交易签名
+    /// 计算在给定基础费用下实际支付的优先费，出价低于基础费用时返回 None
+    pub fn effective_priority_fee(&self, base_fee: U256) -> Option<U256> {
+        match self.transaction_type {
+            TransactionType::EIP1559 => {
+                let max_fee = self.max_fee_per_gas?;
+                let max_priority_fee = self.max_priority_fee_per_gas?;
+                let headroom = max_fee.checked_sub(base_fee)?;
+                Some(max_priority_fee.min(headroom))
+            }
+            TransactionType::Legacy | TransactionType::EIP2930 => {
+                self.gas_price?.checked_sub(base_fee)
+            }
+        }
+    }
+
+    /// 计算在给定基础费用下实际支付的 gas 价格
+    pub fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        match self.transaction_type {
+            TransactionType::EIP1559 => match self.effective_priority_fee(base_fee) {
+                Some(priority_fee) => base_fee + priority_fee,
+                None => self.max_fee_per_gas.unwrap_or_default(),
+            },
+            TransactionType::Legacy | TransactionType::EIP2930 => {
+                self.gas_price.unwrap_or_default()
+            }
+        }
+    }
+
+    /// 解码已签名的原始交易（RLP 或 EIP-2718 类型化编码）
+    ///
+    /// 发送方需通过 [`Transaction::recover_sender`] 恢复后填入 `from`。
+    pub fn from_raw(raw: &[u8]) -> Result<Self, String> {
+        let (typed, signature) =
+            TypedTransaction::decode_signed(&Rlp::new(raw)).map_err(|e| e.to_string())?;
+        let transaction_type = match &typed {
+            TypedTransaction::Legacy(_) => TransactionType::Legacy,
+            TypedTransaction::Eip2930(_) => TransactionType::EIP2930,
+            TypedTransaction::Eip1559(_) => TransactionType::EIP1559,
+        };
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match &typed {
+            TypedTransaction::Eip1559(tx) => (tx.max_fee_per_gas, tx.max_priority_fee_per_gas),
+            _ => (None, None),
+        };
+
+        // v 统一为恢复 ID（0 或 1）
+        let recovery_id = match signature.v {
+            v if v >= 35 => (v - 35) % 2,
+            v if v >= 27 => v - 27,
+            v => v,
+        };
+        let mut signature_bytes = vec![0u8; SIGNATURE_LENGTH];
+        signature.r.to_big_endian(&mut signature_bytes[..32]);
+        signature.s.to_big_endian(&mut signature_bytes[32..64]);
+        signature_bytes[64] = recovery_id as u8;
+
+        Ok(Self {
+            hash: H256(ethers::utils::keccak256(raw)),
+            from: Address::default(),
+            to: typed.to_addr().map(|to| Address::from(*to)),
+            value: typed.value().copied().unwrap_or_default(),
+            nonce: typed.nonce().map_or(0, |nonce| nonce.low_u64()),
+            gas_limit: typed.gas().map_or(0, |gas| gas.low_u64()),
+            gas_price: match &typed {
+                TypedTransaction::Eip1559(_) => None,
+                _ => typed.gas_price(),
+            },
+            data: typed.data().map(|data| data.to_vec()).unwrap_or_default(),
+            signature: signature_bytes,
+            transaction_type,
+            chain_id: typed.chain_id().map_or(0, |id| id.low_u64()),
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        })
+    }
+
+    /// 转换为以太坊类型化交易，用于计算签名哈希
+    fn to_typed(&self) -> TypedTransaction {
+        let mut request = TransactionRequest::new()
+            .from(H160(self.from.0))
+            .value(self.value)
+            .nonce(self.nonce)
+            .gas(self.gas_limit)
+            .data(self.data.clone())
+            .chain_id(self.chain_id);
+        if let Some(to) = &self.to {
+            request = request.to(H160(to.0));
+        }
+        if let Some(gas_price) = self.gas_price {
+            request = request.gas_price(gas_price);
+        }
+
+        match self.transaction_type {
+            TransactionType::Legacy => request.into(),
+            TransactionType::EIP2930 => {
+                Eip2930TransactionRequest::new(request, AccessList::default()).into()
+            }
+            TransactionType::EIP1559 => {
+                let mut eip1559 = Eip1559TransactionRequest::new()
+                    .from(H160(self.from.0))
+                    .value(self.value)
+                    .nonce(self.nonce)
+                    .gas(self.gas_limit)
+                    .data(self.data.clone())
+                    .chain_id(self.chain_id);
+                if let Some(to) = &self.to {
+                    eip1559 = eip1559.to(H160(to.0));
+                }
+                if let Some(max_fee) = self.max_fee_per_gas {
+                    eip1559 = eip1559.max_fee_per_gas(max_fee);
+                }
+                if let Some(max_priority_fee) = self.max_priority_fee_per_gas {
+                    eip1559 = eip1559.max_priority_fee_per_gas(max_priority_fee);
+                }
+                eip1559.into()
+            }
+        }
+    }
+
+    /// 签名哈希，与以太坊钱包签名的消息一致
+    pub fn sighash(&self) -> H256 {
+        self.to_typed().sighash()
+    }
+
+    /// 从签名中恢复发送方
+    pub fn recover_sender(&self) -> Result<Address, String> {
+        self.recover_sender_with(&Secp256k1::verification_only())
+    }
+
+    /// 使用已有上下文恢复发送方
+    pub fn recover_sender_with<C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+    ) -> Result<Address, String> {
+        let mut signature = self.signature.clone();
+        // 兼容 27/28 形式的 v
+        if let Some(v) = signature.get_mut(SIGNATURE_LENGTH - 1) {
+            if *v >= 27 {
+                *v -= 27;
+            }
+        }
+        signing::recover_signer_with(secp, self.sighash().0, &signature)
+    }
+
+    /// 验证交易签名由 `from` 签发
     pub fn verify_signature(&self) -> bool {
-        // TODO: 实现实际的签名验证逻辑
-        // 目前返回 true 作为占位符
-        true
+        self.recover_sender()
+            .is_ok_and(|sender| sender == self.from)
     }
 
     /// 验证交易是否满足最低 gas 价格要求
@@ -149,3 +301,70 @@ impl Transaction {
         }
     }
 }
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {} -> ", self.hash, self.from)?;
+        match &self.to {
+            Some(to) => write!(f, "{}", to)?,
+            None => f.write_str("contract creation")?,
+        }
+        write!(f, " value {} nonce {}", self.value, self.nonce)
+    }
+}
+
+/// 解析 `0x` 前缀的已签名原始交易，并恢复发送方
+impl FromStr for Transaction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = serde_hex::decode(s).map_err(|e| e.to_string())?;
+        let mut transaction = Self::from_raw(&raw)?;
+        transaction.from = transaction.recover_sender()?;
+        Ok(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_json() {
+        let tx = Transaction::new(
+            H256::repeat_byte(0xab),
+            Address([1u8; 20]),
+            None,
+            U256::from(255),
+            7,
+            21000,
+            Some(U256::from(1)),
+            vec![0xca, 0xfe],
+            vec![0u8; SIGNATURE_LENGTH],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        let json = serde_json::to_value(&tx).unwrap();
+        assert_eq!(json["from"], "0x0101010101010101010101010101010101010101");
+        assert_eq!(json["value"], "0xff");
+        assert_eq!(json["data"], "0xcafe");
+        assert_eq!(
+            json["signature"],
+            format!("0x{}", "00".repeat(SIGNATURE_LENGTH))
+        );
+        let decoded: Transaction = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.data, tx.data);
+        assert_eq!(decoded.from, tx.from);
+
+        assert_eq!(
+            tx.to_string(),
+            format!(
+                "{:?} 0x0101010101010101010101010101010101010101 -> contract creation value 255 nonce 7",
+                tx.hash
+            )
+        );
+        assert!("0x00".parse::<Transaction>().is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! 可插拔的交易校验规则
+//!
+//! 链运营方实现 [`TxValidator`] 并通过 `FairVM::add_tx_validator` 注册自定义规则，例如要求发送方
+//! 在 KYC 登记名单中、只允许指定地址部署合约。规则在交易进入交易池和校验区块时各执行一次：
+//! 交易池阶段拒绝的交易不会进入交易池，区块阶段任一交易被拒绝时整个区块被拒绝，
+//! 因此同一网络的所有节点必须注册相同的规则。
+
+use crate::account::Address;
+use crate::storage::Storage;
+use crate::transaction::Transaction;
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// 校验阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationStage {
+    /// 交易进入交易池
+    Mempool,
+    /// 校验收到的区块
+    Block,
+}
+
+impl fmt::Display for ValidationStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mempool => write!(f, "交易池"),
+            Self::Block => write!(f, "区块"),
+        }
+    }
+}
+
+/// 规则可读取的校验上下文
+pub struct ValidationContext<'a> {
+    pub stage: ValidationStage,
+    /// 交易将被打包或所在的区块高度
+    pub block_number: u64,
+    /// 区块执行前的状态
+    pub storage: &'a (dyn Storage + Send + Sync),
+}
+
+/// 交易校验错误类型
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{stage}校验时交易 {hash:?} 被规则 {rule} 拒绝: {reason}")]
+pub struct ValidationError {
+    pub stage: ValidationStage,
+    pub hash: ethers::types::H256,
+    pub rule: String,
+    pub reason: String,
+}
+
+/// 交易校验规则
+#[async_trait]
+pub trait TxValidator: Send + Sync {
+    /// 规则名称，出现在拒绝原因中
+    fn name(&self) -> &str;
+
+    /// 校验交易，拒绝时返回原因
+    async fn validate(
+        &self,
+        tx: &Transaction,
+        context: &ValidationContext<'_>,
+    ) -> Result<(), String>;
+}
+
+/// 已注册的校验规则，按注册顺序执行
+#[derive(Clone, Default)]
+pub struct TxValidators {
+    validators: Vec<Arc<dyn TxValidator>>,
+}
+
+impl TxValidators {
+    /// 注册规则
+    pub fn add(&mut self, validator: Arc<dyn TxValidator>) {
+        self.validators.push(validator);
+    }
+
+    /// 已注册的规则名称
+    pub fn names(&self) -> Vec<String> {
+        self.validators
+            .iter()
+            .map(|validator| validator.name().to_string())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// 依次执行全部规则，返回第一个拒绝
+    pub async fn validate(
+        &self,
+        tx: &Transaction,
+        context: &ValidationContext<'_>,
+    ) -> Result<(), ValidationError> {
+        for validator in &self.validators {
+            if let Err(reason) = validator.validate(tx, context).await {
+                return Err(ValidationError {
+                    stage: context.stage,
+                    hash: tx.hash,
+                    rule: validator.name().to_string(),
+                    reason,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 只允许名单内的地址发送交易，例如 KYC 登记过的账户
+#[derive(Debug, Clone, Default)]
+pub struct SenderAllowlist {
+    senders: HashSet<Address>,
+}
+
+impl SenderAllowlist {
+    pub fn new(senders: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            senders: senders.into_iter().collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl TxValidator for SenderAllowlist {
+    fn name(&self) -> &str {
+        "sender-allowlist"
+    }
+
+    async fn validate(
+        &self,
+        tx: &Transaction,
+        _context: &ValidationContext<'_>,
+    ) -> Result<(), String> {
+        if self.senders.contains(&tx.from) {
+            Ok(())
+        } else {
+            Err(format!("发送方 {} 不在名单中", tx.from))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::transaction::TransactionType;
+    use ethers::types::{H256, U256};
+
+    /// 拒绝携带调用数据的交易
+    struct NoData;
+
+    #[async_trait]
+    impl TxValidator for NoData {
+        fn name(&self) -> &str {
+            "no-data"
+        }
+
+        async fn validate(
+            &self,
+            tx: &Transaction,
+            context: &ValidationContext<'_>,
+        ) -> Result<(), String> {
+            if tx.data.is_empty() || context.stage == ValidationStage::Mempool {
+                Ok(())
+            } else {
+                Err("不允许调用数据".into())
+            }
+        }
+    }
+
+    fn transaction(from: u8, data: Vec<u8>) -> Transaction {
+        Transaction::new(
+            H256::from_low_u64_be(from as u64),
+            Address([from; 20]),
+            Some(Address([9u8; 20])),
+            U256::zero(),
+            0,
+            21_000,
+            Some(U256::zero()),
+            data,
+            Vec::new(),
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_validators() {
+        let storage = MemoryStorage::new();
+        let context = |stage| ValidationContext {
+            stage,
+            block_number: 1,
+            storage: &storage,
+        };
+        let mut validators = TxValidators::default();
+        assert!(validators
+            .validate(
+                &transaction(2, Vec::new()),
+                &context(ValidationStage::Block)
+            )
+            .await
+            .is_ok());
+
+        validators.add(Arc::new(SenderAllowlist::new([Address([1u8; 20])])));
+        validators.add(Arc::new(NoData));
+        assert_eq!(validators.names(), vec!["sender-allowlist", "no-data"]);
+
+        let error = validators
+            .validate(
+                &transaction(2, Vec::new()),
+                &context(ValidationStage::Mempool),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.rule, "sender-allowlist");
+        assert_eq!(error.hash, H256::from_low_u64_be(2));
+
+        let tx = transaction(1, vec![1]);
+        assert!(validators
+            .validate(&tx, &context(ValidationStage::Mempool))
+            .await
+            .is_ok());
+        let error = validators
+            .validate(&tx, &context(ValidationStage::Block))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            (error.rule.as_str(), error.stage),
+            ("no-data", ValidationStage::Block)
+        );
+    }
+}
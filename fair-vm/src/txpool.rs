@@ -0,0 +1,547 @@
+//! 交易池
+//!
+//! 交易进入共识前先进入 [`TxPool`]。每个账户的交易按 nonce 排列：从账户当前 nonce 起连续的
+//! 交易可执行（pending），nonce 之间有缺口的交易排队等待（queued），缺口补齐后转为可执行。
+//! 交易池总数受 `Config.tx_pool_size` 限制，单个账户的交易数受账户槽位限制；池满时逐出有效
+//! gas 价格最低的账户末尾交易，新交易的价格不高于它时拒绝。
+//!
+//! 同一账户同一 nonce 的新交易在费用上限和优先费都至少提高 [`PRICE_BUMP_PERCENT`] 时替换
+//! 池中的交易，用于加速或取消尚未打包的交易。
+//!
+//! 启用二维 nonce（[`NonceMode::TwoDimensional`]）时，同一账户的每个 nonce 通道单独排队，
+//! 一个通道的缺口不会阻塞其他通道，账户槽位按账户的全部通道合计。
+
+use crate::account::Address;
+use crate::nonce::NonceMode;
+use crate::transaction::Transaction;
+use ethers::types::{H256, U256};
+use fair_vm_core::config::Config;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use thiserror::Error;
+
+/// 单个账户默认可占用的交易槽位
+pub const DEFAULT_ACCOUNT_SLOTS: usize = 64;
+
+/// 替换同 nonce 交易时费用至少提高的百分比
+pub const PRICE_BUMP_PERCENT: u64 = 10;
+
+/// 交易池错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TxPoolError {
+    #[error("交易 {0:?} 已在交易池中")]
+    AlreadyKnown(H256),
+
+    #[error("交易 nonce {nonce} 低于账户当前 nonce {expected}")]
+    NonceTooLow { nonce: u64, expected: u64 },
+
+    #[error("替换 nonce 为 {nonce} 的交易需要把费用上限和优先费都至少提高 {bump}%")]
+    ReplacementUnderpriced { nonce: u64, bump: u64 },
+
+    #[error("账户 {account} 在交易池中的交易数已达上限 {limit}")]
+    AccountLimit { account: Address, limit: usize },
+
+    #[error("交易池已满（{limit} 笔），gas 价格 {price} 不高于池中最低价格")]
+    PoolFull { limit: usize, price: U256 },
+}
+
+/// 加入交易的结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AddOutcome {
+    /// 因此转为可执行的交易，按 nonce 排列；交易进入排队时为空
+    pub promoted: Vec<Transaction>,
+    /// 被替换的同 nonce 交易
+    pub replaced: Option<Transaction>,
+}
+
+/// 交易愿意支付的最高 gas 价格
+fn fee_cap(tx: &Transaction) -> U256 {
+    tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default()
+}
+
+/// 交易愿意支付的最高优先费
+fn tip(tx: &Transaction) -> U256 {
+    tx.max_priority_fee_per_gas
+        .or(tx.gas_price)
+        .unwrap_or_default()
+}
+
+/// 费用按 [`PRICE_BUMP_PERCENT`] 提高后的值
+pub fn bumped(fee: U256) -> U256 {
+    fee.saturating_mul(U256::from(100 + PRICE_BUMP_PERCENT)) / 100
+}
+
+/// `replacement` 的费用是否足以替换 `original`
+pub fn is_replacement(original: &Transaction, replacement: &Transaction) -> bool {
+    fee_cap(replacement) >= bumped(fee_cap(original)) && tip(replacement) >= bumped(tip(original))
+}
+
+/// 单个账户一个 nonce 通道的交易
+#[derive(Debug, Default)]
+struct AccountQueue {
+    /// 通道当前 nonce
+    nonce: u64,
+    txs: BTreeMap<u64, Transaction>,
+}
+
+impl AccountQueue {
+    /// 第一个缺口的 nonce，此前的交易均可执行
+    fn pending_end(&self) -> u64 {
+        let mut next = self.nonce;
+        for nonce in self.txs.range(self.nonce..).map(|(nonce, _)| *nonce) {
+            if nonce != next {
+                break;
+            }
+            next += 1;
+        }
+        next
+    }
+}
+
+/// 交易池
+#[derive(Debug)]
+pub struct TxPool {
+    /// 交易总数上限
+    max_size: usize,
+    /// 单个账户的交易数上限
+    account_slots: usize,
+    /// 计算 EIP-1559 交易有效价格使用的基础费用
+    base_fee: U256,
+    nonce_mode: NonceMode,
+    /// （账户，nonce 通道）到交易的索引
+    accounts: HashMap<(Address, u64), AccountQueue>,
+    /// 交易哈希到（账户，nonce）的索引
+    hashes: HashMap<H256, (Address, u64)>,
+}
+
+impl Default for TxPool {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+impl TxPool {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            account_slots: DEFAULT_ACCOUNT_SLOTS,
+            base_fee: U256::zero(),
+            nonce_mode: NonceMode::Sequential,
+            accounts: HashMap::new(),
+            hashes: HashMap::new(),
+        }
+    }
+
+    /// 按节点配置创建交易池
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.tx_pool_size)
+    }
+
+    /// 设置单个账户的交易数上限
+    pub fn with_account_slots(mut self, account_slots: usize) -> Self {
+        self.account_slots = account_slots;
+        self
+    }
+
+    /// 设置 nonce 模式
+    pub fn with_nonce_mode(mut self, nonce_mode: NonceMode) -> Self {
+        self.nonce_mode = nonce_mode;
+        self
+    }
+
+    /// 设置计算有效价格使用的基础费用
+    pub fn set_base_fee(&mut self, base_fee: U256) {
+        self.base_fee = base_fee;
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    pub fn contains(&self, hash: &H256) -> bool {
+        self.hashes.contains_key(hash)
+    }
+
+    /// 按哈希查询交易
+    pub fn get(&self, hash: &H256) -> Option<&Transaction> {
+        let (account, nonce) = self.hashes.get(hash)?;
+        self.accounts
+            .get(&self.lane(account, *nonce))?
+            .txs
+            .get(nonce)
+    }
+
+    /// 交易所在的（账户，nonce 通道）
+    fn lane(&self, account: &Address, nonce: u64) -> (Address, u64) {
+        (*account, self.nonce_mode.lane(nonce))
+    }
+
+    /// 账户在全部通道中的交易数
+    fn account_len(&self, account: &Address) -> usize {
+        self.accounts
+            .iter()
+            .filter(|((owner, _), _)| owner == account)
+            .map(|(_, queue)| queue.txs.len())
+            .sum()
+    }
+
+    /// 加入交易，`account_nonce` 为发送方在交易所在通道的当前 nonce
+    pub fn add(&mut self, tx: Transaction, account_nonce: u64) -> Result<AddOutcome, TxPoolError> {
+        if self.hashes.contains_key(&tx.hash) {
+            return Err(TxPoolError::AlreadyKnown(tx.hash));
+        }
+        self.prune(&tx.from, account_nonce);
+        let lane = self.lane(&tx.from, tx.nonce);
+        let account_nonce = self
+            .accounts
+            .get(&lane)
+            .map_or(account_nonce, |queue| queue.nonce);
+        if tx.nonce < account_nonce {
+            return Err(TxPoolError::NonceTooLow {
+                nonce: tx.nonce,
+                expected: account_nonce,
+            });
+        }
+        let account_len = self.account_len(&tx.from);
+        if let Some(queue) = self.accounts.get_mut(&lane) {
+            if let Some(original) = queue.txs.get(&tx.nonce) {
+                if !is_replacement(original, &tx) {
+                    return Err(TxPoolError::ReplacementUnderpriced {
+                        nonce: tx.nonce,
+                        bump: PRICE_BUMP_PERCENT,
+                    });
+                }
+                let pending = tx.nonce < queue.pending_end();
+                self.hashes.insert(tx.hash, (tx.from, tx.nonce));
+                let replaced = queue.txs.insert(tx.nonce, tx.clone());
+                if let Some(replaced) = &replaced {
+                    self.hashes.remove(&replaced.hash);
+                }
+                return Ok(AddOutcome {
+                    promoted: if pending { vec![tx] } else { Vec::new() },
+                    replaced,
+                });
+            }
+        }
+        if account_len >= self.account_slots {
+            return Err(TxPoolError::AccountLimit {
+                account: tx.from,
+                limit: self.account_slots,
+            });
+        }
+        if self.len() >= self.max_size {
+            let price = tx.effective_gas_price(self.base_fee);
+            match self.cheapest_tail() {
+                Some((hash, lowest)) if price > lowest => {
+                    self.remove(&hash);
+                }
+                _ => {
+                    return Err(TxPoolError::PoolFull {
+                        limit: self.max_size,
+                        price,
+                    })
+                }
+            }
+        }
+
+        let queue = self.accounts.entry(lane).or_insert_with(|| AccountQueue {
+            nonce: account_nonce,
+            txs: BTreeMap::new(),
+        });
+        let before = queue.pending_end();
+        self.hashes.insert(tx.hash, (tx.from, tx.nonce));
+        queue.txs.insert(tx.nonce, tx);
+        let after = queue.pending_end();
+        Ok(AddOutcome {
+            promoted: queue
+                .txs
+                .range(before..after)
+                .map(|(_, tx)| tx.clone())
+                .collect(),
+            replaced: None,
+        })
+    }
+
+    /// 移除交易，同一账户 nonce 更高的交易转为排队
+    pub fn remove(&mut self, hash: &H256) -> Option<Transaction> {
+        let (account, nonce) = self.hashes.remove(hash)?;
+        let lane = self.lane(&account, nonce);
+        let queue = self.accounts.get_mut(&lane)?;
+        let tx = queue.txs.remove(&nonce);
+        if queue.txs.is_empty() {
+            self.accounts.remove(&lane);
+        }
+        tx
+    }
+
+    /// `nonce` 所在通道前进到 `nonce` 后移除已打包或已失效的交易
+    pub fn prune(&mut self, account: &Address, nonce: u64) {
+        let lane = self.lane(account, nonce);
+        let Some(queue) = self.accounts.get_mut(&lane) else {
+            return;
+        };
+        if nonce <= queue.nonce {
+            return;
+        }
+        queue.nonce = nonce;
+        let kept = queue.txs.split_off(&nonce);
+        for tx in std::mem::replace(&mut queue.txs, kept).into_values() {
+            self.hashes.remove(&tx.hash);
+        }
+        if queue.txs.is_empty() {
+            self.accounts.remove(&lane);
+        }
+    }
+
+    /// 可执行的交易，按账户分组并按 nonce 排列
+    pub fn pending(&self) -> HashMap<Address, Vec<Transaction>> {
+        by_account(self.collect(|queue| queue.txs.range(..queue.pending_end())))
+    }
+
+    /// 排队等待缺口补齐的交易，按账户分组并按 nonce 排列
+    pub fn queued(&self) -> HashMap<Address, Vec<Transaction>> {
+        by_account(self.collect(|queue| queue.txs.range(queue.pending_end()..)))
+    }
+
+    /// 按打包顺序取出最多 `limit` 笔可执行交易
+    ///
+    /// 同一通道的交易按 nonce 先后取出，通道之间每次取下一笔交易有效价格最高的通道。
+    pub fn ready(&self, limit: usize) -> Vec<Transaction> {
+        let mut pending = self.collect(|queue| queue.txs.range(..queue.pending_end()));
+        let mut heads = BinaryHeap::new();
+        for (lane, txs) in &mut pending {
+            txs.reverse();
+            if let Some(tx) = txs.last() {
+                heads.push((tx.effective_gas_price(self.base_fee), Reverse(*lane)));
+            }
+        }
+
+        let mut ready = Vec::new();
+        while ready.len() < limit {
+            let Some((_, Reverse(lane))) = heads.pop() else {
+                break;
+            };
+            let txs = pending.get_mut(&lane).expect("通道在可执行交易中");
+            if let Some(tx) = txs.pop() {
+                ready.push(tx);
+            }
+            if let Some(next) = txs.last() {
+                heads.push((next.effective_gas_price(self.base_fee), Reverse(lane)));
+            }
+        }
+        ready
+    }
+
+    fn collect<'a, I>(
+        &'a self,
+        select: impl Fn(&'a AccountQueue) -> I,
+    ) -> HashMap<(Address, u64), Vec<Transaction>>
+    where
+        I: Iterator<Item = (&'a u64, &'a Transaction)>,
+    {
+        self.accounts
+            .iter()
+            .filter_map(|(lane, queue)| {
+                let txs: Vec<Transaction> = select(queue).map(|(_, tx)| tx.clone()).collect();
+                (!txs.is_empty()).then_some((*lane, txs))
+            })
+            .collect()
+    }
+
+    /// 各账户末尾交易中有效价格最低的一笔
+    fn cheapest_tail(&self) -> Option<(H256, U256)> {
+        self.accounts
+            .values()
+            .filter_map(|queue| queue.txs.values().next_back())
+            .map(|tx| (tx.hash, tx.effective_gas_price(self.base_fee)))
+            .min_by_key(|(_, price)| *price)
+    }
+}
+
+/// 把各通道的交易合并到账户下，按 nonce 排列
+fn by_account(
+    lanes: HashMap<(Address, u64), Vec<Transaction>>,
+) -> HashMap<Address, Vec<Transaction>> {
+    let mut accounts: HashMap<Address, Vec<Transaction>> = HashMap::new();
+    for ((account, _), txs) in lanes {
+        accounts.entry(account).or_default().extend(txs);
+    }
+    for txs in accounts.values_mut() {
+        txs.sort_by_key(|tx| tx.nonce);
+    }
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionType;
+
+    fn tx(from: u8, nonce: u64, gas_price: u64) -> Transaction {
+        Transaction {
+            hash: H256::from_low_u64_be(((from as u64) << 32) | nonce),
+            from: Address([from; 20]),
+            to: Some(Address([9u8; 20])),
+            value: U256::zero(),
+            nonce,
+            gas_limit: 21000,
+            gas_price: Some(U256::from(gas_price)),
+            data: vec![],
+            signature: vec![],
+            transaction_type: TransactionType::Legacy,
+            chain_id: 1,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+        }
+    }
+
+    fn nonces(txs: &[Transaction]) -> Vec<u64> {
+        txs.iter().map(|tx| tx.nonce).collect()
+    }
+
+    #[test]
+    fn test_pending_and_queued() {
+        let mut pool = TxPool::new(10);
+        let account = Address([1u8; 20]);
+
+        assert!(pool.add(tx(1, 7, 1), 5).unwrap().promoted.is_empty());
+        assert_eq!(nonces(&pool.add(tx(1, 5, 1), 5).unwrap().promoted), vec![5]);
+        assert_eq!(nonces(&pool.pending()[&account]), vec![5]);
+        assert_eq!(nonces(&pool.queued()[&account]), vec![7]);
+
+        // 补齐缺口后排队的交易转为可执行
+        assert_eq!(
+            nonces(&pool.add(tx(1, 6, 1), 5).unwrap().promoted),
+            vec![6, 7]
+        );
+        assert!(pool.queued().is_empty());
+
+        assert_eq!(
+            pool.add(tx(1, 4, 1), 5),
+            Err(TxPoolError::NonceTooLow {
+                nonce: 4,
+                expected: 5
+            })
+        );
+        assert_eq!(
+            pool.add(tx(1, 6, 1), 5),
+            Err(TxPoolError::AlreadyKnown(tx(1, 6, 1).hash))
+        );
+
+        pool.prune(&account, 7);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(nonces(&pool.pending()[&account]), vec![7]);
+    }
+
+    #[test]
+    fn test_limits_and_eviction() {
+        let mut pool = TxPool::new(3).with_account_slots(2);
+        pool.add(tx(1, 0, 5), 0).unwrap();
+        pool.add(tx(1, 1, 1), 0).unwrap();
+        assert!(matches!(
+            pool.add(tx(1, 2, 9), 0),
+            Err(TxPoolError::AccountLimit { limit: 2, .. })
+        ));
+        pool.add(tx(2, 0, 3), 0).unwrap();
+
+        assert!(matches!(
+            pool.add(tx(3, 0, 1), 0),
+            Err(TxPoolError::PoolFull { limit: 3, .. })
+        ));
+        // 逐出价格最低的账户末尾交易
+        pool.add(tx(3, 0, 2), 0).unwrap();
+        assert!(!pool.contains(&tx(1, 1, 1).hash));
+        assert_eq!(pool.len(), 3);
+    }
+
+    #[test]
+    fn test_ready_order() {
+        let mut pool = TxPool::new(10);
+        pool.add(tx(1, 0, 1), 0).unwrap();
+        pool.add(tx(1, 1, 10), 0).unwrap();
+        pool.add(tx(2, 0, 5), 0).unwrap();
+        pool.add(tx(3, 1, 50), 0).unwrap();
+
+        let ready: Vec<(u8, u64)> = pool
+            .ready(10)
+            .iter()
+            .map(|tx| (tx.from.0[0], tx.nonce))
+            .collect();
+        assert_eq!(ready, vec![(2, 0), (1, 0), (1, 1)]);
+        assert_eq!(pool.ready(1).len(), 1);
+    }
+
+    #[test]
+    fn test_replacement() {
+        let mut pool = TxPool::new(10);
+        let account = Address([1u8; 20]);
+        let original = tx(1, 0, 100);
+        pool.add(original.clone(), 0).unwrap();
+        pool.add(tx(1, 2, 100), 0).unwrap();
+
+        let mut underpriced = tx(1, 0, 109);
+        underpriced.hash = H256::repeat_byte(1);
+        assert_eq!(
+            pool.add(underpriced, 0),
+            Err(TxPoolError::ReplacementUnderpriced {
+                nonce: 0,
+                bump: PRICE_BUMP_PERCENT
+            })
+        );
+
+        let mut replacement = tx(1, 0, 110);
+        replacement.hash = H256::repeat_byte(2);
+        let outcome = pool.add(replacement.clone(), 0).unwrap();
+        assert_eq!(outcome.replaced.map(|tx| tx.hash), Some(original.hash));
+        assert_eq!(nonces(&outcome.promoted), vec![0]);
+        assert!(!pool.contains(&original.hash));
+        assert_eq!(
+            pool.get(&replacement.hash).unwrap().gas_price,
+            Some(U256::from(110))
+        );
+        assert_eq!(pool.len(), 2);
+
+        // 替换排队中的交易不会使其可执行
+        let mut queued = tx(1, 2, 200);
+        queued.hash = H256::repeat_byte(3);
+        let outcome = pool.add(queued, 0).unwrap();
+        assert!(outcome.promoted.is_empty());
+        assert_eq!(pool.queued()[&account].len(), 1);
+    }
+
+    #[test]
+    fn test_nonce_lanes() {
+        let mut pool = TxPool::new(10)
+            .with_account_slots(3)
+            .with_nonce_mode(NonceMode::TwoDimensional);
+        let account = Address([1u8; 20]);
+        let lane = |key, sequence| crate::nonce::compose(key, sequence).unwrap();
+
+        // 通道 0 有缺口时通道 1 的交易仍可执行
+        assert!(pool.add(tx(1, 1, 1), 0).unwrap().promoted.is_empty());
+        assert_eq!(
+            nonces(&pool.add(tx(1, lane(1, 0), 1), lane(1, 0)).unwrap().promoted),
+            vec![lane(1, 0)]
+        );
+        assert_eq!(nonces(&pool.pending()[&account]), vec![lane(1, 0)]);
+        assert_eq!(nonces(&pool.queued()[&account]), vec![1]);
+
+        assert!(matches!(
+            pool.add(tx(1, lane(2, 5), 1), lane(2, 3)),
+            Ok(AddOutcome { ref promoted, .. }) if promoted.is_empty()
+        ));
+        // 账户槽位按全部通道合计
+        assert!(matches!(
+            pool.add(tx(1, lane(3, 0), 1), lane(3, 0)),
+            Err(TxPoolError::AccountLimit { limit: 3, .. })
+        ));
+
+        pool.prune(&account, lane(1, 1));
+        assert_eq!(pool.len(), 2);
+        assert!(pool.pending().is_empty());
+    }
+}
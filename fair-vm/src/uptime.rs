@@ -0,0 +1,236 @@
+//! 验证人在线率统计
+//!
+//! 每个区块按质押配置中未被监禁的验证人轮换顺序都有一个预定出块者。区块被接受时记录实际出块者：
+//! 预定出块者按时出块计为出块，否则计为错过一个出块槽。计数保存在质押系统地址的存储槽中，
+//! 随区块状态一起提交，供奖励分配和监禁逻辑使用。出块者未知的区块不计入统计。
+//! 除累计计数外还保存上次解禁以来的预定和错过槽位数，作为监禁判定的依据。
+
+use crate::jailing;
+use crate::staking::{StakingConfig, ValidatorStake, STAKING_ADDRESS};
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// 在线率的基点
+pub const UPTIME_BASIS_POINTS: u64 = 10_000;
+
+/// 验证人出块统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStats {
+    pub node_id: String,
+    /// 实际出块数，包含非预定槽位的出块
+    pub proposed: u64,
+    /// 作为预定出块者的槽位数
+    pub expected: u64,
+    /// 错过的预定槽位数
+    pub missed: u64,
+    /// 最近一次出块的高度
+    pub last_proposed: Option<u64>,
+    /// 被监禁时的区块高度，未被监禁时为 None
+    pub jailed_at: Option<u64>,
+}
+
+impl ValidatorStats {
+    /// 在线率（基点），尚无预定槽位时为满值
+    pub fn uptime(&self) -> u64 {
+        if self.expected == 0 {
+            return UPTIME_BASIS_POINTS;
+        }
+        (self.expected - self.missed) * UPTIME_BASIS_POINTS / self.expected
+    }
+}
+
+/// 参与出块轮换的验证人，全部被监禁时所有验证人都参与轮换
+pub async fn active_validators<'a>(
+    storage: &(dyn Storage + Send + Sync),
+    config: &'a StakingConfig,
+) -> Vec<&'a ValidatorStake> {
+    let mut active = Vec::with_capacity(config.validators.len());
+    for validator in &config.validators {
+        if jailing::jailed_at(storage, &validator.node_id)
+            .await
+            .is_none()
+        {
+            active.push(validator);
+        }
+    }
+    if active.is_empty() {
+        return config.validators.iter().collect();
+    }
+    active
+}
+
+/// 区块的预定出块者，按 `validators` 的顺序轮换
+pub fn scheduled_proposer<'a>(
+    validators: &[&'a ValidatorStake],
+    number: u64,
+) -> Option<&'a ValidatorStake> {
+    if validators.is_empty() || number == 0 {
+        return None;
+    }
+    let index = (number - 1) % validators.len() as u64;
+    validators.get(index as usize).copied()
+}
+
+fn stats_slot(node_id: &str, field: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(b"fairvm-uptime-");
+    hasher.update(field);
+    hasher.update(node_id.as_bytes());
+    hasher.finalize().into()
+}
+
+async fn read_counter(storage: &(dyn Storage + Send + Sync), node_id: &str, field: &[u8]) -> u64 {
+    let word = storage
+        .get_storage_value(&STAKING_ADDRESS, stats_slot(node_id, field))
+        .await;
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&word[24..]);
+    u64::from_be_bytes(bytes)
+}
+
+async fn write_counter(
+    storage: &mut (dyn Storage + Send + Sync),
+    node_id: &str,
+    field: &[u8],
+    value: u64,
+) {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    storage
+        .set_storage_value(&STAKING_ADDRESS, stats_slot(node_id, field), word)
+        .await;
+}
+
+async fn increment(storage: &mut (dyn Storage + Send + Sync), node_id: &str, field: &[u8]) {
+    let value = read_counter(storage, node_id, field).await;
+    write_counter(storage, node_id, field, value.saturating_add(1)).await;
+}
+
+/// 查询验证人的出块统计
+pub async fn stats_of(storage: &(dyn Storage + Send + Sync), node_id: &str) -> ValidatorStats {
+    // 高度加 1 保存，0 表示从未出块
+    let last = read_counter(storage, node_id, b"last").await;
+    ValidatorStats {
+        node_id: node_id.to_string(),
+        proposed: read_counter(storage, node_id, b"proposed").await,
+        expected: read_counter(storage, node_id, b"expected").await,
+        missed: read_counter(storage, node_id, b"missed").await,
+        last_proposed: last.checked_sub(1),
+        jailed_at: jailing::jailed_at(storage, node_id).await,
+    }
+}
+
+/// 上次解禁以来的预定槽位数和错过槽位数
+pub async fn window_of(storage: &(dyn Storage + Send + Sync), node_id: &str) -> (u64, u64) {
+    (
+        read_counter(storage, node_id, b"window-expected").await,
+        read_counter(storage, node_id, b"window-missed").await,
+    )
+}
+
+/// 解禁时清空最近槽位统计
+pub async fn reset_window(storage: &mut (dyn Storage + Send + Sync), node_id: &str) {
+    write_counter(storage, node_id, b"window-expected", 0).await;
+    write_counter(storage, node_id, b"window-missed", 0).await;
+}
+
+/// 记录区块 `number` 由 `proposer` 提出，预定出块者错过槽位时返回其节点 ID
+pub async fn record_block(
+    storage: &mut (dyn Storage + Send + Sync),
+    config: &StakingConfig,
+    number: u64,
+    proposer: &str,
+) -> Option<String> {
+    let active = active_validators(storage, config).await;
+    let scheduled = scheduled_proposer(&active, number).map(|v| v.node_id.clone());
+    let mut missed = None;
+    if let Some(scheduled) = scheduled {
+        increment(storage, &scheduled, b"expected").await;
+        increment(storage, &scheduled, b"window-expected").await;
+        if scheduled != proposer {
+            increment(storage, &scheduled, b"missed").await;
+            increment(storage, &scheduled, b"window-missed").await;
+            missed = Some(scheduled);
+        }
+    }
+    if config.validators.iter().any(|v| v.node_id == proposer) {
+        increment(storage, proposer, b"proposed").await;
+        write_counter(storage, proposer, b"last", number.saturating_add(1)).await;
+    }
+    missed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Address;
+    use crate::storage::MemoryStorage;
+    use ethers::types::U256;
+
+    fn config() -> StakingConfig {
+        StakingConfig {
+            validators: ["NodeID-A", "NodeID-B"]
+                .iter()
+                .enumerate()
+                .map(|(i, node_id)| ValidatorStake {
+                    node_id: node_id.to_string(),
+                    reward_address: Address([i as u8 + 1; 20]),
+                    stake: U256::from(100),
+                })
+                .collect(),
+            epoch_length: 10,
+            epoch_reward: U256::zero(),
+            stake_weight_percent: 100,
+            jailing: None,
+        }
+    }
+
+    #[test]
+    fn test_scheduled_proposer() {
+        let config = config();
+        let validators: Vec<_> = config.validators.iter().collect();
+        assert!(scheduled_proposer(&validators, 0).is_none());
+        assert_eq!(
+            scheduled_proposer(&validators, 1).unwrap().node_id,
+            "NodeID-A"
+        );
+        assert_eq!(
+            scheduled_proposer(&validators, 2).unwrap().node_id,
+            "NodeID-B"
+        );
+        assert_eq!(
+            scheduled_proposer(&validators, 3).unwrap().node_id,
+            "NodeID-A"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_block() {
+        let config = config();
+        let mut storage = MemoryStorage::default();
+        // B 在第 2 个槽位掉线，A 代为出块
+        for (number, proposer) in [
+            (1, "NodeID-A"),
+            (2, "NodeID-A"),
+            (3, "NodeID-A"),
+            (4, "NodeID-B"),
+        ] {
+            record_block(&mut storage, &config, number, proposer).await;
+        }
+
+        let a = stats_of(&storage, "NodeID-A").await;
+        assert_eq!((a.proposed, a.expected, a.missed), (3, 2, 0));
+        assert_eq!(a.last_proposed, Some(3));
+        assert_eq!(a.uptime(), UPTIME_BASIS_POINTS);
+
+        let b = stats_of(&storage, "NodeID-B").await;
+        assert_eq!((b.proposed, b.expected, b.missed), (1, 2, 1));
+        assert_eq!(b.uptime(), 5_000);
+
+        let unknown = stats_of(&storage, "NodeID-C").await;
+        assert_eq!(unknown.last_proposed, None);
+        assert_eq!(unknown.uptime(), UPTIME_BASIS_POINTS);
+    }
+}
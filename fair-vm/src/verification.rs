@@ -0,0 +1,443 @@
+//! 并行签名验证
+//!
+//! 签名恢复是校验大区块时开销最大的部分。验证池基于 rayon 线程池，
+//! 将交易按批分配到各线程，每批复用同一个 secp256k1 上下文。
+//! 恢复出的发送方按签名哈希和签名缓存，交易池准入、出块和区块校验共用同一缓存，
+//! 每个签名在节点上只恢复一次。交易哈希由调用方提供，不能作为缓存键。
+
+use crate::account::Address;
+use crate::transaction::Transaction;
+use ethers::types::H256;
+use lru::LruCache;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use secp256k1::Secp256k1;
+use sha3::{Digest, Keccak256};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+/// 每批交易数下限，过小的批次不值得分发到多个线程
+const MIN_BATCH_SIZE: usize = 16;
+
+/// 发送方缓存默认容量
+pub const DEFAULT_SENDER_CACHE_SIZE: usize = 65_536;
+
+/// 签名验证错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VerificationError {
+    #[error("交易 {hash:?}（索引 {index}）签名无效: {reason}")]
+    InvalidSignature {
+        index: usize,
+        hash: H256,
+        reason: String,
+    },
+
+    #[error("交易 {hash:?}（索引 {index}）发送方不匹配: 声明 {expected}, 签名者 {actual}")]
+    SenderMismatch {
+        index: usize,
+        hash: H256,
+        expected: Address,
+        actual: Address,
+    },
+
+    #[error("验证线程池错误: {0}")]
+    Pool(String),
+}
+
+/// 已恢复发送方的 LRU 缓存，按 [`SenderCache::key`] 索引
+///
+/// 只缓存恢复成功的结果。克隆后共享同一缓存。
+#[derive(Clone)]
+pub struct SenderCache {
+    inner: Arc<Mutex<LruCache<H256, Address>>>,
+}
+
+impl std::fmt::Debug for SenderCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SenderCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Default for SenderCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SENDER_CACHE_SIZE)
+    }
+}
+
+impl SenderCache {
+    /// 创建缓存，容量至少为 1
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            inner: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+
+    /// 缓存键，由签名哈希和签名计算
+    ///
+    /// 签名哈希覆盖交易内容，内容或签名不同的交易不会命中同一条目。
+    pub fn key(tx: &Transaction) -> H256 {
+        let mut hasher = Keccak256::new();
+        hasher.update(tx.sighash().as_bytes());
+        hasher.update(&tx.signature);
+        H256::from_slice(&hasher.finalize())
+    }
+
+    /// 按缓存键查询发送方
+    pub fn get(&self, key: &H256) -> Option<Address> {
+        self.lock().get(key).copied()
+    }
+
+    /// 记录缓存键对应的发送方
+    pub fn insert(&self, key: H256, sender: Address) {
+        self.lock().put(key, sender);
+    }
+
+    /// 缓存条目数
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 淘汰最久未使用的条目直到不超过 `len` 条，返回淘汰的条目数
+    pub fn shrink_to(&self, len: usize) -> usize {
+        let mut cache = self.lock();
+        let mut evicted = 0;
+        while cache.len() > len && cache.pop_lru().is_some() {
+            evicted += 1;
+        }
+        evicted
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<H256, Address>> {
+        // 缓存内容在持锁期间不会处于中间状态，锁中毒时继续使用
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 签名验证池
+#[derive(Clone)]
+pub struct SignatureVerifier {
+    pool: Arc<ThreadPool>,
+    cache: SenderCache,
+}
+
+impl std::fmt::Debug for SignatureVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignatureVerifier")
+            .field("threads", &self.threads())
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+impl Default for SignatureVerifier {
+    fn default() -> Self {
+        Self::new(0).expect("创建签名验证线程池失败")
+    }
+}
+
+impl SignatureVerifier {
+    /// 创建验证池，`threads` 为 0 时使用 CPU 核数
+    pub fn new(threads: usize) -> Result<Self, VerificationError> {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("sig-verify-{}", index))
+            .build()
+            .map_err(|e| VerificationError::Pool(e.to_string()))?;
+        Ok(Self {
+            pool: Arc::new(pool),
+            cache: SenderCache::default(),
+        })
+    }
+
+    /// 使用指定的发送方缓存
+    pub fn with_sender_cache(mut self, cache: SenderCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// 发送方缓存
+    pub fn sender_cache(&self) -> &SenderCache {
+        &self.cache
+    }
+
+    /// 并行度
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// 并行恢复交易发送方，结果顺序与输入一致
+    ///
+    /// 已缓存的交易直接返回缓存结果，其余交易恢复成功后写入缓存。
+    pub fn recover_senders(&self, transactions: &[Transaction]) -> Vec<Result<Address, String>> {
+        let keys: Vec<H256> = transactions.iter().map(SenderCache::key).collect();
+        let mut senders: Vec<Option<Result<Address, String>>> =
+            keys.iter().map(|key| self.cache.get(key).map(Ok)).collect();
+        let misses: Vec<(usize, &Transaction)> = transactions
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| senders[*index].is_none())
+            .collect();
+        if misses.is_empty() {
+            return senders.into_iter().flatten().collect();
+        }
+
+        let threads = self.threads().max(1);
+        let batch_size = ((misses.len() + threads - 1) / threads).max(MIN_BATCH_SIZE);
+        let recovered: Vec<Result<Address, String>> = self.pool.install(|| {
+            misses
+                .par_chunks(batch_size)
+                .flat_map_iter(|batch| {
+                    let secp = Secp256k1::verification_only();
+                    batch
+                        .iter()
+                        .map(|(_, tx)| tx.recover_sender_with(&secp))
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        });
+
+        for ((index, _), sender) in misses.into_iter().zip(recovered) {
+            if let Ok(address) = &sender {
+                self.cache.insert(keys[index], *address);
+            }
+            senders[index] = Some(sender);
+        }
+        senders.into_iter().flatten().collect()
+    }
+
+    /// 验证每笔交易都由声明的发送方签名，返回第一个错误
+    pub fn verify_transactions(
+        &self,
+        transactions: &[Transaction],
+    ) -> Result<(), VerificationError> {
+        for (index, (tx, sender)) in transactions
+            .iter()
+            .zip(self.recover_senders(transactions))
+            .enumerate()
+        {
+            check_sender(index, tx, sender)?;
+        }
+        Ok(())
+    }
+
+    /// 在验证池中恢复单笔交易的发送方，不阻塞异步运行时
+    pub async fn recover_sender(&self, tx: Transaction) -> Result<Address, VerificationError> {
+        let key = SenderCache::key(&tx);
+        if let Some(sender) = self.cache.get(&key) {
+            return Ok(sender);
+        }
+        self.run(move |verifier| {
+            let sender =
+                tx.recover_sender()
+                    .map_err(|reason| VerificationError::InvalidSignature {
+                        index: 0,
+                        hash: tx.hash,
+                        reason,
+                    })?;
+            verifier.cache.insert(key, sender);
+            Ok(sender)
+        })
+        .await?
+    }
+
+    /// 在验证池中验证区块交易，不阻塞异步运行时，验证通过后归还交易
+    pub async fn verify_block(
+        &self,
+        transactions: Vec<Transaction>,
+    ) -> Result<Vec<Transaction>, VerificationError> {
+        self.run(move |verifier| {
+            verifier
+                .verify_transactions(&transactions)
+                .map(|()| transactions)
+        })
+        .await?
+    }
+
+    async fn run<T, F>(&self, f: F) -> Result<T, VerificationError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&SignatureVerifier) -> T + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let verifier = self.clone();
+        self.pool.spawn(move || {
+            let _ = sender.send(f(&verifier));
+        });
+        receiver
+            .await
+            .map_err(|_| VerificationError::Pool("验证任务被取消".to_string()))
+    }
+}
+
+fn check_sender(
+    index: usize,
+    tx: &Transaction,
+    sender: Result<Address, String>,
+) -> Result<(), VerificationError> {
+    match sender {
+        Ok(actual) if actual == tx.from => Ok(()),
+        Ok(actual) => Err(VerificationError::SenderMismatch {
+            index,
+            hash: tx.hash,
+            expected: tx.from,
+            actual,
+        }),
+        Err(reason) => Err(VerificationError::InvalidSignature {
+            index,
+            hash: tx.hash,
+            reason,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::sign_digest;
+    use crate::transaction::TransactionType;
+    use ethers::types::U256;
+    use secp256k1::SecretKey;
+
+    fn signed_tx(key: u8, nonce: u64) -> Transaction {
+        let secret_key = SecretKey::from_slice(&[key; 32]).unwrap();
+        let mut tx = Transaction::new(
+            H256::from_low_u64_be(nonce),
+            Address::default(),
+            Some(Address([9u8; 20])),
+            U256::from(1),
+            nonce,
+            21000,
+            Some(U256::one()),
+            vec![],
+            vec![],
+            TransactionType::Legacy,
+            1,
+            None,
+            None,
+        );
+        let (signature, signer) = sign_digest(&secret_key, tx.sighash().0).unwrap();
+        tx.from = signer;
+        tx.signature = signature;
+        tx
+    }
+
+    #[test]
+    fn test_recover_senders_in_order() {
+        let verifier = SignatureVerifier::new(4).unwrap();
+        let transactions: Vec<Transaction> =
+            (0..100).map(|i| signed_tx(1 + (i % 3) as u8, i)).collect();
+        let senders = verifier.recover_senders(&transactions);
+        for (tx, sender) in transactions.iter().zip(senders) {
+            assert_eq!(sender.unwrap(), tx.from);
+        }
+        assert!(verifier.verify_transactions(&transactions).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_block_reports_first_bad_signature() {
+        let verifier = SignatureVerifier::new(2).unwrap();
+        let mut transactions: Vec<Transaction> = (0..40).map(|i| signed_tx(1, i)).collect();
+        transactions[7].from = Address([5u8; 20]);
+        transactions[20].signature.truncate(10);
+
+        match verifier.verify_block(transactions.clone()).await {
+            Err(VerificationError::SenderMismatch { index, .. }) => assert_eq!(index, 7),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        transactions[7] = signed_tx(1, 7);
+        assert!(matches!(
+            verifier.verify_block(transactions).await,
+            Err(VerificationError::InvalidSignature { index: 20, .. })
+        ));
+
+        let tx = signed_tx(2, 0);
+        assert_eq!(verifier.recover_sender(tx.clone()).await.unwrap(), tx.from);
+    }
+
+    #[tokio::test]
+    async fn test_sender_cache_is_shared() {
+        let verifier = SignatureVerifier::new(2).unwrap();
+        let shared = verifier.clone();
+        let tx = signed_tx(3, 1);
+
+        assert_eq!(verifier.recover_sender(tx.clone()).await.unwrap(), tx.from);
+        assert_eq!(
+            shared.sender_cache().get(&SenderCache::key(&tx)),
+            Some(tx.from)
+        );
+        assert!(shared
+            .verify_transactions(std::slice::from_ref(&tx))
+            .is_ok());
+
+        // 沿用已缓存交易的哈希伪造的交易不会命中缓存
+        let victim = signed_tx(4, 1);
+        verifier.recover_sender(victim.clone()).await.unwrap();
+        let mut forged = signed_tx(3, 5);
+        forged.hash = victim.hash;
+        forged.from = victim.from;
+        assert!(matches!(
+            shared.verify_transactions(&[forged.clone()]),
+            Err(VerificationError::SenderMismatch { .. })
+        ));
+        forged.signature.clear();
+        assert!(shared.recover_sender(forged).await.is_err());
+
+        // 恢复失败的结果不缓存
+        let mut invalid = signed_tx(3, 2);
+        invalid.signature.clear();
+        assert!(verifier.recover_sender(invalid.clone()).await.is_err());
+        assert!(verifier
+            .sender_cache()
+            .get(&SenderCache::key(&invalid))
+            .is_none());
+
+        let cache = SenderCache::new(1);
+        cache.insert(H256::from_low_u64_be(1), Address([1u8; 20]));
+        cache.insert(H256::from_low_u64_be(2), Address([2u8; 20]));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&H256::from_low_u64_be(1)).is_none());
+        assert_eq!(cache.shrink_to(0), 1);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_raw_transaction_signed_by_wallet() {
+        use ethers::signers::{LocalWallet, Signer};
+        use ethers::types::transaction::eip2718::TypedTransaction;
+        use ethers::types::Eip1559TransactionRequest;
+
+        let wallet = LocalWallet::from_bytes(&[7u8; 32])
+            .unwrap()
+            .with_chain_id(1337u64);
+        let typed: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(ethers::types::H160([9u8; 20]))
+            .value(5u64)
+            .nonce(3u64)
+            .gas(21000u64)
+            .max_fee_per_gas(10u64)
+            .max_priority_fee_per_gas(1u64)
+            .chain_id(1337u64)
+            .into();
+        let signature = wallet.sign_transaction_sync(&typed).unwrap();
+        let raw = typed.rlp_signed(&signature);
+
+        let mut tx = Transaction::from_raw(&raw).unwrap();
+        assert_eq!(tx.chain_id, 1337);
+        assert_eq!(tx.nonce, 3);
+        assert_eq!(tx.hash, H256(ethers::utils::keccak256(&raw)));
+        tx.from = tx.recover_sender().unwrap();
+        assert_eq!(tx.from, Address::from(wallet.address()));
+        assert!(tx.verify_signature());
+    }
+}
@@ -0,0 +1,191 @@
+//! 创世锁仓
+//!
+//! 创世账户可配置锁仓计划：`start + cliff` 之前全部锁定，之后自 `start` 起按时间线性释放，
+//! `start + duration` 时全部释放。锁定部分仍计入余额，但不能转出或支付手续费：交易池按最新区块
+//! 时间拒绝超出可用余额的交易，EVM 转账按区块时间检查，不需要另行部署锁仓合约。
+
+use crate::account::Address;
+use crate::genesis::Genesis;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 锁仓计划，时间均为 Unix 秒
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VestingSchedule {
+    /// 锁定总额
+    pub amount: u64,
+    /// 开始释放的时间
+    pub start: u64,
+    /// 悬崖期，期内不释放
+    #[serde(default)]
+    pub cliff: u64,
+    /// 释放总时长，0 表示悬崖期结束后一次性释放
+    #[serde(default)]
+    pub duration: u64,
+}
+
+/// 锁仓错误类型
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum VestingError {
+    #[error("无效的锁仓计划: {0}")]
+    InvalidSchedule(String),
+
+    #[error("{address} 可用余额 {spendable} 不足，需要 {required}，锁定 {locked}")]
+    Locked {
+        address: Address,
+        spendable: U256,
+        required: U256,
+        locked: U256,
+    },
+}
+
+impl VestingSchedule {
+    /// 校验计划，锁定总额不能超过账户的创世余额
+    pub fn validate(&self, balance: u64) -> Result<(), VestingError> {
+        if self.amount > balance {
+            return Err(VestingError::InvalidSchedule(format!(
+                "锁定总额 {} 超过创世余额 {}",
+                self.amount, balance
+            )));
+        }
+        if self.duration != 0 && self.cliff > self.duration {
+            return Err(VestingError::InvalidSchedule(
+                "悬崖期不能长于释放总时长".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// `timestamp` 时仍锁定的金额
+    pub fn locked_at(&self, timestamp: u64) -> u64 {
+        let elapsed = timestamp.saturating_sub(self.start);
+        if timestamp < self.start || elapsed < self.cliff {
+            return self.amount;
+        }
+        if elapsed >= self.duration {
+            return 0;
+        }
+        let released = self.amount as u128 * elapsed as u128 / self.duration as u128;
+        self.amount - released as u64
+    }
+}
+
+/// 全部账户的锁仓计划
+#[derive(Debug, Clone, Default)]
+pub struct VestingSchedules {
+    schedules: HashMap<Address, VestingSchedule>,
+}
+
+impl VestingSchedules {
+    /// 收集并校验创世账户的锁仓计划
+    pub fn from_genesis(genesis: &Genesis) -> Result<Self, VestingError> {
+        let mut schedules = HashMap::new();
+        for (address, account) in &genesis.alloc {
+            if let Some(schedule) = &account.vesting {
+                schedule.validate(account.balance)?;
+                schedules.insert(Address(address.0), schedule.clone());
+            }
+        }
+        Ok(Self { schedules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schedules.is_empty()
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&VestingSchedule> {
+        self.schedules.get(address)
+    }
+
+    /// `timestamp` 时账户仍锁定的金额
+    pub fn locked(&self, address: &Address, timestamp: u64) -> U256 {
+        self.schedules
+            .get(address)
+            .map_or(U256::zero(), |schedule| {
+                U256::from(schedule.locked_at(timestamp))
+            })
+    }
+
+    /// 检查余额为 `balance` 的账户在 `timestamp` 时能否花费 `required`
+    pub fn check(
+        &self,
+        address: &Address,
+        balance: U256,
+        required: U256,
+        timestamp: u64,
+    ) -> Result<(), VestingError> {
+        let locked = self.locked(address, timestamp);
+        let spendable = balance.saturating_sub(locked);
+        if required > spendable {
+            return Err(VestingError::Locked {
+                address: *address,
+                spendable,
+                required,
+                locked,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_at() {
+        let schedule = VestingSchedule {
+            amount: 1_000,
+            start: 100,
+            cliff: 50,
+            duration: 200,
+        };
+        assert_eq!(schedule.locked_at(0), 1_000);
+        assert_eq!(schedule.locked_at(149), 1_000);
+        // 悬崖期结束时已按时间释放四分之一
+        assert_eq!(schedule.locked_at(150), 750);
+        assert_eq!(schedule.locked_at(200), 500);
+        assert_eq!(schedule.locked_at(300), 0);
+        assert!(schedule.validate(999).is_err());
+
+        let once = VestingSchedule {
+            duration: 0,
+            ..schedule
+        };
+        assert_eq!(once.locked_at(149), 1_000);
+        assert_eq!(once.locked_at(150), 0);
+    }
+
+    #[test]
+    fn test_check() {
+        let mut genesis = Genesis::new(1);
+        let address = Address([1u8; 20]);
+        genesis.add_vesting_account(
+            address.into(),
+            1_000,
+            VestingSchedule {
+                amount: 600,
+                start: 0,
+                cliff: 0,
+                duration: 100,
+            },
+        );
+        let schedules = VestingSchedules::from_genesis(&genesis).unwrap();
+        let balance = U256::from(1_000);
+        assert!(schedules
+            .check(&address, balance, U256::from(400), 0)
+            .is_ok());
+        assert!(matches!(
+            schedules.check(&address, balance, U256::from(401), 0),
+            Err(VestingError::Locked { locked, .. }) if locked == U256::from(600)
+        ));
+        assert!(schedules
+            .check(&address, balance, U256::from(700), 50)
+            .is_ok());
+        // 没有锁仓计划的账户不受限制
+        assert!(schedules
+            .check(&Address([2u8; 20]), balance, balance, 0)
+            .is_ok());
+    }
+}
@@ -23,7 +23,7 @@ pub trait VmExt: Send + Sync {
     async fn get_state(&self) -> Arc<RwLock<Box<dyn StateTrait>>>;
 
     /// 获取存储
-    async fn get_storage_arc(&self) -> Arc<RwLock<Box<dyn crate::storage::Storage + Send + Sync>>>;
+    async fn get_storage_handle(&self) -> crate::storage::StateHandle;
 
     /// 执行交易
     async fn execute_transaction(
@@ -42,6 +42,7 @@ impl From<VmExecutionResult> for CoreExecutionResult {
             gas_used: result.gas_used,
             return_data: result.return_data,
             status: result.success,
+            gas_refunded: 0,
         }
     }
 }
@@ -0,0 +1,274 @@
+//! 事件 Webhook 推送
+//!
+//! 按配置的 URL 与事件过滤器将匹配的事件以 JSON POST 推送出去，
+//! 失败时按指数退避重试，配置了密钥时附带 HMAC-SHA256 签名头。
+
+use crate::event::{Event, EventHandler};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+/// 签名请求头
+pub const SIGNATURE_HEADER: &str = "X-FairVM-Signature";
+
+/// 事件名称请求头
+pub const EVENT_HEADER: &str = "X-FairVM-Event";
+
+/// 退避时间上限
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// 单次请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Webhook 错误
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("序列化失败: {0}")]
+    Serialization(String),
+
+    #[error("请求失败: {0}")]
+    Request(String),
+
+    #[error("服务端返回状态码 {0}")]
+    Status(u16),
+
+    #[error("重试 {0} 次后仍然失败")]
+    RetriesExhausted(u32),
+}
+
+/// 单个 Webhook 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// 推送地址
+    pub url: String,
+    /// HMAC 签名密钥
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// 订阅的事件名称，为空表示全部事件
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// 最大重试次数
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// 首次重试等待时间（毫秒）
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+impl WebhookConfig {
+    /// 创建订阅全部事件的配置
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+            events: Vec::new(),
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+        }
+    }
+
+    /// 设置签名密钥
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// 设置事件过滤器
+    pub fn with_events(mut self, events: Vec<String>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// 事件是否匹配过滤器
+    pub fn matches(&self, event: &Event) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event.event_type.name())
+    }
+
+    /// 第 `attempt` 次重试前的等待时间
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor)).min(MAX_BACKOFF)
+    }
+}
+
+/// 计算请求体的 HMAC-SHA256 签名
+pub fn sign_payload(secret: &str, payload: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 支持任意长度的密钥");
+    mac.update(payload);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// 待投递的事件
+struct Delivery {
+    /// Webhook 配置
+    config: Arc<WebhookConfig>,
+    /// 事件名称
+    event_name: &'static str,
+    /// 请求体
+    payload: Vec<u8>,
+}
+
+/// Webhook 分发器
+///
+/// 作为 [`EventHandler`] 注册到事件管理器，事件在后台任务中异步投递，不阻塞事件发布。
+pub struct WebhookDispatcher {
+    /// Webhook 配置
+    webhooks: Vec<Arc<WebhookConfig>>,
+    /// 投递队列
+    sender: mpsc::UnboundedSender<Delivery>,
+}
+
+impl WebhookDispatcher {
+    /// 创建分发器并启动后台投递任务，需要在 tokio 运行时中调用
+    pub fn spawn(webhooks: Vec<WebhookConfig>) -> Arc<Self> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Delivery>();
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        tokio::spawn(async move {
+            while let Some(delivery) = receiver.recv().await {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = deliver(&client, &delivery).await {
+                        log::warn!("Webhook 推送到 {} 失败: {}", delivery.config.url, e);
+                    }
+                });
+            }
+        });
+
+        Arc::new(Self {
+            webhooks: webhooks.into_iter().map(Arc::new).collect(),
+            sender,
+        })
+    }
+
+    /// 已配置的 Webhook
+    pub fn webhooks(&self) -> impl Iterator<Item = &WebhookConfig> {
+        self.webhooks.iter().map(|config| config.as_ref())
+    }
+}
+
+impl EventHandler for WebhookDispatcher {
+    fn handle_event(&self, event: &Event) {
+        let matching: Vec<_> = self
+            .webhooks
+            .iter()
+            .filter(|config| config.matches(event))
+            .collect();
+        if matching.is_empty() {
+            return;
+        }
+
+        let payload = match serde_json::to_vec(event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::error!("{}", WebhookError::Serialization(e.to_string()));
+                return;
+            }
+        };
+        for config in matching {
+            let delivery = Delivery {
+                config: config.clone(),
+                event_name: event.event_type.name(),
+                payload: payload.clone(),
+            };
+            if self.sender.send(delivery).is_err() {
+                log::warn!("Webhook 投递任务已停止");
+                return;
+            }
+        }
+    }
+}
+
+/// 投递事件，失败时按退避策略重试
+async fn deliver(client: &reqwest::Client, delivery: &Delivery) -> Result<(), WebhookError> {
+    let config = &delivery.config;
+    let signature = config
+        .secret
+        .as_deref()
+        .map(|secret| sign_payload(secret, &delivery.payload));
+
+    for attempt in 0..=config.max_retries {
+        if attempt > 0 {
+            tokio::time::sleep(config.backoff(attempt - 1)).await;
+        }
+
+        let mut request = client
+            .post(&config.url)
+            .header("Content-Type", "application/json")
+            .header(EVENT_HEADER, delivery.event_name)
+            .body(delivery.payload.clone());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        let result = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => WebhookError::Status(response.status().as_u16()),
+            Err(e) => WebhookError::Request(e.to_string()),
+        };
+        log::debug!(
+            "Webhook 推送到 {} 第 {} 次失败: {}",
+            config.url,
+            attempt + 1,
+            result
+        );
+    }
+
+    Err(WebhookError::RetriesExhausted(config.max_retries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventType;
+    use chrono::Utc;
+    use serde_json::json;
+
+    #[test]
+    fn test_sign_payload() {
+        assert_eq!(
+            sign_payload("secret", br#"{"a":1}"#),
+            "sha256=aa9e2e3575f5d7098b6caccd790888c36d5fdb63342a73bada2d6a51747a8494"
+        );
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let event = Event {
+            event_type: EventType::BlockFinalized,
+            data: json!({}),
+            timestamp: Utc::now(),
+        };
+        assert!(WebhookConfig::new("http://localhost").matches(&event));
+        assert!(WebhookConfig::new("http://localhost")
+            .with_events(vec!["block_finalized".to_string()])
+            .matches(&event));
+        assert!(!WebhookConfig::new("http://localhost")
+            .with_events(vec!["transaction_dropped".to_string()])
+            .matches(&event));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let config = WebhookConfig::new("http://localhost");
+        assert_eq!(config.backoff(0), Duration::from_millis(500));
+        assert_eq!(config.backoff(3), Duration::from_millis(4000));
+        assert_eq!(config.backoff(64), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,3 @@
+// gets the current gas price
+>> {"jsonrpc":"2.0","id":1,"method":"eth_gasPrice"}
+<< {"jsonrpc":"2.0","id":1,"result":"0x3b9aca00"}
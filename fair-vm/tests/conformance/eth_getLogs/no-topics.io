@@ -0,0 +1,3 @@
+// queries for all logs across a range of blocks
+>> {"jsonrpc":"2.0","id":1,"method":"eth_getLogs","params":[{"fromBlock":"0x0","toBlock":"0x0"}]}
+<< {"jsonrpc":"2.0","id":1,"result":[]}
@@ -0,0 +1,3 @@
+// gets storage of a non-existent account
+>> {"jsonrpc":"2.0","id":1,"method":"eth_getStorageAt","params":["0x1111111111111111111111111111111111111111","0x0000000000000000000000000000000000000000000000000000000000000000","latest"]}
+<< {"jsonrpc":"2.0","id":1,"result":"0x0000000000000000000000000000000000000000000000000000000000000000"}
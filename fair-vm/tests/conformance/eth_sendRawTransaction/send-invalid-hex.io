@@ -0,0 +1,3 @@
+// sends a raw transaction that is not valid hex
+>> {"jsonrpc":"2.0","id":1,"method":"eth_sendRawTransaction","params":["0xzz"]}
+<< {"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid params"}}
@@ -0,0 +1,3 @@
+// gets the network id
+>> {"jsonrpc":"2.0","id":1,"method":"net_version"}
+<< {"jsonrpc":"2.0","id":1,"result":"12345"}
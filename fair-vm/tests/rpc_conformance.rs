@@ -0,0 +1,340 @@
+//! JSON-RPC 规范一致性测试
+//!
+//! 按 ethereum/execution-apis 的 `.io` 格式读取 `tests/conformance/<方法>/<用例>.io`，
+//! 对开发节点逐条发送请求并按方法汇总结果。用例里的期望值来自规范测试链，与本地链的
+//! 具体数值不同，因此只比较响应结构：
+//!
+//! - 结果与错误二选一，错误码必须一致
+//! - 对象的字段集合一致，数组按第一个元素比较
+//! - 地址、哈希、布隆等定长数据长度一致，数量（quantity）不能有前导零
+//!
+//! 返回 `-32601` 的方法记为未实现，只在严格模式下导致失败。
+//!
+//! 环境变量：
+//!
+//! - `FAIRVM_RPC_URL`：对外部节点的 HTTP 端点运行，未设置时使用进程内的开发节点
+//! - `FAIRVM_CONFORMANCE_STRICT`：未实现的方法也视为失败
+//!
+//! 命令行参数作为方法名过滤，例如 `cargo test --test rpc_conformance -- eth_getBalance`。
+
+use fair_vm::api::{ApiServer, VmExt};
+use fair_vm::FairVM;
+use jsonrpc_core::IoHandler;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 开发节点开放的命名空间
+const NAMESPACES: [&str; 6] = ["eth", "net", "web3", "chain", "fairvm", "wallet"];
+
+/// 方法不存在的错误码
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// 一条用例
+struct Case {
+    method: String,
+    name: String,
+    request: String,
+    expected: Value,
+}
+
+/// 用例结果
+enum Outcome {
+    Pass,
+    Fail(String),
+    Unsupported,
+}
+
+/// 被测节点
+enum Target {
+    Local(IoHandler),
+    Remote {
+        url: String,
+        client: reqwest::Client,
+        runtime: tokio::runtime::Runtime,
+    },
+}
+
+impl Target {
+    fn from_env() -> Self {
+        match std::env::var("FAIRVM_RPC_URL") {
+            Ok(url) => Target::Remote {
+                url,
+                client: reqwest::Client::new(),
+                runtime: tokio::runtime::Runtime::new().expect("创建运行时失败"),
+            },
+            Err(_) => {
+                let vm: Arc<RwLock<dyn VmExt>> = Arc::new(RwLock::new(FairVM::new()));
+                let namespaces: Vec<String> = NAMESPACES.iter().map(|s| s.to_string()).collect();
+                Target::Local(ApiServer::new(vm).io_handler(&namespaces))
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Target::Local(_) => "进程内开发节点".to_string(),
+            Target::Remote { url, .. } => url.clone(),
+        }
+    }
+
+    fn call(&self, request: &str) -> Result<Value, String> {
+        let response = match self {
+            // 处理器内部会创建自己的运行时，只能在运行时之外同步调用
+            Target::Local(io) => io
+                .handle_request_sync(request)
+                .ok_or_else(|| "没有响应".to_string())?,
+            Target::Remote {
+                url,
+                client,
+                runtime,
+            } => runtime.block_on(async {
+                client
+                    .post(url)
+                    .header("content-type", "application/json")
+                    .body(request.to_string())
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .text()
+                    .await
+                    .map_err(|e| e.to_string())
+            })?,
+        };
+        serde_json::from_str(&response).map_err(|e| format!("响应不是 JSON: {}", e))
+    }
+}
+
+/// 读取全部用例，按方法和文件名排序
+fn load_cases(root: &Path) -> Vec<Case> {
+    let mut cases = Vec::new();
+    let mut methods: Vec<PathBuf> = fs::read_dir(root)
+        .expect("读取用例目录失败")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    methods.sort();
+    for dir in methods {
+        let method = dir.file_name().unwrap().to_string_lossy().into_owned();
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "io"))
+            .collect();
+        files.sort();
+        for file in files {
+            let content = fs::read_to_string(&file).unwrap();
+            let (request, response) =
+                parse_io(&content).unwrap_or_else(|| panic!("用例格式错误: {}", file.display()));
+            cases.push(Case {
+                method: method.clone(),
+                name: file.file_stem().unwrap().to_string_lossy().into_owned(),
+                request,
+                expected: serde_json::from_str(&response)
+                    .unwrap_or_else(|e| panic!("{}: {}", file.display(), e)),
+            });
+        }
+    }
+    cases
+}
+
+/// 解析 `.io` 文件中的一对请求和响应，`//` 开头的行为注释
+fn parse_io(content: &str) -> Option<(String, String)> {
+    let mut request = None;
+    let mut response = None;
+    for line in content.lines() {
+        if let Some(body) = line.strip_prefix(">> ") {
+            request = Some(body.to_string());
+        } else if let Some(body) = line.strip_prefix("<< ") {
+            response = Some(body.to_string());
+        }
+    }
+    request.zip(response)
+}
+
+fn run_case(target: &Target, case: &Case) -> Outcome {
+    let actual = match target.call(&case.request) {
+        Ok(actual) => actual,
+        Err(e) => return Outcome::Fail(e),
+    };
+    if actual["error"]["code"].as_i64() == Some(METHOD_NOT_FOUND) {
+        return Outcome::Unsupported;
+    }
+    match check_response(&case.expected, &actual) {
+        Ok(()) => Outcome::Pass,
+        Err(reason) => Outcome::Fail(reason),
+    }
+}
+
+/// 比较响应信封和结果结构
+fn check_response(expected: &Value, actual: &Value) -> Result<(), String> {
+    if actual["jsonrpc"] != "2.0" {
+        return Err("缺少 jsonrpc: \"2.0\"".to_string());
+    }
+    if actual["id"] != expected["id"] {
+        return Err(format!("id 不一致: {}", actual["id"]));
+    }
+    match (expected.get("result"), actual.get("result")) {
+        (Some(expected), Some(actual)) => check_shape("result", expected, actual),
+        (None, None) => {
+            let (want, got) = (&expected["error"]["code"], &actual["error"]["code"]);
+            if want == got {
+                Ok(())
+            } else {
+                Err(format!("错误码应为 {}，实际为 {}", want, got))
+            }
+        }
+        (Some(_), None) => Err(format!("应返回结果，实际返回错误 {}", actual["error"])),
+        (None, Some(result)) => Err(format!("应返回错误，实际返回 {}", result)),
+    }
+}
+
+/// 按结构比较两个值，`path` 用于定位不一致的字段
+fn check_shape(path: &str, expected: &Value, actual: &Value) -> Result<(), String> {
+    let mismatch = || {
+        Err(format!(
+            "{}: 应为 {} 形式，实际为 {}",
+            path, expected, actual
+        ))
+    };
+    match (expected, actual) {
+        (Value::Null, Value::Null) | (Value::Bool(_), Value::Bool(_)) => Ok(()),
+        (Value::Number(_), Value::Number(_)) => Ok(()),
+        (Value::String(expected), Value::String(actual)) => {
+            if hex_matches(expected, actual) {
+                Ok(())
+            } else {
+                mismatch()
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            match (expected.first(), actual.first()) {
+                (Some(expected), Some(actual)) => {
+                    check_shape(&format!("{}[0]", path), expected, actual)
+                }
+                _ => Ok(()),
+            }
+        }
+        (Value::Object(expected), Value::Object(actual)) => {
+            let missing: Vec<&String> = expected
+                .keys()
+                .filter(|k| !actual.contains_key(*k))
+                .collect();
+            let extra: Vec<&String> = actual
+                .keys()
+                .filter(|k| !expected.contains_key(*k))
+                .collect();
+            if !missing.is_empty() || !extra.is_empty() {
+                return Err(format!(
+                    "{}: 缺少字段 {:?}，多出字段 {:?}",
+                    path, missing, extra
+                ));
+            }
+            expected.iter().try_for_each(|(key, value)| {
+                check_shape(&format!("{}.{}", path, key), value, &actual[key])
+            })
+        }
+        _ => mismatch(),
+    }
+}
+
+/// 十六进制字符串按编码类别比较，非十六进制字符串只要求同为字符串
+fn hex_matches(expected: &str, actual: &str) -> bool {
+    let Some(digits) = expected.strip_prefix("0x") else {
+        return !actual.starts_with("0x");
+    };
+    let Some(actual_digits) = actual.strip_prefix("0x") else {
+        return false;
+    };
+    if !actual_digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+    let is_data = |digits: &str| digits.len() % 2 == 0;
+    match expected.len() {
+        // 空数据，例如没有代码的账户
+        2 => is_data(actual_digits),
+        // 8 字节 nonce、地址、哈希和布隆过滤器
+        18 | 42 | 66 | 514 => actual.len() == expected.len(),
+        _ if is_quantity(digits) => is_quantity(actual_digits),
+        _ => is_data(actual_digits),
+    }
+}
+
+/// 数量编码：至少一位，除 `0x0` 外不能有前导零
+fn is_quantity(digits: &str) -> bool {
+    !digits.is_empty() && (digits == "0" || !digits.starts_with('0'))
+}
+
+fn main() -> ExitCode {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance");
+    let filters: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect();
+    let strict = std::env::var("FAIRVM_CONFORMANCE_STRICT").is_ok();
+    let target = Target::from_env();
+
+    let cases: Vec<Case> = load_cases(&root)
+        .into_iter()
+        .filter(|case| {
+            filters.is_empty() || filters.iter().any(|f| case.method.contains(f.as_str()))
+        })
+        .collect();
+    println!(
+        "JSON-RPC 一致性测试: {} 条用例，节点 {}",
+        cases.len(),
+        target.describe()
+    );
+
+    // 方法 -> (通过, 失败, 未实现)
+    let mut summary: BTreeMap<&str, (usize, usize, usize)> = BTreeMap::new();
+    for case in &cases {
+        let counts = summary.entry(case.method.as_str()).or_default();
+        match run_case(&target, case) {
+            Outcome::Pass => counts.0 += 1,
+            Outcome::Fail(reason) => {
+                counts.1 += 1;
+                println!("  失败 {}/{}: {}", case.method, case.name, reason);
+            }
+            Outcome::Unsupported => counts.2 += 1,
+        }
+    }
+
+    println!();
+    println!(
+        "{:<32} {:>6} {:>6} {:>6}  状态",
+        "方法", "通过", "失败", "未实现"
+    );
+    let (mut compliant, mut failed, mut unsupported) = (0, 0, 0);
+    for (method, (pass, fail, missing)) in &summary {
+        let status = if *fail > 0 {
+            failed += 1;
+            "不符合"
+        } else if *missing > 0 {
+            unsupported += 1;
+            "未实现"
+        } else {
+            compliant += 1;
+            "符合"
+        };
+        println!(
+            "{:<32} {:>6} {:>6} {:>6}  {}",
+            method, pass, fail, missing, status
+        );
+    }
+    println!();
+    println!(
+        "{} 个方法符合规范，{} 个不符合，{} 个未实现",
+        compliant, failed, unsupported
+    );
+
+    if failed > 0 || (strict && unsupported > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,51 @@
+#!/bin/bash
+
+# 确保脚本从项目根目录运行
+if ! [[ "$0" =~ scripts/build.static.sh ]]; then
+    echo "错误: 必须从项目根目录运行此脚本"
+    exit 1
+fi
+
+# 获取项目根目录的绝对路径
+PROJECT_ROOT=$(pwd)
+
+set -e
+
+# 目标架构，默认构建 x86_64 和 aarch64
+ARCHES=("$@")
+if [ ${#ARCHES[@]} -eq 0 ]; then
+    ARCHES=(x86_64 aarch64)
+fi
+
+# 安装了 cross 时在容器中交叉编译，否则需要本机已有对应的 musl 工具链
+if command -v cross >/dev/null 2>&1; then
+    CARGO=cross
+else
+    CARGO=cargo
+fi
+
+mkdir -p "${PROJECT_ROOT}/dist"
+
+for ARCH in "${ARCHES[@]}"; do
+    case "${ARCH}" in
+        x86_64|aarch64) ;;
+        *)
+            echo "错误: 不支持的架构 ${ARCH}，可选 x86_64 或 aarch64"
+            exit 1
+            ;;
+    esac
+    TARGET="${ARCH}-unknown-linux-musl"
+
+    echo "开始构建 ${TARGET} 静态二进制..."
+    # 静态构建不包含硬件钱包支持（hardware-wallet 功能）
+    ${CARGO} build --workspace --bins --profile dist --target "${TARGET}" --no-default-features
+
+    for BIN in "${PROJECT_ROOT}/target/${TARGET}/dist/"*; do
+        if [ -f "${BIN}" ] && [ -x "${BIN}" ]; then
+            cp "${BIN}" "${PROJECT_ROOT}/dist/$(basename "${BIN}")-${TARGET}"
+        fi
+    done
+done
+
+echo "构建完成。二进制文件位置："
+ls -l "${PROJECT_ROOT}/dist"